@@ -0,0 +1,536 @@
+//! DES: the key schedule and the block cipher, and Triple DES built on it
+
+use std::error::Error;
+use std::fmt;
+
+use crypto_core::i18n::Catalog;
+use crypto_core::modes::{self, Mode};
+use crypto_core::registry::{Capabilities, CipherPlugin, Registration};
+use crypto_core::rng;
+use crypto_core::{tr, BlockCipher, CipherError, CipherFamily, CipherInfo, SymmetricCipher};
+
+/// PC-1 Permutation table for initial key permutation
+const PC1: [u8; 56] = [
+    57, 49, 41, 33, 25, 17,  9,  1,
+    58, 50, 42, 34, 26, 18, 10,  2,
+    59, 51, 43, 35, 27, 19, 11,  3,
+    60, 52, 44, 36, 63, 55, 47, 39,
+    31, 23, 15,  7, 62, 54, 46, 38,
+    30, 22, 14,  6, 61, 53, 45, 37,
+    29, 21, 13,  5, 28, 20, 12,  4
+];
+
+/// The four weak keys, whose 16 round keys are all the same, so
+/// encrypting twice decrypts
+const WEAK_KEYS: [u64; 4] = [0x0101010101010101, 0xFEFEFEFEFEFEFEFE, 0xE0E0E0E0F1F1F1F1, 0x1F1F1F1F0E0E0E0E];
+
+/// Key generation struct that can handle more flexible input
+pub struct DesKeyGenerator {
+    /// Raw input key
+    raw_key: Vec<u8>,
+    /// Processed 56-bit key
+    k_plus: u64,
+}
+
+impl DesKeyGenerator {
+    /// Create key from various input types
+    pub fn new(input: &[u8]) -> Result<Self, Box<dyn Error>> {
+        // Validate and process input
+        let processed_key = Self::process_key(input)?;
+        
+        Ok(Self {
+            raw_key: input.to_vec(),
+            k_plus: processed_key,
+        })
+    }
+
+    /// A fresh random key with odd parity in every byte, as DES hardware
+    /// checks, and never one of the weak keys
+    pub fn random() -> Result<Self, Box<dyn Error>> {
+        loop {
+            let mut key = rng::key(8)?;
+            for byte in &mut key {
+                // The low bit of each byte is parity; PC-1 drops it
+                *byte = (*byte & 0xFE) | u8::from((*byte >> 1).count_ones() % 2 == 0);
+            }
+            let value = u64::from_be_bytes(key.as_slice().try_into().expect("8 bytes"));
+            if !WEAK_KEYS.contains(&value) {
+                return Self::new(&key);
+            }
+        }
+    }
+
+    /// Flexible key processing method
+    fn process_key(key_bytes: &[u8]) -> Result<u64, Box<dyn Error>> {
+        // Different processing strategies based on input length
+        match key_bytes.len() {
+            // If exactly 8 bytes (standard DES key length)
+            8 => Self::process_standard_key(key_bytes),
+            
+            // If less than 8 bytes, pad with zeros
+            0..=7 => {
+                let mut padded_key = vec![0u8; 8];
+                padded_key[..key_bytes.len()].copy_from_slice(key_bytes);
+                Self::process_standard_key(&padded_key)
+            },
+            
+            // If more than 8 bytes, truncate
+            _ => {
+                let truncated_key = &key_bytes[..8];
+                Self::process_standard_key(truncated_key)
+            }
+        }
+    }
+
+    /// Standard DES key processing with PC-1 permutation
+    fn process_standard_key(key_bytes: &[u8]) -> Result<u64, Box<dyn Error>> {
+        // Convert key to 64-bit integer
+        let mut key_64bit: u64 = 0;
+        for (i, &byte) in key_bytes.iter().enumerate() {
+            key_64bit |= (byte as u64) << (56 - i * 8);
+        }
+
+        // Perform PC-1 permutation
+        let mut k_plus: u64 = 0;
+        for (i, &pos) in PC1.iter().enumerate() {
+            let bit = (key_64bit >> (64 - pos)) & 1;
+            k_plus |= bit << (55 - i);
+        }
+
+        Ok(k_plus)
+    }
+
+    /// Raw input key
+    pub fn raw_key(&self) -> &[u8] {
+        &self.raw_key
+    }
+
+    /// The 56-bit key after PC-1
+    pub fn k_plus(&self) -> u64 {
+        self.k_plus
+    }
+}
+
+/// Key details for display: the raw input, as text when it is UTF-8, and K+
+impl fmt::Display for DesKeyGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", tr!("Raw Input (bytes): {}", format!("{:?}", self.raw_key)))?;
+        // Try to convert to a string, but handle non-UTF8 gracefully
+        match std::str::from_utf8(&self.raw_key) {
+            Ok(string_repr) => writeln!(f, "{}", tr!("Raw Input (as string): {}", string_repr))?,
+            Err(_) => writeln!(f, "{}", tr!("Raw Input (non-UTF8)"))?,
+        }
+        write!(f, "{}", tr!("K+ Key (hex): {}", format!("0x{:014X}", self.k_plus)))
+    }
+}
+
+/// Romanian for the key report and the demo
+pub const MESSAGES: Catalog = &[
+    ("Raw Input (bytes): {}", "Intrare brută (octeți): {}"),
+    ("Raw Input (as string): {}", "Intrare brută (ca text): {}"),
+    ("Raw Input (non-UTF8)", "Intrare brută (nu este UTF-8)"),
+    ("K+ Key (hex): {}", "Cheia K+ (hex): {}"),
+    ("--- New Key Generation ---", "--- Generare cheie nouă ---"),
+    ("--- Random Key ---", "--- Cheie aleatoare ---"),
+    ("Error generating key: {}", "Eroare la generarea cheii: {}"),
+];
+
+/// Initial permutation of a block
+const IP: [u8; 64] = [
+    58, 50, 42, 34, 26, 18, 10, 2,
+    60, 52, 44, 36, 28, 20, 12, 4,
+    62, 54, 46, 38, 30, 22, 14, 6,
+    64, 56, 48, 40, 32, 24, 16, 8,
+    57, 49, 41, 33, 25, 17,  9, 1,
+    59, 51, 43, 35, 27, 19, 11, 3,
+    61, 53, 45, 37, 29, 21, 13, 5,
+    63, 55, 47, 39, 31, 23, 15, 7
+];
+
+/// Final permutation, the inverse of IP
+const FP: [u8; 64] = [
+    40, 8, 48, 16, 56, 24, 64, 32,
+    39, 7, 47, 15, 55, 23, 63, 31,
+    38, 6, 46, 14, 54, 22, 62, 30,
+    37, 5, 45, 13, 53, 21, 61, 29,
+    36, 4, 44, 12, 52, 20, 60, 28,
+    35, 3, 43, 11, 51, 19, 59, 27,
+    34, 2, 42, 10, 50, 18, 58, 26,
+    33, 1, 41,  9, 49, 17, 57, 25
+];
+
+/// Expansion of the 32-bit half block to 48 bits
+const E: [u8; 48] = [
+    32,  1,  2,  3,  4,  5,
+     4,  5,  6,  7,  8,  9,
+     8,  9, 10, 11, 12, 13,
+    12, 13, 14, 15, 16, 17,
+    16, 17, 18, 19, 20, 21,
+    20, 21, 22, 23, 24, 25,
+    24, 25, 26, 27, 28, 29,
+    28, 29, 30, 31, 32,  1
+];
+
+/// Permutation of the S-box output
+const P: [u8; 32] = [
+    16,  7, 20, 21, 29, 12, 28, 17,
+     1, 15, 23, 26,  5, 18, 31, 10,
+     2,  8, 24, 14, 32, 27,  3,  9,
+    19, 13, 30,  6, 22, 11,  4, 25
+];
+
+/// PC-2 selects the 48-bit round key from C and D
+const PC2: [u8; 48] = [
+    14, 17, 11, 24,  1,  5,
+     3, 28, 15,  6, 21, 10,
+    23, 19, 12,  4, 26,  8,
+    16,  7, 27, 20, 13,  2,
+    41, 52, 31, 37, 47, 55,
+    30, 40, 51, 45, 33, 48,
+    44, 49, 39, 56, 34, 53,
+    46, 42, 50, 36, 29, 32
+];
+
+/// Left rotations of C and D before each round
+const SHIFTS: [u32; 16] = [1, 1, 2, 2, 2, 2, 2, 2, 1, 2, 2, 2, 2, 2, 2, 1];
+
+/// S-boxes: the outer bits of each 6-bit group pick the row, the inner four the column
+const S_BOXES: [[[u8; 16]; 4]; 8] = [
+    [
+        [14, 4, 13, 1, 2, 15, 11, 8, 3, 10, 6, 12, 5, 9, 0, 7],
+        [0, 15, 7, 4, 14, 2, 13, 1, 10, 6, 12, 11, 9, 5, 3, 8],
+        [4, 1, 14, 8, 13, 6, 2, 11, 15, 12, 9, 7, 3, 10, 5, 0],
+        [15, 12, 8, 2, 4, 9, 1, 7, 5, 11, 3, 14, 10, 0, 6, 13],
+    ],
+    [
+        [15, 1, 8, 14, 6, 11, 3, 4, 9, 7, 2, 13, 12, 0, 5, 10],
+        [3, 13, 4, 7, 15, 2, 8, 14, 12, 0, 1, 10, 6, 9, 11, 5],
+        [0, 14, 7, 11, 10, 4, 13, 1, 5, 8, 12, 6, 9, 3, 2, 15],
+        [13, 8, 10, 1, 3, 15, 4, 2, 11, 6, 7, 12, 0, 5, 14, 9],
+    ],
+    [
+        [10, 0, 9, 14, 6, 3, 15, 5, 1, 13, 12, 7, 11, 4, 2, 8],
+        [13, 7, 0, 9, 3, 4, 6, 10, 2, 8, 5, 14, 12, 11, 15, 1],
+        [13, 6, 4, 9, 8, 15, 3, 0, 11, 1, 2, 12, 5, 10, 14, 7],
+        [1, 10, 13, 0, 6, 9, 8, 7, 4, 15, 14, 3, 11, 5, 2, 12],
+    ],
+    [
+        [7, 13, 14, 3, 0, 6, 9, 10, 1, 2, 8, 5, 11, 12, 4, 15],
+        [13, 8, 11, 5, 6, 15, 0, 3, 4, 7, 2, 12, 1, 10, 14, 9],
+        [10, 6, 9, 0, 12, 11, 7, 13, 15, 1, 3, 14, 5, 2, 8, 4],
+        [3, 15, 0, 6, 10, 1, 13, 8, 9, 4, 5, 11, 12, 7, 2, 14],
+    ],
+    [
+        [2, 12, 4, 1, 7, 10, 11, 6, 8, 5, 3, 15, 13, 0, 14, 9],
+        [14, 11, 2, 12, 4, 7, 13, 1, 5, 0, 15, 10, 3, 9, 8, 6],
+        [4, 2, 1, 11, 10, 13, 7, 8, 15, 9, 12, 5, 6, 3, 0, 14],
+        [11, 8, 12, 7, 1, 14, 2, 13, 6, 15, 0, 9, 10, 4, 5, 3],
+    ],
+    [
+        [12, 1, 10, 15, 9, 2, 6, 8, 0, 13, 3, 4, 14, 7, 5, 11],
+        [10, 15, 4, 2, 7, 12, 9, 5, 6, 1, 13, 14, 0, 11, 3, 8],
+        [9, 14, 15, 5, 2, 8, 12, 3, 7, 0, 4, 10, 1, 13, 11, 6],
+        [4, 3, 2, 12, 9, 5, 15, 10, 11, 14, 1, 7, 6, 0, 8, 13],
+    ],
+    [
+        [4, 11, 2, 14, 15, 0, 8, 13, 3, 12, 9, 7, 5, 10, 6, 1],
+        [13, 0, 11, 7, 4, 9, 1, 10, 14, 3, 5, 12, 2, 15, 8, 6],
+        [1, 4, 11, 13, 12, 3, 7, 14, 10, 15, 6, 8, 0, 5, 9, 2],
+        [6, 11, 13, 8, 1, 4, 10, 7, 9, 5, 0, 15, 14, 2, 3, 12],
+    ],
+    [
+        [13, 2, 8, 4, 6, 15, 11, 1, 10, 9, 3, 14, 5, 0, 12, 7],
+        [1, 15, 13, 8, 10, 3, 7, 4, 12, 5, 6, 11, 0, 14, 9, 2],
+        [7, 11, 4, 1, 9, 12, 14, 2, 0, 6, 10, 13, 15, 3, 5, 8],
+        [2, 1, 14, 7, 4, 10, 8, 13, 15, 12, 9, 0, 3, 5, 6, 11],
+    ],
+];
+
+/// Pick bits of `input` (numbered from 1 at the most significant of
+/// `input_bits`) in the order given by `table`
+fn permute(input: u64, input_bits: u32, table: &[u8]) -> u64 {
+    let mut output = 0;
+    for &pos in table {
+        output = (output << 1) | ((input >> (input_bits - pos as u32)) & 1);
+    }
+    output
+}
+
+/// DES in ECB mode with PKCS#7 padding, as a [`SymmetricCipher`], and as a
+/// [`BlockCipher`] for the other modes
+pub struct Des {
+    /// 48-bit round keys K1..K16
+    subkeys: [u64; 16],
+}
+
+impl Des {
+    /// Round keys derived from K+ by rotating its halves and applying PC-2
+    pub fn subkeys(&self) -> &[u64; 16] {
+        &self.subkeys
+    }
+
+    pub fn encrypt_block(&self, block: u64) -> u64 {
+        self.trace_block(block, self.subkeys.iter()).output
+    }
+
+    pub fn decrypt_block(&self, block: u64) -> u64 {
+        self.trace_block(block, self.subkeys.iter().rev()).output
+    }
+
+    /// Encrypt one block, keeping the halves after every round
+    pub fn trace_encryption(&self, block: u64) -> DesTrace {
+        self.trace_block(block, self.subkeys.iter())
+    }
+
+    /// IP, 16 Feistel rounds and the final permutation
+    fn trace_block<'a>(&self, block: u64, subkeys: impl Iterator<Item = &'a u64>) -> DesTrace {
+        let permuted = permute(block, 64, &IP);
+        let (mut left, mut right) = (permuted >> 32, permuted & 0xFFFF_FFFF);
+        let mut rounds = [(0, 0); 16];
+
+        for (round, &subkey) in rounds.iter_mut().zip(subkeys) {
+            (left, right) = (right, left ^ feistel(right, subkey));
+            *round = (left as u32, right as u32);
+        }
+
+        // The halves are swapped once more before the final permutation
+        DesTrace { permuted, rounds, output: permute((right << 32) | left, 64, &FP) }
+    }
+}
+
+/// One block on its way through DES, for showing the rounds
+pub struct DesTrace {
+    /// The block after the initial permutation, L0 ‖ R0
+    pub permuted: u64,
+    /// L and R after each of the 16 rounds
+    pub rounds: [(u32, u32); 16],
+    pub output: u64,
+}
+
+/// The round function f(R, K)
+fn feistel(right: u64, subkey: u64) -> u64 {
+    let mixed = permute(right, 32, &E) ^ subkey;
+
+    let mut output = 0;
+    for (i, s_box) in S_BOXES.iter().enumerate() {
+        let group = (mixed >> (42 - 6 * i)) & 0x3F;
+        let row = (((group >> 4) & 0b10) | (group & 1)) as usize;
+        let column = ((group >> 1) & 0xF) as usize;
+        output = (output << 4) | s_box[row][column] as u64;
+    }
+
+    permute(output, 32, &P)
+}
+
+impl SymmetricCipher for Des {
+    fn validate_key(key: &[u8]) -> Result<(), CipherError> {
+        if key.len() == 8 {
+            Ok(())
+        } else {
+            Err(CipherError::InvalidKey(format!("{} (got {} bytes)", INFO.key_description, key.len())))
+        }
+    }
+
+    fn new(key: &[u8]) -> Result<Self, CipherError> {
+        <Self as SymmetricCipher>::validate_key(key)?;
+        let k_plus = DesKeyGenerator::new(key)
+            .map_err(|e| CipherError::InvalidKey(e.to_string()))?
+            .k_plus();
+
+        let (mut c, mut d) = ((k_plus >> 28) as u32, (k_plus & 0x0FFF_FFFF) as u32);
+        let mut subkeys = [0; 16];
+        for (subkey, &shift) in subkeys.iter_mut().zip(&SHIFTS) {
+            c = ((c << shift) | (c >> (28 - shift))) & 0x0FFF_FFFF;
+            d = ((d << shift) | (d >> (28 - shift))) & 0x0FFF_FFFF;
+            *subkey = permute(((c as u64) << 28) | d as u64, 56, &PC2);
+        }
+
+        Ok(Des { subkeys })
+    }
+
+    fn info(&self) -> CipherInfo {
+        INFO
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        modes::encrypt(self, Mode::Ecb, &[], plaintext).unwrap_or_default()
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, CipherError> {
+        modes::decrypt(self, Mode::Ecb, &[], ciphertext)
+    }
+}
+
+impl BlockCipher for Des {
+    fn block_size(&self) -> usize {
+        8
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let input = u64::from_be_bytes(block.try_into().expect("DES blocks are 8 bytes"));
+        block.copy_from_slice(&Des::encrypt_block(self, input).to_be_bytes());
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let input = u64::from_be_bytes(block.try_into().expect("DES blocks are 8 bytes"));
+        block.copy_from_slice(&Des::decrypt_block(self, input).to_be_bytes());
+    }
+}
+
+const INFO: CipherInfo = CipherInfo {
+    name: "des",
+    family: CipherFamily::Block,
+    key_description: "exactly 8 bytes",
+    block_size: Some(8),
+};
+
+/// Triple DES (TDEA, NIST SP 800-67) with three independent keys: each
+/// block is encrypted with K1, decrypted with K2 and encrypted with K3, so
+/// three equal keys make it single DES again
+pub struct TripleDes {
+    stages: [Des; 3],
+}
+
+impl SymmetricCipher for TripleDes {
+    fn validate_key(key: &[u8]) -> Result<(), CipherError> {
+        if key.len() == 24 {
+            Ok(())
+        } else {
+            Err(CipherError::InvalidKey(format!("{} (got {} bytes)", TRIPLE_INFO.key_description, key.len())))
+        }
+    }
+
+    fn new(key: &[u8]) -> Result<Self, CipherError> {
+        <Self as SymmetricCipher>::validate_key(key)?;
+        let stage = |index: usize| <Des as SymmetricCipher>::new(&key[8 * index..8 * index + 8]);
+        Ok(TripleDes { stages: [stage(0)?, stage(1)?, stage(2)?] })
+    }
+
+    fn info(&self) -> CipherInfo {
+        TRIPLE_INFO
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        modes::encrypt(self, Mode::Ecb, &[], plaintext).unwrap_or_default()
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, CipherError> {
+        modes::decrypt(self, Mode::Ecb, &[], ciphertext)
+    }
+}
+
+impl BlockCipher for TripleDes {
+    fn block_size(&self) -> usize {
+        8
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let [k1, k2, k3] = &self.stages;
+        let input = u64::from_be_bytes(block.try_into().expect("DES blocks are 8 bytes"));
+        block.copy_from_slice(&k3.encrypt_block(k2.decrypt_block(k1.encrypt_block(input))).to_be_bytes());
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let [k1, k2, k3] = &self.stages;
+        let input = u64::from_be_bytes(block.try_into().expect("DES blocks are 8 bytes"));
+        block.copy_from_slice(&k1.decrypt_block(k2.encrypt_block(k3.decrypt_block(input))).to_be_bytes());
+    }
+}
+
+const TRIPLE_INFO: CipherInfo = CipherInfo {
+    name: "3des",
+    family: CipherFamily::Block,
+    key_description: "exactly 24 bytes (K1 ‖ K2 ‖ K3)",
+    block_size: Some(8),
+};
+
+/// DES and Triple DES for the cipher registry
+pub struct DesPlugin;
+
+impl CipherPlugin for DesPlugin {
+    fn name(&self) -> &'static str {
+        "DES"
+    }
+
+    fn registrations(&self) -> Result<Vec<Registration>, CipherError> {
+        let exact = Capabilities { exact_round_trip: true, deterministic: true };
+        Ok(vec![
+            Registration::symmetric::<Des>("MORTYNOR", exact)?,
+            Registration::symmetric::<TripleDes>("ONE KEY!TWO KEY!RED KEY!", exact)?,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The worked example from J. Orlin Grabbe, "The DES Algorithm Illustrated"
+    #[test]
+    fn encrypts_the_textbook_example() {
+        let des = Des::new(&0x133457799BBCDFF1u64.to_be_bytes()).unwrap();
+        assert_eq!(des.subkeys()[0], 0b000110_110000_001011_101111_111111_000111_000001_110010);
+        assert_eq!(des.encrypt_block(0x0123456789ABCDEF), 0x85E813540F0AB405);
+        assert_eq!(des.decrypt_block(0x85E813540F0AB405), 0x0123456789ABCDEF);
+
+        let trace = des.trace_encryption(0x0123456789ABCDEF);
+        assert_eq!(trace.permuted, 0xCC00CCFF_F0AAF0AA);
+        assert_eq!(trace.rounds[0], (0xF0AAF0AA, 0xEF4A6544));
+        assert_eq!(trace.rounds[15], (0x43423234, 0x0A4CD995));
+        assert_eq!(trace.output, 0x85E813540F0AB405);
+    }
+
+    #[test]
+    fn padded_messages_round_trip() {
+        let des = Des::new(b"MORTYNOR").unwrap();
+        for length in [0, 7, 8, 13] {
+            let message = vec![b'a'; length];
+            let ciphertext = des.encrypt(&message);
+            assert_eq!(ciphertext.len(), (length / 8 + 1) * 8);
+            assert_eq!(des.decrypt(&ciphertext).unwrap(), message);
+        }
+        assert!(Des::new(b"SHORT").is_err());
+    }
+
+    /// The example in NIST SP 800-67, appendix B
+    #[test]
+    fn triple_des_matches_sp_800_67() {
+        use crypto_core::codec::decode_hex;
+
+        let tdes = TripleDes::new(&decode_hex("0123456789abcdef23456789abcdef01456789abcdef0123").unwrap()).unwrap();
+        let mut data = b"The qufck brown fox jump".to_vec();
+        for block in data.chunks_mut(8) {
+            BlockCipher::encrypt_block(&tdes, block);
+        }
+        assert_eq!(data, decode_hex("a826fd8ce53b855fcce21c8112256fe668d5c05dd9b6b900").unwrap());
+        for block in data.chunks_mut(8) {
+            BlockCipher::decrypt_block(&tdes, block);
+        }
+        assert_eq!(data, b"The qufck brown fox jump");
+
+        // Three copies of one key are single DES
+        let single = TripleDes::new(&b"MORTYNOR".repeat(3)).unwrap();
+        assert_eq!(single.encrypt(b"hello DES"), Des::new(b"MORTYNOR").unwrap().encrypt(b"hello DES"));
+        assert!(TripleDes::new(b"MORTYNOR").is_err());
+    }
+
+    #[test]
+    fn random_keys_have_odd_parity_and_repeat_under_a_seed() {
+        let key = DesKeyGenerator::random().unwrap();
+        assert!(key.raw_key().iter().all(|byte| byte.count_ones() % 2 == 1));
+        assert_ne!(key.raw_key(), DesKeyGenerator::random().unwrap().raw_key());
+
+        let seeded = || DesKeyGenerator::random().unwrap().k_plus();
+        assert_eq!(rng::with_seed(1, seeded), rng::with_seed(1, seeded));
+    }
+
+    #[test]
+    fn every_message_has_a_romanian_translation() {
+        use crypto_core::i18n::{mismatched_placeholders, untranslated};
+
+        for source in [include_str!("lib.rs"), include_str!("main.rs")] {
+            assert_eq!(untranslated(source, &[MESSAGES]), Vec::<String>::new());
+        }
+        assert!(mismatched_placeholders(MESSAGES).is_empty());
+    }
+}
@@ -0,0 +1,226 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::net::TcpListener;
+use std::path::Path;
+use std::process::Command;
+use std::thread;
+
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::client::http_request;
+use crate::exec::Execute;
+use crate::profile::CertificateProfile;
+use crate::provision::is_valid_username;
+use crate::server::{self, Request, Response};
+use crate::subject::SubjectDn;
+
+/// Path the client serves the challenge token under, as in ACME http-01
+const CHALLENGE_PREFIX: &str = "/.well-known/acme-challenge/";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OrderStatus {
+    Pending,
+    Valid,
+}
+
+/// An in-progress certificate order
+struct AcmeOrder {
+    identifier: String,
+    token: String,
+    status: OrderStatus,
+}
+
+/// An ACME server to enroll with
+pub(crate) struct AcmeServer<'a> {
+    pub(crate) url: &'a str,
+    /// API token the server requires, sent as a bearer token
+    pub(crate) token: Option<&'a str>,
+    /// CA certificate to verify an HTTPS server against
+    pub(crate) ca_cert_path: &'a str,
+}
+
+/// Mutable state shared across requests of one `pki serve` run
+#[derive(Default)]
+pub(crate) struct ServerState {
+    acme_orders: RefCell<HashMap<String, AcmeOrder>>,
+}
+
+impl PKIConfig {
+    /// Handle `/acme/...` endpoints: create an order, validate its challenge,
+    /// and finalize it with a CSR. The challenge token is fetched from the
+    /// host the identifier names on `challenge_port`, never from whoever
+    /// happens to be asking.
+    pub(crate) fn route_acme(
+        &self,
+        request: &Request,
+        path: &[&str],
+        state: &ServerState,
+        challenge_port: u16,
+    ) -> io::Result<Response> {
+        match path {
+            ["order"] => {
+                let identifier = String::from_utf8_lossy(&request.body).trim().to_string();
+                if !is_valid_username(&identifier) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        tr!("Invalid identifier {}", identifier)
+                    ));
+                }
+
+                let order_id = self.random_hex(8)?;
+                let token = self.random_hex(16)?;
+                let response = format!("order: {}\ntoken: {}\nstatus: pending", order_id, token);
+
+                state.acme_orders.borrow_mut().insert(order_id, AcmeOrder {
+                    identifier,
+                    token,
+                    status: OrderStatus::Pending,
+                });
+
+                Ok(Response::text(200, response))
+            }
+            ["order", order_id, "challenge"] => {
+                let (identifier, token, status) = match state.acme_orders.borrow().get(*order_id) {
+                    Some(order) => (order.identifier.clone(), order.token.clone(), order.status),
+                    None => return Ok(Response::text(404, "Unknown order")),
+                };
+                if status == OrderStatus::Valid {
+                    return Ok(Response::text(200, "status: valid"));
+                }
+
+                // Fetch the token from the identifier's own host, proving the
+                // requester controls the name it will be certified for
+                let challenge_url = format!("http://{}:{}", identifier, challenge_port);
+                let fetched = http_request(&challenge_url, "GET", &format!("{}{}", CHALLENGE_PREFIX, token), &[], &[], "")
+                    .map(|response| response.text());
+
+                if fetched.as_deref().ok() != Some(token.as_str()) {
+                    state.acme_orders.borrow_mut().remove(*order_id);
+                    return Err(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        tr!("Challenge validation failed; the order was discarded")
+                    ));
+                }
+
+                if let Some(order) = state.acme_orders.borrow_mut().get_mut(*order_id) {
+                    order.status = OrderStatus::Valid;
+                }
+                Ok(Response::text(200, "status: valid"))
+            }
+            ["order", order_id, "finalize"] => {
+                let identifier = match state.acme_orders.borrow().get(*order_id) {
+                    Some(order) if order.status == OrderStatus::Valid => order.identifier.clone(),
+                    Some(_) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::PermissionDenied,
+                            tr!("Order challenge has not been validated")
+                        ));
+                    }
+                    None => return Ok(Response::text(404, "Unknown order")),
+                };
+
+                let profile = request.query_param("profile").map(CertificateProfile::parse).transpose()?;
+
+                let certificate = self.issue_from_csr_pem(&request.body, profile, Some(&identifier), server::requested_replacement(request))?;
+                state.acme_orders.borrow_mut().remove(*order_id);
+
+                Ok(Response::new(200, "application/x-pem-file", certificate))
+            }
+            _ => Ok(Response::text(404, "Not found")),
+        }
+    }
+
+    /// Obtain a certificate for `username` from a `pki serve` instance using
+    /// the order → challenge → finalize flow. The server fetches the challenge
+    /// from the host named `username`, so this must run there. A certificate
+    /// the server already issued to `username` is only replaced with `renew`.
+    pub(crate) fn acme_enroll(
+        &self,
+        server: &AcmeServer,
+        username: &str,
+        challenge_port: u16,
+        profile: Option<CertificateProfile>,
+        subject_alt_names: &[String],
+        renew: bool,
+    ) -> io::Result<String> {
+        let user_key_path = format!("{}/{}_private_key.pem", self.users_dir, username);
+        let user_csr_path = format!("{}/{}_csr.pem", self.users_dir, username);
+        let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
+
+        fs::create_dir_all(&self.users_dir)?;
+        if !Path::new(&user_key_path).exists() {
+            self.generate_user_key(username)?;
+        }
+        let subject = self.subject_for_user(username, SubjectDn::default());
+        self.generate_csr_with_subject_alt_names(username, &subject, subject_alt_names)?;
+
+        let authorization = server.token.map(|token| format!("Bearer {}", token));
+        let headers: Vec<(&str, &str)> = authorization.iter().map(|value| ("Authorization", value.as_str())).collect();
+
+        // 1. Order
+        let order = http_request(server.url, "POST", "/acme/order", &headers, username.as_bytes(), server.ca_cert_path)?
+            .error_for_status()?
+            .text();
+        let field = |name: &str| {
+            order
+                .lines()
+                .find_map(|line| line.strip_prefix(name))
+                .map(|value| value.trim().to_string())
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, tr!("Malformed order response")))
+        };
+        let (order_id, token) = (field("order:")?, field("token:")?);
+
+        // 2. Challenge: answer the server's fetch of the token
+        let listener = TcpListener::bind(("0.0.0.0", challenge_port))?;
+        let expected_path = format!("{}{}", CHALLENGE_PREFIX, token);
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let response = match server::read_request(&mut stream) {
+                    Ok(request) if request.path == expected_path => Response::text(200, token.clone()),
+                    _ => Response::text(404, "Not found"),
+                };
+                let _ = server::write_response(&mut stream, &response);
+            }
+        });
+
+        http_request(server.url, "POST", &format!("/acme/order/{}/challenge", order_id), &headers, &[], server.ca_cert_path)?
+            .error_for_status()?;
+
+        // 3. Finalize with the CSR
+        let mut query = Vec::new();
+        if let Some(profile) = profile {
+            query.push(format!("profile={}", profile.name()));
+        }
+        if renew {
+            query.push(String::from("renew=true"));
+        }
+        let finalize_path = if query.is_empty() {
+            format!("/acme/order/{}/finalize", order_id)
+        } else {
+            format!("/acme/order/{}/finalize?{}", order_id, query.join("&"))
+        };
+        let certificate = http_request(server.url, "POST", &finalize_path, &headers, &fs::read(&user_csr_path)?, server.ca_cert_path)?
+            .error_for_status()?;
+
+        fs::write(&user_cert_path, certificate.body)?;
+        Ok(user_cert_path)
+    }
+}
+
+impl PKIConfig {
+    /// Random hex string of `bytes` bytes from openssl's CSPRNG
+    pub(crate) fn random_hex(&self, bytes: usize) -> io::Result<String> {
+        let output = Command::new("openssl")
+            .args(["rand", "-hex", &bytes.to_string()])
+            .run(self.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to generate random bytes")));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
@@ -0,0 +1,82 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::audit::format_unix_time;
+use crate::exec;
+use crate::inventory::unix_now;
+use crate::permissions::create_private_dir;
+
+impl PKIConfig {
+    /// Directory holding replaced keys, certificates and CRLs, next to `ca_dir`
+    fn archive_root(&self) -> String {
+        let pki_root = Path::new(&self.ca_dir).parent().unwrap_or(Path::new("."));
+        pki_root.join("archive").to_string_lossy().into_owned()
+    }
+
+    /// Guard an operation about to replace existing PKI files: unless
+    /// `--force` was given, ask for confirmation (or refuse when not
+    /// interactive), then move the files into a timestamped archive
+    /// directory. Returns that directory, if anything was archived.
+    pub(crate) fn replace_existing(&self, paths: &[&str]) -> io::Result<Option<String>> {
+        let existing: Vec<&str> = paths.iter().copied().filter(|path| Path::new(path).exists()).collect();
+        if existing.is_empty() {
+            return Ok(None);
+        }
+
+        if !self.force && !exec::is_dry_run() {
+            confirm_replace(&existing)?;
+        }
+        self.archive_files(&existing).map(Some)
+    }
+
+    /// Move files into a new `archive/<timestamp>` directory and return it
+    pub(crate) fn archive_files(&self, paths: &[&str]) -> io::Result<String> {
+        // e.g. 20261016T185743Z
+        let stamp: String = format_unix_time(unix_now())
+            .trim_end_matches(" UTC")
+            .replace(' ', "T")
+            .replace(['-', ':'], "")
+            + "Z";
+
+        let mut archive_dir = format!("{}/{}", self.archive_root(), stamp);
+        let mut attempt = 1;
+        while Path::new(&archive_dir).exists() {
+            attempt += 1;
+            archive_dir = format!("{}/{}-{}", self.archive_root(), stamp, attempt);
+        }
+        create_private_dir(&archive_dir)?;
+
+        for path in paths {
+            let file_name = Path::new(path).file_name().unwrap_or_default();
+            exec::rename(path, Path::new(&archive_dir).join(file_name))?;
+        }
+
+        Ok(archive_dir)
+    }
+}
+
+/// Ask on the terminal whether existing files may be replaced
+fn confirm_replace(paths: &[&str]) -> io::Result<()> {
+    let refused = || io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        tr!("{} already exists; rerun with --force to replace it (the old files are archived)", paths.join(", "))
+    );
+
+    if !io::stdin().is_terminal() {
+        return Err(refused());
+    }
+
+    eprint!("Replace {}? The old files will be archived. [y/N] ", paths.join(", "));
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+
+    match answer.trim().to_ascii_lowercase().as_str() {
+        "y" | "yes" => Ok(()),
+        _ => Err(refused()),
+    }
+}
@@ -0,0 +1,85 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+use tracing::info;
+
+use crate::PKIConfig;
+use crate::exec;
+use crate::inventory::unix_now;
+
+/// One line of the CA audit log
+pub(crate) struct AuditEvent {
+    /// Seconds since the Unix epoch
+    pub(crate) timestamp: u64,
+    pub(crate) action: String,
+    pub(crate) detail: String,
+}
+
+impl PKIConfig {
+    fn audit_log_path(&self) -> String {
+        format!("{}/audit.log", self.ca_dir)
+    }
+
+    /// Append an event to the CA audit log (`timestamp<TAB>action<TAB>detail`)
+    pub(crate) fn record_audit_event(&self, action: &str, detail: &str) -> io::Result<()> {
+        if exec::is_dry_run() {
+            exec::plan(&format!("log {} {} to {}", action, detail, self.audit_log_path()));
+            return Ok(());
+        }
+        info!(action, detail, "audit event");
+
+        let timestamp = unix_now();
+        let mut log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.audit_log_path())?;
+
+        writeln!(log, "{}\t{}\t{}", timestamp, action, detail.replace(['\t', '\n'], " "))
+    }
+
+    /// The most recent audit events, newest first
+    pub(crate) fn recent_audit_events(&self, limit: usize) -> io::Result<Vec<AuditEvent>> {
+        let log_path = self.audit_log_path();
+        if !Path::new(&log_path).exists() {
+            return Ok(Vec::new());
+        }
+
+        Ok(fs::read_to_string(log_path)?
+            .lines()
+            .rev()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                Some(AuditEvent {
+                    timestamp: fields.next()?.parse().ok()?,
+                    action: fields.next()?.to_string(),
+                    detail: fields.next().unwrap_or_default().to_string(),
+                })
+            })
+            .take(limit)
+            .collect())
+    }
+}
+
+/// Format Unix seconds as `YYYY-MM-DD HH:MM:SS UTC`
+pub(crate) fn format_unix_time(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let seconds = timestamp % 86_400;
+
+    // Civil-from-days (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year, month, day,
+        seconds / 3600, (seconds % 3600) / 60, seconds % 60
+    )
+}
@@ -0,0 +1,333 @@
+use std::fs;
+use std::io;
+use std::process::Command;
+
+use crypto_core::config::Config;
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::database::{asn1_time, IssuanceEntry};
+use crate::digest::Digest;
+use crate::exec::{self, Execute};
+use crate::keyalgorithm::KeyAlgorithm;
+use crate::native::NativeBackend;
+use crate::profile::CertificateProfile;
+
+/// Does the cryptography of issuing and using certificates: key
+/// generation, certificate requests, signing certificates and documents,
+/// and checking document signatures. Paths and bookkeeping (archives, the
+/// issuance database, the audit log) stay in [`PKIConfig`].
+pub(crate) trait CryptoBackend: Send + Sync {
+    /// `pki.backend` value selecting this backend
+    fn name(&self) -> &'static str;
+
+    /// Whether it runs the `openssl` binary
+    fn needs_openssl(&self) -> bool;
+
+    /// Create the CA key pair with [`PKIConfig::ca_signer`]
+    fn generate_ca_key(&self, pki: &PKIConfig) -> io::Result<()>;
+
+    /// Self-sign the CA certificate
+    fn create_ca_certificate(&self, pki: &PKIConfig) -> io::Result<()>;
+
+    /// Write a new key to the user's (already created, owner-only) key file
+    fn generate_user_key(&self, pki: &PKIConfig, username: &str) -> io::Result<()>;
+
+    /// Write the user's CSR for `subject` (`/CN=.../O=...`), requesting
+    /// `subject_alt_names` (`DNS:...`, `IP:...`, `email:...`)
+    fn create_csr(&self, pki: &PKIConfig, username: &str, subject: &str, subject_alt_names: &[String]) -> io::Result<()>;
+
+    /// Sign the user's CSR into a certificate with `serial` (hex), adding
+    /// the profile, subject alternative names and configured extensions
+    fn issue_certificate(
+        &self,
+        pki: &PKIConfig,
+        username: &str,
+        serial: &str,
+        profile: Option<CertificateProfile>,
+        subject_alt_names: &[String],
+    ) -> io::Result<()>;
+
+    /// What the issuance database records about a certificate
+    fn issuance_entry(&self, pki: &PKIConfig, cert_path: &str) -> io::Result<IssuanceEntry>;
+
+    /// Write the detached signature `<document>.sig`
+    fn sign_document(&self, pki: &PKIConfig, username: &str, document_path: &str) -> io::Result<()>;
+
+    /// Check `<document>.sig` against the user's certificate
+    fn verify_document_signature(&self, pki: &PKIConfig, username: &str, document_path: &str, digest: Digest) -> io::Result<bool>;
+
+    /// Digest algorithm named in the DigestInfo of `<document>.sig`
+    fn signature_digest(&self, pki: &PKIConfig, username: &str, document_path: &str) -> io::Result<Option<String>>;
+}
+
+/// The backend chosen with `pki.backend`
+pub(crate) fn from_config(config: &Config) -> io::Result<Box<dyn CryptoBackend>> {
+    match config.get("pki.backend").unwrap_or("openssl") {
+        "openssl" => Ok(Box::new(OpensslBackend)),
+        "native" => Ok(Box::new(NativeBackend)),
+        other => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            tr!("Unknown backend {} (expected openssl or native)", other)
+        )),
+    }
+}
+
+/// Runs the `openssl` command line for everything, which also covers CA
+/// keys in PKCS#11 tokens and user keys encrypted under the master key
+pub(crate) struct OpensslBackend;
+
+impl CryptoBackend for OpensslBackend {
+    fn name(&self) -> &'static str {
+        "openssl"
+    }
+
+    fn needs_openssl(&self) -> bool {
+        true
+    }
+
+    fn generate_ca_key(&self, pki: &PKIConfig) -> io::Result<()> {
+        pki.ca_signer.generate_key(pki.runner(), pki.ca_key_algorithm, pki.ca_key_bits)
+    }
+
+    fn create_ca_certificate(&self, pki: &PKIConfig) -> io::Result<()> {
+        let ca_cert_path = format!("{}/ca_certificate.pem", pki.ca_dir);
+        pki.check_digest_for_key(&pki.ca_signer.key_args("-in"))?;
+
+        let output = Command::new("openssl")
+            .args([
+                "req", "-x509", "-new", "-nodes",
+                &pki.signing_digest().flag(),
+                "-days", &pki.ca_validity_days.to_string(),
+                "-out", &ca_cert_path,
+                "-subj", &pki.ca_subject.to_subj()
+            ])
+            .args(pki.ca_signer.key_args("-key"))
+            .execute(pki.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to create CA self-signed certificate")));
+        }
+
+        Ok(())
+    }
+
+    fn generate_user_key(&self, pki: &PKIConfig, username: &str) -> io::Result<()> {
+        let user_key_path = format!("{}/{}_private_key.pem", pki.users_dir, username);
+
+        let output = pki
+            .unlock_user_keys(
+                Command::new("openssl")
+                    .arg("genpkey")
+                    .args(pki.user_key_algorithm.genpkey_args(pki.user_key_bits))
+                    .args(pki.new_user_key_args())
+                    .args(["-out", &user_key_path])
+            )?
+            .execute(pki.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to generate private key for user {}", username)));
+        }
+
+        Ok(())
+    }
+
+    fn create_csr(&self, pki: &PKIConfig, username: &str, subject: &str, subject_alt_names: &[String]) -> io::Result<()> {
+        let user_csr_path = format!("{}/{}_csr.pem", pki.users_dir, username);
+
+        let mut command = Command::new("openssl");
+        command
+            .args(["req", "-new"])
+            .args(pki.user_key_args("-key", username))
+            .args(["-out", &user_csr_path, "-subj", subject]);
+        if !subject_alt_names.is_empty() {
+            command.args(["-addext", &format!("subjectAltName = {}", subject_alt_names.join(", "))]);
+        }
+        let output = pki.unlock_user_keys(&mut command)?.execute(pki.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to generate CSR for user {}", username)));
+        }
+
+        Ok(())
+    }
+
+    fn issue_certificate(
+        &self,
+        pki: &PKIConfig,
+        username: &str,
+        serial: &str,
+        profile: Option<CertificateProfile>,
+        subject_alt_names: &[String],
+    ) -> io::Result<()> {
+        let ca_cert_path = format!("{}/ca_certificate.pem", pki.ca_dir);
+        let user_csr_path = format!("{}/{}_csr.pem", pki.users_dir, username);
+        let user_cert_path = format!("{}/{}_certificate.pem", pki.users_dir, username);
+        let ext_path = format!("{}/{}_ext.cnf", pki.users_dir, username);
+        pki.check_digest_for_key(&pki.ca_signer.key_args("-in"))?;
+
+        let mut command = Command::new("openssl");
+        command.args([
+            "x509", "-req",
+            "-in", &user_csr_path,
+            "-CA", &ca_cert_path,
+            "-set_serial", &format!("0x{}", serial),
+            "-out", &user_cert_path,
+            "-days", &pki.user_validity_days.to_string(),
+            &pki.signing_digest().flag()
+        ]);
+        command.args(pki.ca_signer.key_args("-CAkey"));
+
+        // The CSR may come from elsewhere, so its own key decides; a dry run
+        // has not written the local one yet, which gets the configured type
+        let rsa_key = fs::read_to_string(&user_csr_path)
+            .ok()
+            .and_then(|pem| KeyAlgorithm::is_rsa_request(&pem))
+            .unwrap_or(pki.user_key_algorithm == KeyAlgorithm::Rsa);
+        exec::write(&ext_path, pki.user_extension_config(profile, subject_alt_names, rsa_key))?;
+        command.args(["-extfile", &ext_path, "-extensions", "v3_profile"]);
+
+        let output = command.execute(pki.runner());
+        exec::remove_file(&ext_path)?;
+        let output = output?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to sign certificate for user {}", username)));
+        }
+
+        Ok(())
+    }
+
+    fn issuance_entry(&self, pki: &PKIConfig, cert_path: &str) -> io::Result<IssuanceEntry> {
+        let output = Command::new("openssl")
+            .args([
+                "x509", "-noout",
+                "-serial", "-subject", "-enddate",
+                "-nameopt", "compat",
+                "-dateopt", "iso_8601",
+                "-in", cert_path
+            ])
+            .run(pki.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to read certificate {}", cert_path)));
+        }
+
+        let text = String::from_utf8_lossy(&output.stdout);
+        let field = |prefix: &str| {
+            text.lines()
+                .find_map(|line| line.strip_prefix(prefix))
+                .map(str::trim)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, tr!("Missing {} in {}", prefix, cert_path)))
+        };
+
+        let expiry = asn1_time(field("notAfter=")?)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, tr!("Unexpected expiry in {}", cert_path)))?;
+
+        Ok(IssuanceEntry {
+            serial: field("serial=")?.to_string(),
+            subject: field("subject=")?.to_string(),
+            expiry,
+        })
+    }
+
+    fn sign_document(&self, pki: &PKIConfig, username: &str, document_path: &str) -> io::Result<()> {
+        let signature_path = format!("{}.sig", document_path);
+        let algorithm = pki.check_digest_for_key(&pki.user_key_args("-in", username))?;
+
+        let mut command = Command::new("openssl");
+        if algorithm == Some(KeyAlgorithm::Ed25519) {
+            // EdDSA hashes the document itself, which dgst cannot do
+            command
+                .args(["pkeyutl", "-sign", "-rawin"])
+                .args(pki.user_key_args("-inkey", username))
+                .args(["-out", &signature_path, "-in", document_path]);
+        } else {
+            command
+                .args(["dgst", &pki.signing_digest().flag()])
+                .args(pki.user_key_args("-sign", username))
+                .args(["-out", &signature_path, document_path]);
+        }
+        let output = pki.unlock_user_keys(&mut command)?.execute(pki.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to sign document for user {}", username)));
+        }
+
+        Ok(())
+    }
+
+    fn verify_document_signature(&self, pki: &PKIConfig, username: &str, document_path: &str, digest: Digest) -> io::Result<bool> {
+        let user_cert_path = format!("{}/{}_certificate.pem", pki.users_dir, username);
+        let signature_path = format!("{}.sig", document_path);
+        let public_key_path = format!("{}.pubkey.pem", signature_path);
+
+        // dgst -verify expects a public key, not a certificate
+        let output = Command::new("openssl")
+            .args([
+                "x509", "-noout", "-pubkey",
+                "-in", &user_cert_path,
+                "-out", &public_key_path
+            ])
+            .run(pki.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to read public key of user {}", username)));
+        }
+
+        let ed25519 = fs::read_to_string(&public_key_path).is_ok_and(|pem| KeyAlgorithm::is_ed25519_public_key(&pem));
+        let mut command = Command::new("openssl");
+        if ed25519 {
+            command.args([
+                "pkeyutl", "-verify", "-rawin",
+                "-pubin", "-inkey", &public_key_path,
+                "-sigfile", &signature_path,
+                "-in", document_path
+            ]);
+        } else {
+            command.args([
+                "dgst", &digest.flag(),
+                "-verify", &public_key_path,
+                "-signature", &signature_path,
+                document_path
+            ]);
+        }
+        let output = command.run(pki.runner());
+        fs::remove_file(&public_key_path)?;
+
+        Ok(output?.status.success())
+    }
+
+    fn signature_digest(&self, pki: &PKIConfig, username: &str, document_path: &str) -> io::Result<Option<String>> {
+        let user_cert_path = format!("{}/{}_certificate.pem", pki.users_dir, username);
+        let signature_path = format!("{}.sig", document_path);
+        let public_key_path = format!("{}.pubkey.pem", signature_path);
+
+        let output = Command::new("openssl")
+            .args(["x509", "-noout", "-pubkey", "-in", &user_cert_path, "-out", &public_key_path])
+            .run(pki.runner())?;
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to read public key of user {}", username)));
+        }
+
+        let output = Command::new("openssl")
+            .args([
+                "pkeyutl", "-verifyrecover", "-asn1parse",
+                "-pubin", "-inkey", &public_key_path,
+                "-in", &signature_path
+            ])
+            .run(pki.runner());
+        fs::remove_file(&public_key_path)?;
+        let output = output?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .find_map(|line| line.split_once("OBJECT"))
+            .and_then(|(_, rest)| rest.trim().strip_prefix(':'))
+            .map(str::to_string))
+    }
+}
@@ -0,0 +1,157 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use crypto_core::tr;
+use ring::aead::Aad;
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::PKIConfig;
+use crate::exec::{self, Execute};
+use crate::masterkey::{nonce_from, wrapping_key};
+use crate::permissions::create_private_file;
+
+/// Start of every backup; also authenticated, so a backup cannot be passed
+/// off as some other ciphertext made with the same passphrase
+const MAGIC: &[u8; 8] = b"PKIBAK01";
+
+/// PBKDF2-HMAC-SHA256 rounds for deriving the archive key from the passphrase
+const PBKDF2_ITERATIONS: u32 = 600_000;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+impl PKIConfig {
+    /// Archive the CA and user directories into a passphrase-encrypted file.
+    ///
+    /// Each directory is archived relative to itself, so a restore unpacks
+    /// into whatever locations are configured then. The two archives are
+    /// encrypted together with AES-256-GCM, which also detects tampering:
+    /// `MAGIC`, iterations, salt, nonce, then the sealed length of the CA
+    /// archive, the CA archive and the users archive.
+    pub(crate) fn backup(&self, output_path: &str, passphrase: &str) -> io::Result<()> {
+        if !Path::new(&self.ca_dir).is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                tr!("CA directory {} not found", self.ca_dir)
+            ));
+        }
+        if exec::is_dry_run() {
+            exec::plan(&format!("archive {} and {} into {}", self.ca_dir, self.users_dir, output_path));
+            return Ok(());
+        }
+
+        let ca_archive = self.archive_directory(&self.ca_dir, &format!("{}.ca.tmp", output_path))?;
+        let users_archive = match Path::new(&self.users_dir).is_dir() {
+            true => self.archive_directory(&self.users_dir, &format!("{}.users.tmp", output_path))?,
+            false => Vec::new(),
+        };
+
+        let mut sealed = (ca_archive.len() as u64).to_be_bytes().to_vec();
+        sealed.extend_from_slice(&ca_archive);
+        sealed.extend_from_slice(&users_archive);
+
+        let rng = SystemRandom::new();
+        let mut salt = [0; SALT_LEN];
+        let mut nonce = [0; NONCE_LEN];
+        rng.fill(&mut salt).map_err(|_| io::Error::other(tr!("Failed to generate a salt")))?;
+        rng.fill(&mut nonce).map_err(|_| io::Error::other(tr!("Failed to generate a nonce")))?;
+
+        let mut header = MAGIC.to_vec();
+        header.extend_from_slice(&PBKDF2_ITERATIONS.to_be_bytes());
+        header.extend_from_slice(&salt);
+        header.extend_from_slice(&nonce);
+
+        wrapping_key(passphrase, PBKDF2_ITERATIONS, &salt)?
+            .seal_in_place_append_tag(nonce_from(&nonce)?, Aad::from(&header), &mut sealed)
+            .map_err(|_| io::Error::other(tr!("Failed to encrypt backup {}", output_path)))?;
+
+        create_private_file(output_path)?;
+        header.extend_from_slice(&sealed);
+        fs::write(output_path, header)
+    }
+
+    /// Check and decrypt a backup created by [`PKIConfig::backup`] and
+    /// unpack it into the configured CA and user directories
+    pub(crate) fn restore(&self, backup_path: &str, passphrase: &str) -> io::Result<()> {
+        let ca_key_path = format!("{}/ca_private_key.pem", self.ca_dir);
+        if Path::new(&ca_key_path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                tr!("A CA key already exists at {}; refusing to restore over it", ca_key_path)
+            ));
+        }
+
+        // Everything is authenticated before tar sees a byte of it
+        let backup = fs::read(backup_path)?;
+        let header_len = MAGIC.len() + 4 + SALT_LEN + NONCE_LEN;
+        let undecryptable = || io::Error::new(
+            io::ErrorKind::InvalidData,
+            tr!("Failed to decrypt backup {} (wrong passphrase?)", backup_path)
+        );
+        if backup.len() < header_len || !backup.starts_with(MAGIC) {
+            return Err(undecryptable());
+        }
+        let (header, sealed) = backup.split_at(header_len);
+        let iterations = u32::from_be_bytes(header[8..12].try_into().map_err(|_| undecryptable())?);
+        // The header is only authenticated once the key is derived, so an
+        // unexpected count must not decide how long deriving it takes
+        if iterations != PBKDF2_ITERATIONS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                tr!("Backup {} uses {} PBKDF2 iterations instead of {}", backup_path, iterations, PBKDF2_ITERATIONS)
+            ));
+        }
+        let (salt, nonce) = header[12..].split_at(SALT_LEN);
+
+        let mut buffer = sealed.to_vec();
+        let archives = wrapping_key(passphrase, iterations, salt)?
+            .open_in_place(nonce_from(nonce)?, Aad::from(header), &mut buffer)
+            .map_err(|_| undecryptable())?;
+        let (length, archives) = archives.split_at_checked(8).ok_or_else(undecryptable)?;
+        let ca_len = usize::try_from(u64::from_be_bytes(length.try_into().map_err(|_| undecryptable())?))
+            .map_err(|_| undecryptable())?;
+        let (ca_archive, users_archive) = archives.split_at_checked(ca_len).ok_or_else(undecryptable)?;
+
+        self.unpack_directory(ca_archive, &self.ca_dir, backup_path)?;
+        if !users_archive.is_empty() {
+            self.unpack_directory(users_archive, &self.users_dir, backup_path)?;
+        }
+
+        self.fix_permissions()
+    }
+
+    /// A tar archive of the contents of `dir`, made in `scratch_path`
+    fn archive_directory(&self, dir: &str, scratch_path: &str) -> io::Result<Vec<u8>> {
+        create_private_file(scratch_path)?;
+        let output = Command::new("tar")
+            .args(["-cf", scratch_path, "-C", dir, "."])
+            .run(self.runner());
+        let archive = fs::read(scratch_path);
+        fs::remove_file(scratch_path)?;
+
+        if !output?.status.success() {
+            return Err(io::Error::other(tr!("Failed to archive the PKI directories")));
+        }
+        archive
+    }
+
+    /// Unpack a tar archive of a directory's contents into `dir`
+    fn unpack_directory(&self, archive: &[u8], dir: &str, backup_path: &str) -> io::Result<()> {
+        let archive_path = format!("{}.tar.tmp", backup_path);
+        create_private_file(&archive_path)?;
+        exec::write(&archive_path, archive)?;
+        exec::create_dir_all(dir)?;
+
+        let output = Command::new("tar")
+            .args(["-xf", &archive_path, "-C", dir])
+            .execute(self.runner());
+        exec::remove_file(&archive_path)?;
+
+        if !output?.status.success() {
+            return Err(io::Error::other(tr!("Failed to unpack backup {}", backup_path)));
+        }
+        Ok(())
+    }
+}
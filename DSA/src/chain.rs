@@ -0,0 +1,475 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use crypto_core::der::{self, Reader};
+use crypto_core::{tr, CipherError};
+
+use crate::PKIConfig;
+use crate::digest::Digest;
+use crate::exec::{self, Execute};
+use crate::inventory::{parse_asn1_time, unix_now};
+use crate::keyalgorithm::ED25519_OID;
+use crate::native::{subject_from_name, BASIC_CONSTRAINTS};
+
+impl PKIConfig {
+    /// Intermediate CA certificates in `ca_dir/intermediates`, ordered by file name
+    /// (name them so the issuing CA sorts first and the one below the root last)
+    pub(crate) fn intermediate_certificates(&self) -> io::Result<Vec<String>> {
+        let intermediates_dir = format!("{}/intermediates", self.ca_dir);
+        if !Path::new(&intermediates_dir).is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut paths: Vec<String> = fs::read_dir(&intermediates_dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "pem"))
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        paths.sort();
+
+        Ok(paths)
+    }
+
+    /// Usernames that currently have an issued certificate
+    pub(crate) fn issued_usernames(&self) -> io::Result<Vec<String>> {
+        let mut usernames: Vec<String> = fs::read_dir(&self.users_dir)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                entry.file_name()
+                    .to_str()
+                    .and_then(|name| name.strip_suffix("_certificate.pem"))
+                    .map(str::to_string)
+            })
+            .collect();
+        usernames.sort();
+
+        Ok(usernames)
+    }
+
+    /// Write the CA bundle and a fullchain file for every issued user.
+    ///
+    /// `ca_chain.pem` holds the intermediates (and the cross-signed CA
+    /// certificate after a rotation) followed by the root; each
+    /// `<user>_fullchain.pem` holds the user certificate and intermediates,
+    /// leaving the root out as TLS servers expect. Returns the files written.
+    pub(crate) fn export_chain(&self) -> io::Result<Vec<String>> {
+        let ca_cert_path = format!("{}/ca_certificate.pem", self.ca_dir);
+        let chain_path = format!("{}/ca_chain.pem", self.ca_dir);
+
+        if !Path::new(&ca_cert_path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                tr!("CA certificate not found; initialize the PKI first")
+            ));
+        }
+
+        let mut intermediates = String::new();
+        for path in self.intermediate_certificates()? {
+            push_pem(&mut intermediates, &fs::read_to_string(path)?);
+        }
+        // Lets clients that only trust the previous root reach the current one
+        let cross_signed_path = self.cross_signed_path();
+        if Path::new(&cross_signed_path).exists() {
+            push_pem(&mut intermediates, &fs::read_to_string(&cross_signed_path)?);
+        }
+
+        let mut bundle = intermediates.clone();
+        push_pem(&mut bundle, &fs::read_to_string(&ca_cert_path)?);
+        exec::write(&chain_path, bundle)?;
+
+        let mut written = vec![chain_path];
+        for username in self.issued_usernames()? {
+            let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
+            let fullchain_path = format!("{}/{}_fullchain.pem", self.users_dir, username);
+
+            let mut fullchain = String::new();
+            push_pem(&mut fullchain, &fs::read_to_string(&user_cert_path)?);
+            fullchain.push_str(&intermediates);
+            exec::write(&fullchain_path, fullchain)?;
+
+            written.push(fullchain_path);
+        }
+
+        Ok(written)
+    }
+}
+
+/// Append a PEM blob, making sure it ends with a newline
+fn push_pem(bundle: &mut String, pem: &str) {
+    bundle.push_str(pem.trim_end());
+    bundle.push('\n');
+}
+
+/// Outcome of building and checking a certificate's chain to the CA
+pub(crate) struct ChainCheck {
+    pub(crate) valid: bool,
+    /// Subjects from the certificate itself up to the root
+    pub(crate) subjects: Vec<String>,
+    /// openssl's reason when the chain does not verify
+    pub(crate) error: Option<String>,
+}
+
+impl PKIConfig {
+    /// Verify a certificate against the CA and its intermediates, as of
+    /// `at_time` (Unix seconds) or now
+    pub(crate) fn check_certificate_chain(&self, cert_path: &str, at_time: Option<u64>) -> io::Result<ChainCheck> {
+        let ca_cert_path = format!("{}/ca_certificate.pem", self.ca_dir);
+
+        let mut command = Command::new("openssl");
+        command.args(["verify", "-show_chain", "-nameopt", "RFC2253", "-CAfile", &ca_cert_path]);
+        for intermediate in self.intermediate_certificates()? {
+            command.args(["-untrusted", &intermediate]);
+        }
+        if let Some(at_time) = at_time {
+            command.args(["-attime", &at_time.to_string()]);
+        }
+        let output = command.arg(cert_path).run(self.runner())?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let subjects = stdout
+            .lines()
+            .filter_map(|line| line.strip_prefix("depth="))
+            .filter_map(|line| line.split_once(": "))
+            .map(|(_, subject)| subject.trim_end_matches(" (untrusted)").to_string())
+            .collect();
+
+        let error = (!output.status.success()).then(|| {
+            String::from_utf8_lossy(&output.stderr)
+                .lines()
+                .find_map(|line| line.split_once("lookup: ").map(|(_, reason)| reason.to_string()))
+                .unwrap_or_else(|| String::from("verification failed"))
+        });
+
+        Ok(ChainCheck { valid: output.status.success(), subjects, error })
+    }
+}
+
+/// One certificate on the way from a user certificate up to the root
+pub(crate) struct ChainLink {
+    pub(crate) path: String,
+    pub(crate) subject: String,
+    pub(crate) issuer: String,
+    pub(crate) not_before: u64,
+    pub(crate) not_after: u64,
+    /// Signed by the key of the next certificate up (the root by its own)
+    pub(crate) signature_valid: bool,
+    /// Now falls between notBefore and notAfter
+    pub(crate) within_validity: bool,
+    /// basicConstraints fit the place in the chain: no CA flag on the user
+    /// certificate, CA:TRUE above it with any path length left
+    pub(crate) constraints_valid: bool,
+}
+
+impl ChainLink {
+    pub(crate) fn is_valid(&self) -> bool {
+        self.signature_valid && self.within_validity && self.constraints_valid
+    }
+}
+
+/// Outcome of checking a user certificate's chain link by link
+pub(crate) struct ChainVerification {
+    /// From the user certificate up to the last certificate reached
+    pub(crate) links: Vec<ChainLink>,
+    /// The chain ends in the PKI's root certificate, rather than at an
+    /// issuer that could not be found
+    pub(crate) anchored: bool,
+}
+
+impl ChainVerification {
+    pub(crate) fn is_valid(&self) -> bool {
+        self.anchored && self.links.iter().all(ChainLink::is_valid)
+    }
+}
+
+/// What chain verification reads out of a certificate
+struct ChainCertificate {
+    path: String,
+    /// The signed TBSCertificate, as encoded
+    tbs: Vec<u8>,
+    /// Encoded object identifier of the signature algorithm
+    signature_algorithm: Vec<u8>,
+    signature: Vec<u8>,
+    /// Encoded issuer and subject Names, compared to link the chain
+    issuer: Vec<u8>,
+    subject: Vec<u8>,
+    not_before: u64,
+    not_after: u64,
+    /// SubjectPublicKeyInfo, as encoded
+    public_key: Vec<u8>,
+    /// cA and pathLenConstraint of basicConstraints; `None` without the extension
+    basic_constraints: Option<(bool, Option<u64>)>,
+}
+
+impl ChainCertificate {
+    fn read(path: &str) -> io::Result<Self> {
+        let invalid = |e: CipherError| io::Error::new(io::ErrorKind::InvalidData, tr!("Cannot read certificate {}: {}", path, e));
+        let (_, certificate) = der::pem_decode(&fs::read_to_string(path)?).map_err(invalid)?;
+        Self::parse(path, &certificate).map_err(invalid)
+    }
+
+    fn parse(path: &str, certificate: &[u8]) -> Result<Self, CipherError> {
+        let mut outer = Reader::new(certificate).nested(der::SEQUENCE)?;
+        let tbs = outer.raw_element()?;
+        let signature_algorithm = outer.nested(der::SEQUENCE)?.raw_element()?.to_vec();
+        // The first byte of the BIT STRING counts its unused bits
+        let signature = outer.expect(der::BIT_STRING)?.get(1..).unwrap_or_default().to_vec();
+
+        let mut fields = Reader::new(tbs).nested(der::SEQUENCE)?;
+        if fields.peek() == Some(der::CONTEXT_0) {
+            fields.element()?;
+        }
+        fields.element()?;
+        fields.element()?;
+        let issuer = fields.raw_element()?.to_vec();
+        let mut validity = fields.nested(der::SEQUENCE)?;
+        let not_before = certificate_time(&mut validity)?;
+        let not_after = certificate_time(&mut validity)?;
+        let subject = fields.raw_element()?.to_vec();
+        let public_key = fields.raw_element()?.to_vec();
+
+        // Then optional unique IDs and the [3] extensions
+        let mut basic_constraints = None;
+        while !fields.is_empty() {
+            if fields.peek() == Some(der::CONTEXT_3) {
+                basic_constraints = basic_constraints_in(fields.nested(der::CONTEXT_3)?.nested(der::SEQUENCE)?)?;
+            } else {
+                fields.element()?;
+            }
+        }
+
+        Ok(ChainCertificate {
+            path: path.to_string(),
+            tbs: tbs.to_vec(),
+            signature_algorithm,
+            signature,
+            issuer,
+            subject,
+            not_before,
+            not_after,
+            public_key,
+            basic_constraints,
+        })
+    }
+}
+
+/// A UTCTime or GeneralizedTime of a Validity, in Unix seconds
+fn certificate_time(validity: &mut Reader) -> Result<u64, CipherError> {
+    let (_, time) = validity.element()?;
+    std::str::from_utf8(time)
+        .ok()
+        .and_then(parse_asn1_time)
+        .ok_or_else(|| CipherError::InvalidInput(String::from("malformed certificate validity")))
+}
+
+/// cA and pathLenConstraint of the basicConstraints in a list of extensions
+fn basic_constraints_in(mut extensions: Reader) -> Result<Option<(bool, Option<u64>)>, CipherError> {
+    let basic_constraints = der::encode_oid(BASIC_CONSTRAINTS).unwrap_or_default();
+    while !extensions.is_empty() {
+        let mut extension = extensions.nested(der::SEQUENCE)?;
+        if extension.raw_element()? != basic_constraints {
+            continue;
+        }
+        if extension.peek() == Some(der::BOOLEAN) {
+            extension.element()?;
+        }
+        let value = extension.expect(der::OCTET_STRING)?;
+        let mut constraints = Reader::new(value).nested(der::SEQUENCE)?;
+        let mut ca = false;
+        if constraints.peek() == Some(der::BOOLEAN) {
+            ca = constraints.expect(der::BOOLEAN)?.iter().any(|&byte| byte != 0);
+        }
+        let mut path_len = None;
+        if constraints.peek() == Some(der::INTEGER) {
+            let bytes = constraints.expect(der::INTEGER)?;
+            path_len = Some(bytes.iter().fold(0u64, |value, &byte| value.saturating_mul(256).saturating_add(u64::from(byte))));
+        }
+        return Ok(Some((ca, path_len)));
+    }
+    Ok(None)
+}
+
+/// Whether the basicConstraints of the certificate `depth` places above the
+/// user certificate allow it there: the user certificate must not be a CA,
+/// and every issuer must be one whose path length covers the CA
+/// certificates between it and the user
+fn constraints_fit(basic_constraints: Option<(bool, Option<u64>)>, depth: usize) -> bool {
+    match (depth, basic_constraints) {
+        (0, constraints) => !matches!(constraints, Some((true, _))),
+        (depth, Some((true, path_len))) => path_len.is_none_or(|path_len| path_len >= depth as u64 - 1),
+        _ => false,
+    }
+}
+
+impl PKIConfig {
+    /// Check a user's certificate against the CA (and any intermediates in
+    /// `ca_dir/intermediates`) link by link: each signature with the key
+    /// of the certificate above it, each validity window against now, and
+    /// each basicConstraints against its place in the chain.
+    ///
+    /// Unlike [`PKIConfig::check_certificate_chain`], which only reports
+    /// whether openssl accepts the chain, this says which check failed
+    /// where. A missing issuer ends the chain unanchored.
+    pub(crate) fn verify_certificate_chain(&self, username: &str) -> io::Result<ChainVerification> {
+        let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
+        let ca_cert_path = format!("{}/ca_certificate.pem", self.ca_dir);
+        if !Path::new(&user_cert_path).exists() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, tr!("Certificate for user {} not found", username)));
+        }
+        if !Path::new(&ca_cert_path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                tr!("CA certificate not found; initialize the PKI first")
+            ));
+        }
+
+        // Intermediates first, so one sharing the root's name is not skipped
+        let mut issuers = self
+            .intermediate_certificates()?
+            .iter()
+            .map(|path| ChainCertificate::read(path))
+            .collect::<io::Result<Vec<_>>>()?;
+        issuers.push(ChainCertificate::read(&ca_cert_path)?);
+        let root = issuers.len() - 1;
+
+        let leaf = ChainCertificate::read(&user_cert_path)?;
+        let scratch_path = format!("{}/{}_chain", self.users_dir, username);
+        let now = unix_now();
+        let mut used = vec![false; issuers.len()];
+        let (mut certificate, mut position) = (&leaf, None);
+        let mut links = Vec::new();
+        let anchored = loop {
+            let at_root = position == Some(root);
+            let issuer = if at_root {
+                Some(root)
+            } else {
+                (0..issuers.len()).find(|&index| !used[index] && issuers[index].subject == certificate.issuer)
+            };
+            let signature_valid = match issuer {
+                Some(index) => self.signed_by(certificate, &issuers[index], &scratch_path)?,
+                None => false,
+            };
+            let name = |name: &[u8]| subject_from_name(name).unwrap_or_default();
+            links.push(ChainLink {
+                path: certificate.path.clone(),
+                subject: name(&certificate.subject),
+                issuer: name(&certificate.issuer),
+                not_before: certificate.not_before,
+                not_after: certificate.not_after,
+                signature_valid,
+                within_validity: (certificate.not_before..=certificate.not_after).contains(&now),
+                constraints_valid: constraints_fit(certificate.basic_constraints, links.len()),
+            });
+
+            match issuer {
+                Some(index) if !at_root => {
+                    used[index] = true;
+                    certificate = &issuers[index];
+                    position = Some(index);
+                }
+                _ => break at_root,
+            }
+        };
+
+        Ok(ChainVerification { links, anchored })
+    }
+
+    /// Whether the key of `issuer` made the signature on `certificate`,
+    /// checked by `openssl pkeyutl` over files at `scratch_path` that are
+    /// removed afterwards
+    fn signed_by(&self, certificate: &ChainCertificate, issuer: &ChainCertificate, scratch_path: &str) -> io::Result<bool> {
+        let digest = if der::encode_oid(ED25519_OID).as_deref() == Some(&certificate.signature_algorithm[..]) {
+            None
+        } else {
+            match Digest::from_signature_algorithm(&certificate.signature_algorithm) {
+                Some(digest) => Some(digest),
+                None => return Ok(false),
+            }
+        };
+
+        let tbs_path = format!("{}.tbs", scratch_path);
+        let signature_path = format!("{}.sig", scratch_path);
+        let key_path = format!("{}.pubkey.pem", scratch_path);
+        let result = fs::write(&tbs_path, &certificate.tbs)
+            .and_then(|_| fs::write(&signature_path, &certificate.signature))
+            .and_then(|_| fs::write(&key_path, der::pem_encode("PUBLIC KEY", &issuer.public_key)))
+            .and_then(|_| {
+                let mut command = Command::new("openssl");
+                command.args(["pkeyutl", "-verify", "-pubin", "-inkey", &key_path, "-rawin"]);
+                command.args(["-in", &tbs_path, "-sigfile", &signature_path]);
+                if let Some(digest) = digest {
+                    command.args(["-digest", digest.name()]);
+                }
+                command.run(self.runner())
+            });
+        for path in [&tbs_path, &signature_path, &key_path] {
+            let _ = fs::remove_file(path);
+        }
+
+        Ok(result?.status.success())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constraints_depend_on_the_place_in_the_chain() {
+        // The user certificate
+        assert!(constraints_fit(None, 0));
+        assert!(constraints_fit(Some((false, None)), 0));
+        assert!(!constraints_fit(Some((true, None)), 0));
+
+        // Issuers
+        assert!(constraints_fit(Some((true, None)), 1));
+        assert!(constraints_fit(Some((true, Some(0))), 1));
+        assert!(!constraints_fit(Some((true, Some(0))), 2));
+        assert!(constraints_fit(Some((true, Some(1))), 2));
+        assert!(!constraints_fit(Some((false, None)), 1));
+        assert!(!constraints_fit(None, 1));
+    }
+
+    #[test]
+    fn certificates_give_their_links_and_constraints() {
+        let name = |cn: &str| der::sequence(&[der::encode(der::SET, &der::sequence(&[
+            der::encode_oid("2.5.4.3").unwrap(),
+            der::encode(der::UTF8_STRING, cn.as_bytes()),
+        ]))]);
+        let basic_constraints = der::sequence(&[
+            der::encode_oid(BASIC_CONSTRAINTS).unwrap(),
+            der::encode(der::BOOLEAN, &[0xff]),
+            der::encode(der::OCTET_STRING, &der::sequence(&[
+                der::encode(der::BOOLEAN, &[0xff]),
+                der::encode(der::INTEGER, &[1]),
+            ])),
+        ]);
+        let algorithm = der::sequence(&[der::encode_oid("1.2.840.113549.1.1.11").unwrap()]);
+        let tbs = der::sequence(&[
+            der::encode(der::CONTEXT_0, &der::encode(der::INTEGER, &[2])),
+            der::encode(der::INTEGER, &[7]),
+            algorithm.clone(),
+            name("Root"),
+            der::sequence(&[
+                der::encode(der::UTC_TIME, b"260101000000Z"),
+                der::encode(der::GENERALIZED_TIME, b"20360101000000Z"),
+            ]),
+            name("Issuing CA"),
+            der::sequence(&[algorithm.clone(), der::encode(der::BIT_STRING, &[0, 1])]),
+            der::encode(der::CONTEXT_3, &der::sequence(&[basic_constraints])),
+        ]);
+        let certificate = der::sequence(&[tbs.clone(), algorithm, der::encode(der::BIT_STRING, &[0, 0xAB, 0xCD])]);
+
+        let parsed = ChainCertificate::parse("ca.pem", &certificate).unwrap();
+        assert_eq!(parsed.tbs, tbs);
+        assert_eq!(parsed.signature, [0xAB, 0xCD]);
+        assert_eq!(parsed.issuer, name("Root"));
+        assert_eq!(subject_from_name(&parsed.subject).unwrap(), "/CN=Issuing CA");
+        assert_eq!(parsed.not_before, parse_asn1_time("260101000000Z").unwrap());
+        assert_eq!(parsed.not_after, parse_asn1_time("20360101000000Z").unwrap());
+        assert_eq!(parsed.basic_constraints, Some((true, Some(1))));
+        assert_eq!(Digest::from_signature_algorithm(&parsed.signature_algorithm), Some(Digest::Sha256));
+    }
+}
@@ -0,0 +1,648 @@
+//! The `pki` command line: global flags, then one subcommand. Values with
+//! their own parsers (profiles, formats, subjects) stay strings here and
+//! are checked where they are used, so their errors are translated.
+
+use clap::builder::RangedU64ValueParser;
+use clap::{ArgAction, Args, Parser, Subcommand};
+
+#[derive(Parser)]
+#[command(name = "pki", about = "A small PKI around OpenSSL: a CA, user certificates and signed documents")]
+#[command(subcommand_required = true, arg_required_else_help = true)]
+pub(crate) struct Cli {
+    #[command(flatten)]
+    pub(crate) global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub(crate) command: PkiCommand,
+}
+
+/// Flags every subcommand takes, before or after its name
+#[derive(Args, Default)]
+pub(crate) struct GlobalArgs {
+    /// Override a setting for this run, e.g. `--set user_validity_days=10`
+    #[arg(long = "set", value_name = "KEY=VALUE", global = true)]
+    pub(crate) assignments: Vec<String>,
+
+    /// Log more; repeat for debug and trace output
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    pub(crate) verbose: u8,
+
+    /// Log format (text or json)
+    #[arg(long, value_name = "FORMAT", global = true)]
+    pub(crate) log_format: Option<String>,
+
+    /// Message language (en or ro)
+    #[arg(long, value_name = "CODE", global = true)]
+    pub(crate) lang: Option<String>,
+
+    /// Print results as text or json
+    #[arg(long, value_name = "FORMAT", global = true)]
+    pub(crate) output: Option<String>,
+
+    /// Digest for signatures and certificates (sha256, sha384 or sha512)
+    #[arg(long, value_name = "NAME", global = true)]
+    pub(crate) digest: Option<String>,
+
+    /// Crypto backend (openssl or native)
+    #[arg(long, value_name = "NAME", global = true)]
+    pub(crate) backend: Option<String>,
+
+    /// Print what would be done without writing anything
+    #[arg(long, global = true)]
+    pub(crate) dry_run: bool,
+
+    /// Replace existing keys and certificates
+    #[arg(long, global = true)]
+    pub(crate) force: bool,
+
+    /// Certificate policy OID for issued certificates; repeatable
+    #[arg(long = "policy", value_name = "OID", global = true)]
+    pub(crate) policy_oids: Vec<String>,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum PkiCommand {
+    /// Create the CA key and self-signed certificate
+    Init,
+    /// Issue a key and certificate for a user
+    Issue {
+        /// Subject fields over `pki.user_subject`, e.g. `/CN=Ana/OU=Ops`
+        #[arg(long, value_name = "DN")]
+        subject: Option<String>,
+        #[arg(long, value_name = "client|server|email|codesign")]
+        profile: Option<String>,
+        /// Subject alternative name such as `DNS:<name>`; repeatable
+        #[arg(long = "san", value_name = "TYPE:VALUE")]
+        subject_alt_names: Vec<String>,
+        user: String,
+    },
+    /// Issue a user a new certificate, archiving the old one
+    Renew {
+        /// Replace the key as well
+        #[arg(long)]
+        rotate_key: bool,
+        user: String,
+    },
+    /// Revoke a user's certificate and update the CRL
+    Revoke { user: String },
+    /// Sign a document as a user into `<file>.sig`
+    Sign {
+        /// Timestamp the signature with this TSA
+        #[arg(long, value_name = "URL", conflicts_with = "embed")]
+        tsa: Option<String>,
+        /// Write a CMS container with the document inside, `<file>.p7m`
+        #[arg(long)]
+        embed: bool,
+        user: String,
+        file: String,
+    },
+    /// Add a user's signature to the co-signed container `<file>.p7s`
+    Cosign { user: String, file: String },
+    /// Verify `<user> <file>`, a co-signed `<file>` or a `<file>.p7m`
+    Verify {
+        /// CA certificate of the TSA that timestamped the signature
+        #[arg(long, value_name = "FILE")]
+        tsa_ca: Option<String>,
+        /// Signer a co-signed container must have; repeatable
+        #[arg(long = "require", value_name = "USER")]
+        required: Vec<String>,
+        #[arg(required = true, num_args = 1..=2, value_names = ["USER", "FILE"])]
+        args: Vec<String>,
+    },
+    /// Take the document out of a `<file>.p7m`
+    Extract {
+        container: String,
+        // A distinct id, or it would be the global `--output`
+        #[arg(id = "output_file", value_name = "OUTPUT")]
+        output: Option<String>,
+    },
+    /// Encrypt a file to users' certificates as CMS enveloped data
+    Encrypt {
+        #[arg(long = "for", value_name = "USER", required = true)]
+        recipients: Vec<String>,
+        #[arg(long, value_name = "FILE")]
+        out: Option<String>,
+        file: String,
+    },
+    /// Decrypt a `<file>.p7m` with a user's key
+    Decrypt {
+        #[arg(long, value_name = "FILE")]
+        out: Option<String>,
+        user: String,
+        file: String,
+    },
+    /// Sign a manifest of a binary, or verify one with `codesign verify`
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Codesign {
+        #[command(subcommand)]
+        action: Option<CodesignAction>,
+        #[arg(long, value_name = "URL")]
+        tsa: Option<String>,
+        #[arg(required = true)]
+        user: Option<String>,
+        #[arg(required = true)]
+        binary: Option<String>,
+    },
+    /// Sign a Merkle manifest of a directory
+    SignDir { user: String, dir: String },
+    /// Compare a directory with its signed manifest
+    VerifyDir { user: String, dir: String },
+    /// Issue a certificate for a CSR made elsewhere
+    SignCsr {
+        #[arg(long, value_name = "client|server|email|codesign")]
+        profile: Option<String>,
+        file: String,
+    },
+    /// Sign CSRs dropped into a directory, or manage the approval queue
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Watch {
+        #[command(subcommand)]
+        action: Option<WatchAction>,
+        /// Process the directory once and exit
+        #[arg(long)]
+        once: bool,
+        #[arg(long, value_name = "SECONDS", default_value_t = 5, value_parser = RangedU64ValueParser::<u64>::new().range(1..))]
+        interval: u64,
+        #[arg(long, value_name = "DIR")]
+        outbox: Option<String>,
+        /// Required subject, `*` matching anything, e.g. `/CN=*/O=Course`
+        #[arg(long = "subject", value_name = "PATTERN")]
+        subject_pattern: Option<String>,
+        #[arg(long, value_name = "BITS")]
+        min_key_bits: Option<u32>,
+        /// Queue acceptable CSRs for `pki watch approve`
+        #[arg(long = "approval")]
+        require_approval: bool,
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+        #[arg(required = true)]
+        incoming_dir: Option<String>,
+    },
+    /// Show a user's certificate
+    Inspect { user: String },
+    /// List issued certificates
+    List {
+        /// Warning window for certificates about to expire
+        #[arg(long, value_name = "DAYS", default_value_t = 30)]
+        days: u32,
+    },
+    /// List certificates expiring soon
+    Expiring {
+        #[arg(long, value_name = "DAYS", default_value_t = 30)]
+        days: u32,
+    },
+    /// Write the CA chain files
+    ExportChain,
+    /// Check a user's certificate chain link by link
+    VerifyChain { user: String },
+    /// Write an encrypted backup of the CA and user directories
+    Backup {
+        #[arg(long, value_name = "FILE", required = true)]
+        out: String,
+        /// Defaults to PKI_BACKUP_PASSPHRASE
+        #[arg(long)]
+        passphrase: Option<String>,
+    },
+    /// Restore a backup into the configured directories
+    Restore {
+        /// Defaults to PKI_BACKUP_PASSPHRASE
+        #[arg(long)]
+        passphrase: Option<String>,
+        backup: String,
+    },
+    /// Rotate, extend or split the CA
+    Ca {
+        #[command(subcommand)]
+        action: CaAction,
+    },
+    /// Show where the CA key is kept
+    CaKey,
+    /// Write a user's identity onto a YubiKey
+    Yubikey {
+        #[command(subcommand)]
+        action: YubikeyAction,
+    },
+    /// Sign SSH keys with the CA
+    Ssh {
+        #[command(subcommand)]
+        action: SshAction,
+    },
+    /// Export a Java truststore or a user's keystore
+    ExportJava {
+        #[command(subcommand)]
+        kind: KeystoreKind,
+        /// pkcs12 or jks
+        #[arg(long, value_name = "FORMAT", global = true)]
+        format: Option<String>,
+        #[arg(long, value_name = "FILE", global = true)]
+        out: Option<String>,
+        /// Defaults to PKI_KEYSTORE_PASSWORD
+        #[arg(long, value_name = "PASSWORD", global = true)]
+        storepass: Option<String>,
+    },
+    /// Serve enrollment, revocation and status over HTTP(S)
+    Serve {
+        #[arg(long, value_name = "ADDR:PORT")]
+        listen: Option<String>,
+        /// Bearer token for requests; defaults to PKI_API_TOKEN, else one is generated
+        #[arg(long)]
+        token: Option<String>,
+        #[arg(long, value_name = "FILE", requires = "tls_key")]
+        tls_cert: Option<String>,
+        #[arg(long, value_name = "FILE", requires = "tls_cert")]
+        tls_key: Option<String>,
+        /// Require client certificates issued by the CA
+        #[arg(long = "client-auth", requires = "tls_cert")]
+        require_client_cert: bool,
+        /// Port ACME challenges are fetched from on the identifier's host
+        #[arg(long, value_name = "PORT", default_value_t = 5002)]
+        challenge_port: u16,
+    },
+    /// Get a certificate from an ACME server
+    Acme {
+        #[command(subcommand)]
+        action: AcmeAction,
+    },
+    /// Write an HTML dashboard of the PKI
+    Report {
+        #[arg(long = "html", value_name = "DIR", required = true)]
+        output_dir: String,
+        #[arg(long, value_name = "DAYS", default_value_t = 30)]
+        days: u32,
+    },
+    /// Print Prometheus metrics, or write them for node_exporter
+    Metrics {
+        #[arg(long, value_name = "DAYS", default_value_t = 30)]
+        days: u32,
+        #[arg(long, value_name = "FILE")]
+        out: Option<String>,
+    },
+    /// Email users whose certificates expire soon
+    Notify {
+        #[arg(long, value_name = "HOST[:PORT]", required = true)]
+        smtp: String,
+        #[arg(long)]
+        starttls: bool,
+        #[arg(long, value_name = "FILE")]
+        smtp_ca: Option<String>,
+        #[arg(long, value_name = "ADDRESS")]
+        from: Option<String>,
+        /// Warning window, or `<profile>=<days>` for one profile; repeatable
+        #[arg(long = "days", value_name = "DAYS")]
+        windows: Vec<String>,
+        /// Email addresses of users, as in `pki user import`
+        #[arg(long, value_name = "FILE")]
+        csv: Option<String>,
+    },
+    /// Add the CA certificate to the system trust store, or take it out
+    Trust {
+        #[command(subcommand)]
+        action: TrustAction,
+    },
+    /// Set up an example PKI, or run a TLS handshake with it
+    Demo {
+        #[command(subcommand)]
+        kind: Option<DemoKind>,
+    },
+    /// Check OpenSSL and file permissions
+    Doctor {
+        /// Restrict private keys and the CA directory to their owner
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Provision users in bulk
+    User {
+        #[command(subcommand)]
+        action: UserAction,
+    },
+    /// Show the certificate transparency log, or prove entries in it
+    Log {
+        #[command(subcommand)]
+        action: Option<LogAction>,
+    },
+    /// Encrypt user keys under a master key
+    Keys {
+        #[command(subcommand)]
+        action: KeysAction,
+    },
+    /// DSA keys and signatures done by hand
+    Dsa {
+        #[command(subcommand)]
+        action: DsaAction,
+    },
+    /// A toy blockchain ledger signed with user keys
+    Chain {
+        #[command(subcommand)]
+        action: ChainAction,
+    },
+    /// Convert a certificate, CSR or CRL between PEM and DER
+    Convert {
+        /// pem or der; the other encoding by default
+        #[arg(long, value_name = "FORMAT")]
+        to: Option<String>,
+        input: String,
+        #[arg(id = "output_file", value_name = "OUTPUT")]
+        output: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum CodesignAction {
+    /// Check a binary against its signed manifest
+    Verify { user: String, binary: String },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum WatchAction {
+    /// List CSRs awaiting approval
+    List { incoming_dir: String },
+    /// Sign a queued CSR
+    Approve {
+        #[arg(long, value_name = "DIR")]
+        outbox: Option<String>,
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+        incoming_dir: String,
+        file: String,
+    },
+    /// Move a queued CSR to `rejected/`
+    Reject {
+        #[arg(long, value_name = "TEXT", default_value = "rejected by the CA operator")]
+        reason: String,
+        incoming_dir: String,
+        file: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum CaAction {
+    /// Replace the CA key and certificate, archiving the old ones
+    Rotate {
+        /// Also cross-sign the new CA certificate with the old key
+        #[arg(long)]
+        cross_sign: bool,
+        /// List users for renewal instead of re-issuing their certificates
+        #[arg(long)]
+        mark_renewal: bool,
+    },
+    /// Create an intermediate CA
+    Intermediate {
+        /// Permitted name subtree; repeatable
+        #[arg(long = "permit", value_name = "NAME")]
+        permitted: Vec<String>,
+        /// Excluded name subtree; repeatable
+        #[arg(long = "exclude", value_name = "NAME")]
+        excluded: Vec<String>,
+        name: String,
+    },
+    /// Split the CA key into Shamir shares
+    Split {
+        #[arg(long, value_name = "K", required = true, value_parser = clap::value_parser!(u8).range(1..))]
+        threshold: u8,
+        #[arg(long = "shares", value_name = "N", required = true, value_parser = clap::value_parser!(u8).range(1..))]
+        count: u8,
+        /// Remove the key file once the shares are written
+        #[arg(long)]
+        remove_key: bool,
+    },
+    /// Restore the CA key from shares
+    Combine {
+        #[arg(required = true, value_name = "SHARE.JSON")]
+        shares: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum YubikeyAction {
+    /// Import a user's key and certificate, or generate the key on the device
+    Provision {
+        #[arg(long, value_name = "9a|9c|9d|9e", default_value = "9a")]
+        slot: String,
+        #[arg(long)]
+        on_device: bool,
+        user: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum SshAction {
+    /// Certify an SSH public key
+    SignKey {
+        #[arg(long = "principal", value_name = "NAME", required = true)]
+        principals: Vec<String>,
+        /// Key identity; the key file's name by default
+        #[arg(long, value_name = "ID")]
+        identity: Option<String>,
+        /// ssh-keygen validity such as `+52w`; the user validity by default
+        #[arg(long, value_name = "INTERVAL")]
+        validity: Option<String>,
+        public_key: String,
+    },
+    /// Print the SSH CA public key
+    CaPubkey,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum KeystoreKind {
+    /// The CA certificate as a truststore
+    Truststore,
+    /// A user's key and certificate chain
+    Identity { user: String },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum AcmeAction {
+    /// Order a certificate for a user over HTTP-01
+    Enroll {
+        #[arg(long, value_name = "URL", required = true)]
+        server: String,
+        /// CA certificate of the ACME server; the PKI's own by default
+        #[arg(long, value_name = "FILE")]
+        ca_cert: Option<String>,
+        /// Bearer token of the server; defaults to PKI_API_TOKEN
+        #[arg(long)]
+        token: Option<String>,
+        #[arg(long, value_name = "PORT", default_value_t = 5002)]
+        challenge_port: u16,
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+        #[arg(long = "san", value_name = "DNS:<user>")]
+        subject_alt_names: Vec<String>,
+        /// Replace a certificate the server already issued to the user
+        #[arg(long)]
+        renew: bool,
+        user: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum TrustAction {
+    Install,
+    Uninstall,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum DemoKind {
+    /// Serve HTTPS with a fresh certificate and connect to it
+    Tls {
+        /// Require and present a client certificate
+        #[arg(long)]
+        mtls: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum UserAction {
+    /// Provision every user in a CSV file
+    Import {
+        #[arg(long, value_name = "WORKERS", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        jobs: Option<usize>,
+        csv: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum LogAction {
+    /// Write an inclusion proof for a user's certificate
+    Prove { user: String },
+    /// Check a user's certificate is in the log
+    Verify { user: String, proof: Option<String> },
+    /// Show the log still extends an older tree head
+    Consistency {
+        old_size: usize,
+        /// Root hash remembered for `old_size`, in hex
+        old_root: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum KeysAction {
+    /// Encrypt user keys under a new master key
+    Protect {
+        /// Defaults to PKI_MASTER_PASSPHRASE
+        #[arg(long)]
+        passphrase: Option<String>,
+    },
+    /// Decrypt user keys and remove the master key
+    Unprotect {
+        /// Defaults to PKI_MASTER_PASSPHRASE
+        #[arg(long)]
+        passphrase: Option<String>,
+    },
+    /// Re-wrap the master key under a new passphrase
+    ChangePassphrase {
+        /// Defaults to PKI_MASTER_PASSPHRASE
+        #[arg(long)]
+        passphrase: Option<String>,
+        /// Defaults to PKI_NEW_MASTER_PASSPHRASE
+        #[arg(long)]
+        new_passphrase: Option<String>,
+    },
+}
+
+/// DSA domain sizes; FIPS 186-4's (2048, 256) by default
+#[derive(Args)]
+pub(crate) struct DsaSizes {
+    /// Bits of p
+    #[arg(long = "bits", value_name = "L", default_value_t = 2048)]
+    pub(crate) l_bits: u64,
+    /// Bits of q
+    #[arg(long = "qbits", value_name = "N", default_value_t = 256)]
+    pub(crate) n_bits: u64,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum DsaAction {
+    /// Generate domain parameters and a key for a user
+    Keygen {
+        #[command(flatten)]
+        sizes: DsaSizes,
+        user: String,
+    },
+    Sign { user: String, file: String },
+    Verify { user: String, file: String },
+    /// Recover a key from two signatures that reused k
+    Attack {
+        #[command(subcommand)]
+        kind: DsaAttack,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum DsaAttack {
+    RepeatedK {
+        #[command(flatten)]
+        sizes: DsaSizes,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum ChainAction {
+    /// Start a ledger with a genesis block
+    Init {
+        /// Leading zero bits a block hash needs
+        #[arg(long, value_name = "BITS", default_value_t = 16, value_parser = clap::value_parser!(u32).range(0..=64))]
+        difficulty: u32,
+    },
+    /// Queue a signed transfer
+    Send {
+        from: String,
+        to: String,
+        #[arg(value_parser = clap::value_parser!(u64).range(1..))]
+        amount: u64,
+    },
+    /// Mine the queued transfers into a block
+    Mine { miner: String },
+    /// Print every block, then validate the ledger
+    Show,
+    Validate,
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn command_line_is_consistent() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn codesign_and_watch_take_an_action_or_their_own_arguments() {
+        let parse = |args: &[&str]| Cli::try_parse_from(["pki"].iter().chain(args)).map(|cli| cli.command);
+
+        assert!(matches!(
+            parse(&["codesign", "verify", "ana", "tool"]),
+            Ok(PkiCommand::Codesign { action: Some(CodesignAction::Verify { .. }), .. })
+        ));
+        assert!(matches!(
+            parse(&["codesign", "--tsa", "http://tsa", "ana", "tool"]),
+            Ok(PkiCommand::Codesign { action: None, user: Some(_), binary: Some(_), .. })
+        ));
+        assert!(parse(&["codesign", "ana"]).is_err());
+
+        assert!(matches!(parse(&["watch", "list", "incoming"]), Ok(PkiCommand::Watch { action: Some(WatchAction::List { .. }), .. })));
+        assert!(matches!(parse(&["watch", "--once", "incoming"]), Ok(PkiCommand::Watch { action: None, once: true, .. })));
+        assert!(parse(&["watch", "--interval", "0", "incoming"]).is_err());
+    }
+
+    #[test]
+    fn global_flags_go_before_or_after_the_subcommand() {
+        let cli = Cli::try_parse_from(["pki", "--set", "a=1", "issue", "--force", "ana", "--output", "json"]).unwrap();
+        assert_eq!(cli.global.assignments, ["a=1"]);
+        assert!(cli.global.force);
+        assert_eq!(cli.global.output.as_deref(), Some("json"));
+
+        let cli = Cli::try_parse_from(["pki", "-vv", "verify", "ana", "notes.txt"]).unwrap();
+        assert_eq!(cli.global.verbose, 2);
+        assert!(matches!(cli.command, PkiCommand::Verify { args, .. } if args == ["ana", "notes.txt"]));
+        assert!(Cli::try_parse_from(["pki", "verify", "a", "b", "c"]).is_err());
+
+        // A positional output file is not the global --output
+        let cli = Cli::try_parse_from(["pki", "--output", "json", "convert", "a.der", "a.pem"]).unwrap();
+        assert_eq!(cli.global.output.as_deref(), Some("json"));
+        assert!(matches!(cli.command, PkiCommand::Convert { output: Some(output), .. } if output == "a.pem"));
+    }
+}
@@ -0,0 +1,152 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+
+use crypto_core::tr;
+use rustls::pki_types::{PrivateKeyDer, ServerName};
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+
+/// Response from [`http_request`]
+pub(crate) struct HttpResponse {
+    pub(crate) status: u16,
+    pub(crate) body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub(crate) fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).trim().to_string()
+    }
+
+    /// Turn non-2xx responses into errors carrying the server's message
+    pub(crate) fn error_for_status(self) -> io::Result<Self> {
+        if (200..300).contains(&self.status) {
+            Ok(self)
+        } else {
+            Err(io::Error::other(tr!("Server returned {}: {}", self.status, self.text())))
+        }
+    }
+}
+
+/// Send a single HTTP/1.1 request to `base_url` (`http://host:port` or
+/// `https://host:port`). HTTPS servers are verified against `ca_cert_path`.
+pub(crate) fn http_request(
+    base_url: &str,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+    ca_cert_path: &str,
+) -> io::Result<HttpResponse> {
+    let (tls, authority) = match base_url.split_once("://") {
+        Some(("https", authority)) => (true, authority),
+        Some(("http", authority)) => (false, authority),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("Unsupported server URL {}", base_url)
+            ));
+        }
+    };
+    let authority = authority.trim_end_matches('/');
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse::<u16>().map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid port in {}", base_url))
+        })?),
+        None => (authority, if tls { 443 } else { 80 }),
+    };
+
+    let stream = TcpStream::connect((host, port))?;
+
+    let mut request = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        method, path, authority, body.len()
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+
+    if tls {
+        exchange(tls_connect(host, stream, ca_cert_path, None)?, request.as_bytes(), body)
+    } else {
+        exchange(stream, request.as_bytes(), body)
+    }
+}
+
+/// Start TLS on a connected stream, verifying the server as `host` against
+/// the certificates in `ca_cert_path`. `client_identity` holds the
+/// certificate file and key to authenticate with, if any.
+pub(crate) fn tls_connect(
+    host: &str,
+    stream: TcpStream,
+    ca_cert_path: &str,
+    client_identity: Option<(&str, PrivateKeyDer<'static>)>,
+) -> io::Result<StreamOwned<ClientConnection, TcpStream>> {
+    let mut roots = RootCertStore::empty();
+    for cert in rustls_pemfile::certs(&mut BufReader::new(File::open(ca_cert_path)?)) {
+        roots.add(cert?).map_err(io::Error::other)?;
+    }
+
+    let config = ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(io::Error::other)?
+        .with_root_certificates(roots);
+    let config = match client_identity {
+        Some((cert_path, key)) => {
+            let certs = rustls_pemfile::certs(&mut BufReader::new(File::open(cert_path)?))
+                .collect::<Result<Vec<_>, _>>()?;
+            config.with_client_auth_cert(certs, key).map_err(io::Error::other)?
+        }
+        None => config.with_no_client_auth(),
+    };
+    let server_name = ServerName::try_from(host.to_string()).map_err(io::Error::other)?;
+    let connection = ClientConnection::new(Arc::new(config), server_name).map_err(io::Error::other)?;
+
+    Ok(StreamOwned::new(connection, stream))
+}
+
+pub(crate) fn exchange(mut stream: impl Read + Write, head: &[u8], body: &[u8]) -> io::Result<HttpResponse> {
+    stream.write_all(head)?;
+    stream.write_all(body)?;
+    stream.flush()?;
+
+    let mut reader = BufReader::new(stream);
+    let mut status_line = String::new();
+    reader.read_line(&mut status_line)?;
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, tr!("Malformed HTTP status line")))?;
+
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let mut body = Vec::new();
+    match content_length {
+        Some(length) => {
+            body.resize(length, 0);
+            reader.read_exact(&mut body)?;
+        }
+        None => {
+            reader.read_to_end(&mut body)?;
+        }
+    }
+
+    Ok(HttpResponse { status, body })
+}
@@ -0,0 +1,161 @@
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+
+use crypto_core::codec::encode_hex;
+use crypto_core::tr;
+use ring::digest::Context;
+
+use crate::PKIConfig;
+use crate::digest::Digest;
+use crate::exec;
+use crate::verification::VerificationReport;
+
+/// Signed description of an executable, stored as `<binary>.codesign`
+/// with its detached signature in `<binary>.codesign.sig`
+pub(crate) struct CodeManifest {
+    /// File name of the executable, without its directory
+    pub(crate) file: String,
+    pub(crate) size: u64,
+    pub(crate) digest: Digest,
+    /// Hex digest of the executable
+    pub(crate) hash: String,
+    pub(crate) signer: String,
+    /// Serial of the signer's certificate, upper-case hex
+    pub(crate) serial: String,
+}
+
+impl CodeManifest {
+    fn to_text(&self) -> String {
+        format!(
+            "pki-codesign-manifest: 1\nfile: {}\nsize: {}\ndigest: {}\nhash: {}\nsigner: {}\nserial: {}\n",
+            self.file, self.size, self.digest.name(), self.hash, self.signer, self.serial
+        )
+    }
+
+    fn parse(text: &str) -> io::Result<Self> {
+        let field = |name: &str| {
+            text.lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix(": "))
+                .map(str::to_string)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, tr!("Code signing manifest lacks {}", name)))
+        };
+
+        if field("pki-codesign-manifest")? != "1" {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, tr!("Unsupported code signing manifest version")));
+        }
+        Ok(CodeManifest {
+            file: field("file")?,
+            size: field("size")?
+                .parse()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, tr!("Invalid size in code signing manifest")))?,
+            digest: Digest::parse(&field("digest")?)?,
+            hash: field("hash")?,
+            signer: field("signer")?,
+            serial: field("serial")?,
+        })
+    }
+}
+
+/// Everything `pki codesign verify` checks
+pub(crate) struct CodeSignReport {
+    pub(crate) manifest: CodeManifest,
+    /// Signature, chain, validity, timestamp and revocation of the manifest
+    pub(crate) signature: VerificationReport,
+    /// The signer's certificate allows code signing
+    pub(crate) code_signing_usage: bool,
+    /// The manifest names this executable and the verifying user
+    pub(crate) manifest_matches: bool,
+    /// Size and hash of the executable match the manifest
+    pub(crate) binary_matches: bool,
+}
+
+impl CodeSignReport {
+    pub(crate) fn is_valid(&self) -> bool {
+        self.signature.is_valid() && self.code_signing_usage && self.manifest_matches && self.binary_matches
+    }
+}
+
+impl PKIConfig {
+    /// Hash an executable, write its manifest and sign the manifest with a
+    /// code-signing certificate. Returns the manifest path.
+    pub(crate) fn codesign(&self, username: &str, binary_path: &str) -> io::Result<String> {
+        let signer = self.inspect_certificate(username, 0)?;
+        if !has_code_signing_usage(signer.extended_key_usage.as_deref()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("The certificate of {} does not allow code signing; issue it with the codesign profile", username)
+            ));
+        }
+
+        let digest = self.signing_digest();
+        let (size, hash) = hash_file(binary_path, digest)?;
+        let manifest = CodeManifest {
+            file: file_name(binary_path),
+            size,
+            digest,
+            hash,
+            signer: username.to_string(),
+            serial: signer.info.serial,
+        };
+
+        let manifest_path = format!("{}.codesign", binary_path);
+        exec::write(&manifest_path, manifest.to_text())?;
+        self.sign_document(username, &manifest_path)?;
+
+        Ok(manifest_path)
+    }
+
+    /// Check an executable against its signed manifest
+    pub(crate) fn verify_codesign(&self, username: &str, binary_path: &str) -> io::Result<CodeSignReport> {
+        let manifest_path = format!("{}.codesign", binary_path);
+        if !Path::new(&manifest_path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                tr!("Code signing manifest {} not found", manifest_path)
+            ));
+        }
+
+        let signature = self.verify_document(username, &manifest_path)?;
+        let manifest = CodeManifest::parse(&fs::read_to_string(&manifest_path)?)?;
+        let (size, hash) = hash_file(binary_path, manifest.digest)?;
+
+        Ok(CodeSignReport {
+            code_signing_usage: has_code_signing_usage(signature.signer.extended_key_usage.as_deref()),
+            manifest_matches: manifest.file == file_name(binary_path)
+                && manifest.signer == username
+                && manifest.serial == signature.signer.info.serial,
+            binary_matches: manifest.size == size && manifest.hash.eq_ignore_ascii_case(&hash),
+            manifest,
+            signature,
+        })
+    }
+}
+
+/// Whether OpenSSL's rendering of an extended key usage includes code signing
+fn has_code_signing_usage(extended_key_usage: Option<&str>) -> bool {
+    extended_key_usage.is_some_and(|usage| usage.contains("Code Signing"))
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path).file_name().unwrap_or_default().to_string_lossy().into_owned()
+}
+
+/// Size and hex digest of a file, read in chunks
+fn hash_file(path: &str, digest: Digest) -> io::Result<(u64, String)> {
+    let mut file = File::open(path)?;
+    let mut context = Context::new(digest.algorithm());
+    let mut buffer = [0; 64 * 1024];
+    let mut size = 0;
+
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        context.update(&buffer[..read]);
+        size += read as u64;
+    }
+
+    Ok((size, encode_hex(context.finish().as_ref())))
+}
@@ -0,0 +1,198 @@
+//! DER copies of the PEM files the PKI makes (`pki.artifact_format = der`)
+//! and `pki convert` between the two encodings. PEM stays the working copy
+//! every command reads.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crypto_core::der::{self, Reader};
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::exec;
+use crate::permissions::create_private_file;
+
+/// Encoding of certificates, keys, requests and CRLs on disk
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum ArtifactFormat {
+    #[default]
+    Pem,
+    Der,
+}
+
+impl ArtifactFormat {
+    pub(crate) fn parse(name: &str) -> io::Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "pem" => Ok(Self::Pem),
+            "der" => Ok(Self::Der),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("Unknown format {} (expected pem or der)", name)
+            )),
+        }
+    }
+
+    /// Also the file extension
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Pem => "pem",
+            Self::Der => "der",
+        }
+    }
+}
+
+impl PKIConfig {
+    /// With DER artifacts configured, write a DER copy of a PEM file the
+    /// PKI has just made next to it (`alice_certificate.pem` gets
+    /// `alice_certificate.der`). Never called for private keys: a second,
+    /// unprotected copy would outlive `pki keys protect`
+    pub(crate) fn export_artifact(&self, pem_path: &str) -> io::Result<()> {
+        if self.artifact_format == ArtifactFormat::Pem {
+            return Ok(());
+        }
+        let der_path = with_extension(pem_path, ArtifactFormat::Der);
+        if exec::is_dry_run() {
+            exec::plan(&format!("write {}", der_path));
+            return Ok(());
+        }
+        convert_file(pem_path, &der_path, ArtifactFormat::Der).map(|_| ())
+    }
+}
+
+/// `path` with the extension of `format` in place of its own
+pub(crate) fn with_extension(path: &str, format: ArtifactFormat) -> String {
+    Path::new(path).with_extension(format.name()).to_string_lossy().into_owned()
+}
+
+/// The encoding `input` is in: PEM when it is text with a BEGIN line
+pub(crate) fn detect_format(input: &[u8]) -> ArtifactFormat {
+    match std::str::from_utf8(input) {
+        Ok(text) if text.contains("-----BEGIN ") => ArtifactFormat::Pem,
+        _ => ArtifactFormat::Der,
+    }
+}
+
+/// Write the certificate, key, request or CRL in `input` to `output` in
+/// `format`. Private keys get an owner-only file. Returns the PEM label of
+/// what was converted.
+pub(crate) fn convert_file(input: &str, output: &str, format: ArtifactFormat) -> io::Result<String> {
+    let bytes = fs::read(input)?;
+    let (label, der) = match detect_format(&bytes) {
+        ArtifactFormat::Pem => {
+            let text = String::from_utf8_lossy(&bytes);
+            der::pem_decode(&text).map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, tr!("Cannot convert {}: {}", input, e))
+            })?
+        }
+        ArtifactFormat::Der => {
+            let label = der_label(&bytes).ok_or_else(|| io::Error::new(
+                io::ErrorKind::InvalidData,
+                tr!("{} is not a DER certificate, key, request or CRL", input)
+            ))?;
+            (label.to_string(), bytes)
+        }
+    };
+
+    if label.contains("PRIVATE KEY") {
+        create_private_file(output)?;
+    }
+    match format {
+        ArtifactFormat::Pem => exec::write(output, der::pem_encode(&label, &der))?,
+        ArtifactFormat::Der => exec::write(output, &der)?,
+    }
+    Ok(label)
+}
+
+/// The PEM label for a DER structure, told apart by its first elements:
+///
+/// - certificate: `{ { [0] version, ... }, algorithm, signature }`
+/// - CRL: `{ { version, algorithm, issuer, ... }, algorithm, signature }`
+/// - request: `{ { version, subject, key, [0] attributes }, algorithm, signature }`
+/// - PKCS#8 key: `{ version, algorithm, OCTET STRING }`
+/// - encrypted PKCS#8 key: `{ algorithm, OCTET STRING }`
+fn der_label(der: &[u8]) -> Option<&'static str> {
+    let mut outer = Reader::new(der);
+    let mut top = outer.nested(der::SEQUENCE).ok()?;
+    if !outer.is_empty() {
+        return None;
+    }
+
+    match top.peek()? {
+        der::INTEGER => {
+            top.integer().ok()?;
+            top.nested(der::SEQUENCE).ok()?;
+            top.expect(der::OCTET_STRING).ok()?;
+            Some("PRIVATE KEY")
+        }
+        der::SEQUENCE => {
+            let mut first = top.nested(der::SEQUENCE).ok()?;
+            if top.peek()? == der::OCTET_STRING {
+                return Some("ENCRYPTED PRIVATE KEY");
+            }
+            match first.peek()? {
+                der::CONTEXT_0 => Some("CERTIFICATE"),
+                // A v1 CRL has no version and starts with the algorithm
+                der::SEQUENCE => {
+                    first.expect(der::SEQUENCE).ok()?;
+                    label_after_algorithm(&mut first)
+                }
+                der::INTEGER => {
+                    first.integer().ok()?;
+                    // The algorithm of a CRL or a v1 certificate, or the
+                    // subject Name of a request
+                    match first.nested(der::SEQUENCE).ok()?.peek() {
+                        Some(der::OBJECT_IDENTIFIER) => label_after_algorithm(&mut first),
+                        _ => Some("CERTIFICATE REQUEST"),
+                    }
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Tell a CRL from a certificate once `tbs` is past the signature
+/// algorithm: both go on with the issuer Name, after which a CRL has its
+/// thisUpdate Time and a certificate its Validity SEQUENCE
+fn label_after_algorithm(tbs: &mut Reader) -> Option<&'static str> {
+    tbs.expect(der::SEQUENCE).ok()?;
+    match tbs.peek()? {
+        der::UTC_TIME | der::GENERALIZED_TIME => Some("X509 CRL"),
+        der::SEQUENCE => Some("CERTIFICATE"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_follow_the_der_structure() {
+        let algorithm = der::sequence(&[der::encode_oid("1.2.840.113549.1.1.11").unwrap()]);
+        let signed = |tbs: Vec<u8>| der::sequence(&[tbs, algorithm.clone(), der::encode(der::BIT_STRING, &[0])]);
+        let integer = |value: u8| der::encode(der::INTEGER, &[value]);
+        let name = der::sequence(&[der::encode(der::SET, &[])]);
+        let time = der::encode(der::UTC_TIME, b"260101000000Z");
+        let validity = der::sequence(&[time.clone(), time.clone()]);
+
+        let certificate = signed(der::sequence(&[der::encode(der::CONTEXT_0, &integer(2)), integer(1)]));
+        let v1_certificate = signed(der::sequence(&[integer(1), algorithm.clone(), name.clone(), validity, name.clone()]));
+        let crl = signed(der::sequence(&[integer(1), algorithm.clone(), name.clone(), time.clone()]));
+        let v1_crl = signed(der::sequence(&[algorithm.clone(), name.clone(), time]));
+        let request = signed(der::sequence(&[integer(0), name.clone()]));
+        let key = der::sequence(&[integer(0), algorithm.clone(), der::encode(der::OCTET_STRING, &[1])]);
+        let encrypted_key = der::sequence(&[algorithm.clone(), der::encode(der::OCTET_STRING, &[1])]);
+
+        assert_eq!(der_label(&certificate), Some("CERTIFICATE"));
+        assert_eq!(der_label(&v1_certificate), Some("CERTIFICATE"));
+        assert_eq!(der_label(&crl), Some("X509 CRL"));
+        assert_eq!(der_label(&v1_crl), Some("X509 CRL"));
+        assert_eq!(der_label(&request), Some("CERTIFICATE REQUEST"));
+        assert_eq!(der_label(&key), Some("PRIVATE KEY"));
+        assert_eq!(der_label(&encrypted_key), Some("ENCRYPTED PRIVATE KEY"));
+        assert_eq!(der_label(b"not DER"), None);
+    }
+}
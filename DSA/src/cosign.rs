@@ -0,0 +1,182 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use crypto_core::der::{self, Reader};
+use crypto_core::{tr, CipherError};
+
+use crate::PKIConfig;
+use crate::exec::{self, Execute};
+use crate::pem::common_name_from_subject;
+use crate::runner::CommandRunner;
+
+/// Result of verifying a multi-signer signature container
+pub(crate) struct CosignReport {
+    /// Whether every signature in the container verified against the CA
+    pub(crate) valid: bool,
+    /// Each SignerInfo in the container, in its (DER SET) order
+    pub(crate) signers: Vec<SignerStatus>,
+    /// Required signers without a signature that verified
+    pub(crate) missing: Vec<String>,
+}
+
+/// One signature in a container
+pub(crate) struct SignerStatus {
+    /// Common name of the signer's certificate
+    pub(crate) name: String,
+    /// Whether this signature and its certificate chain verified
+    pub(crate) valid: bool,
+}
+
+impl PKIConfig {
+    /// Add a user's signature to the document's shared signature container
+    pub(crate) fn cosign_document(&self, username: &str, document_path: &str) -> io::Result<()> {
+        let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
+        let container_path = format!("{}.p7s", document_path);
+        let pending_path = format!("{}.tmp", container_path);
+
+        let output = if Path::new(&container_path).exists() {
+            // Append another SignerInfo to the existing container
+            self.unlock_user_keys(
+                Command::new("openssl")
+                    .args([
+                        "cms", "-resign", "-binary",
+                        "-inform", "PEM",
+                        "-in", &container_path,
+                        "-content", document_path,
+                        "-signer", &user_cert_path,
+                        "-md", self.signing_digest().name(),
+                        "-outform", "PEM",
+                        "-out", &pending_path
+                    ])
+                    .args(self.user_key_args("-inkey", username))
+            )?
+            .execute(self.runner())?
+        } else {
+            self.unlock_user_keys(
+                Command::new("openssl")
+                    .args([
+                        "cms", "-sign", "-binary",
+                        "-in", document_path,
+                        "-signer", &user_cert_path,
+                        "-md", self.signing_digest().name(),
+                        "-outform", "PEM",
+                        "-out", &pending_path
+                    ])
+                    .args(self.user_key_args("-inkey", username))
+            )?
+            .execute(self.runner())?
+        };
+
+        if !output.status.success() {
+            let _ = fs::remove_file(&pending_path);
+            return Err(io::Error::other(tr!("Failed to co-sign document for user {}", username)));
+        }
+
+        exec::rename(&pending_path, &container_path)?;
+
+        Ok(())
+    }
+
+    /// Verify each signature in a document's container on its own and
+    /// check that every required signer has one that verified
+    pub(crate) fn verify_cosigned_document(&self, document_path: &str, required: &[String]) -> io::Result<CosignReport> {
+        let container_path = format!("{}.p7s", document_path);
+
+        if !Path::new(&container_path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                tr!("Signature container {} not found", container_path)
+            ));
+        }
+
+        let unreadable = || io::Error::new(io::ErrorKind::InvalidData, tr!("Failed to read signers from {}", container_path));
+        let (label, container) = der::pem_decode(&fs::read_to_string(&container_path)?).map_err(|_| unreadable())?;
+        let single_signer_containers = split_signer_infos(&container).map_err(|_| unreadable())?;
+
+        let mut signers = Vec::new();
+        for (index, single) in single_signer_containers.iter().enumerate() {
+            let single_path = format!("{}.signer{}.pem", container_path, index);
+            fs::write(&single_path, der::pem_encode(&label, single))?;
+            let status = self.verify_single_signer(document_path, &single_path);
+            fs::remove_file(&single_path)?;
+            signers.push(status?);
+        }
+
+        let valid = !signers.is_empty() && signers.iter().all(|signer| signer.valid);
+        let missing = required
+            .iter()
+            .filter(|user| !signers.iter().any(|signer| signer.valid && signer.name == **user))
+            .cloned()
+            .collect();
+
+        Ok(CosignReport { valid, signers, missing })
+    }
+
+    /// Verify a container holding one SignerInfo against the CA and name
+    /// its signer, whose certificate is read without checks if it fails
+    fn verify_single_signer(&self, document_path: &str, container_path: &str) -> io::Result<SignerStatus> {
+        let ca_cert_path = format!("{}/ca_certificate.pem", self.ca_dir);
+        let signer_path = format!("{}.signer.pem", container_path);
+        let null_device = if cfg!(windows) { "NUL" } else { "/dev/null" };
+
+        let verify = |checks: &[&str]| {
+            Command::new("openssl")
+                .args(["cms", "-verify", "-binary", "-inform", "PEM"])
+                .args(checks)
+                .args(["-in", container_path, "-content", document_path, "-signer", &signer_path, "-out", null_device])
+                .run(self.runner())
+        };
+
+        let valid = verify(&["-CAfile", &ca_cert_path, "-purpose", "any"])?.status.success();
+        if !valid && !verify(&["-noverify", "-nosigs"])?.status.success() {
+            return Err(io::Error::other(tr!("Failed to read signers from {}", container_path)));
+        }
+
+        let signer_pem = fs::read_to_string(&signer_path)?;
+        fs::remove_file(&signer_path)?;
+        let name = certificate_common_name(self.runner(), &signer_pem)?.unwrap_or_default();
+
+        Ok(SignerStatus { name, valid })
+    }
+}
+
+/// A CMS SignedData container once for each of its SignerInfos, each copy
+/// carrying that signer alone so the signatures can be verified one by one
+fn split_signer_infos(content_info: &[u8]) -> Result<Vec<Vec<u8>>, CipherError> {
+    let mut content_info = Reader::new(content_info).nested(der::SEQUENCE)?;
+    let content_type = content_info.raw_element()?.to_vec();
+    let mut signed_data = content_info.nested(der::CONTEXT_0)?.nested(der::SEQUENCE)?;
+
+    // Version, digest algorithms, content and certificates stay as they are;
+    // the SignerInfos come last
+    let mut fields = Vec::new();
+    while !signed_data.is_empty() {
+        fields.push(signed_data.raw_element()?.to_vec());
+    }
+    let signer_infos = fields.pop().unwrap_or_default();
+    let mut signer_infos = Reader::new(&signer_infos).nested(der::SET)?;
+
+    let mut containers = Vec::new();
+    while !signer_infos.is_empty() {
+        let mut single = fields.clone();
+        single.push(der::encode(der::SET, signer_infos.raw_element()?));
+        containers.push(der::sequence(&[content_type.clone(), der::encode(der::CONTEXT_0, &der::sequence(&single))]));
+    }
+    Ok(containers)
+}
+
+/// Extract the subject CN of a PEM certificate
+pub(crate) fn certificate_common_name(runner: &dyn CommandRunner, pem: &str) -> io::Result<Option<String>> {
+    let output = runner.output_with_input(
+        Command::new("openssl").args(["x509", "-noout", "-subject", "-nameopt", "RFC2253"]),
+        pem.as_bytes()
+    )?;
+
+    if !output.status.success() {
+        return Err(io::Error::other(tr!("Failed to read certificate subject")));
+    }
+
+    Ok(common_name_from_subject(&String::from_utf8_lossy(&output.stdout)))
+}
@@ -0,0 +1,228 @@
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::pem::{common_name_from_subject, rfc2253_fields};
+use crate::exec::{self, Execute};
+use crate::inventory::CertificateStatus;
+use crate::profile::CertificateProfile;
+use crate::provision::is_valid_username;
+use crate::san;
+use crate::watch::matches_pattern;
+
+/// What to do when an external CSR is for a user who already has a certificate
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExistingCertificate {
+    /// Ask first, or require `--force`, as for a CSR named on the command line
+    Confirm,
+    /// Refuse while it is still valid; an expired or revoked one is replaced
+    Keep,
+    /// Replace it, as the requester asked to renew
+    Renew,
+}
+
+/// Which subject alternative names an external CSR may ask for
+#[derive(Clone, Copy)]
+pub(crate) enum SanPolicy<'a> {
+    /// Those matching `pki.csr_san_patterns`
+    Configured,
+    /// Only these, as for an ACME order validated for one identifier
+    Only(&'a [String]),
+}
+
+impl PKIConfig {
+    /// Check an externally generated CSR against the issuance policy.
+    ///
+    /// The CSR must carry a valid self-signature, a CN usable as a username,
+    /// no other subject fields than those `pki.user_subject` sets (with the
+    /// same values) and an RSA key of at least `user_key_bits`. Returns the CN.
+    pub(crate) fn check_csr_policy(&self, csr_path: &str) -> io::Result<String> {
+        self.check_csr_policy_with_key_bits(csr_path, self.user_key_bits)
+    }
+
+    /// [`PKIConfig::check_csr_policy`] with an explicit minimum RSA key size
+    pub(crate) fn check_csr_policy_with_key_bits(&self, csr_path: &str, min_key_bits: u32) -> io::Result<String> {
+        let output = Command::new("openssl")
+            .args(["req", "-verify", "-noout", "-in", csr_path])
+            .run(self.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                tr!("CSR {} has an invalid signature", csr_path)
+            ));
+        }
+
+        let output = Command::new("openssl")
+            .args(["req", "-noout", "-subject", "-nameopt", "RFC2253", "-in", csr_path])
+            .run(self.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to read subject of CSR {}", csr_path)));
+        }
+
+        let subject = String::from_utf8_lossy(&output.stdout);
+        let Some(username) = common_name_from_subject(&subject) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                tr!("CSR {} has no common name", csr_path)
+            ));
+        };
+        if !is_valid_username(&username) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                tr!("CSR common name {} is not a valid username", username)
+            ));
+        }
+        // The certificate keeps the CSR's subject, so only the CN is the
+        // requester's to choose
+        for (kind, value) in rfc2253_fields(subject.trim().trim_start_matches("subject=")).unwrap_or_default() {
+            let permitted = match kind.as_str() {
+                "CN" => value == username,
+                _ => self.user_subject.field(&kind) == Some(value.as_str()),
+            };
+            if !permitted {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    tr!("CSR {} has subject field {}={}, which the configured user subject does not allow", csr_path, kind, value)
+                ));
+            }
+        }
+
+        let output = Command::new("openssl")
+            .args(["req", "-noout", "-text", "-in", csr_path])
+            .run(self.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to read public key of CSR {}", csr_path)));
+        }
+
+        let text = String::from_utf8_lossy(&output.stdout);
+        let key_bits = text
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("Public-Key: ("))
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|bits| bits.parse::<u32>().ok());
+
+        if text.contains("rsaEncryption") && key_bits.is_some_and(|bits| bits < min_key_bits) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                tr!("CSR key is smaller than the required {} bits", min_key_bits)
+            ));
+        }
+
+        Ok(username)
+    }
+
+    /// Subject of a CSR in the `/CN=alice/O=Course` form taken by `-subj`
+    pub(crate) fn csr_subject(&self, csr_path: &str) -> io::Result<String> {
+        let output = Command::new("openssl")
+            .args(["req", "-noout", "-subject", "-nameopt", "compat", "-in", csr_path])
+            .run(self.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to read subject of CSR {}", csr_path)));
+        }
+
+        let subject = String::from_utf8_lossy(&output.stdout);
+        Ok(subject.trim().trim_start_matches("subject=").trim().to_string())
+    }
+
+    /// The subject alternative names an external CSR requests, once each
+    /// is checked against `policy`. A CSR asking for any name the policy
+    /// does not allow is refused rather than issued without it.
+    pub(crate) fn permitted_subject_alt_names(&self, csr_path: &str, policy: SanPolicy) -> io::Result<Vec<String>> {
+        let requested = san::requested_subject_alt_names(csr_path)?;
+        for name in &requested {
+            let permitted = match policy {
+                SanPolicy::Configured => self.csr_san_patterns.iter().any(|pattern| matches_pattern(pattern, name)),
+                SanPolicy::Only(allowed) => allowed.contains(name),
+            };
+            if !permitted {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    tr!("CSR {} requests subject alternative name {}, which the policy does not allow", csr_path, name)
+                ));
+            }
+        }
+        Ok(requested)
+    }
+
+    /// Issue a certificate for a CSR that was generated off the CA machine
+    pub(crate) fn sign_external_csr(
+        &self,
+        csr_path: &str,
+        profile: Option<CertificateProfile>,
+        san_policy: SanPolicy,
+        existing: ExistingCertificate,
+    ) -> io::Result<String> {
+        let username = self.check_csr_policy(csr_path)?;
+        let subject_alt_names = self.permitted_subject_alt_names(csr_path, san_policy)?;
+        self.issue_for_external_csr(csr_path, &username, profile, &subject_alt_names, existing)?;
+
+        Ok(username)
+    }
+
+    /// Sign a checked external CSR for `username` with the names it was
+    /// allowed. Unless `existing` is [`ExistingCertificate::Confirm`], the
+    /// user's current certificate and CSR are archived up front (or the CSR
+    /// refused with `AlreadyExists`) and moved back if signing fails.
+    pub(crate) fn issue_for_external_csr(
+        &self,
+        csr_path: &str,
+        username: &str,
+        profile: Option<CertificateProfile>,
+        subject_alt_names: &[String],
+        existing: ExistingCertificate,
+    ) -> io::Result<()> {
+        let user_csr_path = format!("{}/{}_csr.pem", self.users_dir, username);
+        let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
+
+        // Copying a file onto itself would truncate it
+        let same_file = Path::new(csr_path).canonicalize().ok() == Path::new(&user_csr_path).canonicalize().ok();
+        let mut replaced = vec![user_cert_path.as_str()];
+        if !same_file {
+            replaced.push(&user_csr_path);
+        }
+
+        let archive = if existing != ExistingCertificate::Confirm && Path::new(&user_cert_path).exists() {
+            self.check_replaceable(username, existing)?;
+            let archived: Vec<&str> = replaced.iter().copied().filter(|path| Path::new(path).exists()).collect();
+            Some((self.archive_files(&archived)?, archived))
+        } else {
+            None
+        };
+
+        let issued = (|| {
+            if !same_file {
+                exec::copy(csr_path, &user_csr_path)?;
+            }
+            self.sign_user_certificate_with_extensions(username, profile, subject_alt_names)
+        })();
+        if let (Err(_), Some((archive_dir, archived))) = (&issued, &archive) {
+            self.restore_archived(archive_dir, &replaced, archived)?;
+        }
+        issued
+    }
+
+    /// Refuse to replace a user's certificate that is still valid, unless
+    /// `existing` asks to renew it
+    pub(crate) fn check_replaceable(&self, username: &str, existing: ExistingCertificate) -> io::Result<()> {
+        let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
+        if existing == ExistingCertificate::Renew || !Path::new(&user_cert_path).exists() {
+            return Ok(());
+        }
+
+        let status = self.inspect_certificate(username, 0)?.info.status;
+        if matches!(status, CertificateStatus::Valid | CertificateStatus::Expiring) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                tr!("{} already holds a valid certificate; ask to renew it to replace it", username)
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+use crypto_core::codec::encode_hex;
+use crypto_core::rng;
+
+use crate::PKIConfig;
+use crate::exec;
+use crate::inventory::parse_asn1_time;
+
+/// Serial number length recommended by the CA/Browser Forum baseline requirements
+const SERIAL_BYTES: usize = 20;
+
+/// One certificate in the issuance database
+pub(crate) struct IssuedCertificate {
+    pub(crate) revoked: bool,
+    /// Revocation time in Unix seconds
+    pub(crate) revoked_at: Option<u64>,
+    /// Serial number in upper-case hex
+    pub(crate) serial: String,
+}
+
+/// A new line of the issuance database
+pub(crate) struct IssuanceEntry {
+    /// Serial number in upper-case hex
+    pub(crate) serial: String,
+    /// Subject in OpenSSL's `compat` form, `/CN=.../O=...`
+    pub(crate) subject: String,
+    /// Expiry as `openssl ca` stores it, e.g. `270101120000Z`
+    pub(crate) expiry: String,
+}
+
+impl PKIConfig {
+    /// Issuance database, kept in the `index.txt` format of `openssl ca` so
+    /// revocation and CRL generation can use it directly
+    pub(crate) fn database_path(&self) -> String {
+        format!("{}/index.txt", self.ca_dir)
+    }
+
+    /// Write the minimal `openssl ca` configuration pointing at the
+    /// issuance database and return its path
+    pub(crate) fn ca_config(&self) -> io::Result<String> {
+        let config_path = format!("{}/ca.cnf", self.ca_dir);
+        let database_path = self.database_path();
+
+        if !Path::new(&database_path).exists() {
+            exec::write(&database_path, "")?;
+        }
+
+        exec::write(&config_path, format!(
+            "[ ca ]\ndefault_ca = pki_ca\n\n\
+             [ pki_ca ]\ndatabase = {}\nunique_subject = no\ndefault_md = {}\ndefault_crl_days = 30\n",
+            database_path,
+            self.signing_digest().name()
+        ))?;
+
+        Ok(config_path)
+    }
+
+    /// Every certificate recorded in the issuance database
+    pub(crate) fn issued_certificates(&self) -> io::Result<Vec<IssuedCertificate>> {
+        let database_path = self.database_path();
+        if !Path::new(&database_path).exists() {
+            return Ok(Vec::new());
+        }
+
+        Ok(fs::read_to_string(database_path)?
+            .lines()
+            .filter_map(|line| {
+                // status, expiry, revocation date, serial, file name, subject
+                let fields: Vec<&str> = line.split('\t').collect();
+                let [status, _, revocation, serial, _, _] = fields.as_slice() else {
+                    return None;
+                };
+                Some(IssuedCertificate {
+                    revoked: *status == "R",
+                    // May carry a reason after a comma
+                    revoked_at: revocation.split(',').next().and_then(parse_asn1_time),
+                    serial: serial.to_ascii_uppercase(),
+                })
+            })
+            .collect())
+    }
+
+    /// A fresh positive 160-bit serial that is not yet in the database
+    pub(crate) fn new_serial(&self) -> io::Result<String> {
+        let issued = self.issued_certificates()?;
+
+        loop {
+            let mut bytes = rng::bytes(SERIAL_BYTES)?;
+
+            // Clear the top bit so the DER INTEGER stays positive in 20 bytes
+            bytes[0] &= 0x7f;
+            let serial = encode_hex(&bytes).to_ascii_uppercase();
+
+            if bytes[0] != 0 && !issued.iter().any(|certificate| certificate.serial == serial) {
+                return Ok(serial);
+            }
+        }
+    }
+
+    /// Append a freshly issued certificate to the issuance database
+    pub(crate) fn record_issuance(&self, cert_path: &str) -> io::Result<()> {
+        if exec::is_dry_run() {
+            exec::plan(&format!("record {} in {} and {}", cert_path, self.database_path(), self.transparency_log_path()));
+            return Ok(());
+        }
+
+        let entry = self.backend.issuance_entry(self, cert_path)?;
+        self.ca_config()?;
+        let mut database = OpenOptions::new().append(true).open(self.database_path())?;
+        writeln!(database, "V\t{}\t\t{}\tunknown\t{}", entry.expiry, entry.serial, entry.subject)?;
+
+        self.append_to_log(cert_path, &entry.serial, &entry.subject)
+    }
+}
+
+/// Convert `YYYY-MM-DD HH:MM:SSZ` into the ASN.1 time `openssl ca` stores:
+/// UTCTime before 2050, GeneralizedTime after
+pub(crate) fn asn1_time(iso_time: &str) -> Option<String> {
+    let digits: String = iso_time.chars().filter(char::is_ascii_digit).collect();
+    if digits.len() != 14 {
+        return None;
+    }
+
+    let year: u32 = digits[..4].parse().ok()?;
+    Some(if year < 2050 {
+        format!("{}Z", &digits[2..])
+    } else {
+        format!("{}Z", digits)
+    })
+}
@@ -0,0 +1,162 @@
+use std::io;
+use std::process::Command;
+
+use crypto_core::{der, tr};
+
+use crate::PKIConfig;
+use crate::exec::{self, Execute};
+use crate::keyalgorithm::KeyAlgorithm;
+
+/// Message digest used for certificate, CRL and document signatures
+/// (`--digest sha256|sha384|sha512`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum Digest {
+    #[default]
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl Digest {
+    pub(crate) fn parse(name: &str) -> io::Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "sha256" => Ok(Self::Sha256),
+            "sha384" => Ok(Self::Sha384),
+            "sha512" => Ok(Self::Sha512),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("Unknown digest {} (expected sha256, sha384 or sha512)", name)
+            )),
+        }
+    }
+
+    /// Name as understood by openssl, e.g. `sha384`
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::Sha384 => "sha384",
+            Self::Sha512 => "sha512",
+        }
+    }
+
+    /// The same algorithm in `ring`, for hashing files in-process
+    pub(crate) fn algorithm(&self) -> &'static ring::digest::Algorithm {
+        match self {
+            Self::Sha256 => &ring::digest::SHA256,
+            Self::Sha384 => &ring::digest::SHA384,
+            Self::Sha512 => &ring::digest::SHA512,
+        }
+    }
+
+    /// Option selecting the digest in `openssl dgst`, `x509` and `req`
+    pub(crate) fn flag(&self) -> String {
+        format!("-{}", self.name())
+    }
+
+    /// Object identifier of the hash, as a DigestInfo names it
+    fn oid(&self) -> &'static str {
+        match self {
+            Self::Sha256 => "2.16.840.1.101.3.4.2.1",
+            Self::Sha384 => "2.16.840.1.101.3.4.2.2",
+            Self::Sha512 => "2.16.840.1.101.3.4.2.3",
+        }
+    }
+
+    /// Object identifier of PKCS#1 v1.5 RSA signatures with this hash
+    /// (`sha256WithRSAEncryption`, ...) in certificates and requests
+    pub(crate) fn rsa_signature_oid(&self) -> &'static str {
+        match self {
+            Self::Sha256 => "1.2.840.113549.1.1.11",
+            Self::Sha384 => "1.2.840.113549.1.1.12",
+            Self::Sha512 => "1.2.840.113549.1.1.13",
+        }
+    }
+
+    /// Object identifier of ECDSA signatures with this hash
+    /// (`ecdsa-with-SHA256`, ...)
+    fn ecdsa_signature_oid(&self) -> &'static str {
+        match self {
+            Self::Sha256 => "1.2.840.10045.4.3.2",
+            Self::Sha384 => "1.2.840.10045.4.3.3",
+            Self::Sha512 => "1.2.840.10045.4.3.4",
+        }
+    }
+
+    /// The digest of an RSA or ECDSA signature algorithm, given its
+    /// encoded object identifier
+    pub(crate) fn from_signature_algorithm(oid: &[u8]) -> Option<Self> {
+        [Self::Sha256, Self::Sha384, Self::Sha512].into_iter().find(|digest| {
+            [digest.rsa_signature_oid(), digest.ecdsa_signature_oid()]
+                .iter()
+                .any(|dotted| der::encode_oid(dotted).as_deref() == Some(oid))
+        })
+    }
+
+    /// DigestInfo { { hash OID, NULL }, hash }, what a PKCS#1 v1.5 signature covers
+    pub(crate) fn digest_info(&self, hash: &[u8]) -> Vec<u8> {
+        let algorithm = der::sequence(&[der::encode_oid(self.oid()).unwrap_or_default(), der::encode(der::NULL, &[])]);
+        der::sequence(&[algorithm, der::encode(der::OCTET_STRING, hash)])
+    }
+
+    /// Smallest RSA modulus, in bits, that fits a PKCS#1 v1.5 signature
+    /// with this digest: the DigestInfo (19 bytes of header plus the hash)
+    /// and at least 11 bytes of padding
+    fn min_rsa_bits(&self) -> u32 {
+        let hash_bytes = match self {
+            Self::Sha256 => 32,
+            Self::Sha384 => 48,
+            Self::Sha512 => 64,
+        };
+        (19 + hash_bytes + 11) * 8
+    }
+
+    /// Refuse an RSA key too small to sign with this digest
+    pub(crate) fn check_rsa_key(&self, key_bits: u32) -> io::Result<()> {
+        if key_bits < self.min_rsa_bits() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("A {}-bit RSA key is too small to sign with {}", key_bits, self.name())
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl PKIConfig {
+    /// Digest for new signatures: the one chosen with `--digest`, SHA-256 otherwise
+    pub(crate) fn signing_digest(&self) -> Digest {
+        self.digest.unwrap_or_default()
+    }
+
+    /// Check that the key selected by `key_args` (arguments for
+    /// `openssl pkey`, e.g. `-in <file>`) can sign with the chosen digest,
+    /// returning the type of the key when it is one the PKI makes
+    pub(crate) fn check_digest_for_key(&self, key_args: &[String]) -> io::Result<Option<KeyAlgorithm>> {
+        // Keys planned in a dry run do not exist yet
+        if exec::is_dry_run() {
+            return Ok(None);
+        }
+
+        let digest = self.signing_digest();
+        let output = self
+            .unlock_user_keys(Command::new("openssl").args(["pkey", "-noout", "-text_pub"]).args(key_args))?
+            .run(self.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to read the signing key")));
+        }
+
+        let text = String::from_utf8_lossy(&output.stdout);
+        let algorithm = KeyAlgorithm::from_key_text(&text);
+        let key_bits = text
+            .lines()
+            .find_map(|line| line.strip_prefix("Public-Key: ("))
+            .and_then(|rest| rest.split(' ').next())
+            .and_then(|bits| bits.parse::<u32>().ok());
+
+        if let (Some(KeyAlgorithm::Rsa), Some(key_bits)) = (algorithm, key_bits) {
+            digest.check_rsa_key(key_bits)?;
+        }
+        Ok(algorithm)
+    }
+}
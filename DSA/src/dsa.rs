@@ -0,0 +1,120 @@
+use std::fs;
+use std::io;
+
+use crypto_core::dsa::{self, DsaParameters, DsaPrivateKey, DsaSignature};
+use crypto_core::primes::random_range;
+use crypto_core::tr;
+use num_bigint::BigUint;
+use num_traits::One;
+use serde_json::{json, Value};
+
+use crate::PKIConfig;
+use crate::exec;
+use crate::permissions;
+
+/// What `pki dsa attack repeated-k` showed
+pub(crate) struct RepeatedNonceReport {
+    pub(crate) messages: [String; 2],
+    pub(crate) signatures: [DsaSignature; 2],
+    pub(crate) recovered_x: Option<BigUint>,
+    pub(crate) actual_x: BigUint,
+}
+
+impl PKIConfig {
+    /// Key written by `pki dsa keygen`, next to the user's openssl key
+    pub(crate) fn dsa_key_path(&self, username: &str) -> String {
+        format!("{}/{}_dsa_key.json", self.users_dir, username)
+    }
+
+    /// Generate DSA parameters of the given sizes and a key in them, with
+    /// every step done here rather than by openssl
+    pub(crate) fn generate_dsa_key(&self, username: &str, l_bits: u64, n_bits: u64) -> io::Result<DsaPrivateKey> {
+        let key_path = self.dsa_key_path(username);
+        self.init_pki_structure()?;
+        self.replace_existing(&[&key_path])?;
+
+        let key = DsaPrivateKey::generate(l_bits, n_bits)?;
+        permissions::create_private_file(&key_path)?;
+        let json = json!({
+            "p": key.parameters.p.to_str_radix(16),
+            "q": key.parameters.q.to_str_radix(16),
+            "g": key.parameters.g.to_str_radix(16),
+            "x": key.x.to_str_radix(16),
+            "y": key.y.to_str_radix(16),
+        });
+        exec::write(&key_path, format!("{:#}\n", json))?;
+
+        Ok(key)
+    }
+
+    pub(crate) fn read_dsa_key(&self, username: &str) -> io::Result<DsaPrivateKey> {
+        let key_path = self.dsa_key_path(username);
+        let json = read_json(&key_path)?;
+        let number = |field: &str| hex_field(&json, field, &key_path);
+        Ok(DsaPrivateKey {
+            parameters: DsaParameters { p: number("p")?, q: number("q")?, g: number("g")? },
+            x: number("x")?,
+            y: number("y")?,
+        })
+    }
+
+    /// Sign a document with the user's DSA key into `<file>.dsa.sig`
+    pub(crate) fn dsa_sign_document(&self, username: &str, document_path: &str) -> io::Result<String> {
+        let signature = self.read_dsa_key(username)?.sign(&fs::read(document_path)?)?;
+        let signature_path = format!("{}.dsa.sig", document_path);
+        let json = json!({ "r": signature.r.to_str_radix(16), "s": signature.s.to_str_radix(16) });
+        exec::write(&signature_path, format!("{:#}\n", json))?;
+        Ok(signature_path)
+    }
+
+    /// Check `<file>.dsa.sig` against the user's DSA public key
+    pub(crate) fn dsa_verify_document(&self, username: &str, document_path: &str) -> io::Result<bool> {
+        let public = self.read_dsa_key(username)?.public();
+        let signature_path = format!("{}.dsa.sig", document_path);
+        let json = read_json(&signature_path)?;
+        let signature = DsaSignature {
+            r: hex_field(&json, "r", &signature_path)?,
+            s: hex_field(&json, "s", &signature_path)?,
+        };
+        Ok(public.verify(&fs::read(document_path)?, &signature))
+    }
+}
+
+/// Sign two messages with the same k and recover the private key from the
+/// two signatures alone
+pub(crate) fn repeated_nonce_demo(l_bits: u64, n_bits: u64) -> io::Result<RepeatedNonceReport> {
+    let key = DsaPrivateKey::generate(l_bits, n_bits)?;
+    let messages = [String::from("Transfer 10 lei to Alice"), String::from("Transfer 20 lei to Bob")];
+    loop {
+        let k = random_range(&BigUint::one(), &key.parameters.q)?;
+        let (Some(first), Some(second)) =
+            (key.sign_with_nonce(messages[0].as_bytes(), &k), key.sign_with_nonce(messages[1].as_bytes(), &k))
+        else {
+            continue;
+        };
+
+        let recovered = dsa::recover_key_from_repeated_nonce(
+            &key.public(),
+            (messages[0].as_bytes(), &first),
+            (messages[1].as_bytes(), &second),
+        );
+        return Ok(RepeatedNonceReport {
+            messages,
+            signatures: [first, second],
+            recovered_x: recovered.map(|recovered| recovered.x),
+            actual_x: key.x,
+        });
+    }
+}
+
+fn read_json(path: &str) -> io::Result<Value> {
+    serde_json::from_str(&fs::read_to_string(path)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, tr!("{} is not valid JSON: {}", path, e)))
+}
+
+fn hex_field(json: &Value, field: &str, path: &str) -> io::Result<BigUint> {
+    json[field]
+        .as_str()
+        .and_then(|hex| BigUint::parse_bytes(hex.as_bytes(), 16))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, tr!("{} has no valid {}", path, field)))
+}
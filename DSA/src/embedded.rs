@@ -0,0 +1,153 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::cosign::certificate_common_name;
+use crate::pem::split_certificates;
+use crate::exec::Execute;
+
+/// Result of verifying a document with an embedded signature
+pub(crate) struct EmbeddedReport {
+    /// Whether the signature and the signer's chain verified against the CA
+    pub(crate) valid: bool,
+    /// Common names of the users who signed the document
+    pub(crate) signers: Vec<String>,
+}
+
+/// Default path of the document packaged in `container_path`: the same
+/// path without its `.p7m` extension
+pub(crate) fn embedded_document_path(container_path: &str) -> io::Result<String> {
+    container_path
+        .strip_suffix(".p7m")
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| io::Error::new(
+            io::ErrorKind::InvalidInput,
+            tr!("{} is not a .p7m file; name the output file explicitly", container_path)
+        ))
+}
+
+impl PKIConfig {
+    /// Package the document and the user's signature together in `<file>.p7m`
+    pub(crate) fn sign_document_embedded(&self, username: &str, document_path: &str) -> io::Result<String> {
+        let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
+        let container_path = format!("{}.p7m", document_path);
+
+        let output = self
+            .unlock_user_keys(
+                Command::new("openssl")
+                    .args([
+                        "cms", "-sign", "-binary", "-nodetach",
+                        "-in", document_path,
+                        "-signer", &user_cert_path,
+                        "-md", self.signing_digest().name(),
+                        "-outform", "PEM",
+                        "-out", &container_path
+                    ])
+                    .args(self.user_key_args("-inkey", username))
+            )?
+            .execute(self.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to sign document for user {}", username)));
+        }
+
+        Ok(container_path)
+    }
+
+    /// Verify a `.p7m` package without extracting the document
+    pub(crate) fn verify_embedded_document(&self, container_path: &str) -> io::Result<EmbeddedReport> {
+        let null_path = if cfg!(windows) { "NUL" } else { "/dev/null" };
+        self.open_embedded_document(container_path, null_path, |command| command.run(self.runner()))
+    }
+
+    /// Verify a `.p7m` package and write the original document to `output_path`
+    pub(crate) fn extract_document(&self, container_path: &str, output_path: &str) -> io::Result<EmbeddedReport> {
+        if Path::new(output_path).exists() && !self.force {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                tr!("{} already exists; rerun with --force to overwrite it", output_path)
+            ));
+        }
+
+        let report = self.open_embedded_document(container_path, output_path, |command| command.execute(self.runner()))?;
+        if !report.valid {
+            let _ = fs::remove_file(output_path);
+            return Err(io::Error::other(tr!(
+                "Signature in {} is not valid; the document was not extracted", container_path
+            )));
+        }
+
+        Ok(report)
+    }
+
+    /// Run `cms -verify` on a package, writing its content to `output_path`
+    /// through `run`, and collect the signers
+    fn open_embedded_document(
+        &self,
+        container_path: &str,
+        output_path: &str,
+        run: impl FnOnce(&mut Command) -> io::Result<Output>,
+    ) -> io::Result<EmbeddedReport> {
+        let ca_cert_path = format!("{}/ca_certificate.pem", self.ca_dir);
+        let signers_path = format!("{}.signers.pem", container_path);
+
+        if !Path::new(container_path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                tr!("Signed document {} not found", container_path)
+            ));
+        }
+
+        let mut command = Command::new("openssl");
+        command.args([
+            "cms", "-verify", "-binary",
+            "-inform", "PEM",
+            "-in", container_path,
+            "-CAfile", &ca_cert_path,
+            "-purpose", "any",
+            "-signer", &signers_path,
+            "-out", output_path
+        ]);
+        for intermediate in self.intermediate_certificates()? {
+            command.args(["-untrusted", &intermediate]);
+        }
+        let valid = run(&mut command)?.status.success();
+
+        // On failure, list the signers without checking signatures or chains
+        if !valid {
+            let output = Command::new("openssl")
+                .args([
+                    "cms", "-verify", "-binary",
+                    "-noverify", "-nosigs",
+                    "-inform", "PEM",
+                    "-in", container_path,
+                    "-signer", &signers_path,
+                    "-out", if cfg!(windows) { "NUL" } else { "/dev/null" }
+                ])
+                .run(self.runner())?;
+
+            if !output.status.success() {
+                return Err(io::Error::other(tr!("Failed to read signers from {}", container_path)));
+            }
+        }
+
+        let signer_pems = fs::read_to_string(&signers_path).unwrap_or_default();
+        let _ = fs::remove_file(&signers_path);
+
+        let mut signers = Vec::new();
+        for pem in split_certificates(&signer_pems) {
+            if let Some(common_name) = certificate_common_name(self.runner(), &pem)? {
+                if !signers.contains(&common_name) {
+                    signers.push(common_name);
+                }
+            }
+        }
+
+        Ok(EmbeddedReport { valid, signers })
+    }
+}
@@ -0,0 +1,74 @@
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::exec::Execute;
+
+impl PKIConfig {
+    /// Encrypt a file to one or more users' certificates (CMS enveloped data)
+    pub(crate) fn encrypt_for_users(&self, recipients: &[String], input_path: &str, output_path: &str) -> io::Result<()> {
+        if recipients.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("At least one recipient is required for encryption")
+            ));
+        }
+
+        let mut recipient_certs = Vec::new();
+        for username in recipients {
+            let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
+            if !Path::new(&user_cert_path).exists() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    tr!("Certificate for user {} not found", username)
+                ));
+            }
+            recipient_certs.push(user_cert_path);
+        }
+
+        let output = Command::new("openssl")
+            .args([
+                "cms", "-encrypt", "-binary",
+                "-aes-256-cbc",
+                "-in", input_path,
+                "-outform", "PEM",
+                "-out", output_path
+            ])
+            .args(&recipient_certs)
+            .execute(self.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to encrypt {}", input_path)));
+        }
+
+        Ok(())
+    }
+
+    /// Decrypt CMS enveloped data with a recipient's private key
+    pub(crate) fn decrypt_for_user(&self, username: &str, input_path: &str, output_path: &str) -> io::Result<()> {
+        let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
+
+        let output = self
+            .unlock_user_keys(
+                Command::new("openssl")
+                    .args([
+                        "cms", "-decrypt", "-binary",
+                        "-inform", "PEM",
+                        "-in", input_path,
+                        "-recip", &user_cert_path,
+                        "-out", output_path
+                    ])
+                    .args(self.user_key_args("-inkey", username))
+            )?
+            .execute(self.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to decrypt {} for user {}", input_path, username)));
+        }
+
+        Ok(())
+    }
+}
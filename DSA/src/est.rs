@@ -0,0 +1,65 @@
+use std::fs;
+use std::io;
+use std::process::Command;
+
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::csr::ExistingCertificate;
+use crate::exec::Execute;
+use crate::pem::csr_pem_from_base64;
+use crate::runner::CommandRunner;
+use crate::server::{Request, Response};
+
+/// Content type of EST certificate responses (RFC 7030 section 4.1.3)
+const PKCS7_CERTS_ONLY: &str = "application/pkcs7-mime; smime-type=certs-only";
+
+impl PKIConfig {
+    /// `GET /.well-known/est/cacerts`: the CA chain as base64 PKCS#7
+    pub(crate) fn est_cacerts(&self) -> io::Result<Response> {
+        let mut cert_paths = self.intermediate_certificates()?;
+        cert_paths.push(format!("{}/ca_certificate.pem", self.ca_dir));
+
+        let body = certs_only_base64(self.runner(), &cert_paths)?;
+        Ok(Response::new(200, PKCS7_CERTS_ONLY, body))
+    }
+
+    /// `POST /.well-known/est/simpleenroll`: base64 DER PKCS#10 in, base64
+    /// PKCS#7 holding the issued certificate out. Only `simplereenroll`
+    /// (`reenroll`) replaces a certificate that is still valid.
+    pub(crate) fn est_simple_enroll(&self, request: &Request, reenroll: bool) -> io::Result<Response> {
+        // Re-wrap the base64 DER as PEM so it goes through the regular CSR pipeline
+        let csr_pem = csr_pem_from_base64(&String::from_utf8_lossy(&request.body))?;
+
+        let existing = if reenroll { ExistingCertificate::Renew } else { ExistingCertificate::Keep };
+        let certificate = self.issue_from_csr_pem(csr_pem.as_bytes(), None, None, existing)?;
+
+        let issued_path = format!("{}/.est_issued.pem", self.users_dir);
+        fs::write(&issued_path, certificate)?;
+        let body = certs_only_base64(self.runner(), std::slice::from_ref(&issued_path));
+        fs::remove_file(&issued_path)?;
+
+        Ok(Response::new(200, PKCS7_CERTS_ONLY, body?))
+    }
+}
+
+/// Degenerate PKCS#7 (certs-only) of the given PEM files, base64 encoded
+fn certs_only_base64(runner: &dyn CommandRunner, cert_paths: &[String]) -> io::Result<String> {
+    let mut command = Command::new("openssl");
+    command.args(["crl2pkcs7", "-nocrl", "-outform", "PEM"]);
+    for path in cert_paths {
+        command.args(["-certfile", path]);
+    }
+    let output = command.run(runner)?;
+
+    if !output.status.success() {
+        return Err(io::Error::other(tr!("Failed to build PKCS#7 certificate bundle")));
+    }
+
+    // The PEM body is exactly the base64 EST wants
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .map(|line| format!("{}\r\n", line))
+        .collect())
+}
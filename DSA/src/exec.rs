@@ -0,0 +1,121 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::{Command, ExitStatus, Output};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::runner::CommandRunner;
+use crate::signer;
+
+/// Set by the global `--dry-run` flag
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn set_dry_run(enabled: bool) {
+    DRY_RUN.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn is_dry_run() -> bool {
+    DRY_RUN.load(Ordering::Relaxed)
+}
+
+/// Print a planned operation in dry-run mode
+pub(crate) fn plan(operation: &str) {
+    println!("[dry-run] {}", operation);
+}
+
+/// Runs a [`Command`] through a [`CommandRunner`]. Commands that change
+/// files use [`Execute::execute`] instead of [`Execute::run`] so `--dry-run`
+/// can intercept them.
+pub(crate) trait Execute {
+    /// Run a command that only reads state
+    fn run(&mut self, runner: &dyn CommandRunner) -> io::Result<Output>;
+
+    /// Run the command, or in dry-run mode print it and report success
+    /// with empty output
+    fn execute(&mut self, runner: &dyn CommandRunner) -> io::Result<Output>;
+}
+
+impl Execute for Command {
+    fn run(&mut self, runner: &dyn CommandRunner) -> io::Result<Output> {
+        signer::unlock_ca_key(self)?;
+        runner.output(self)
+    }
+
+    fn execute(&mut self, runner: &dyn CommandRunner) -> io::Result<Output> {
+        if is_dry_run() {
+            plan(&command_line(self));
+            return Ok(Output {
+                status: ExitStatus::default(),
+                stdout: Vec::new(),
+                stderr: Vec::new(),
+            });
+        }
+
+        signer::unlock_ca_key(self)?;
+        runner.output(self)
+    }
+}
+
+pub(crate) fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    if is_dry_run() {
+        plan(&format!("write {}", path.as_ref().display()));
+        return Ok(());
+    }
+    fs::write(path, contents)
+}
+
+pub(crate) fn remove_file(path: impl AsRef<Path>) -> io::Result<()> {
+    if is_dry_run() {
+        plan(&format!("remove {}", path.as_ref().display()));
+        return Ok(());
+    }
+    fs::remove_file(path)
+}
+
+pub(crate) fn remove_dir(path: impl AsRef<Path>) -> io::Result<()> {
+    if is_dry_run() {
+        plan(&format!("remove directory {}", path.as_ref().display()));
+        return Ok(());
+    }
+    fs::remove_dir(path)
+}
+
+pub(crate) fn create_dir_all(path: impl AsRef<Path>) -> io::Result<()> {
+    if is_dry_run() {
+        plan(&format!("create directory {}", path.as_ref().display()));
+        return Ok(());
+    }
+    fs::create_dir_all(path)
+}
+
+pub(crate) fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> io::Result<()> {
+    if is_dry_run() {
+        plan(&format!("rename {} to {}", from.as_ref().display(), to.as_ref().display()));
+        return Ok(());
+    }
+    fs::rename(from, to)
+}
+
+pub(crate) fn copy(from: impl AsRef<Path>, to: impl AsRef<Path>) -> io::Result<()> {
+    if is_dry_run() {
+        plan(&format!("copy {} to {}", from.as_ref().display(), to.as_ref().display()));
+        return Ok(());
+    }
+    fs::copy(from, to).map(|_| ())
+}
+
+/// Shell-style rendering of a command line
+pub(crate) fn command_line(command: &Command) -> String {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| {
+            let arg = arg.to_string_lossy();
+            if !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c)) {
+                arg.into_owned()
+            } else {
+                format!("'{}'", arg.replace('\'', "'\\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
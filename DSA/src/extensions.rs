@@ -0,0 +1,95 @@
+use std::io;
+
+use crypto_core::config::Config;
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::profile::CertificateProfile;
+
+/// Where relying parties fetch revocation data and the issuer certificate,
+/// embedded in issued certificates as CRL distribution point and
+/// authority information access extensions
+pub(crate) struct DistributionPoints {
+    /// `pki.crl_url`, e.g. the `/crl` endpoint of `pki serve`
+    pub(crate) crl_url: Option<String>,
+    /// `pki.ocsp_url`
+    pub(crate) ocsp_url: Option<String>,
+    /// `pki.ca_issuers_url`, e.g. the `/ca` endpoint of `pki serve`
+    pub(crate) ca_issuers_url: Option<String>,
+}
+
+impl DistributionPoints {
+    pub(crate) fn from_config(config: &Config) -> Self {
+        DistributionPoints {
+            crl_url: config.get("pki.crl_url").map(str::to_string),
+            ocsp_url: config.get("pki.ocsp_url").map(str::to_string),
+            ca_issuers_url: config.get("pki.ca_issuers_url").map(str::to_string),
+        }
+    }
+
+    /// Extension file lines for the configured URLs
+    fn extension_lines(&self) -> String {
+        let mut lines = String::new();
+
+        if let Some(url) = &self.crl_url {
+            lines.push_str(&format!("crlDistributionPoints = URI:{}\n", url));
+        }
+
+        let access: Vec<String> = [("OCSP", &self.ocsp_url), ("caIssuers", &self.ca_issuers_url)]
+            .into_iter()
+            .filter_map(|(method, url)| url.as_ref().map(|url| format!("{};URI:{}", method, url)))
+            .collect();
+        if !access.is_empty() {
+            lines.push_str(&format!("authorityInfoAccess = {}\n", access.join(", ")));
+        }
+
+        lines
+    }
+}
+
+/// Check that `oid` is a dotted object identifier such as `2.23.140.1.2.1`
+pub(crate) fn parse_policy_oid(oid: &str) -> io::Result<String> {
+    let arcs: Vec<&str> = oid.split('.').collect();
+    if arcs.len() < 2 || arcs.iter().any(|arc| arc.is_empty() || !arc.chars().all(|c| c.is_ascii_digit())) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            tr!("Invalid policy OID {} (expected dotted digits, e.g. 2.23.140.1.2.1)", oid)
+        ));
+    }
+    Ok(oid.to_string())
+}
+
+impl PKIConfig {
+    /// Extension file line for the `--policy` OIDs, if any
+    pub(crate) fn policy_lines(&self) -> String {
+        if self.policy_oids.is_empty() {
+            return String::new();
+        }
+        format!("certificatePolicies = {}\n", self.policy_oids.join(", "))
+    }
+
+    /// OpenSSL extension file contents, with a `v3_profile` section, for a
+    /// user certificate: an end entity with the profile's key usages, where
+    /// `rsa_key` says whether the certified key is RSA and so may encipher.
+    /// `subject_alt_names` are OpenSSL general names such as `DNS:localhost`.
+    pub(crate) fn user_extension_config(
+        &self,
+        profile: Option<CertificateProfile>,
+        subject_alt_names: &[String],
+        rsa_key: bool,
+    ) -> String {
+        let mut lines = String::from("basicConstraints = critical, CA:FALSE\n");
+        let key_usage = CertificateProfile::key_usage(profile, rsa_key);
+        lines.push_str(&format!("keyUsage = critical, {}\n", key_usage.join(", ")));
+        if let Some(profile) = profile {
+            lines.push_str(&profile.extension_lines());
+        }
+        if !subject_alt_names.is_empty() {
+            lines.push_str(&format!("subjectAltName = {}\n", subject_alt_names.join(", ")));
+        }
+        lines.push_str(&self.distribution_points.extension_lines());
+        lines.push_str(&self.policy_lines());
+
+        format!("[v3_profile]\n{}", lines)
+    }
+}
@@ -0,0 +1,261 @@
+use std::io;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::exec::Execute;
+
+/// Lifecycle state of an issued certificate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CertificateStatus {
+    Valid,
+    Expiring,
+    Expired,
+    Revoked,
+}
+
+impl CertificateStatus {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Valid => "valid",
+            Self::Expiring => "expiring",
+            Self::Expired => "expired",
+            Self::Revoked => "revoked",
+        }
+    }
+}
+
+/// Summary of one user certificate
+pub(crate) struct CertificateInfo {
+    pub(crate) username: String,
+    /// Serial number in upper-case hex
+    pub(crate) serial: String,
+    /// Validity bounds in Unix seconds
+    pub(crate) not_before: u64,
+    pub(crate) not_after: u64,
+    pub(crate) status: CertificateStatus,
+}
+
+impl CertificateInfo {
+    /// Whole days until the certificate expires, negative once it has
+    pub(crate) fn days_left(&self) -> i64 {
+        (self.not_after as i64 - unix_now() as i64).div_euclid(86_400)
+    }
+}
+
+/// Full details of one user certificate
+pub(crate) struct CertificateDetails {
+    pub(crate) info: CertificateInfo,
+    pub(crate) subject: String,
+    pub(crate) issuer: String,
+    /// SHA-256 fingerprint, colon-separated hex
+    pub(crate) fingerprint: String,
+    pub(crate) extended_key_usage: Option<String>,
+}
+
+/// Dates of the current CRL
+pub(crate) struct CrlInfo {
+    pub(crate) last_update: u64,
+    pub(crate) next_update: Option<u64>,
+}
+
+impl CrlInfo {
+    /// A CRL is fresh until its nextUpdate passes
+    pub(crate) fn is_fresh(&self) -> bool {
+        self.next_update.is_some_and(|next_update| next_update > unix_now())
+    }
+}
+
+impl PKIConfig {
+    /// Inspect every issued user certificate. Certificates expiring within
+    /// `warn_days` are reported as [`CertificateStatus::Expiring`].
+    pub(crate) fn certificate_inventory(&self, warn_days: u32) -> io::Result<Vec<CertificateInfo>> {
+        let revoked = self.revoked_serials()?;
+
+        self.issued_usernames()?
+            .into_iter()
+            .map(|username| Ok(self.read_certificate(username, &revoked, warn_days)?.info))
+            .collect()
+    }
+
+    /// Certificates that have expired or expire within `within_days`,
+    /// soonest first; revoked ones are left out
+    pub(crate) fn expiring_certificates(&self, within_days: u32) -> io::Result<Vec<CertificateInfo>> {
+        let mut expiring: Vec<CertificateInfo> = self
+            .certificate_inventory(within_days)?
+            .into_iter()
+            .filter(|info| matches!(info.status, CertificateStatus::Expiring | CertificateStatus::Expired))
+            .collect();
+        expiring.sort_by_key(|info| info.not_after);
+        Ok(expiring)
+    }
+
+    /// Subject, issuer, fingerprint, usage and status of a user's certificate
+    pub(crate) fn inspect_certificate(&self, username: &str, warn_days: u32) -> io::Result<CertificateDetails> {
+        let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
+        if !Path::new(&user_cert_path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                tr!("Certificate for user {} not found", username)
+            ));
+        }
+
+        self.read_certificate(username.to_string(), &self.revoked_serials()?, warn_days)
+    }
+
+    fn read_certificate(&self, username: String, revoked: &[String], warn_days: u32) -> io::Result<CertificateDetails> {
+        let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
+        let output = Command::new("openssl")
+            .args([
+                "x509", "-noout",
+                "-serial", "-startdate", "-enddate",
+                "-subject", "-issuer", "-nameopt", "RFC2253",
+                "-fingerprint", "-sha256",
+                "-ext", "extendedKeyUsage",
+                "-in", &user_cert_path
+            ])
+            .run(self.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to read certificate of user {}", username)));
+        }
+
+        let text = String::from_utf8_lossy(&output.stdout);
+        let field = |prefix: &str| {
+            text.lines()
+                .find_map(|line| line.strip_prefix(prefix))
+                .map(str::trim)
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        let serial = field("serial=").to_ascii_uppercase();
+        let not_before = parse_openssl_time(&field("notBefore=")).unwrap_or_default();
+        let not_after = parse_openssl_time(&field("notAfter=")).unwrap_or_default();
+
+        let now = unix_now();
+        let status = if revoked.contains(&serial) {
+            CertificateStatus::Revoked
+        } else if not_after <= now {
+            CertificateStatus::Expired
+        } else if not_after <= now + u64::from(warn_days) * 86_400 {
+            CertificateStatus::Expiring
+        } else {
+            CertificateStatus::Valid
+        };
+
+        // The usage list is on the line after the extension name
+        let extended_key_usage = text
+            .lines()
+            .skip_while(|line| !line.contains("Extended Key Usage"))
+            .nth(1)
+            .map(|line| line.trim().to_string());
+
+        Ok(CertificateDetails {
+            subject: field("subject="),
+            issuer: field("issuer="),
+            fingerprint: field("sha256 Fingerprint="),
+            extended_key_usage,
+            info: CertificateInfo { username, serial, not_before, not_after, status },
+        })
+    }
+
+    /// Serial numbers (upper-case hex) marked revoked in the issuance database
+    pub(crate) fn revoked_serials(&self) -> io::Result<Vec<String>> {
+        Ok(self
+            .issued_certificates()?
+            .into_iter()
+            .filter(|certificate| certificate.revoked)
+            .map(|certificate| certificate.serial)
+            .collect())
+    }
+
+    /// Update dates of the current CRL, if one has been generated
+    pub(crate) fn crl_info(&self) -> io::Result<Option<CrlInfo>> {
+        let crl_path = format!("{}/ca_crl.pem", self.ca_dir);
+        if !Path::new(&crl_path).exists() {
+            return Ok(None);
+        }
+
+        let output = Command::new("openssl")
+            .args(["crl", "-noout", "-lastupdate", "-nextupdate", "-in", &crl_path])
+            .run(self.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to read Certificate Revocation List")));
+        }
+
+        let text = String::from_utf8_lossy(&output.stdout);
+        let field = |prefix: &str| {
+            text.lines()
+                .find_map(|line| line.strip_prefix(prefix))
+                .and_then(parse_openssl_time)
+        };
+
+        Ok(Some(CrlInfo {
+            last_update: field("lastUpdate=").unwrap_or_default(),
+            next_update: field("nextUpdate="),
+        }))
+    }
+}
+
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Parse OpenSSL's `Mon DD HH:MM:SS YYYY GMT` into Unix seconds
+pub(crate) fn parse_openssl_time(text: &str) -> Option<u64> {
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+    let mut parts = text.split_whitespace();
+    let month_name = parts.next()?;
+    let month = MONTHS.iter().position(|&month| month == month_name)? as i64 + 1;
+    let day: i64 = parts.next()?.parse().ok()?;
+    // Timestamp tokens may carry fractional seconds (`19:00:00.123`)
+    let mut clock = parts.next()?.split('.').next()?.split(':').map(|part| part.parse::<u64>().ok());
+    let (hours, minutes, seconds) = (clock.next()??, clock.next()??, clock.next()??);
+    let year: i64 = parts.next()?.parse().ok()?;
+
+    unix_from_civil(year, month, day, hours * 3600 + minutes * 60 + seconds)
+}
+
+/// Parse an ASN.1 UTCTime (`YYMMDDHHMMSSZ`) or GeneralizedTime
+/// (`YYYYMMDDHHMMSSZ`), as stored in the issuance database
+pub(crate) fn parse_asn1_time(text: &str) -> Option<u64> {
+    let digits = text.strip_suffix('Z')?;
+    if !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    let (year, rest) = match digits.len() {
+        12 => {
+            let year: i64 = digits[..2].parse().ok()?;
+            (if year < 50 { 2000 + year } else { 1900 + year }, &digits[2..])
+        }
+        14 => (digits[..4].parse().ok()?, &digits[4..]),
+        _ => return None,
+    };
+    let field = |range: std::ops::Range<usize>| rest[range].parse::<u64>().ok();
+
+    let seconds_of_day = field(4..6)? * 3600 + field(6..8)? * 60 + field(8..10)?;
+    unix_from_civil(year, field(0..2)? as i64, field(2..4)? as i64, seconds_of_day)
+}
+
+/// Unix seconds of a UTC calendar date plus seconds into that day
+fn unix_from_civil(year: i64, month: i64, day: i64, seconds_of_day: u64) -> Option<u64> {
+    // Days-from-civil (Howard Hinnant's algorithm)
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let year_of_era = y.rem_euclid(400);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    u64::try_from(days).ok().map(|days| days * 86_400 + seconds_of_day)
+}
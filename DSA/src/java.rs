@@ -0,0 +1,145 @@
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::exec::{self, Execute};
+use crate::permissions::{create_private_file, restrict_to_owner};
+
+/// Environment variable carrying the store password to openssl and keytool
+const STOREPASS_ENV: &str = "PKI_KEYSTORE_PASSWORD";
+
+/// Java key store formats
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum KeystoreFormat {
+    /// PKCS#12, the default keystore type since Java 9
+    Pkcs12,
+    /// Legacy Java KeyStore, written through `keytool`
+    Jks,
+}
+
+impl KeystoreFormat {
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "pkcs12" | "p12" => Some(Self::Pkcs12),
+            "jks" => Some(Self::Jks),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn extension(&self) -> &'static str {
+        match self {
+            Self::Pkcs12 => "p12",
+            Self::Jks => "jks",
+        }
+    }
+}
+
+impl PKIConfig {
+    /// Export the CA certificate as a Java truststore
+    pub(crate) fn export_truststore(&self, output_path: &str, format: KeystoreFormat, storepass: &str) -> io::Result<()> {
+        let ca_cert_path = format!("{}/ca_certificate.pem", self.ca_dir);
+
+        let output = match format {
+            KeystoreFormat::Pkcs12 => Command::new("openssl")
+                .args([
+                    "pkcs12", "-export", "-nokeys",
+                    "-in", &ca_cert_path,
+                    "-caname", "pki-ca",
+                    "-jdktrust", "anyExtendedKeyUsage",
+                    "-passout", &format!("env:{}", STOREPASS_ENV),
+                    "-out", output_path
+                ])
+                .env(STOREPASS_ENV, storepass)
+                .execute(self.runner())?,
+            KeystoreFormat::Jks => Command::new("keytool")
+                .args([
+                    "-importcert", "-noprompt",
+                    "-alias", "pki-ca",
+                    "-file", &ca_cert_path,
+                    "-keystore", output_path,
+                    "-storetype", "JKS",
+                    "-storepass:env", STOREPASS_ENV
+                ])
+                .env(STOREPASS_ENV, storepass)
+                .execute(self.runner())?,
+        };
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to export truststore {}", output_path)));
+        }
+
+        Ok(())
+    }
+
+    /// Export a user's key, certificate and CA chain as a Java keystore
+    pub(crate) fn export_identity_keystore(
+        &self,
+        username: &str,
+        output_path: &str,
+        format: KeystoreFormat,
+        storepass: &str,
+    ) -> io::Result<()> {
+        let ca_cert_path = format!("{}/ca_certificate.pem", self.ca_dir);
+        let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
+
+        if !Path::new(&user_cert_path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                tr!("Certificate for user {} not found", username)
+            ));
+        }
+
+        // JKS is produced by converting an intermediate PKCS#12 file
+        let pkcs12_path = match format {
+            KeystoreFormat::Pkcs12 => output_path.to_string(),
+            KeystoreFormat::Jks => format!("{}.p12.tmp", output_path),
+        };
+        create_private_file(&pkcs12_path)?;
+
+        let output = self
+            .unlock_user_keys(
+                Command::new("openssl")
+                    .args(["pkcs12", "-export"])
+                    .args(self.user_key_args("-inkey", username))
+                    .args([
+                        "-in", &user_cert_path,
+                        "-certfile", &ca_cert_path,
+                        "-name", username,
+                        "-passout", &format!("env:{}", STOREPASS_ENV),
+                        "-out", &pkcs12_path
+                    ])
+                    .env(STOREPASS_ENV, storepass)
+            )?
+            .execute(self.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to export keystore for user {}", username)));
+        }
+
+        if format == KeystoreFormat::Jks {
+            let output = Command::new("keytool")
+                .args([
+                    "-importkeystore", "-noprompt",
+                    "-srckeystore", &pkcs12_path,
+                    "-srcstoretype", "PKCS12",
+                    "-srcstorepass:env", STOREPASS_ENV,
+                    "-destkeystore", output_path,
+                    "-deststoretype", "JKS",
+                    "-deststorepass:env", STOREPASS_ENV
+                ])
+                .env(STOREPASS_ENV, storepass)
+                .execute(self.runner());
+            exec::remove_file(&pkcs12_path)?;
+
+            if !output?.status.success() {
+                return Err(io::Error::other(tr!("Failed to convert keystore for user {} to JKS", username)));
+            }
+            restrict_to_owner(output_path)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use std::io;
+
+use crypto_core::der::{self, Reader};
+use crypto_core::tr;
+
+/// Object identifier of Ed25519 keys and signatures (RFC 8410)
+pub(crate) const ED25519_OID: &str = "1.3.101.112";
+
+/// Type of the keys the PKI generates: RSA of the configured size, ECDSA
+/// on a NIST curve or Ed25519 (`pki.ca_key_algorithm`, `pki.user_key_algorithm`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum KeyAlgorithm {
+    #[default]
+    Rsa,
+    EcdsaP256,
+    EcdsaP384,
+    Ed25519,
+}
+
+impl KeyAlgorithm {
+    pub(crate) fn parse(name: &str) -> io::Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "rsa" => Ok(Self::Rsa),
+            "ecdsa-p256" | "p256" | "p-256" => Ok(Self::EcdsaP256),
+            "ecdsa-p384" | "p384" | "p-384" => Ok(Self::EcdsaP384),
+            "ed25519" => Ok(Self::Ed25519),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("Unknown key algorithm {} (expected rsa, ecdsa-p256, ecdsa-p384 or ed25519)", name)
+            )),
+        }
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Rsa => "rsa",
+            Self::EcdsaP256 => "ecdsa-p256",
+            Self::EcdsaP384 => "ecdsa-p384",
+            Self::Ed25519 => "ed25519",
+        }
+    }
+
+    /// The type of a key from the `openssl pkey -text_pub` description of it
+    pub(crate) fn from_key_text(text: &str) -> Option<Self> {
+        if text.starts_with("ED25519 ") {
+            Some(Self::Ed25519)
+        } else if text.contains("NIST CURVE: P-256") {
+            Some(Self::EcdsaP256)
+        } else if text.contains("NIST CURVE: P-384") {
+            Some(Self::EcdsaP384)
+        } else {
+            text.lines().any(|line| line.starts_with("Modulus:")).then_some(Self::Rsa)
+        }
+    }
+
+    /// Whether a PEM SubjectPublicKeyInfo holds an Ed25519 key
+    pub(crate) fn is_ed25519_public_key(pem: &str) -> bool {
+        let Ok((_, spki)) = der::pem_decode(pem) else {
+            return false;
+        };
+        let algorithm = Reader::new(&spki)
+            .nested(der::SEQUENCE)
+            .and_then(|mut info| info.nested(der::SEQUENCE))
+            .and_then(|mut algorithm| algorithm.raw_element());
+        algorithm.is_ok_and(|oid| der::encode_oid(ED25519_OID).as_deref() == Some(oid))
+    }
+
+    /// Whether a PEM certificate request holds an RSA key, from the
+    /// algorithm of its SubjectPublicKeyInfo; `None` if it does not parse
+    pub(crate) fn is_rsa_request(pem: &str) -> Option<bool> {
+        let (_, request) = der::pem_decode(pem).ok()?;
+        let mut request = Reader::new(&request);
+        let mut info = request.nested(der::SEQUENCE).and_then(|mut request| request.nested(der::SEQUENCE)).ok()?;
+        info.integer().ok()?;
+        info.expect(der::SEQUENCE).ok()?;
+        let algorithm = info
+            .nested(der::SEQUENCE)
+            .and_then(|mut spki| spki.nested(der::SEQUENCE))
+            .and_then(|mut algorithm| algorithm.expect(der::OBJECT_IDENTIFIER))
+            .ok()?;
+        Some(algorithm == der::RSA_ENCRYPTION)
+    }
+
+    /// Arguments for `openssl genpkey` making a key of this type, where
+    /// `rsa_bits` is the size used for RSA
+    pub(crate) fn genpkey_args(&self, rsa_bits: u32) -> Vec<String> {
+        let (algorithm, option) = match self {
+            Self::Rsa => ("RSA", format!("rsa_keygen_bits:{}", rsa_bits)),
+            Self::EcdsaP256 => ("EC", String::from("ec_paramgen_curve:P-256")),
+            Self::EcdsaP384 => ("EC", String::from("ec_paramgen_curve:P-384")),
+            // Ed25519 has no parameters at all
+            Self::Ed25519 => return vec!["-algorithm".to_string(), "ED25519".to_string()],
+        };
+        let mut args = vec!["-algorithm".to_string(), algorithm.to_string(), "-pkeyopt".to_string(), option];
+        if *self != Self::Rsa {
+            // Name the curve rather than spelling out its parameters
+            args.extend(["-pkeyopt".to_string(), "ec_param_enc:named_curve".to_string()]);
+        }
+        args
+    }
+}
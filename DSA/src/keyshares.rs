@@ -0,0 +1,125 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crypto_core::codec::encode_hex;
+use crypto_core::hash::sha256;
+use crypto_core::shamir::{self, Share};
+use crypto_core::tr;
+use serde_json::{json, Value};
+
+use crate::PKIConfig;
+use crate::exec;
+use crate::permissions::{create_private_dir, create_private_file};
+
+impl PKIConfig {
+    /// Directory `pki ca split` writes the shares into, one file per holder
+    pub(crate) fn ca_key_shares_dir(&self) -> String {
+        format!("{}/key_shares", self.ca_dir)
+    }
+
+    /// Split the CA key file with Shamir's scheme into `count` shares, any
+    /// `threshold` of which restore it. With `remove_key` the key file is
+    /// deleted afterwards, so nothing can be signed until enough share
+    /// holders bring their shares together. Returns the share files.
+    pub(crate) fn split_ca_key(&self, threshold: u8, count: u8, remove_key: bool) -> io::Result<Vec<String>> {
+        let key_path = self.ca_key_file()?;
+        let key = fs::read(&key_path)?;
+        let shares = shamir::split(&key, threshold, count)?;
+
+        let shares_dir = self.ca_key_shares_dir();
+        let share_paths: Vec<String> = shares.iter().map(|share| format!("{}/ca_key_share_{}.json", shares_dir, share.x)).collect();
+        create_private_dir(&shares_dir)?;
+        self.replace_existing(&share_paths.iter().map(String::as_str).collect::<Vec<_>>())?;
+
+        // The hash lets `combine` tell a restored key from garbage produced
+        // by too few or mismatched shares
+        let key_hash = encode_hex(&sha256(&key));
+        for (share, path) in shares.iter().zip(&share_paths) {
+            create_private_file(path)?;
+            let json = json!({
+                "threshold": threshold,
+                "shares": count,
+                "share": share.to_string(),
+                "key_sha256": key_hash,
+            });
+            exec::write(path, format!("{:#}\n", json))?;
+        }
+
+        if remove_key {
+            exec::remove_file(&key_path)?;
+        }
+        let detail = format!("{} of {} shares{}", threshold, count, if remove_key { ", key file removed" } else { "" });
+        self.record_audit_event("ca-key-split", &detail)?;
+
+        Ok(share_paths)
+    }
+
+    /// Rebuild the CA key file from share files written by
+    /// [`PKIConfig::split_ca_key`]. Returns the key path.
+    pub(crate) fn combine_ca_key(&self, share_paths: &[String]) -> io::Result<String> {
+        let key_path = self.ca_key_file()?;
+        if Path::new(&key_path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                tr!("A CA key already exists at {}; refusing to overwrite it", key_path)
+            ));
+        }
+
+        let share_files = share_paths.iter().map(|path| ShareFile::read(path)).collect::<io::Result<Vec<_>>>()?;
+        let Some(first) = share_files.first() else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("No share files given")));
+        };
+        if share_files.iter().any(|file| file.key_sha256 != first.key_sha256) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("The shares come from different splits")));
+        }
+        if share_files.len() < first.threshold {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("{} share(s) given, but {} are needed", share_files.len(), first.threshold)
+            ));
+        }
+
+        let shares: Vec<Share> = share_files.iter().map(|file| file.share.clone()).collect();
+        let key = shamir::combine(&shares)?;
+        if encode_hex(&sha256(&key)) != first.key_sha256 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, tr!("The shares do not reproduce the CA key; one is corrupt")));
+        }
+
+        create_private_file(&key_path)?;
+        exec::write(&key_path, key)?;
+        self.record_audit_event("ca-key-combined", &format!("{} shares", shares.len()))?;
+
+        Ok(key_path)
+    }
+
+    /// Only a key held in a file can be split
+    fn ca_key_file(&self) -> io::Result<String> {
+        self.ca_signer.key_file().map(String::from).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                tr!("The CA key is held in {}; only a key file can be split", self.ca_signer.describe())
+            )
+        })
+    }
+}
+
+/// One holder's share file
+struct ShareFile {
+    threshold: usize,
+    share: Share,
+    key_sha256: String,
+}
+
+impl ShareFile {
+    fn read(path: &str) -> io::Result<Self> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, tr!("{} is not a CA key share", path));
+        let json: Value = serde_json::from_str(&fs::read_to_string(path)?).map_err(|_| invalid())?;
+
+        Ok(ShareFile {
+            threshold: json["threshold"].as_u64().ok_or_else(invalid)? as usize,
+            share: json["share"].as_str().and_then(Share::parse).ok_or_else(invalid)?,
+            key_sha256: json["key_sha256"].as_str().ok_or_else(invalid)?.to_string(),
+        })
+    }
+}
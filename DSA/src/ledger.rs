@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crypto_core::chain::{Block, Chain, InvalidChain, Transaction};
+use crypto_core::codec::encode_hex;
+use crypto_core::dsa::{DsaPublicKey, DsaSignature};
+use crypto_core::tr;
+use num_bigint::BigUint;
+use serde_json::{json, Value};
+
+use crate::PKIConfig;
+use crate::exec;
+use crate::inventory::unix_now;
+use crate::transparency::from_hex;
+
+/// A block just added by `pki chain mine`
+pub(crate) struct MinedBlock {
+    pub(crate) index: u64,
+    pub(crate) hash: String,
+    pub(crate) nonce: u64,
+    pub(crate) attempts: u64,
+    pub(crate) transactions: usize,
+}
+
+impl PKIConfig {
+    /// The course blockchain, next to the CA and user directories
+    pub(crate) fn chain_path(&self) -> String {
+        self.pki_root_file("chain.json")
+    }
+
+    /// Signed transfers waiting to be mined
+    fn pending_path(&self) -> String {
+        self.pki_root_file("chain_pending.json")
+    }
+
+    fn pki_root_file(&self, name: &str) -> String {
+        let pki_root = Path::new(&self.ca_dir).parent().unwrap_or(Path::new("."));
+        pki_root.join(name).to_string_lossy().into_owned()
+    }
+
+    /// Start a chain with a genesis block needing `difficulty` leading zero bits
+    pub(crate) fn init_chain(&self, difficulty: u32) -> io::Result<Chain> {
+        self.replace_existing(&[&self.chain_path(), &self.pending_path()])?;
+        let chain = Chain::new(difficulty, unix_now());
+        exec::create_dir_all(Path::new(&self.chain_path()).parent().unwrap_or(Path::new(".")))?;
+        self.save_chain(&chain)?;
+        Ok(chain)
+    }
+
+    pub(crate) fn read_chain(&self) -> io::Result<Chain> {
+        let path = self.chain_path();
+        if !Path::new(&path).exists() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, tr!("No chain at {}; run `pki chain init`", path)));
+        }
+        let json: Value = serde_json::from_str(&fs::read_to_string(&path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, tr!("{} is not valid JSON: {}", path, e)))?;
+        let blocks = json["blocks"].as_array().ok_or_else(|| malformed(&path))?;
+        Ok(Chain { blocks: blocks.iter().map(|block| block_from_json(block).ok_or_else(|| malformed(&path))).collect::<io::Result<_>>()? })
+    }
+
+    fn save_chain(&self, chain: &Chain) -> io::Result<()> {
+        let json = json!({ "blocks": chain.blocks.iter().map(block_to_json).collect::<Vec<_>>() });
+        exec::write(self.chain_path(), format!("{:#}\n", json))
+    }
+
+    fn read_pending(&self) -> io::Result<Vec<Transaction>> {
+        let path = self.pending_path();
+        if !Path::new(&path).exists() {
+            return Ok(Vec::new());
+        }
+        let json: Value = serde_json::from_str(&fs::read_to_string(&path)?).map_err(|_| malformed(&path))?;
+        json.as_array()
+            .ok_or_else(|| malformed(&path))?
+            .iter()
+            .map(|transaction| transaction_from_json(transaction).ok_or_else(|| malformed(&path)))
+            .collect()
+    }
+
+    /// Sign a transfer with the sender's `pki dsa` key and queue it for the
+    /// next block. Returns its sequence number.
+    pub(crate) fn send_coins(&self, from: &str, to: &str, amount: u64) -> io::Result<u64> {
+        let chain = self.read_chain()?;
+        let mut pending = self.read_pending()?;
+        let queued = pending.iter().filter(|transaction| transaction.from.as_deref() == Some(from)).count() as u64;
+        let sequence = chain.next_sequence(from) + queued;
+
+        let transaction = Transaction::signed(&self.read_dsa_key(from)?, from, to, amount, sequence)?;
+        pending.push(transaction);
+        let json = Value::Array(pending.iter().map(transaction_to_json).collect());
+        exec::write(self.pending_path(), format!("{:#}\n", json))?;
+        Ok(sequence)
+    }
+
+    /// Mine the queued transfers into a block paying `miner` the reward
+    pub(crate) fn mine_block(&self, miner: &str) -> io::Result<MinedBlock> {
+        let mut chain = self.read_chain()?;
+        let pending = self.read_pending()?;
+        let (block, attempts) = chain
+            .mine(pending, miner, unix_now(), |user| self.dsa_public_key(user))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, tr!("Cannot mine: {}", e)))?;
+        let mined = MinedBlock {
+            index: block.index,
+            hash: encode_hex(&block.hash()),
+            nonce: block.nonce,
+            attempts,
+            transactions: block.transactions.len(),
+        };
+
+        self.save_chain(&chain)?;
+        if Path::new(&self.pending_path()).exists() {
+            exec::remove_file(self.pending_path())?;
+        }
+        Ok(mined)
+    }
+
+    /// Replay the chain, checking every block; on success the balances
+    pub(crate) fn validate_chain(&self, chain: &Chain) -> Result<BTreeMap<String, u64>, InvalidChain> {
+        chain.balances(|user| self.dsa_public_key(user))
+    }
+
+    fn dsa_public_key(&self, username: &str) -> Option<DsaPublicKey> {
+        self.read_dsa_key(username).ok().map(|key| key.public())
+    }
+}
+
+fn malformed(path: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, tr!("{} is not a valid chain file", path))
+}
+
+pub(crate) fn block_to_json(block: &Block) -> Value {
+    json!({
+        "index": block.index,
+        "hash": encode_hex(&block.hash()),
+        "previous_hash": encode_hex(&block.previous_hash),
+        "timestamp": block.timestamp,
+        "difficulty": block.difficulty,
+        "nonce": block.nonce,
+        "merkle_root": encode_hex(&block.merkle_root()),
+        "transactions": block.transactions.iter().map(transaction_to_json).collect::<Vec<_>>(),
+    })
+}
+
+/// The stored `hash` and `merkle_root` are for reading only; both are
+/// recomputed from the other fields
+fn block_from_json(value: &Value) -> Option<Block> {
+    Some(Block {
+        index: value["index"].as_u64()?,
+        previous_hash: from_hex(value["previous_hash"].as_str()?)?,
+        timestamp: value["timestamp"].as_u64()?,
+        difficulty: u32::try_from(value["difficulty"].as_u64()?).ok()?,
+        nonce: value["nonce"].as_u64()?,
+        transactions: value["transactions"].as_array()?.iter().map(transaction_from_json).collect::<Option<_>>()?,
+    })
+}
+
+fn transaction_to_json(transaction: &Transaction) -> Value {
+    json!({
+        "from": transaction.from,
+        "to": transaction.to,
+        "amount": transaction.amount,
+        "sequence": transaction.sequence,
+        "signature": transaction.signature.as_ref().map(|signature| json!({
+            "r": signature.r.to_str_radix(16),
+            "s": signature.s.to_str_radix(16),
+        })),
+    })
+}
+
+fn transaction_from_json(value: &Value) -> Option<Transaction> {
+    let number = |field: &Value| field.as_str().and_then(|hex| BigUint::parse_bytes(hex.as_bytes(), 16));
+    let signature = match &value["signature"] {
+        Value::Null => None,
+        signature => Some(DsaSignature { r: number(&signature["r"])?, s: number(&signature["s"])? }),
+    };
+    Some(Transaction {
+        from: match &value["from"] {
+            Value::Null => None,
+            from => Some(from.as_str()?.to_string()),
+        },
+        to: value["to"].as_str()?.to_string(),
+        amount: value["amount"].as_u64()?,
+        sequence: value["sequence"].as_u64()?,
+        signature,
+    })
+}
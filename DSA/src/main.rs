@@ -1,165 +1,374 @@
-use std::process::{Command, Stdio};
+use std::process::Command;
 use std::fs;
 use std::path::Path;
 use std::env;
-use std::io::{self, Write};
+use std::io;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+mod acme;
+mod archive;
+mod audit;
+mod backend;
+mod backup;
+mod chain;
+mod cli;
+mod client;
+mod codesign;
+mod convert;
+mod cosign;
+mod csr;
+mod database;
+mod digest;
+mod dsa;
+mod embedded;
+mod envelope;
+mod est;
+mod exec;
+mod extensions;
+mod inventory;
+mod keyalgorithm;
+mod keyshares;
+mod java;
+mod ledger;
+mod manifest;
+mod masterkey;
+mod messages;
+mod metrics;
+mod native;
+mod notify;
+mod openssl;
+mod output;
+mod passphrase;
+mod pem;
+mod permissions;
+mod profile;
+mod provision;
+mod renewal;
+mod report;
+mod rotation;
+mod runner;
+mod san;
+mod server;
+mod signer;
+mod ssh;
+mod subca;
+mod subject;
+mod timestamp;
+mod tlsdemo;
+mod transparency;
+mod trust;
+mod verification;
+mod watch;
+mod yubikey;
+
+use acme::AcmeServer;
+use audit::format_unix_time;
+use backend::CryptoBackend;
+use clap::error::{ContextKind, ContextValue};
+use clap::{CommandFactory, FromArgMatches};
+use cli::{
+    AcmeAction, CaAction, ChainAction, Cli, CodesignAction, DemoKind, DsaAction, DsaAttack, DsaSizes, GlobalArgs, KeysAction,
+    KeystoreKind, LogAction, PkiCommand, SshAction, TrustAction, UserAction, WatchAction, YubikeyAction,
+};
+use crypto_core::codec::encode_hex;
+use crypto_core::config::Config;
+use crypto_core::i18n::{self, Lang};
+use crypto_core::logging::{self, LogFormat, LogOptions};
+use convert::ArtifactFormat;
+use csr::{ExistingCertificate, SanPolicy};
+use crypto_core::tr;
+use digest::Digest;
+use exec::Execute;
+use extensions::DistributionPoints;
+use java::KeystoreFormat;
+use keyalgorithm::KeyAlgorithm;
+use notify::{Delivery, NotifyWindows, SmtpSettings};
+use output::{CommandOutput, OutputFormat};
+use profile::CertificateProfile;
+use runner::{CommandRunner, SystemRunner};
+use server::ServerSettings;
+use serde_json::json;
+use signer::{CaSigner, FileSigner, Pkcs11Signer};
+use subca::NameConstraints;
+use subject::SubjectDn;
+use timestamp::TimestampCheck;
+use tracing::{debug, warn};
+use trust::TrustStore;
+use watch::WatchPolicy;
 
 /// PKI Configuration Structure
 struct PKIConfig {
+    ca_key_algorithm: KeyAlgorithm,
+    user_key_algorithm: KeyAlgorithm,
+    /// RSA key sizes; ECDSA keys take theirs from the curve
     ca_key_bits: u32,
     user_key_bits: u32,
     ca_validity_days: u32,
     user_validity_days: u32,
     ca_dir: String,
     users_dir: String,
+    /// Subject of the CA certificate (`pki.ca_subject`)
+    ca_subject: SubjectDn,
+    /// Subject fields every user certificate starts from (`pki.user_subject`)
+    user_subject: SubjectDn,
+    /// Whether certificates, requests and CRLs also get a DER copy
+    artifact_format: ArtifactFormat,
+    tsa_url: Option<String>,
+    tsa_ca_file: Option<String>,
+    /// CRL and AIA URLs embedded in issued certificates
+    distribution_points: DistributionPoints,
+    /// Subject alternative names external CSRs may request, as patterns
+    /// such as `DNS:*.example.com` (`pki.csr_san_patterns`)
+    csr_san_patterns: Vec<String>,
+    /// Address `pki serve` listens on unless `--listen` is given
+    listen: String,
+    ca_signer: Box<dyn CaSigner>,
+    /// Generates keys and signs requests, certificates and documents (`pki.backend`)
+    backend: Box<dyn CryptoBackend>,
+    /// Runs openssl and the other external tools
+    runner: Box<dyn CommandRunner>,
+    /// Replace existing keys and certificates without asking (`--force`)
+    force: bool,
+    /// Digest chosen with `--digest`; see [`PKIConfig::signing_digest`]
+    digest: Option<Digest>,
+    /// Certificate policy OIDs added to issued certificates (`--policy`)
+    policy_oids: Vec<String>,
+    /// Serializes serial allocation and issuance database writes when
+    /// users are provisioned concurrently
+    issuance_lock: Mutex<()>,
+    /// Unlocks user private keys encrypted under the master key (`PKI_MASTER_PASSPHRASE`)
+    master_passphrase: Option<String>,
+    /// Master key once unwrapped, so the passphrase is stretched only once per run
+    master_key: OnceLock<String>,
 }
 
 impl PKIConfig {
-    fn new() -> Self {
-        PKIConfig {
-            ca_key_bits: 4096,
-            user_key_bits: 2048,
-            ca_validity_days: 3650,
-            user_validity_days: 365,
-            ca_dir: String::from("./pki/ca"),
-            users_dir: String::from("./pki/users"),
-        }
+    /// The PKI described by the `pki.*` settings
+    fn from_config(config: &Config) -> io::Result<Self> {
+        let ca_dir = config.get("pki.ca_dir").unwrap_or(".").to_string();
+
+        // Keep the CA key in a PKCS#11 token when a key URI is configured
+        let ca_signer: Box<dyn CaSigner> = match config.get("pki.ca_pkcs11_uri") {
+            Some(key_uri) => Box::new(Pkcs11Signer { key_uri: key_uri.to_string() }),
+            None => Box::new(FileSigner {
+                key_path: format!("{}/ca_private_key.pem", ca_dir),
+                encrypt: config.parse("pki.encrypt_ca_key")?,
+            }),
+        };
+
+        Ok(PKIConfig {
+            ca_key_algorithm: KeyAlgorithm::parse(config.get("pki.ca_key_algorithm").unwrap_or("rsa"))?,
+            user_key_algorithm: KeyAlgorithm::parse(config.get("pki.user_key_algorithm").unwrap_or("rsa"))?,
+            ca_key_bits: config.parse("pki.ca_key_bits")?,
+            user_key_bits: config.parse("pki.user_key_bits")?,
+            ca_validity_days: config.parse("pki.ca_validity_days")?,
+            user_validity_days: config.parse("pki.user_validity_days")?,
+            ca_dir,
+            users_dir: config.get("pki.users_dir").unwrap_or(".").to_string(),
+            ca_subject: SubjectDn::parse(config.get("pki.ca_subject").unwrap_or("/CN=DotUnity CA/O=DotCompany/OU=IT Department"))?,
+            user_subject: config.get("pki.user_subject").map(SubjectDn::parse).transpose()?.unwrap_or_default(),
+            artifact_format: ArtifactFormat::parse(config.get("pki.artifact_format").unwrap_or("pem"))?,
+            tsa_url: config.get("pki.tsa_url").map(str::to_string),
+            tsa_ca_file: config.get("pki.tsa_ca_file").map(str::to_string),
+            distribution_points: DistributionPoints::from_config(config),
+            csr_san_patterns: config
+                .get("pki.csr_san_patterns")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|pattern| !pattern.is_empty())
+                .map(str::to_string)
+                .collect(),
+            listen: config.get("pki.listen").unwrap_or(":8443").to_string(),
+            ca_signer,
+            backend: backend::from_config(config)?,
+            runner: Box::new(SystemRunner),
+            force: false,
+            digest: config.get("pki.digest").map(Digest::parse).transpose()?,
+            policy_oids: Vec::new(),
+            issuance_lock: Mutex::new(()),
+            master_passphrase: env::var("PKI_MASTER_PASSPHRASE").ok(),
+            master_key: OnceLock::new(),
+        })
+    }
+
+    fn runner(&self) -> &dyn CommandRunner {
+        self.runner.as_ref()
     }
 
     /// Initialize PKI directory structure
     fn init_pki_structure(&self) -> io::Result<()> {
-        fs::create_dir_all(&self.ca_dir)?;
-        fs::create_dir_all(&self.users_dir)?;
+        permissions::create_private_dir(&self.ca_dir)?;
+        exec::create_dir_all(&self.users_dir)?;
         Ok(())
     }
 
-    /// Generate CA Private Key
-    fn generate_ca_key(&self) -> io::Result<()> {
-        let ca_key_path = format!("{}/ca_private_key.pem", self.ca_dir);
-        
-        let output = Command::new("openssl")
-            .args(&[
-                "genrsa", 
-                "-out", &ca_key_path, 
-                &self.ca_key_bits.to_string()
-            ])
-            .output()?;
+    /// Create the CA unless one already exists, resuming a half-finished
+    /// setup. With `--force` an existing CA is archived and replaced.
+    /// Returns whether a new CA certificate was created.
+    fn init_ca(&self) -> io::Result<bool> {
+        let ca_cert_path = format!("{}/ca_certificate.pem", self.ca_dir);
+        let crl_path = format!("{}/ca_crl.pem", self.ca_dir);
+        self.init_pki_structure()?;
 
-        if !output.status.success() {
-            return Err(io::Error::new(
-                io::ErrorKind::Other, 
-                "Failed to generate CA private key"
-            ));
+        if Path::new(&ca_cert_path).exists() {
+            if !self.force {
+                if !self.ca_signer.key_exists() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        tr!("CA certificate exists but its key ({}) is missing", self.ca_signer.describe())
+                    ));
+                }
+                return Ok(false);
+            }
+
+            let mut replaced = vec![ca_cert_path.as_str(), crl_path.as_str()];
+            replaced.extend(self.ca_signer.key_file());
+            self.replace_existing(&replaced)?;
         }
 
-        Ok(())
+        if !self.ca_signer.key_exists() {
+            self.generate_ca_key()?;
+        }
+        self.create_ca_certificate()?;
+
+        Ok(true)
+    }
+
+    /// Generate CA Private Key
+    fn generate_ca_key(&self) -> io::Result<()> {
+        self.backend.generate_ca_key(self)
     }
 
     /// Create Self-Signed CA Certificate
     fn create_ca_certificate(&self) -> io::Result<()> {
-        let ca_key_path = format!("{}/ca_private_key.pem", self.ca_dir);
-        let ca_cert_path = format!("{}/ca_certificate.pem", self.ca_dir);
-        
-        let output = Command::new("openssl")
-            .args(&[
-                "req", "-x509", "-new", "-nodes",
-                "-key", &ca_key_path,
-                "-sha256",
-                "-days", &self.ca_validity_days.to_string(),
-                "-out", &ca_cert_path,
-                "-subj", "/CN=DotUnity CA/O=DotCompany/OU=IT Department"
-            ])
-            .output()?;
-
-        if !output.status.success() {
-            return Err(io::Error::new(
-                io::ErrorKind::Other, 
-                "Failed to create CA self-signed certificate"
-            ));
-        }
-
-        Ok(())
+        self.backend.create_ca_certificate(self)?;
+        self.export_artifact(&format!("{}/ca_certificate.pem", self.ca_dir))?;
+        self.record_audit_event("ca-created", &self.ca_signer.describe())
     }
 
     /// Generate User Private Key
     fn generate_user_key(&self, username: &str) -> io::Result<()> {
         let user_key_path = format!("{}/{}_private_key.pem", self.users_dir, username);
-        
-        let output = Command::new("openssl")
-            .args(&[
-                "genrsa", 
-                "-out", &user_key_path, 
-                &self.user_key_bits.to_string()
-            ])
-            .output()?;
+        self.replace_existing(&[&user_key_path])?;
+        permissions::create_private_file(&user_key_path)?;
+        self.backend.generate_user_key(self, username)
+    }
 
-        if !output.status.success() {
-            return Err(io::Error::new(
-                io::ErrorKind::Other, 
-                format!("Failed to generate private key for user {}", username)
-            ));
-        }
+    /// Generate a CSR with an explicit subject (`/CN=.../O=...`)
+    fn generate_csr_with_subject(&self, username: &str, subject: &str) -> io::Result<()> {
+        self.generate_csr_with_subject_alt_names(username, subject, &[])
+    }
 
-        Ok(())
+    /// Generate a CSR for `subject` that requests subject alternative names
+    /// (`DNS:host.example`, `IP:10.0.0.1`, `email:ana@example.com`)
+    fn generate_csr_with_subject_alt_names(&self, username: &str, subject: &str, subject_alt_names: &[String]) -> io::Result<()> {
+        for name in subject_alt_names {
+            san::parse_subject_alt_name(name)?;
+        }
+        self.backend.create_csr(self, username, subject, subject_alt_names)?;
+        self.export_artifact(&format!("{}/{}_csr.pem", self.users_dir, username))
     }
 
-    /// Generate Certificate Signing Request (CSR)
-    fn generate_csr(&self, username: &str) -> io::Result<()> {
+    /// Bring a user to an issued certificate, skipping the key, CSR and
+    /// signing steps that were already completed. Returns whether anything
+    /// had to be done.
+    fn ensure_user_certificate(
+        &self,
+        username: &str,
+        subject: &str,
+        profile: Option<CertificateProfile>,
+    ) -> io::Result<bool> {
         let user_key_path = format!("{}/{}_private_key.pem", self.users_dir, username);
         let user_csr_path = format!("{}/{}_csr.pem", self.users_dir, username);
-        
-        let output = Command::new("openssl")
-            .args(&[
-                "req", "-new", 
-                "-key", &user_key_path,
-                "-out", &user_csr_path,
-                "-subj", &format!("/CN={}/O=MyOrganization", username)
-            ])
-            .output()?;
+        let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
 
-        if !output.status.success() {
-            return Err(io::Error::new(
-                io::ErrorKind::Other, 
-                format!("Failed to generate CSR for user {}", username)
-            ));
+        if Path::new(&user_cert_path).exists() {
+            debug!(username, "certificate already issued");
+            return Ok(false);
+        }
+        if Path::new(&user_key_path).exists() {
+            debug!(username, "reusing existing key");
+        } else {
+            self.generate_user_key(username)?;
+        }
+        if Path::new(&user_csr_path).exists() {
+            debug!(username, "reusing existing CSR");
+        } else {
+            self.generate_csr_with_subject(username, subject)?;
         }
+        self.sign_user_certificate_with_profile(username, profile)?;
 
-        Ok(())
+        Ok(true)
+    }
+
+    /// Issue a user a certificate for `subject`, creating their key if they
+    /// have none and always writing a fresh CSR. An existing certificate is
+    /// only replaced with `--force`; returns whether one was issued.
+    fn issue_user_certificate(
+        &self,
+        username: &str,
+        subject: &str,
+        profile: Option<CertificateProfile>,
+        subject_alt_names: &[String],
+    ) -> io::Result<bool> {
+        let user_key_path = format!("{}/{}_private_key.pem", self.users_dir, username);
+        let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
+
+        if Path::new(&user_cert_path).exists() && !self.force {
+            debug!(username, "certificate already issued");
+            return Ok(false);
+        }
+        if !Path::new(&user_key_path).exists() {
+            self.generate_user_key(username)?;
+        }
+        self.generate_csr_with_subject_alt_names(username, subject, subject_alt_names)?;
+        self.sign_user_certificate_with_extensions(username, profile, subject_alt_names)?;
+
+        Ok(true)
     }
 
     /// Sign User Certificate
     fn sign_user_certificate(&self, username: &str) -> io::Result<()> {
-        let ca_key_path = format!("{}/ca_private_key.pem", self.ca_dir);
-        let ca_cert_path = format!("{}/ca_certificate.pem", self.ca_dir);
-        let user_csr_path = format!("{}/{}_csr.pem", self.users_dir, username);
+        self.sign_user_certificate_with_profile(username, None)
+    }
+
+    /// Sign a user's CSR, adding the extensions of an optional profile
+    fn sign_user_certificate_with_profile(&self, username: &str, profile: Option<CertificateProfile>) -> io::Result<()> {
+        self.sign_user_certificate_with_extensions(username, profile, &[])
+    }
+
+    /// Sign a user's CSR with an optional profile and subject alternative
+    /// names. The names a CSR requests are never copied on their own:
+    /// callers pass the ones they generated, or checked with
+    /// [`PKIConfig::permitted_subject_alt_names`].
+    fn sign_user_certificate_with_extensions(
+        &self,
+        username: &str,
+        profile: Option<CertificateProfile>,
+        subject_alt_names: &[String],
+    ) -> io::Result<()> {
         let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
-        
-        let output = Command::new("openssl")
-            .args(&[
-                "x509", "-req", 
-                "-in", &user_csr_path,
-                "-CA", &ca_cert_path,
-                "-CAkey", &ca_key_path,
-                "-CAcreateserial",
-                "-out", &user_cert_path,
-                "-days", &self.user_validity_days.to_string(),
-                "-sha256"
-            ])
-            .output()?;
+        self.replace_existing(&[&user_cert_path])?;
 
-        if !output.status.success() {
-            return Err(io::Error::new(
-                io::ErrorKind::Other, 
-                format!("Failed to sign certificate for user {}", username)
-            ));
-        }
+        let _issuance = self.issuance_lock.lock().unwrap_or_else(PoisonError::into_inner);
+        let serial = self.new_serial()?;
 
-        Ok(())
+        self.backend.issue_certificate(self, username, &serial, profile, subject_alt_names)?;
+        self.export_artifact(&user_cert_path)?;
+        self.record_issuance(&user_cert_path)?;
+
+        let detail = match profile {
+            Some(profile) => format!("{} (profile {})", username, profile.name()),
+            None => username.to_string(),
+        };
+        self.record_audit_event("issued", &detail)
     }
 
     /// Revoke User Certificate
     fn revoke_user_certificate(&self, username: &str) -> io::Result<()> {
-        let ca_key_path = format!("{}/ca_private_key.pem", self.ca_dir);
         let ca_cert_path = format!("{}/ca_certificate.pem", self.ca_dir);
         let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
         let crl_path = format!("{}/ca_crl.pem", self.ca_dir);
@@ -168,113 +377,1363 @@ impl PKIConfig {
         if !Path::new(&user_cert_path).exists() {
             return Err(io::Error::new(
                 io::ErrorKind::NotFound, 
-                format!("Certificate for user {} not found", username)
+                tr!("Certificate for user {} not found", username)
             ));
         }
 
+        let ca_config_path = self.ca_config()?;
+
         // Revoke certificate
         let output = Command::new("openssl")
-            .args(&[
+            .args([
                 "ca", 
+                "-config", &ca_config_path,
                 "-revoke", &user_cert_path,
-                "-keyfile", &ca_key_path,
                 "-cert", &ca_cert_path
             ])
-            .output()?;
+            .args(self.ca_signer.key_args("-keyfile"))
+            .execute(self.runner())?;
 
         if !output.status.success() {
-            return Err(io::Error::new(
-                io::ErrorKind::Other, 
-                format!("Failed to revoke certificate for user {}", username)
-            ));
+            return Err(io::Error::other(tr!("Failed to revoke certificate for user {}", username)));
+        }
+
+        // Publishing the new CRL is the point of revoking; keep the old one
+        if Path::new(&crl_path).exists() {
+            self.archive_files(&[&crl_path])?;
         }
 
         // Generate Certificate Revocation List (CRL)
         let crl_output = Command::new("openssl")
-            .args(&[
+            .args([
                 "ca", 
+                "-config", &ca_config_path,
                 "-gencrl", 
-                "-keyfile", &ca_key_path,
                 "-cert", &ca_cert_path,
                 "-out", &crl_path
             ])
-            .output()?;
+            .args(self.ca_signer.key_args("-keyfile"))
+            .execute(self.runner())?;
 
         if !crl_output.status.success() {
-            return Err(io::Error::new(
-                io::ErrorKind::Other, 
-                "Failed to generate Certificate Revocation List"
-            ));
+            return Err(io::Error::other(tr!("Failed to generate Certificate Revocation List")));
         }
+        self.export_artifact(&crl_path)?;
 
-        Ok(())
+        self.record_audit_event("revoked", username)
     }
 
     /// Sign Document/File
     fn sign_document(&self, username: &str, document_path: &str) -> io::Result<()> {
-        let user_key_path = format!("{}/{}_private_key.pem", self.users_dir, username);
-        let signature_path = format!("{}.sig", document_path);
-        
-        let output = Command::new("openssl")
-            .args(&[
-                "dgst", "-sha256", 
-                "-sign", &user_key_path,
-                "-out", &signature_path,
-                document_path
-            ])
-            .output()?;
+        self.backend.sign_document(self, username, document_path)
+    }
 
-        if !output.status.success() {
+    /// Verify Document Signature
+    fn verify_document_signature(&self, username: &str, document_path: &str) -> io::Result<bool> {
+        // Without --digest, verify with the digest the signature names
+        let digests = match self.digest {
+            Some(digest) => vec![digest],
+            None => match self.signature_digest(username, document_path)?.and_then(|name| Digest::parse(&name).ok()) {
+                Some(digest) => vec![digest],
+                // ECDSA signatures do not name theirs, so try each
+                None => vec![Digest::Sha256, Digest::Sha384, Digest::Sha512],
+            },
+        };
+
+        for digest in digests {
+            if self.backend.verify_document_signature(self, username, document_path, digest)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Passphrase from `--passphrase` or the PKI_BACKUP_PASSPHRASE environment variable
+fn backup_passphrase(flag_value: Option<String>) -> io::Result<String> {
+    flag_value
+        .or_else(|| env::var("PKI_BACKUP_PASSPHRASE").ok())
+        .filter(|passphrase| !passphrase.is_empty())
+        .ok_or_else(|| io::Error::new(
+            io::ErrorKind::InvalidInput,
+            tr!("A passphrase is required (--passphrase or PKI_BACKUP_PASSPHRASE)")
+        ))
+}
+
+/// Master passphrase from `flag` or the given environment variable
+fn master_passphrase(flag_value: Option<String>, flag: &str, env_name: &str) -> io::Result<String> {
+    flag_value
+        .or_else(|| env::var(env_name).ok())
+        .filter(|passphrase| !passphrase.is_empty())
+        .ok_or_else(|| io::Error::new(
+            io::ErrorKind::InvalidInput,
+            tr!("A master passphrase is required ({} or {})", flag, env_name)
+        ))
+}
+
+/// Print a warning for every private key or CA directory anyone can read
+fn warn_world_readable(pki_config: &PKIConfig) {
+    let Ok(problems) = pki_config.permission_problems() else {
+        return;
+    };
+
+    for problem in problems.iter().filter(|problem| problem.is_world_readable()) {
+        warn!(path = %problem.path, mode = %format!("{:o}", problem.mode), "world-readable; run `pki doctor --fix`");
+    }
+}
+
+/// Run the example PKI setup flow
+fn run_demo(pki_config: &PKIConfig) -> io::Result<()> {
+    // Initialize PKI structure, CA Key and Self-Signed Certificate,
+    // keeping an existing CA
+    if !pki_config.init_ca()? {
+        println!("{}", tr!("Using existing CA in {}", pki_config.ca_dir));
+    }
+
+    // Example user: key, CSR and certificate, each only if missing
+    let test_user = "tudor_popov";
+    let subject = pki_config.subject_for_user(test_user, SubjectDn::default());
+    if !pki_config.ensure_user_certificate(test_user, &subject, None)? {
+        println!("{}", tr!("Certificate for {} already issued", test_user));
+    }
+
+    println!("{}", tr!("PKI Setup Complete!"));
+
+    Ok(())
+}
+
+fn main() {
+    // The message alone, not the `Custom { kind, error }` of an `io::Error`
+    if let Err(e) = run() {
+        eprintln!("{}", tr!("Error: {}", e));
+        std::process::exit(1);
+    }
+}
+
+/// Parse the command line and run the subcommand it names
+fn run() -> io::Result<()> {
+    // A usage error is reported in the requested output format, so the
+    // global flags are read from whatever clap could make of the arguments
+    let parsed = Cli::command().try_get_matches();
+    let global = match &parsed {
+        Ok(matches) => GlobalArgs::from_arg_matches(matches),
+        Err(_) => GlobalArgs::from_arg_matches(&Cli::command().ignore_errors(true).get_matches()),
+    }
+    .unwrap_or_default();
+
+    // Global flags are the last configuration layer, the dedicated ones
+    // below taking precedence over `--set`
+    let mut config = Config::load_for("pki")?;
+    for assignment in &global.assignments {
+        config.set_assignment(assignment, "pki")?;
+    }
+    let mut log_options = LogOptions::from_config(&config)?;
+    log_options.verbosity = global.verbose.min(3);
+    if let Some(name) = &global.log_format {
+        log_options.format = LogFormat::parse(name)?;
+    }
+    logging::init(&log_options);
+
+    if let Some(code) = &global.lang {
+        config.set("ui.lang", code);
+    }
+    i18n::init(Lang::from_config(&config)?, &[messages::MESSAGES]);
+    if let Some(name) = &global.output {
+        config.set("output.format", name);
+    }
+    if let Some(name) = &global.digest {
+        config.set("pki.digest", name);
+    }
+    if let Some(name) = &global.backend {
+        config.set("pki.backend", name);
+    }
+    let format: OutputFormat = config.parse("output.format")?;
+    let matches = match parsed {
+        Ok(matches) => matches,
+        // Help and version go to stdout as usual
+        Err(e) if format == OutputFormat::Json && e.use_stderr() => {
+            output::print_json_error("pki", &usage_error(&e));
+            std::process::exit(1);
+        }
+        Err(e) => e.exit(),
+    };
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let command = matches.subcommand_name().unwrap_or("pki");
+    let mut pki_config = PKIConfig::from_config(&config)?;
+
+    pki_config.force = global.force;
+    pki_config.policy_oids = global
+        .policy_oids
+        .iter()
+        .map(|oid| extensions::parse_policy_oid(oid))
+        .collect::<io::Result<_>>()?;
+    if global.dry_run {
+        // These only read state, talk to other parties or run indefinitely,
+        // so there is nothing meaningful to plan
+        if matches!(command, "verify" | "verify-chain" | "verify-dir" | "serve" | "acme" | "yubikey" | "doctor") {
             return Err(io::Error::new(
-                io::ErrorKind::Other, 
-                format!("Failed to sign document for user {}", username)
+                io::ErrorKind::InvalidInput,
+                tr!("--dry-run is not supported for {}", command)
             ));
         }
-
-        Ok(())
+        if format == OutputFormat::Json {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("--dry-run prints plain text and cannot be combined with --output json")
+            ));
+        }
+        exec::set_dry_run(true);
     }
 
-    /// Verify Document Signature
-    fn verify_document_signature(&self, username: &str, document_path: &str) -> io::Result<bool> {
-        let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
-        let signature_path = format!("{}.sig", document_path);
-        
-        let output = Command::new("openssl")
-            .args(&[
-                "dgst", "-sha256", 
-                "-verify", &user_cert_path,
-                "-signature", &signature_path,
-                document_path
-            ])
-            .output()?;
+    let mut out = CommandOutput::new(format, command);
+    let _span = tracing::info_span!("pki", command).entered();
+
+    // `pki doctor` reports these problems itself instead of failing on them,
+    // and `pki dsa`, `pki chain` and `pki convert` do their own arithmetic
+    let result = (|| {
+        if !matches!(command, "doctor" | "dsa" | "chain" | "convert") {
+            if pki_config.backend.needs_openssl() || !NATIVE_COMMANDS.contains(&command) {
+                openssl::require_openssl()?;
+            }
+            warn_world_readable(&pki_config);
+        }
+        run_command(&mut pki_config, cli.command, &mut out)
+    })();
 
-        Ok(output.status.success())
+    let succeeded = match result {
+        Ok(()) => out.finish(),
+        Err(e) if format == OutputFormat::Json => {
+            output::print_json_error(command, &e);
+            std::process::exit(1);
+        }
+        Err(e) => return Err(e),
+    };
+
+    if global.dry_run {
+        println!("{}", tr!("Dry run: nothing was written"));
     }
+    // A verification that failed, or an import with failed rows
+    if !succeeded {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// A clap usage error as one line, for the JSON error object
+fn usage_error(error: &clap::Error) -> io::Error {
+    let message = match error.get(ContextKind::InvalidSubcommand) {
+        Some(ContextValue::String(name)) => tr!("Unknown command: {}", name),
+        _ => {
+            // The paragraph above clap's usage line and tip
+            let rendered = error.render().to_string();
+            let paragraph: Vec<&str> = rendered.lines().take_while(|line| !line.is_empty()).map(str::trim).collect();
+            paragraph.join(" ").trim_start_matches("error: ").to_string()
+        }
+    };
+    io::Error::new(io::ErrorKind::InvalidInput, message)
 }
 
-fn main() -> io::Result<()> {
-    let pki_config = PKIConfig::new();
+/// Subcommands the native backend runs without the openssl binary (plain
+/// `pki sign`; timestamps and embedded signatures still use it)
+const NATIVE_COMMANDS: [&str; 4] = ["init", "issue", "user", "sign"];
 
-    // Initialize PKI structure
-    pki_config.init_pki_structure()?;
+/// Run one `pki` subcommand, reporting results through `out`
+fn run_command(pki_config: &mut PKIConfig, command: PkiCommand, out: &mut CommandOutput) -> io::Result<()> {
+    match command {
+        PkiCommand::Init => {
+            let created = pki_config.init_ca()?;
+            if created {
+                out.line(tr!("CA created in {}", pki_config.ca_dir));
+            } else {
+                out.line(tr!("CA already initialized in {}; use --force to replace it", pki_config.ca_dir));
+            }
+            out.field("created", created);
+            out.field("ca_certificate", format!("{}/ca_certificate.pem", pki_config.ca_dir));
+        }
+        PkiCommand::Encrypt { recipients, out: output_path, file: input_path } => {
+            let output_path = output_path.unwrap_or_else(|| format!("{}.p7m", input_path));
 
-    // Generate CA Key and Self-Signed Certificate
-    pki_config.generate_ca_key()?;
-    pki_config.create_ca_certificate()?;
+            pki_config.encrypt_for_users(&recipients, &input_path, &output_path)?;
+            out.line(tr!("Encrypted {} -> {}", input_path, output_path));
+            out.field("input", input_path);
+            out.field("output", output_path);
+            out.field("recipients", recipients);
+        }
+        PkiCommand::Decrypt { out: output_path, user: username, file: input_path } => {
+            let output_path = match output_path {
+                Some(path) => path,
+                None => match input_path.strip_suffix(".p7m") {
+                    Some(stripped) => stripped.to_string(),
+                    None => format!("{}.dec", input_path),
+                },
+            };
 
-    // Example user operations
-    let test_user = "tudor_popov";
-    
-    // Generate user key
-    pki_config.generate_user_key(test_user)?;
-    
-    // Generate CSR
-    pki_config.generate_csr(test_user)?;
-    
-    // Sign User Certificate
-    pki_config.sign_user_certificate(test_user)?;
-
-    println!("PKI Setup Complete!");
+            pki_config.decrypt_for_user(&username, &input_path, &output_path)?;
+            out.line(tr!("Decrypted {} -> {}", input_path, output_path));
+            out.field("input", input_path);
+            out.field("output", output_path);
+        }
+        PkiCommand::Sign { tsa, embed, user: username, file: document_path } => {
+            if embed {
+                let container_path = pki_config.sign_document_embedded(&username, &document_path)?;
+                out.line(tr!("Signed {} as {} into {}", document_path, username, container_path));
+                out.field("signer", username);
+                out.field("signed_document", container_path);
+                return Ok(());
+            }
+
+            if tsa.is_some() {
+                pki_config.tsa_url = tsa;
+            }
+
+            pki_config.sign_document(&username, &document_path)?;
+            out.line(tr!("Signed {} as {}", document_path, username));
+            out.field("signer", username);
+            out.field("signature", format!("{}.sig", document_path));
+
+            if let Some(tsa_url) = &pki_config.tsa_url {
+                let token_path = pki_config.timestamp_signature(&document_path, tsa_url)?;
+                out.line(tr!("Timestamp token stored in {}", token_path));
+                out.field("timestamp_token", token_path);
+            } else {
+                // A token from an earlier signature no longer matches
+                let stale_token_path = format!("{}.sig.tsr", document_path);
+                if Path::new(&stale_token_path).exists() {
+                    exec::remove_file(&stale_token_path)?;
+                }
+            }
+        }
+        PkiCommand::Cosign { user: username, file: document_path } => {
+            pki_config.cosign_document(&username, &document_path)?;
+            out.line(tr!("Added signature of {} to {}.p7s", username, document_path));
+            out.field("signer", username);
+            out.field("container", format!("{}.p7s", document_path));
+        }
+        PkiCommand::Verify { tsa_ca, required, args } => {
+            // A single .p7m argument is a document with its signature embedded
+            if let [container_path] = args.as_slice() {
+                if container_path.ends_with(".p7m") {
+                    let report = pki_config.verify_embedded_document(container_path)?;
+                    for signer in &report.signers {
+                        out.line(tr!("Signer: {}", signer));
+                    }
+                    if report.valid {
+                        out.line(tr!("Signature OK"));
+                    } else {
+                        out.line(tr!("Signature verification FAILED"));
+                    }
+                    out.verdict(report.valid);
+                    out.field("signers", report.signers);
+                    return Ok(());
+                }
+            }
+
+            // Any other single argument means a co-signed document container
+            let [username, document_path] = args.as_slice() else {
+                let report = pki_config.verify_cosigned_document(&args[0], &required)?;
+                for signer in &report.signers {
+                    out.line(tr!("Signer: {} ({})", signer.name, if signer.valid { tr!("OK") } else { tr!("FAILED") }));
+                }
+                for signer in &report.missing {
+                    out.line(tr!("Missing required signer: {}", signer));
+                }
+                let valid = report.valid && report.missing.is_empty();
+                if valid {
+                    out.line(tr!("Signature container OK"));
+                } else {
+                    out.line(tr!("Signature container verification FAILED"));
+                }
+                out.verdict(valid);
+                out.field("signers", report.signers.iter().map(|signer| json!({ "name": signer.name, "valid": signer.valid })).collect::<Vec<_>>());
+                out.field("missing_signers", report.missing);
+                return Ok(());
+            };
+            if tsa_ca.is_some() {
+                pki_config.tsa_ca_file = tsa_ca;
+            }
+
+            let report = pki_config.verify_document(username, document_path)?;
+            let signer = &report.signer;
+
+            out.line(tr!("Signature: {}", if report.signature_valid { tr!("OK") } else { tr!("FAILED") }));
+            out.line(tr!("Digest: {}", report.digest.as_deref().unwrap_or(tr!("unknown"))));
+            out.line(tr!("Signer: {} (serial {})", signer.subject, signer.info.serial));
+            out.line(tr!("Issuer: {}", signer.issuer));
+            out.line(tr!("Fingerprint: {}", signer.fingerprint));
+            if report.chain.valid {
+                out.line(tr!("Chain: OK ({})", report.chain.subjects.join(" <- ")));
+            } else {
+                out.line(tr!("Chain: FAILED ({})", report.chain.error.as_deref().unwrap_or(tr!("unknown error"))));
+            }
+            match report.timestamp {
+                TimestampCheck::Valid => out.line(tr!("Timestamp: OK ({})", format_unix_time(report.signing_time))),
+                TimestampCheck::Invalid => out.line(tr!("Timestamp: FAILED")),
+                TimestampCheck::Unverified => out.line(tr!("Timestamp: unverified (no --tsa-ca; checking validity as of now)")),
+                TimestampCheck::Absent => out.line(tr!("Timestamp: none (checking validity as of now)")),
+            }
+            out.line(tr!(
+                "Validity: {} to {} ({} at signing time)",
+                format_unix_time(signer.info.not_before),
+                format_unix_time(signer.info.not_after),
+                if report.valid_at_signing_time() { "valid" } else { "NOT valid" }
+            ));
+            match report.revoked_at {
+                Some(revoked_at) if report.revoked_before_signing() => {
+                    out.line(tr!("Revocation: revoked {} (no timestamp proves the signature predates it)", format_unix_time(revoked_at)));
+                }
+                Some(revoked_at) => {
+                    out.line(tr!("Revocation: revoked {} (after the timestamped signing time)", format_unix_time(revoked_at)));
+                }
+                None => out.line(tr!("Revocation: not revoked")),
+            }
+
+            let valid = report.is_valid();
+            if valid {
+                out.line(tr!("Signature OK"));
+            } else {
+                out.line(tr!("Signature verification FAILED"));
+            }
+            out.verdict(valid);
+            out.field("signature_valid", report.signature_valid);
+            out.field("digest", report.digest.clone());
+            out.field("signer", json!({
+                "username": username,
+                "subject": signer.subject,
+                "issuer": signer.issuer,
+                "serial": signer.info.serial,
+                "fingerprint": signer.fingerprint,
+                "not_before": signer.info.not_before,
+                "not_after": signer.info.not_after,
+                "valid_at_signing_time": report.valid_at_signing_time(),
+            }));
+            out.field("chain", json!({
+                "valid": report.chain.valid,
+                "subjects": report.chain.subjects,
+                "error": report.chain.error,
+            }));
+            out.field("signing_time", report.signing_time);
+            if report.timestamp != TimestampCheck::Absent {
+                out.field("timestamp", json!({
+                    "verified": report.timestamp != TimestampCheck::Unverified,
+                    "valid": report.timestamp == TimestampCheck::Valid,
+                    "time": report.signing_time_from_timestamp.then_some(report.signing_time),
+                }));
+            }
+            out.field("revocation", json!({
+                "revoked": report.revoked_at.is_some(),
+                "revoked_at": report.revoked_at,
+                "revoked_before_signing": report.revoked_before_signing(),
+            }));
+        }
+        PkiCommand::Extract { container: container_path, output } => {
+            let output_path = match output {
+                Some(output_path) => output_path,
+                None => embedded::embedded_document_path(&container_path)?,
+            };
+
+            let report = pki_config.extract_document(&container_path, &output_path)?;
+            out.line(tr!("Signed by: {}", report.signers.join(", ")));
+            out.line(tr!("Extracted {} to {}", container_path, output_path));
+            out.field("signers", report.signers);
+            out.field("document", output_path);
+        }
+        PkiCommand::Codesign { action: Some(CodesignAction::Verify { user: username, binary: binary_path }), .. } => {
+            if exec::is_dry_run() {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("--dry-run is not supported for codesign verify")));
+            }
+            let report = pki_config.verify_codesign(&username, &binary_path)?;
+            let signer = &report.signature.signer;
+
+            out.line(tr!("Signature: {}", if report.signature.signature_valid { tr!("OK") } else { tr!("FAILED") }));
+            out.line(tr!("Signer: {} (serial {})", signer.subject, signer.info.serial));
+            out.line(tr!("Code signing usage: {}", if report.code_signing_usage { tr!("OK") } else { tr!("MISSING") }));
+            out.line(tr!(
+                "Chain: {}",
+                if report.signature.chain.valid { "OK" } else { report.signature.chain.error.as_deref().unwrap_or("FAILED") }
+            ));
+            if report.signature.revoked_before_signing() {
+                out.line(tr!("Revocation: signer certificate REVOKED"));
+            }
+            out.line(tr!(
+                "Binary: {} ({} bytes, {} {})",
+                if report.binary_matches { "matches the manifest" } else { "MODIFIED" },
+                report.manifest.size, report.manifest.digest.name(), report.manifest.hash
+            ));
+            if !report.manifest_matches {
+                out.line(tr!(
+                    "Manifest is for {} signed by {}, not this binary and signer",
+                    report.manifest.file, report.manifest.signer
+                ));
+            }
+            out.line(if report.is_valid() { tr!("Code signature OK") } else { tr!("Code signature verification FAILED") });
+
+            out.verdict(report.is_valid());
+            out.field("signature_valid", report.signature.signature_valid);
+            out.field("code_signing_usage", report.code_signing_usage);
+            out.field("chain_valid", report.signature.chain.valid);
+            out.field("revoked", report.signature.revoked_before_signing());
+            out.field("manifest_matches", report.manifest_matches);
+            out.field("binary_matches", report.binary_matches);
+            out.field("digest", report.manifest.digest.name());
+            out.field("hash", report.manifest.hash.as_str());
+        }
+        PkiCommand::Codesign { action: None, tsa, user, binary } => {
+            // clap requires both without the verify action
+            let (username, binary_path) = (user.unwrap_or_default(), binary.unwrap_or_default());
+
+            let manifest_path = pki_config.codesign(&username, &binary_path)?;
+            out.line(tr!("Signed manifest of {} written to {}", binary_path, manifest_path));
+            out.field("signer", username);
+            out.field("manifest", manifest_path.as_str());
+            out.field("signature", format!("{}.sig", manifest_path));
+
+            let stale_token_path = format!("{}.sig.tsr", manifest_path);
+            if let Some(tsa_url) = &tsa {
+                let token_path = pki_config.timestamp_signature(&manifest_path, tsa_url)?;
+                out.line(tr!("Timestamp token stored in {}", token_path));
+                out.field("timestamp_token", token_path);
+            } else if Path::new(&stale_token_path).exists() {
+                // A token from an earlier signature no longer matches
+                exec::remove_file(&stale_token_path)?;
+            }
+        }
+        PkiCommand::SignDir { user: username, dir: dir_path } => {
+            let (manifest_path, merkle_root) = pki_config.sign_directory(&username, &dir_path)?;
+            out.line(tr!("Signed manifest of {} written to {}", dir_path, manifest_path));
+            out.line(tr!("Merkle root: {}", merkle_root));
+            out.field("signer", username);
+            out.field("manifest", manifest_path);
+            out.field("merkle_root", merkle_root);
+        }
+        PkiCommand::VerifyDir { user: username, dir: dir_path } => {
+            let report = pki_config.verify_directory(&username, &dir_path)?;
+            for path in &report.diff.added {
+                out.line(tr!("Added: {}", path));
+            }
+            for path in &report.diff.removed {
+                out.line(tr!("Removed: {}", path));
+            }
+            for path in &report.diff.modified {
+                out.line(tr!("Modified: {}", path));
+            }
+            if !report.signature_valid {
+                out.line(tr!("Manifest signature verification FAILED"));
+            } else if report.diff.is_empty() {
+                out.line(tr!("Directory OK"));
+            } else {
+                out.line(tr!("Directory contents differ from the signed manifest"));
+            }
+            out.verdict(report.signature_valid && report.diff.is_empty());
+            out.field("signature_valid", report.signature_valid);
+            out.field("added", report.diff.added);
+            out.field("removed", report.diff.removed);
+            out.field("modified", report.diff.modified);
+            out.field("merkle_root", report.merkle_root);
+        }
+        PkiCommand::SignCsr { profile, file: csr_path } => {
+            let profile = profile.map(|name| CertificateProfile::parse(&name)).transpose()?;
+
+            let username = pki_config.sign_external_csr(&csr_path, profile, SanPolicy::Configured, ExistingCertificate::Confirm)?;
+            let certificate_path = format!("{}/{}_certificate.pem", pki_config.users_dir, username);
+            out.line(tr!("Issued certificate {}", certificate_path));
+            out.field("username", username);
+            out.field("certificate", certificate_path);
+        }
+        PkiCommand::Watch { action, once, interval, outbox, subject_pattern, min_key_bits, require_approval, profile, incoming_dir } => {
+            match action {
+                Some(WatchAction::List { incoming_dir }) => {
+                    let pending = pki_config.pending_csrs(&incoming_dir)?;
+                    for file in &pending {
+                        out.line(file);
+                    }
+                    out.line(tr!("{} CSR(s) awaiting approval", pending.len()));
+                    out.field("pending", pending);
+                }
+                Some(WatchAction::Approve { outbox, profile, incoming_dir, file }) => {
+                    let policy = WatchPolicy {
+                        subject_pattern: None,
+                        min_key_bits: None,
+                        require_approval: false,
+                        profile: profile.map(|name| CertificateProfile::parse(&name)).transpose()?,
+                        outbox,
+                    };
+                    let approved = pki_config.approve_pending_csr(&incoming_dir, &file, &policy)?;
+                    out.line(approved.describe());
+                    out.field("processed", vec![approved.to_json()]);
+                }
+                Some(WatchAction::Reject { reason, incoming_dir, file }) => {
+                    let rejected = pki_config.reject_pending_csr(&incoming_dir, &file, &reason)?;
+                    out.line(rejected.describe());
+                    out.field("processed", vec![rejected.to_json()]);
+                }
+                None => {
+                    // clap requires the directory without an action
+                    let incoming_dir = incoming_dir.unwrap_or_default();
+                    if exec::is_dry_run() && !once {
+                        return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("--dry-run requires --once for watch")));
+                    }
+                    let policy = WatchPolicy {
+                        subject_pattern,
+                        min_key_bits,
+                        require_approval,
+                        profile: profile.map(|name| CertificateProfile::parse(&name)).transpose()?,
+                        outbox,
+                    };
+
+                    if once {
+                        let processed = pki_config.process_incoming_csrs(&incoming_dir, &policy)?;
+                        for csr in &processed {
+                            out.line(csr.describe());
+                        }
+                        out.field("processed", processed.iter().map(|csr| csr.to_json()).collect::<Vec<_>>());
+                        return Ok(());
+                    }
+
+                    out.line(tr!("Watching {} for CSRs every {}s", incoming_dir, interval));
+                    loop {
+                        for csr in pki_config.process_incoming_csrs(&incoming_dir, &policy)? {
+                            // Each CSR is reported as it is handled, one JSON object per line
+                            if out.is_json() {
+                                println!("{}", csr.to_json());
+                            } else {
+                                println!("{}", csr.describe());
+                            }
+                        }
+                        thread::sleep(Duration::from_secs(interval));
+                    }
+                }
+            }
+        }
+        PkiCommand::Inspect { user: username } => {
+            let details = pki_config.inspect_certificate(&username, 30)?;
+            let info = &details.info;
+
+            out.line(tr!("Subject:     {}", details.subject));
+            out.line(tr!("Issuer:      {}", details.issuer));
+            out.line(tr!("Serial:      {}", info.serial));
+            out.line(tr!("Not before:  {}", format_unix_time(info.not_before)));
+            out.line(tr!("Not after:   {}", format_unix_time(info.not_after)));
+            out.line(tr!("Status:      {}", tr!(info.status.name())));
+            if let Some(usage) = &details.extended_key_usage {
+                out.line(tr!("Usage:       {}", usage));
+            }
+            out.line(tr!("SHA-256:     {}", details.fingerprint));
+
+            out.field("username", info.username.as_str());
+            out.field("subject", details.subject.as_str());
+            out.field("issuer", details.issuer.as_str());
+            out.field("serial", info.serial.as_str());
+            out.field("not_before", info.not_before);
+            out.field("not_after", info.not_after);
+            out.field("status", info.status.name());
+            out.field("extended_key_usage", details.extended_key_usage.clone());
+            out.field("sha256_fingerprint", details.fingerprint.as_str());
+        }
+        PkiCommand::List { days: warn_days } => {
+            let inventory = pki_config.certificate_inventory(warn_days)?;
+
+            out.line(format!(
+                "{:<20} {:<10} {:<23}  {:<23}  {}",
+                tr!("USER"), tr!("STATUS"), tr!("VALID FROM"), tr!("VALID UNTIL"), tr!("SERIAL")
+            ));
+            for info in &inventory {
+                out.line(format!(
+                    "{:<20} {:<10} {:<23}  {:<23}  {}",
+                    info.username,
+                    tr!(info.status.name()),
+                    format_unix_time(info.not_before),
+                    format_unix_time(info.not_after),
+                    info.serial,
+                ));
+            }
+            out.field("certificates", inventory.iter().map(|info| json!({
+                "username": info.username,
+                "serial": info.serial,
+                "not_before": info.not_before,
+                "not_after": info.not_after,
+                "status": info.status.name(),
+            })).collect::<Vec<_>>());
+        }
+        PkiCommand::Expiring { days: within_days } => {
+            let expiring = pki_config.expiring_certificates(within_days)?;
+
+            if expiring.is_empty() {
+                out.line(tr!("No certificates expire within {} days", within_days));
+            } else {
+                out.line(format!("{:<20} {:<10} {:<23}  {}", tr!("USER"), tr!("STATUS"), tr!("VALID UNTIL"), tr!("DAYS LEFT")));
+            }
+            for info in &expiring {
+                out.line(format!(
+                    "{:<20} {:<10} {:<23}  {}",
+                    info.username,
+                    tr!(info.status.name()),
+                    format_unix_time(info.not_after),
+                    info.days_left(),
+                ));
+            }
+            out.field("days", within_days);
+            out.field("certificates", expiring.iter().map(|info| json!({
+                "username": info.username,
+                "serial": info.serial,
+                "not_after": info.not_after,
+                "days_left": info.days_left(),
+                "status": info.status.name(),
+            })).collect::<Vec<_>>());
+        }
+        PkiCommand::ExportChain => {
+            let written = pki_config.export_chain()?;
+            for path in &written {
+                out.line(tr!("Wrote {}", path));
+            }
+            out.field("written", written);
+        }
+        PkiCommand::VerifyChain { user: username } => {
+            let verification = pki_config.verify_certificate_chain(&username)?;
+
+            let check = |passed: bool| if passed { tr!("OK") } else { tr!("FAILED") };
+            for link in &verification.links {
+                out.line(link.subject.clone());
+                out.line(tr!(
+                    "  signature {}, valid {} to {} {}, basicConstraints {}",
+                    check(link.signature_valid),
+                    format_unix_time(link.not_before),
+                    format_unix_time(link.not_after),
+                    check(link.within_validity),
+                    check(link.constraints_valid)
+                ));
+            }
+            if !verification.anchored {
+                let last_issuer = verification.links.last().map(|link| link.issuer.as_str()).unwrap_or_default();
+                out.line(tr!("Issuer {} not found among the CA certificates", last_issuer));
+            }
+            if verification.is_valid() {
+                out.line(tr!("Chain of {} OK", username));
+            } else {
+                out.line(tr!("Chain of {} verification FAILED", username));
+            }
+            out.verdict(verification.is_valid());
+            out.field("anchored", verification.anchored);
+            out.field("links", verification.links.iter().map(|link| json!({
+                "certificate": link.path,
+                "subject": link.subject,
+                "issuer": link.issuer,
+                "not_before": link.not_before,
+                "not_after": link.not_after,
+                "signature_valid": link.signature_valid,
+                "within_validity": link.within_validity,
+                "constraints_valid": link.constraints_valid,
+                "valid": link.is_valid(),
+            })).collect::<Vec<_>>());
+        }
+        PkiCommand::Backup { out: output_path, passphrase } => {
+            pki_config.backup(&output_path, &backup_passphrase(passphrase)?)?;
+            out.line(tr!("Encrypted backup written to {}", output_path));
+            out.field("backup", output_path);
+        }
+        PkiCommand::Restore { passphrase, backup: backup_path } => {
+            pki_config.restore(&backup_path, &backup_passphrase(passphrase)?)?;
+            out.line(tr!("Restored PKI from {}", backup_path));
+            out.field("backup", backup_path);
+        }
+        PkiCommand::Ca { action: CaAction::Rotate { cross_sign, mark_renewal } } => {
+            let report = pki_config.rotate_ca(cross_sign, !mark_renewal)?;
+            out.line(tr!("New CA key and certificate created; old CA archived in {}", report.archive_dir));
+            if let Some(path) = &report.cross_signed {
+                out.line(tr!("Cross-signed certificate written to {}", path));
+            }
+            for username in &report.reissued {
+                out.line(tr!("Re-issued certificate for {}", username));
+            }
+            if !report.pending_renewal.is_empty() {
+                out.line(tr!(
+                    "Marked for renewal in {}: {}",
+                    pki_config.pending_renewal_path(),
+                    report.pending_renewal.join(", ")
+                ));
+            }
+            out.field("archive_dir", report.archive_dir);
+            out.field("cross_signed", report.cross_signed);
+            out.field("reissued", report.reissued);
+            out.field("pending_renewal", report.pending_renewal);
+        }
+        PkiCommand::Ca { action: CaAction::Intermediate { permitted, excluded, name } } => {
+            let constraints = NameConstraints { permitted, excluded };
+            let cert_path = pki_config.create_sub_ca(&name, &constraints)?;
+            out.line(tr!("Sub-CA {} certificate written to {}", name, cert_path));
+            out.field("name", name);
+            out.field("certificate", cert_path);
+            out.field("permitted", constraints.permitted);
+            out.field("excluded", constraints.excluded);
+            out.field("policies", pki_config.policy_oids.clone());
+        }
+        PkiCommand::Ca { action: CaAction::Split { threshold, count, remove_key } } => {
+            let share_paths = pki_config.split_ca_key(threshold, count, remove_key)?;
+            out.line(tr!("CA key split into {} shares, any {} of which restore it:", count, threshold));
+            for path in &share_paths {
+                out.line(format!("  {}", path));
+            }
+            if remove_key {
+                out.line(tr!("The CA key file was removed; run `pki ca combine` with the shares to sign again"));
+            } else {
+                out.line(tr!("Hand one share to each holder, then remove the key with --remove-key or by hand"));
+            }
+            out.field("threshold", threshold);
+            out.field("shares", share_paths);
+            out.field("key_removed", remove_key);
+        }
+        PkiCommand::Ca { action: CaAction::Combine { shares } } => {
+            let key_path = pki_config.combine_ca_key(&shares)?;
+            out.line(tr!("CA key restored to {} from {} shares", key_path, shares.len()));
+            out.field("key", key_path);
+        }
+        PkiCommand::CaKey => {
+            out.line(tr!("CA key: {}", pki_config.ca_signer.describe()));
+            out.field("ca_key", pki_config.ca_signer.describe());
+        }
+        PkiCommand::Yubikey { action: YubikeyAction::Provision { slot, on_device, user: username } } => {
+            pki_config.provision_yubikey(&username, &slot, on_device)?;
+            out.line(tr!("Provisioned {} into PIV slot {}", username, slot));
+            out.field("username", username);
+            out.field("slot", slot);
+        }
+        PkiCommand::Ssh { action: SshAction::SignKey { principals, identity, validity, public_key: public_key_path } } => {
+            let identity = identity.unwrap_or_else(|| {
+                Path::new(&public_key_path)
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            });
+            let validity = validity.unwrap_or_else(|| format!("+{}d", pki_config.user_validity_days));
+
+            let certificate_path = pki_config.sign_ssh_key(&public_key_path, &identity, &principals, &validity)?;
+            out.line(tr!("SSH certificate written to {}", certificate_path));
+            out.field("certificate", certificate_path);
+            out.field("identity", identity);
+            out.field("principals", principals);
+        }
+        PkiCommand::Ssh { action: SshAction::CaPubkey } => {
+            let public_key = pki_config.ssh_ca_public_key()?;
+            out.line(&public_key);
+            out.field("public_key", public_key);
+        }
+        PkiCommand::ExportJava { kind, format, out: output_path, storepass } => {
+            let format = match format {
+                Some(name) => KeystoreFormat::from_name(&name).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, tr!("Unknown keystore format {}", name))
+                })?,
+                None => KeystoreFormat::Pkcs12,
+            };
+            let storepass = storepass
+                .or_else(|| env::var("PKI_KEYSTORE_PASSWORD").ok())
+                .ok_or_else(|| io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    tr!("A store password is required (--storepass or PKI_KEYSTORE_PASSWORD)")
+                ))?;
+
+            match kind {
+                KeystoreKind::Truststore => {
+                    let output_path = output_path.unwrap_or_else(|| format!("truststore.{}", format.extension()));
+                    pki_config.export_truststore(&output_path, format, &storepass)?;
+                    out.line(tr!("Truststore written to {}", output_path));
+                    out.field("keystore", output_path);
+                }
+                KeystoreKind::Identity { user: username } => {
+                    let output_path = output_path.unwrap_or_else(|| format!("{}.{}", username, format.extension()));
+                    pki_config.export_identity_keystore(&username, &output_path, format, &storepass)?;
+                    out.line(tr!("Keystore for {} written to {}", username, output_path));
+                    out.field("username", username);
+                    out.field("keystore", output_path);
+                }
+            }
+            out.field("format", format.extension());
+        }
+        PkiCommand::Serve { listen, token, tls_cert, tls_key, require_client_cert, challenge_port } => {
+            let settings = ServerSettings {
+                listen: server::normalize_listen_address(listen.as_ref().unwrap_or(&pki_config.listen)),
+                token: token.or_else(|| env::var("PKI_API_TOKEN").ok()),
+                tls_cert,
+                tls_key,
+                require_client_cert,
+                acme_challenge_port: challenge_port,
+            };
+            pki_config.serve(settings)?;
+        }
+        PkiCommand::Acme {
+            action: AcmeAction::Enroll { server: server_url, ca_cert, token, challenge_port, profile, subject_alt_names, renew, user: username },
+        } => {
+            let profile = profile.map(|name| CertificateProfile::parse(&name)).transpose()?;
+            let ca_cert_path = ca_cert.unwrap_or_else(|| format!("{}/ca_certificate.pem", pki_config.ca_dir));
+            let token = token.or_else(|| env::var("PKI_API_TOKEN").ok());
+            let server = AcmeServer { url: &server_url, token: token.as_deref(), ca_cert_path: &ca_cert_path };
+
+            let certificate_path = pki_config.acme_enroll(&server, &username, challenge_port, profile, &subject_alt_names, renew)?;
+            out.line(tr!("Certificate for {} written to {}", username, certificate_path));
+            out.field("username", username);
+            out.field("certificate", certificate_path);
+        }
+        PkiCommand::Report { output_dir, days: warn_days } => {
+            let index_path = pki_config.write_html_report(&output_dir, warn_days)?;
+            out.line(tr!("Dashboard written to {}", index_path));
+            out.field("report", index_path);
+        }
+        PkiCommand::Metrics { days: warn_days, out: output_file } => {
+            let metrics = pki_config.collect_metrics(warn_days)?;
+            match output_file {
+                // For node_exporter's textfile collector, which expects the file to be replaced whole
+                Some(output_file) => {
+                    let partial_file = format!("{}.tmp", output_file);
+                    exec::write(&partial_file, metrics.to_prometheus())?;
+                    exec::rename(&partial_file, &output_file)?;
+                    out.line(tr!("Metrics written to {}", output_file));
+                    out.field("file", output_file);
+                }
+                None => out.line(metrics.to_prometheus().trim_end()),
+            }
+            out.field("metrics", metrics.to_json());
+        }
+        PkiCommand::Notify { smtp: smtp_server, starttls, smtp_ca, from, windows, csv } => {
+            let smtp = SmtpSettings {
+                server: smtp_server,
+                from: from.unwrap_or_else(|| String::from("pki@localhost")),
+                starttls,
+                ca_cert: smtp_ca.unwrap_or_else(|| format!("{}/ca_certificate.pem", pki_config.ca_dir)),
+                credentials: env::var("PKI_SMTP_USER").ok().zip(env::var("PKI_SMTP_PASSWORD").ok()),
+            };
+            let windows = NotifyWindows::parse(&windows)?;
+
+            let notices = pki_config.expiry_notices(&windows, csv.as_deref())?;
+            let deliveries = pki_config.send_expiry_notices(&smtp, &notices)?;
+
+            for (notice, delivery) in notices.iter().zip(&deliveries) {
+                let outcome = match delivery {
+                    Delivery::Sent => tr!("notified {}", notice.email.as_deref().unwrap_or_default()),
+                    Delivery::AlreadySent => tr!("already notified").to_string(),
+                    Delivery::NoAddress => tr!("SKIPPED (no email address)").to_string(),
+                    Delivery::Failed(e) => tr!("FAILED ({})", e),
+                };
+                out.line(tr!("{} expires in {} days  {}", format!("{:<20}", notice.username), format!("{:>3}", notice.days_left()), outcome));
+            }
+            let sent = deliveries.iter().filter(|delivery| matches!(delivery, Delivery::Sent)).count();
+            out.line(tr!("Sent {} of {} expiry notices", sent, notices.len()));
+            out.field("sent", sent);
+            out.field("notices", notices.iter().zip(&deliveries).map(|(notice, delivery)| json!({
+                "username": notice.username,
+                "serial": notice.serial,
+                "profile": notice.profile.map(|profile| profile.name()),
+                "email": notice.email,
+                "not_after": notice.not_after,
+                "delivery": delivery.name(),
+                "error": match delivery {
+                    Delivery::Failed(e) => Some(e.to_string()),
+                    _ => None,
+                },
+            })).collect::<Vec<_>>());
+        }
+        PkiCommand::Trust { action } => {
+            let install = matches!(action, TrustAction::Install);
+
+            let store = TrustStore::detect()?;
+            if install {
+                pki_config.install_ca_trust(store)?;
+                out.line(tr!("CA certificate installed into the {}", store.name()));
+            } else {
+                pki_config.uninstall_ca_trust(store)?;
+                out.line(tr!("CA certificate removed from the {}", store.name()));
+            }
+            out.field("store", store.name());
+            out.field("installed", install);
+        }
+        PkiCommand::Demo { kind: None } => run_demo(pki_config)?,
+        PkiCommand::Demo { kind: Some(DemoKind::Tls { mtls }) } => {
+            if exec::is_dry_run() {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("--dry-run is not supported for demo tls")));
+            }
+            // The demo certificates are throwaway and the previous ones are archived anyway
+            pki_config.force = true;
+            let report = pki_config.run_tls_demo(mtls)?;
+
+            out.line(tr!("Server certificate {} (localhost, 127.0.0.1)", report.server_cert));
+            out.line(tr!("Serving HTTPS on {}", report.address));
+            if let Some(reason) = &report.anonymous_rejection {
+                out.line(tr!("Client without a certificate: rejected ({})", reason));
+            }
+            out.line(tr!("Handshake: {} with {}", report.protocol, report.cipher_suite));
+            out.line(tr!("Server identity verified against {}/ca_certificate.pem", pki_config.ca_dir));
+            out.line(tr!(
+                "Client certificate: {}",
+                if report.client_authenticated { "presented and verified" } else { "not requested" }
+            ));
+            out.line(tr!("Response: {}", report.response));
+
+            out.field("address", report.address);
+            out.field("server_certificate", report.server_cert);
+            out.field("protocol", report.protocol);
+            out.field("cipher_suite", report.cipher_suite);
+            out.field("anonymous_rejection", report.anonymous_rejection);
+            out.field("client_authenticated", report.client_authenticated);
+            out.field("response", report.response);
+        }
+        PkiCommand::Doctor { fix } => {
+            let openssl_version = match openssl::require_openssl() {
+                Ok(version) => {
+                    out.line(tr!("OpenSSL {}", version));
+                    Some(version)
+                }
+                Err(e) => {
+                    out.line(tr!("OpenSSL: {}", e));
+                    None
+                }
+            };
+            out.field("openssl_version", openssl_version.map(|version| version.to_string()));
+            out.line(tr!("Backend: {}", pki_config.backend.name()));
+            out.field("backend", pki_config.backend.name());
+
+            let problems = pki_config.permission_problems()?;
+            for problem in &problems {
+                out.line(tr!(
+                    "{}: mode {} allows access by {}",
+                    problem.path,
+                    format!("{:o}", problem.mode),
+                    if problem.is_world_readable() { "all users" } else { "group or other users" },
+                ));
+            }
+            out.field("permission_problems", problems.iter().map(|problem| json!({
+                "path": problem.path,
+                "mode": format!("{:o}", problem.mode),
+                "world_readable": problem.is_world_readable(),
+            })).collect::<Vec<_>>());
+
+            if openssl_version.is_none() && pki_config.backend.needs_openssl() {
+                return Err(io::Error::other(tr!("OpenSSL 3 is required")));
+            }
+
+            if problems.is_empty() {
+                out.line(tr!("Permissions OK"));
+            } else if fix {
+                pki_config.fix_permissions()?;
+                out.line(tr!("Restricted {} path(s) to their owner", problems.len()));
+                out.field("fixed", true);
+            } else {
+                return Err(io::Error::other(tr!(
+                    "{} permission problem(s) found; rerun with --fix to restrict them",
+                    problems.len()
+                )));
+            }
+        }
+        PkiCommand::User { action: UserAction::Import { jobs, csv: csv_path } } => {
+            let jobs = jobs.unwrap_or_else(|| thread::available_parallelism().map(usize::from).unwrap_or(1));
+
+            let results = pki_config.import_users(&csv_path, jobs)?;
+            let mut failures = 0;
+
+            for row in &results {
+                match &row.result {
+                    Ok(()) => out.line(tr!("line {}: {} OK", row.line, row.username)),
+                    Err(e) => {
+                        failures += 1;
+                        out.line(tr!("line {}: {} FAILED ({})", row.line, row.username, e));
+                    }
+                }
+            }
+            out.line(tr!(
+                "Provisioned {} of {} users ({} failed)",
+                results.len() - failures, results.len(), failures
+            ));
+            out.field("provisioned", results.len() - failures);
+            out.field("failed", failures);
+            if failures > 0 {
+                out.fail();
+            }
+            out.field("users", results.iter().map(|row| json!({
+                "line": row.line,
+                "username": row.username,
+                "ok": row.result.is_ok(),
+                "error": row.result.as_ref().err().map(|e| e.to_string()),
+            })).collect::<Vec<_>>());
+        }
+        PkiCommand::Log { action: None } => {
+            let entries = pki_config.log_entries()?;
+            let (tree_size, root_hash) = pki_config.log_tree_head()?;
+            for (index, entry) in entries.iter().enumerate() {
+                out.line(format!(
+                    "{:>4}  {}  {}  {}",
+                    index, format_unix_time(entry.logged_at), entry.serial, entry.subject
+                ));
+            }
+            out.line(tr!("Tree size: {}", tree_size));
+            out.line(tr!("Root hash: {}", encode_hex(&root_hash)));
+            out.field("entries", entries.iter().map(|entry| json!({
+                "leaf_hash": encode_hex(&entry.leaf_hash),
+                "logged_at": entry.logged_at,
+                "serial": entry.serial,
+                "subject": entry.subject,
+            })).collect::<Vec<_>>());
+            out.field("tree_size", tree_size);
+            out.field("root_hash", encode_hex(&root_hash));
+        }
+        PkiCommand::Log { action: Some(LogAction::Prove { user: username }) } => {
+            let proof = pki_config.inclusion_proof(&username)?;
+            let proof_path = format!("{}/{}_inclusion.json", pki_config.users_dir, username);
+            exec::write(&proof_path, format!("{:#}\n", proof.to_json()))?;
+
+            out.line(tr!("Leaf {} of {} (root {})", proof.leaf_index, proof.tree_size, encode_hex(&proof.root_hash)));
+            for (level, hash) in proof.audit_path.iter().enumerate() {
+                out.line(tr!("  path[{}] {}", level, encode_hex(hash)));
+            }
+            out.line(tr!("Inclusion proof written to {}", proof_path));
+            out.field("proof", proof.to_json());
+            out.field("proof_file", proof_path);
+        }
+        PkiCommand::Log { action: Some(LogAction::Verify { user: username, proof: proof_path }) } => {
+            let proof = match proof_path {
+                Some(proof_path) => {
+                    let value = serde_json::from_str(&fs::read_to_string(proof_path)?).map_err(io::Error::other)?;
+                    transparency::InclusionProof::from_json(&value)?
+                }
+                None => pki_config.inclusion_proof(&username)?,
+            };
+
+            let valid = pki_config.verify_inclusion_proof(&username, &proof)?;
+            if valid {
+                out.line(tr!(
+                    "Certificate of {} is entry {} in the log's first {} entries (root {})",
+                    username, proof.leaf_index, proof.tree_size, encode_hex(&proof.root_hash)
+                ));
+            } else {
+                out.line(tr!("Inclusion proof verification FAILED"));
+            }
+            out.verdict(valid);
+            out.field("proof", proof.to_json());
+        }
+        PkiCommand::Log { action: Some(LogAction::Consistency { old_size, old_root }) } => {
+            let remembered_root = old_root
+                .map(|root| {
+                    transparency::from_hex(&root)
+                        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid root hash {}", root)))
+                })
+                .transpose()?;
+
+            // Without a remembered root this only shows the proof; with
+            // one it checks the log was not rewritten since
+            let proof = pki_config.consistency_proof(old_size)?;
+            let valid = proof.is_valid() && remembered_root.is_none_or(|root| root == proof.old_root);
+            out.line(tr!("Entries 1-{} (root {})", proof.old_size, encode_hex(&proof.old_root)));
+            out.line(tr!("Entries 1-{} (root {})", proof.new_size, encode_hex(&proof.new_root)));
+            for (level, hash) in proof.path.iter().enumerate() {
+                out.line(tr!("  path[{}] {}", level, encode_hex(hash)));
+            }
+            if valid {
+                out.line(tr!("The current log extends the older one without changing it"));
+            } else {
+                out.line(tr!("Consistency proof verification FAILED: the log was rewritten"));
+            }
+            out.verdict(valid);
+            out.field("old_size", proof.old_size);
+            out.field("old_root", encode_hex(&proof.old_root));
+            out.field("new_size", proof.new_size);
+            out.field("new_root", encode_hex(&proof.new_root));
+            out.field("path", proof.path.iter().map(|hash| encode_hex(hash)).collect::<Vec<_>>());
+        }
+        PkiCommand::Keys { action } => {
+            match action {
+                KeysAction::Protect { passphrase } => {
+                    let passphrase = master_passphrase(passphrase, "--passphrase", "PKI_MASTER_PASSPHRASE")?;
+                    let encrypted = pki_config.protect_user_keys(&passphrase)?;
+                    out.line(tr!(
+                        "{} user key(s) encrypted under the master key in {}/master_key.json",
+                        encrypted.len(),
+                        pki_config.ca_dir
+                    ));
+                    out.line(tr!("Set PKI_MASTER_PASSPHRASE to sign, decrypt or issue with user keys"));
+                    out.field("encrypted", encrypted);
+                }
+                KeysAction::Unprotect { passphrase } => {
+                    let passphrase = master_passphrase(passphrase, "--passphrase", "PKI_MASTER_PASSPHRASE")?;
+                    let decrypted = pki_config.unprotect_user_keys(&passphrase)?;
+                    out.line(tr!("{} user key(s) decrypted; the master key was removed", decrypted.len()));
+                    out.field("decrypted", decrypted);
+                }
+                KeysAction::ChangePassphrase { passphrase, new_passphrase } => {
+                    let passphrase = master_passphrase(passphrase, "--passphrase", "PKI_MASTER_PASSPHRASE")?;
+                    let new_passphrase = master_passphrase(new_passphrase, "--new-passphrase", "PKI_NEW_MASTER_PASSPHRASE")?;
+                    pki_config.change_master_passphrase(&passphrase, &new_passphrase)?;
+                    out.line(tr!("Master key re-wrapped under the new passphrase"));
+                }
+            }
+            out.field("protected", pki_config.user_keys_protected());
+        }
+        PkiCommand::Dsa { action } => match action {
+            DsaAction::Keygen { sizes: DsaSizes { l_bits, n_bits }, user: username } => {
+                warn_unless_standard_dsa_size(l_bits, n_bits);
+                let key = pki_config.generate_dsa_key(&username, l_bits, n_bits)?;
+                let key_path = pki_config.dsa_key_path(&username);
+                out.line(tr!("DSA key for {} ({}-bit p, {}-bit q) written to {}", username, l_bits, n_bits, key_path));
+                out.field("username", username);
+                out.field("key", key_path);
+                out.field("y", key.y.to_str_radix(16));
+            }
+            DsaAction::Sign { user: username, file: document_path } => {
+                let signature_path = pki_config.dsa_sign_document(&username, &document_path)?;
+                out.line(tr!("Signed {} as {} into {}", document_path, username, signature_path));
+                out.field("signer", username);
+                out.field("signature", signature_path);
+            }
+            DsaAction::Verify { user: username, file: document_path } => {
+                let valid = pki_config.dsa_verify_document(&username, &document_path)?;
+                out.line(if valid { "DSA signature valid" } else { "DSA signature INVALID" });
+                out.field("signer", username);
+                out.field("valid", valid);
+                if !valid {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, tr!("DSA signature verification failed")));
+                }
+            }
+            DsaAction::Attack { kind: DsaAttack::RepeatedK { sizes: DsaSizes { l_bits, n_bits } } } => {
+                warn_unless_standard_dsa_size(l_bits, n_bits);
+                let report = dsa::repeated_nonce_demo(l_bits, n_bits)?;
+                for (message, signature) in report.messages.iter().zip(&report.signatures) {
+                    out.line(format!("\"{}\": r = {}, s = {}", message, signature.r.to_str_radix(16), signature.s.to_str_radix(16)));
+                }
+                out.line(tr!("Both signatures share r, so both used the same k:"));
+                out.line(tr!("  k = (H₁ − H₂)/(s₁ − s₂) mod q, then x = (s₁·k − H₁)/r mod q"));
+                let recovered = report.recovered_x.as_ref() == Some(&report.actual_x);
+                match &report.recovered_x {
+                    Some(x) => out.line(tr!("Recovered x = {} (matches the real key: {})", x.to_str_radix(16), recovered)),
+                    None => out.line(tr!("The recovered x does not reproduce y")),
+                }
+                out.field("r", report.signatures[0].r.to_str_radix(16));
+                out.field("recovered", recovered);
+            }
+        },
+        PkiCommand::Chain { action } => match action {
+            ChainAction::Init { difficulty } => {
+                pki_config.init_chain(difficulty)?;
+                out.line(tr!("Chain started in {} (difficulty {} bits)", pki_config.chain_path(), difficulty));
+                out.field("chain", pki_config.chain_path());
+                out.field("difficulty", difficulty);
+            }
+            ChainAction::Send { from, to, amount } => {
+                let sequence = pki_config.send_coins(&from, &to, amount)?;
+                out.line(tr!("{} → {}: {} (sequence {}), waiting to be mined", from, to, amount, sequence));
+                out.field("from", from);
+                out.field("to", to);
+                out.field("amount", amount);
+                out.field("sequence", sequence);
+            }
+            ChainAction::Mine { miner } => {
+                let block = pki_config.mine_block(&miner)?;
+                out.line(tr!(
+                    "Mined block {} with {} transaction(s): nonce {} after {} attempt(s)",
+                    block.index, block.transactions, block.nonce, block.attempts
+                ));
+                out.line(tr!("Hash: {}", block.hash));
+                out.field("index", block.index);
+                out.field("hash", block.hash);
+                out.field("nonce", block.nonce);
+                out.field("attempts", block.attempts);
+                out.field("transactions", block.transactions);
+            }
+            action @ (ChainAction::Show | ChainAction::Validate) => {
+                let chain = pki_config.read_chain()?;
+                if matches!(action, ChainAction::Show) {
+                    for block in &chain.blocks {
+                        out.line(tr!("Block {}  {}", block.index, encode_hex(&block.hash())));
+                        for transaction in &block.transactions {
+                            let from = transaction.from.as_deref().unwrap_or("(reward)");
+                            out.line(format!("  {} → {}: {}", from, transaction.to, transaction.amount));
+                        }
+                    }
+                    out.field("blocks", chain.blocks.iter().map(ledger::block_to_json).collect::<Vec<_>>());
+                }
+                match pki_config.validate_chain(&chain) {
+                    Ok(balances) => {
+                        out.line(tr!("Chain VALID: {} block(s)", chain.blocks.len()));
+                        for (user, balance) in &balances {
+                            out.line(format!("  {}: {}", user, balance));
+                        }
+                        out.field("valid", true);
+                        out.field("balances", serde_json::to_value(&balances).map_err(io::Error::other)?);
+                    }
+                    Err(e) => {
+                        out.line(tr!("Chain INVALID: {}", e));
+                        out.field("valid", false);
+                        out.field("error", e.to_string());
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, tr!("Chain validation failed: {}", e)));
+                    }
+                }
+            }
+        },
+        PkiCommand::Renew { rotate_key, user: username } => {
+            let report = pki_config.renew_user_certificate(&username, rotate_key)?;
+            out.line(tr!("Renewed certificate {}; the previous one is in {}", report.certificate, report.archive_dir));
+            if report.rotated_key {
+                out.line(tr!("Generated a new key for {}", username));
+            }
+            out.field("username", username);
+            out.field("certificate", report.certificate);
+            out.field("archive_dir", report.archive_dir);
+            out.field("rotated_key", report.rotated_key);
+        }
+        PkiCommand::Issue { subject, profile, subject_alt_names, user: username } => {
+            if !provision::is_valid_username(&username) {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid username {}", username)));
+            }
+            let profile = profile.map(|name| CertificateProfile::parse(&name)).transpose()?;
+            // The subject's fields go over `pki.user_subject`
+            let fields = subject.map(|subject| SubjectDn::parse(&subject)).transpose()?.unwrap_or_default();
+            let subject = pki_config.subject_for_user(&username, fields);
+
+            let issued = pki_config.issue_user_certificate(&username, &subject, profile, &subject_alt_names)?;
+            let certificate_path = format!("{}/{}_certificate.pem", pki_config.users_dir, username);
+            if issued {
+                out.line(tr!("Issued certificate {}", certificate_path));
+            } else {
+                out.line(tr!("Certificate for {} already issued; use --force to replace it", username));
+            }
+            out.field("username", username);
+            out.field("issued", issued);
+            out.field("certificate", certificate_path);
+        }
+        PkiCommand::Convert { to, input, output } => {
+            // Without --to, the other encoding
+            let format = match to {
+                Some(name) => ArtifactFormat::parse(&name)?,
+                None => match convert::detect_format(&fs::read(&input)?) {
+                    ArtifactFormat::Pem => ArtifactFormat::Der,
+                    ArtifactFormat::Der => ArtifactFormat::Pem,
+                },
+            };
+            let output = output.unwrap_or_else(|| convert::with_extension(&input, format));
+            if output == input {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("The output file must differ from the input file")));
+            }
+            if Path::new(&output).exists() && !pki_config.force {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    tr!("{} already exists; rerun with --force to overwrite it", output)
+                ));
+            }
+
+            let label = convert::convert_file(&input, &output, format)?;
+            out.line(tr!("Wrote {} as {} to {}", label.to_ascii_lowercase(), format.name().to_ascii_uppercase(), output));
+            out.field("kind", label.to_ascii_lowercase());
+            out.field("format", format.name());
+            out.field("output", output);
+        }
+        PkiCommand::Revoke { user: username } => {
+            pki_config.revoke_user_certificate(&username)?;
+            out.line(tr!("Revoked certificate for {}", username));
+            out.field("username", username);
+            out.field("crl", format!("{}/ca_crl.pem", pki_config.ca_dir));
+        }
+    }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// DSA sizes outside FIPS 186-4 are allowed, with a warning
+fn warn_unless_standard_dsa_size(l_bits: u64, n_bits: u64) {
+    if !crypto_core::dsa::STANDARD_SIZES.contains(&(l_bits, n_bits)) {
+        warn!(l_bits, n_bits, "not a FIPS 186-4 size; fine for experiments, not for real keys");
+    }
+}
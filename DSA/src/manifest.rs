@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+
+use crypto_core::codec::encode_hex;
+use crypto_core::hash::Sha256;
+use crypto_core::merkle;
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::exec;
+
+/// Differences between a signed manifest and the current directory contents
+pub(crate) struct ManifestDiff {
+    pub(crate) added: Vec<String>,
+    pub(crate) removed: Vec<String>,
+    pub(crate) modified: Vec<String>,
+}
+
+impl ManifestDiff {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Result of verifying a signed directory manifest
+pub(crate) struct DirectoryReport {
+    /// Whether the manifest signature verified for the user
+    pub(crate) signature_valid: bool,
+    pub(crate) diff: ManifestDiff,
+    /// Merkle root of the directory as it is now; equal to the signed
+    /// root exactly when nothing changed
+    pub(crate) merkle_root: String,
+}
+
+impl PKIConfig {
+    /// Hash every file in a directory, build a manifest headed by the
+    /// Merkle root of its lines and sign it. Returns the manifest path and
+    /// the root.
+    pub(crate) fn sign_directory(&self, username: &str, dir_path: &str) -> io::Result<(String, String)> {
+        let manifest_path = manifest_path_for(dir_path);
+        let entries = hash_directory(dir_path)?;
+
+        let root = merkle_root(&entries);
+        let manifest: String = std::iter::once(format!("# merkle-root {}\n", root))
+            .chain(entries.iter().map(|(path, hash)| format!("{}\n", manifest_line(path, hash))))
+            .collect();
+        exec::write(&manifest_path, manifest)?;
+
+        self.sign_document(username, &manifest_path)?;
+
+        Ok((manifest_path, root))
+    }
+
+    /// Verify a directory's signed manifest and report changed files
+    pub(crate) fn verify_directory(&self, username: &str, dir_path: &str) -> io::Result<DirectoryReport> {
+        let manifest_path = manifest_path_for(dir_path);
+
+        if !Path::new(&manifest_path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                tr!("Manifest {} not found", manifest_path)
+            ));
+        }
+
+        let signature_valid = self.verify_document_signature(username, &manifest_path)?;
+
+        let signed = parse_manifest(&fs::read_to_string(&manifest_path)?)?;
+        let current = hash_directory(dir_path)?;
+
+        let mut diff = ManifestDiff {
+            added: Vec::new(),
+            removed: Vec::new(),
+            modified: Vec::new(),
+        };
+
+        for (path, hash) in &current {
+            match signed.get(path) {
+                None => diff.added.push(path.clone()),
+                Some(signed_hash) if signed_hash != hash => diff.modified.push(path.clone()),
+                Some(_) => {}
+            }
+        }
+        for path in signed.keys() {
+            if !current.contains_key(path) {
+                diff.removed.push(path.clone());
+            }
+        }
+
+        Ok(DirectoryReport { signature_valid, diff, merkle_root: merkle_root(&current) })
+    }
+}
+
+/// The manifest lives next to the directory so it never lists itself
+fn manifest_path_for(dir_path: &str) -> String {
+    format!("{}.manifest", dir_path.trim_end_matches(['/', '\\']))
+}
+
+/// Collect all regular files below `dir`, as paths relative to `root`
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<String>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+        } else if path.is_file() {
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            let components: Vec<String> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            files.push(components.join("/"));
+        }
+    }
+    Ok(())
+}
+
+/// SHA-256 hash of every file in a directory, keyed by relative path
+fn hash_directory(dir_path: &str) -> io::Result<BTreeMap<String, String>> {
+    let root = Path::new(dir_path);
+    if !root.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            tr!("Directory {} not found", dir_path)
+        ));
+    }
+
+    let mut files = Vec::new();
+    collect_files(root, root, &mut files)?;
+
+    files
+        .into_iter()
+        .map(|file| {
+            let hash = hash_file(&root.join(&file))?;
+            Ok((file, hash))
+        })
+        .collect()
+}
+
+/// Hex SHA-256 of a file, read in chunks
+fn hash_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(encode_hex(&hasher.finalize()))
+}
+
+fn manifest_line(path: &str, hash: &str) -> String {
+    format!("{}  {}", hash, path)
+}
+
+/// Merkle root over the manifest lines, in path order, so one hash stands
+/// for the whole directory and a single line can be proven to be in it
+fn merkle_root(entries: &BTreeMap<String, String>) -> String {
+    let leaves: Vec<merkle::Hash> = entries
+        .iter()
+        .map(|(path, hash)| merkle::leaf_hash(manifest_line(path, hash).as_bytes()))
+        .collect();
+    encode_hex(&merkle::root(&leaves))
+}
+
+/// Parse `<sha256>  <relative path>` manifest lines, skipping the `#` header
+fn parse_manifest(contents: &str) -> io::Result<BTreeMap<String, String>> {
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            line.split_once("  ")
+                .map(|(hash, path)| (path.to_string(), hash.to_string()))
+                .ok_or_else(|| io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    tr!("Malformed manifest line: {}", line)
+                ))
+        })
+        .collect()
+}
@@ -0,0 +1,671 @@
+//! Romanian for everything `pki` prints; see [`crypto_core::i18n`]
+
+use crypto_core::i18n::Catalog;
+
+pub(crate) const MESSAGES: Catalog = &[
+    // main.rs
+    ("Error: {}", "Eroare: {}"),
+    ("CA certificate exists but its key ({}) is missing", "Certificatul CA există, dar cheia sa ({}) lipsește"),
+    ("Failed to create CA self-signed certificate", "Nu s-a putut crea certificatul CA autosemnat"),
+    ("Failed to generate private key for user {}", "Nu s-a putut genera cheia privată pentru utilizatorul {}"),
+    ("Failed to generate CSR for user {}", "Nu s-a putut genera CSR-ul pentru utilizatorul {}"),
+    ("Failed to sign certificate for user {}", "Nu s-a putut semna certificatul pentru utilizatorul {}"),
+    ("Certificate for user {} not found", "Certificatul utilizatorului {} nu a fost găsit"),
+    ("Failed to revoke certificate for user {}", "Nu s-a putut revoca certificatul utilizatorului {}"),
+    ("Failed to generate Certificate Revocation List", "Nu s-a putut genera lista de revocare a certificatelor"),
+    ("Failed to sign document for user {}", "Nu s-a putut semna documentul pentru utilizatorul {}"),
+    ("Failed to read public key of user {}", "Nu s-a putut citi cheia publică a utilizatorului {}"),
+    (
+        "A passphrase is required (--passphrase or PKI_BACKUP_PASSPHRASE)",
+        "Este necesară o frază de acces (--passphrase sau PKI_BACKUP_PASSPHRASE)",
+    ),
+    ("A master passphrase is required ({} or {})", "Este necesară o frază de acces principală ({} sau {})"),
+    ("Using existing CA in {}", "Se folosește CA-ul existent din {}"),
+    ("Certificate for {} already issued", "Certificatul pentru {} a fost deja emis"),
+    (
+        "Unknown key algorithm {} (expected rsa, ecdsa-p256, ecdsa-p384 or ed25519)",
+        "Algoritm de cheie necunoscut {} (se aștepta rsa, ecdsa-p256, ecdsa-p384 sau ed25519)"
+    ),
+    (
+        "The native backend cannot make {} keys; set pki.backend = openssl",
+        "Backend-ul nativ nu poate genera chei {}; setați pki.backend = openssl"
+    ),
+    (
+        "Certificate for {} already issued; use --force to replace it",
+        "Certificatul pentru {} a fost deja emis; folosiți --force pentru a-l înlocui"
+    ),
+    ("PKI Setup Complete!", "Configurarea PKI este completă!"),
+    ("--dry-run is not supported for {}", "--dry-run nu este acceptat pentru {}"),
+    (
+        "--dry-run prints plain text and cannot be combined with --output json",
+        "--dry-run afișează text simplu și nu poate fi combinat cu --output json",
+    ),
+    ("Dry run: nothing was written", "Rulare de probă: nu s-a scris nimic"),
+    ("CA created in {}", "CA creat în {}"),
+    ("CA already initialized in {}; use --force to replace it", "CA deja inițializat în {}; folosiți --force pentru a-l înlocui"),
+    ("Encrypted {} -> {}", "Criptat {} -> {}"),
+    ("Decrypted {} -> {}", "Decriptat {} -> {}"),
+    ("Signed {} as {} into {}", "Semnat {} ca {} în {}"),
+    ("Signed {} as {}", "Semnat {} ca {}"),
+    ("Timestamp token stored in {}", "Jetonul de marcă temporală a fost salvat în {}"),
+    ("Added signature of {} to {}.p7s", "S-a adăugat semnătura lui {} la {}.p7s"),
+    ("Signer: {}", "Semnatar: {}"),
+    ("Signer: {} ({})", "Semnatar: {} ({})"),
+    ("Signature OK", "Semnătură OK"),
+    ("Signature verification FAILED", "Verificarea semnăturii a EȘUAT"),
+    ("Missing required signer: {}", "Lipsește semnatarul necesar: {}"),
+    ("Signature container OK", "Container de semnături OK"),
+    ("Signature container verification FAILED", "Verificarea containerului de semnături a EȘUAT"),
+    ("Signature: {}", "Semnătură: {}"),
+    ("OK", "OK"),
+    ("FAILED", "EȘUAT"),
+    ("Digest: {}", "Rezumat: {}"),
+    ("unknown", "necunoscut"),
+    ("Signer: {} (serial {})", "Semnatar: {} (serie {})"),
+    ("Issuer: {}", "Emitent: {}"),
+    ("Fingerprint: {}", "Amprentă: {}"),
+    ("Chain: OK ({})", "Lanț: OK ({})"),
+    ("Chain: FAILED ({})", "Lanț: EȘUAT ({})"),
+    (
+        "  signature {}, valid {} to {} {}, basicConstraints {}",
+        "  semnătură {}, valabil de la {} până la {} {}, basicConstraints {}"
+    ),
+    ("Issuer {} not found among the CA certificates", "Emitentul {} nu a fost găsit printre certificatele CA"),
+    ("Chain of {} OK", "Lanțul lui {} OK"),
+    ("Chain of {} verification FAILED", "Verificarea lanțului lui {} a EȘUAT"),
+    ("unknown error", "eroare necunoscută"),
+    ("Timestamp: OK ({})", "Marcă temporală: OK ({})"),
+    ("Timestamp: FAILED", "Marcă temporală: EȘUAT"),
+    (
+        "Timestamp: unverified (no --tsa-ca; checking validity as of now)",
+        "Marcă temporală: neverificată (fără --tsa-ca; valabilitatea este verificată pentru momentul actual)",
+    ),
+    (
+        "Timestamp: none (checking validity as of now)",
+        "Marcă temporală: niciuna (valabilitatea este verificată pentru momentul actual)",
+    ),
+    ("Validity: {} to {} ({} at signing time)", "Valabilitate: {} până la {} ({} la momentul semnării)"),
+    (
+        "Revocation: revoked {} (no timestamp proves the signature predates it)",
+        "Revocare: revocat {} (nicio marcă temporală nu dovedește că semnătura este anterioară)",
+    ),
+    (
+        "Revocation: revoked {} (after the timestamped signing time)",
+        "Revocare: revocat {} (după momentul semnării marcat temporal)",
+    ),
+    ("Revocation: not revoked", "Revocare: nerevocat"),
+    ("Signed by: {}", "Semnat de: {}"),
+    ("Extracted {} to {}", "S-a extras {} în {}"),
+    ("--dry-run is not supported for codesign verify", "--dry-run nu este acceptat pentru codesign verify"),
+    ("Code signing usage: {}", "Utilizare pentru semnarea codului: {}"),
+    ("MISSING", "LIPSEȘTE"),
+    ("Chain: {}", "Lanț: {}"),
+    ("Revocation: signer certificate REVOKED", "Revocare: certificatul semnatarului este REVOCAT"),
+    ("Binary: {} ({} bytes, {} {})", "Binar: {} ({} octeți, {} {})"),
+    (
+        "Manifest is for {} signed by {}, not this binary and signer",
+        "Manifestul este pentru {} semnat de {}, nu pentru acest binar și semnatar",
+    ),
+    ("Code signature OK", "Semnătura codului OK"),
+    ("Code signature verification FAILED", "Verificarea semnăturii codului a EȘUAT"),
+    ("Signed manifest of {} written to {}", "Manifestul semnat al lui {} a fost scris în {}"),
+    ("Merkle root: {}", "Rădăcina Merkle: {}"),
+    ("Added: {}", "Adăugat: {}"),
+    ("Removed: {}", "Eliminat: {}"),
+    ("Modified: {}", "Modificat: {}"),
+    ("Manifest signature verification FAILED", "Verificarea semnăturii manifestului a EȘUAT"),
+    ("Directory OK", "Director OK"),
+    ("Directory contents differ from the signed manifest", "Conținutul directorului diferă de manifestul semnat"),
+    ("Issued certificate {}", "Certificat emis {}"),
+    ("{} CSR(s) awaiting approval", "{} CSR-uri așteaptă aprobarea"),
+    ("--dry-run requires --once for watch", "--dry-run necesită --once pentru watch"),
+    ("Watching {} for CSRs every {}s", "Se urmărește {} pentru CSR-uri la fiecare {}s"),
+    ("Subject:     {}", "Subiect:     {}"),
+    ("Issuer:      {}", "Emitent:     {}"),
+    ("Serial:      {}", "Serie:       {}"),
+    ("Not before:  {}", "Nu înainte:  {}"),
+    ("Not after:   {}", "Nu după:     {}"),
+    ("Status:      {}", "Stare:       {}"),
+    ("Usage:       {}", "Utilizare:   {}"),
+    ("SHA-256:     {}", "SHA-256:     {}"),
+    ("USER", "UTILIZATOR"),
+    ("STATUS", "STARE"),
+    ("VALID FROM", "VALABIL DE LA"),
+    ("VALID UNTIL", "VALABIL PÂNĂ LA"),
+    ("SERIAL", "SERIE"),
+    ("DAYS LEFT", "ZILE RĂMASE"),
+    ("No certificates expire within {} days", "Niciun certificat nu expiră în următoarele {} zile"),
+    ("Wrote {}", "S-a scris {}"),
+    ("Encrypted backup written to {}", "Copia de rezervă criptată a fost scrisă în {}"),
+    ("Restored PKI from {}", "PKI restaurat din {}"),
+    (
+        "New CA key and certificate created; old CA archived in {}",
+        "Cheie și certificat CA noi create; vechiul CA a fost arhivat în {}",
+    ),
+    ("Cross-signed certificate written to {}", "Certificatul cu semnătură încrucișată a fost scris în {}"),
+    ("Re-issued certificate for {}", "Certificat reemis pentru {}"),
+    ("Marked for renewal in {}: {}", "Marcat pentru reînnoire în {}: {}"),
+    ("Sub-CA {} certificate written to {}", "Certificatul sub-CA-ului {} a fost scris în {}"),
+    (
+        "CA key split into {} shares, any {} of which restore it:",
+        "Cheia CA a fost divizată în {} părți, oricare {} dintre ele o restaurează:",
+    ),
+    (
+        "The CA key file was removed; run `pki ca combine` with the shares to sign again",
+        "Fișierul cheii CA a fost eliminat; rulați `pki ca combine` cu părțile pentru a semna din nou",
+    ),
+    (
+        "Hand one share to each holder, then remove the key with --remove-key or by hand",
+        "Dați câte o parte fiecărui deținător, apoi eliminați cheia cu --remove-key sau manual",
+    ),
+    ("CA key restored to {} from {} shares", "Cheia CA a fost restaurată în {} din {} părți"),
+    ("CA key: {}", "Cheia CA: {}"),
+    ("Provisioned {} into PIV slot {}", "{} a fost configurat în slotul PIV {}"),
+    ("SSH certificate written to {}", "Certificatul SSH a fost scris în {}"),
+    ("Unknown keystore format {}", "Format de depozit de chei necunoscut {}"),
+    (
+        "A store password is required (--storepass or PKI_KEYSTORE_PASSWORD)",
+        "Este necesară o parolă pentru depozit (--storepass sau PKI_KEYSTORE_PASSWORD)",
+    ),
+    ("Truststore written to {}", "Depozitul de încredere a fost scris în {}"),
+    ("Keystore for {} written to {}", "Depozitul de chei pentru {} a fost scris în {}"),
+    ("Certificate for {} written to {}", "Certificatul pentru {} a fost scris în {}"),
+    ("Dashboard written to {}", "Tabloul de bord a fost scris în {}"),
+    ("Metrics written to {}", "Metricile au fost scrise în {}"),
+    ("notified {}", "notificat {}"),
+    ("already notified", "deja notificat"),
+    ("SKIPPED (no email address)", "OMIS (nicio adresă de e-mail)"),
+    ("FAILED ({})", "EȘUAT ({})"),
+    ("{} expires in {} days  {}", "{} expiră în {} zile  {}"),
+    ("Sent {} of {} expiry notices", "S-au trimis {} din {} notificări de expirare"),
+    ("CA certificate installed into the {}", "Certificatul CA a fost instalat în {}"),
+    ("CA certificate removed from the {}", "Certificatul CA a fost eliminat din {}"),
+    ("--dry-run is not supported for demo tls", "--dry-run nu este acceptat pentru demo tls"),
+    ("Server certificate {} (localhost, 127.0.0.1)", "Certificatul serverului {} (localhost, 127.0.0.1)"),
+    ("Serving HTTPS on {}", "Se servește HTTPS pe {}"),
+    ("Client without a certificate: rejected ({})", "Client fără certificat: respins ({})"),
+    ("Handshake: {} with {}", "Handshake: {} cu {}"),
+    (
+        "Server identity verified against {}/ca_certificate.pem",
+        "Identitatea serverului a fost verificată față de {}/ca_certificate.pem",
+    ),
+    ("Client certificate: {}", "Certificatul clientului: {}"),
+    ("Response: {}", "Răspuns: {}"),
+    ("OpenSSL {}", "OpenSSL {}"),
+    ("OpenSSL: {}", "OpenSSL: {}"),
+    ("{}: mode {} allows access by {}", "{}: modul {} permite accesul pentru {}"),
+    ("OpenSSL 3 is required", "Este necesar OpenSSL 3"),
+    ("Backend: {}", "Implementare criptografică: {}"),
+    ("Permissions OK", "Permisiuni OK"),
+    ("Restricted {} path(s) to their owner", "S-au restricționat {} căi la proprietarul lor"),
+    (
+        "{} permission problem(s) found; rerun with --fix to restrict them",
+        "S-au găsit {} probleme de permisiuni; rulați din nou cu --fix pentru a le restricționa",
+    ),
+    ("line {}: {} OK", "linia {}: {} OK"),
+    ("line {}: {} FAILED ({})", "linia {}: {} EȘUAT ({})"),
+    ("Provisioned {} of {} users ({} failed)", "S-au configurat {} din {} utilizatori ({} eșuați)"),
+    ("Tree size: {}", "Dimensiunea arborelui: {}"),
+    ("Root hash: {}", "Hash-ul rădăcinii: {}"),
+    ("Leaf {} of {} (root {})", "Frunza {} din {} (rădăcina {})"),
+    ("  path[{}] {}", "  drum[{}] {}"),
+    ("Inclusion proof written to {}", "Dovada de includere a fost scrisă în {}"),
+    (
+        "Certificate of {} is entry {} in the log's first {} entries (root {})",
+        "Certificatul lui {} este intrarea {} din primele {} intrări ale jurnalului (rădăcina {})",
+    ),
+    ("Inclusion proof verification FAILED", "Verificarea dovezii de includere a EȘUAT"),
+    ("Invalid root hash {}", "Hash de rădăcină invalid {}"),
+    ("Entries 1-{} (root {})", "Intrările 1-{} (rădăcina {})"),
+    ("The current log extends the older one without changing it", "Jurnalul curent îl extinde pe cel vechi fără să-l modifice"),
+    (
+        "Consistency proof verification FAILED: the log was rewritten",
+        "Verificarea dovezii de consistență a EȘUAT: jurnalul a fost rescris",
+    ),
+    (
+        "{} user key(s) encrypted under the master key in {}/master_key.json",
+        "{} chei de utilizator criptate sub cheia principală în {}/master_key.json",
+    ),
+    (
+        "Set PKI_MASTER_PASSPHRASE to sign, decrypt or issue with user keys",
+        "Setați PKI_MASTER_PASSPHRASE pentru a semna, decripta sau emite cu cheile utilizatorilor",
+    ),
+    (
+        "{} user key(s) decrypted; the master key was removed",
+        "{} chei de utilizator decriptate; cheia principală a fost eliminată",
+    ),
+    ("Master key re-wrapped under the new passphrase", "Cheia principală a fost reîmpachetată sub noua frază de acces"),
+    ("DSA key for {} ({}-bit p, {}-bit q) written to {}", "Cheia DSA pentru {} (p de {} biți, q de {} biți) a fost scrisă în {}"),
+    ("DSA signature verification failed", "Verificarea semnăturii DSA a eșuat"),
+    ("Both signatures share r, so both used the same k:", "Ambele semnături au același r, deci au folosit același k:"),
+    (
+        "  k = (H₁ − H₂)/(s₁ − s₂) mod q, then x = (s₁·k − H₁)/r mod q",
+        "  k = (H₁ − H₂)/(s₁ − s₂) mod q, apoi x = (s₁·k − H₁)/r mod q",
+    ),
+    ("Recovered x = {} (matches the real key: {})", "x recuperat = {} (coincide cu cheia reală: {})"),
+    ("The recovered x does not reproduce y", "x recuperat nu îl reproduce pe y"),
+    ("Chain started in {} (difficulty {} bits)", "Lanț pornit în {} (dificultate {} biți)"),
+    ("{} → {}: {} (sequence {}), waiting to be mined", "{} → {}: {} (secvența {}), așteaptă să fie minată"),
+    (
+        "Mined block {} with {} transaction(s): nonce {} after {} attempt(s)",
+        "S-a minat blocul {} cu {} tranzacții: nonce {} după {} încercări",
+    ),
+    ("Hash: {}", "Hash: {}"),
+    ("Block {}  {}", "Blocul {}  {}"),
+    ("Chain VALID: {} block(s)", "Lanț VALID: {} blocuri"),
+    ("Chain INVALID: {}", "Lanț INVALID: {}"),
+    ("Chain validation failed: {}", "Validarea lanțului a eșuat: {}"),
+    ("Revoked certificate for {}", "Certificatul pentru {} a fost revocat"),
+    ("valid", "valid"),
+    ("expiring", "expiră"),
+    ("expired", "expirat"),
+    ("revoked", "revocat"),
+    // acme.rs
+    ("Invalid identifier {}", "Identificator invalid {}"),
+    ("Challenge validation failed; the order was discarded", "Validarea provocării a eșuat; comanda a fost abandonată"),
+    ("Order challenge has not been validated", "Provocarea comenzii nu a fost validată"),
+    ("Malformed order response", "Răspuns la comandă malformat"),
+    ("Failed to generate random bytes", "Nu s-au putut genera octeți aleatori"),
+    // archive.rs
+    (
+        "{} already exists; rerun with --force to replace it (the old files are archived)",
+        "{} există deja; rulați din nou cu --force pentru a-l înlocui (fișierele vechi sunt arhivate)",
+    ),
+    // backend.rs
+    ("Unknown backend {} (expected openssl or native)", "Implementare necunoscută {} (se aștepta openssl sau native)"),
+    // backup.rs
+    ("CA directory {} not found", "Directorul CA {} nu a fost găsit"),
+    ("Failed to archive the PKI directories", "Nu s-au putut arhiva directoarele PKI"),
+    ("Failed to encrypt backup {}", "Nu s-a putut cripta copia de rezervă {}"),
+    (
+        "A CA key already exists at {}; refusing to restore over it",
+        "Există deja o cheie CA la {}; restaurarea peste ea este refuzată",
+    ),
+    ("Failed to decrypt backup {} (wrong passphrase?)", "Nu s-a putut decripta copia de rezervă {} (frază de acces greșită?)"),
+    (
+        "Backup {} uses {} PBKDF2 iterations instead of {}",
+        "Copia de rezervă {} folosește {} iterații PBKDF2 în loc de {}",
+    ),
+    ("Failed to unpack backup {}", "Nu s-a putut despacheta copia de rezervă {}"),
+    // chain.rs
+    ("CA certificate not found; initialize the PKI first", "Certificatul CA nu a fost găsit; inițializați mai întâi PKI"),
+    // client.rs
+    ("Server returned {}: {}", "Serverul a răspuns {}: {}"),
+    ("Unsupported server URL {}", "URL de server neacceptat {}"),
+    ("Invalid port in {}", "Port invalid în {}"),
+    ("Malformed HTTP status line", "Linie de stare HTTP malformată"),
+    // codesign.rs
+    ("Code signing manifest lacks {}", "Manifestului de semnare a codului îi lipsește {}"),
+    ("Unsupported code signing manifest version", "Versiune neacceptată a manifestului de semnare a codului"),
+    ("Invalid size in code signing manifest", "Dimensiune invalidă în manifestul de semnare a codului"),
+    (
+        "The certificate of {} does not allow code signing; issue it with the codesign profile",
+        "Certificatul lui {} nu permite semnarea codului; emiteți-l cu profilul codesign",
+    ),
+    ("Code signing manifest {} not found", "Manifestul de semnare a codului {} nu a fost găsit"),
+    // cosign.rs
+    ("Failed to co-sign document for user {}", "Nu s-a putut co-semna documentul pentru utilizatorul {}"),
+    ("Signature container {} not found", "Containerul de semnături {} nu a fost găsit"),
+    ("Failed to read signers from {}", "Nu s-au putut citi semnatarii din {}"),
+    ("Failed to read certificate subject", "Nu s-a putut citi subiectul certificatului"),
+    // csr.rs
+    ("CSR {} has an invalid signature", "CSR-ul {} are o semnătură invalidă"),
+    ("Failed to read subject of CSR {}", "Nu s-a putut citi subiectul CSR-ului {}"),
+    ("CSR {} has no common name", "CSR-ul {} nu are un nume comun"),
+    ("CSR common name {} is not a valid username", "Numele comun {} din CSR nu este un nume de utilizator valid"),
+    (
+        "CSR {} has subject field {}={}, which the configured user subject does not allow",
+        "CSR-ul {} are câmpul de subiect {}={}, pe care subiectul configurat pentru utilizatori nu îl permite"
+    ),
+    (
+        "CSR {} requests subject alternative name {}, which the policy does not allow",
+        "CSR-ul {} solicită numele alternativ {}, pe care politica nu îl permite"
+    ),
+    ("Failed to read public key of CSR {}", "Nu s-a putut citi cheia publică a CSR-ului {}"),
+    ("CSR key is smaller than the required {} bits", "Cheia din CSR este mai mică decât cei {} biți necesari"),
+    (
+        "{} already holds a valid certificate; ask to renew it to replace it",
+        "{} are deja un certificat valid; cereți reînnoirea lui pentru a-l înlocui"
+    ),
+    // database.rs
+    ("Failed to read certificate {}", "Nu s-a putut citi certificatul {}"),
+    ("Missing {} in {}", "Lipsește {} în {}"),
+    ("Unexpected expiry in {}", "Expirare neașteptată în {}"),
+    // digest.rs
+    ("Unknown digest {} (expected sha256, sha384 or sha512)", "Rezumat necunoscut {} (se așteaptă sha256, sha384 sau sha512)"),
+    ("Failed to read the signing key", "Nu s-a putut citi cheia de semnare"),
+    ("A {}-bit RSA key is too small to sign with {}", "O cheie RSA de {} biți este prea mică pentru a semna cu {}"),
+    // dsa.rs
+    ("{} is not valid JSON: {}", "{} nu este JSON valid: {}"),
+    ("{} has no valid {}", "{} nu are un {} valid"),
+    // embedded.rs
+    ("{} is not a .p7m file; name the output file explicitly", "{} nu este un fișier .p7m; numiți explicit fișierul de ieșire"),
+    ("The output file must differ from the input file", "Fișierul de ieșire trebuie să difere de cel de intrare"),
+    ("{} already exists; rerun with --force to overwrite it", "{} există deja; rulați din nou cu --force pentru a-l suprascrie"),
+    ("Unknown format {} (expected pem or der)", "Format necunoscut {} (se aștepta pem sau der)"),
+    ("Cannot convert {}: {}", "{} nu poate fi convertit: {}"),
+    ("Cannot read CSR {}: {}", "CSR-ul {} nu poate fi citit: {}"),
+    ("Cannot read certificate {}: {}", "Certificatul {} nu poate fi citit: {}"),
+    (
+        "The certificate of {} is revoked; renew it with --rotate-key",
+        "Certificatul lui {} este revocat; reînnoiți-l cu --rotate-key"
+    ),
+    (
+        "Renewed certificate {}; the previous one is in {}",
+        "Certificatul {} a fost reînnoit; cel anterior se află în {}"
+    ),
+    ("Generated a new key for {}", "A fost generată o cheie nouă pentru {}"),
+    (
+        "{} is not a DER certificate, key, request or CRL",
+        "{} nu este un certificat, o cheie, o cerere sau un CRL DER"
+    ),
+    ("Wrote {} as {} to {}", "S-a scris {} ca {} în {}"),
+    (
+        "Signature in {} is not valid; the document was not extracted",
+        "Semnătura din {} nu este validă; documentul nu a fost extras",
+    ),
+    ("Signed document {} not found", "Documentul semnat {} nu a fost găsit"),
+    // envelope.rs
+    ("At least one recipient is required for encryption", "Criptarea necesită cel puțin un destinatar"),
+    ("Failed to encrypt {}", "Nu s-a putut cripta {}"),
+    ("Failed to decrypt {} for user {}", "Nu s-a putut decripta {} pentru utilizatorul {}"),
+    // est.rs
+    ("Failed to build PKCS#7 certificate bundle", "Nu s-a putut construi pachetul de certificate PKCS#7"),
+    // extensions.rs
+    (
+        "Invalid policy OID {} (expected dotted digits, e.g. 2.23.140.1.2.1)",
+        "OID de politică invalid {} (se așteaptă cifre separate prin puncte, de ex. 2.23.140.1.2.1)",
+    ),
+    // inventory.rs
+    ("Failed to read certificate of user {}", "Nu s-a putut citi certificatul utilizatorului {}"),
+    ("Failed to read Certificate Revocation List", "Nu s-a putut citi lista de revocare a certificatelor"),
+    // java.rs
+    ("Failed to export truststore {}", "Nu s-a putut exporta depozitul de încredere {}"),
+    ("Failed to export keystore for user {}", "Nu s-a putut exporta depozitul de chei pentru utilizatorul {}"),
+    ("Failed to convert keystore for user {} to JKS", "Nu s-a putut converti în JKS depozitul de chei al utilizatorului {}"),
+    // keyshares.rs
+    ("A CA key already exists at {}; refusing to overwrite it", "Există deja o cheie CA la {}; suprascrierea ei este refuzată"),
+    ("No share files given", "Nu s-a dat niciun fișier de parte"),
+    ("The shares come from different splits", "Părțile provin din divizări diferite"),
+    ("{} share(s) given, but {} are needed", "S-au dat {} parte(părți), dar sunt necesare {}"),
+    ("The shares do not reproduce the CA key; one is corrupt", "Părțile nu reproduc cheia CA; una este coruptă"),
+    (
+        "The CA key is held in {}; only a key file can be split",
+        "Cheia CA este păstrată în {}; doar un fișier de cheie poate fi divizat",
+    ),
+    ("{} is not a CA key share", "{} nu este o parte a cheii CA"),
+    // ledger.rs
+    ("No chain at {}; run `pki chain init`", "Niciun lanț la {}; rulați `pki chain init`"),
+    ("Cannot mine: {}", "Nu se poate mina: {}"),
+    ("{} is not a valid chain file", "{} nu este un fișier de lanț valid"),
+    // manifest.rs
+    ("Manifest {} not found", "Manifestul {} nu a fost găsit"),
+    ("Directory {} not found", "Directorul {} nu a fost găsit"),
+    ("Malformed manifest line: {}", "Linie de manifest malformată: {}"),
+    // masterkey.rs
+    ("Failed to generate a salt", "Nu s-a putut genera o sare"),
+    ("Failed to generate a nonce", "Nu s-a putut genera un nonce"),
+    ("Failed to wrap the master key", "Nu s-a putut împacheta cheia principală"),
+    ("Wrong master passphrase", "Frază de acces principală greșită"),
+    ("Corrupt master key", "Cheie principală coruptă"),
+    ("Malformed master key file", "Fișier de cheie principală malformat"),
+    ("Failed to decrypt {}", "Nu s-a putut decripta {}"),
+    ("No private key found in {}", "Nicio cheie privată găsită în {}"),
+    (
+        "The master passphrase must be at least 12 characters long",
+        "Fraza de acces principală trebuie să aibă cel puțin 12 caractere",
+    ),
+    ("Failed to generate the master key", "Nu s-a putut genera cheia principală"),
+    ("Master passphrase: ", "Fraza de acces principală: "),
+    ("CA key passphrase: ", "Fraza de acces a cheii CA: "),
+    ("Repeat the CA key passphrase: ", "Repetați fraza de acces a cheii CA: "),
+    ("The passphrases do not match", "Frazele de acces nu coincid"),
+    (
+        "The CA passphrase must be at least 12 characters long",
+        "Fraza de acces a CA trebuie să aibă cel puțin 12 caractere",
+    ),
+    (
+        "The CA key is encrypted; set {} or run pki on a terminal",
+        "Cheia CA este criptată; setați {} sau rulați pki într-un terminal",
+    ),
+    (
+        "User keys are protected by a master passphrase; set PKI_MASTER_PASSPHRASE",
+        "Cheile utilizatorilor sunt protejate de o frază de acces principală; setați PKI_MASTER_PASSPHRASE",
+    ),
+    ("User keys are not protected by a master key", "Cheile utilizatorilor nu sunt protejate de o cheie principală"),
+    ("Failed to re-encrypt {}", "Nu s-a putut recripta {}"),
+    ("Invalid PBKDF2 iteration count", "Număr de iterații PBKDF2 invalid"),
+    ("Failed to set up AES-256-GCM", "Nu s-a putut configura AES-256-GCM"),
+    ("Invalid nonce", "Nonce invalid"),
+    // native.rs
+    ("Cannot use the key in {}: {}", "Cheia din {} nu poate fi folosită: {}"),
+    (
+        "The native backend cannot use {}; set pki.backend = openssl",
+        "Implementarea nativă nu poate folosi {}; setați pki.backend = openssl",
+    ),
+    (
+        "User keys protected by a master key need pki.backend = openssl",
+        "Cheile utilizatorilor protejate de o cheie principală necesită pki.backend = openssl",
+    ),
+    ("Invalid subject {}", "Subiect invalid {}"),
+    ("Unsupported subject field {}", "Câmp de subiect neacceptat {}"),
+    ("Country {} is not a two-letter code such as RO", "Țara {} nu este un cod din două litere precum RO"),
+    ("Unsupported subject alternative name {}", "Nume alternativ de subiect neacceptat {}"),
+    ("The CA key does not match the CA certificate", "Cheia CA nu corespunde certificatului CA"),
+    ("The CSR in {} is not signed by its own key", "CSR-ul din {} nu este semnat cu propria sa cheie"),
+    ("The certificate of {} is not signed by the CA", "Certificatul lui {} nu este semnat de CA"),
+    // notify.rs
+    (
+        "Invalid warning window {} (expected <days> or <profile>=<days>)",
+        "Fereastră de avertizare invalidă {} (se așteaptă <zile> sau <profil>=<zile>)",
+    ),
+    (
+        "Refusing to send SMTP credentials over an unencrypted connection; add --starttls",
+        "Se refuză trimiterea datelor de autentificare SMTP printr-o conexiune necriptată; adăugați --starttls",
+    ),
+    ("{} does not offer STARTTLS", "{} nu oferă STARTTLS"),
+    ("SMTP server closed the connection", "Serverul SMTP a închis conexiunea"),
+    ("Malformed SMTP reply", "Răspuns SMTP malformat"),
+    ("SMTP server replied: {}", "Serverul SMTP a răspuns: {}"),
+    // openssl.rs
+    (
+        "The openssl command was not found; install OpenSSL 3 and make sure it is on the PATH",
+        "Comanda openssl nu a fost găsită; instalați OpenSSL 3 și asigurați-vă că este în PATH",
+    ),
+    ("`openssl version` failed", "`openssl version` a eșuat"),
+    ("Unrecognized openssl version: {}", "Versiune openssl nerecunoscută: {}"),
+    (
+        "OpenSSL {} is too old; version {} or newer is required",
+        "OpenSSL {} este prea vechi; este necesară versiunea {} sau mai nouă",
+    ),
+    // pem.rs
+    (
+        "Request body is not a base64 encoded certificate request",
+        "Corpul cererii nu este o cerere de certificat codificată base64",
+    ),
+    // permissions.rs
+    ("USERNAME is not set", "USERNAME nu este setat"),
+    ("Failed to restrict access to {}", "Nu s-a putut restricționa accesul la {}"),
+    // profile.rs
+    ("Unknown profile {}", "Profil necunoscut {}"),
+    // provision.rs
+    ("Duplicate of line {}", "Duplicat al liniei {}"),
+    ("CSV file is empty", "Fișierul CSV este gol"),
+    ("CSV header must contain a username column", "Antetul CSV trebuie să conțină o coloană username"),
+    ("Missing username", "Lipsește numele de utilizator"),
+    ("Invalid username {}", "Nume de utilizator invalid {}"),
+    // rotation.rs
+    (
+        "Cannot rotate {}; create the new key on the token and re-run pki init",
+        "{} nu poate fi rotit; creați noua cheie pe token și rulați din nou pki init",
+    ),
+    ("Nothing to rotate", "Nimic de rotit"),
+    ("Failed to cross-sign the new CA certificate", "Nu s-a putut semna încrucișat noul certificat CA"),
+    // server.rs
+    ("Serving PKI on {}://{}", "Se servește PKI pe {}://{}"),
+    ("No --token or PKI_API_TOKEN given; send this one: {}", "Nu s-a dat --token sau PKI_API_TOKEN; trimiteți acesta: {}"),
+    (
+        "Client certificate authentication requires --tls-cert and --tls-key",
+        "Autentificarea cu certificat de client necesită --tls-cert și --tls-key",
+    ),
+    (
+        "CSR common name {} does not match the authorized identifier",
+        "Numele comun {} din CSR nu corespunde identificatorului autorizat",
+    ),
+    ("Missing or invalid API token", "Token API lipsă sau invalid"),
+    ("Malformed request line", "Linie de cerere malformată"),
+    ("Invalid Content-Length", "Content-Length invalid"),
+    ("Request body too large", "Corpul cererii este prea mare"),
+    ("Request headers too large", "Antetele cererii sunt prea mari"),
+    // signer.rs
+    ("SSH certificate signing is not supported with {}", "Semnarea certificatelor SSH nu este acceptată cu {}"),
+    ("Failed to generate CA private key", "Nu s-a putut genera cheia privată a CA-ului"),
+    ("PEM file {}", "Fișierul PEM {}"),
+    ("CA key {} is not accessible; create it on the token first", "Cheia CA {} nu este accesibilă; creați-o întâi pe token"),
+    ("PKCS#11 key {}", "Cheia PKCS#11 {}"),
+    // ssh.rs
+    ("At least one principal is required for an SSH certificate", "Un certificat SSH necesită cel puțin un principal"),
+    ("{} is not an OpenSSH public key (.pub) file", "{} nu este un fișier de cheie publică OpenSSH (.pub)"),
+    ("Public key {} not found", "Cheia publică {} nu a fost găsită"),
+    ("Failed to sign SSH key {}", "Nu s-a putut semna cheia SSH {}"),
+    ("Failed to export the CA public key in OpenSSH format", "Nu s-a putut exporta cheia publică a CA-ului în format OpenSSH"),
+    // subca.rs
+    (
+        "Invalid name constraint {} (expected DNS:, email:, IP:, URI: or dirName:<value>)",
+        "Constrângere de nume invalidă {} (se așteaptă DNS:, email:, IP:, URI: sau dirName:<valoare>)",
+    ),
+    ("Invalid sub-CA name {}", "Nume de sub-CA invalid {}"),
+    ("Failed to generate key for sub-CA {}", "Nu s-a putut genera cheia pentru sub-CA-ul {}"),
+    ("Failed to generate CSR for sub-CA {}", "Nu s-a putut genera CSR-ul pentru sub-CA-ul {}"),
+    ("Failed to sign certificate for sub-CA {}", "Nu s-a putut semna certificatul pentru sub-CA-ul {}"),
+    // timestamp.rs
+    ("Failed to create timestamp query for {}", "Nu s-a putut crea cererea de marcă temporală pentru {}"),
+    ("Failed to obtain timestamp token from {}", "Nu s-a putut obține jetonul de marcă temporală de la {}"),
+    ("TSA {} rejected the timestamp request", "TSA {} a respins cererea de marcă temporală"),
+    ("Failed to read timestamp token {}", "Nu s-a putut citi jetonul de marcă temporală {}"),
+    // tlsdemo.rs
+    ("TLS demo server has no certificate", "Serverul demo TLS nu are certificat"),
+    ("The server accepted a client without a certificate ({})", "Serverul a acceptat un client fără certificat ({})"),
+    ("TLS demo server panicked", "Serverul demo TLS s-a oprit cu panică"),
+    // transparency.rs
+    ("Malformed inclusion proof", "Dovadă de includere malformată"),
+    ("Corrupt entry on line {} of {}", "Intrare coruptă pe linia {} din {}"),
+    ("The certificate of {} is not in the transparency log", "Certificatul lui {} nu este în jurnalul de transparență"),
+    (
+        "The log has {} entries; pick an older size from 1 to {}",
+        "Jurnalul are {} intrări; alegeți o dimensiune mai veche, de la 1 la {}",
+    ),
+    ("No certificate in {}", "Niciun certificat în {}"),
+    // trust.rs
+    (
+        "No supported trust store found (looked for {} and {})",
+        "Nu s-a găsit niciun depozit de încredere acceptat (s-au căutat {} și {})",
+    ),
+    (
+        "Failed to install the CA certificate into the {} (are you root/administrator?)",
+        "Nu s-a putut instala certificatul CA în {} (sunteți root/administrator?)",
+    ),
+    ("The CA certificate is not installed ({} does not exist)", "Certificatul CA nu este instalat ({} nu există)"),
+    (
+        "Failed to remove the CA certificate from the {} (are you root/administrator?)",
+        "Nu s-a putut elimina certificatul CA din {} (sunteți root/administrator?)",
+    ),
+    (
+        "{} (run as root to change the system trust store)",
+        "{} (rulați ca root pentru a modifica depozitul de încredere al sistemului)",
+    ),
+    // watch.rs
+    ("{}{}: signed, certificate {}", "{}{}: semnat, certificatul {}"),
+    ("{}{}: queued for approval", "{}{}: în așteptarea aprobării"),
+    ("{}{}: rejected, {}", "{}{}: respins, {}"),
+    ("{}{}: failed, will retry ({})", "{}{}: eșuat, se va reîncerca ({})"),
+    ("subject {} does not match {}", "subiectul {} nu corespunde cu {}"),
+    ("No pending CSR {}", "Niciun CSR în așteptare {}"),
+    // yubikey.rs
+    ("Unsupported PIV slot {} (expected one of {})", "Slot PIV neacceptat {} (se așteaptă unul dintre {})"),
+    ("Failed to generate a key in PIV slot {}", "Nu s-a putut genera o cheie în slotul PIV {}"),
+    ("Failed to create a CSR from PIV slot {}", "Nu s-a putut crea un CSR din slotul PIV {}"),
+    ("Key or certificate for user {} not found", "Cheia sau certificatul utilizatorului {} nu a fost găsit"),
+    ("Failed to import key of user {} into PIV slot {}", "Nu s-a putut importa cheia utilizatorului {} în slotul PIV {}"),
+    (
+        "Failed to import certificate of user {} into PIV slot {}",
+        "Nu s-a putut importa certificatul utilizatorului {} în slotul PIV {}",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_core::i18n::{mismatched_placeholders, untranslated, CORE};
+
+    use crate::inventory::CertificateStatus;
+
+    #[test]
+    fn every_message_has_a_romanian_translation() {
+        let sources = [
+            include_str!("acme.rs"),
+            include_str!("archive.rs"),
+            include_str!("audit.rs"),
+            include_str!("backend.rs"),
+            include_str!("backup.rs"),
+            include_str!("chain.rs"),
+            include_str!("cli.rs"),
+            include_str!("client.rs"),
+            include_str!("codesign.rs"),
+            include_str!("convert.rs"),
+            include_str!("cosign.rs"),
+            include_str!("csr.rs"),
+            include_str!("database.rs"),
+            include_str!("digest.rs"),
+            include_str!("dsa.rs"),
+            include_str!("embedded.rs"),
+            include_str!("envelope.rs"),
+            include_str!("est.rs"),
+            include_str!("exec.rs"),
+            include_str!("extensions.rs"),
+            include_str!("inventory.rs"),
+            include_str!("keyalgorithm.rs"),
+            include_str!("java.rs"),
+            include_str!("keyshares.rs"),
+            include_str!("ledger.rs"),
+            include_str!("main.rs"),
+            include_str!("manifest.rs"),
+            include_str!("masterkey.rs"),
+            include_str!("metrics.rs"),
+            include_str!("native.rs"),
+            include_str!("notify.rs"),
+            include_str!("openssl.rs"),
+            include_str!("output.rs"),
+            include_str!("passphrase.rs"),
+            include_str!("pem.rs"),
+            include_str!("permissions.rs"),
+            include_str!("profile.rs"),
+            include_str!("provision.rs"),
+            include_str!("renewal.rs"),
+            include_str!("report.rs"),
+            include_str!("rotation.rs"),
+            include_str!("runner.rs"),
+            include_str!("san.rs"),
+            include_str!("server.rs"),
+            include_str!("signer.rs"),
+            include_str!("ssh.rs"),
+            include_str!("subca.rs"),
+            include_str!("subject.rs"),
+            include_str!("timestamp.rs"),
+            include_str!("tlsdemo.rs"),
+            include_str!("transparency.rs"),
+            include_str!("trust.rs"),
+            include_str!("verification.rs"),
+            include_str!("watch.rs"),
+            include_str!("yubikey.rs"),
+        ];
+        for source in sources {
+            assert_eq!(untranslated(source, &[CORE, MESSAGES]), Vec::<String>::new());
+        }
+        // Shown through `tr!` but not written as literals there
+        for status in [CertificateStatus::Valid, CertificateStatus::Expiring, CertificateStatus::Expired, CertificateStatus::Revoked] {
+            assert!(MESSAGES.iter().any(|(english, _)| *english == status.name()), "{}", status.name());
+        }
+        assert!(mismatched_placeholders(MESSAGES).is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+use std::io;
+
+use serde_json::{json, Value};
+
+use crate::PKIConfig;
+use crate::inventory::{unix_now, CertificateStatus, CrlInfo};
+
+const STATUSES: [CertificateStatus; 4] = [
+    CertificateStatus::Valid,
+    CertificateStatus::Expiring,
+    CertificateStatus::Expired,
+    CertificateStatus::Revoked,
+];
+
+/// Snapshot of the CA state for monitoring
+pub(crate) struct Metrics {
+    /// Expiry warning window the `expiring` count was taken with
+    pub(crate) warn_days: u32,
+    /// User certificates per lifecycle state
+    pub(crate) certificates: Vec<(CertificateStatus, usize)>,
+    pub(crate) crl: Option<CrlInfo>,
+    /// Most recent time of each audited action, in Unix seconds
+    pub(crate) last_operations: Vec<(String, u64)>,
+    pub(crate) transparency_log_entries: usize,
+    pub(crate) collected_at: u64,
+}
+
+impl PKIConfig {
+    /// Count certificates by state and read the CRL dates, audit log and
+    /// transparency log
+    pub(crate) fn collect_metrics(&self, warn_days: u32) -> io::Result<Metrics> {
+        let inventory = self.certificate_inventory(warn_days)?;
+        let certificates = STATUSES
+            .iter()
+            .map(|&status| (status, inventory.iter().filter(|info| info.status == status).count()))
+            .collect();
+
+        let mut last_operations: Vec<(String, u64)> = Vec::new();
+        for event in self.recent_audit_events(usize::MAX)? {
+            if !last_operations.iter().any(|(action, _)| *action == event.action) {
+                last_operations.push((event.action, event.timestamp));
+            }
+        }
+        last_operations.sort();
+
+        Ok(Metrics {
+            warn_days,
+            certificates,
+            crl: self.crl_info()?,
+            last_operations,
+            transparency_log_entries: self.log_entries()?.len(),
+            collected_at: unix_now(),
+        })
+    }
+}
+
+impl Metrics {
+    /// Prometheus text exposition format (version 0.0.4)
+    pub(crate) fn to_prometheus(&self) -> String {
+        let mut text = String::new();
+        let mut family = |name: &str, kind: &str, help: &str, samples: &[(String, u64)]| {
+            text.push_str(&format!("# HELP {} {}\n# TYPE {} {}\n", name, help, name, kind));
+            for (labels, value) in samples {
+                text.push_str(&format!("{}{} {}\n", name, labels, value));
+            }
+        };
+
+        let total = self.certificates.iter().map(|(_, count)| count).sum::<usize>();
+        family("pki_certificates_issued", "gauge", "User certificates issued by the CA", &[(String::new(), total as u64)]);
+        family(
+            "pki_certificates",
+            "gauge",
+            &format!("User certificates by status (expiring: within {} days)", self.warn_days),
+            &self
+                .certificates
+                .iter()
+                .map(|(status, count)| (format!("{{status=\"{}\"}}", status.name()), *count as u64))
+                .collect::<Vec<_>>(),
+        );
+
+        if let Some(crl) = &self.crl {
+            family("pki_crl_last_update_timestamp_seconds", "gauge", "Time the current CRL was issued", &[(String::new(), crl.last_update)]);
+            family(
+                "pki_crl_age_seconds",
+                "gauge",
+                "Seconds since the current CRL was issued",
+                &[(String::new(), self.collected_at.saturating_sub(crl.last_update))],
+            );
+            if let Some(next_update) = crl.next_update {
+                family("pki_crl_next_update_timestamp_seconds", "gauge", "Time the current CRL expires", &[(String::new(), next_update)]);
+            }
+        }
+
+        family(
+            "pki_last_operation_timestamp_seconds",
+            "gauge",
+            "Time each CA operation was last performed",
+            &self
+                .last_operations
+                .iter()
+                .map(|(action, timestamp)| (format!("{{action=\"{}\"}}", escape_label(action)), *timestamp))
+                .collect::<Vec<_>>(),
+        );
+        family(
+            "pki_transparency_log_entries",
+            "gauge",
+            "Certificates recorded in the transparency log",
+            &[(String::new(), self.transparency_log_entries as u64)],
+        );
+
+        text
+    }
+
+    pub(crate) fn to_json(&self) -> Value {
+        json!({
+            "warn_days": self.warn_days,
+            "certificates": self
+                .certificates
+                .iter()
+                .map(|(status, count)| (status.name().to_string(), Value::from(*count)))
+                .collect::<serde_json::Map<_, _>>(),
+            "crl": self.crl.as_ref().map(|crl| json!({
+                "last_update": crl.last_update,
+                "next_update": crl.next_update,
+                "age_seconds": self.collected_at.saturating_sub(crl.last_update),
+            })),
+            "last_operations": self
+                .last_operations
+                .iter()
+                .map(|(action, timestamp)| (action.clone(), Value::from(*timestamp)))
+                .collect::<serde_json::Map<_, _>>(),
+            "transparency_log_entries": self.transparency_log_entries,
+            "collected_at": self.collected_at,
+        })
+    }
+}
+
+/// Escape a Prometheus label value
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
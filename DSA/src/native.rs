@@ -0,0 +1,562 @@
+//! The native backend: RSA keys, certificate requests, certificates and
+//! document signatures made in-process with the `rsa` crate and
+//! crypto-core's DER, so `pki init`, issuance and `pki sign` work on
+//! machines without the `openssl` binary. The files are the same ones
+//! OpenSSL writes and reads.
+//!
+//! Only the operations behind [`CryptoBackend`] are native; every other
+//! command still runs openssl, which stays the default backend.
+
+use std::fs;
+use std::io;
+
+use crypto_core::codec::encode_hex;
+use crypto_core::der::{self, Reader};
+use crypto_core::{tr, CipherError};
+use rsa::pkcs1::DecodeRsaPrivateKey;
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey, LineEnding};
+use rsa::rand_core::OsRng;
+use rsa::traits::PublicKeyParts;
+use rsa::{Pkcs1v15Sign, RsaPrivateKey, RsaPublicKey};
+
+use crate::PKIConfig;
+use crate::audit::format_unix_time;
+use crate::backend::CryptoBackend;
+use crate::database::{asn1_time, IssuanceEntry};
+use crate::digest::Digest;
+use crate::exec;
+use crate::inventory::unix_now;
+use crate::keyalgorithm::KeyAlgorithm;
+use crate::profile::{CertificateProfile, KEY_USAGE_BITS};
+use crate::san::{general_name, EXTENSION_REQUEST, SUBJECT_ALT_NAME, URI_NAME};
+use crate::subject::{subject_fields, ATTRIBUTES};
+
+const SUBJECT_KEY_IDENTIFIER: &str = "2.5.29.14";
+const KEY_USAGE: &str = "2.5.29.15";
+pub(crate) const BASIC_CONSTRAINTS: &str = "2.5.29.19";
+const CRL_DISTRIBUTION_POINTS: &str = "2.5.29.31";
+const CERTIFICATE_POLICIES: &str = "2.5.29.32";
+const AUTHORITY_KEY_IDENTIFIER: &str = "2.5.29.35";
+const EXTENDED_KEY_USAGE: &str = "2.5.29.37";
+const AUTHORITY_INFO_ACCESS: &str = "1.3.6.1.5.5.7.1.1";
+const OCSP: &str = "1.3.6.1.5.5.7.48.1";
+const CA_ISSUERS: &str = "1.3.6.1.5.5.7.48.2";
+
+/// Context-specific tags of the key identifier and of the CRL distribution
+/// point fields
+const KEY_IDENTIFIER: u8 = 0x80;
+const DISTRIBUTION_POINT: u8 = 0xA0;
+const FULL_NAME: u8 = 0xA0;
+
+fn invalid_data(error: CipherError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
+
+/// In dry-run mode, print `operation` instead of doing it
+fn planned(operation: &str) -> bool {
+    if exec::is_dry_run() {
+        exec::plan(&format!("{} (native backend)", operation));
+    }
+    exec::is_dry_run()
+}
+
+fn oid(dotted: &str) -> Vec<u8> {
+    der::encode_oid(dotted).expect("the object identifiers used here are valid")
+}
+
+/// A PKCS#8 or PKCS#1 PEM RSA key, as openssl and this backend write them
+fn read_private_key(path: &str) -> io::Result<RsaPrivateKey> {
+    let pem = fs::read_to_string(path)?;
+    RsaPrivateKey::from_pkcs8_pem(&pem)
+        .or_else(|e| RsaPrivateKey::from_pkcs1_pem(&pem).map_err(|_| e))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, tr!("Cannot use the key in {}: {}", path, e)))
+}
+
+/// SubjectPublicKeyInfo of an RSA key
+fn public_key_der(key: &RsaPublicKey) -> io::Result<Vec<u8>> {
+    Ok(key.to_public_key_der().map_err(io::Error::other)?.into_vec())
+}
+
+fn read_public_key(public_key: &[u8]) -> io::Result<RsaPublicKey> {
+    RsaPublicKey::from_public_key_der(public_key).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+/// The CA key file; keys in a token or encrypted ones can only be used
+/// through openssl
+fn ca_key_path(pki: &PKIConfig) -> io::Result<&str> {
+    pki.ca_signer.key_file().filter(|_| !pki.ca_signer.key_encrypted()).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            tr!("The native backend cannot use {}; set pki.backend = openssl", pki.ca_signer.describe())
+        )
+    })
+}
+
+/// Master-key encryption of user keys is done by openssl
+fn check_user_keys(pki: &PKIConfig) -> io::Result<()> {
+    if pki.user_keys_protected() {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            tr!("User keys protected by a master key need pki.backend = openssl")
+        ));
+    }
+    Ok(())
+}
+
+/// The native backend only does RSA
+fn check_key_algorithm(algorithm: KeyAlgorithm) -> io::Result<()> {
+    if algorithm != KeyAlgorithm::Rsa {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            tr!("The native backend cannot make {} keys; set pki.backend = openssl", algorithm.name())
+        ));
+    }
+    Ok(())
+}
+
+fn generate_key(path: &str, bits: u32) -> io::Result<()> {
+    if planned(&format!("generate a {}-bit RSA key in {}", bits, path)) {
+        return Ok(());
+    }
+    let key = RsaPrivateKey::new(&mut OsRng, bits as usize).map_err(io::Error::other)?;
+    exec::write(path, key.to_pkcs8_pem(LineEnding::LF).map_err(io::Error::other)?.as_bytes())
+}
+
+/// DigestInfo of `data`, the input of a PKCS#1 v1.5 signature
+fn digest_info(digest: Digest, data: &[u8]) -> Vec<u8> {
+    digest.digest_info(ring::digest::digest(digest.algorithm(), data).as_ref())
+}
+
+/// PKCS#1 v1.5 signature over `data`, after checking the key is big enough
+fn sign(key: &RsaPrivateKey, digest: Digest, data: &[u8]) -> io::Result<Vec<u8>> {
+    digest.check_rsa_key(key.n().bits() as u32)?;
+    key.sign(Pkcs1v15Sign::new_unprefixed(), &digest_info(digest, data)).map_err(io::Error::other)
+}
+
+fn verify(key: &RsaPublicKey, digest: Digest, data: &[u8], signature: &[u8]) -> bool {
+    key.verify(Pkcs1v15Sign::new_unprefixed(), &digest_info(digest, data), signature).is_ok()
+}
+
+/// AlgorithmIdentifier { sha*WithRSAEncryption, NULL }
+fn signature_algorithm(digest: Digest) -> Vec<u8> {
+    der::sequence(&[oid(digest.rsa_signature_oid()), der::encode(der::NULL, &[])])
+}
+
+/// The digest of an RSA signature algorithm
+fn signature_digest(algorithm: &[u8]) -> Result<Digest, CipherError> {
+    let found = Reader::new(algorithm).nested(der::SEQUENCE)?.raw_element()?;
+    [Digest::Sha256, Digest::Sha384, Digest::Sha512]
+        .into_iter()
+        .find(|digest| oid(digest.rsa_signature_oid()) == found)
+        .ok_or_else(|| CipherError::InvalidInput(String::from("unsupported signature algorithm")))
+}
+
+/// `tbs` with its signature, as a certificate or request:
+/// SEQUENCE { tbs, algorithm, BIT STRING signature }
+fn signed(tbs: Vec<u8>, key: &RsaPrivateKey, digest: Digest) -> io::Result<Vec<u8>> {
+    let signature = sign(key, digest, &tbs)?;
+    Ok(der::sequence(&[tbs, signature_algorithm(digest), der::encode(der::BIT_STRING, &[&[0][..], &signature].concat())]))
+}
+
+/// Check the signature of a certificate or request whose signer's key is `key`
+fn check_signed(signed: &[u8], key: &RsaPublicKey) -> Result<bool, CipherError> {
+    let mut outer = Reader::new(signed).nested(der::SEQUENCE)?;
+    let tbs = outer.raw_element()?;
+    let digest = signature_digest(outer.raw_element()?)?;
+    let signature = outer.expect(der::BIT_STRING)?;
+    Ok(signature.split_first().is_some_and(|(&unused, signature)| unused == 0 && verify(key, digest, tbs, signature)))
+}
+
+/// A Name from an OpenSSL `-subj` string such as `/CN=alice/O=Course`,
+/// where `\` escapes the next character
+pub(crate) fn name_from_subject(subject: &str) -> io::Result<Vec<u8>> {
+    let mut name = Vec::new();
+    for ((_, dotted, string_type), value) in subject_fields(subject)? {
+        let attribute = der::sequence(&[oid(dotted), der::encode(string_type, value.as_bytes())]);
+        name.push(der::encode(der::SET, &attribute));
+    }
+    Ok(der::sequence(&name))
+}
+
+/// A Name in OpenSSL's `compat` form, `/CN=alice/O=Course`, with `/` and `\`
+/// in values escaped so [`name_from_subject`] reads it back. Attributes
+/// missing from [`ATTRIBUTES`] are named by their hex-encoded OID.
+pub(crate) fn subject_from_name(name: &[u8]) -> Result<String, CipherError> {
+    let mut subject = String::new();
+    let mut rdns = Reader::new(name).nested(der::SEQUENCE)?;
+    while !rdns.is_empty() {
+        let mut attributes = rdns.nested(der::SET)?;
+        while !attributes.is_empty() {
+            let mut attribute = attributes.nested(der::SEQUENCE)?;
+            let kind = attribute.raw_element()?;
+            let (_, value) = attribute.element()?;
+            let short = ATTRIBUTES
+                .iter()
+                .find(|(_, dotted, _)| oid(dotted) == kind)
+                .map(|(short, _, _)| short.to_string())
+                .unwrap_or_else(|| encode_hex(kind));
+            let value = String::from_utf8_lossy(value).replace('\\', "\\\\").replace('/', "\\/");
+            subject.push_str(&format!("/{}={}", short, value));
+        }
+    }
+    Ok(subject)
+}
+
+/// Validity { notBefore, notAfter } from now for `days`
+fn validity(days: u32) -> Vec<u8> {
+    let now = unix_now();
+    let time = |unix: u64| {
+        let text = asn1_time(&format_unix_time(unix)).unwrap_or_default();
+        let tag = if text.len() == 13 { der::UTC_TIME } else { der::GENERALIZED_TIME };
+        der::encode(tag, text.as_bytes())
+    };
+    der::sequence(&[time(now), time(now + u64::from(days) * 86_400)])
+}
+
+/// SHA-1 of the subjectPublicKey bits, as OpenSSL computes key identifiers
+fn key_identifier(public_key: &[u8]) -> Result<Vec<u8>, CipherError> {
+    let mut info = Reader::new(public_key).nested(der::SEQUENCE)?;
+    info.element()?;
+    let bits = info.expect(der::BIT_STRING)?;
+    Ok(ring::digest::digest(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY, bits.get(1..).unwrap_or_default()).as_ref().to_vec())
+}
+
+/// Extension { id, critical, OCTET STRING value }
+fn extension(id: &str, critical: bool, value: Vec<u8>) -> Vec<u8> {
+    let mut parts = vec![oid(id)];
+    if critical {
+        parts.push(der::encode(der::BOOLEAN, &[0xff]));
+    }
+    parts.push(der::encode(der::OCTET_STRING, &value));
+    der::sequence(&parts)
+}
+
+/// Key identifiers of the subject and of the issuer
+fn key_identifiers(public_key: &[u8], issuer_public_key: &[u8]) -> Result<Vec<Vec<u8>>, CipherError> {
+    Ok(vec![
+        extension(SUBJECT_KEY_IDENTIFIER, false, der::encode(der::OCTET_STRING, &key_identifier(public_key)?)),
+        extension(AUTHORITY_KEY_IDENTIFIER, false, der::sequence(&[der::encode(KEY_IDENTIFIER, &key_identifier(issuer_public_key)?)])),
+    ])
+}
+
+/// keyUsage BIT STRING with the named bits set, trailing zero bits dropped
+fn key_usage_bits(usage: &[&str]) -> Vec<u8> {
+    let bits = KEY_USAGE_BITS
+        .iter()
+        .enumerate()
+        .filter(|(_, name)| usage.contains(name))
+        .fold(0u8, |bits, (index, _)| bits | (0x80 >> index));
+    der::encode(der::BIT_STRING, &[bits.trailing_zeros().min(7) as u8, bits])
+}
+
+/// The extensions [`PKIConfig::user_extension_config`] asks openssl for
+fn user_extensions(
+    pki: &PKIConfig,
+    profile: Option<CertificateProfile>,
+    subject_alt_names: &[String],
+) -> io::Result<Vec<Vec<u8>>> {
+    // An end entity; the native backend only makes RSA keys
+    let mut extensions = vec![
+        extension(BASIC_CONSTRAINTS, true, der::sequence(&[])),
+        extension(KEY_USAGE, true, key_usage_bits(&CertificateProfile::key_usage(profile, true))),
+    ];
+    if let Some(profile) = profile {
+        extensions.push(extension(EXTENDED_KEY_USAGE, false, der::sequence(&[oid(profile.extended_key_usage_oid())])));
+    }
+    if !subject_alt_names.is_empty() {
+        let names = subject_alt_names.iter().map(|name| general_name(name)).collect::<io::Result<Vec<_>>>()?;
+        extensions.push(extension(SUBJECT_ALT_NAME, false, der::sequence(&names)));
+    }
+
+    let points = &pki.distribution_points;
+    if let Some(url) = &points.crl_url {
+        let full_name = der::encode(FULL_NAME, &der::encode(URI_NAME, url.as_bytes()));
+        let point = der::sequence(&[der::encode(DISTRIBUTION_POINT, &full_name)]);
+        extensions.push(extension(CRL_DISTRIBUTION_POINTS, false, der::sequence(&[point])));
+    }
+    let access: Vec<Vec<u8>> = [(OCSP, &points.ocsp_url), (CA_ISSUERS, &points.ca_issuers_url)]
+        .into_iter()
+        .filter_map(|(method, url)| url.as_ref().map(|url| der::sequence(&[oid(method), der::encode(URI_NAME, url.as_bytes())])))
+        .collect();
+    if !access.is_empty() {
+        extensions.push(extension(AUTHORITY_INFO_ACCESS, false, der::sequence(&access)));
+    }
+
+    if !pki.policy_oids.is_empty() {
+        let policies: Vec<Vec<u8>> = pki.policy_oids.iter().map(|policy| der::sequence(&[oid(policy)])).collect();
+        extensions.push(extension(CERTIFICATE_POLICIES, false, der::sequence(&policies)));
+    }
+    Ok(extensions)
+}
+
+/// TBSCertificate of a v3 certificate
+fn tbs_certificate(
+    serial: &[u8],
+    digest: Digest,
+    issuer: &[u8],
+    days: u32,
+    subject: &[u8],
+    public_key: &[u8],
+    extensions: &[Vec<u8>],
+) -> Vec<u8> {
+    der::sequence(&[
+        der::encode(der::CONTEXT_0, &der::encode(der::INTEGER, &[2])),
+        der::encode(der::INTEGER, serial),
+        signature_algorithm(digest),
+        issuer.to_vec(),
+        validity(days),
+        subject.to_vec(),
+        public_key.to_vec(),
+        der::encode(der::CONTEXT_3, &der::sequence(extensions)),
+    ])
+}
+
+/// The parts of a certificate the backend reads
+struct Certificate {
+    /// INTEGER contents
+    serial: Vec<u8>,
+    subject: Vec<u8>,
+    /// As stored: `YYMMDDHHMMSSZ` or `YYYYMMDDHHMMSSZ`
+    not_after: String,
+    /// SubjectPublicKeyInfo
+    public_key: Vec<u8>,
+}
+
+impl Certificate {
+    fn read(path: &str) -> io::Result<Self> {
+        let (label, der) = der::pem_decode(&fs::read_to_string(path)?).map_err(invalid_data)?;
+        if label != "CERTIFICATE" {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, tr!("No certificate in {}", path)));
+        }
+        Self::parse(&der).map_err(invalid_data)
+    }
+
+    fn parse(der: &[u8]) -> Result<Self, CipherError> {
+        let mut tbs = Reader::new(der).nested(der::SEQUENCE)?.nested(der::SEQUENCE)?;
+        if tbs.peek() == Some(der::CONTEXT_0) {
+            tbs.element()?;
+        }
+        let serial = tbs.expect(der::INTEGER)?.to_vec();
+        tbs.element()?;
+        tbs.element()?;
+        let mut validity = tbs.nested(der::SEQUENCE)?;
+        validity.element()?;
+        let not_after = String::from_utf8_lossy(validity.element()?.1).into_owned();
+        let subject = tbs.raw_element()?.to_vec();
+        let public_key = tbs.raw_element()?.to_vec();
+        Ok(Certificate { serial, subject, not_after, public_key })
+    }
+
+    fn rsa_key(&self) -> io::Result<RsaPublicKey> {
+        read_public_key(&self.public_key)
+    }
+}
+
+/// Subject and SubjectPublicKeyInfo of a CSR, once its self-signature checks out
+fn read_csr(path: &str) -> io::Result<(Vec<u8>, Vec<u8>)> {
+    let (_, der) = der::pem_decode(&fs::read_to_string(path)?).map_err(invalid_data)?;
+    let parse = || -> Result<(Vec<u8>, Vec<u8>), CipherError> {
+        let mut info = Reader::new(&der).nested(der::SEQUENCE)?.nested(der::SEQUENCE)?;
+        info.integer()?;
+        Ok((info.raw_element()?.to_vec(), info.raw_element()?.to_vec()))
+    };
+    let (subject, public_key) = parse().map_err(invalid_data)?;
+    let self_signed = check_signed(&der, &read_public_key(&public_key)?).map_err(invalid_data)?;
+
+    match (subject, public_key, self_signed) {
+        (subject, public_key, true) => Ok((subject, public_key)),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, tr!("The CSR in {} is not signed by its own key", path))),
+    }
+}
+
+/// Uses the `rsa` crate for keys and signatures and `ring` for hashing; no
+/// external process
+pub(crate) struct NativeBackend;
+
+impl CryptoBackend for NativeBackend {
+    fn name(&self) -> &'static str {
+        "native"
+    }
+
+    fn needs_openssl(&self) -> bool {
+        false
+    }
+
+    fn generate_ca_key(&self, pki: &PKIConfig) -> io::Result<()> {
+        let key_path = ca_key_path(pki)?;
+        check_key_algorithm(pki.ca_key_algorithm)?;
+        crate::permissions::create_private_file(key_path)?;
+        generate_key(key_path, pki.ca_key_bits)
+    }
+
+    fn create_ca_certificate(&self, pki: &PKIConfig) -> io::Result<()> {
+        let ca_cert_path = format!("{}/ca_certificate.pem", pki.ca_dir);
+        if planned(&format!("self-sign {}", ca_cert_path)) {
+            return Ok(());
+        }
+
+        let key = read_private_key(ca_key_path(pki)?)?;
+        let public_key = public_key_der(&key.to_public_key())?;
+        let name = name_from_subject(&pki.ca_subject.to_subj())?;
+        let mut serial = crypto_core::rng::bytes(20)?;
+        serial[0] &= 0x7f;
+
+        let mut extensions = key_identifiers(&public_key, &public_key).map_err(invalid_data)?;
+        extensions.push(extension(BASIC_CONSTRAINTS, true, der::sequence(&[der::encode(der::BOOLEAN, &[0xff])])));
+        let tbs = tbs_certificate(&serial, pki.signing_digest(), &name, pki.ca_validity_days, &name, &public_key, &extensions);
+        exec::write(&ca_cert_path, der::pem_encode("CERTIFICATE", &signed(tbs, &key, pki.signing_digest())?))
+    }
+
+    fn generate_user_key(&self, pki: &PKIConfig, username: &str) -> io::Result<()> {
+        check_user_keys(pki)?;
+        check_key_algorithm(pki.user_key_algorithm)?;
+        generate_key(&format!("{}/{}_private_key.pem", pki.users_dir, username), pki.user_key_bits)
+    }
+
+    fn create_csr(&self, pki: &PKIConfig, username: &str, subject: &str, subject_alt_names: &[String]) -> io::Result<()> {
+        let user_csr_path = format!("{}/{}_csr.pem", pki.users_dir, username);
+        check_user_keys(pki)?;
+        if planned(&format!("write the CSR {} for {}", user_csr_path, subject)) {
+            return Ok(());
+        }
+
+        let key = read_private_key(&format!("{}/{}_private_key.pem", pki.users_dir, username))?;
+        // Subject alternative names go in an extension request attribute
+        let mut attributes = Vec::new();
+        if !subject_alt_names.is_empty() {
+            let names = subject_alt_names.iter().map(|name| general_name(name)).collect::<io::Result<Vec<_>>>()?;
+            let extensions = der::sequence(&[extension(SUBJECT_ALT_NAME, false, der::sequence(&names))]);
+            attributes = der::sequence(&[oid(EXTENSION_REQUEST), der::encode(der::SET, &extensions)]);
+        }
+        // Version 0, subject, key and attributes
+        let info = der::sequence(&[
+            der::encode_integer(&0u32.into()),
+            name_from_subject(subject)?,
+            public_key_der(&key.to_public_key())?,
+            der::encode(der::CONTEXT_0, &attributes),
+        ]);
+        exec::write(&user_csr_path, der::pem_encode("CERTIFICATE REQUEST", &signed(info, &key, pki.signing_digest())?))
+    }
+
+    fn issue_certificate(
+        &self,
+        pki: &PKIConfig,
+        username: &str,
+        serial: &str,
+        profile: Option<CertificateProfile>,
+        subject_alt_names: &[String],
+    ) -> io::Result<()> {
+        let user_csr_path = format!("{}/{}_csr.pem", pki.users_dir, username);
+        let user_cert_path = format!("{}/{}_certificate.pem", pki.users_dir, username);
+        let extensions = user_extensions(pki, profile, subject_alt_names)?;
+        if planned(&format!("sign {} into {}", user_csr_path, user_cert_path)) {
+            return Ok(());
+        }
+
+        let ca = Certificate::read(&format!("{}/ca_certificate.pem", pki.ca_dir))?;
+        let ca_key = read_private_key(ca_key_path(pki)?)?;
+        if ca_key.to_public_key() != ca.rsa_key()? {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, tr!("The CA key does not match the CA certificate")));
+        }
+        let (subject, public_key) = read_csr(&user_csr_path)?;
+        let serial = crypto_core::codec::decode_hex(serial).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+
+        let mut all_extensions = key_identifiers(&public_key, &ca.public_key).map_err(invalid_data)?;
+        all_extensions.extend(extensions);
+        let tbs = tbs_certificate(&serial, pki.signing_digest(), &ca.subject, pki.user_validity_days, &subject, &public_key, &all_extensions);
+        exec::write(&user_cert_path, der::pem_encode("CERTIFICATE", &signed(tbs, &ca_key, pki.signing_digest())?))
+    }
+
+    fn issuance_entry(&self, _pki: &PKIConfig, cert_path: &str) -> io::Result<IssuanceEntry> {
+        let certificate = Certificate::read(cert_path)?;
+        // A leading zero byte only keeps the INTEGER positive
+        let serial = match certificate.serial.as_slice() {
+            [0, rest @ ..] if !rest.is_empty() => rest,
+            serial => serial,
+        };
+        Ok(IssuanceEntry {
+            serial: encode_hex(serial).to_ascii_uppercase(),
+            subject: subject_from_name(&certificate.subject).map_err(invalid_data)?,
+            expiry: certificate.not_after,
+        })
+    }
+
+    fn sign_document(&self, pki: &PKIConfig, username: &str, document_path: &str) -> io::Result<()> {
+        let signature_path = format!("{}.sig", document_path);
+        check_user_keys(pki)?;
+        if planned(&format!("sign {} into {}", document_path, signature_path)) {
+            return Ok(());
+        }
+
+        let key = read_private_key(&format!("{}/{}_private_key.pem", pki.users_dir, username))?;
+        exec::write(&signature_path, sign(&key, pki.signing_digest(), &fs::read(document_path)?)?)
+    }
+
+    fn verify_document_signature(&self, pki: &PKIConfig, username: &str, document_path: &str, digest: Digest) -> io::Result<bool> {
+        let public_key = user_public_key(pki, username)?;
+        let Ok(signature) = fs::read(format!("{}.sig", document_path)) else {
+            return Ok(false);
+        };
+        Ok(verify(&public_key, digest, &fs::read(document_path)?, &signature))
+    }
+
+    fn signature_digest(&self, pki: &PKIConfig, username: &str, document_path: &str) -> io::Result<Option<String>> {
+        let public_key = user_public_key(pki, username)?;
+        let Ok(signature) = fs::read(format!("{}.sig", document_path)) else {
+            return Ok(None);
+        };
+        // The digest whose DigestInfo the signature holds
+        let document = fs::read(document_path)?;
+        Ok([Digest::Sha256, Digest::Sha384, Digest::Sha512]
+            .into_iter()
+            .find(|&digest| verify(&public_key, digest, &document, &signature))
+            .map(|digest| digest.name().to_string()))
+    }
+}
+
+/// The key in a user's certificate, once the certificate's signature checks
+/// out against the CA certificate
+fn user_public_key(pki: &PKIConfig, username: &str) -> io::Result<RsaPublicKey> {
+    let user_cert_path = format!("{}/{}_certificate.pem", pki.users_dir, username);
+    let unreadable = |_| io::Error::other(tr!("Failed to read public key of user {}", username));
+    let certificate = Certificate::read(&user_cert_path).map_err(unreadable)?;
+    let (_, der) = der::pem_decode(&fs::read_to_string(&user_cert_path)?).map_err(invalid_data)?;
+
+    let ca_key = Certificate::read(&format!("{}/ca_certificate.pem", pki.ca_dir))?.rsa_key()?;
+    if !check_signed(&der, &ca_key).map_err(invalid_data)? {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            tr!("The certificate of {} is not signed by the CA", username)
+        ));
+    }
+    certificate.rsa_key()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_usage_drops_trailing_zero_bits() {
+        assert_eq!(key_usage_bits(&["digitalSignature"]), [der::BIT_STRING, 2, 7, 0x80]);
+        assert_eq!(key_usage_bits(&["digitalSignature", "keyEncipherment"]), [der::BIT_STRING, 2, 5, 0xa0]);
+    }
+
+    #[test]
+    fn subjects_round_trip_through_names() {
+        let name = name_from_subject("/CN=Ana Pop/O=Course\\/2026/C=RO/emailAddress=ana@example.com").unwrap();
+        let subject = subject_from_name(&name).unwrap();
+        assert_eq!(subject, "/CN=Ana Pop/O=Course\\/2026/C=RO/emailAddress=ana@example.com");
+        assert_eq!(name_from_subject(&subject).unwrap(), name);
+        // countryName is a PrintableString, the rest UTF8String
+        assert!(name.windows(3).any(|window| window == [der::PRINTABLE_STRING, 2, b'R']));
+
+        let escaped = name_from_subject("/CN=back\\\\slash/OU=a=b").unwrap();
+        assert_eq!(name_from_subject(&subject_from_name(&escaped).unwrap()).unwrap(), escaped);
+
+        assert!(name_from_subject("CN=alice").is_err());
+        assert!(name_from_subject("/CN").is_err());
+        assert_eq!(name_from_subject("/XX=1").unwrap_err().kind(), io::ErrorKind::Unsupported);
+    }
+
+}
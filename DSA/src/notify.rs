@@ -0,0 +1,361 @@
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::Command;
+use std::time::Duration;
+
+use crypto_core::codec::encode_base64;
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::audit::format_unix_time;
+use crate::client::tls_connect;
+use crate::exec::{self, Execute};
+use crate::inventory::{unix_now, CertificateStatus};
+use crate::profile::CertificateProfile;
+use crate::provision::parse_users_csv;
+
+/// SMTP relay used by `pki notify`
+pub(crate) struct SmtpSettings {
+    /// `host[:port]`, port 25 by default
+    pub(crate) server: String,
+    /// Envelope and header sender address
+    pub(crate) from: String,
+    /// Upgrade the connection with STARTTLS, verifying the relay against `ca_cert`
+    pub(crate) starttls: bool,
+    pub(crate) ca_cert: String,
+    /// User name and password for AUTH PLAIN
+    pub(crate) credentials: Option<(String, String)>,
+}
+
+/// How many days before expiry users are warned, optionally per profile
+/// (`--days 30 --days server=60`)
+pub(crate) struct NotifyWindows {
+    pub(crate) default_days: u32,
+    pub(crate) profile_days: Vec<(CertificateProfile, u32)>,
+}
+
+impl NotifyWindows {
+    pub(crate) fn parse(values: &[String]) -> io::Result<Self> {
+        let invalid = |value: &str| io::Error::new(
+            io::ErrorKind::InvalidInput,
+            tr!("Invalid warning window {} (expected <days> or <profile>=<days>)", value)
+        );
+
+        let mut windows = NotifyWindows { default_days: 30, profile_days: Vec::new() };
+        for value in values {
+            match value.split_once('=') {
+                Some((profile, days)) => {
+                    let days = days.parse::<u32>().map_err(|_| invalid(value))?;
+                    windows.profile_days.push((CertificateProfile::parse(profile)?, days));
+                }
+                None => windows.default_days = value.parse::<u32>().map_err(|_| invalid(value))?,
+            }
+        }
+        Ok(windows)
+    }
+
+    pub(crate) fn days_for(&self, profile: Option<CertificateProfile>) -> u32 {
+        self.profile_days
+            .iter()
+            .rev()
+            .find(|(window_profile, _)| Some(*window_profile) == profile)
+            .map_or(self.default_days, |(_, days)| *days)
+    }
+
+    fn longest(&self) -> u32 {
+        self.profile_days.iter().map(|(_, days)| *days).fold(self.default_days, u32::max)
+    }
+}
+
+/// A certificate inside its warning window
+pub(crate) struct ExpiryNotice {
+    pub(crate) username: String,
+    pub(crate) serial: String,
+    pub(crate) profile: Option<CertificateProfile>,
+    /// Address from the CSV if given, else from the certificate
+    pub(crate) email: Option<String>,
+    /// Expiry in Unix seconds
+    pub(crate) not_after: u64,
+}
+
+impl ExpiryNotice {
+    pub(crate) fn days_left(&self) -> u64 {
+        self.not_after.saturating_sub(unix_now()) / 86_400
+    }
+
+    fn message(&self, from: &str, to: &str) -> String {
+        let days = match self.days_left() {
+            1 => String::from("1 day"),
+            days => format!("{} days", days),
+        };
+
+        format!(
+            "From: {from}\n\
+             To: {to}\n\
+             Subject: Your certificate expires in {days}\n\
+             Date: {date}\n\
+             MIME-Version: 1.0\n\
+             Content-Type: text/plain; charset=utf-8\n\
+             \n\
+             Hello {username},\n\
+             \n\
+             your certificate (serial {serial}) expires on {expiry}, in {days}.\n\
+             Please ask for a renewal before then so that signing and\n\
+             authentication keep working.\n",
+            date = format_rfc5322_time(unix_now()),
+            username = self.username,
+            serial = self.serial,
+            expiry = format_unix_time(self.not_after),
+        )
+    }
+}
+
+/// What happened to one notice
+pub(crate) enum Delivery {
+    Sent,
+    /// Sent on an earlier run for the same certificate
+    AlreadySent,
+    NoAddress,
+    Failed(io::Error),
+}
+
+impl Delivery {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Sent => "sent",
+            Self::AlreadySent => "already-sent",
+            Self::NoAddress => "no-address",
+            Self::Failed(_) => "failed",
+        }
+    }
+}
+
+impl PKIConfig {
+    /// Valid certificates expiring within their profile's warning window.
+    /// Profiles and addresses come from `csv_path` when given, falling back
+    /// to the certificate's extended key usage and email addresses.
+    pub(crate) fn expiry_notices(&self, windows: &NotifyWindows, csv_path: Option<&str>) -> io::Result<Vec<ExpiryNotice>> {
+        let records = match csv_path {
+            Some(csv_path) => parse_users_csv(&fs::read_to_string(csv_path)?)?
+                .into_iter()
+                .filter_map(|(_, _, record)| record.ok())
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let now = unix_now();
+        let mut notices = Vec::new();
+        for username in self.issued_usernames()? {
+            let details = self.inspect_certificate(&username, windows.longest())?;
+            if details.info.status != CertificateStatus::Expiring {
+                continue;
+            }
+
+            let record = records.iter().find(|record| record.username == username);
+            let profile = record
+                .and_then(|record| record.profile)
+                .or_else(|| details.extended_key_usage.as_deref().and_then(CertificateProfile::from_extended_key_usage));
+            if details.info.not_after > now + u64::from(windows.days_for(profile)) * 86_400 {
+                continue;
+            }
+
+            let email = match record.and_then(|record| record.email.clone()) {
+                Some(email) => Some(email),
+                None => self.certificate_email(&username)?,
+            };
+            notices.push(ExpiryNotice {
+                username,
+                serial: details.info.serial,
+                profile,
+                email: email.filter(|email| is_valid_address(email)),
+                not_after: details.info.not_after,
+            });
+        }
+
+        Ok(notices)
+    }
+
+    /// Email each notice's user, skipping certificates already notified about
+    /// unless `--force` is given
+    pub(crate) fn send_expiry_notices(&self, smtp: &SmtpSettings, notices: &[ExpiryNotice]) -> io::Result<Vec<Delivery>> {
+        let notified: Vec<String> = self
+            .recent_audit_events(usize::MAX)?
+            .into_iter()
+            .filter(|event| event.action == "expiry-notified")
+            .map(|event| event.detail)
+            .collect();
+
+        notices
+            .iter()
+            .map(|notice| {
+                let detail = format!("{} {}", notice.username, notice.serial);
+                let Some(email) = &notice.email else {
+                    return Ok(Delivery::NoAddress);
+                };
+                if notified.contains(&detail) && !self.force {
+                    return Ok(Delivery::AlreadySent);
+                }
+
+                if exec::is_dry_run() {
+                    exec::plan(&format!("email {} via {} about certificate {}", email, smtp.server, notice.serial));
+                } else if let Err(e) = send_mail(smtp, email, &notice.message(&smtp.from, email)) {
+                    return Ok(Delivery::Failed(e));
+                }
+                self.record_audit_event("expiry-notified", &detail)?;
+                Ok(Delivery::Sent)
+            })
+            .collect()
+    }
+
+    /// First email address in a user's certificate (subject or subjectAltName)
+    fn certificate_email(&self, username: &str) -> io::Result<Option<String>> {
+        let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
+        let output = Command::new("openssl")
+            .args(["x509", "-noout", "-email", "-in", &user_cert_path])
+            .run(self.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to read certificate of user {}", username)));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).lines().next().map(|line| line.trim().to_string()))
+    }
+}
+
+/// Plain `local@domain` addresses only, so nothing can break out of the
+/// SMTP command or header it is written into
+fn is_valid_address(address: &str) -> bool {
+    address
+        .split_once('@')
+        .is_some_and(|(local, domain)| !local.is_empty() && !domain.is_empty() && !domain.contains('@'))
+        && !address.chars().any(|c| c.is_whitespace() || c.is_control() || "<>,;\"".contains(c))
+}
+
+/// Deliver one message through the relay
+fn send_mail(smtp: &SmtpSettings, to: &str, message: &str) -> io::Result<()> {
+    if smtp.credentials.is_some() && !smtp.starttls {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            tr!("Refusing to send SMTP credentials over an unencrypted connection; add --starttls")
+        ));
+    }
+
+    let (host, port) = match smtp.server.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse::<u16>().map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid port in {}", smtp.server))
+        })?),
+        None => (smtp.server.as_str(), 25),
+    };
+    let stream = TcpStream::connect((host, port))?;
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+
+    let mut session = SmtpSession::new(stream);
+    session.reply(&[220])?;
+    let hello = format!("EHLO {}", smtp.from.rsplit('@').next().unwrap_or("localhost"));
+    let extensions = session.command(&hello, &[250])?;
+
+    if smtp.starttls {
+        if !extensions.lines().any(|line| line.get(4..).is_some_and(|name| name.eq_ignore_ascii_case("STARTTLS"))) {
+            return Err(io::Error::other(tr!("{} does not offer STARTTLS", smtp.server)));
+        }
+        session.command("STARTTLS", &[220])?;
+
+        let mut session = SmtpSession::new(tls_connect(host, session.into_inner(), &smtp.ca_cert, None)?);
+        session.command(&hello, &[250])?;
+        session.deliver(smtp, to, message)
+    } else {
+        session.deliver(smtp, to, message)
+    }
+}
+
+/// Command/reply exchange with an SMTP server (RFC 5321)
+struct SmtpSession<S: Read + Write> {
+    reader: BufReader<S>,
+}
+
+impl<S: Read + Write> SmtpSession<S> {
+    fn new(stream: S) -> Self {
+        SmtpSession { reader: BufReader::new(stream) }
+    }
+
+    fn into_inner(self) -> S {
+        self.reader.into_inner()
+    }
+
+    /// Read a possibly multi-line reply, failing unless its code is one of `expected`
+    fn reply(&mut self, expected: &[u16]) -> io::Result<String> {
+        let mut text = String::new();
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, tr!("SMTP server closed the connection")));
+            }
+            text.push_str(line.trim_end());
+            text.push('\n');
+            // `250-...` continues the reply, `250 ...` ends it
+            if line.as_bytes().get(3) != Some(&b'-') {
+                break;
+            }
+        }
+
+        let code = text
+            .get(..3)
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, tr!("Malformed SMTP reply")))?;
+        if !expected.contains(&code) {
+            return Err(io::Error::other(tr!("SMTP server replied: {}", text.trim_end())));
+        }
+        Ok(text)
+    }
+
+    fn command(&mut self, line: &str, expected: &[u16]) -> io::Result<String> {
+        let stream = self.reader.get_mut();
+        stream.write_all(line.as_bytes())?;
+        stream.write_all(b"\r\n")?;
+        stream.flush()?;
+        self.reply(expected)
+    }
+
+    fn deliver(&mut self, smtp: &SmtpSettings, to: &str, message: &str) -> io::Result<()> {
+        if let Some((user, password)) = &smtp.credentials {
+            let token = encode_base64(format!("\0{}\0{}", user, password).as_bytes());
+            self.command(&format!("AUTH PLAIN {}", token), &[235])?;
+        }
+        self.command(&format!("MAIL FROM:<{}>", smtp.from), &[250])?;
+        self.command(&format!("RCPT TO:<{}>", to), &[250, 251])?;
+        self.command("DATA", &[354])?;
+
+        // Lines starting with a dot are escaped by doubling it
+        let mut data = String::new();
+        for line in message.lines() {
+            if line.starts_with('.') {
+                data.push('.');
+            }
+            data.push_str(line);
+            data.push_str("\r\n");
+        }
+        data.push('.');
+        self.command(&data, &[250])?;
+
+        self.command("QUIT", &[221])?;
+        Ok(())
+    }
+}
+
+/// Format Unix seconds as an email `Date:` header value
+fn format_rfc5322_time(timestamp: u64) -> String {
+    const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+    // `YYYY-MM-DD HH:MM:SS UTC`
+    let stamp = format_unix_time(timestamp);
+    let month = stamp[5..7].parse::<usize>().unwrap_or(1);
+    format!(
+        "{}, {} {} {} {} +0000",
+        WEEKDAYS[(timestamp / 86_400 % 7) as usize],
+        stamp[8..10].trim_start_matches('0'),
+        MONTHS[month - 1],
+        &stamp[..4],
+        &stamp[11..19]
+    )
+}
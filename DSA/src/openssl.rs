@@ -0,0 +1,80 @@
+use std::fmt;
+use std::io;
+use std::process::Command;
+
+use crypto_core::tr;
+
+/// Oldest release supporting every flag the PKI passes (providers,
+/// `-dateopt`, `-jdktrust`)
+const MINIMUM_VERSION: OpensslVersion = OpensslVersion { major: 3, minor: 0, patch: 0 };
+
+/// Version of the `openssl` binary on the PATH
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct OpensslVersion {
+    pub(crate) major: u32,
+    pub(crate) minor: u32,
+    pub(crate) patch: u32,
+}
+
+impl fmt::Display for OpensslVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Find the `openssl` binary and parse its version
+pub(crate) fn detect_openssl() -> io::Result<OpensslVersion> {
+    let output = Command::new("openssl").arg("version").output().map_err(|e| {
+        if e.kind() == io::ErrorKind::NotFound {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                tr!("The openssl command was not found; install OpenSSL 3 and make sure it is on the PATH")
+            )
+        } else {
+            e
+        }
+    })?;
+
+    if !output.status.success() {
+        return Err(io::Error::other(tr!("`openssl version` failed")));
+    }
+
+    let banner = String::from_utf8_lossy(&output.stdout);
+    parse_version(&banner).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            tr!("Unrecognized openssl version: {}", banner.trim())
+        )
+    })
+}
+
+/// Fail early unless a recent enough OpenSSL is installed
+pub(crate) fn require_openssl() -> io::Result<OpensslVersion> {
+    let version = detect_openssl()?;
+
+    if version < MINIMUM_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            tr!("OpenSSL {} is too old; version {} or newer is required", version, MINIMUM_VERSION)
+        ));
+    }
+
+    Ok(version)
+}
+
+/// Parse `OpenSSL 3.0.13 30 Jan 2024 (Library: ...)`. LibreSSL banners are
+/// rejected since it lacks the provider interface.
+fn parse_version(banner: &str) -> Option<OpensslVersion> {
+    let version = banner.strip_prefix("OpenSSL ")?.split_whitespace().next()?;
+
+    // Drop letter suffixes of 1.x releases such as `1.1.1w`
+    let mut numbers = version
+        .split('.')
+        .map(|part| part.trim_end_matches(|c: char| c.is_ascii_alphabetic()).parse::<u32>());
+
+    Some(OpensslVersion {
+        major: numbers.next()?.ok()?,
+        minor: numbers.next()?.ok()?,
+        patch: numbers.next().and_then(Result::ok).unwrap_or(0),
+    })
+}
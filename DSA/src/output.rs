@@ -0,0 +1,99 @@
+use std::io;
+use std::str::FromStr;
+
+use crypto_core::tr;
+use serde_json::{Map, Value};
+
+/// How command results are printed (`--output text|json`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl OutputFormat {
+    pub(crate) fn parse(name: &str) -> io::Result<Self> {
+        match name {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("Unknown output format {} (expected text or json)", name)
+            )),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = io::Error;
+
+    fn from_str(name: &str) -> io::Result<Self> {
+        Self::parse(name)
+    }
+}
+
+/// Result of one command. Text lines are printed as they are produced;
+/// fields are collected and printed as a single JSON object at the end.
+pub(crate) struct CommandOutput {
+    format: OutputFormat,
+    fields: Map<String, Value>,
+}
+
+impl CommandOutput {
+    pub(crate) fn new(format: OutputFormat, command: &str) -> Self {
+        let mut fields = Map::new();
+        fields.insert("command".to_string(), Value::from(command));
+        fields.insert("ok".to_string(), Value::from(true));
+        CommandOutput { format, fields }
+    }
+
+    pub(crate) fn is_json(&self) -> bool {
+        self.format == OutputFormat::Json
+    }
+
+    /// Human-readable line, shown in text mode only
+    pub(crate) fn line(&self, text: impl AsRef<str>) {
+        if !self.is_json() {
+            println!("{}", text.as_ref());
+        }
+    }
+
+    /// Structured result, shown in JSON mode only
+    pub(crate) fn field(&mut self, key: &str, value: impl Into<Value>) {
+        self.fields.insert(key.to_string(), value.into());
+    }
+
+    /// Record a negative result, such as a signature that does not verify.
+    /// Everything is still printed, but JSON reports `"ok": false` and `pki`
+    /// exits with status 1, so scripts checking `$?` do not accept it.
+    pub(crate) fn fail(&mut self) {
+        self.fields.insert("ok".to_string(), Value::from(false));
+    }
+
+    /// The `valid` field of a verification, failing the command when false
+    pub(crate) fn verdict(&mut self, valid: bool) {
+        self.field("valid", valid);
+        if !valid {
+            self.fail();
+        }
+    }
+
+    /// Print the JSON document in JSON mode. Returns whether the command
+    /// succeeded.
+    pub(crate) fn finish(self) -> bool {
+        let succeeded = self.fields.get("ok") != Some(&Value::from(false));
+        if self.is_json() {
+            println!("{}", Value::Object(self.fields));
+        }
+        succeeded
+    }
+}
+
+/// Report a failed command as `{"command": ..., "ok": false, "error": ...}`
+pub(crate) fn print_json_error(command: &str, error: &io::Error) {
+    let mut fields = Map::new();
+    fields.insert("command".to_string(), Value::from(command));
+    fields.insert("ok".to_string(), Value::from(false));
+    fields.insert("error".to_string(), Value::from(error.to_string()));
+    println!("{}", Value::Object(fields));
+}
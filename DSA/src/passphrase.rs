@@ -0,0 +1,33 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::process::Command;
+
+/// Ask for a passphrase on the terminal without echoing it, or `None`
+/// when stdin is not a terminal and nobody can answer
+pub(crate) fn prompt(prompt: &str) -> io::Result<Option<String>> {
+    if !io::stdin().is_terminal() {
+        return Ok(None);
+    }
+
+    eprint!("{}", prompt);
+    io::stderr().flush()?;
+
+    let echo_off = set_echo(false);
+    let mut answer = String::new();
+    let read = io::stdin().lock().read_line(&mut answer);
+    if echo_off {
+        set_echo(true);
+        eprintln!();
+    }
+    read?;
+
+    Ok(Some(answer.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+/// Switch terminal echo with `stty`, which reads the terminal from stdin.
+/// Returns whether it worked.
+fn set_echo(enabled: bool) -> bool {
+    Command::new("stty")
+        .arg(if enabled { "echo" } else { "-echo" })
+        .status()
+        .is_ok_and(|status| status.success())
+}
@@ -0,0 +1,80 @@
+//! PEM and subject text the PKI reads from users, the network and
+//! `openssl`. Nothing here uses the rest of the crate, so the fuzz targets
+//! in `fuzz/` include this file directly.
+
+use std::io;
+
+use crypto_core::codec::{decode_base64, Strictness};
+use crypto_core::tr;
+
+/// Split a concatenated PEM bundle into individual certificates
+pub(crate) fn split_certificates(pems: &str) -> Vec<String> {
+    const END: &str = "-----END CERTIFICATE-----";
+
+    pems.split_inclusive(END)
+        .filter_map(|chunk| {
+            let start = chunk.find("-----BEGIN CERTIFICATE-----")?;
+            chunk.ends_with(END).then(|| format!("{}\n", &chunk[start..]))
+        })
+        .collect()
+}
+
+/// Pick the CN out of `subject=...` output printed with `-nameopt RFC2253`
+pub(crate) fn common_name_from_subject(subject: &str) -> Option<String> {
+    rfc2253_fields(subject.trim().trim_start_matches("subject="))?
+        .into_iter()
+        .find_map(|(kind, value)| (kind == "CN" && !value.is_empty()).then_some(value))
+}
+
+/// Split an RFC 2253 name such as `CN=Pop\, Ana,O=Course` into attribute
+/// types and values, where `\` escapes the next character or starts two hex
+/// digits for one byte of UTF-8. `None` when it is malformed.
+pub(crate) fn rfc2253_fields(name: &str) -> Option<Vec<(String, String)>> {
+    let mut fields = Vec::new();
+    let (mut kind, mut value) = (String::new(), None::<Vec<u8>>);
+    let mut chars = name.trim().chars();
+    while let Some(c) = chars.next() {
+        match (c, &mut value) {
+            (',' | '+', Some(_)) => fields.push((std::mem::take(&mut kind), value.take())),
+            ('=', None) => value = Some(Vec::new()),
+            ('\\', Some(bytes)) => match chars.next()? {
+                high if high.is_ascii_hexdigit() => {
+                    let low = chars.next().filter(char::is_ascii_hexdigit)?;
+                    bytes.push(u8::from_str_radix(&format!("{}{}", high, low), 16).ok()?);
+                }
+                escaped => bytes.extend_from_slice(escaped.encode_utf8(&mut [0; 4]).as_bytes()),
+            },
+            (c, Some(bytes)) => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+            (c, None) => kind.push(c),
+        }
+    }
+    fields.push((kind, value));
+
+    fields
+        .into_iter()
+        .map(|(kind, value)| Some((kind.trim().to_string(), String::from_utf8(value?).ok()?)))
+        .collect()
+}
+
+/// Wrap the base64 DER of a PKCS#10 request, as EST clients send it, in a
+/// PEM `CERTIFICATE REQUEST` block
+pub(crate) fn csr_pem_from_base64(body: &str) -> io::Result<String> {
+    match decode_base64(body, Strictness::Lenient) {
+        Ok(der) if !der.is_empty() => {}
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                tr!("Request body is not a base64 encoded certificate request")
+            ));
+        }
+    }
+
+    let encoded: String = body.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    let mut csr_pem = String::from("-----BEGIN CERTIFICATE REQUEST-----\n");
+    for line in encoded.as_bytes().chunks(64) {
+        csr_pem.push_str(&String::from_utf8_lossy(line));
+        csr_pem.push('\n');
+    }
+    csr_pem.push_str("-----END CERTIFICATE REQUEST-----\n");
+    Ok(csr_pem)
+}
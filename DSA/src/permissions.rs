@@ -0,0 +1,180 @@
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::Path;
+
+
+use crate::PKIConfig;
+use crate::exec;
+
+/// A private key or directory that other users can access
+pub(crate) struct PermissionProblem {
+    pub(crate) path: String,
+    /// Unix mode bits, e.g. `0o644`
+    pub(crate) mode: u32,
+}
+
+impl PermissionProblem {
+    /// Readable by every user on the system, not just the owner's group
+    pub(crate) fn is_world_readable(&self) -> bool {
+        self.mode & 0o004 != 0
+    }
+}
+
+impl PKIConfig {
+    /// Files holding private key material: the CA key (when stored as a
+    /// file) and its shares, the wrapped master key, sub-CA keys, user keys
+    /// (openssl and `pki dsa`) and exported keystores
+    fn private_material_paths(&self) -> io::Result<Vec<String>> {
+        let mut paths = vec![
+            format!("{}/ca_private_key.pem", self.ca_dir),
+            format!("{}/master_key.json", self.ca_dir),
+        ];
+
+        if Path::new(&self.users_dir).exists() {
+            for entry in fs::read_dir(&self.users_dir)? {
+                let path = entry?.path();
+                let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                if file_name.ends_with("_private_key.pem")
+                    || file_name.ends_with("_dsa_key.json")
+                    || file_name.ends_with(".p12") || file_name.ends_with(".jks") {
+                    paths.push(path.to_string_lossy().into_owned());
+                }
+            }
+        }
+
+        let shares_dir = self.ca_key_shares_dir();
+        if Path::new(&shares_dir).exists() {
+            for entry in fs::read_dir(&shares_dir)? {
+                paths.push(entry?.path().to_string_lossy().into_owned());
+            }
+        }
+
+        let sub_ca_root = format!("{}/sub_ca", self.ca_dir);
+        if Path::new(&sub_ca_root).exists() {
+            for entry in fs::read_dir(&sub_ca_root)? {
+                paths.push(entry?.path().join("private_key.pem").to_string_lossy().into_owned());
+            }
+        }
+
+        paths.retain(|path| Path::new(path).exists());
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// The CA directory and private keys that are accessible by group or others
+    pub(crate) fn permission_problems(&self) -> io::Result<Vec<PermissionProblem>> {
+        let mut problems = Vec::new();
+
+        let mut paths = Vec::new();
+        if Path::new(&self.ca_dir).exists() {
+            paths.push(self.ca_dir.clone());
+        }
+        paths.extend(self.private_material_paths()?);
+
+        for path in paths {
+            if let Some(mode) = mode_bits(&path)? {
+                if mode & 0o077 != 0 {
+                    problems.push(PermissionProblem { path, mode });
+                }
+            }
+        }
+
+        Ok(problems)
+    }
+
+    /// Restrict the CA directory and every private key to their owner
+    pub(crate) fn fix_permissions(&self) -> io::Result<()> {
+        if Path::new(&self.ca_dir).exists() {
+            restrict_to_owner(&self.ca_dir)?;
+        }
+        for path in self.private_material_paths()? {
+            restrict_to_owner(&path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Create a directory (and parents) accessible only by its owner
+pub(crate) fn create_private_dir(path: &str) -> io::Result<()> {
+    exec::create_dir_all(path)?;
+    restrict_to_owner(path)
+}
+
+/// Create (or truncate) an empty owner-only file, so that a key later
+/// written into it by openssl is never readable by anyone else
+pub(crate) fn create_private_file(path: &str) -> io::Result<()> {
+    if exec::is_dry_run() {
+        exec::plan(&format!("create owner-only file {}", path));
+        return Ok(());
+    }
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    options.open(path)?;
+    restrict_to_owner(path)
+}
+
+/// Remove group and other access: mode 0700/0600 on Unix, an ACL granting
+/// only the current user on Windows
+pub(crate) fn restrict_to_owner(path: &str) -> io::Result<()> {
+    if exec::is_dry_run() {
+        exec::plan(&format!("restrict {} to its owner", path));
+        return Ok(());
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = if Path::new(path).is_dir() { 0o700 } else { 0o600 };
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))
+    }
+
+    #[cfg(windows)]
+    {
+        use crypto_core::tr;
+
+        let user = std::env::var("USERNAME").map_err(|_| io::Error::other(tr!("USERNAME is not set")))?;
+        let grant = if Path::new(path).is_dir() {
+            format!("{}:(OI)(CI)F", user)
+        } else {
+            format!("{}:F", user)
+        };
+
+        let output = std::process::Command::new("icacls")
+            .args([path, "/inheritance:r", "/grant:r", &grant])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to restrict access to {}", path)));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = path;
+        Ok(())
+    }
+}
+
+/// Permission bits of `path`, where the platform has them
+fn mode_bits(path: &str) -> io::Result<Option<u32>> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        Ok(Some(fs::metadata(path)?.permissions().mode() & 0o777))
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        Ok(None)
+    }
+}
@@ -0,0 +1,107 @@
+use std::io;
+
+use crypto_core::tr;
+
+/// keyUsage bits by their OpenSSL extension file names, in bit order
+pub(crate) const KEY_USAGE_BITS: [&str; 7] = [
+    "digitalSignature",
+    "nonRepudiation",
+    "keyEncipherment",
+    "dataEncipherment",
+    "keyAgreement",
+    "keyCertSign",
+    "cRLSign",
+];
+
+/// Certificate profiles selecting the extensions of issued user certificates
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CertificateProfile {
+    Client,
+    Server,
+    Email,
+    CodeSigning,
+}
+
+impl CertificateProfile {
+    /// Look up a profile by its command-line name
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "client" => Some(Self::Client),
+            "server" => Some(Self::Server),
+            "email" => Some(Self::Email),
+            "codesign" | "code-signing" => Some(Self::CodeSigning),
+            _ => None,
+        }
+    }
+
+    /// Like [`CertificateProfile::from_name`], with an error for unknown names
+    pub(crate) fn parse(name: &str) -> io::Result<Self> {
+        Self::from_name(name).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, tr!("Unknown profile {}", name))
+        })
+    }
+
+    /// Profile whose usage OpenSSL prints as `text`, e.g. `TLS Web Server Authentication`
+    pub(crate) fn from_extended_key_usage(text: &str) -> Option<Self> {
+        [Self::Client, Self::Server, Self::Email, Self::CodeSigning]
+            .into_iter()
+            .find(|profile| text.contains(profile.extended_key_usage_text()))
+    }
+
+    fn extended_key_usage_text(&self) -> &'static str {
+        match self {
+            Self::Client => "TLS Web Client Authentication",
+            Self::Server => "TLS Web Server Authentication",
+            Self::Email => "E-mail Protection",
+            Self::CodeSigning => "Code Signing",
+        }
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Client => "client",
+            Self::Server => "server",
+            Self::Email => "email",
+            Self::CodeSigning => "codesign",
+        }
+    }
+
+    /// Object identifier of this profile's extended key usage
+    pub(crate) fn extended_key_usage_oid(&self) -> &'static str {
+        match self {
+            Self::Server => "1.3.6.1.5.5.7.3.1",
+            Self::Client => "1.3.6.1.5.5.7.3.2",
+            Self::CodeSigning => "1.3.6.1.5.5.7.3.3",
+            Self::Email => "1.3.6.1.5.5.7.3.4",
+        }
+    }
+
+    /// keyUsage of a user certificate with an optional profile. Only RSA
+    /// keys encrypt session keys, so only they get `keyEncipherment`;
+    /// without a profile the certificate signs documents and receives
+    /// encrypted ones.
+    pub(crate) fn key_usage(profile: Option<Self>, rsa_key: bool) -> Vec<&'static str> {
+        let (mut usage, encipherment) = match profile {
+            None => (vec!["digitalSignature", "nonRepudiation"], true),
+            Some(Self::Client | Self::CodeSigning) => (vec!["digitalSignature"], false),
+            Some(Self::Server) => (vec!["digitalSignature"], true),
+            Some(Self::Email) => (vec!["digitalSignature", "nonRepudiation"], true),
+        };
+        if encipherment && rsa_key {
+            usage.push("keyEncipherment");
+        }
+        usage
+    }
+
+    /// Extension file lines for this profile's extended key usage
+    pub(crate) fn extension_lines(&self) -> String {
+        let extended_key_usage = match self {
+            Self::Client => "clientAuth",
+            Self::Server => "serverAuth",
+            Self::Email => "emailProtection",
+            Self::CodeSigning => "codeSigning",
+        };
+
+        format!("extendedKeyUsage = {}\n", extended_key_usage)
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+
+use crypto_core::tr;
+use tracing::{info, warn};
+
+use crate::PKIConfig;
+use crate::profile::CertificateProfile;
+use crate::subject::SubjectDn;
+
+/// One user row of a provisioning CSV
+pub(crate) struct UserRecord {
+    pub(crate) username: String,
+    pub(crate) common_name: Option<String>,
+    pub(crate) organization: Option<String>,
+    pub(crate) organizational_unit: Option<String>,
+    pub(crate) locality: Option<String>,
+    pub(crate) state: Option<String>,
+    pub(crate) country: Option<String>,
+    pub(crate) email: Option<String>,
+    pub(crate) profile: Option<CertificateProfile>,
+}
+
+impl UserRecord {
+    /// Subject fields this row sets; the rest come from `pki.user_subject`
+    pub(crate) fn subject(&self) -> SubjectDn {
+        SubjectDn {
+            common_name: self.common_name.clone(),
+            organization: self.organization.clone(),
+            organizational_unit: self.organizational_unit.clone(),
+            locality: self.locality.clone(),
+            state: self.state.clone(),
+            country: self.country.clone(),
+            email: self.email.clone(),
+        }
+    }
+}
+
+/// Outcome of provisioning one CSV row
+pub(crate) struct ProvisionResult {
+    pub(crate) line: usize,
+    pub(crate) username: String,
+    pub(crate) result: io::Result<()>,
+}
+
+impl PKIConfig {
+    /// Generate key, CSR and certificate for every user listed in a CSV
+    /// file, provisioning up to `jobs` users at a time
+    pub(crate) fn import_users(&self, csv_path: &str, jobs: usize) -> io::Result<Vec<ProvisionResult>> {
+        let contents = fs::read_to_string(csv_path)?;
+
+        // Two workers must never write the same user's files
+        let mut first_lines = HashMap::new();
+        let rows: Vec<_> = parse_users_csv(&contents)?
+            .into_iter()
+            .map(|(line, username, record)| {
+                let record = match first_lines.get(&username) {
+                    Some(first_line) => Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        tr!("Duplicate of line {}", first_line)
+                    )),
+                    None => {
+                        first_lines.insert(username.clone(), line);
+                        record
+                    }
+                };
+                (line, username, record)
+            })
+            .collect();
+
+        let queue = Mutex::new(rows.into_iter());
+        let mut results: Vec<ProvisionResult> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..jobs.max(1))
+                .map(|_| scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let next = queue.lock().unwrap_or_else(PoisonError::into_inner).next();
+                        let Some((line, username, record)) = next else {
+                            break;
+                        };
+                        let result = record.and_then(|record| self.provision_user(&record));
+                        match &result {
+                            Ok(()) => info!(line, username = %username, "provisioned"),
+                            Err(e) => warn!(line, username = %username, error = %e, "provisioning failed"),
+                        }
+                        done.push(ProvisionResult { line, username, result });
+                    }
+                    done
+                }))
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap_or_default())
+                .collect()
+        });
+
+        results.sort_by_key(|row| row.line);
+        Ok(results)
+    }
+
+    /// Run the issuance flow for a single user, resuming any earlier attempt
+    pub(crate) fn provision_user(&self, record: &UserRecord) -> io::Result<()> {
+        let subject = self.subject_for_user(&record.username, record.subject());
+        self.ensure_user_certificate(&record.username, &subject, record.profile)?;
+        Ok(())
+    }
+}
+
+/// Parse a CSV with a header row naming the columns.
+///
+/// `username` is required; `cn`, `o`, `ou`, `l`, `st`, `c`, `email` and
+/// `profile` are optional.
+/// Rows that fail to parse are returned as errors so the import can carry on.
+pub(crate) fn parse_users_csv(contents: &str) -> io::Result<Vec<(usize, String, io::Result<UserRecord>)>> {
+    let mut lines = contents
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+    let Some((_, header)) = lines.next() else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, tr!("CSV file is empty")));
+    };
+    let columns: Vec<String> = split_csv_line(header)
+        .into_iter()
+        .map(|column| column.to_ascii_lowercase())
+        .collect();
+
+    let column = |name: &str| columns.iter().position(|column| column == name);
+    let Some(username_column) = column("username") else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, tr!("CSV header must contain a username column")));
+    };
+    let (cn, o, ou, email, profile) = (column("cn"), column("o"), column("ou"), column("email"), column("profile"));
+    let (l, st, c) = (column("l"), column("st"), column("c"));
+
+    Ok(lines
+        .map(|(line_number, line)| {
+            let fields = split_csv_line(line);
+            let field = |index: Option<usize>| {
+                index
+                    .and_then(|index| fields.get(index))
+                    .filter(|value| !value.is_empty())
+                    .cloned()
+            };
+
+            let username = field(Some(username_column)).unwrap_or_default();
+            let record = if username.is_empty() {
+                Err(io::Error::new(io::ErrorKind::InvalidData, tr!("Missing username")))
+            } else if !is_valid_username(&username) {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    tr!("Invalid username {}", username)
+                ))
+            } else {
+                match field(profile).map(|name| CertificateProfile::from_name(&name).ok_or(name)) {
+                    Some(Err(name)) => Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        tr!("Unknown profile {}", name)
+                    )),
+                    profile => {
+                        let record = UserRecord {
+                            username: username.clone(),
+                            common_name: field(cn),
+                            organization: field(o),
+                            organizational_unit: field(ou),
+                            locality: field(l),
+                            state: field(st),
+                            country: field(c),
+                            email: field(email),
+                            profile: profile.and_then(Result::ok),
+                        };
+                        record.subject().check().map(|()| record)
+                    }
+                }
+            };
+
+            (line_number, username, record)
+        })
+        .collect())
+}
+
+/// Split a CSV line on commas, honouring double-quoted fields
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut current).trim().to_string()),
+            _ => current.push(c),
+        }
+    }
+    fields.push(current.trim().to_string());
+
+    fields
+}
+
+/// Usernames become file names, so keep them to a safe character set
+pub(crate) fn is_valid_username(username: &str) -> bool {
+    !username.is_empty()
+        && username.chars().all(|c| c.is_ascii_alphanumeric() || "._-".contains(c))
+        && !username.starts_with('.')
+}
@@ -0,0 +1,101 @@
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::exec::{self, Execute};
+use crate::inventory::CertificateStatus;
+use crate::profile::CertificateProfile;
+use crate::san;
+use crate::subject::SubjectDn;
+
+/// What `pki renew` did
+pub(crate) struct RenewalReport {
+    /// Where the previous certificate and CSR (and key, when rotated) went
+    pub(crate) archive_dir: String,
+    pub(crate) rotated_key: bool,
+    pub(crate) certificate: String,
+}
+
+impl PKIConfig {
+    /// Issue a user a new certificate with a fresh validity window in
+    /// place of their current one.
+    ///
+    /// The subject, profile and subject alternative names carry over. The
+    /// previous certificate and CSR are archived rather than overwritten,
+    /// and so is the key when `rotate_key` replaces it; they are moved back
+    /// if reissuing fails. A revoked certificate is only renewed onto a new
+    /// key.
+    pub(crate) fn renew_user_certificate(&self, username: &str, rotate_key: bool) -> io::Result<RenewalReport> {
+        let user_key_path = format!("{}/{}_private_key.pem", self.users_dir, username);
+        let user_csr_path = format!("{}/{}_csr.pem", self.users_dir, username);
+        let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
+
+        let details = self.inspect_certificate(username, 0)?;
+        if details.info.status == CertificateStatus::Revoked && !rotate_key {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("The certificate of {} is revoked; renew it with --rotate-key", username)
+            ));
+        }
+        let subject = self.certificate_subject(&user_cert_path)?;
+        let profile = details
+            .extended_key_usage
+            .as_deref()
+            .and_then(CertificateProfile::from_extended_key_usage);
+        let subject_alt_names = san::certificate_subject_alt_names(&user_cert_path)?;
+
+        let mut replaced = vec![user_cert_path.as_str()];
+        if rotate_key {
+            replaced.push(&user_key_path);
+        }
+        replaced.push(&user_csr_path);
+        let archived: Vec<&str> = replaced.iter().copied().filter(|path| Path::new(path).exists()).collect();
+        let archive_dir = self.archive_files(&archived)?;
+
+        let reissued = (|| {
+            if rotate_key {
+                self.generate_user_key(username)?;
+            }
+            self.generate_csr_with_subject_alt_names(username, &subject, &subject_alt_names)?;
+            self.sign_user_certificate_with_extensions(username, profile, &subject_alt_names)
+        })();
+        if let Err(e) = reissued {
+            self.restore_archived(&archive_dir, &replaced, &archived)?;
+            return Err(e);
+        }
+        self.record_audit_event("renewed", &format!("{} (previous certificate in {})", username, archive_dir))?;
+
+        Ok(RenewalReport { archive_dir, rotated_key: rotate_key, certificate: user_cert_path })
+    }
+
+    /// Undo a renewal that failed part way: drop whatever it wrote in place
+    /// of `replaced` and move the `archived` files back from `archive_dir`
+    pub(crate) fn restore_archived(&self, archive_dir: &str, replaced: &[&str], archived: &[&str]) -> io::Result<()> {
+        for path in replaced {
+            if archived.contains(path) {
+                let file_name = Path::new(path).file_name().unwrap_or_default();
+                exec::rename(Path::new(archive_dir).join(file_name), path)?;
+            } else if Path::new(path).exists() {
+                exec::remove_file(path)?;
+            }
+        }
+        exec::remove_dir(archive_dir)
+    }
+
+    /// Subject of a certificate in the `/CN=alice/O=Course` form taken by `-subj`
+    fn certificate_subject(&self, cert_path: &str) -> io::Result<String> {
+        let output = Command::new("openssl")
+            .args(["x509", "-noout", "-subject", "-nameopt", "RFC2253", "-in", cert_path])
+            .run(self.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to read certificate {}", cert_path)));
+        }
+
+        let subject = String::from_utf8_lossy(&output.stdout);
+        Ok(SubjectDn::parse_rfc2253(subject.trim().trim_start_matches("subject="))?.to_subj())
+    }
+}
@@ -0,0 +1,94 @@
+use std::io;
+use std::path::Path;
+
+use crate::PKIConfig;
+use crate::audit::format_unix_time;
+use crate::exec;
+use crate::inventory::{unix_now, CertificateStatus};
+
+/// Audit events shown on the dashboard
+const RECENT_EVENTS: usize = 25;
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin-bottom:2em}\
+th,td{border:1px solid #ccc;padding:.3em .8em;text-align:left}\
+th{background:#f0f0f0}\
+.valid{color:#1a7f37}.expiring{color:#9a6700}.expired,.revoked,.stale{color:#cf222e}\
+.summary span{margin-right:2em;font-weight:bold}";
+
+impl PKIConfig {
+    /// Write a static `index.html` dashboard of the CA state into `output_dir`
+    pub(crate) fn write_html_report(&self, output_dir: &str, warn_days: u32) -> io::Result<String> {
+        let inventory = self.certificate_inventory(warn_days)?;
+        let crl = self.crl_info()?;
+        let events = self.recent_audit_events(RECENT_EVENTS)?;
+
+        let count = |status: CertificateStatus| inventory.iter().filter(|info| info.status == status).count();
+
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>PKI status</title>");
+        html.push_str(&format!("<style>{}</style></head><body>\n", STYLE));
+        html.push_str("<h1>PKI status</h1>\n");
+        html.push_str(&format!("<p>Generated {}</p>\n", format_unix_time(unix_now())));
+
+        html.push_str("<p class=\"summary\">");
+        html.push_str(&format!("<span>Issued: {}</span>", inventory.len()));
+        for status in [CertificateStatus::Valid, CertificateStatus::Expiring, CertificateStatus::Expired, CertificateStatus::Revoked] {
+            html.push_str(&format!(
+                "<span class=\"{0}\">{0}: {1}</span>",
+                status.name(), count(status)
+            ));
+        }
+        html.push_str("</p>\n");
+
+        html.push_str("<h2>Certificate revocation list</h2>\n");
+        match &crl {
+            Some(crl) => html.push_str(&format!(
+                "<p class=\"{}\">Last update {}; next update {}</p>\n",
+                if crl.is_fresh() { "valid" } else { "stale" },
+                format_unix_time(crl.last_update),
+                crl.next_update.map(format_unix_time).unwrap_or_else(|| String::from("not set"))
+            )),
+            None => html.push_str("<p class=\"stale\">No CRL has been generated</p>\n"),
+        }
+
+        html.push_str(&format!("<h2>Certificates (expiry warning: {} days)</h2>\n", warn_days));
+        html.push_str("<table><tr><th>User</th><th>Serial</th><th>Not before</th><th>Not after</th><th>Status</th></tr>\n");
+        for info in &inventory {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td><code>{}</code></td><td>{}</td><td>{}</td><td class=\"{status}\">{status}</td></tr>\n",
+                escape_html(&info.username),
+                escape_html(&info.serial),
+                format_unix_time(info.not_before),
+                format_unix_time(info.not_after),
+                status = info.status.name()
+            ));
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>Recent audit events</h2>\n");
+        html.push_str("<table><tr><th>Time</th><th>Action</th><th>Detail</th></tr>\n");
+        for event in &events {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                format_unix_time(event.timestamp),
+                escape_html(&event.action),
+                escape_html(&event.detail)
+            ));
+        }
+        html.push_str("</table>\n</body></html>\n");
+
+        exec::create_dir_all(output_dir)?;
+        let index_path = Path::new(output_dir).join("index.html");
+        exec::write(&index_path, html)?;
+
+        Ok(index_path.to_string_lossy().into_owned())
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
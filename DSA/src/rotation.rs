@@ -0,0 +1,162 @@
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::exec::{self, Execute};
+use crate::inventory::CertificateStatus;
+use crate::san;
+use crate::signer;
+
+/// Extensions of the old CA's certificate for the new CA key
+const CROSS_SIGN_EXTENSIONS: &str = "[ v3_cross ]\n\
+    basicConstraints = critical, CA:TRUE\n\
+    keyUsage = critical, keyCertSign, cRLSign\n\
+    subjectKeyIdentifier = hash\n\
+    authorityKeyIdentifier = keyid:always\n";
+
+/// What `pki ca rotate` did
+pub(crate) struct RotationReport {
+    /// Where the old CA certificate and key and the replaced user
+    /// certificates were moved
+    pub(crate) archive_dir: String,
+    /// The new CA certificate signed by the old CA key
+    pub(crate) cross_signed: Option<String>,
+    /// Users issued a certificate under the new CA
+    pub(crate) reissued: Vec<String>,
+    /// Users listed in the pending renewal file instead
+    pub(crate) pending_renewal: Vec<String>,
+}
+
+impl PKIConfig {
+    /// Certificate of the new CA key issued by the previous CA, written by
+    /// `pki ca rotate --cross-sign` so clients still trusting the old root
+    /// can validate certificates from the new one
+    pub(crate) fn cross_signed_path(&self) -> String {
+        format!("{}/ca_cross_signed.pem", self.ca_dir)
+    }
+
+    /// Users whose certificates still chain to a retired CA
+    pub(crate) fn pending_renewal_path(&self) -> String {
+        format!("{}/pending_renewal.txt", self.ca_dir)
+    }
+
+    /// Replace the CA key and certificate.
+    ///
+    /// The old CA certificate and key are archived; the issuance database
+    /// and CRL are kept, so earlier revocations still count. With
+    /// `cross_sign`, the old key certifies the new one. Every valid user
+    /// certificate is then re-issued from its stored CSR (without its
+    /// original profile), or only listed for renewal when `reissue` is off or
+    /// the CSR is missing. Finally the chain bundles are rewritten.
+    pub(crate) fn rotate_ca(&self, cross_sign: bool, reissue: bool) -> io::Result<RotationReport> {
+        let ca_cert_path = format!("{}/ca_certificate.pem", self.ca_dir);
+
+        if !Path::new(&ca_cert_path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                tr!("CA certificate not found; initialize the PKI first")
+            ));
+        }
+        let Some(ca_key_path) = self.ca_signer.key_file() else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                tr!("Cannot rotate {}; create the new key on the token and re-run pki init", self.ca_signer.describe())
+            ));
+        };
+
+        // Revoked and expired certificates are left as they are
+        let mut reissued = Vec::new();
+        let mut pending_renewal = Vec::new();
+        for certificate in self.certificate_inventory(0)? {
+            if certificate.status != CertificateStatus::Valid {
+                continue;
+            }
+            let user_csr_path = format!("{}/{}_csr.pem", self.users_dir, certificate.username);
+            if reissue && Path::new(&user_csr_path).exists() {
+                // The names were checked when the certificate was issued
+                let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, certificate.username);
+                let subject_alt_names = san::certificate_subject_alt_names(&user_cert_path)?;
+                reissued.push((certificate.username, subject_alt_names));
+            } else {
+                pending_renewal.push(certificate.username);
+            }
+        }
+
+        let cross_signed_path = self.cross_signed_path();
+        let pending_renewal_path = self.pending_renewal_path();
+        let reissued_cert_paths: Vec<String> = reissued
+            .iter()
+            .map(|(username, _)| format!("{}/{}_certificate.pem", self.users_dir, username))
+            .collect();
+
+        let mut replaced = vec![
+            ca_cert_path.as_str(), ca_key_path, cross_signed_path.as_str(), pending_renewal_path.as_str(),
+        ];
+        replaced.extend(reissued_cert_paths.iter().map(String::as_str));
+        let archive_dir = self
+            .replace_existing(&replaced)?
+            .ok_or_else(|| io::Error::other(tr!("Nothing to rotate")))?;
+
+        self.generate_ca_key()?;
+        self.create_ca_certificate()?;
+
+        let cross_signed = if cross_sign {
+            let old_cert_path = format!("{}/ca_certificate.pem", archive_dir);
+            let old_key_path = Path::new(&archive_dir)
+                .join(Path::new(ca_key_path).file_name().unwrap_or_default())
+                .to_string_lossy()
+                .into_owned();
+            self.cross_sign_ca(&old_cert_path, &old_key_path)?;
+            Some(cross_signed_path)
+        } else {
+            None
+        };
+
+        for (username, subject_alt_names) in &reissued {
+            self.sign_user_certificate_with_extensions(username, None, subject_alt_names)?;
+        }
+
+        if !pending_renewal.is_empty() {
+            let list: String = pending_renewal.iter().map(|username| format!("{}\n", username)).collect();
+            exec::write(self.pending_renewal_path(), list)?;
+        }
+
+        self.export_chain()?;
+        self.record_audit_event("ca-rotated", &archive_dir)?;
+
+        let reissued = reissued.into_iter().map(|(username, _)| username).collect();
+        Ok(RotationReport { archive_dir, cross_signed, reissued, pending_renewal })
+    }
+
+    /// Certify the current CA key with a retired CA certificate and key
+    fn cross_sign_ca(&self, old_cert_path: &str, old_key_path: &str) -> io::Result<()> {
+        let ca_cert_path = format!("{}/ca_certificate.pem", self.ca_dir);
+        let ext_path = format!("{}/cross_sign_ext.cnf", self.ca_dir);
+        let serial = self.new_serial()?;
+
+        exec::write(&ext_path, CROSS_SIGN_EXTENSIONS)?;
+        let output = Command::new("openssl")
+            .args([
+                "x509",
+                "-in", &ca_cert_path,
+                "-CA", old_cert_path,
+                "-set_serial", &format!("0x{}", serial),
+                "-days", &self.ca_validity_days.to_string(),
+                &self.signing_digest().flag(),
+                "-clrext", "-extfile", &ext_path, "-extensions", "v3_cross",
+                "-out", &self.cross_signed_path()
+            ])
+            .args(signer::key_file_args("-CAkey", old_key_path))
+            .execute(self.runner());
+        exec::remove_file(&ext_path)?;
+
+        if !output?.status.success() {
+            return Err(io::Error::other(tr!("Failed to cross-sign the new CA certificate")));
+        }
+
+        Ok(())
+    }
+}
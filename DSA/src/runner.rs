@@ -0,0 +1,341 @@
+use std::io::{self, Write};
+use std::process::{Command, ExitStatus, Output, Stdio};
+
+use tracing::debug;
+
+use crate::exec::command_line;
+
+/// Runs the external programs (openssl, ssh-keygen, keytool, ...) that
+/// [`PKIConfig`](crate::PKIConfig) orchestrates, so tests can substitute
+/// scripted results for the real binaries.
+///
+/// Only helpers with no [`PKIConfig`](crate::PKIConfig) at hand still run
+/// processes directly: the `openssl version` probe at startup, `stty` while
+/// reading a passphrase and `icacls` when restricting a file on Windows.
+pub(crate) trait CommandRunner: Send + Sync {
+    /// Run a command to completion and capture its output
+    fn output(&self, command: &mut Command) -> io::Result<Output>;
+
+    /// Like [`CommandRunner::output`], feeding `input` to the command's stdin
+    fn output_with_input(&self, command: &mut Command, input: &[u8]) -> io::Result<Output>;
+
+    /// Run a command with the terminal attached, so it can prompt the user
+    fn interactive(&self, command: &mut Command) -> io::Result<ExitStatus>;
+}
+
+/// Runs commands as real child processes
+pub(crate) struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn output(&self, command: &mut Command) -> io::Result<Output> {
+        debug!(command = %command_line(command), "running");
+        let output = command.output()?;
+        log_failure(command, &output);
+        Ok(output)
+    }
+
+    fn output_with_input(&self, command: &mut Command, input: &[u8]) -> io::Result<Output> {
+        debug!(command = %command_line(command), input_bytes = input.len(), "running");
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(input)?;
+        }
+        let output = child.wait_with_output()?;
+        log_failure(command, &output);
+        Ok(output)
+    }
+
+    fn interactive(&self, command: &mut Command) -> io::Result<ExitStatus> {
+        // The arguments may carry a PIN, so only the program is logged
+        debug!(program = %command.get_program().to_string_lossy(), "running interactively");
+        command.status()
+    }
+}
+
+/// Callers turn a failed command into their own error; keep what the tool
+/// itself said for `-vv`
+fn log_failure(command: &Command, output: &Output) {
+    if !output.status.success() {
+        debug!(
+            command = %command_line(command),
+            status = %output.status,
+            stderr = %String::from_utf8_lossy(&output.stderr).trim(),
+            "command failed"
+        );
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::collections::VecDeque;
+    use std::fs;
+    use std::os::unix::process::ExitStatusExt;
+    use std::path::Path;
+    use std::process::ExitStatus;
+    use std::sync::{Arc, Mutex};
+
+    use crypto_core::config::Config;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::PKIConfig;
+    use crate::digest::Digest;
+    use crate::exec::command_line;
+    use crate::java::KeystoreFormat;
+    use crate::keyalgorithm::KeyAlgorithm;
+    use crate::signer::{FileSigner, Pkcs11Signer};
+    use crate::trust::TrustStore;
+
+    /// Replays scripted results in order and records every command line.
+    /// Like openssl, it creates the file named by `-out`.
+    struct ScriptedRunner {
+        results: Mutex<VecDeque<(bool, &'static str)>>,
+        commands: Mutex<Vec<String>>,
+    }
+
+    impl ScriptedRunner {
+        fn new(results: &[(bool, &'static str)]) -> Arc<Self> {
+            Arc::new(ScriptedRunner {
+                results: Mutex::new(results.iter().copied().collect()),
+                commands: Mutex::new(Vec::new()),
+            })
+        }
+
+        fn commands(&self) -> Vec<String> {
+            self.commands.lock().unwrap().clone()
+        }
+    }
+
+    impl CommandRunner for ScriptedRunner {
+        fn output(&self, command: &mut Command) -> io::Result<Output> {
+            self.commands.lock().unwrap().push(command_line(command));
+
+            let args: Vec<String> = command.get_args().map(|arg| arg.to_string_lossy().into_owned()).collect();
+            if let Some(position) = args.iter().position(|arg| arg == "-out") {
+                fs::write(&args[position + 1], "")?;
+            }
+
+            let (success, stdout) = self.results.lock().unwrap().pop_front().expect("unexpected command");
+            Ok(Output {
+                status: ExitStatus::from_raw(if success { 0 } else { 1 << 8 }),
+                stdout: stdout.as_bytes().to_vec(),
+                stderr: Vec::new(),
+            })
+        }
+
+        fn output_with_input(&self, command: &mut Command, _input: &[u8]) -> io::Result<Output> {
+            self.output(command)
+        }
+
+        fn interactive(&self, command: &mut Command) -> io::Result<ExitStatus> {
+            self.output(command).map(|output| output.status)
+        }
+    }
+
+    /// Lets a test keep inspecting the runner it handed to a [`PKIConfig`]
+    impl CommandRunner for Arc<ScriptedRunner> {
+        fn output(&self, command: &mut Command) -> io::Result<Output> {
+            self.as_ref().output(command)
+        }
+
+        fn output_with_input(&self, command: &mut Command, input: &[u8]) -> io::Result<Output> {
+            self.as_ref().output_with_input(command, input)
+        }
+
+        fn interactive(&self, command: &mut Command) -> io::Result<ExitStatus> {
+            self.as_ref().interactive(command)
+        }
+    }
+
+    const RSA_2048: &str = "Public-Key: (2048 bit)\nModulus:\n    00:c1\n";
+
+    /// A PKI rooted in a fresh temporary directory whose commands all go to `runner`
+    fn config(runner: &Arc<ScriptedRunner>) -> (TempDir, PKIConfig) {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = PKIConfig::from_config(&Config::defaults()).unwrap();
+        config.ca_dir = dir.path().join("ca").to_string_lossy().into_owned();
+        config.users_dir = dir.path().join("users").to_string_lossy().into_owned();
+        config.ca_signer = Box::new(FileSigner { key_path: format!("{}/ca_private_key.pem", config.ca_dir), encrypt: false });
+        config.runner = Box::new(Arc::clone(runner));
+        fs::create_dir_all(&config.ca_dir).unwrap();
+        fs::create_dir_all(&config.users_dir).unwrap();
+        (dir, config)
+    }
+
+    #[test]
+    fn failed_signing_is_reported() {
+        let runner = ScriptedRunner::new(&[(true, RSA_2048), (false, "")]);
+        let (dir, config) = config(&runner);
+        let document = dir.path().join("report.txt").to_string_lossy().into_owned();
+
+        let error = config.sign_document("alice", &document).unwrap_err();
+        assert_eq!(error.to_string(), "Failed to sign document for user alice");
+    }
+
+    #[test]
+    fn rejected_signature_verifies_as_false() {
+        let runner = ScriptedRunner::new(&[
+            (true, ""),
+            (true, "    0:d=0  hl=2 l=  81 cons: SEQUENCE\n    4:d=2  hl=2 l=   9 prim: OBJECT            :sha512\n"),
+            (true, ""),
+            (false, "Verification failure\n"),
+        ]);
+        let (dir, config) = config(&runner);
+        let document = dir.path().join("report.txt").to_string_lossy().into_owned();
+
+        assert!(!config.verify_document_signature("alice", &document).unwrap());
+        // Verified with the digest named in the signature
+        assert!(runner.commands()[3].starts_with("openssl dgst -sha512 -verify"));
+    }
+
+    #[test]
+    fn small_rsa_key_is_refused_for_sha512() {
+        let runner = ScriptedRunner::new(&[(true, "Public-Key: (512 bit)\nModulus:\n")]);
+        let (_dir, mut config) = config(&runner);
+        config.digest = Some(Digest::Sha512);
+
+        let error = config.sign_document("alice", "report.txt").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(runner.commands().len(), 1, "nothing may be signed after the check fails");
+    }
+
+    #[test]
+    fn ca_key_generation_failure_stops_init() {
+        let runner = ScriptedRunner::new(&[(false, "")]);
+        let (_dir, config) = config(&runner);
+
+        let error = config.init_ca().unwrap_err();
+        assert_eq!(error.to_string(), "Failed to generate CA private key");
+        assert_eq!(runner.commands().len(), 1);
+    }
+
+    #[test]
+    fn pkcs11_ca_key_stays_on_the_token() {
+        let runner = ScriptedRunner::new(&[(true, RSA_2048), (true, ""), (false, "")]);
+        let (_dir, mut config) = config(&runner);
+        config.ca_signer = Box::new(Pkcs11Signer { key_uri: "pkcs11:token=PKI;object=ca".into() });
+
+        assert!(config.init_ca().unwrap());
+        let commands = runner.commands();
+        assert!(commands[0].starts_with("openssl pkey -noout -text_pub"));
+        assert!(commands[0].ends_with("-provider pkcs11 -provider default -in 'pkcs11:token=PKI;object=ca'"));
+        assert!(commands[1].starts_with("openssl req -x509"));
+        assert!(commands[1].ends_with("-provider pkcs11 -provider default -key 'pkcs11:token=PKI;object=ca'"));
+        assert_eq!(commands.len(), 2);
+
+        let files: Vec<String> = fs::read_dir(&config.ca_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        assert!(files.iter().all(|file| !file.contains("key")), "{:?}", files);
+
+        // A key missing from the token is reported, not generated
+        let error = config.ca_signer.generate_key(config.runner(), KeyAlgorithm::Rsa, 4096).unwrap_err();
+        assert_eq!(error.to_string(), "CA key pkcs11:token=PKI;object=ca is not accessible; create it on the token first");
+        assert!(runner.commands()[2].starts_with("openssl pkey -pubout -provider pkcs11 -provider default -in"));
+    }
+
+    #[test]
+    fn trust_install_reports_missing_privileges() {
+        let runner = ScriptedRunner::new(&[(false, "")]);
+        let (_dir, config) = config(&runner);
+        let ca_cert_path = format!("{}/ca_certificate.pem", config.ca_dir);
+        fs::write(&ca_cert_path, "").unwrap();
+
+        let error = config.install_ca_trust(TrustStore::WindowsRoot).unwrap_err();
+        assert!(error.to_string().contains("administrator"));
+        assert_eq!(runner.commands(), [format!("certutil -addstore -f Root {}", ca_cert_path)]);
+    }
+
+    #[test]
+    fn yubikey_import_failure_stops_provisioning() {
+        let runner = ScriptedRunner::new(&[(false, "")]);
+        let (_dir, config) = config(&runner);
+        let user_key_path = format!("{}/alice_private_key.pem", config.users_dir);
+        fs::write(&user_key_path, "").unwrap();
+        fs::write(format!("{}/alice_certificate.pem", config.users_dir), "").unwrap();
+
+        let error = config.provision_yubikey("alice", "9a", false).unwrap_err();
+        assert_eq!(error.to_string(), "Failed to import key of user alice into PIV slot 9a");
+        assert!(runner.commands()[0].starts_with("ykman piv keys import"));
+        assert!(runner.commands()[0].ends_with(&format!("9a {}", user_key_path)));
+        assert_eq!(runner.commands().len(), 1);
+    }
+
+    #[test]
+    fn yubikey_import_writes_key_then_certificate() {
+        let runner = ScriptedRunner::new(&[(true, ""), (true, "")]);
+        let (_dir, config) = config(&runner);
+        let user_key_path = format!("{}/bob_private_key.pem", config.users_dir);
+        let user_cert_path = format!("{}/bob_certificate.pem", config.users_dir);
+        fs::write(&user_key_path, "").unwrap();
+        fs::write(&user_cert_path, "").unwrap();
+
+        config.provision_yubikey("bob", "9c", false).unwrap();
+        assert_eq!(runner.commands(), [
+            format!("ykman piv keys import 9c {}", user_key_path),
+            format!("ykman piv certificates import 9c {}", user_cert_path),
+        ]);
+
+        // An unknown slot is refused before ykman runs
+        let error = config.provision_yubikey("bob", "9f", false).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(runner.commands().len(), 2);
+    }
+
+    #[test]
+    fn java_keystores_keep_the_password_off_the_command_line() {
+        let runner = ScriptedRunner::new(&[(true, ""), (true, ""), (true, ""), (false, "")]);
+        let (dir, config) = config(&runner);
+        let ca_cert_path = format!("{}/ca_certificate.pem", config.ca_dir);
+        let user_cert_path = format!("{}/eve_certificate.pem", config.users_dir);
+        fs::write(&user_cert_path, "").unwrap();
+        let truststore = dir.path().join("truststore.p12").to_string_lossy().into_owned();
+        let keystore = dir.path().join("eve.jks").to_string_lossy().into_owned();
+
+        config.export_truststore(&truststore, KeystoreFormat::Pkcs12, "s3cret").unwrap();
+        config.export_truststore(&truststore, KeystoreFormat::Jks, "s3cret").unwrap();
+        let error = config.export_identity_keystore("eve", &keystore, KeystoreFormat::Jks, "s3cret").unwrap_err();
+        assert_eq!(error.to_string(), "Failed to convert keystore for user eve to JKS");
+
+        let commands = runner.commands();
+        assert_eq!(commands[0], format!(
+            "openssl pkcs12 -export -nokeys -in {} -caname pki-ca -jdktrust anyExtendedKeyUsage -passout env:PKI_KEYSTORE_PASSWORD -out {}",
+            ca_cert_path, truststore
+        ));
+        assert_eq!(commands[1], format!(
+            "keytool -importcert -noprompt -alias pki-ca -file {} -keystore {} -storetype JKS -storepass:env PKI_KEYSTORE_PASSWORD",
+            ca_cert_path, truststore
+        ));
+        // JKS goes through a PKCS#12 file that is removed even when keytool fails
+        assert_eq!(commands[2], format!(
+            "openssl pkcs12 -export -inkey {}/eve_private_key.pem -in {} -certfile {} -name eve -passout env:PKI_KEYSTORE_PASSWORD -out {}.p12.tmp",
+            config.users_dir, user_cert_path, ca_cert_path, keystore
+        ));
+        assert!(commands[3].starts_with(&format!("keytool -importkeystore -noprompt -srckeystore {}.p12.tmp -srcstoretype PKCS12", keystore)));
+        assert!(commands[3].ends_with(&format!("-destkeystore {} -deststoretype JKS -deststorepass:env PKI_KEYSTORE_PASSWORD", keystore)));
+        assert!(!Path::new(&format!("{}.p12.tmp", keystore)).exists());
+        assert!(commands.iter().all(|command| !command.contains("s3cret")));
+    }
+
+    #[test]
+    fn serials_stay_positive() {
+        let runner = ScriptedRunner::new(&[]);
+        let (_dir, config) = config(&runner);
+
+        for seed in 0..32 {
+            let serial = crypto_core::rng::with_seed(seed, || config.new_serial().unwrap());
+            assert_eq!(serial.len(), 40);
+            assert!(matches!(serial.as_bytes()[0], b'0'..=b'7'), "{}", serial);
+            assert_ne!(&serial[..2], "00");
+            assert_eq!(serial, crypto_core::rng::with_seed(seed, || config.new_serial().unwrap()));
+        }
+        // Drawn in-process, not from `openssl rand`
+        assert!(runner.commands().is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+//! Subject alternative names in OpenSSL's `DNS:`, `IP:`, `email:` and
+//! `URI:` syntax: their GeneralName encoding, and reading back the ones a
+//! CSR asks for so issuance can carry them into the certificate, or the
+//! ones a certificate has so renewal can keep them.
+
+use std::fs;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crypto_core::der::{self, Reader};
+use crypto_core::{tr, CipherError};
+
+/// Object identifier of the subjectAltName extension
+pub(crate) const SUBJECT_ALT_NAME: &str = "2.5.29.17";
+/// PKCS#9 extensionRequest, the CSR attribute holding requested extensions
+pub(crate) const EXTENSION_REQUEST: &str = "1.2.840.113549.1.9.14";
+
+/// Context-specific tags of the GeneralName choices
+pub(crate) const EMAIL_NAME: u8 = 0x81;
+pub(crate) const DNS_NAME: u8 = 0x82;
+pub(crate) const URI_NAME: u8 = 0x86;
+pub(crate) const IP_ADDRESS: u8 = 0x87;
+
+/// Check that `name` is a subject alternative name this PKI can issue
+pub(crate) fn parse_subject_alt_name(name: &str) -> io::Result<String> {
+    general_name(name)?;
+    Ok(name.to_string())
+}
+
+/// A GeneralName from OpenSSL's `DNS:`, `IP:`, `email:` or `URI:` syntax
+pub(crate) fn general_name(name: &str) -> io::Result<Vec<u8>> {
+    let unsupported = || io::Error::new(io::ErrorKind::InvalidInput, tr!("Unsupported subject alternative name {}", name));
+    let (kind, value) = name.split_once(':').ok_or_else(unsupported)?;
+    match kind {
+        "DNS" => Ok(der::encode(DNS_NAME, value.as_bytes())),
+        "email" => Ok(der::encode(EMAIL_NAME, value.as_bytes())),
+        "URI" => Ok(der::encode(URI_NAME, value.as_bytes())),
+        "IP" => match value.parse::<IpAddr>().map_err(|_| unsupported())? {
+            IpAddr::V4(address) => Ok(der::encode(IP_ADDRESS, &address.octets())),
+            IpAddr::V6(address) => Ok(der::encode(IP_ADDRESS, &address.octets())),
+        },
+        _ => Err(unsupported()),
+    }
+}
+
+/// OpenSSL syntax for a GeneralName; `None` for the choices the PKI
+/// does not issue (directory names, other names, ...)
+fn general_name_text(tag: u8, value: &[u8]) -> Option<String> {
+    let text = || String::from_utf8(value.to_vec()).ok();
+    match tag {
+        DNS_NAME => Some(format!("DNS:{}", text()?)),
+        EMAIL_NAME => Some(format!("email:{}", text()?)),
+        URI_NAME => Some(format!("URI:{}", text()?)),
+        IP_ADDRESS => match value.len() {
+            4 => Some(format!("IP:{}", Ipv4Addr::from(<[u8; 4]>::try_from(value).ok()?))),
+            16 => Some(format!("IP:{}", Ipv6Addr::from(<[u8; 16]>::try_from(value).ok()?))),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The subject alternative names requested in the CSR at `csr_path`, in
+/// OpenSSL syntax. Other requested extensions are ignored: a requester
+/// does not get to choose key usages or CA constraints.
+pub(crate) fn requested_subject_alt_names(csr_path: &str) -> io::Result<Vec<String>> {
+    let invalid = |e: CipherError| io::Error::new(io::ErrorKind::InvalidData, tr!("Cannot read CSR {}: {}", csr_path, e));
+    let (_, csr) = der::pem_decode(&fs::read_to_string(csr_path)?).map_err(invalid)?;
+    subject_alt_names_in_csr(&csr).map_err(invalid)
+}
+
+fn subject_alt_names_in_csr(csr: &[u8]) -> Result<Vec<String>, CipherError> {
+    let mut info = Reader::new(csr).nested(der::SEQUENCE)?.nested(der::SEQUENCE)?;
+    info.integer()?;
+    info.element()?;
+    info.element()?;
+    if info.peek() != Some(der::CONTEXT_0) {
+        return Ok(Vec::new());
+    }
+
+    let extension_request = der::encode_oid(EXTENSION_REQUEST).unwrap_or_default();
+    let mut names = Vec::new();
+    let mut attributes = info.nested(der::CONTEXT_0)?;
+    while !attributes.is_empty() {
+        let mut attribute = attributes.nested(der::SEQUENCE)?;
+        if attribute.raw_element()? != extension_request {
+            continue;
+        }
+        let mut values = attribute.nested(der::SET)?;
+        while !values.is_empty() {
+            names.extend(subject_alt_names_in_extensions(values.nested(der::SEQUENCE)?)?);
+        }
+    }
+    Ok(names)
+}
+
+/// The subject alternative names of the certificate at `cert_path`, in
+/// OpenSSL syntax
+pub(crate) fn certificate_subject_alt_names(cert_path: &str) -> io::Result<Vec<String>> {
+    let invalid = |e: CipherError| io::Error::new(io::ErrorKind::InvalidData, tr!("Cannot read certificate {}: {}", cert_path, e));
+    let (_, certificate) = der::pem_decode(&fs::read_to_string(cert_path)?).map_err(invalid)?;
+    subject_alt_names_in_certificate(&certificate).map_err(invalid)
+}
+
+fn subject_alt_names_in_certificate(certificate: &[u8]) -> Result<Vec<String>, CipherError> {
+    let mut tbs = Reader::new(certificate).nested(der::SEQUENCE)?.nested(der::SEQUENCE)?;
+    // Version, serial, algorithm, issuer, validity, subject, key, then
+    // optional unique IDs and the [3] extensions
+    while !tbs.is_empty() {
+        if tbs.peek() == Some(der::CONTEXT_3) {
+            return subject_alt_names_in_extensions(tbs.nested(der::CONTEXT_3)?.nested(der::SEQUENCE)?);
+        }
+        tbs.element()?;
+    }
+    Ok(Vec::new())
+}
+
+/// Names in the subjectAltName of a list of extensions
+fn subject_alt_names_in_extensions(mut extensions: Reader) -> Result<Vec<String>, CipherError> {
+    let subject_alt_name = der::encode_oid(SUBJECT_ALT_NAME).unwrap_or_default();
+    let mut names = Vec::new();
+    while !extensions.is_empty() {
+        let mut extension = extensions.nested(der::SEQUENCE)?;
+        if extension.raw_element()? != subject_alt_name {
+            continue;
+        }
+        if extension.peek() == Some(der::BOOLEAN) {
+            extension.element()?;
+        }
+        let value = extension.expect(der::OCTET_STRING)?;
+        let mut general_names = Reader::new(value).nested(der::SEQUENCE)?;
+        while !general_names.is_empty() {
+            let (tag, value) = general_names.element()?;
+            names.extend(general_name_text(tag, value));
+        }
+    }
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn general_names_use_openssl_syntax() {
+        assert_eq!(general_name("DNS:localhost").unwrap(), der::encode(DNS_NAME, b"localhost"));
+        assert_eq!(general_name("IP:127.0.0.1").unwrap(), [IP_ADDRESS, 4, 127, 0, 0, 1]);
+        assert_eq!(general_name("IP:::1").unwrap().len(), 18);
+        assert!(general_name("RID:1.2.3").is_err());
+        assert!(general_name("IP:localhost").is_err());
+    }
+
+    #[test]
+    fn requested_names_come_back_from_the_extension_request() {
+        let requested = ["DNS:example.com", "IP:10.0.0.1", "IP:::1", "email:ana@example.com"];
+        let names: Vec<Vec<u8>> = requested.iter().map(|name| general_name(name).unwrap()).collect();
+        let extension = der::sequence(&[
+            der::encode_oid(SUBJECT_ALT_NAME).unwrap(),
+            der::encode(der::OCTET_STRING, &der::sequence(&names)),
+        ]);
+        let attribute = der::sequence(&[
+            der::encode_oid(EXTENSION_REQUEST).unwrap(),
+            der::encode(der::SET, &der::sequence(&[extension])),
+        ]);
+        let info = |attributes: &[u8]| {
+            der::sequence(&[
+                der::encode(der::INTEGER, &[0]),
+                der::sequence(&[]),
+                der::sequence(&[]),
+                der::encode(der::CONTEXT_0, attributes),
+            ])
+        };
+        let csr = |info: Vec<u8>| der::sequence(&[info, der::sequence(&[]), der::encode(der::BIT_STRING, &[0])]);
+
+        assert_eq!(subject_alt_names_in_csr(&csr(info(&attribute))).unwrap(), requested);
+        assert!(subject_alt_names_in_csr(&csr(info(&[]))).unwrap().is_empty());
+    }
+}
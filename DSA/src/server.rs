@@ -0,0 +1,434 @@
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, TcpListener};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crypto_core::codec::{decode_base64, encode_hex, Strictness};
+use crypto_core::mac::constant_time_eq;
+use crypto_core::{rng, tr};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig, ServerConnection, StreamOwned};
+use tracing::{info, warn};
+
+use crate::PKIConfig;
+use crate::acme::ServerState;
+use crate::csr::{ExistingCertificate, SanPolicy};
+use crate::profile::CertificateProfile;
+use crate::provision::is_valid_username;
+
+/// Largest request body accepted (CSRs are a few KiB)
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Largest request line plus headers accepted
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+
+/// How long a client may stall a read or write before it is dropped, so one
+/// slow connection cannot hold up the ones queued behind it
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Settings for the enrollment server
+pub(crate) struct ServerSettings {
+    /// Address to listen on, e.g. `0.0.0.0:8443`
+    pub(crate) listen: String,
+    /// Bearer token required for CSR submission, ACME orders and revocation
+    pub(crate) token: Option<String>,
+    /// Server certificate and key for HTTPS; plain HTTP without them
+    pub(crate) tls_cert: Option<String>,
+    pub(crate) tls_key: Option<String>,
+    /// Require client certificates issued by the PKI's CA (mTLS)
+    pub(crate) require_client_cert: bool,
+    /// Port ACME http-01 challenges are fetched from on the identifier's host
+    pub(crate) acme_challenge_port: u16,
+}
+
+/// A parsed HTTP request
+pub(crate) struct Request {
+    pub(crate) method: String,
+    pub(crate) path: String,
+    pub(crate) query: String,
+    pub(crate) headers: Vec<(String, String)>,
+    pub(crate) body: Vec<u8>,
+    /// Address of the connecting client
+    pub(crate) peer: Option<IpAddr>,
+}
+
+impl Request {
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub(crate) fn query_param(&self, name: &str) -> Option<&str> {
+        self.query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+    }
+}
+
+/// An HTTP response
+pub(crate) struct Response {
+    pub(crate) status: u16,
+    pub(crate) content_type: &'static str,
+    pub(crate) body: Vec<u8>,
+}
+
+impl Response {
+    pub(crate) fn new(status: u16, content_type: &'static str, body: impl Into<Vec<u8>>) -> Self {
+        Response { status, content_type, body: body.into() }
+    }
+
+    pub(crate) fn text(status: u16, body: impl Into<String>) -> Self {
+        let mut body = body.into();
+        body.push('\n');
+        Self::new(status, "text/plain; charset=utf-8", body)
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            409 => "Conflict",
+            413 => "Payload Too Large",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+impl PKIConfig {
+    /// Serve the PKI over HTTP(S) until the process is stopped.
+    ///
+    /// Connections are handled one at a time so CA operations never race.
+    /// Without a token or client certificates a random token is generated
+    /// and printed, so the write endpoints are never open.
+    pub(crate) fn serve(&self, mut settings: ServerSettings) -> io::Result<()> {
+        let tls_config = self.server_tls_config(&settings)?;
+        let state = ServerState::default();
+        let listener = TcpListener::bind(&settings.listen)?;
+
+        println!("{}", tr!("Serving PKI on {}://{}", if tls_config.is_some() { "https" } else { "http" }, listener.local_addr()?));
+        if settings.token.is_none() && !settings.require_client_cert {
+            let token = encode_hex(&rng::key(16)?);
+            println!("{}", tr!("No --token or PKI_API_TOKEN given; send this one: {}", token));
+            settings.token = Some(token);
+        }
+        let settings = &settings;
+
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!(error = %e, "failed to accept connection");
+                    continue;
+                }
+            };
+
+            let peer = stream.peer_addr().ok().map(|address| address.ip());
+            if let Err(e) = stream.set_read_timeout(Some(IO_TIMEOUT)).and_then(|()| stream.set_write_timeout(Some(IO_TIMEOUT))) {
+                warn!(peer = ?peer, error = %e, "failed to set connection timeouts");
+                continue;
+            }
+            let result = match &tls_config {
+                Some(config) => ServerConnection::new(Arc::clone(config))
+                    .map_err(io::Error::other)
+                    .and_then(|connection| {
+                        self.handle_connection(StreamOwned::new(connection, stream), peer, settings, &state)
+                    }),
+                None => self.handle_connection(stream, peer, settings, &state),
+            };
+
+            if let Err(e) = result {
+                warn!(peer = ?peer, error = %e, "connection error");
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn server_tls_config(&self, settings: &ServerSettings) -> io::Result<Option<Arc<ServerConfig>>> {
+        let (Some(cert_path), Some(key_path)) = (&settings.tls_cert, &settings.tls_key) else {
+            if settings.require_client_cert {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    tr!("Client certificate authentication requires --tls-cert and --tls-key")
+                ));
+            }
+            return Ok(None);
+        };
+
+        let certs = rustls_pemfile::certs(&mut BufReader::new(File::open(cert_path)?))
+            .collect::<Result<Vec<_>, _>>()?;
+        let key = self.tls_private_key(key_path)?;
+
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let builder = ServerConfig::builder_with_provider(Arc::clone(&provider))
+            .with_safe_default_protocol_versions()
+            .map_err(io::Error::other)?;
+
+        let builder = if settings.require_client_cert {
+            let ca_cert_path = format!("{}/ca_certificate.pem", self.ca_dir);
+            let mut roots = RootCertStore::empty();
+            for cert in rustls_pemfile::certs(&mut BufReader::new(File::open(&ca_cert_path)?)) {
+                roots.add(cert?).map_err(io::Error::other)?;
+            }
+
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .map_err(io::Error::other)?;
+            builder.with_client_cert_verifier(verifier)
+        } else {
+            builder.with_no_client_auth()
+        };
+
+        let config = builder
+            .with_single_cert(certs, key)
+            .map_err(io::Error::other)?;
+
+        Ok(Some(Arc::new(config)))
+    }
+
+    fn handle_connection(
+        &self,
+        mut stream: impl Read + Write,
+        peer: Option<IpAddr>,
+        settings: &ServerSettings,
+        state: &ServerState,
+    ) -> io::Result<()> {
+        let response = match read_request(&mut stream) {
+            Ok(mut request) => {
+                request.peer = peer;
+                let response = self.route(&request, settings, state);
+                info!(peer = ?peer, method = %request.method, path = %request.path, status = response.status, "request");
+                response
+            }
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => Response::text(400, e.to_string()),
+            Err(e) if e.kind() == io::ErrorKind::OutOfMemory => Response::text(413, e.to_string()),
+            Err(e) => return Err(e),
+        };
+
+        write_response(&mut stream, &response)
+    }
+
+    /// Dispatch a request to the matching endpoint
+    pub(crate) fn route(&self, request: &Request, settings: &ServerSettings, state: &ServerState) -> Response {
+        let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+
+        let result = match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["ca"]) => self.serve_file(&format!("{}/ca_certificate.pem", self.ca_dir), "application/x-pem-file"),
+            ("GET", ["crl"]) => self.serve_file(&format!("{}/ca_crl.pem", self.ca_dir), "application/x-pem-file"),
+            ("GET", ["certificates", username]) if is_valid_username(username) => self.serve_file(
+                &format!("{}/{}_certificate.pem", self.users_dir, username),
+                "application/x-pem-file"
+            ),
+            ("POST", ["csr"]) => authorize(request, settings).and_then(|()| self.enroll(request)),
+            ("POST", ["certificates", username, "revoke"]) if is_valid_username(username) => {
+                authorize(request, settings).and_then(|()| {
+                    self.revoke_user_certificate(username)?;
+                    Ok(Response::text(200, format!("Revoked certificate for {}", username)))
+                })
+            }
+            ("POST", ["acme", acme_path @ ..]) => {
+                authorize(request, settings).and_then(|()| self.route_acme(request, acme_path, state, settings.acme_challenge_port))
+            }
+            ("GET", [".well-known", "est", "cacerts"]) => self.est_cacerts(),
+            ("GET", ["metrics"]) => self
+                .collect_metrics(30)
+                .map(|metrics| Response::new(200, "text/plain; version=0.0.4", metrics.to_prometheus())),
+            ("POST", [".well-known", "est", action @ ("simpleenroll" | "simplereenroll")]) => {
+                authorize(request, settings).and_then(|()| self.est_simple_enroll(request, *action == "simplereenroll"))
+            }
+            (_, ["ca"] | ["crl"] | ["csr"] | ["metrics"] | ["certificates", ..] | ["acme", ..] | [".well-known", "est", ..]) => {
+                Ok(Response::text(405, "Method not allowed"))
+            }
+            _ => Ok(Response::text(404, "Not found")),
+        };
+
+        result.unwrap_or_else(|e| match e.kind() {
+            io::ErrorKind::PermissionDenied => Response::text(401, e.to_string()),
+            io::ErrorKind::NotFound => Response::text(404, e.to_string()),
+            io::ErrorKind::AlreadyExists => Response::text(409, e.to_string()),
+            io::ErrorKind::InvalidData | io::ErrorKind::InvalidInput => Response::text(400, e.to_string()),
+            _ => {
+                warn!(method = %request.method, path = %request.path, error = %e, "request failed");
+                Response::text(500, e.to_string())
+            }
+        })
+    }
+
+    fn serve_file(&self, path: &str, content_type: &'static str) -> io::Result<Response> {
+        if !Path::new(path).exists() {
+            return Ok(Response::text(404, "Not found"));
+        }
+        Ok(Response::new(200, content_type, fs::read(path)?))
+    }
+
+    /// Issue a certificate for a PEM CSR posted as the request body. A user
+    /// who still holds a valid certificate must ask for `?renew=true`.
+    fn enroll(&self, request: &Request) -> io::Result<Response> {
+        let profile = request.query_param("profile").map(CertificateProfile::parse).transpose()?;
+
+        let certificate = self.issue_from_csr_pem(&request.body, profile, None, requested_replacement(request))?;
+        Ok(Response::new(200, "application/x-pem-file", certificate))
+    }
+
+    /// Issue a certificate for CSR bytes received over the network, optionally
+    /// insisting on the CSR's common name, which then also limits its subject
+    /// alternative names to `DNS:<name>`. Returns the PEM certificate.
+    pub(crate) fn issue_from_csr_pem(
+        &self,
+        csr_pem: &[u8],
+        profile: Option<CertificateProfile>,
+        expected_username: Option<&str>,
+        existing: ExistingCertificate,
+    ) -> io::Result<Vec<u8>> {
+        let pending_csr_path = format!("{}/.incoming_csr.pem", self.users_dir);
+        fs::write(&pending_csr_path, csr_pem)?;
+        // An authorized identifier is the only name its CSR may carry
+        let authorized_names: Vec<String> = expected_username.map(|identifier| format!("DNS:{}", identifier)).into_iter().collect();
+        let san_policy = match expected_username {
+            Some(_) => SanPolicy::Only(&authorized_names),
+            None => SanPolicy::Configured,
+        };
+
+        let result = self.check_csr_policy(&pending_csr_path).and_then(|username| {
+            if expected_username.is_some_and(|expected| expected != username) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    tr!("CSR common name {} does not match the authorized identifier", username)
+                ));
+            }
+            self.sign_external_csr(&pending_csr_path, profile, san_policy, existing)
+        });
+        fs::remove_file(&pending_csr_path)?;
+
+        let username = result?;
+        fs::read(format!("{}/{}_certificate.pem", self.users_dir, username))
+    }
+}
+
+/// Whether a request may replace a certificate its user still holds:
+/// only when it asks to with `?renew=true`
+pub(crate) fn requested_replacement(request: &Request) -> ExistingCertificate {
+    match request.query_param("renew") {
+        Some("true") => ExistingCertificate::Renew,
+        _ => ExistingCertificate::Keep,
+    }
+}
+
+/// Check the API token when one is configured, sent either as a bearer token
+/// or as the password of HTTP Basic credentials (as EST clients do)
+pub(crate) fn authorize(request: &Request, settings: &ServerSettings) -> io::Result<()> {
+    let Some(token) = &settings.token else {
+        return Ok(());
+    };
+
+    let authorization = request.header("Authorization").unwrap_or_default();
+    let presented = if let Some(bearer) = authorization.strip_prefix("Bearer ") {
+        Some(bearer.trim().to_string())
+    } else if let Some(basic) = authorization.strip_prefix("Basic ") {
+        decode_base64(basic.trim(), Strictness::Lenient)
+            .ok()
+            .and_then(|credentials| String::from_utf8(credentials).ok())
+            .and_then(|credentials| credentials.split_once(':').map(|(_, password)| password.to_string()))
+    } else {
+        None
+    };
+
+    if !presented.is_some_and(|presented| constant_time_eq(presented.as_bytes(), token.as_bytes())) {
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, tr!("Missing or invalid API token")));
+    }
+
+    Ok(())
+}
+
+/// Read one line of the request head, counting it against `budget`
+fn read_head_line(reader: &mut impl BufRead, budget: &mut usize) -> io::Result<String> {
+    let mut line = String::new();
+    let read = reader.take(*budget as u64).read_line(&mut line)?;
+    *budget -= read;
+    if *budget == 0 {
+        return Err(io::Error::new(io::ErrorKind::OutOfMemory, tr!("Request headers too large")));
+    }
+    Ok(line)
+}
+
+/// Read one HTTP/1.x request from the stream
+pub(crate) fn read_request(stream: &mut impl Read) -> io::Result<Request> {
+    let mut reader = BufReader::new(stream);
+    let mut head_budget = MAX_HEAD_BYTES;
+
+    let request_line = read_head_line(&mut reader, &mut head_budget)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, tr!("Malformed request line")));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let mut headers = Vec::new();
+    loop {
+        let line = read_head_line(&mut reader, &mut head_budget)?;
+        if line.is_empty() {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("Content-Length"))
+        .map(|(_, value)| value.parse::<usize>())
+        .transpose()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, tr!("Invalid Content-Length")))?
+        .unwrap_or(0);
+
+    if content_length > MAX_BODY_BYTES {
+        return Err(io::Error::new(io::ErrorKind::OutOfMemory, tr!("Request body too large")));
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    Ok(Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: query.to_string(),
+        headers,
+        body,
+        peer: None,
+    })
+}
+
+pub(crate) fn write_response(stream: &mut impl Write, response: &Response) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.reason(),
+        response.content_type,
+        response.body.len()
+    )?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+/// Accept `:8443` as shorthand for all interfaces
+pub(crate) fn normalize_listen_address(listen: &str) -> String {
+    match listen.strip_prefix(':') {
+        Some(port) => format!("0.0.0.0:{}", port),
+        None => listen.to_string(),
+    }
+}
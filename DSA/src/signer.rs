@@ -0,0 +1,209 @@
+use std::env;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Mutex, PoisonError};
+
+use crypto_core::tr;
+
+use crate::exec::{self, Execute};
+use crate::keyalgorithm::KeyAlgorithm;
+use crate::masterkey::is_encrypted_key;
+use crate::passphrase;
+use crate::permissions::create_private_file;
+use crate::runner::CommandRunner;
+
+/// Environment variable openssl reads the passphrase of an encrypted CA key from
+pub(crate) const CA_PASSPHRASE_ENV: &str = "PKI_CA_PASSPHRASE";
+
+/// The CA passphrase once typed in, so it is asked for at most once per run
+static CA_PASSPHRASE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Backend holding the CA private key.
+///
+/// Every openssl invocation that needs the CA key asks the signer for the
+/// arguments selecting it, so the key material itself never has to be a
+/// file the PKI code reads.
+pub(crate) trait CaSigner: Send + Sync {
+    /// Create the CA key pair, `bits` being the size of an RSA key
+    fn generate_key(&self, runner: &dyn CommandRunner, algorithm: KeyAlgorithm, bits: u32) -> io::Result<()>;
+
+    /// Whether a CA key already exists and must not be silently replaced
+    fn key_exists(&self) -> bool;
+
+    /// The key file, when the key is stored on disk
+    fn key_file(&self) -> Option<&str> {
+        None
+    }
+
+    /// Arguments selecting the CA key for an openssl subcommand, where
+    /// `key_option` is the subcommand's key flag (`-key`, `-CAkey`, `-keyfile`)
+    fn key_args(&self, key_option: &str) -> Vec<String>;
+
+    /// Human-readable description of where the key lives
+    fn describe(&self) -> String;
+
+    /// Whether the key is, or will be made, encrypted under the CA passphrase
+    fn key_encrypted(&self) -> bool {
+        false
+    }
+
+    /// CA key file that `ssh-keygen` can read for signing SSH certificates
+    fn ssh_key_path(&self) -> io::Result<String> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            tr!("SSH certificate signing is not supported with {}", self.describe())
+        ))
+    }
+}
+
+/// CA key stored as a PEM file on disk
+pub(crate) struct FileSigner {
+    pub(crate) key_path: String,
+    /// Encrypt a new key with AES-256 under the CA passphrase (`pki.encrypt_ca_key`)
+    pub(crate) encrypt: bool,
+}
+
+impl CaSigner for FileSigner {
+    fn generate_key(&self, runner: &dyn CommandRunner, algorithm: KeyAlgorithm, bits: u32) -> io::Result<()> {
+        let mut encryption = Vec::new();
+        if self.encrypt {
+            if !exec::is_dry_run() {
+                ca_passphrase(true)?;
+            }
+            encryption = vec!["-aes-256-cbc".to_string(), "-pass".to_string(), format!("env:{}", CA_PASSPHRASE_ENV)];
+        }
+        create_private_file(&self.key_path)?;
+
+        let output = Command::new("openssl")
+            .arg("genpkey")
+            .args(algorithm.genpkey_args(bits))
+            .args(encryption)
+            .args(["-out", &self.key_path])
+            .execute(runner)?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to generate CA private key")));
+        }
+
+        Ok(())
+    }
+
+    fn key_exists(&self) -> bool {
+        Path::new(&self.key_path).exists()
+    }
+
+    fn key_file(&self) -> Option<&str> {
+        Some(&self.key_path)
+    }
+
+    fn key_args(&self, key_option: &str) -> Vec<String> {
+        key_file_args(key_option, &self.key_path)
+    }
+
+    fn describe(&self) -> String {
+        tr!("PEM file {}", self.key_path)
+    }
+
+    fn key_encrypted(&self) -> bool {
+        self.encrypt || is_encrypted_key(&self.key_path).unwrap_or(false)
+    }
+
+    fn ssh_key_path(&self) -> io::Result<String> {
+        Ok(self.key_path.clone())
+    }
+}
+
+/// CA key held in a PKCS#11 token (SoftHSM, smartcard, HSM), used through
+/// the OpenSSL 3 `pkcs11` provider
+pub(crate) struct Pkcs11Signer {
+    /// RFC 7512 URI of the private key, e.g.
+    /// `pkcs11:token=PKI;object=ca;type=private?pin-value=1234`
+    pub(crate) key_uri: String,
+}
+
+impl CaSigner for Pkcs11Signer {
+    fn generate_key(&self, runner: &dyn CommandRunner, _algorithm: KeyAlgorithm, _bits: u32) -> io::Result<()> {
+        // Keys are created on the token itself (e.g. with pkcs11-tool --keypairgen)
+        // so they are never exportable; nothing to do here beyond checking access
+        let output = Command::new("openssl")
+            .args(["pkey", "-pubout", "-provider", "pkcs11", "-provider", "default", "-in", &self.key_uri])
+            .run(runner)?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!(
+                "CA key {} is not accessible; create it on the token first", self.key_uri
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn key_exists(&self) -> bool {
+        // Token keys are managed outside the PKI and never regenerated by it
+        true
+    }
+
+    fn key_args(&self, key_option: &str) -> Vec<String> {
+        vec![
+            "-provider".to_string(), "pkcs11".to_string(),
+            "-provider".to_string(), "default".to_string(),
+            key_option.to_string(), self.key_uri.clone(),
+        ]
+    }
+
+    fn describe(&self) -> String {
+        tr!("PKCS#11 key {}", self.key_uri)
+    }
+}
+
+/// Arguments selecting a CA key file for `key_option`, plus `-passin` when
+/// the file is encrypted; see [`unlock_ca_key`]
+pub(crate) fn key_file_args(key_option: &str, key_path: &str) -> Vec<String> {
+    let mut args = vec![key_option.to_string(), key_path.to_string()];
+    if is_encrypted_key(key_path).unwrap_or(false) {
+        args.extend(["-passin".to_string(), format!("env:{}", CA_PASSPHRASE_ENV)]);
+    }
+    args
+}
+
+/// Hand the CA passphrase to an openssl command that reads or writes an
+/// encrypted CA key, through its environment so it never appears on a
+/// command line. A passphrase already in the environment is inherited.
+pub(crate) fn unlock_ca_key(command: &mut Command) -> io::Result<()> {
+    let passphrase_arg = format!("env:{}", CA_PASSPHRASE_ENV);
+    if command.get_args().any(|arg| arg == passphrase_arg.as_str()) && env::var_os(CA_PASSPHRASE_ENV).is_none() {
+        command.env(CA_PASSPHRASE_ENV, ca_passphrase(false)?);
+    }
+    Ok(())
+}
+
+/// `$PKI_CA_PASSPHRASE`, else the passphrase typed in on the terminal
+/// (twice for a new key)
+fn ca_passphrase(new_key: bool) -> io::Result<String> {
+    let mut typed = CA_PASSPHRASE.lock().unwrap_or_else(PoisonError::into_inner);
+    let passphrase = match (env::var(CA_PASSPHRASE_ENV), typed.as_ref()) {
+        (Ok(passphrase), _) => passphrase,
+        (Err(_), Some(passphrase)) => return Ok(passphrase.clone()),
+        (Err(_), None) => {
+            let missing = || io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                tr!("The CA key is encrypted; set {} or run pki on a terminal", CA_PASSPHRASE_ENV)
+            );
+            let passphrase = passphrase::prompt(tr!("CA key passphrase: "))?.ok_or_else(missing)?;
+            if new_key && passphrase::prompt(tr!("Repeat the CA key passphrase: "))?.as_ref() != Some(&passphrase) {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("The passphrases do not match")));
+            }
+            passphrase
+        }
+    };
+
+    if new_key && passphrase.chars().count() < 12 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            tr!("The CA passphrase must be at least 12 characters long")
+        ));
+    }
+    *typed = Some(passphrase.clone());
+    Ok(passphrase)
+}
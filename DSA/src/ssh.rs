@@ -0,0 +1,69 @@
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::exec::Execute;
+
+impl PKIConfig {
+    /// Sign an OpenSSH public key with the CA, producing `<key>-cert.pub`
+    pub(crate) fn sign_ssh_key(
+        &self,
+        public_key_path: &str,
+        identity: &str,
+        principals: &[String],
+        validity: &str,
+    ) -> io::Result<String> {
+        if principals.is_empty() {
+            // A certificate without principals is valid for every account
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("At least one principal is required for an SSH certificate")
+            ));
+        }
+
+        let Some(key_stem) = public_key_path.strip_suffix(".pub") else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("{} is not an OpenSSH public key (.pub) file", public_key_path)
+            ));
+        };
+        if !Path::new(public_key_path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                tr!("Public key {} not found", public_key_path)
+            ));
+        }
+
+        let output = Command::new("ssh-keygen")
+            .args([
+                "-s", &self.ca_signer.ssh_key_path()?,
+                "-I", identity,
+                "-n", &principals.join(","),
+                "-V", validity,
+                public_key_path
+            ])
+            .execute(self.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to sign SSH key {}", public_key_path)));
+        }
+
+        Ok(format!("{}-cert.pub", key_stem))
+    }
+
+    /// CA public key in OpenSSH format, for `TrustedUserCAKeys` on servers
+    pub(crate) fn ssh_ca_public_key(&self) -> io::Result<String> {
+        let output = Command::new("ssh-keygen")
+            .args(["-y", "-f", &self.ca_signer.ssh_key_path()?])
+            .run(self.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to export the CA public key in OpenSSH format")));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
@@ -0,0 +1,144 @@
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::exec::{self, Execute};
+use crate::permissions::{create_private_dir, create_private_file};
+use crate::provision::is_valid_username;
+
+/// Name constraints of a sub-CA, as OpenSSL general names such as
+/// `DNS:example.com`, `email:example.com` or `IP:10.0.0.0/255.0.0.0`
+pub(crate) struct NameConstraints {
+    pub(crate) permitted: Vec<String>,
+    pub(crate) excluded: Vec<String>,
+}
+
+impl NameConstraints {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.permitted.is_empty() && self.excluded.is_empty()
+    }
+
+    /// Check that every constraint names a supported general name type
+    pub(crate) fn validate(&self) -> io::Result<()> {
+        for name in self.permitted.iter().chain(&self.excluded) {
+            let valid = name
+                .split_once(':')
+                .is_some_and(|(kind, value)| ["DNS", "email", "IP", "URI", "dirName"].contains(&kind) && !value.is_empty());
+            if !valid {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    tr!("Invalid name constraint {} (expected DNS:, email:, IP:, URI: or dirName:<value>)", name)
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// `nameConstraints` extension line
+    fn extension_line(&self) -> String {
+        let subtrees: Vec<String> = self
+            .permitted
+            .iter()
+            .map(|name| format!("permitted;{}", name))
+            .chain(self.excluded.iter().map(|name| format!("excluded;{}", name)))
+            .collect();
+        format!("nameConstraints = critical, {}\n", subtrees.join(", "))
+    }
+}
+
+impl PKIConfig {
+    /// Directory holding a sub-CA's key and certificate; kept apart from
+    /// `ca_dir/intermediates`, which describes the issuing chain of user
+    /// certificates
+    pub(crate) fn sub_ca_dir(&self, name: &str) -> String {
+        format!("{}/sub_ca/{}", self.ca_dir, name)
+    }
+
+    /// Create a sub-CA key and a certificate for it signed by the CA, limited
+    /// to issuing end-entity certificates (`pathlen:0`) inside the given name
+    /// constraints and carrying the `--policy` OIDs. Returns the certificate path.
+    pub(crate) fn create_sub_ca(&self, name: &str, constraints: &NameConstraints) -> io::Result<String> {
+        if !is_valid_username(name) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid sub-CA name {}", name)));
+        }
+        constraints.validate()?;
+
+        let ca_cert_path = format!("{}/ca_certificate.pem", self.ca_dir);
+        let sub_ca_dir = self.sub_ca_dir(name);
+        let key_path = format!("{}/private_key.pem", sub_ca_dir);
+        let csr_path = format!("{}/csr.pem", sub_ca_dir);
+        let cert_path = format!("{}/certificate.pem", sub_ca_dir);
+        let ext_path = format!("{}/ext.cnf", sub_ca_dir);
+
+        if !Path::new(&ca_cert_path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                tr!("CA certificate not found; initialize the PKI first")
+            ));
+        }
+        self.replace_existing(&[&cert_path, &key_path])?;
+        create_private_dir(&sub_ca_dir)?;
+
+        create_private_file(&key_path)?;
+        let output = Command::new("openssl")
+            .arg("genpkey")
+            .args(self.ca_key_algorithm.genpkey_args(self.ca_key_bits))
+            .args(["-out", &key_path])
+            .execute(self.runner())?;
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to generate key for sub-CA {}", name)));
+        }
+
+        let output = Command::new("openssl")
+            .args([
+                "req", "-new",
+                "-key", &key_path,
+                "-out", &csr_path,
+                "-subj", &self.ca_subject.clone().with_common_name(name).to_subj()
+            ])
+            .execute(self.runner())?;
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to generate CSR for sub-CA {}", name)));
+        }
+
+        let mut extensions = String::from(
+            "[ v3_sub_ca ]\n\
+             basicConstraints = critical, CA:TRUE, pathlen:0\n\
+             keyUsage = critical, keyCertSign, cRLSign\n"
+        );
+        if !constraints.is_empty() {
+            extensions.push_str(&constraints.extension_line());
+        }
+        extensions.push_str(&self.policy_lines());
+        exec::write(&ext_path, extensions)?;
+
+        self.check_digest_for_key(&self.ca_signer.key_args("-in"))?;
+        let serial = self.new_serial()?;
+        let output = Command::new("openssl")
+            .args([
+                "x509", "-req",
+                "-in", &csr_path,
+                "-CA", &ca_cert_path,
+                "-set_serial", &format!("0x{}", serial),
+                "-days", &self.ca_validity_days.to_string(),
+                &self.signing_digest().flag(),
+                "-extfile", &ext_path, "-extensions", "v3_sub_ca",
+                "-out", &cert_path
+            ])
+            .args(self.ca_signer.key_args("-CAkey"))
+            .execute(self.runner());
+        exec::remove_file(&ext_path)?;
+        exec::remove_file(&csr_path)?;
+
+        if !output?.status.success() {
+            return Err(io::Error::other(tr!("Failed to sign certificate for sub-CA {}", name)));
+        }
+        self.record_issuance(&cert_path)?;
+        self.record_audit_event("sub-ca-created", name)?;
+
+        Ok(cert_path)
+    }
+}
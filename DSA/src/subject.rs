@@ -0,0 +1,243 @@
+use std::io;
+
+use crypto_core::der;
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::pem::rfc2253_fields;
+
+/// Distinguished name of a certificate subject. The CA's comes from
+/// `pki.ca_subject`; users get `pki.user_subject` with their own fields
+/// on top and their username as the CN unless they have another.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct SubjectDn {
+    pub(crate) common_name: Option<String>,
+    pub(crate) organization: Option<String>,
+    pub(crate) organizational_unit: Option<String>,
+    pub(crate) locality: Option<String>,
+    pub(crate) state: Option<String>,
+    /// Two-letter country code
+    pub(crate) country: Option<String>,
+    pub(crate) email: Option<String>,
+}
+
+/// A subject attribute: its `-subj` short name, its OID and the string type
+/// OpenSSL gives the value (UTF8String unless the standard says otherwise)
+pub(crate) type Attribute = (&'static str, &'static str, u8);
+
+/// The attributes a subject may name
+pub(crate) const ATTRIBUTES: [Attribute; 8] = [
+    ("CN", "2.5.4.3", der::UTF8_STRING),
+    ("serialNumber", "2.5.4.5", der::PRINTABLE_STRING),
+    ("C", "2.5.4.6", der::PRINTABLE_STRING),
+    ("L", "2.5.4.7", der::UTF8_STRING),
+    ("ST", "2.5.4.8", der::UTF8_STRING),
+    ("O", "2.5.4.10", der::UTF8_STRING),
+    ("OU", "2.5.4.11", der::UTF8_STRING),
+    ("emailAddress", "1.2.840.113549.1.9.1", der::IA5_STRING),
+];
+
+/// Split an OpenSSL `-subj` string such as `/CN=alice/O=Course`, where `\`
+/// escapes the next character, into its fields in order, each with its
+/// entry in [`ATTRIBUTES`]
+pub(crate) fn subject_fields(subject: &str) -> io::Result<Vec<(Attribute, String)>> {
+    let bad_subject = || io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid subject {}", subject));
+
+    let mut fields = Vec::new();
+    let (mut kind, mut value) = (String::new(), None::<String>);
+    let mut chars = subject.strip_prefix('/').ok_or_else(bad_subject)?.chars();
+    while let Some(c) = chars.next() {
+        match (c, &mut value) {
+            ('/', _) => fields.push((std::mem::take(&mut kind), value.take())),
+            ('=', None) => value = Some(String::new()),
+            ('\\', part) => part.as_mut().unwrap_or(&mut kind).push(chars.next().ok_or_else(bad_subject)?),
+            (c, part) => part.as_mut().unwrap_or(&mut kind).push(c),
+        }
+    }
+    fields.push((kind, value));
+
+    fields
+        .into_iter()
+        .map(|(kind, value)| {
+            let value = value.filter(|value| !value.is_empty()).ok_or_else(bad_subject)?;
+            let attribute = ATTRIBUTES.iter().find(|(short, _, _)| *short == kind).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::Unsupported, tr!("Unsupported subject field {}", kind))
+            })?;
+            Ok((*attribute, value))
+        })
+        .collect()
+}
+
+impl SubjectDn {
+    /// Parse an OpenSSL `-subj` string such as `/CN=alice/O=Course`.
+    /// Fields may come in any order.
+    pub(crate) fn parse(subject: &str) -> io::Result<Self> {
+        let mut dn = SubjectDn::default();
+        for ((kind, _, _), value) in subject_fields(subject)? {
+            *dn.field_mut(kind)? = Some(value);
+        }
+        dn.check()?;
+        Ok(dn)
+    }
+
+    /// Parse the RFC 2253 form `openssl -nameopt RFC2253` prints, such as
+    /// `CN=Pop\, Ana,O=Course`
+    pub(crate) fn parse_rfc2253(name: &str) -> io::Result<Self> {
+        let bad_subject = || io::Error::new(io::ErrorKind::InvalidData, tr!("Invalid subject {}", name));
+
+        let mut dn = SubjectDn::default();
+        for (kind, value) in rfc2253_fields(name).ok_or_else(bad_subject)? {
+            if value.is_empty() {
+                return Err(bad_subject());
+            }
+            *dn.field_mut(&kind)? = Some(value);
+        }
+        dn.check()?;
+        Ok(dn)
+    }
+
+    /// The field an attribute short name such as `CN` sets
+    fn field_mut(&mut self, kind: &str) -> io::Result<&mut Option<String>> {
+        match kind {
+            "CN" => Ok(&mut self.common_name),
+            "O" => Ok(&mut self.organization),
+            "OU" => Ok(&mut self.organizational_unit),
+            "L" => Ok(&mut self.locality),
+            "ST" => Ok(&mut self.state),
+            "C" => Ok(&mut self.country),
+            "emailAddress" => Ok(&mut self.email),
+            _ => Err(io::Error::new(io::ErrorKind::Unsupported, tr!("Unsupported subject field {}", kind))),
+        }
+    }
+
+    /// Reject values a certificate cannot carry
+    pub(crate) fn check(&self) -> io::Result<()> {
+        if let Some(country) = &self.country {
+            if country.len() != 2 || !country.chars().all(|c| c.is_ascii_uppercase()) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    tr!("Country {} is not a two-letter code such as RO", country)
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// These fields, with the ones they leave out taken from `defaults`
+    pub(crate) fn or(self, defaults: &SubjectDn) -> SubjectDn {
+        let pick = |field: Option<String>, default: &Option<String>| field.or_else(|| default.clone());
+        SubjectDn {
+            common_name: pick(self.common_name, &defaults.common_name),
+            organization: pick(self.organization, &defaults.organization),
+            organizational_unit: pick(self.organizational_unit, &defaults.organizational_unit),
+            locality: pick(self.locality, &defaults.locality),
+            state: pick(self.state, &defaults.state),
+            country: pick(self.country, &defaults.country),
+            email: pick(self.email, &defaults.email),
+        }
+    }
+
+    /// The same name with another CN
+    pub(crate) fn with_common_name(self, common_name: &str) -> SubjectDn {
+        SubjectDn { common_name: Some(common_name.to_string()), ..self }
+    }
+
+    /// The value of the field with short name `kind`, such as `O`
+    pub(crate) fn field(&self, kind: &str) -> Option<&str> {
+        self.fields().find(|(short, _)| *short == kind).map(|(_, value)| value)
+    }
+
+    /// Short names and values in the order they appear in the name
+    fn fields(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("CN", &self.common_name),
+            ("O", &self.organization),
+            ("OU", &self.organizational_unit),
+            ("L", &self.locality),
+            ("ST", &self.state),
+            ("C", &self.country),
+            ("emailAddress", &self.email),
+        ]
+        .into_iter()
+        .filter_map(|(kind, value)| value.as_deref().map(|value| (kind, value)))
+    }
+
+    /// The OpenSSL `-subj` string, `/CN=alice/O=Course`
+    pub(crate) fn to_subj(&self) -> String {
+        self.fields()
+            .map(|(kind, value)| format!("/{}={}", kind, escape_subject_value(value)))
+            .collect()
+    }
+
+    /// The RFC 4514 string other tools take, `CN=alice,O=Course`
+    pub(crate) fn to_rfc4514(&self) -> String {
+        self.fields()
+            .map(|(kind, value)| {
+                let escaped: String = value
+                    .chars()
+                    .flat_map(|c| match c {
+                        ',' | '+' | '"' | '\\' | '<' | '>' | ';' | '=' => vec!['\\', c],
+                        c => vec![c],
+                    })
+                    .collect();
+                format!("{}={}", kind, escaped)
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+impl PKIConfig {
+    /// `-subj` string for a user's certificate: `fields` over the
+    /// configured user subject, with the username as the default CN
+    pub(crate) fn subject_for_user(&self, username: &str, fields: SubjectDn) -> String {
+        let subject = fields.or(&self.user_subject);
+        match subject.common_name {
+            Some(_) => subject.to_subj(),
+            None => subject.with_common_name(username).to_subj(),
+        }
+    }
+}
+
+/// Escape characters with special meaning in OpenSSL `-subj` strings
+fn escape_subject_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('/', "\\/").replace('=', "\\=").replace('+', "\\+")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subjects_parse_merge_and_print() {
+        let dn = SubjectDn::parse("/O=Course\\/2026/C=RO/CN=Ana Pop/emailAddress=ana@example.com").unwrap();
+        assert_eq!(dn.organization.as_deref(), Some("Course/2026"));
+        assert_eq!(dn.to_subj(), "/CN=Ana Pop/O=Course\\/2026/C=RO/emailAddress=ana@example.com");
+        assert_eq!(SubjectDn::parse(&dn.to_subj()).unwrap(), dn);
+
+        let defaults = SubjectDn::parse("/O=DotCompany/OU=IT/L=Chisinau").unwrap();
+        let merged = SubjectDn::parse("/OU=Sales").unwrap().or(&defaults).with_common_name("bob");
+        assert_eq!(merged.to_subj(), "/CN=bob/O=DotCompany/OU=Sales/L=Chisinau");
+        assert_eq!(merged.to_rfc4514(), "CN=bob,O=DotCompany,OU=Sales,L=Chisinau");
+
+        assert!(SubjectDn::parse("CN=alice").is_err());
+        assert!(SubjectDn::parse("/CN").is_err());
+        assert!(SubjectDn::parse("/C=Romania").is_err());
+        assert_eq!(SubjectDn::parse("/XX=1").unwrap_err().kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn rfc2253_names_unescape() {
+        let dn = SubjectDn::parse_rfc2253("CN=Pop\\, Ana+emailAddress=ana@example.com,O=Curs \\C8\\99i lab\\/2026,C=RO").unwrap();
+        assert_eq!(dn.common_name.as_deref(), Some("Pop, Ana"));
+        assert_eq!(dn.organization.as_deref(), Some("Curs și lab/2026"));
+        assert_eq!(dn.to_subj(), "/CN=Pop, Ana/O=Curs și lab\\/2026/C=RO/emailAddress=ana@example.com");
+
+        let subject = "subject=O=Course,CN=Pop\\, Ana";
+        assert_eq!(crate::pem::common_name_from_subject(subject).as_deref(), Some("Pop, Ana"));
+
+        assert!(SubjectDn::parse_rfc2253("CN=alice\\").is_err());
+        assert!(SubjectDn::parse_rfc2253("CN").is_err());
+        assert_eq!(SubjectDn::parse_rfc2253("DC=example").unwrap_err().kind(), io::ErrorKind::Unsupported);
+    }
+}
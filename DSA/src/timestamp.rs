@@ -0,0 +1,123 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::exec::{self, Execute};
+
+/// What `pki verify` made of the timestamp token next to a signature
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TimestampCheck {
+    /// No token
+    Absent,
+    Valid,
+    Invalid,
+    /// A token, but no TSA CA certificate to check it against
+    Unverified,
+}
+
+impl PKIConfig {
+    /// Request an RFC 3161 timestamp token over a document's signature
+    pub(crate) fn timestamp_signature(&self, document_path: &str, tsa_url: &str) -> io::Result<String> {
+        let signature_path = format!("{}.sig", document_path);
+        let query_path = format!("{}.tsq", signature_path);
+        let token_path = format!("{}.tsr", signature_path);
+
+        // Build the timestamp query over the signature bytes
+        let output = Command::new("openssl")
+            .args([
+                "ts", "-query",
+                "-data", &signature_path,
+                "-sha256", "-cert",
+                "-out", &query_path
+            ])
+            .execute(self.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to create timestamp query for {}", signature_path)));
+        }
+
+        // Submit the query to the TSA
+        let output = Command::new("curl")
+            .args([
+                "-sS", "--fail",
+                "-H", "Content-Type: application/timestamp-query",
+                "--data-binary", &format!("@{}", query_path),
+                "-o", &token_path,
+                tsa_url
+            ])
+            .execute(self.runner());
+        exec::remove_file(&query_path)?;
+
+        if !output?.status.success() {
+            return Err(io::Error::other(tr!("Failed to obtain timestamp token from {}", tsa_url)));
+        }
+
+        if exec::is_dry_run() {
+            return Ok(token_path);
+        }
+
+        // Make sure the TSA actually granted the request
+        let output = Command::new("openssl")
+            .args(["ts", "-reply", "-in", &token_path, "-text"])
+            .run(self.runner())?;
+
+        if !output.status.success() || !String::from_utf8_lossy(&output.stdout).contains("Status: Granted") {
+            let _ = fs::remove_file(&token_path);
+            return Err(io::Error::other(tr!("TSA {} rejected the timestamp request", tsa_url)));
+        }
+
+        Ok(token_path)
+    }
+
+    /// Verify the timestamp token stored next to a document's signature, if
+    /// any, against the configured TSA CA certificate
+    pub(crate) fn verify_signature_timestamp(&self, document_path: &str) -> io::Result<TimestampCheck> {
+        let signature_path = format!("{}.sig", document_path);
+        let token_path = format!("{}.tsr", signature_path);
+
+        if !Path::new(&token_path).exists() {
+            return Ok(TimestampCheck::Absent);
+        }
+        let Some(tsa_ca_file) = &self.tsa_ca_file else {
+            return Ok(TimestampCheck::Unverified);
+        };
+
+        let output = Command::new("openssl")
+            .args([
+                "ts", "-verify",
+                "-data", &signature_path,
+                "-in", &token_path,
+                "-CAfile", tsa_ca_file
+            ])
+            .run(self.runner())?;
+
+        Ok(if output.status.success() { TimestampCheck::Valid } else { TimestampCheck::Invalid })
+    }
+
+    /// Read the generation time recorded in a document's timestamp token
+    pub(crate) fn signature_timestamp_time(&self, document_path: &str) -> io::Result<Option<String>> {
+        let token_path = format!("{}.sig.tsr", document_path);
+
+        if !Path::new(&token_path).exists() {
+            return Ok(None);
+        }
+
+        let output = Command::new("openssl")
+            .args(["ts", "-reply", "-in", &token_path, "-text"])
+            .run(self.runner())?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!("Failed to read timestamp token {}", token_path)));
+        }
+
+        let text = String::from_utf8_lossy(&output.stdout);
+        Ok(text
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("Time stamp:"))
+            .map(|time| time.trim().to_string()))
+    }
+}
@@ -0,0 +1,154 @@
+use std::io::{self, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+
+use crypto_core::tr;
+use rustls::{ServerConnection, StreamOwned};
+
+use crate::PKIConfig;
+use crate::client::{exchange, tls_connect};
+use crate::profile::CertificateProfile;
+use crate::subject::SubjectDn;
+use crate::server::{read_request, write_response, Response, ServerSettings};
+
+const SERVER_USER: &str = "tls-demo-server";
+const CLIENT_USER: &str = "tls-demo-client";
+
+/// What `pki demo tls` observed
+pub(crate) struct TlsDemoReport {
+    pub(crate) address: String,
+    pub(crate) server_cert: String,
+    /// Negotiated protocol version and cipher suite, e.g. `TLSv1_3`
+    pub(crate) protocol: String,
+    pub(crate) cipher_suite: String,
+    /// With mTLS: why the server turned away a client without a certificate
+    pub(crate) anonymous_rejection: Option<String>,
+    /// Whether the server saw a verified client certificate
+    pub(crate) client_authenticated: bool,
+    pub(crate) response: String,
+}
+
+impl PKIConfig {
+    /// Issue a server certificate for `localhost` (and with `mutual`, a
+    /// client certificate), serve HTTPS with it on a loopback port and
+    /// connect a client that trusts only the CA
+    pub(crate) fn run_tls_demo(&self, mutual: bool) -> io::Result<TlsDemoReport> {
+        self.init_ca()?;
+        let ca_cert_path = format!("{}/ca_certificate.pem", self.ca_dir);
+
+        let server_cert = self.issue_demo_certificate(
+            SERVER_USER,
+            CertificateProfile::Server,
+            &[String::from("DNS:localhost"), String::from("IP:127.0.0.1")],
+        )?;
+        let client_identity = if mutual {
+            Some(self.issue_demo_certificate(CLIENT_USER, CertificateProfile::Client, &[])?)
+        } else {
+            None
+        };
+
+        let settings = ServerSettings {
+            listen: String::from("127.0.0.1:0"),
+            token: None,
+            tls_cert: Some(server_cert.clone()),
+            tls_key: Some(format!("{}/{}_private_key.pem", self.users_dir, SERVER_USER)),
+            require_client_cert: mutual,
+            // The demo only handshakes; it never validates ACME challenges
+            acme_challenge_port: 0,
+        };
+        let tls_config = self
+            .server_tls_config(&settings)?
+            .ok_or_else(|| io::Error::other(tr!("TLS demo server has no certificate")))?;
+        let listener = TcpListener::bind(&settings.listen)?;
+        let address = listener.local_addr()?;
+
+        // With mTLS, a client without a certificate connects first and must be turned away
+        let connections = if mutual { 2 } else { 1 };
+        let server = thread::spawn(move || {
+            // Whether the client served last presented a certificate
+            let mut client_authenticated = false;
+            for _ in 0..connections {
+                let (stream, _) = listener.accept()?;
+                let connection = ServerConnection::new(Arc::clone(&tls_config)).map_err(io::Error::other)?;
+                let mut stream = StreamOwned::new(connection, stream);
+                let Ok(request) = read_request(&mut stream) else {
+                    continue;
+                };
+
+                // The verifier has checked any certificate against the CA by now
+                client_authenticated = stream.conn.peer_certificates().is_some();
+                let client = if client_authenticated {
+                    "a client with a certificate issued by the CA"
+                } else {
+                    "an anonymous client"
+                };
+                let body = format!("Hello {} requesting {} over TLS\n", client, request.path);
+                write_response(&mut stream, &Response::text(200, body))?;
+                stream.conn.send_close_notify();
+                stream.flush()?;
+            }
+            Ok::<_, io::Error>(client_authenticated)
+        });
+
+        let request = b"GET /hello HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n";
+        let anonymous_rejection = if mutual {
+            let stream = tls_connect("localhost", TcpStream::connect(address)?, &ca_cert_path, None)?;
+            match exchange(stream, request, &[]) {
+                Ok(response) => return Err(io::Error::other(tr!(
+                    "The server accepted a client without a certificate ({})",
+                    response.text()
+                ))),
+                Err(e) => Some(e.to_string()),
+            }
+        } else {
+            None
+        };
+
+        let client_identity = match &client_identity {
+            Some(cert_path) => {
+                let key = self.tls_private_key(&format!("{}/{}_private_key.pem", self.users_dir, CLIENT_USER))?;
+                Some((cert_path.as_str(), key))
+            }
+            None => None,
+        };
+        let mut stream = tls_connect("localhost", TcpStream::connect(address)?, &ca_cert_path, client_identity)?;
+        // Finish the handshake first so its parameters can be reported
+        while stream.conn.is_handshaking() {
+            stream.conn.complete_io(&mut stream.sock)?;
+        }
+        let protocol = stream.conn.protocol_version().map(|version| format!("{:?}", version)).unwrap_or_default();
+        let cipher_suite = stream
+            .conn
+            .negotiated_cipher_suite()
+            .map(|suite| format!("{:?}", suite.suite()))
+            .unwrap_or_default();
+        let response = exchange(&mut stream, request, &[])?.error_for_status()?;
+
+        let client_authenticated = server.join().map_err(|_| io::Error::other(tr!("TLS demo server panicked")))??;
+
+        Ok(TlsDemoReport {
+            address: address.to_string(),
+            server_cert,
+            protocol,
+            cipher_suite,
+            anonymous_rejection,
+            client_authenticated,
+            response: response.text(),
+        })
+    }
+
+    /// Fresh key, CSR and certificate for a demo identity; earlier ones are archived
+    fn issue_demo_certificate(
+        &self,
+        username: &str,
+        profile: CertificateProfile,
+        subject_alt_names: &[String],
+    ) -> io::Result<String> {
+        self.generate_user_key(username)?;
+        self.generate_csr_with_subject(username, &self.subject_for_user(username, SubjectDn::default()))?;
+        self.sign_user_certificate_with_extensions(username, Some(profile), subject_alt_names)?;
+
+        Ok(format!("{}/{}_certificate.pem", self.users_dir, username))
+    }
+}
@@ -0,0 +1,203 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, Write};
+use std::path::Path;
+
+use crypto_core::codec::{decode_hex, encode_hex};
+use crypto_core::merkle::{self, leaf_hash, Hash};
+use crypto_core::tr;
+use serde_json::{json, Value};
+
+use crate::PKIConfig;
+use crate::inventory::unix_now;
+
+/// One logged certificate
+pub(crate) struct LogEntry {
+    pub(crate) leaf_hash: Hash,
+    /// When the certificate was logged, in Unix seconds
+    pub(crate) logged_at: u64,
+    pub(crate) serial: String,
+    pub(crate) subject: String,
+}
+
+/// Proof that a leaf is part of the tree of the first `tree_size` entries
+pub(crate) struct InclusionProof {
+    pub(crate) leaf_index: usize,
+    pub(crate) tree_size: usize,
+    pub(crate) leaf_hash: Hash,
+    pub(crate) root_hash: Hash,
+    /// Sibling hashes from the leaf up to the root
+    pub(crate) audit_path: Vec<Hash>,
+}
+
+/// Proof that the log of `new_size` entries extends the one of `old_size`
+pub(crate) struct ConsistencyProof {
+    pub(crate) old_size: usize,
+    pub(crate) old_root: Hash,
+    pub(crate) new_size: usize,
+    pub(crate) new_root: Hash,
+    pub(crate) path: Vec<Hash>,
+}
+
+impl ConsistencyProof {
+    pub(crate) fn is_valid(&self) -> bool {
+        merkle::verify_consistency(self.old_size, self.new_size, &self.old_root, &self.new_root, &self.path)
+    }
+}
+
+impl InclusionProof {
+    pub(crate) fn to_json(&self) -> Value {
+        json!({
+            "leaf_index": self.leaf_index,
+            "tree_size": self.tree_size,
+            "leaf_hash": encode_hex(&self.leaf_hash),
+            "root_hash": encode_hex(&self.root_hash),
+            "audit_path": self.audit_path.iter().map(|hash| encode_hex(hash)).collect::<Vec<_>>(),
+        })
+    }
+
+    pub(crate) fn from_json(value: &Value) -> io::Result<Self> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, tr!("Malformed inclusion proof"));
+        let number = |key: &str| value[key].as_u64().map(|n| n as usize).ok_or_else(invalid);
+        let hash = |value: &Value| value.as_str().and_then(from_hex).ok_or_else(invalid);
+
+        Ok(InclusionProof {
+            leaf_index: number("leaf_index")?,
+            tree_size: number("tree_size")?,
+            leaf_hash: hash(&value["leaf_hash"])?,
+            root_hash: hash(&value["root_hash"])?,
+            audit_path: value["audit_path"]
+                .as_array()
+                .ok_or_else(invalid)?
+                .iter()
+                .map(hash)
+                .collect::<io::Result<_>>()?,
+        })
+    }
+
+    /// Recompute the root from the leaf and audit path and compare it with
+    /// the proof's root (RFC 9162, section 2.1.3.2)
+    pub(crate) fn is_valid(&self) -> bool {
+        merkle::verify_inclusion(self.leaf_index, self.tree_size, &self.leaf_hash, &self.audit_path, &self.root_hash)
+    }
+}
+
+impl PKIConfig {
+    /// Append-only log of every issued certificate, one tab-separated entry
+    /// per line: leaf hash, time logged, serial, subject
+    pub(crate) fn transparency_log_path(&self) -> String {
+        format!("{}/transparency.log", self.ca_dir)
+    }
+
+    /// Add a freshly issued certificate to the log
+    pub(crate) fn append_to_log(&self, cert_path: &str, serial: &str, subject: &str) -> io::Result<()> {
+        let leaf_hash = leaf_hash(&certificate_der(cert_path)?);
+
+        let mut log = OpenOptions::new().create(true).append(true).open(self.transparency_log_path())?;
+        writeln!(log, "{}\t{}\t{}\t{}", encode_hex(&leaf_hash), unix_now(), serial, subject)
+    }
+
+    pub(crate) fn log_entries(&self) -> io::Result<Vec<LogEntry>> {
+        let log_path = self.transparency_log_path();
+        if !Path::new(&log_path).exists() {
+            return Ok(Vec::new());
+        }
+
+        fs::read_to_string(&log_path)?
+            .lines()
+            .enumerate()
+            .map(|(number, line)| {
+                let fields: Vec<&str> = line.splitn(4, '\t').collect();
+                let entry = match fields.as_slice() {
+                    [hash, logged_at, serial, subject] => from_hex(hash).zip(logged_at.parse().ok()).map(|(leaf_hash, logged_at)| {
+                        LogEntry { leaf_hash, logged_at, serial: serial.to_string(), subject: subject.to_string() }
+                    }),
+                    _ => None,
+                };
+                entry.ok_or_else(|| io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    tr!("Corrupt entry on line {} of {}", number + 1, log_path)
+                ))
+            })
+            .collect()
+    }
+
+    /// Number of entries and Merkle tree root of the whole log
+    pub(crate) fn log_tree_head(&self) -> io::Result<(usize, Hash)> {
+        let leaves = self.log_leaves()?;
+        Ok((leaves.len(), merkle::root(&leaves)))
+    }
+
+    /// Prove that a user's current certificate is in the log, against the
+    /// tree of all entries so far
+    pub(crate) fn inclusion_proof(&self, username: &str) -> io::Result<InclusionProof> {
+        let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
+        let leaf_hash = leaf_hash(&certificate_der(&user_cert_path)?);
+        let leaves = self.log_leaves()?;
+
+        let leaf_index = leaves.iter().position(|leaf| *leaf == leaf_hash).ok_or_else(|| io::Error::new(
+            io::ErrorKind::NotFound,
+            tr!("The certificate of {} is not in the transparency log", username)
+        ))?;
+
+        Ok(InclusionProof {
+            leaf_index,
+            tree_size: leaves.len(),
+            leaf_hash,
+            root_hash: merkle::root(&leaves),
+            audit_path: merkle::inclusion_proof(leaf_index, &leaves),
+        })
+    }
+
+    /// Check a proof made earlier against the log as it stands now: the
+    /// proof must hold, be for the user's current certificate and its root
+    /// must match the log's first `tree_size` entries, which an append-only
+    /// log never changes
+    pub(crate) fn verify_inclusion_proof(&self, username: &str, proof: &InclusionProof) -> io::Result<bool> {
+        let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
+        let leaf_hash = leaf_hash(&certificate_der(&user_cert_path)?);
+        let leaves = self.log_leaves()?;
+
+        if proof.leaf_hash != leaf_hash || proof.tree_size > leaves.len() {
+            return Ok(false);
+        }
+        Ok(proof.is_valid() && merkle::root(&leaves[..proof.tree_size]) == proof.root_hash)
+    }
+
+    /// Prove that the log's first `old_size` entries are unchanged in the
+    /// log as it stands now. Returns the old and current tree heads and the
+    /// proof.
+    pub(crate) fn consistency_proof(&self, old_size: usize) -> io::Result<ConsistencyProof> {
+        let leaves = self.log_leaves()?;
+        if old_size == 0 || old_size > leaves.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("The log has {} entries; pick an older size from 1 to {}", leaves.len(), leaves.len())
+            ));
+        }
+
+        Ok(ConsistencyProof {
+            old_size,
+            old_root: merkle::root(&leaves[..old_size]),
+            new_size: leaves.len(),
+            new_root: merkle::root(&leaves),
+            path: merkle::consistency_proof(old_size, &leaves),
+        })
+    }
+
+    fn log_leaves(&self) -> io::Result<Vec<Hash>> {
+        Ok(self.log_entries()?.into_iter().map(|entry| entry.leaf_hash).collect())
+    }
+}
+
+/// DER encoding of the first certificate in a PEM file
+pub(crate) fn certificate_der(cert_path: &str) -> io::Result<Vec<u8>> {
+    let certificate = rustls_pemfile::certs(&mut BufReader::new(File::open(cert_path)?))
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, tr!("No certificate in {}", cert_path)))??;
+    Ok(certificate.to_vec())
+}
+
+/// A SHA-256 hash written as 64 hex digits
+pub(crate) fn from_hex(text: &str) -> Option<Hash> {
+    decode_hex(text).ok()?.try_into().ok()
+}
@@ -0,0 +1,182 @@
+use std::io;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use crypto_core::codec::encode_hex;
+use crypto_core::tr;
+use ring::digest::{digest, SHA1_FOR_LEGACY_USE_ONLY, SHA256};
+
+use crate::PKIConfig;
+use crate::exec::{self, Execute};
+use crate::transparency::certificate_der;
+
+const DEBIAN_ANCHORS: &str = "/usr/local/share/ca-certificates";
+const REDHAT_ANCHORS: &str = "/etc/pki/ca-trust/source/anchors";
+const MACOS_KEYCHAIN: &str = "/Library/Keychains/System.keychain";
+
+/// Operating system trust store the CA certificate is installed into.
+/// Firefox keeps its own store and needs the certificate imported by hand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TrustStore {
+    /// Debian and Ubuntu `ca-certificates`
+    DebianCaCertificates,
+    /// Fedora and RHEL `ca-trust`
+    RedHatCaTrust,
+    MacKeychain,
+    /// The local machine's Trusted Root Certification Authorities
+    WindowsRoot,
+}
+
+impl TrustStore {
+    /// The store of the running system
+    pub(crate) fn detect() -> io::Result<Self> {
+        if cfg!(target_os = "macos") {
+            Ok(Self::MacKeychain)
+        } else if cfg!(windows) {
+            Ok(Self::WindowsRoot)
+        } else if Path::new(DEBIAN_ANCHORS).is_dir() {
+            Ok(Self::DebianCaCertificates)
+        } else if Path::new(REDHAT_ANCHORS).is_dir() {
+            Ok(Self::RedHatCaTrust)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                tr!("No supported trust store found (looked for {} and {})", DEBIAN_ANCHORS, REDHAT_ANCHORS)
+            ))
+        }
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::DebianCaCertificates => "ca-certificates",
+            Self::RedHatCaTrust => "ca-trust",
+            Self::MacKeychain => "macOS system keychain",
+            Self::WindowsRoot => "Windows Root store",
+        }
+    }
+}
+
+impl PKIConfig {
+    /// Make the system trust the CA certificate, so browsers and other TLS
+    /// clients accept certificates it issued. Needs root or administrator
+    /// rights.
+    pub(crate) fn install_ca_trust(&self, store: TrustStore) -> io::Result<()> {
+        let ca_cert_path = self.existing_ca_certificate()?;
+
+        let output = match store {
+            TrustStore::DebianCaCertificates | TrustStore::RedHatCaTrust => {
+                exec::copy(&ca_cert_path, self.trust_anchor_path(store)?).map_err(needs_privileges)?;
+                self.refresh_anchors(store, false)?
+            }
+            TrustStore::MacKeychain => Command::new("security")
+                .args(["add-trusted-cert", "-d", "-r", "trustRoot", "-k", MACOS_KEYCHAIN, &ca_cert_path])
+                .execute(self.runner())?,
+            TrustStore::WindowsRoot => Command::new("certutil")
+                .args(["-addstore", "-f", "Root", &ca_cert_path])
+                .execute(self.runner())?,
+        };
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!(
+                "Failed to install the CA certificate into the {} (are you root/administrator?)",
+                store.name()
+            )));
+        }
+        self.record_audit_event("trust-installed", store.name())
+    }
+
+    /// Undo [`PKIConfig::install_ca_trust`] for the current CA certificate
+    pub(crate) fn uninstall_ca_trust(&self, store: TrustStore) -> io::Result<()> {
+        let ca_cert_path = self.existing_ca_certificate()?;
+        let thumbprint = encode_hex(digest(&SHA1_FOR_LEGACY_USE_ONLY, &certificate_der(&ca_cert_path)?).as_ref());
+
+        let output = match store {
+            TrustStore::DebianCaCertificates | TrustStore::RedHatCaTrust => {
+                let anchor_path = self.trust_anchor_path(store)?;
+                if !Path::new(&anchor_path).exists() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        tr!("The CA certificate is not installed ({} does not exist)", anchor_path)
+                    ));
+                }
+                exec::remove_file(&anchor_path).map_err(needs_privileges)?;
+                self.refresh_anchors(store, true)?
+            }
+            TrustStore::MacKeychain => {
+                let output = Command::new("security")
+                    .args(["remove-trusted-cert", "-d", &ca_cert_path])
+                    .execute(self.runner())?;
+                if output.status.success() {
+                    Command::new("security")
+                        .args(["delete-certificate", "-Z", &thumbprint, MACOS_KEYCHAIN])
+                        .execute(self.runner())?
+                } else {
+                    output
+                }
+            }
+            TrustStore::WindowsRoot => Command::new("certutil")
+                .args(["-delstore", "Root", &thumbprint])
+                .execute(self.runner())?,
+        };
+
+        if !output.status.success() {
+            return Err(io::Error::other(tr!(
+                "Failed to remove the CA certificate from the {} (are you root/administrator?)",
+                store.name()
+            )));
+        }
+        self.record_audit_event("trust-uninstalled", store.name())
+    }
+
+    fn existing_ca_certificate(&self) -> io::Result<String> {
+        let ca_cert_path = format!("{}/ca_certificate.pem", self.ca_dir);
+        if !Path::new(&ca_cert_path).exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                tr!("CA certificate not found; initialize the PKI first")
+            ));
+        }
+        Ok(ca_cert_path)
+    }
+
+    /// Anchor file named after the CA certificate's fingerprint, so
+    /// several course PKIs can be trusted side by side
+    fn trust_anchor_path(&self, store: TrustStore) -> io::Result<String> {
+        let ca_cert_path = self.existing_ca_certificate()?;
+        let fingerprint = encode_hex(digest(&SHA256, &certificate_der(&ca_cert_path)?).as_ref());
+
+        Ok(match store {
+            TrustStore::RedHatCaTrust => format!("{}/pki-ca-{}.pem", REDHAT_ANCHORS, &fingerprint[..16]),
+            // update-ca-certificates only picks up `.crt` files
+            _ => format!("{}/pki-ca-{}.crt", DEBIAN_ANCHORS, &fingerprint[..16]),
+        })
+    }
+
+    /// Rebuild the system bundle from the anchor directory
+    fn refresh_anchors(&self, store: TrustStore, removed: bool) -> io::Result<Output> {
+        let mut command = match store {
+            TrustStore::RedHatCaTrust => {
+                let mut command = Command::new("update-ca-trust");
+                command.arg("extract");
+                command
+            }
+            _ => {
+                let mut command = Command::new("update-ca-certificates");
+                // Without --fresh, links to removed anchors are left behind
+                if removed {
+                    command.arg("--fresh");
+                }
+                command
+            }
+        };
+        command.execute(self.runner())
+    }
+}
+
+fn needs_privileges(error: io::Error) -> io::Error {
+    if error.kind() == io::ErrorKind::PermissionDenied {
+        io::Error::new(error.kind(), tr!("{} (run as root to change the system trust store)", error))
+    } else {
+        error
+    }
+}
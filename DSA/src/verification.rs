@@ -0,0 +1,89 @@
+use std::io;
+
+use crate::PKIConfig;
+use crate::chain::ChainCheck;
+use crate::inventory::{parse_openssl_time, unix_now, CertificateDetails};
+use crate::timestamp::TimestampCheck;
+
+/// Everything `pki verify` checks about a detached signature
+pub(crate) struct VerificationReport {
+    /// The signature matches the document and the signer's public key
+    pub(crate) signature_valid: bool,
+    /// Digest algorithm recorded in the signature, e.g. `sha256`
+    pub(crate) digest: Option<String>,
+    pub(crate) signer: CertificateDetails,
+    /// Chain checked as of the signing time
+    pub(crate) chain: ChainCheck,
+    /// Whether a timestamp token is present and verifies
+    pub(crate) timestamp: TimestampCheck,
+    /// Unix seconds the signature is known to exist at: the timestamp when
+    /// it verifies, otherwise (also when it went unverified) the time of
+    /// verification
+    pub(crate) signing_time: u64,
+    pub(crate) signing_time_from_timestamp: bool,
+    /// Revocation time of the signer certificate, if revoked
+    pub(crate) revoked_at: Option<u64>,
+}
+
+impl VerificationReport {
+    /// The signer's certificate was within its validity period when signing
+    pub(crate) fn valid_at_signing_time(&self) -> bool {
+        let info = &self.signer.info;
+        info.not_before <= self.signing_time && self.signing_time < info.not_after
+    }
+
+    /// A revocation only invalidates signatures not proven to predate it
+    pub(crate) fn revoked_before_signing(&self) -> bool {
+        self.revoked_at.is_some_and(|revoked_at| !self.signing_time_from_timestamp || revoked_at <= self.signing_time)
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        self.signature_valid
+            && self.chain.valid
+            && self.valid_at_signing_time()
+            && self.timestamp != TimestampCheck::Invalid
+            && !self.revoked_before_signing()
+    }
+}
+
+impl PKIConfig {
+    /// Verify a document's detached signature and collect the signer,
+    /// chain, timing and revocation details
+    pub(crate) fn verify_document(&self, username: &str, document_path: &str) -> io::Result<VerificationReport> {
+        let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
+
+        let signature_valid = self.verify_document_signature(username, document_path)?;
+        let signer = self.inspect_certificate(username, 0)?;
+        let digest = self.signature_digest(username, document_path)?;
+
+        let timestamp = self.verify_signature_timestamp(document_path)?;
+        let timestamp_time = match timestamp {
+            TimestampCheck::Valid => self.signature_timestamp_time(document_path)?.as_deref().and_then(parse_openssl_time),
+            _ => None,
+        };
+        let signing_time = timestamp_time.unwrap_or_else(unix_now);
+
+        let chain = self.check_certificate_chain(&user_cert_path, Some(signing_time))?;
+        let revoked_at = self
+            .issued_certificates()?
+            .into_iter()
+            .find(|certificate| certificate.revoked && certificate.serial == signer.info.serial)
+            .map(|certificate| certificate.revoked_at.unwrap_or_default());
+
+        Ok(VerificationReport {
+            signature_valid,
+            digest,
+            signer,
+            chain,
+            timestamp,
+            signing_time,
+            signing_time_from_timestamp: timestamp_time.is_some(),
+            revoked_at,
+        })
+    }
+
+    /// Digest algorithm named in the DigestInfo of an RSA signature
+    pub(crate) fn signature_digest(&self, username: &str, document_path: &str) -> io::Result<Option<String>> {
+        self.backend.signature_digest(self, username, document_path)
+    }
+}
@@ -0,0 +1,256 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crypto_core::tr;
+use serde_json::{json, Value};
+use tracing::warn;
+
+use crate::PKIConfig;
+use crate::csr::{ExistingCertificate, SanPolicy};
+use crate::exec;
+use crate::profile::CertificateProfile;
+
+/// What `pki watch` accepts from its incoming directory
+pub(crate) struct WatchPolicy {
+    /// Required subject as written for `-subj`, `*` matching any run of
+    /// characters (e.g. `/CN=*/O=Course`)
+    pub(crate) subject_pattern: Option<String>,
+    /// Smallest RSA key accepted; defaults to the user key size
+    pub(crate) min_key_bits: Option<u32>,
+    /// Queue acceptable CSRs for `pki watch approve` instead of signing them
+    pub(crate) require_approval: bool,
+    pub(crate) profile: Option<CertificateProfile>,
+    /// Where issued certificates are written; defaults to `<incoming>/outbox`
+    pub(crate) outbox: Option<String>,
+}
+
+/// What happened to a CSR found in the incoming directory
+pub(crate) enum WatchOutcome {
+    /// Issued, with the certificate copied to the outbox
+    Signed { certificate: String },
+    /// Moved to `pending/` to wait for approval
+    Queued,
+    /// Moved to `rejected/`, with the reason next to it
+    Rejected { reason: String },
+    /// Left in place to be retried, e.g. because signing failed
+    Failed { error: String },
+}
+
+pub(crate) struct WatchedCsr {
+    /// File name within the incoming directory
+    pub(crate) file: String,
+    pub(crate) username: Option<String>,
+    pub(crate) outcome: WatchOutcome,
+}
+
+impl WatchedCsr {
+    pub(crate) fn describe(&self) -> String {
+        let who = self.username.as_deref().map(|username| format!(" ({})", username)).unwrap_or_default();
+        match &self.outcome {
+            WatchOutcome::Signed { certificate } => tr!("{}{}: signed, certificate {}", self.file, who, certificate),
+            WatchOutcome::Queued => tr!("{}{}: queued for approval", self.file, who),
+            WatchOutcome::Rejected { reason } => tr!("{}{}: rejected, {}", self.file, who, reason),
+            WatchOutcome::Failed { error } => tr!("{}{}: failed, will retry ({})", self.file, who, error),
+        }
+    }
+
+    pub(crate) fn to_json(&self) -> Value {
+        let (status, detail) = match &self.outcome {
+            WatchOutcome::Signed { certificate } => ("signed", Some(certificate)),
+            WatchOutcome::Queued => ("queued", None),
+            WatchOutcome::Rejected { reason } => ("rejected", Some(reason)),
+            WatchOutcome::Failed { error } => ("failed", Some(error)),
+        };
+        json!({
+            "file": self.file,
+            "username": self.username,
+            "status": status,
+            "detail": detail,
+        })
+    }
+}
+
+impl PKIConfig {
+    /// Handle every complete CSR in `incoming_dir` once: reject those the
+    /// policy refuses, and sign or queue the rest
+    pub(crate) fn process_incoming_csrs(&self, incoming_dir: &str, policy: &WatchPolicy) -> io::Result<Vec<WatchedCsr>> {
+        let mut processed = Vec::new();
+        for file in incoming_csr_files(incoming_dir)? {
+            let csr_path = format!("{}/{}", incoming_dir, file);
+
+            let username = match self.check_watch_policy(&csr_path, policy) {
+                Ok(username) => username,
+                Err(e) if matches!(e.kind(), io::ErrorKind::InvalidData | io::ErrorKind::AlreadyExists) => {
+                    warn!(file = %file, reason = %e, "CSR rejected");
+                    reject_csr_file(&csr_path, incoming_dir, &file, &e.to_string())?;
+                    self.record_audit_event("csr-rejected", &format!("{} {}", file, e))?;
+                    processed.push(WatchedCsr {
+                        file,
+                        username: None,
+                        outcome: WatchOutcome::Rejected { reason: e.to_string() },
+                    });
+                    continue;
+                }
+                Err(e) => {
+                    warn!(file = %file, error = %e, "CSR could not be checked");
+                    processed.push(WatchedCsr { file, username: None, outcome: WatchOutcome::Failed { error: e.to_string() } });
+                    continue;
+                }
+            };
+
+            let outcome = if policy.require_approval {
+                exec::create_dir_all(format!("{}/pending", incoming_dir))?;
+                exec::rename(&csr_path, format!("{}/pending/{}", incoming_dir, file))?;
+                self.record_audit_event("csr-queued", &format!("{} {}", file, username))?;
+                WatchOutcome::Queued
+            } else {
+                match self.issue_from_csr_file(&csr_path, &username, incoming_dir, policy) {
+                    Ok(certificate) => {
+                        exec::remove_file(&csr_path)?;
+                        WatchOutcome::Signed { certificate }
+                    }
+                    Err(e) => {
+                        warn!(file = %file, username = %username, error = %e, "signing failed");
+                        WatchOutcome::Failed { error: e.to_string() }
+                    }
+                }
+            };
+            processed.push(WatchedCsr { file, username: Some(username), outcome });
+        }
+
+        Ok(processed)
+    }
+
+    /// CSRs waiting in `<incoming>/pending` for approval
+    pub(crate) fn pending_csrs(&self, incoming_dir: &str) -> io::Result<Vec<String>> {
+        let pending_dir = format!("{}/pending", incoming_dir);
+        if !Path::new(&pending_dir).is_dir() {
+            return Ok(Vec::new());
+        }
+        incoming_csr_files(&pending_dir)
+    }
+
+    /// Sign a queued CSR and write its certificate to the outbox
+    pub(crate) fn approve_pending_csr(&self, incoming_dir: &str, file: &str, policy: &WatchPolicy) -> io::Result<WatchedCsr> {
+        let csr_path = pending_csr_path(incoming_dir, file)?;
+        // The key size and subject were checked when the CSR was queued
+        let username = self.check_csr_policy_with_key_bits(&csr_path, 0)?;
+
+        let certificate = self.issue_from_csr_file(&csr_path, &username, incoming_dir, policy)?;
+        exec::remove_file(&csr_path)?;
+
+        Ok(WatchedCsr { file: file.to_string(), username: Some(username), outcome: WatchOutcome::Signed { certificate } })
+    }
+
+    /// Move a queued CSR to `rejected/` without signing it
+    pub(crate) fn reject_pending_csr(&self, incoming_dir: &str, file: &str, reason: &str) -> io::Result<WatchedCsr> {
+        let csr_path = pending_csr_path(incoming_dir, file)?;
+        reject_csr_file(&csr_path, incoming_dir, file, reason)?;
+        self.record_audit_event("csr-rejected", &format!("{} {}", file, reason))?;
+
+        Ok(WatchedCsr { file: file.to_string(), username: None, outcome: WatchOutcome::Rejected { reason: reason.to_string() } })
+    }
+
+    /// The base CSR policy, the subject alternative names it allows, and the
+    /// watch-specific subject and key size rules. A CSR for a user who still
+    /// holds a valid certificate is refused with `AlreadyExists`, since anyone
+    /// able to write to the directory could otherwise replace it; other
+    /// policy violations are `InvalidData` errors.
+    fn check_watch_policy(&self, csr_path: &str, policy: &WatchPolicy) -> io::Result<String> {
+        let username = self.check_csr_policy_with_key_bits(csr_path, policy.min_key_bits.unwrap_or(self.user_key_bits))?;
+
+        if let Some(pattern) = &policy.subject_pattern {
+            let subject = self.csr_subject(csr_path)?;
+            if !matches_pattern(pattern, &subject) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    tr!("subject {} does not match {}", subject, pattern)
+                ));
+            }
+        }
+        // Refuse disallowed names now rather than queueing them for approval
+        self.permitted_subject_alt_names(csr_path, SanPolicy::Configured)?;
+        self.check_replaceable(&username, ExistingCertificate::Keep)?;
+
+        Ok(username)
+    }
+
+    /// Issue a certificate for a checked CSR and copy it to the outbox. A
+    /// valid certificate issued since the CSR was checked is still kept.
+    fn issue_from_csr_file(
+        &self,
+        csr_path: &str,
+        username: &str,
+        incoming_dir: &str,
+        policy: &WatchPolicy,
+    ) -> io::Result<String> {
+        let subject_alt_names = self.permitted_subject_alt_names(csr_path, SanPolicy::Configured)?;
+        self.issue_for_external_csr(csr_path, username, policy.profile, &subject_alt_names, ExistingCertificate::Keep)?;
+
+        let outbox = policy.outbox.clone().unwrap_or_else(|| format!("{}/outbox", incoming_dir));
+        let outbox_cert_path = format!("{}/{}_certificate.pem", outbox, username);
+        exec::create_dir_all(&outbox)?;
+        exec::copy(format!("{}/{}_certificate.pem", self.users_dir, username), &outbox_cert_path)?;
+
+        Ok(outbox_cert_path)
+    }
+}
+
+/// CSR files in a directory that have been written completely. Hidden files
+/// (uploads in progress) and subdirectories are skipped.
+fn incoming_csr_files(dir: &str) -> io::Result<Vec<String>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file = entry.file_name().to_string_lossy().into_owned();
+        if file.starts_with('.') || !entry.file_type()?.is_file() || !(file.ends_with(".csr") || file.ends_with(".pem")) {
+            continue;
+        }
+
+        // A CSR still being copied in has no END line yet
+        let contents = fs::read_to_string(entry.path()).unwrap_or_default();
+        if contents.trim_end().ends_with("CERTIFICATE REQUEST-----") {
+            files.push(file);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn pending_csr_path(incoming_dir: &str, file: &str) -> io::Result<String> {
+    let csr_path = format!("{}/pending/{}", incoming_dir, file);
+    if file.contains('/') || !Path::new(&csr_path).is_file() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, tr!("No pending CSR {}", file)));
+    }
+    Ok(csr_path)
+}
+
+/// Move a CSR to `<incoming>/rejected/`, with the reason in `<file>.reason`
+fn reject_csr_file(csr_path: &str, incoming_dir: &str, file: &str, reason: &str) -> io::Result<()> {
+    let rejected_dir = format!("{}/rejected", incoming_dir);
+    exec::create_dir_all(&rejected_dir)?;
+    exec::rename(csr_path, format!("{}/{}", rejected_dir, file))?;
+    exec::write(format!("{}/{}.reason", rejected_dir, file), format!("{}\n", reason))
+}
+
+/// Match `text` against a pattern where `*` stands for any run of characters
+pub(crate) fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
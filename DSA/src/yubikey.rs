@@ -0,0 +1,111 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use crypto_core::tr;
+
+use crate::PKIConfig;
+use crate::permissions;
+
+/// PIV slots usable for user identities
+const PIV_SLOTS: [&str; 4] = ["9a", "9c", "9d", "9e"];
+
+impl PKIConfig {
+    /// Write a user's identity onto a YubiKey PIV slot.
+    ///
+    /// With `on_device` the key pair is generated on the YubiKey and only the
+    /// CSR leaves it; otherwise the existing key and certificate are imported.
+    pub(crate) fn provision_yubikey(&self, username: &str, slot: &str, on_device: bool) -> io::Result<()> {
+        if !PIV_SLOTS.contains(&slot) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("Unsupported PIV slot {} (expected one of {})", slot, PIV_SLOTS.join(", "))
+            ));
+        }
+
+        let user_key_path = format!("{}/{}_private_key.pem", self.users_dir, username);
+        let user_csr_path = format!("{}/{}_csr.pem", self.users_dir, username);
+        let user_cert_path = format!("{}/{}_certificate.pem", self.users_dir, username);
+
+        if on_device {
+            let public_key_path = format!("{}/{}_piv_public_key.pem", self.users_dir, username);
+
+            self.run_ykman(
+                &["piv", "keys", "generate", "--algorithm", "RSA2048", slot, &public_key_path],
+                &tr!("Failed to generate a key in PIV slot {}", slot)
+            )?;
+            let result = self.run_ykman(
+                &[
+                    "piv", "certificates", "request",
+                    "--subject", &self.user_subject.clone().with_common_name(username).to_rfc4514(),
+                    slot, &public_key_path, &user_csr_path
+                ],
+                &tr!("Failed to create a CSR from PIV slot {}", slot)
+            );
+            fs::remove_file(&public_key_path)?;
+            result?;
+
+            self.sign_user_certificate(username)?;
+        } else {
+            if !Path::new(&user_key_path).exists() || !Path::new(&user_cert_path).exists() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    tr!("Key or certificate for user {} not found", username)
+                ));
+            }
+
+            if self.user_keys_protected() {
+                // ykman cannot be handed the master key safely, so it gets a
+                // decrypted copy that exists only for the import
+                let decrypted_key_path = format!("{}/{}_piv_import_key.pem", self.users_dir, username);
+                permissions::create_private_file(&decrypted_key_path)?;
+                let result = self.private_key_pem(&user_key_path).and_then(|key| {
+                    fs::write(&decrypted_key_path, key)?;
+                    self.run_ykman(
+                        &["piv", "keys", "import", slot, &decrypted_key_path],
+                        &tr!("Failed to import key of user {} into PIV slot {}", username, slot)
+                    )
+                });
+                fs::remove_file(&decrypted_key_path)?;
+                result?;
+            } else {
+                self.run_ykman(
+                    &["piv", "keys", "import", slot, &user_key_path],
+                    &tr!("Failed to import key of user {} into PIV slot {}", username, slot)
+                )?;
+            }
+        }
+
+        self.run_ykman(
+            &["piv", "certificates", "import", slot, &user_cert_path],
+            &tr!("Failed to import certificate of user {} into PIV slot {}", username, slot)
+        )
+    }
+
+    /// Run `ykman` with the terminal attached so it can prompt for the PIN and
+    /// management key unless they are supplied through the environment
+    fn run_ykman(&self, args: &[&str], error_message: &str) -> io::Result<()> {
+        // Options go after the `piv <group> <action>` subcommand
+        let (subcommand, rest) = args.split_at(3);
+        let mut command = Command::new("ykman");
+        command.args(subcommand);
+
+        // Only writing operations take the management key
+        if let (Ok(management_key), false) = (env::var("PKI_YUBIKEY_MANAGEMENT_KEY"), subcommand[2] == "request") {
+            command.args(["--management-key", &management_key]);
+        }
+        if let Ok(pin) = env::var("PKI_YUBIKEY_PIN") {
+            command.args(["--pin", &pin]);
+        }
+
+        let status = self.runner().interactive(command.args(rest))?;
+
+        if !status.success() {
+            return Err(io::Error::other(error_message.to_string()));
+        }
+
+        Ok(())
+    }
+}
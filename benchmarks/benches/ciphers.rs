@@ -0,0 +1,121 @@
+//! Throughput of every registered cipher and of the hashes on the same
+//! input sizes, and the latency of single operations. `cargo run -p benchmarks --bin
+//! bench-report` turns the results into the course's tables.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use crypto_core::aes::Aes128;
+use crypto_core::chacha20::ChaCha20Poly1305;
+use crypto_core::ecc::{Curve, EcdsaPrivateKey};
+use crypto_core::hash::{sha256, Sha256};
+use crypto_core::mac;
+use crypto_core::modes::{self, Mode};
+use crypto_core::registry::{CipherPlugin, Registry};
+use crypto_core::rsa::RsaPrivateKey;
+use crypto_core::{BlockCipher, ClassicalCipher, SymmetricCipher};
+use des::{Des, DesPlugin};
+use playfair::PlayfairPlugin;
+use student_ciphers::StudentCiphers;
+
+/// Input sizes in bytes: a short message, a page, a small file
+const SIZES: [usize; 3] = [64, 1024, 16 * 1024];
+
+/// Letters only, so the classical ciphers do the same work per byte
+fn message(len: usize) -> String {
+    "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG".chars().cycle().take(len).collect()
+}
+
+/// crypto-core's ciphers and every plugin's, each run with its example key
+fn registry() -> Registry {
+    let mut registry = Registry::builtin();
+    let plugins: [&dyn CipherPlugin; 3] = [&DesPlugin, &PlayfairPlugin, &StudentCiphers];
+    for plugin in plugins {
+        registry.install(plugin).unwrap();
+    }
+    registry
+}
+
+fn classical(c: &mut Criterion) {
+    let ciphers: Vec<(&str, Box<dyn ClassicalCipher>)> = registry()
+        .iter()
+        .filter(|registration| registration.is_classical())
+        .map(|registration| (registration.name(), registration.classical_cipher(registration.example_key).unwrap()))
+        .collect();
+
+    let mut group = c.benchmark_group("classical");
+    for size in SIZES {
+        let text = message(size);
+        group.throughput(Throughput::Bytes(size as u64));
+        for (name, cipher) in &ciphers {
+            group.bench_with_input(BenchmarkId::new(*name, size), &text, |b, text| b.iter(|| cipher.encrypt(black_box(text)).unwrap()));
+        }
+    }
+    group.finish();
+}
+
+fn symmetric(c: &mut Criterion) {
+    let des = Des::new(b"MORTYNOR").unwrap();
+    let aes = Aes128::new(b"YELLOW SUBMARINE").unwrap();
+    let aead = ChaCha20Poly1305::new(&[7; 32]).unwrap();
+    let block_ciphers: [(&str, &dyn BlockCipher); 2] = [("des", &des), ("aes", &aes)];
+    // The rest as registered: ECB for block ciphers, a fresh nonce for ChaCha20
+    let ciphers: Vec<(&str, Box<dyn SymmetricCipher>)> = registry()
+        .iter()
+        .filter(|registration| !registration.is_classical() && !block_ciphers.iter().any(|(name, _)| *name == registration.name()))
+        .map(|registration| (registration.name(), registration.symmetric_cipher(registration.example_key.as_bytes()).unwrap()))
+        .collect();
+
+    let mut group = c.benchmark_group("symmetric");
+    for size in SIZES {
+        let data = message(size).into_bytes();
+        group.throughput(Throughput::Bytes(size as u64));
+        for (name, cipher) in block_ciphers {
+            for mode in [Mode::Ecb, Mode::Cbc, Mode::Ctr] {
+                let iv = vec![0; if mode.needs_iv() { cipher.block_size() } else { 0 }];
+                let id = BenchmarkId::new(format!("{}-{}", name, mode.name()), size);
+                group.bench_with_input(id, &data, |b, data| b.iter(|| modes::encrypt(cipher, mode, &iv, black_box(data)).unwrap()));
+            }
+        }
+        for (name, cipher) in &ciphers {
+            group.bench_with_input(BenchmarkId::new(*name, size), &data, |b, data| b.iter(|| cipher.encrypt(black_box(data))));
+        }
+        group.bench_with_input(BenchmarkId::new("chacha20-poly1305", size), &data, |b, data| {
+            b.iter(|| aead.seal(&[0; 12], b"", black_box(data)))
+        });
+    }
+    group.finish();
+}
+
+fn hashes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hash");
+    for size in SIZES {
+        let data = message(size).into_bytes();
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("sha256", size), &data, |b, data| b.iter(|| sha256(black_box(data))));
+        group.bench_with_input(BenchmarkId::new("hmac-sha256", size), &data, |b, data| {
+            b.iter(|| mac::hmac::<Sha256>(b"key", black_box(data)))
+        });
+    }
+    group.finish();
+}
+
+/// One block, or one public-key operation, from call to result
+fn latency(c: &mut Criterion) {
+    let des = Des::new(b"MORTYNOR").unwrap();
+    let aes = Aes128::new(b"YELLOW SUBMARINE").unwrap();
+    let rsa = RsaPrivateKey::generate(2048).unwrap();
+    let ecdsa = EcdsaPrivateKey::generate(Curve::p256()).unwrap();
+    let message = b"the lecture starts at nine";
+
+    let mut group = c.benchmark_group("latency");
+    group.sample_size(20);
+    group.bench_function("des-block", |b| b.iter(|| BlockCipher::encrypt_block(&des, black_box(&mut [0; 8]))));
+    group.bench_function("aes-block", |b| b.iter(|| aes.encrypt_block(black_box(&mut [0; 16]))));
+    group.bench_function("rsa2048-sign", |b| b.iter(|| rsa.sign(black_box(message))));
+    let signature = rsa.sign(message);
+    group.bench_function("rsa2048-verify", |b| b.iter(|| rsa.public().verify(black_box(message), &signature)));
+    group.bench_function("ecdsa-p256-sign", |b| b.iter(|| ecdsa.sign(black_box(message)).unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, classical, symmetric, hashes, latency);
+criterion_main!(benches);
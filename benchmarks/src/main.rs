@@ -0,0 +1,127 @@
+//! `bench-report [<criterion dir>]`: collect the results of `cargo bench
+//! -p benchmarks` into `report.md` and `report.csv` in the criterion
+//! directory (by default `target/criterion`).
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+/// One measured benchmark
+struct Row {
+    group: String,
+    algorithm: String,
+    /// Input size in bytes, for the throughput benchmarks
+    bytes: Option<u64>,
+    /// Mean time per iteration in nanoseconds
+    mean_ns: f64,
+}
+
+impl Row {
+    fn mib_per_second(&self) -> Option<f64> {
+        self.bytes.map(|bytes| bytes as f64 / (self.mean_ns / 1e9) / (1024.0 * 1024.0))
+    }
+}
+
+/// Every `new/benchmark.json` under `dir`, with its estimates
+fn collect(dir: &Path, rows: &mut Vec<Row>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        if path.file_name().is_some_and(|name| name == "new") {
+            if let Some(row) = read_row(&path)? {
+                rows.push(row);
+            }
+        } else {
+            collect(&path, rows)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_row(dir: &Path) -> io::Result<Option<Row>> {
+    let read = |name: &str| -> io::Result<Option<Value>> {
+        match fs::read_to_string(dir.join(name)) {
+            Ok(text) => Ok(serde_json::from_str(&text).ok()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    };
+    let (Some(benchmark), Some(estimates)) = (read("benchmark.json")?, read("estimates.json")?) else {
+        return Ok(None);
+    };
+    let Some(mean_ns) = estimates["mean"]["point_estimate"].as_f64() else {
+        return Ok(None);
+    };
+
+    Ok(Some(Row {
+        group: benchmark["group_id"].as_str().unwrap_or("").to_string(),
+        algorithm: benchmark["function_id"].as_str().unwrap_or("").to_string(),
+        bytes: benchmark["throughput"]["Bytes"].as_u64(),
+        mean_ns,
+    }))
+}
+
+/// A time in the unit that keeps it readable
+fn format_time(ns: f64) -> String {
+    match ns {
+        ns if ns < 1e3 => format!("{:.1} ns", ns),
+        ns if ns < 1e6 => format!("{:.2} µs", ns / 1e3),
+        ns if ns < 1e9 => format!("{:.2} ms", ns / 1e6),
+        ns => format!("{:.2} s", ns / 1e9),
+    }
+}
+
+fn markdown(rows: &[Row]) -> String {
+    let mut report = String::from("| Group | Algorithm | Input | Mean time | Throughput |\n|---|---|---:|---:|---:|\n");
+    for row in rows {
+        report.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            row.group,
+            row.algorithm,
+            row.bytes.map(|bytes| format!("{} B", bytes)).unwrap_or_else(|| String::from("—")),
+            format_time(row.mean_ns),
+            row.mib_per_second().map(|speed| format!("{:.2} MiB/s", speed)).unwrap_or_else(|| String::from("—")),
+        ));
+    }
+    report
+}
+
+fn csv(rows: &[Row]) -> String {
+    let mut report = String::from("group,algorithm,input_bytes,mean_ns,mib_per_s\n");
+    for row in rows {
+        report.push_str(&format!(
+            "{},{},{},{:.1},{}\n",
+            row.group,
+            row.algorithm,
+            row.bytes.map(|bytes| bytes.to_string()).unwrap_or_default(),
+            row.mean_ns,
+            row.mib_per_second().map(|speed| format!("{:.3}", speed)).unwrap_or_default(),
+        ));
+    }
+    report
+}
+
+fn main() -> io::Result<()> {
+    let dir = match env::args().nth(1) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("CARGO_TARGET_DIR").unwrap_or_else(|| "target".into())).join("criterion"),
+    };
+    let mut rows = Vec::new();
+    collect(&dir, &mut rows).map_err(|e| io::Error::new(e.kind(), format!("{}: {} (run `cargo bench -p benchmarks` first)", dir.display(), e)))?;
+    if rows.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("No benchmark results in {}", dir.display())));
+    }
+    rows.sort_by(|a, b| (&a.group, a.bytes, &a.algorithm).cmp(&(&b.group, b.bytes, &b.algorithm)));
+
+    let table = markdown(&rows);
+    fs::write(dir.join("report.md"), &table)?;
+    fs::write(dir.join("report.csv"), csv(&rows))?;
+    print!("{}", table);
+    println!("\nWrote {} and {}", dir.join("report.md").display(), dir.join("report.csv").display());
+    Ok(())
+}
@@ -0,0 +1,147 @@
+//! Proptest strategies shared by the conformance suites in `tests/`: valid
+//! keys for every cipher, messages over each alphabet, and corruptions of
+//! ciphertexts, plus the registry of every cipher for the suite that runs
+//! them all. `cargo test -p conformance` runs every suite.
+
+use crypto_core::aes::Aes128;
+use crypto_core::hill::{self, Hill};
+use crypto_core::language::Alphabet;
+use crypto_core::registry::{CipherPlugin, Registry};
+use crypto_core::{BlockCipher, SymmetricCipher};
+use des::{Des, DesPlugin};
+use playfair::PlayfairPlugin;
+use proptest::prelude::*;
+use student_ciphers::StudentCiphers;
+
+/// crypto-core's ciphers and every plugin's, as `crypto` registers them
+pub fn registry() -> Registry {
+    let mut registry = Registry::builtin();
+    let plugins: [&dyn CipherPlugin; 3] = [&DesPlugin, &PlayfairPlugin, &StudentCiphers];
+    for plugin in plugins {
+        registry.install(plugin).expect("the plugins' names are distinct");
+    }
+    registry
+}
+
+/// Messages over `alphabet` in either case, with spaces, digits and
+/// punctuation mixed in for the ciphers that pass them through
+pub fn message(alphabet: Alphabet) -> impl Strategy<Value = String> {
+    let letter = prop::sample::select(alphabet.letters());
+    let character = prop_oneof![
+        6 => (letter, any::<bool>()).prop_map(|(c, upper)| if upper { c } else { c.to_lowercase().next().unwrap_or(c) }),
+        1 => prop::sample::select(&[' ', ',', '.', '!', '0', '7', '\n'][..]),
+    ];
+    prop::collection::vec(character, 0..200).prop_map(|chars| chars.into_iter().collect())
+}
+
+/// Upper-case letters of `alphabet` only, at least one
+pub fn letters(alphabet: Alphabet, max: usize) -> impl Strategy<Value = String> {
+    prop::collection::vec(prop::sample::select(alphabet.letters()), 1..max).prop_map(|chars| chars.into_iter().collect())
+}
+
+/// Either alphabet, for properties that hold over both
+pub fn alphabet() -> impl Strategy<Value = Alphabet> {
+    prop_oneof![Just(Alphabet::Latin), Just(Alphabet::Romanian)]
+}
+
+/// Arbitrary bytes, including none
+pub fn bytes(max: usize) -> impl Strategy<Value = Vec<u8>> {
+    prop::collection::vec(any::<u8>(), 0..max)
+}
+
+pub fn caesar_key() -> impl Strategy<Value = String> {
+    (0u8..26).prop_map(|shift| shift.to_string())
+}
+
+pub fn vigenere_key() -> impl Strategy<Value = String> {
+    "[A-Za-z]{1,12}"
+}
+
+/// `<rails>` or `<rails>,<offset>`
+pub fn railfence_key() -> impl Strategy<Value = String> {
+    (2usize..12, prop::option::of(0usize..30)).prop_map(|(rails, offset)| match offset {
+        Some(offset) => format!("{},{}", rails, offset),
+        None => rails.to_string(),
+    })
+}
+
+/// One keyword, or two for double transposition
+pub fn columnar_key() -> impl Strategy<Value = String> {
+    prop_oneof!["[A-Z]{2,10}", "[A-Z]{2,8} [A-Z]{2,8}"]
+}
+
+/// At least 7 letters, Romanian ones included
+pub fn playfair_key() -> impl Strategy<Value = String> {
+    letters(Alphabet::Romanian, 16).prop_filter("Playfair keys have at least 7 letters", |key| key.chars().count() >= 7)
+}
+
+/// A 2×2 or 3×3 matrix invertible modulo the alphabet size, as numbers
+pub fn hill_key(alphabet: Alphabet) -> impl Strategy<Value = String> {
+    let modulus = alphabet.size() as i64;
+    prop_oneof![Just(4usize), Just(9usize)]
+        .prop_flat_map(move |len| prop::collection::vec(0..modulus, len))
+        .prop_filter("the matrix must be invertible", move |values| {
+            let size = if values.len() == 4 { 2 } else { 3 };
+            let matrix: hill::Matrix = values.chunks(size).map(<[i64]>::to_vec).collect();
+            hill::inverse_matrix(&matrix, modulus).is_some()
+        })
+        .prop_map(|values| values.iter().map(i64::to_string).collect::<Vec<_>>().join(" "))
+        .prop_filter("the cipher must accept the key", move |key| Hill::with_alphabet(key, alphabet).is_ok())
+}
+
+/// A key of exactly `len` bytes
+pub fn block_key(len: usize) -> impl Strategy<Value = Vec<u8>> {
+    prop::collection::vec(any::<u8>(), len)
+}
+
+/// A DES or AES key, kept as bytes so failing cases print
+#[derive(Clone, Debug)]
+pub enum BlockKey {
+    Des(Vec<u8>),
+    Aes(Vec<u8>),
+}
+
+impl BlockKey {
+    pub fn cipher(&self) -> Box<dyn BlockCipher> {
+        match self {
+            BlockKey::Des(key) => Box::new(Des::new(key).unwrap()),
+            BlockKey::Aes(key) => Box::new(Aes128::new(key).unwrap()),
+        }
+    }
+}
+
+pub fn block_cipher_key() -> impl Strategy<Value = BlockKey> {
+    prop_oneof![block_key(8).prop_map(BlockKey::Des), block_key(16).prop_map(BlockKey::Aes)]
+}
+
+/// A change to a ciphertext that always makes it differ from the original
+#[derive(Clone, Debug)]
+pub enum Corruption {
+    /// Flip one bit, at a position taken modulo the length
+    FlipBit { position: usize, bit: u8 },
+    /// Drop this many bytes (at least one) from the end
+    Truncate(usize),
+    /// Append bytes
+    Extend(Vec<u8>),
+}
+
+impl Corruption {
+    pub fn apply(&self, ciphertext: &[u8]) -> Vec<u8> {
+        let mut corrupted = ciphertext.to_vec();
+        match self {
+            Corruption::FlipBit { .. } | Corruption::Truncate(_) if ciphertext.is_empty() => corrupted.push(0),
+            Corruption::FlipBit { position, bit } => corrupted[position % ciphertext.len()] ^= 1 << (bit % 8),
+            Corruption::Truncate(count) => corrupted.truncate(ciphertext.len() - 1 - count % ciphertext.len()),
+            Corruption::Extend(extra) => corrupted.extend(extra),
+        }
+        corrupted
+    }
+}
+
+pub fn corruption() -> impl Strategy<Value = Corruption> {
+    prop_oneof![
+        4 => (any::<usize>(), any::<u8>()).prop_map(|(position, bit)| Corruption::FlipBit { position, bit }),
+        1 => any::<usize>().prop_map(Corruption::Truncate),
+        1 => prop::collection::vec(any::<u8>(), 1..20).prop_map(Corruption::Extend),
+    ]
+}
@@ -0,0 +1,90 @@
+//! Round trips and key validation for the classical ciphers
+
+use conformance::*;
+use crypto_core::caesar::Caesar;
+use crypto_core::columnar::Columnar;
+use crypto_core::hill::Hill;
+use crypto_core::language::Alphabet;
+use crypto_core::railfence::RailFence;
+use crypto_core::vigenere::Vigenere;
+use crypto_core::ClassicalCipher;
+use playfair::Playfair;
+use proptest::prelude::*;
+
+fn round_trip(cipher: &dyn ClassicalCipher, text: &str) -> String {
+    cipher.decrypt(&cipher.encrypt(text).unwrap()).unwrap()
+}
+
+proptest! {
+    #[test]
+    fn caesar_round_trips_exactly(key in caesar_key(), text in message(Alphabet::Latin)) {
+        prop_assert_eq!(round_trip(&Caesar::new(&key).unwrap(), &text), text);
+    }
+
+    #[test]
+    fn caesar_keeps_everything_but_letters(key in caesar_key(), text in message(Alphabet::Latin)) {
+        let encrypted = Caesar::new(&key).unwrap().encrypt(&text).unwrap();
+        let unchanged = |c: char| !c.is_ascii_alphabetic();
+        prop_assert_eq!(encrypted.chars().filter(|&c| unchanged(c)).collect::<String>(), text.chars().filter(|&c| unchanged(c)).collect::<String>());
+    }
+
+    #[test]
+    fn vigenere_round_trips_exactly(key in vigenere_key(), text in message(Alphabet::Latin)) {
+        prop_assert_eq!(round_trip(&Vigenere::new(&key).unwrap(), &text), text);
+    }
+
+    #[test]
+    fn railfence_round_trips_any_text(key in railfence_key(), text in message(Alphabet::Romanian)) {
+        let cipher = RailFence::new(&key).unwrap();
+        let encrypted = cipher.encrypt(&text).unwrap();
+        prop_assert_eq!(encrypted.chars().count(), text.chars().count());
+        prop_assert_eq!(cipher.decrypt(&encrypted).unwrap(), text);
+    }
+
+    /// Transposition keeps the letters, upper-cased, and drops the rest
+    #[test]
+    fn columnar_round_trips_the_letters(key in columnar_key(), text in message(Alphabet::Romanian)) {
+        let letters: String = text.chars().filter(|c| c.is_alphabetic()).flat_map(char::to_uppercase).collect();
+        prop_assert_eq!(round_trip(&Columnar::new(&key).unwrap(), &text), letters);
+    }
+
+    /// Hill keeps the letters of its alphabet, upper-cased, padded with X
+    #[test]
+    fn hill_round_trips_the_letters(
+        (alphabet, key) in alphabet().prop_flat_map(|alphabet| (Just(alphabet), hill_key(alphabet))),
+        text in message(Alphabet::Romanian),
+    ) {
+        let cipher = Hill::with_alphabet(&key, alphabet).unwrap();
+        let letters: String = text.chars().filter_map(|c| alphabet.index(c)).map(|index| alphabet.letter(index)).collect();
+        let decrypted = round_trip(&cipher, &text);
+        prop_assert!(decrypted.starts_with(&letters));
+        prop_assert!(decrypted[letters.len()..].chars().all(|c| c == 'X'));
+        prop_assert!(decrypted.chars().count() - letters.chars().count() < key.split_whitespace().count());
+    }
+
+    /// Playfair upper-cases, merges J into I and pads an odd length with X
+    #[test]
+    fn playfair_round_trips_the_normalised_text(key in playfair_key(), text in letters(Alphabet::Romanian, 120)) {
+        let mut expected = text.to_uppercase().replace('J', "I");
+        if expected.chars().count() % 2 == 1 {
+            expected.push('X');
+        }
+        prop_assert_eq!(round_trip(&Playfair::new(&key).unwrap(), &text), expected);
+    }
+
+    #[test]
+    fn out_of_range_keys_are_rejected(shift in 26u32..1000, rails in 0usize..2, short in "[A-Z]{0,6}", digits in "[A-Z]*[0-9][A-Z0-9]*") {
+        prop_assert!(Caesar::new(&shift.to_string()).is_err());
+        prop_assert!(RailFence::new(&rails.to_string()).is_err());
+        prop_assert!(Playfair::new(&short).is_err());
+        prop_assert!(Vigenere::new(&digits).is_err());
+        prop_assert!(Columnar::new(&digits).is_err());
+    }
+
+    /// A matrix with a repeated row has determinant 0 and no inverse
+    #[test]
+    fn singular_hill_keys_are_rejected(alphabet in alphabet(), row in prop::collection::vec(0i64..26, 2)) {
+        let key = format!("{} {} {} {}", row[0], row[1], row[0], row[1]);
+        prop_assert!(Hill::with_alphabet(&key, alphabet).is_err());
+    }
+}
@@ -0,0 +1,42 @@
+//! Text encodings and secret sharing: round trips, and decoders that
+//! reject rather than panic on arbitrary input
+
+use conformance::*;
+use crypto_core::codec::{Encoding, Strictness};
+use crypto_core::shamir;
+use proptest::prelude::*;
+
+fn encoding() -> impl Strategy<Value = Encoding> {
+    prop_oneof![Just(Encoding::Hex), Just(Encoding::Base32), Just(Encoding::Base64)]
+}
+
+proptest! {
+    #[test]
+    fn encodings_round_trip(encoding in encoding(), data in bytes(200)) {
+        let text = encoding.encode(&data);
+        prop_assert_eq!(encoding.decode(&text, Strictness::Strict).unwrap(), data.clone());
+        prop_assert_eq!(encoding.decode(&text, Strictness::Lenient).unwrap(), data);
+    }
+
+    /// Strict decoding accepts only what encoding produces
+    #[test]
+    fn strict_decoding_accepts_only_canonical_text(encoding in encoding(), text in "\\PC{0,60}") {
+        if let Ok(data) = encoding.decode(&text, Strictness::Strict) {
+            prop_assert_eq!(encoding.encode(&data), text.clone());
+        }
+        let _ = encoding.decode(&text, Strictness::Lenient);
+    }
+
+    #[test]
+    fn any_threshold_of_shares_recovers_the_secret(
+        secret in bytes(64),
+        (threshold, count, chosen) in (1u8..=8, 0u8..=4).prop_flat_map(|(threshold, spare)| {
+            let count = threshold + spare;
+            (Just(threshold), Just(count), prop::sample::subsequence((0..count as usize).collect::<Vec<_>>(), threshold as usize))
+        }),
+    ) {
+        let shares = shamir::split(&secret, threshold, count).unwrap();
+        let chosen: Vec<_> = chosen.into_iter().map(|index| shares[index].clone()).collect();
+        prop_assert_eq!(shamir::combine(&chosen).unwrap(), secret);
+    }
+}
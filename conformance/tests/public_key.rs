@@ -0,0 +1,69 @@
+//! RSA and ECDSA: round trips, and rejection of altered ciphertexts,
+//! signatures and messages. Keys are generated once per suite.
+
+use std::sync::OnceLock;
+
+use conformance::*;
+use crypto_core::ecc::{Curve, EcdsaPrivateKey, EcdsaSignature};
+use crypto_core::rsa::RsaPrivateKey;
+use proptest::prelude::*;
+
+fn rsa_key() -> &'static RsaPrivateKey {
+    static KEY: OnceLock<RsaPrivateKey> = OnceLock::new();
+    KEY.get_or_init(|| RsaPrivateKey::generate(1024).unwrap())
+}
+
+fn ecdsa_key() -> &'static EcdsaPrivateKey {
+    static KEY: OnceLock<EcdsaPrivateKey> = OnceLock::new();
+    KEY.get_or_init(|| EcdsaPrivateKey::generate(Curve::p256()).unwrap())
+}
+
+/// What OAEP with SHA-256 fits under a 1024-bit key
+const OAEP_CAPACITY: usize = 128 - 2 * 32 - 2;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(24))]
+
+    #[test]
+    fn rsa_oaep_round_trips(message in bytes(OAEP_CAPACITY + 1)) {
+        let key = rsa_key();
+        let ciphertext = key.public().encrypt_oaep(&message).unwrap();
+        prop_assert_eq!(key.decrypt_oaep(&ciphertext).unwrap(), message);
+    }
+
+    #[test]
+    fn rsa_oaep_rejects_altered_ciphertexts(message in bytes(OAEP_CAPACITY + 1), corruption in corruption()) {
+        let key = rsa_key();
+        let ciphertext = key.public().encrypt_oaep(&message).unwrap();
+        prop_assert!(key.decrypt_oaep(&corruption.apply(&ciphertext)).is_err());
+    }
+
+    #[test]
+    fn rsa_oaep_refuses_messages_that_do_not_fit(message in prop::collection::vec(any::<u8>(), OAEP_CAPACITY + 1..200)) {
+        prop_assert!(rsa_key().public().encrypt_oaep(&message).is_err());
+    }
+
+    #[test]
+    fn rsa_signatures_verify_only_unaltered(message in bytes(200), other in bytes(200), corruption in corruption()) {
+        let key = rsa_key();
+        let signature = key.sign(&message);
+        prop_assert!(key.public().verify(&message, &signature));
+        prop_assert!(!key.public().verify(&message, &corruption.apply(&signature)));
+        prop_assert_eq!(key.public().verify(&other, &signature), other == message);
+    }
+}
+
+proptest! {
+    // P-256 arithmetic is slow in debug builds
+    #![proptest_config(ProptestConfig::with_cases(6))]
+
+    #[test]
+    fn ecdsa_signatures_verify_only_unaltered(message in bytes(200), other in bytes(200), tweak in 1u32..1000) {
+        let key = ecdsa_key();
+        let signature = key.sign(&message).unwrap();
+        prop_assert!(key.public().verify(&message, &signature));
+        prop_assert_eq!(key.public().verify(&other, &signature), other == message);
+        let altered = EcdsaSignature { r: signature.r.clone(), s: &signature.s + tweak };
+        prop_assert!(!key.public().verify(&message, &altered));
+    }
+}
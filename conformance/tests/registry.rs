@@ -0,0 +1,47 @@
+//! Every registered cipher, plugins included, under its example key: round
+//! trips and the capabilities it claims
+
+use conformance::*;
+use crypto_core::language::Alphabet;
+use proptest::prelude::*;
+
+proptest! {
+    /// Exact round trips where claimed; otherwise the normalised text that
+    /// comes back must itself round-trip exactly
+    #[test]
+    fn classical_ciphers_round_trip_as_claimed(text in letters(Alphabet::Latin, 120)) {
+        for registration in registry().iter().filter(|registration| registration.is_classical()) {
+            let cipher = registration.classical_cipher(registration.example_key).unwrap();
+            let encrypted = cipher.encrypt(&text).unwrap();
+            let decrypted = cipher.decrypt(&encrypted).unwrap();
+            if registration.capabilities.exact_round_trip {
+                prop_assert_eq!(&decrypted, &text, "{}", registration.name());
+            }
+            prop_assert_eq!(cipher.decrypt(&cipher.encrypt(&decrypted).unwrap()).unwrap(), decrypted.clone(), "{}", registration.name());
+            if registration.capabilities.deterministic {
+                prop_assert_eq!(cipher.encrypt(&text).unwrap(), encrypted, "{}", registration.name());
+            }
+        }
+    }
+
+    #[test]
+    fn symmetric_ciphers_round_trip_as_claimed(plaintext in bytes(300)) {
+        for registration in registry().iter().filter(|registration| !registration.is_classical()) {
+            let cipher = registration.symmetric_cipher(registration.example_key.as_bytes()).unwrap();
+            let ciphertext = cipher.encrypt(&plaintext);
+            prop_assert_eq!(cipher.decrypt(&ciphertext).unwrap(), plaintext.clone(), "{}", registration.name());
+            prop_assert_eq!(cipher.encrypt(&plaintext) == ciphertext, registration.capabilities.deterministic, "{}", registration.name());
+        }
+    }
+}
+
+#[test]
+fn registrations_describe_their_ciphers() {
+    let registry = registry();
+    for registration in registry.iter() {
+        assert!(!registration.info.key_description.is_empty(), "{}", registration.name());
+        // Block sizes are only claimed by byte ciphers
+        assert!(registration.info.block_size.is_none() || !registration.is_classical(), "{}", registration.name());
+    }
+    assert_eq!(registry.get("affine").unwrap().origin, "student-ciphers");
+}
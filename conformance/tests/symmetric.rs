@@ -0,0 +1,102 @@
+//! Round trips through every block mode and stream cipher, and rejection
+//! of altered ciphertexts
+
+use conformance::*;
+use crypto_core::aes::Aes128;
+use crypto_core::chacha20::{ChaCha20, ChaCha20Poly1305};
+use crypto_core::modes::{self, Mode};
+use crypto_core::rc4::Rc4;
+use crypto_core::SymmetricCipher;
+use des::Des;
+use proptest::prelude::*;
+
+fn mode() -> impl Strategy<Value = Mode> {
+    prop_oneof![Just(Mode::Ecb), Just(Mode::Cbc), Just(Mode::Ctr)]
+}
+
+proptest! {
+    #[test]
+    fn block_modes_round_trip(key in block_cipher_key(), mode in mode(), iv in block_key(16), plaintext in bytes(300)) {
+        let cipher = key.cipher();
+        let iv = &iv[..cipher.block_size()];
+        let ciphertext = modes::encrypt(cipher.as_ref(), mode, iv, &plaintext).unwrap();
+        if mode != Mode::Ctr {
+            prop_assert_eq!(ciphertext.len() % cipher.block_size(), 0);
+            prop_assert!(ciphertext.len() > plaintext.len());
+        }
+        prop_assert_eq!(modes::decrypt(cipher.as_ref(), mode, iv, &ciphertext).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn symmetric_interface_round_trips(des_key in block_key(8), aes_key in block_key(16), rc4_key in bytes(64), plaintext in bytes(300)) {
+        let des = Des::new(&des_key).unwrap();
+        let aes = Aes128::new(&aes_key).unwrap();
+        let chacha20 = ChaCha20::new(&[aes_key.clone(), aes_key].concat()).unwrap();
+        prop_assert_eq!(des.decrypt(&des.encrypt(&plaintext)).unwrap(), plaintext.clone());
+        prop_assert_eq!(aes.decrypt(&aes.encrypt(&plaintext)).unwrap(), plaintext.clone());
+        prop_assert_eq!(chacha20.decrypt(&chacha20.encrypt(&plaintext)).unwrap(), plaintext.clone());
+        if let Ok(rc4) = Rc4::new(&rc4_key) {
+            prop_assert_eq!(rc4.decrypt(&rc4.encrypt(&plaintext)).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn keys_of_the_wrong_length_are_rejected(key in bytes(40)) {
+        prop_assert_eq!(Des::new(&key).is_ok(), key.len() == 8);
+        prop_assert_eq!(Aes128::new(&key).is_ok(), key.len() == 16);
+        prop_assert_eq!(ChaCha20::new(&key).is_ok(), key.len() == 32);
+    }
+
+    /// Any change to an encrypt-then-MAC message fails the tag check
+    #[test]
+    fn altered_authenticated_messages_are_rejected(
+        key in block_cipher_key(),
+        mode in mode(),
+        mac_key in bytes(32),
+        plaintext in bytes(100),
+        corruption in corruption(),
+    ) {
+        let cipher = key.cipher();
+        let iv = vec![7; cipher.block_size()];
+        let sealed = modes::encrypt_then_mac(cipher.as_ref(), mode, &iv, &mac_key, &plaintext).unwrap();
+        prop_assert_eq!(modes::decrypt_verified(cipher.as_ref(), mode, &mac_key, &sealed).unwrap(), plaintext);
+        prop_assert!(modes::decrypt_verified(cipher.as_ref(), mode, &mac_key, &corruption.apply(&sealed)).is_err());
+    }
+
+    /// The same for the ChaCha20-Poly1305 AEAD, with or without associated data
+    #[test]
+    fn altered_aead_messages_are_rejected(key in block_key(32), associated in bytes(40), plaintext in bytes(100), corruption in corruption()) {
+        let aead = ChaCha20Poly1305::new(&key).unwrap();
+        let nonce = [9; 12];
+        let sealed = aead.seal(&nonce, &associated, &plaintext);
+        prop_assert_eq!(aead.open(&nonce, &associated, &sealed).unwrap(), plaintext);
+        prop_assert!(aead.open(&nonce, &associated, &corruption.apply(&sealed)).is_err());
+    }
+
+    /// Unauthenticated decryption of altered ciphertext may fail or give
+    /// other bytes, but never the plaintext and never a panic
+    #[test]
+    fn altered_ciphertexts_never_decrypt_to_the_plaintext(
+        key in block_cipher_key(),
+        mode in mode(),
+        plaintext in bytes(100),
+        corruption in corruption(),
+    ) {
+        let cipher = key.cipher();
+        let iv = vec![3; cipher.block_size()];
+        let ciphertext = modes::encrypt(cipher.as_ref(), mode, &iv, &plaintext).unwrap();
+        if let Ok(decrypted) = modes::decrypt(cipher.as_ref(), mode, &iv, &corruption.apply(&ciphertext)) {
+            prop_assert_ne!(decrypted, plaintext);
+        }
+    }
+
+    /// Unpadding any whole number of blocks either fails or undoes padding
+    #[test]
+    fn padding_removal_never_panics((block_size, data) in (1usize..=16, 1usize..5).prop_flat_map(|(block_size, blocks)| {
+        (Just(block_size), prop::collection::vec(any::<u8>(), block_size * blocks))
+    })) {
+        if let Ok(unpadded) = modes::unpad_pkcs7(&data, block_size) {
+            prop_assert_eq!(modes::pad_pkcs7(&unpadded, block_size), data);
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! AES-128 from first principles (FIPS-197): the S-box is derived from
+//! inversion in GF(2⁸), and each round is SubBytes, ShiftRows, MixColumns
+//! and AddRoundKey on a 4×4 byte state
+
+use std::sync::OnceLock;
+
+use crate::cipher::{BlockCipher, CipherError, CipherFamily, CipherInfo, SymmetricCipher};
+use crate::modes::{self, Mode};
+
+/// The state, as the 16 input bytes filling its columns one after another
+pub type Block = [u8; 16];
+
+const ROUNDS: usize = 10;
+
+/// AES with a 128-bit key. As a [`SymmetricCipher`] it uses ECB with PKCS#7
+/// padding like DES; [`crate::modes`] provides the others.
+pub struct Aes128 {
+    round_keys: [Block; ROUNDS + 1],
+}
+
+/// Multiplication by x in GF(2⁸) modulo x⁸ + x⁴ + x³ + x + 1
+fn xtime(a: u8) -> u8 {
+    (a << 1) ^ if a & 0x80 != 0 { 0x1b } else { 0 }
+}
+
+/// Multiplication in GF(2⁸), shifting and adding
+pub fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        a = xtime(a);
+        b >>= 1;
+    }
+    product
+}
+
+/// Multiplicative inverse in GF(2⁸) as a²⁵⁴, with 0 mapped to 0
+pub fn gf_inverse(a: u8) -> u8 {
+    (0..254).fold(1, |power, _| gf_mul(power, a))
+}
+
+/// The S-box and its inverse: the GF(2⁸) inverse followed by the affine map
+/// b ⊕ (b ⋘ 1) ⊕ (b ⋘ 2) ⊕ (b ⋘ 3) ⊕ (b ⋘ 4) ⊕ 0x63
+fn s_boxes() -> &'static ([u8; 256], [u8; 256]) {
+    static S_BOXES: OnceLock<([u8; 256], [u8; 256])> = OnceLock::new();
+    S_BOXES.get_or_init(|| {
+        let mut s_box = [0; 256];
+        let mut inverse = [0; 256];
+        for value in 0..=255u8 {
+            let b = gf_inverse(value);
+            let substituted = b ^ b.rotate_left(1) ^ b.rotate_left(2) ^ b.rotate_left(3) ^ b.rotate_left(4) ^ 0x63;
+            s_box[value as usize] = substituted;
+            inverse[substituted as usize] = value;
+        }
+        (s_box, inverse)
+    })
+}
+
+pub fn s_box(value: u8) -> u8 {
+    s_boxes().0[value as usize]
+}
+
+fn sub_bytes(state: &mut Block) {
+    state.iter_mut().for_each(|byte| *byte = s_box(*byte));
+}
+
+fn inv_sub_bytes(state: &mut Block) {
+    state.iter_mut().for_each(|byte| *byte = s_boxes().1[*byte as usize]);
+}
+
+/// Rotate row r left by r positions; byte `4c + r` is row r of column c
+fn shift_rows(state: &mut Block) {
+    let original = *state;
+    for (index, byte) in state.iter_mut().enumerate() {
+        let (row, column) = (index % 4, index / 4);
+        *byte = original[(column + row) % 4 * 4 + row];
+    }
+}
+
+fn inv_shift_rows(state: &mut Block) {
+    let original = *state;
+    for (index, byte) in state.iter_mut().enumerate() {
+        let (row, column) = (index % 4, index / 4);
+        *byte = original[(column + 4 - row) % 4 * 4 + row];
+    }
+}
+
+/// Multiply each column by the matrix with rows `coefficients` rotated right
+fn mix_with(state: &mut Block, coefficients: [u8; 4]) {
+    for column in state.chunks_exact_mut(4) {
+        let original = [column[0], column[1], column[2], column[3]];
+        for (row, byte) in column.iter_mut().enumerate() {
+            *byte = (0..4).fold(0, |sum, i| sum ^ gf_mul(coefficients[(i + 4 - row) % 4], original[i]));
+        }
+    }
+}
+
+fn mix_columns(state: &mut Block) {
+    mix_with(state, [0x02, 0x03, 0x01, 0x01]);
+}
+
+fn inv_mix_columns(state: &mut Block) {
+    mix_with(state, [0x0e, 0x0b, 0x0d, 0x09]);
+}
+
+fn add_round_key(state: &mut Block, round_key: &Block) {
+    state.iter_mut().zip(round_key).for_each(|(byte, key)| *byte ^= key);
+}
+
+/// Key expansion into 44 words: each new word is the one four back XORed with
+/// the previous word, which every fourth word is first rotated, substituted
+/// and XORed with a round constant
+fn expand_key(key: &Block) -> [Block; ROUNDS + 1] {
+    let mut words: Vec<[u8; 4]> = key.chunks_exact(4).map(|word| [word[0], word[1], word[2], word[3]]).collect();
+    let mut round_constant = 1u8;
+    for i in 4..4 * (ROUNDS + 1) {
+        let mut word = words[i - 1];
+        if i % 4 == 0 {
+            word.rotate_left(1);
+            word = word.map(s_box);
+            word[0] ^= round_constant;
+            round_constant = xtime(round_constant);
+        }
+        words.push([0, 1, 2, 3].map(|byte| words[i - 4][byte] ^ word[byte]));
+    }
+
+    let mut round_keys = [[0; 16]; ROUNDS + 1];
+    for (round_key, round_words) in round_keys.iter_mut().zip(words.chunks_exact(4)) {
+        for (bytes, word) in round_key.chunks_exact_mut(4).zip(round_words) {
+            bytes.copy_from_slice(word);
+        }
+    }
+    round_keys
+}
+
+impl Aes128 {
+    /// Round keys 0 (the key itself) to 10
+    pub fn round_keys(&self) -> &[Block; ROUNDS + 1] {
+        &self.round_keys
+    }
+
+    pub fn encrypt_state(&self, state: &mut Block) {
+        add_round_key(state, &self.round_keys[0]);
+        for round in 1..=ROUNDS {
+            sub_bytes(state);
+            shift_rows(state);
+            // The last round leaves out MixColumns
+            if round != ROUNDS {
+                mix_columns(state);
+            }
+            add_round_key(state, &self.round_keys[round]);
+        }
+    }
+
+    pub fn decrypt_state(&self, state: &mut Block) {
+        add_round_key(state, &self.round_keys[ROUNDS]);
+        for round in (0..ROUNDS).rev() {
+            inv_shift_rows(state);
+            inv_sub_bytes(state);
+            add_round_key(state, &self.round_keys[round]);
+            if round != 0 {
+                inv_mix_columns(state);
+            }
+        }
+    }
+}
+
+impl BlockCipher for Aes128 {
+    fn block_size(&self) -> usize {
+        16
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let mut state: Block = block.try_into().expect("AES blocks are 16 bytes");
+        self.encrypt_state(&mut state);
+        block.copy_from_slice(&state);
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let mut state: Block = block.try_into().expect("AES blocks are 16 bytes");
+        self.decrypt_state(&mut state);
+        block.copy_from_slice(&state);
+    }
+}
+
+impl SymmetricCipher for Aes128 {
+    fn validate_key(key: &[u8]) -> Result<(), CipherError> {
+        if key.len() == 16 {
+            Ok(())
+        } else {
+            Err(CipherError::InvalidKey(format!("{} (got {} bytes)", INFO.key_description, key.len())))
+        }
+    }
+
+    fn new(key: &[u8]) -> Result<Self, CipherError> {
+        <Self as SymmetricCipher>::validate_key(key)?;
+        let key: Block = key.try_into().map_err(|_| CipherError::InvalidKey(INFO.key_description.to_string()))?;
+        Ok(Aes128 { round_keys: expand_key(&key) })
+    }
+
+    fn info(&self) -> CipherInfo {
+        INFO
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        modes::encrypt(self, Mode::Ecb, &[], plaintext).unwrap_or_default()
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, CipherError> {
+        modes::decrypt(self, Mode::Ecb, &[], ciphertext)
+    }
+}
+
+const INFO: CipherInfo = CipherInfo {
+    name: "aes",
+    family: CipherFamily::Block,
+    key_description: "16 bytes",
+    block_size: Some(16),
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len()).step_by(2).map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap()).collect()
+    }
+
+    #[test]
+    fn building_blocks_match_fips_197() {
+        // Section 4.2 and the S-box table in figure 7
+        assert_eq!(gf_mul(0x57, 0x83), 0xc1);
+        assert_eq!(gf_mul(0x57, 0x13), 0xfe);
+        assert_eq!((s_box(0x00), s_box(0x53), s_box(0xff)), (0x63, 0xed, 0x16));
+
+        // Appendix A.1: the last word of the expanded key
+        let aes = Aes128::new(&hex("2b7e151628aed2a6abf7158809cf4f3c")).unwrap();
+        assert_eq!(&aes.round_keys()[10][12..], hex("b6630ca6"));
+    }
+
+    #[test]
+    fn encrypts_the_fips_197_examples() {
+        // Appendix B and appendix C.1
+        for (key, plaintext, ciphertext) in [
+            ("2b7e151628aed2a6abf7158809cf4f3c", "3243f6a8885a308d313198a2e0370734", "3925841d02dc09fbdc118597196a0b32"),
+            ("000102030405060708090a0b0c0d0e0f", "00112233445566778899aabbccddeeff", "69c4e0d86a7b0430d8cdb78070b4c55a"),
+        ] {
+            let aes = Aes128::new(&hex(key)).unwrap();
+            let mut block = hex(plaintext);
+            aes.encrypt_block(&mut block);
+            assert_eq!(block, hex(ciphertext));
+            aes.decrypt_block(&mut block);
+            assert_eq!(block, hex(plaintext));
+        }
+    }
+
+    #[test]
+    fn modes_match_sp_800_38a() {
+        let aes = Aes128::new(&hex("2b7e151628aed2a6abf7158809cf4f3c")).unwrap();
+        let plaintext = hex("6bc1bee22e409f96e93d7e117393172a");
+        let cases = [
+            (Mode::Ecb, "", "3ad77bb40d7a3660a89ecaf32466ef97"),
+            (Mode::Cbc, "000102030405060708090a0b0c0d0e0f", "7649abac8119b246cee98e9b12e9197d"),
+            (Mode::Ctr, "f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff", "874d6191b620e3261bef6864990db6ce"),
+        ];
+        for (mode, iv, ciphertext) in cases {
+            let encrypted = modes::encrypt(&aes, mode, &hex(iv), &plaintext).unwrap();
+            // ECB and CBC add a block of padding after the full block
+            assert_eq!(encrypted[..16], hex(ciphertext));
+            assert_eq!(modes::decrypt(&aes, mode, &hex(iv), &encrypted).unwrap(), plaintext);
+        }
+    }
+}
@@ -0,0 +1,370 @@
+//! Argon2id (RFC 9106), the memory-hard password hash that won the Password
+//! Hashing Competition, on top of BLAKE2b (RFC 7693). Memory is filled with
+//! 1 KiB blocks, each mixed from the previous one and an earlier one chosen
+//! independently of the password in the first half of the first pass (no
+//! cache-timing leak) and by the data afterwards (no cheap time–memory
+//! trade-off).
+
+/// Argon2 version 1.3
+const VERSION: u32 = 0x13;
+/// The type field for Argon2id
+const ARGON2ID: u32 = 2;
+/// 64-bit words in a 1024-byte block
+const BLOCK_WORDS: usize = 128;
+/// Slices per pass; lanes synchronize at each
+const SYNC_POINTS: usize = 4;
+
+const BLAKE2B_IV: [u64; 8] = [
+    0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+    0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// BLAKE2b of `data` with an `out_len`-byte digest (1 to 64), unkeyed
+pub fn blake2b(data: &[u8], out_len: usize) -> Vec<u8> {
+    assert!((1..=64).contains(&out_len), "BLAKE2b digests are 1 to 64 bytes");
+    let mut h = BLAKE2B_IV;
+    h[0] ^= 0x0101_0000 ^ out_len as u64;
+
+    let mut offset = 0;
+    // The last block, even an empty one, is compressed with the final flag
+    while data.len() - offset > 128 {
+        offset += 128;
+        blake2b_compress(&mut h, &data[offset - 128..offset], offset as u128, false);
+    }
+    let mut last = [0; 128];
+    last[..data.len() - offset].copy_from_slice(&data[offset..]);
+    blake2b_compress(&mut h, &last, data.len() as u128, true);
+
+    h.iter().flat_map(|word| word.to_le_bytes()).take(out_len).collect()
+}
+
+fn blake2b_compress(h: &mut [u64; 8], block: &[u8], counter: u128, last: bool) {
+    let m: Vec<u64> = block.chunks(8).map(|word| u64::from_le_bytes(word.try_into().expect("8 bytes"))).collect();
+    let mut v = [0; 16];
+    v[..8].copy_from_slice(h);
+    v[8..].copy_from_slice(&BLAKE2B_IV);
+    v[12] ^= counter as u64;
+    v[13] ^= (counter >> 64) as u64;
+    if last {
+        v[14] = !v[14];
+    }
+
+    let mut mix = |a: usize, b: usize, c: usize, d: usize, x: u64, y: u64| {
+        v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+        v[d] = (v[d] ^ v[a]).rotate_right(32);
+        v[c] = v[c].wrapping_add(v[d]);
+        v[b] = (v[b] ^ v[c]).rotate_right(24);
+        v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+        v[d] = (v[d] ^ v[a]).rotate_right(16);
+        v[c] = v[c].wrapping_add(v[d]);
+        v[b] = (v[b] ^ v[c]).rotate_right(63);
+    };
+    for round in 0..12 {
+        let s = &SIGMA[round % 10];
+        mix(0, 4, 8, 12, m[s[0]], m[s[1]]);
+        mix(1, 5, 9, 13, m[s[2]], m[s[3]]);
+        mix(2, 6, 10, 14, m[s[4]], m[s[5]]);
+        mix(3, 7, 11, 15, m[s[6]], m[s[7]]);
+        mix(0, 5, 10, 15, m[s[8]], m[s[9]]);
+        mix(1, 6, 11, 12, m[s[10]], m[s[11]]);
+        mix(2, 7, 8, 13, m[s[12]], m[s[13]]);
+        mix(3, 4, 9, 14, m[s[14]], m[s[15]]);
+    }
+
+    for (i, word) in h.iter_mut().enumerate() {
+        *word ^= v[i] ^ v[i + 8];
+    }
+}
+
+/// H′, BLAKE2b stretched to any length: chained 64-byte digests of which
+/// all but the last contribute their first half
+fn blake2b_long(input: &[&[u8]], out_len: usize) -> Vec<u8> {
+    let mut data = (out_len as u32).to_le_bytes().to_vec();
+    for part in input {
+        data.extend_from_slice(part);
+    }
+    if out_len <= 64 {
+        return blake2b(&data, out_len);
+    }
+
+    // V1 … Vr give their first halves, then V(r+1) is as long as needed
+    let r = out_len.div_ceil(32) - 2;
+    let mut output = Vec::with_capacity(out_len);
+    let mut digest = blake2b(&data, 64);
+    for _ in 1..r {
+        output.extend_from_slice(&digest[..32]);
+        digest = blake2b(&digest, 64);
+    }
+    output.extend_from_slice(&digest[..32]);
+    output.extend(blake2b(&digest, out_len - 32 * r));
+    output
+}
+
+/// Cost parameters: memory in KiB, passes over it, and independent lanes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Argon2id {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub lanes: u32,
+}
+
+type Block = [u64; BLOCK_WORDS];
+
+impl Argon2id {
+    /// 64 MiB, three passes, one lane: RFC 9106's second recommended option
+    /// scaled down for a teaching machine
+    pub const DEFAULT: Argon2id = Argon2id { memory_kib: 64 * 1024, iterations: 3, lanes: 1 };
+
+    /// A `length`-byte key from `password` and `salt`
+    pub fn derive(&self, password: &[u8], salt: &[u8], length: usize) -> Vec<u8> {
+        self.hash(password, salt, &[], &[], length)
+    }
+
+    /// Argon2id with the optional secret `key` and `associated_data`
+    fn hash(&self, password: &[u8], salt: &[u8], key: &[u8], associated_data: &[u8], length: usize) -> Vec<u8> {
+        let lanes = self.lanes.max(1) as usize;
+        let passes = self.iterations.max(1) as usize;
+        // At least 8 blocks per lane, rounded down to whole segments
+        let memory_blocks = (self.memory_kib as usize).max(8 * lanes) / (SYNC_POINTS * lanes) * (SYNC_POINTS * lanes);
+        let lane_length = memory_blocks / lanes;
+        let segment_length = lane_length / SYNC_POINTS;
+
+        let mut h0_input = Vec::new();
+        for value in [lanes as u32, length as u32, self.memory_kib, passes as u32, VERSION, ARGON2ID] {
+            h0_input.extend_from_slice(&value.to_le_bytes());
+        }
+        for part in [password, salt, key, associated_data] {
+            h0_input.extend_from_slice(&(part.len() as u32).to_le_bytes());
+            h0_input.extend_from_slice(part);
+        }
+        let h0 = blake2b(&h0_input, 64);
+
+        let mut memory = vec![[0u64; BLOCK_WORDS]; memory_blocks];
+        for lane in 0..lanes {
+            for column in 0..2 {
+                let bytes = blake2b_long(&[&h0, &(column as u32).to_le_bytes(), &(lane as u32).to_le_bytes()], 1024);
+                memory[lane * lane_length + column] = block_from_bytes(&bytes);
+            }
+        }
+
+        for pass in 0..passes {
+            for slice in 0..SYNC_POINTS {
+                for lane in 0..lanes {
+                    let position = Position { pass, lane, slice };
+                    self.fill_segment(&mut memory, position, Geometry { lanes, lane_length, segment_length, memory_blocks, passes });
+                }
+            }
+        }
+
+        let mut last = [0u64; BLOCK_WORDS];
+        for lane in 0..lanes {
+            xor_into(&mut last, &memory[lane * lane_length + lane_length - 1]);
+        }
+        let bytes: Vec<u8> = last.iter().flat_map(|word| word.to_le_bytes()).collect();
+        blake2b_long(&[&bytes], length)
+    }
+
+    fn fill_segment(&self, memory: &mut [Block], at: Position, geometry: Geometry) {
+        let Geometry { lanes, lane_length, segment_length, memory_blocks, passes } = geometry;
+        // Argon2id addresses independently of the data in the first half of
+        // the first pass
+        let independent = at.pass == 0 && at.slice < SYNC_POINTS / 2;
+        let mut address_input = [0u64; BLOCK_WORDS];
+        let mut addresses = [0u64; BLOCK_WORDS];
+        if independent {
+            address_input[..6].copy_from_slice(&[
+                at.pass as u64,
+                at.lane as u64,
+                at.slice as u64,
+                memory_blocks as u64,
+                passes as u64,
+                ARGON2ID as u64,
+            ]);
+        }
+
+        // The first two blocks of each lane are already filled
+        let start = if at.pass == 0 && at.slice == 0 { 2 } else { 0 };
+        if independent && start == 2 {
+            next_addresses(&mut address_input, &mut addresses);
+        }
+
+        for index in start..segment_length {
+            let column = at.slice * segment_length + index;
+            let current = at.lane * lane_length + column;
+            let previous = if column == 0 { current + lane_length - 1 } else { current - 1 };
+
+            let pseudo_random = if independent {
+                if index % BLOCK_WORDS == 0 {
+                    next_addresses(&mut address_input, &mut addresses);
+                }
+                addresses[index % BLOCK_WORDS]
+            } else {
+                memory[previous][0]
+            };
+
+            let reference_lane = if at.pass == 0 && at.slice == 0 { at.lane } else { (pseudo_random >> 32) as usize % lanes };
+            let same_lane = reference_lane == at.lane;
+            let reference_column = reference_index(at, index, same_lane, pseudo_random as u32, lane_length, segment_length);
+            let reference = reference_lane * lane_length + reference_column;
+
+            let mut block = compress(&memory[previous], &memory[reference]);
+            // Version 1.3 XORs later passes into the old block
+            if at.pass > 0 {
+                xor_into(&mut block, &memory[current]);
+            }
+            memory[current] = block;
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Position {
+    pass: usize,
+    lane: usize,
+    slice: usize,
+}
+
+#[derive(Clone, Copy)]
+struct Geometry {
+    lanes: usize,
+    lane_length: usize,
+    segment_length: usize,
+    memory_blocks: usize,
+    passes: usize,
+}
+
+/// Column of the reference block: a non-uniform pick, weighted towards
+/// recent blocks, among those already filled and not being filled now
+fn reference_index(at: Position, index: usize, same_lane: bool, pseudo_random: u32, lane_length: usize, segment_length: usize) -> usize {
+    let area_size = match (at.pass, same_lane) {
+        (0, _) if at.slice == 0 => index - 1,
+        (0, true) => at.slice * segment_length + index - 1,
+        (0, false) => at.slice * segment_length - usize::from(index == 0),
+        (_, true) => lane_length - segment_length + index - 1,
+        (_, false) => lane_length - segment_length - usize::from(index == 0),
+    } as u64;
+
+    let x = (pseudo_random as u64 * pseudo_random as u64) >> 32;
+    let relative = area_size - 1 - ((area_size * x) >> 32);
+    let start = if at.pass == 0 || at.slice == SYNC_POINTS - 1 { 0 } else { (at.slice + 1) * segment_length };
+    (start + relative as usize) % lane_length
+}
+
+/// The next block of pseudo-random addresses: G(0, G(0, input)) after
+/// bumping the input's counter
+fn next_addresses(input: &mut Block, addresses: &mut Block) {
+    input[6] += 1;
+    let zero = [0u64; BLOCK_WORDS];
+    *addresses = compress(&zero, &compress(&zero, input));
+}
+
+/// The compression function G: the BLAKE2b-based permutation P over the
+/// rows, then the columns, of X ⊕ Y, XORed with X ⊕ Y again
+fn compress(x: &Block, y: &Block) -> Block {
+    let mut r = *x;
+    xor_into(&mut r, y);
+    let mut z = r;
+
+    for row in 0..8 {
+        let mut v: [usize; 16] = [0; 16];
+        for (i, index) in v.iter_mut().enumerate() {
+            *index = 16 * row + i;
+        }
+        permute(&mut z, v);
+    }
+    for column in 0..8 {
+        let mut v: [usize; 16] = [0; 16];
+        for (i, index) in v.iter_mut().enumerate() {
+            *index = 16 * (i / 2) + 2 * column + i % 2;
+        }
+        permute(&mut z, v);
+    }
+
+    xor_into(&mut z, &r);
+    z
+}
+
+/// P on the 16 words of `block` at `v`
+fn permute(block: &mut Block, v: [usize; 16]) {
+    let mut mix = |a: usize, b: usize, c: usize, d: usize| {
+        let (a, b, c, d) = (v[a], v[b], v[c], v[d]);
+        block[a] = block[a].wrapping_add(block[b]).wrapping_add(2u64.wrapping_mul(lower(block[a])).wrapping_mul(lower(block[b])));
+        block[d] = (block[d] ^ block[a]).rotate_right(32);
+        block[c] = block[c].wrapping_add(block[d]).wrapping_add(2u64.wrapping_mul(lower(block[c])).wrapping_mul(lower(block[d])));
+        block[b] = (block[b] ^ block[c]).rotate_right(24);
+        block[a] = block[a].wrapping_add(block[b]).wrapping_add(2u64.wrapping_mul(lower(block[a])).wrapping_mul(lower(block[b])));
+        block[d] = (block[d] ^ block[a]).rotate_right(16);
+        block[c] = block[c].wrapping_add(block[d]).wrapping_add(2u64.wrapping_mul(lower(block[c])).wrapping_mul(lower(block[d])));
+        block[b] = (block[b] ^ block[c]).rotate_right(63);
+    };
+    mix(0, 4, 8, 12);
+    mix(1, 5, 9, 13);
+    mix(2, 6, 10, 14);
+    mix(3, 7, 11, 15);
+    mix(0, 5, 10, 15);
+    mix(1, 6, 11, 12);
+    mix(2, 7, 8, 13);
+    mix(3, 4, 9, 14);
+}
+
+fn lower(word: u64) -> u64 {
+    word & 0xFFFF_FFFF
+}
+
+fn xor_into(block: &mut Block, other: &Block) {
+    for (word, other) in block.iter_mut().zip(other) {
+        *word ^= other;
+    }
+}
+
+fn block_from_bytes(bytes: &[u8]) -> Block {
+    let mut block = [0u64; BLOCK_WORDS];
+    for (word, chunk) in block.iter_mut().zip(bytes.chunks(8)) {
+        *word = u64::from_le_bytes(chunk.try_into().expect("8 bytes"));
+    }
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::encode_hex;
+
+    #[test]
+    fn blake2b_matches_rfc_7693() {
+        assert_eq!(
+            encode_hex(&blake2b(b"abc", 64)),
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+             7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        );
+        assert_eq!(encode_hex(&blake2b(b"", 32)), "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8");
+    }
+
+    #[test]
+    fn argon2id_matches_rfc_9106() {
+        let params = Argon2id { memory_kib: 32, iterations: 3, lanes: 4 };
+        let tag = params.hash(&[1; 32], &[2; 16], &[3; 8], &[4; 12], 32);
+        assert_eq!(encode_hex(&tag), "0d640df58d78766c08c037a34a8b53c9d01ef0452d75b65eb52520e96b01e659");
+    }
+
+    #[test]
+    fn longer_outputs_and_salts_change_the_key() {
+        let params = Argon2id { memory_kib: 64, iterations: 1, lanes: 1 };
+        let long = params.derive(b"password", b"somesalt", 100);
+        assert_eq!(long.len(), 100);
+        assert_ne!(params.derive(b"password", b"othersalt", 32), params.derive(b"password", b"somesalt", 32));
+    }
+}
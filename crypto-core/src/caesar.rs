@@ -0,0 +1,121 @@
+//! Caesar (shift) cipher: every letter moves a fixed number of places
+
+use crate::cipher::{CipherError, CipherFamily, CipherInfo, ClassicalCipher};
+use crate::language::{quadgram_score, Language};
+
+/// Shifts A–Z by `shift`, keeping case; other characters pass through
+pub struct Caesar {
+    shift: u8,
+}
+
+impl Caesar {
+    pub fn with_shift(shift: u8) -> Self {
+        Caesar { shift: shift % 26 }
+    }
+
+    pub fn shift(&self) -> u8 {
+        self.shift
+    }
+
+    fn apply(&self, text: &str, shift: u8) -> String {
+        text.chars()
+            .map(|c| {
+                let base = match c {
+                    'A'..='Z' => b'A',
+                    'a'..='z' => b'a',
+                    _ => return c,
+                };
+                ((c as u8 - base + shift) % 26 + base) as char
+            })
+            .collect()
+    }
+}
+
+impl ClassicalCipher for Caesar {
+    fn validate_key(key: &str) -> Result<(), CipherError> {
+        match key.trim().parse::<u8>() {
+            Ok(shift) if shift < 26 => Ok(()),
+            _ => Err(CipherError::InvalidKey(INFO.key_description.to_string())),
+        }
+    }
+
+    fn new(key: &str) -> Result<Self, CipherError> {
+        <Self as ClassicalCipher>::validate_key(key)?;
+        Ok(Caesar::with_shift(key.trim().parse().unwrap_or_default()))
+    }
+
+    fn info(&self) -> CipherInfo {
+        INFO
+    }
+
+    fn encrypt(&self, plaintext: &str) -> Result<String, CipherError> {
+        Ok(self.apply(plaintext, self.shift))
+    }
+
+    fn decrypt(&self, ciphertext: &str) -> Result<String, CipherError> {
+        Ok(self.apply(ciphertext, (26 - self.shift) % 26))
+    }
+}
+
+const INFO: CipherInfo = CipherInfo {
+    name: "caesar",
+    family: CipherFamily::Substitution,
+    key_description: "a shift from 0 to 25",
+    block_size: None,
+};
+
+/// A candidate decryption found by [`brute_force`] or [`crack`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate {
+    pub shift: u8,
+    pub plaintext: String,
+    /// Quadgram score in the language; higher is better
+    pub score: f64,
+}
+
+/// Decrypt with every shift, in shift order
+pub fn brute_force(ciphertext: &str, language: Language) -> Vec<Candidate> {
+    (0..26)
+        .map(|shift| {
+            let plaintext = Caesar::with_shift(shift).apply(ciphertext, (26 - shift) % 26);
+            let score = quadgram_score(&plaintext, language);
+            Candidate { shift, plaintext, score }
+        })
+        .collect()
+}
+
+/// Recover the shift by picking the decryption that reads most like the
+/// language (the smallest shift when none can be scored)
+pub fn crack(ciphertext: &str, language: Language) -> Candidate {
+    brute_force(ciphertext, language)
+        .into_iter()
+        .min_by(|a, b| b.score.total_cmp(&a.score))
+        .expect("there are 26 shifts")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_keeps_case_and_punctuation() {
+        let caesar = Caesar::new("3").unwrap();
+        assert_eq!(caesar.encrypt("Veni, vidi, vici!").unwrap(), "Yhql, ylgl, ylfl!");
+        assert_eq!(caesar.decrypt("Yhql, ylgl, ylfl!").unwrap(), "Veni, vidi, vici!");
+        assert!(Caesar::new("26").is_err());
+    }
+
+    #[test]
+    fn cracks_english_and_romanian_by_quadgrams() {
+        let plaintext = "The quick brown fox jumps over the lazy dog while the students watch the lecture";
+        let ciphertext = Caesar::with_shift(11).encrypt(plaintext).unwrap();
+
+        let candidate = crack(&ciphertext, Language::English);
+        assert_eq!(candidate.shift, 11);
+        assert_eq!(candidate.plaintext, plaintext);
+        assert_eq!(crack(&Caesar::with_shift(3).encrypt("Zebra").unwrap(), Language::English).shift, 3);
+
+        let romanian = "Mâine plecăm la munte cu trenul de dimineață";
+        assert_eq!(crack(&Caesar::with_shift(20).encrypt(romanian).unwrap(), Language::Romanian).plaintext, romanian);
+    }
+}
@@ -0,0 +1,321 @@
+//! ChaCha20 (RFC 8439): a keystream from 20 rounds of add-rotate-XOR on a
+//! 4×4 matrix of 32-bit words holding constants, the key, a block counter
+//! and a nonce; the modern counterpart to RC4. Poly1305 is the one-time
+//! MAC it is paired with in the ChaCha20-Poly1305 AEAD.
+
+use crate::cipher::{CipherError, CipherFamily, CipherInfo, SymmetricCipher};
+use crate::mac::constant_time_eq;
+use crate::rng;
+
+/// Bytes of a ChaCha20 nonce
+pub const NONCE_LENGTH: usize = 12;
+
+/// Bytes of a Poly1305 tag
+pub const TAG_LENGTH: usize = 16;
+
+/// "expand 32-byte k", the first row of the matrix
+const CONSTANTS: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574];
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+fn words<const N: usize>(bytes: &[u8]) -> [u32; N] {
+    let mut words = [0; N];
+    for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().expect("4 bytes"));
+    }
+    words
+}
+
+/// 64 keystream bytes for one block counter
+pub fn block(key: &[u8; 32], counter: u32, nonce: &[u8; NONCE_LENGTH]) -> [u8; 64] {
+    let key: [u32; 8] = words(key);
+    let nonce: [u32; 3] = words(nonce);
+    let mut initial = [0u32; 16];
+    initial[..4].copy_from_slice(&CONSTANTS);
+    initial[4..12].copy_from_slice(&key);
+    initial[12] = counter;
+    initial[13..].copy_from_slice(&nonce);
+
+    let mut state = initial;
+    // Ten double rounds: the columns, then the diagonals
+    for _ in 0..10 {
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
+    }
+
+    // Adding the input back makes the rounds impossible to run backwards
+    let mut output = [0u8; 64];
+    for ((chunk, word), input) in output.chunks_exact_mut(4).zip(state).zip(initial) {
+        chunk.copy_from_slice(&word.wrapping_add(input).to_le_bytes());
+    }
+    output
+}
+
+/// Symmetric-interface encryption picks a fresh random nonce and writes it
+/// before the ciphertext; [`ChaCha20::apply`] takes the nonce explicitly
+pub struct ChaCha20 {
+    key: [u8; 32],
+}
+
+impl ChaCha20 {
+    /// XOR `data` with the keystream from block `counter` on; encrypts and
+    /// decrypts alike
+    pub fn apply(&self, nonce: &[u8; NONCE_LENGTH], counter: u32, data: &[u8]) -> Vec<u8> {
+        data.chunks(64)
+            .zip(counter..)
+            .flat_map(|(chunk, counter)| {
+                let keystream = block(&self.key, counter, nonce);
+                chunk.iter().zip(keystream).map(|(byte, key)| byte ^ key).collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+impl SymmetricCipher for ChaCha20 {
+    fn validate_key(key: &[u8]) -> Result<(), CipherError> {
+        if key.len() == 32 {
+            Ok(())
+        } else {
+            Err(CipherError::InvalidKey(INFO.key_description.to_string()))
+        }
+    }
+
+    fn new(key: &[u8]) -> Result<Self, CipherError> {
+        <Self as SymmetricCipher>::validate_key(key)?;
+        Ok(ChaCha20 { key: key.try_into().expect("checked above") })
+    }
+
+    fn info(&self) -> CipherInfo {
+        INFO
+    }
+
+    /// nonce ‖ ciphertext, the keystream starting at block 1 as in RFC 8439
+    fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        let nonce: [u8; NONCE_LENGTH] = rng::nonce(NONCE_LENGTH)
+            .expect("the operating system's random number generator failed")
+            .try_into()
+            .expect("12 bytes");
+        [&nonce[..], &self.apply(&nonce, 1, plaintext)].concat()
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, CipherError> {
+        if ciphertext.len() < NONCE_LENGTH {
+            return Err(CipherError::InvalidInput(String::from("too short to hold a nonce")));
+        }
+        let (nonce, ciphertext) = ciphertext.split_at(NONCE_LENGTH);
+        Ok(self.apply(nonce.try_into().expect("12 bytes"), 1, ciphertext))
+    }
+}
+
+const INFO: CipherInfo = CipherInfo {
+    name: "chacha20",
+    family: CipherFamily::Stream,
+    key_description: "exactly 32 bytes",
+    block_size: None,
+};
+
+/// The Poly1305 tag of `message` under a one-time key: the message as
+/// 16-byte numbers evaluated as a polynomial at r modulo 2¹³⁰ − 5, plus s.
+/// A key must never tag two messages.
+pub fn poly1305(key: &[u8; 32], message: &[u8]) -> [u8; TAG_LENGTH] {
+    // Numbers are kept as five 26-bit limbs so products fit in u64
+    const MASK: u32 = 0x3ff_ffff;
+    let le32 = |bytes: &[u8], at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().expect("4 bytes"));
+
+    // r is clamped: some bits cleared, as the design requires
+    let r = [
+        le32(key, 0) & 0x3ff_ffff,
+        (le32(key, 3) >> 2) & 0x3ff_ff03,
+        (le32(key, 6) >> 4) & 0x3ff_c0ff,
+        (le32(key, 9) >> 6) & 0x3f0_3fff,
+        (le32(key, 12) >> 8) & 0x00f_ffff,
+    ];
+    // 2¹³⁰ ≡ 5, so a limb product spilling past 2¹³⁰ wraps around times 5
+    let s = [r[1] * 5, r[2] * 5, r[3] * 5, r[4] * 5];
+    let mut h = [0u32; 5];
+
+    for chunk in message.chunks(16) {
+        // Each chunk gets a 1 byte appended, so trailing zeros still count
+        let mut padded = [0u8; 17];
+        padded[..chunk.len()].copy_from_slice(chunk);
+        padded[chunk.len()] = 1;
+        h[0] += le32(&padded, 0) & MASK;
+        h[1] += (le32(&padded, 3) >> 2) & MASK;
+        h[2] += (le32(&padded, 6) >> 4) & MASK;
+        h[3] += (le32(&padded, 9) >> 6) & MASK;
+        h[4] += (le32(&padded, 12) >> 8) | (u32::from(padded[16]) << 24);
+
+        let [h0, h1, h2, h3, h4] = h.map(u64::from);
+        let [r0, r1, r2, r3, r4] = r.map(u64::from);
+        let [s1, s2, s3, s4] = s.map(u64::from);
+        let mut d = [
+            h0 * r0 + h1 * s4 + h2 * s3 + h3 * s2 + h4 * s1,
+            h0 * r1 + h1 * r0 + h2 * s4 + h3 * s3 + h4 * s2,
+            h0 * r2 + h1 * r1 + h2 * r0 + h3 * s4 + h4 * s3,
+            h0 * r3 + h1 * r2 + h2 * r1 + h3 * r0 + h4 * s4,
+            h0 * r4 + h1 * r3 + h2 * r2 + h3 * r1 + h4 * r0,
+        ];
+        for i in 0..4 {
+            d[i + 1] += d[i] >> 26;
+            h[i] = d[i] as u32 & MASK;
+        }
+        h[4] = d[4] as u32 & MASK;
+        h[0] += (d[4] >> 26) as u32 * 5;
+        h[1] += h[0] >> 26;
+        h[0] &= MASK;
+    }
+
+    // Carry fully, then reduce: h − p replaces h unless it borrows
+    for i in 1..5 {
+        h[i] += h[i - 1] >> 26;
+        h[i - 1] &= MASK;
+    }
+    h[0] += (h[4] >> 26) * 5;
+    h[4] &= MASK;
+    h[1] += h[0] >> 26;
+    h[0] &= MASK;
+    let mut g = [0u32; 5];
+    let mut carry = 5;
+    for i in 0..5 {
+        g[i] = h[i] + carry;
+        carry = g[i] >> 26;
+        g[i] &= MASK;
+    }
+    let use_g = carry == 1;
+    let h = if use_g { g } else { h };
+
+    // Back to four 32-bit words, then add s modulo 2¹²⁸
+    let h = [
+        h[0] | (h[1] << 26),
+        (h[1] >> 6) | (h[2] << 20),
+        (h[2] >> 12) | (h[3] << 14),
+        (h[3] >> 18) | (h[4] << 8),
+    ];
+    let mut tag = [0u8; TAG_LENGTH];
+    let mut sum = 0u64;
+    for (i, word) in h.into_iter().enumerate() {
+        sum += u64::from(word) + u64::from(le32(key, 16 + 4 * i));
+        tag[4 * i..4 * i + 4].copy_from_slice(&(sum as u32).to_le_bytes());
+        sum >>= 32;
+    }
+    tag
+}
+
+/// The ChaCha20-Poly1305 AEAD: ChaCha20 from block 1 encrypts, and block
+/// 0 gives the one-time Poly1305 key that tags the associated data and the
+/// ciphertext
+pub struct ChaCha20Poly1305 {
+    cipher: ChaCha20,
+}
+
+impl ChaCha20Poly1305 {
+    pub fn new(key: &[u8]) -> Result<Self, CipherError> {
+        Ok(ChaCha20Poly1305 { cipher: ChaCha20::new(key)? })
+    }
+
+    fn tag(&self, nonce: &[u8; NONCE_LENGTH], associated: &[u8], ciphertext: &[u8]) -> [u8; TAG_LENGTH] {
+        let one_time_key: [u8; 32] = block(&self.cipher.key, 0, nonce)[..32].try_into().expect("32 bytes");
+        let padding = |len: usize| vec![0u8; (16 - len % 16) % 16];
+        let data = [
+            associated,
+            &padding(associated.len()),
+            ciphertext,
+            &padding(ciphertext.len()),
+            &(associated.len() as u64).to_le_bytes(),
+            &(ciphertext.len() as u64).to_le_bytes(),
+        ]
+        .concat();
+        poly1305(&one_time_key, &data)
+    }
+
+    /// ciphertext ‖ tag. The nonce must never repeat under one key.
+    pub fn seal(&self, nonce: &[u8; NONCE_LENGTH], associated: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let ciphertext = self.cipher.apply(nonce, 1, plaintext);
+        let tag = self.tag(nonce, associated, &ciphertext);
+        [ciphertext, tag.to_vec()].concat()
+    }
+
+    /// Check the tag, then decrypt; an altered message or associated data
+    /// is rejected before any plaintext exists
+    pub fn open(&self, nonce: &[u8; NONCE_LENGTH], associated: &[u8], sealed: &[u8]) -> Result<Vec<u8>, CipherError> {
+        if sealed.len() < TAG_LENGTH {
+            return Err(CipherError::InvalidInput(String::from("too short to hold a tag")));
+        }
+        let (ciphertext, tag) = sealed.split_at(sealed.len() - TAG_LENGTH);
+        if !constant_time_eq(&self.tag(nonce, associated, ciphertext), tag) {
+            return Err(CipherError::InvalidInput(String::from("tag check failed: the message was altered or the key is wrong")));
+        }
+        Ok(self.cipher.apply(nonce, 1, ciphertext))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::{decode_hex, encode_hex};
+
+    const SUNSCREEN: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+
+    fn array<const N: usize>(hex: &str) -> [u8; N] {
+        decode_hex(hex).unwrap().try_into().unwrap()
+    }
+
+    /// RFC 8439, 2.3.2 and 2.4.2
+    #[test]
+    fn matches_rfc_8439_keystream_and_encryption() {
+        let key: [u8; 32] = std::array::from_fn(|i| i as u8);
+        assert_eq!(
+            encode_hex(&block(&key, 1, &array("000000090000004a00000000"))),
+            "10f1e7e4d13b5915500fdd1fa32071c4c7d1f4c733c068030422aa9ac3d46c4e\
+             d2826446079faa0914c2d705d98b02a2b5129cd1de164eb9cbd083e8a2503c4e"
+        );
+
+        let cipher = ChaCha20::new(&key).unwrap();
+        let nonce = array("000000000000004a00000000");
+        let ciphertext = cipher.apply(&nonce, 1, SUNSCREEN);
+        assert_eq!(
+            encode_hex(&ciphertext),
+            "6e2e359a2568f98041ba0728dd0d6981e97e7aec1d4360c20a27afccfd9fae0b\
+             f91b65c5524733ab8f593dabcd62b3571639d624e65152ab8f530c359f0861d8\
+             07ca0dbf500d6a6156a38e088a22b65e52bc514d16ccf806818ce91ab7793736\
+             5af90bbf74a35be6b40b8eedf2785e42874d"
+        );
+        assert_eq!(cipher.apply(&nonce, 1, &ciphertext), SUNSCREEN);
+        assert_eq!(cipher.decrypt(&cipher.encrypt(SUNSCREEN)).unwrap(), SUNSCREEN);
+    }
+
+    /// RFC 8439, 2.5.2 and 2.8.2
+    #[test]
+    fn matches_rfc_8439_poly1305_and_aead() {
+        let key = array("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b");
+        assert_eq!(encode_hex(&poly1305(&key, b"Cryptographic Forum Research Group")), "a8061dc1305136c6c22b8baf0c0127a9");
+
+        let aead = ChaCha20Poly1305::new(&decode_hex("808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f").unwrap()).unwrap();
+        let nonce = array("070000004041424344454647");
+        let associated = decode_hex("50515253c0c1c2c3c4c5c6c7").unwrap();
+        let sealed = aead.seal(&nonce, &associated, SUNSCREEN);
+        assert!(encode_hex(&sealed).starts_with("d31a8d34648e60db7b86afbc53ef7ec2"));
+        assert!(encode_hex(&sealed).ends_with("1ae10b594f09e26a7e902ecbd0600691"));
+        assert_eq!(aead.open(&nonce, &associated, &sealed).unwrap(), SUNSCREEN);
+
+        let mut altered = sealed.clone();
+        altered[0] ^= 1;
+        assert!(aead.open(&nonce, &associated, &altered).is_err());
+        assert!(aead.open(&nonce, b"other data", &sealed).is_err());
+    }
+}
@@ -0,0 +1,331 @@
+//! A toy blockchain for the course project: DSA-signed transfers, blocks
+//! committing to their transactions through a Merkle root and to the
+//! previous block through its hash, and SHA-256 proof of work
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::dsa::{DsaPrivateKey, DsaPublicKey, DsaSignature};
+use crate::hash::sha256;
+use crate::merkle::{self, Hash};
+
+/// Coins created for the miner of each block
+pub const MINING_REWARD: u64 = 50;
+
+/// A transfer of `amount` coins. `from` is `None` for the coinbase
+/// transaction paying the miner, the only kind that needs no signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transaction {
+    pub from: Option<String>,
+    pub to: String,
+    pub amount: u64,
+    /// The sender's count of earlier transfers, so a signed transfer cannot
+    /// be replayed; the block index for a coinbase
+    pub sequence: u64,
+    pub signature: Option<DsaSignature>,
+}
+
+impl Transaction {
+    pub fn coinbase(miner: &str, block_index: u64) -> Self {
+        Transaction { from: None, to: miner.to_string(), amount: MINING_REWARD, sequence: block_index, signature: None }
+    }
+
+    /// A transfer signed with the sender's key
+    pub fn signed(key: &DsaPrivateKey, from: &str, to: &str, amount: u64, sequence: u64) -> io::Result<Self> {
+        let mut transaction =
+            Transaction { from: Some(from.to_string()), to: to.to_string(), amount, sequence, signature: None };
+        transaction.signature = Some(key.sign(&transaction.message())?);
+        Ok(transaction)
+    }
+
+    /// The bytes the sender signs: every field but the signature
+    pub fn message(&self) -> Vec<u8> {
+        format!("{}\n{}\n{}\n{}", self.from.as_deref().unwrap_or(""), self.to, self.amount, self.sequence).into_bytes()
+    }
+
+    /// Merkle leaf, covering the signature too
+    fn leaf(&self) -> Hash {
+        let mut data = self.message();
+        if let Some(signature) = &self.signature {
+            data.extend_from_slice(format!("\n{:x}\n{:x}", signature.r, signature.s).as_bytes());
+        }
+        merkle::leaf_hash(&data)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Block {
+    pub index: u64,
+    pub previous_hash: Hash,
+    /// Unix seconds
+    pub timestamp: u64,
+    /// Leading zero bits the block hash needs
+    pub difficulty: u32,
+    pub nonce: u64,
+    pub transactions: Vec<Transaction>,
+}
+
+impl Block {
+    pub fn merkle_root(&self) -> Hash {
+        merkle::root(&self.transactions.iter().map(Transaction::leaf).collect::<Vec<_>>())
+    }
+
+    /// What is hashed: the transactions enter only through their root, so a
+    /// light client can check a payment with an inclusion proof
+    pub fn header(&self) -> Vec<u8> {
+        let mut header = Vec::with_capacity(92);
+        header.extend_from_slice(&self.index.to_be_bytes());
+        header.extend_from_slice(&self.previous_hash);
+        header.extend_from_slice(&self.timestamp.to_be_bytes());
+        header.extend_from_slice(&self.merkle_root());
+        header.extend_from_slice(&self.difficulty.to_be_bytes());
+        header.extend_from_slice(&self.nonce.to_be_bytes());
+        header
+    }
+
+    pub fn hash(&self) -> Hash {
+        sha256(&self.header())
+    }
+
+    pub fn meets_difficulty(&self) -> bool {
+        leading_zero_bits(&self.hash()) >= self.difficulty
+    }
+
+    /// Try nonces from 0 until the hash has enough leading zeros; about
+    /// 2^difficulty attempts. Returns how many were needed.
+    pub fn mine(&mut self) -> u64 {
+        // Built once; only its last 8 bytes, the nonce, change between attempts
+        let mut header = self.header();
+        let nonce_at = header.len() - 8;
+        for nonce in 0.. {
+            header[nonce_at..].copy_from_slice(&u64::to_be_bytes(nonce));
+            if leading_zero_bits(&sha256(&header)) >= self.difficulty {
+                self.nonce = nonce;
+                return nonce + 1;
+            }
+        }
+        unreachable!("every nonce failed")
+    }
+}
+
+fn leading_zero_bits(hash: &Hash) -> u32 {
+    let mut bits = 0;
+    for byte in hash {
+        bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    bits
+}
+
+/// Why a chain or a new transaction was rejected
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidChain {
+    pub block: u64,
+    pub reason: String,
+}
+
+impl fmt::Display for InvalidChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Block {}: {}", self.block, self.reason)
+    }
+}
+
+impl Error for InvalidChain {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Chain {
+    pub blocks: Vec<Block>,
+}
+
+/// Balances and next sequence numbers while replaying the chain
+#[derive(Default)]
+struct Ledger {
+    balances: BTreeMap<String, u64>,
+    sequences: BTreeMap<String, u64>,
+}
+
+impl Ledger {
+    fn apply(&mut self, transaction: &Transaction, public_key: &impl Fn(&str) -> Option<DsaPublicKey>) -> Result<(), String> {
+        let Some(from) = &transaction.from else {
+            return Err(String::from("a coinbase transaction outside the first position"));
+        };
+        let key = public_key(from).ok_or_else(|| format!("no key for {}", from))?;
+        let signature = transaction.signature.as_ref().ok_or_else(|| format!("unsigned transfer from {}", from))?;
+        if !key.verify(&transaction.message(), signature) {
+            return Err(format!("bad signature on the transfer from {}", from));
+        }
+        let expected = self.sequences.get(from).copied().unwrap_or(0);
+        if transaction.sequence != expected {
+            return Err(format!("transfer {} from {} replayed or out of order (expected {})", transaction.sequence, from, expected));
+        }
+        let balance = self.balances.get(from).copied().unwrap_or(0);
+        if transaction.amount == 0 || transaction.amount > balance {
+            return Err(format!("{} cannot send {} with a balance of {}", from, transaction.amount, balance));
+        }
+
+        self.balances.insert(from.clone(), balance - transaction.amount);
+        *self.balances.entry(transaction.to.clone()).or_default() += transaction.amount;
+        self.sequences.insert(from.clone(), expected + 1);
+        Ok(())
+    }
+
+    fn credit(&mut self, coinbase: &Transaction) {
+        *self.balances.entry(coinbase.to.clone()).or_default() += coinbase.amount;
+    }
+}
+
+impl Chain {
+    /// A chain holding only a mined genesis block
+    pub fn new(difficulty: u32, timestamp: u64) -> Self {
+        let mut genesis =
+            Block { index: 0, previous_hash: [0; 32], timestamp, difficulty, nonce: 0, transactions: Vec::new() };
+        genesis.mine();
+        Chain { blocks: vec![genesis] }
+    }
+
+    pub fn difficulty(&self) -> u32 {
+        self.blocks.first().map_or(0, |genesis| genesis.difficulty)
+    }
+
+    /// Check and mine the transactions into a new block, after the coinbase
+    /// paying `miner`. Returns the block and how many hashes it took.
+    pub fn mine(
+        &mut self,
+        transactions: Vec<Transaction>,
+        miner: &str,
+        timestamp: u64,
+        public_key: impl Fn(&str) -> Option<DsaPublicKey>,
+    ) -> Result<(&Block, u64), InvalidChain> {
+        let index = self.blocks.len() as u64;
+        let coinbase = Transaction::coinbase(miner, index);
+        let mut ledger = self.replay(&public_key)?;
+        ledger.credit(&coinbase);
+        for transaction in &transactions {
+            ledger.apply(transaction, &public_key).map_err(|reason| InvalidChain { block: index, reason })?;
+        }
+
+        let previous_hash = self.blocks.last().map_or([0; 32], Block::hash);
+        let mut block = Block {
+            index,
+            previous_hash,
+            timestamp,
+            difficulty: self.difficulty(),
+            nonce: 0,
+            transactions: std::iter::once(coinbase).chain(transactions).collect(),
+        };
+        let attempts = block.mine();
+        self.blocks.push(block);
+        Ok((self.blocks.last().expect("just pushed"), attempts))
+    }
+
+    /// Check every link, proof of work, signature, sequence number and
+    /// balance from the genesis block on
+    pub fn validate(&self, public_key: impl Fn(&str) -> Option<DsaPublicKey>) -> Result<(), InvalidChain> {
+        self.replay(&public_key).map(|_| ())
+    }
+
+    /// Coins held by everyone who ever received any, once the chain validates
+    pub fn balances(&self, public_key: impl Fn(&str) -> Option<DsaPublicKey>) -> Result<BTreeMap<String, u64>, InvalidChain> {
+        Ok(self.replay(&public_key)?.balances)
+    }
+
+    /// The sequence number `sender`'s next transfer must carry
+    pub fn next_sequence(&self, sender: &str) -> u64 {
+        self.blocks
+            .iter()
+            .flat_map(|block| &block.transactions)
+            .filter(|transaction| transaction.from.as_deref() == Some(sender))
+            .count() as u64
+    }
+
+    fn replay(&self, public_key: &impl Fn(&str) -> Option<DsaPublicKey>) -> Result<Ledger, InvalidChain> {
+        let mut ledger = Ledger::default();
+        let mut previous_hash = [0; 32];
+        for (position, block) in self.blocks.iter().enumerate() {
+            let invalid = |reason: String| InvalidChain { block: position as u64, reason };
+            if block.index != position as u64 {
+                return Err(invalid(format!("index {} out of place", block.index)));
+            }
+            if block.previous_hash != previous_hash {
+                return Err(invalid(String::from("does not link to the previous block's hash")));
+            }
+            if block.difficulty != self.difficulty() || !block.meets_difficulty() {
+                return Err(invalid(format!("hash lacks {} leading zero bits", self.difficulty())));
+            }
+
+            let mut transactions = block.transactions.iter();
+            if position > 0 {
+                match transactions.next() {
+                    Some(coinbase) if coinbase.from.is_none() && coinbase.amount == MINING_REWARD && coinbase.sequence == block.index => {
+                        ledger.credit(coinbase);
+                    }
+                    _ => return Err(invalid(format!("first transaction is not a {}-coin coinbase", MINING_REWARD))),
+                }
+            }
+            for transaction in transactions {
+                ledger.apply(transaction, public_key).map_err(invalid)?;
+            }
+            previous_hash = block.hash();
+        }
+        Ok(ledger)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::dsa::DsaParameters;
+
+    fn keys() -> BTreeMap<String, DsaPrivateKey> {
+        let parameters = DsaParameters::generate(512, 160).unwrap();
+        ["alice", "bob"]
+            .into_iter()
+            .map(|name| (name.to_string(), DsaPrivateKey::generate_in(parameters.clone()).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn mined_transfers_move_coins_and_validate() {
+        let keys = keys();
+        let public_key = |name: &str| keys.get(name).map(DsaPrivateKey::public);
+        let mut chain = Chain::new(8, 0);
+        chain.mine(Vec::new(), "alice", 1, public_key).unwrap();
+
+        let transfer = Transaction::signed(&keys["alice"], "alice", "bob", 20, chain.next_sequence("alice")).unwrap();
+        let (block, _) = chain.mine(vec![transfer.clone()], "bob", 2, public_key).unwrap();
+        assert!(block.meets_difficulty());
+        assert_eq!(chain.balances(public_key).unwrap(), BTreeMap::from([("alice".into(), 30), ("bob".into(), 70)]));
+        assert_eq!(chain.validate(public_key), Ok(()));
+
+        // The same signed transfer cannot be spent twice
+        assert!(chain.mine(vec![transfer], "bob", 3, public_key).is_err());
+        let overdraft = Transaction::signed(&keys["bob"], "bob", "alice", 71, 0).unwrap();
+        assert!(chain.mine(vec![overdraft], "alice", 3, public_key).is_err());
+    }
+
+    #[test]
+    fn tampering_is_caught() {
+        let keys = keys();
+        let public_key = |name: &str| keys.get(name).map(DsaPrivateKey::public);
+        let mut chain = Chain::new(8, 0);
+        chain.mine(Vec::new(), "alice", 1, public_key).unwrap();
+        let transfer = Transaction::signed(&keys["alice"], "alice", "bob", 20, 0).unwrap();
+        chain.mine(vec![transfer], "alice", 2, public_key).unwrap();
+
+        let mut edited = chain.clone();
+        edited.blocks[2].transactions[1].amount = 2;
+        assert_eq!(edited.validate(public_key).unwrap_err().block, 2);
+
+        // Re-signing is impossible without alice's key, and re-mining block 1
+        // still breaks the link from block 2
+        let mut remined = chain.clone();
+        remined.blocks[1].timestamp = 99;
+        remined.blocks[1].mine();
+        assert_eq!(remined.validate(public_key).unwrap_err().block, 2);
+    }
+}
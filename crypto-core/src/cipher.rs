@@ -0,0 +1,109 @@
+use std::error::Error;
+use std::fmt;
+
+/// How a cipher works, for grouping algorithms in tools and reports
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CipherFamily {
+    /// Letters are replaced according to a key (Caesar, Playfair, ...)
+    Substitution,
+    /// Letters are reordered (rail fence, columnar, ...)
+    Transposition,
+    /// Fixed-size blocks of bytes (DES, AES)
+    Block,
+    /// A keystream is combined with the data (RC4, one-time pad, ...)
+    Stream,
+}
+
+impl CipherFamily {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Substitution => "substitution",
+            Self::Transposition => "transposition",
+            Self::Block => "block",
+            Self::Stream => "stream",
+        }
+    }
+}
+
+/// Description of an algorithm
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CipherInfo {
+    /// Short lowercase name, also used as the CLI subcommand (`playfair`)
+    pub name: &'static str,
+    pub family: CipherFamily,
+    /// What a valid key looks like, shown when a key is rejected
+    pub key_description: &'static str,
+    /// Block size in bytes, for block ciphers
+    pub block_size: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CipherError {
+    /// The key does not fit the algorithm
+    InvalidKey(String),
+    /// The plaintext or ciphertext cannot be processed
+    InvalidInput(String),
+}
+
+impl fmt::Display for CipherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidKey(reason) => f.write_str(&crate::tr!("Invalid key: {}", reason)),
+            Self::InvalidInput(reason) => f.write_str(&crate::tr!("Invalid input: {}", reason)),
+        }
+    }
+}
+
+impl Error for CipherError {}
+
+/// A pen-and-paper cipher working on letters.
+///
+/// Instances hold a validated key, so tools can keep a `Box<dyn ClassicalCipher>`
+/// and run any algorithm the same way.
+pub trait ClassicalCipher {
+    /// Check a key without building the cipher
+    fn validate_key(key: &str) -> Result<(), CipherError>
+    where
+        Self: Sized;
+
+    fn new(key: &str) -> Result<Self, CipherError>
+    where
+        Self: Sized;
+
+    fn info(&self) -> CipherInfo;
+
+    fn encrypt(&self, plaintext: &str) -> Result<String, CipherError>;
+
+    fn decrypt(&self, ciphertext: &str) -> Result<String, CipherError>;
+}
+
+/// A modern cipher working on bytes with a binary key
+pub trait SymmetricCipher {
+    /// Check a key without building the cipher
+    fn validate_key(key: &[u8]) -> Result<(), CipherError>
+    where
+        Self: Sized;
+
+    fn new(key: &[u8]) -> Result<Self, CipherError>
+    where
+        Self: Sized;
+
+    fn info(&self) -> CipherInfo;
+
+    fn encrypt(&self, plaintext: &[u8]) -> Vec<u8>;
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, CipherError>;
+}
+
+/// A cipher on fixed-size blocks, which [`crate::modes`] turns into a cipher
+/// on messages of any length
+pub trait BlockCipher {
+    /// Block size in bytes
+    fn block_size(&self) -> usize;
+
+    /// Encrypt one block of `block_size` bytes in place
+    fn encrypt_block(&self, block: &mut [u8]);
+
+    /// Decrypt one block of `block_size` bytes in place
+    fn decrypt_block(&self, block: &mut [u8]);
+}
@@ -0,0 +1,255 @@
+//! Hex, Base32 and Base64 (RFC 4648), the text forms keys, digests and
+//! ciphertexts travel in. Each character carries 4, 5 or 6 bits; Base32 and
+//! Base64 pad the last group of 8 or 4 characters with `=`.
+//!
+//! Strict parsing accepts only the canonical form: no whitespace, exact
+//! padding and zero unused bits, so every byte string has one encoding.
+//! Lenient parsing takes what people paste: whitespace and line breaks,
+//! missing padding, lowercase Base32, URL-safe Base64 (`-` and `_`), and
+//! uppercase hex with a `0x` prefix or `:` separators.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+const HEX: &[u8] = b"0123456789abcdef";
+const BASE32: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strictness {
+    Strict,
+    Lenient,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    Hex,
+    Base32,
+    Base64,
+}
+
+impl Encoding {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Hex => "hex",
+            Self::Base32 => "base32",
+            Self::Base64 => "base64",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "hex" | "base16" => Some(Self::Hex),
+            "base32" => Some(Self::Base32),
+            "base64" => Some(Self::Base64),
+            _ => None,
+        }
+    }
+
+    fn alphabet(self) -> &'static [u8] {
+        match self {
+            Self::Hex => HEX,
+            Self::Base32 => BASE32,
+            Self::Base64 => BASE64,
+        }
+    }
+
+    fn bits_per_char(self) -> u32 {
+        match self {
+            Self::Hex => 4,
+            Self::Base32 => 5,
+            Self::Base64 => 6,
+        }
+    }
+
+    /// Characters per padded group; hex needs no padding
+    fn group_length(self) -> usize {
+        match self {
+            Self::Hex => 1,
+            Self::Base32 => 8,
+            Self::Base64 => 4,
+        }
+    }
+
+    pub fn encode(self, bytes: &[u8]) -> String {
+        let (alphabet, bits_per_char) = (self.alphabet(), self.bits_per_char());
+        let mut text = String::with_capacity(bytes.len() * 8 / bits_per_char as usize + 8);
+        let (mut buffer, mut bits) = (0u32, 0);
+        for &byte in bytes {
+            buffer = (buffer << 8) | u32::from(byte);
+            bits += 8;
+            while bits >= bits_per_char {
+                bits -= bits_per_char;
+                text.push(alphabet[(buffer >> bits) as usize & ((1 << bits_per_char) - 1)] as char);
+            }
+        }
+        if bits > 0 {
+            text.push(alphabet[(buffer << (bits_per_char - bits)) as usize & ((1 << bits_per_char) - 1)] as char);
+        }
+        while !text.len().is_multiple_of(self.group_length()) {
+            text.push('=');
+        }
+        text
+    }
+
+    pub fn decode(self, text: &str, strictness: Strictness) -> Result<Vec<u8>, DecodeError> {
+        self.decode_with_case(text, strictness, strictness == Strictness::Lenient)
+    }
+
+    /// [`Encoding::decode`], taking uppercase hex digits when `any_case` is set
+    fn decode_with_case(self, text: &str, strictness: Strictness, any_case: bool) -> Result<Vec<u8>, DecodeError> {
+        let error = |reason: String| DecodeError { encoding: self, reason };
+        let lenient = strictness == Strictness::Lenient;
+
+        let mut text = text;
+        if lenient && self == Self::Hex {
+            text = text.trim().strip_prefix("0x").or_else(|| text.trim().strip_prefix("0X")).unwrap_or(text);
+        }
+        let characters: Vec<(usize, u8)> = text
+            .bytes()
+            .enumerate()
+            .filter(|&(_, c)| !(lenient && (c.is_ascii_whitespace() || (self == Self::Hex && c == b':'))))
+            .collect();
+
+        let data_length = characters.iter().position(|&(_, c)| c == b'=').unwrap_or(characters.len());
+        let (data, padding) = characters.split_at(data_length);
+        if let Some(&(position, c)) = padding.iter().find(|&&(_, c)| c != b'=') {
+            return Err(error(format!("'{}' at position {} follows the padding", c as char, position)));
+        }
+        if self == Self::Hex && !padding.is_empty() {
+            return Err(error(format!("unexpected '=' at position {}", padding[0].0)));
+        }
+        if !lenient && self != Self::Hex {
+            let expected = (self.group_length() - data.len() % self.group_length()) % self.group_length();
+            if padding.len() != expected {
+                return Err(error(format!("{} padding character(s) where {} are needed", padding.len(), expected)));
+            }
+        }
+
+        let (bits_per_char, mut bytes) = (self.bits_per_char(), Vec::with_capacity(data.len() * 6 / 8));
+        let (mut buffer, mut bits) = (0u32, 0);
+        for &(position, c) in data {
+            let value = self.value(c, lenient, any_case).ok_or_else(|| error(format!("unexpected '{}' at position {}", c as char, position)))?;
+            buffer = (buffer << bits_per_char) | value;
+            bits += bits_per_char;
+            if bits >= 8 {
+                bits -= 8;
+                bytes.push((buffer >> bits) as u8);
+            }
+        }
+
+        // A whole leftover character carries no byte, so the text was cut short
+        if bits >= bits_per_char {
+            return Err(error("the text stops partway through a byte".to_string()));
+        }
+        if !lenient && buffer & ((1 << bits) - 1) != 0 {
+            return Err(error("the unused bits of the last character are not zero".to_string()));
+        }
+        Ok(bytes)
+    }
+
+    fn value(self, c: u8, lenient: bool, any_case: bool) -> Option<u32> {
+        let c = match (self, lenient) {
+            (Self::Hex, _) if any_case => c.to_ascii_lowercase(),
+            (Self::Base32, true) => c.to_ascii_uppercase(),
+            (Self::Base64, true) if c == b'-' => b'+',
+            (Self::Base64, true) if c == b'_' => b'/',
+            _ => c,
+        };
+        self.alphabet().iter().position(|&symbol| symbol == c).map(|value| value as u32)
+    }
+}
+
+/// Text that is not valid in the expected encoding
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodeError {
+    pub encoding: Encoding,
+    pub reason: String,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid {}: {}", self.encoding.name(), self.reason)
+    }
+}
+
+impl Error for DecodeError {}
+
+impl From<DecodeError> for io::Error {
+    fn from(error: DecodeError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidInput, error)
+    }
+}
+
+/// Lowercase hex
+pub fn encode_hex(bytes: &[u8]) -> String {
+    Encoding::Hex.encode(bytes)
+}
+
+/// Hex digits of either case, two per byte, nothing else
+pub fn decode_hex(text: &str) -> Result<Vec<u8>, DecodeError> {
+    Encoding::Hex.decode_with_case(text, Strictness::Strict, true)
+}
+
+/// Standard Base64 with padding
+pub fn encode_base64(bytes: &[u8]) -> String {
+    Encoding::Base64.encode(bytes)
+}
+
+pub fn decode_base64(text: &str, strictness: Strictness) -> Result<Vec<u8>, DecodeError> {
+    Encoding::Base64.decode(text, strictness)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc_4648_test_vectors() {
+        let vectors = [
+            ("", "", "", ""),
+            ("f", "66", "MY======", "Zg=="),
+            ("fo", "666f", "MZXQ====", "Zm8="),
+            ("foo", "666f6f", "MZXW6===", "Zm9v"),
+            ("foob", "666f6f62", "MZXW6YQ=", "Zm9vYg=="),
+            ("fooba", "666f6f6261", "MZXW6YTB", "Zm9vYmE="),
+            ("foobar", "666f6f626172", "MZXW6YTBOI======", "Zm9vYmFy"),
+        ];
+        for (plain, hex, base32, base64) in vectors {
+            for (encoding, encoded) in [(Encoding::Hex, hex), (Encoding::Base32, base32), (Encoding::Base64, base64)] {
+                assert_eq!(encoding.encode(plain.as_bytes()), encoded);
+                assert_eq!(encoding.decode(encoded, Strictness::Strict).unwrap(), plain.as_bytes(), "{}", encoded);
+            }
+        }
+    }
+
+    #[test]
+    fn strict_parsing_rejects_what_lenient_parsing_forgives() {
+        let lenient = [
+            (Encoding::Base64, "Zm9v\nYmE", "fooba"),
+            (Encoding::Base64, "-_8=", "\u{fb}\u{ff}"),
+            (Encoding::Base32, "mzxw6yq", "foob"),
+            (Encoding::Hex, "0x66:6F:6f", "foo"),
+            (Encoding::Hex, "666F6F", "foo"),
+            (Encoding::Base64, "Zh==", "f"),
+        ];
+        for (encoding, text, plain) in lenient {
+            assert!(encoding.decode(text, Strictness::Strict).is_err(), "{}", text);
+            let expected: Vec<u8> = plain.chars().map(|c| c as u8).collect();
+            assert_eq!(encoding.decode(text, Strictness::Lenient).unwrap(), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn malformed_text_fails_either_way() {
+        for strictness in [Strictness::Strict, Strictness::Lenient] {
+            assert!(decode_base64("Zm9vY", strictness).is_err());
+            assert!(decode_base64("Zm=9v", strictness).is_err());
+            assert!(decode_base64("Zm9v!A==", strictness).is_err());
+            assert!(Encoding::Hex.decode("abc", strictness).is_err());
+            assert!(Encoding::Base32.decode("MZX", strictness).is_err());
+        }
+        assert_eq!(decode_hex("6G").unwrap_err().to_string(), "Invalid hex: unexpected 'G' at position 1");
+    }
+}
@@ -0,0 +1,211 @@
+//! Columnar transposition: the text is written in rows under a keyword and
+//! read off column by column in the keyword's alphabetical order
+
+use crate::cipher::{CipherError, CipherFamily, CipherInfo, ClassicalCipher};
+use crate::language::{quadgram_score, Language};
+
+/// Single transposition with one keyword, double with two (`"ZEBRAS STRIPE"`).
+/// Only letters are kept, in upper case, and the last row is left short
+/// rather than padded.
+pub struct Columnar {
+    /// For each keyword, the column read out first, second, ...
+    orders: Vec<Vec<usize>>,
+}
+
+impl Columnar {
+    /// Transposition by explicit read-out orders, e.g. from [`crack`]
+    pub fn from_orders(orders: Vec<Vec<usize>>) -> Result<Self, CipherError> {
+        let valid = |order: &Vec<usize>| {
+            let mut sorted = order.clone();
+            sorted.sort_unstable();
+            order.len() >= 2 && sorted.into_iter().eq(0..order.len())
+        };
+        if orders.is_empty() || !orders.iter().all(valid) {
+            return Err(CipherError::InvalidKey(String::from("each order must be a permutation of at least 2 columns")));
+        }
+        Ok(Columnar { orders })
+    }
+
+    pub fn orders(&self) -> &[Vec<usize>] {
+        &self.orders
+    }
+}
+
+/// Read-out order of the columns under `keyword`: alphabetical, with repeated
+/// letters taken left to right
+pub fn column_order(keyword: &str) -> Vec<usize> {
+    let letters: Vec<char> = keyword.chars().map(|c| c.to_ascii_uppercase()).collect();
+    let mut order: Vec<usize> = (0..letters.len()).collect();
+    order.sort_by_key(|&column| letters[column]);
+    order
+}
+
+/// A keyword with the given read-out order, for showing recovered keys
+pub fn order_keyword(order: &[usize]) -> String {
+    let mut keyword = vec!['A'; order.len()];
+    for (rank, &column) in order.iter().enumerate() {
+        keyword[column] = (b'A' + (rank % 26) as u8) as char;
+    }
+    keyword.into_iter().collect()
+}
+
+/// Number of letters in `column` when `length` letters fill rows of `columns`
+fn column_length(length: usize, columns: usize, column: usize) -> usize {
+    length / columns + usize::from(column < length % columns)
+}
+
+fn transpose(letters: &[char], order: &[usize]) -> Vec<char> {
+    order
+        .iter()
+        .flat_map(|&column| letters.iter().skip(column).step_by(order.len()).copied())
+        .collect()
+}
+
+fn untranspose(letters: &[char], order: &[usize]) -> Vec<char> {
+    let mut plaintext = vec![' '; letters.len()];
+    let mut remaining = letters;
+    for &column in order {
+        let (taken, rest) = remaining.split_at(column_length(letters.len(), order.len(), column));
+        for (row, &c) in taken.iter().enumerate() {
+            plaintext[row * order.len() + column] = c;
+        }
+        remaining = rest;
+    }
+    plaintext
+}
+
+/// Letters of any alphabet, so that Playfair's Ș filler survives a later stage
+fn letters(text: &str) -> Vec<char> {
+    text.chars().filter(|c| c.is_alphabetic()).flat_map(char::to_uppercase).collect()
+}
+
+impl ClassicalCipher for Columnar {
+    fn validate_key(key: &str) -> Result<(), CipherError> {
+        let keywords: Vec<&str> = key.split_whitespace().collect();
+        let valid = |keyword: &&str| keyword.len() >= 2 && keyword.chars().all(|c| c.is_ascii_alphabetic());
+        if (1..=2).contains(&keywords.len()) && keywords.iter().all(valid) {
+            Ok(())
+        } else {
+            Err(CipherError::InvalidKey(INFO.key_description.to_string()))
+        }
+    }
+
+    fn new(key: &str) -> Result<Self, CipherError> {
+        <Self as ClassicalCipher>::validate_key(key)?;
+        Ok(Columnar { orders: key.split_whitespace().map(column_order).collect() })
+    }
+
+    fn info(&self) -> CipherInfo {
+        INFO
+    }
+
+    fn encrypt(&self, plaintext: &str) -> Result<String, CipherError> {
+        let letters = self.orders.iter().fold(letters(plaintext), |letters, order| transpose(&letters, order));
+        Ok(letters.into_iter().collect())
+    }
+
+    fn decrypt(&self, ciphertext: &str) -> Result<String, CipherError> {
+        let letters = self.orders.iter().rev().fold(letters(ciphertext), |letters, order| untranspose(&letters, order));
+        Ok(letters.into_iter().collect())
+    }
+}
+
+const INFO: CipherInfo = CipherInfo {
+    name: "columnar",
+    family: CipherFamily::Transposition,
+    key_description: "a keyword of at least 2 letters, or two keywords for double transposition",
+    block_size: None,
+};
+
+/// Largest width whose orders are all tried; wider ones are hill-climbed
+const EXHAUSTIVE_COLUMNS: usize = 8;
+
+/// Anagramming: find the column order of a single transposition with
+/// `columns` columns by rearranging the columns until the text reads most
+/// like the language. Returns the read-out order and the plaintext.
+pub fn crack(ciphertext: &str, columns: usize, language: Language) -> (Vec<usize>, String) {
+    let letters = letters(ciphertext);
+    let score = |order: &[usize]| quadgram_score(&untranspose(&letters, order).into_iter().collect::<String>(), language);
+
+    let best = if columns <= EXHAUSTIVE_COLUMNS {
+        permutations(columns)
+            .into_iter()
+            .map(|order| (score(&order), order))
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, order)| order)
+            .unwrap_or_default()
+    } else {
+        // Swap pairs of columns while that improves the score
+        let mut order: Vec<usize> = (0..columns).collect();
+        let mut best_score = score(&order);
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for i in 0..columns {
+                for j in i + 1..columns {
+                    order.swap(i, j);
+                    let candidate = score(&order);
+                    if candidate > best_score {
+                        best_score = candidate;
+                        improved = true;
+                    } else {
+                        order.swap(i, j);
+                    }
+                }
+            }
+        }
+        order
+    };
+
+    let plaintext = untranspose(&letters, &best).into_iter().collect();
+    (best, plaintext)
+}
+
+/// Every ordering of `0..n`
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![Vec::new()];
+    }
+    permutations(n - 1)
+        .into_iter()
+        .flat_map(|shorter| {
+            (0..n).map(move |position| {
+                let mut order = shorter.clone();
+                order.insert(position, n - 1);
+                order
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_transposition_with_irregular_columns() {
+        let cipher = Columnar::new("ZEBRAS").unwrap();
+        let ciphertext = cipher.encrypt("We are discovered. Flee at once!").unwrap();
+        assert_eq!(ciphertext, "EVLNACDTESEAROFODEECWIREE");
+        assert_eq!(cipher.decrypt(&ciphertext).unwrap(), "WEAREDISCOVEREDFLEEATONCE");
+        assert!(Columnar::new("A").is_err());
+    }
+
+    #[test]
+    fn double_transposition_round_trip() {
+        let double = Columnar::new("ZEBRAS STRIPE").unwrap();
+        let ciphertext = double.encrypt("WEAREDISCOVEREDFLEEATONCE").unwrap();
+        assert_ne!(ciphertext, Columnar::new("ZEBRAS").unwrap().encrypt("WEAREDISCOVEREDFLEEATONCE").unwrap());
+        assert_eq!(double.decrypt(&ciphertext).unwrap(), "WEAREDISCOVEREDFLEEATONCE");
+    }
+
+    #[test]
+    fn anagramming_recovers_the_column_order() {
+        let plaintext = "THEREISNOTHINGEITHERGOODORBADBUTTHINKINGMAKESITSOANDTHATISTHEQUESTION";
+        let cipher = Columnar::new("CIPHERS").unwrap();
+        let (order, recovered) = crack(&cipher.encrypt(plaintext).unwrap(), 7, Language::English);
+        assert_eq!(recovered, plaintext);
+        assert_eq!(order, column_order("CIPHERS"));
+        assert_eq!(order_keyword(&order), "ADECBFG");
+    }
+}
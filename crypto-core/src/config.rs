@@ -0,0 +1,399 @@
+//! Settings shared by every tool, layered: built-in defaults, then
+//! `crypto.toml`, then environment variables, then command-line flags, each
+//! overriding the one before. The file is `$CRYPTO_CONFIG` if set, else
+//! `crypto.toml` in the working directory when there is one. A tool can add
+//! its own file on top of it, such as `pki.toml` for `pki`.
+//!
+//! Secrets (passphrases, tokens, API keys) are deliberately not settings:
+//! they come from their own environment variables or flags only, so they
+//! never end up in a file that gets committed or shared.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// One setting: its dotted name (`[section]` and key in the file), the
+/// environment variable that overrides the file, and the built-in default.
+/// An empty default means unset.
+pub struct Setting {
+    pub key: &'static str,
+    pub env: &'static str,
+    pub default: &'static str,
+    pub help: &'static str,
+}
+
+pub const SETTINGS: &[Setting] = &[
+    Setting { key: "output.format", env: "CRYPTO_OUTPUT", default: "text", help: "text or json" },
+    Setting { key: "log.format", env: "CRYPTO_LOG_FORMAT", default: "text", help: "text or json" },
+    Setting { key: "ui.lang", env: "CRYPTO_LANG", default: "", help: "en or ro; unset follows the locale" },
+    Setting {
+        key: "ciphers.alphabet",
+        env: "CRYPTO_ALPHABET",
+        default: "latin",
+        help: "latin or romanian: the Hill alphabet and the language cracking assumes",
+    },
+    Setting { key: "keys.dir", env: "CRYPTO_KEY_DIR", default: ".", help: "where relative key file paths are resolved" },
+    Setting { key: "crypto.pki_bin", env: "CRYPTO_PKI_BIN", default: "", help: "the pki executable `crypto pki` runs" },
+    Setting { key: "serve.address", env: "CRYPTO_SERVE_ADDRESS", default: "127.0.0.1:8080", help: "where `crypto serve` listens" },
+    Setting {
+        key: "quiz.results",
+        env: "CRYPTO_QUIZ_RESULTS",
+        default: "quiz-results.csv",
+        help: "the CSV file `crypto quiz` appends scores to",
+    },
+    Setting { key: "vault.path", env: "CRYPTO_VAULT", default: "crypto.vault", help: "the key vault `crypto vault` keeps" },
+    Setting { key: "pki.ca_dir", env: "PKI_CA_DIR", default: "./pki/ca", help: "CA key, certificate, CRL and database" },
+    Setting { key: "pki.users_dir", env: "PKI_USERS_DIR", default: "./pki/users", help: "user keys, CSRs and certificates" },
+    Setting { key: "pki.ca_subject", env: "PKI_CA_SUBJECT", default: "/CN=DotUnity CA/O=DotCompany/OU=IT Department", help: "subject of a new CA certificate, as /CN=.../O=.../OU=.../L=.../ST=.../C=.../emailAddress=..." },
+    Setting { key: "pki.user_subject", env: "PKI_USER_SUBJECT", default: "/O=MyOrganization", help: "subject fields of user certificates; the CN is the username unless given" },
+    Setting { key: "pki.artifact_format", env: "PKI_ARTIFACT_FORMAT", default: "pem", help: "der to also write a DER copy of every certificate, request and CRL" },
+    Setting { key: "pki.ca_key_algorithm", env: "PKI_CA_KEY_ALGORITHM", default: "rsa", help: "rsa, ecdsa-p256, ecdsa-p384 or ed25519 for a new CA key" },
+    Setting { key: "pki.user_key_algorithm", env: "PKI_USER_KEY_ALGORITHM", default: "rsa", help: "rsa, ecdsa-p256, ecdsa-p384 or ed25519 for new user keys" },
+    Setting { key: "pki.ca_key_bits", env: "PKI_CA_KEY_BITS", default: "4096", help: "RSA size of a new CA key" },
+    Setting { key: "pki.user_key_bits", env: "PKI_USER_KEY_BITS", default: "2048", help: "RSA size of new user keys" },
+    Setting { key: "pki.ca_validity_days", env: "PKI_CA_VALIDITY_DAYS", default: "3650", help: "lifetime of a new CA certificate" },
+    Setting { key: "pki.user_validity_days", env: "PKI_USER_VALIDITY_DAYS", default: "365", help: "lifetime of issued certificates" },
+    Setting { key: "pki.backend", env: "PKI_BACKEND", default: "openssl", help: "openssl, or native to make keys, requests, certificates and signatures in-process" },
+    Setting { key: "pki.digest", env: "PKI_DIGEST", default: "", help: "signing digest, as for --digest" },
+    Setting { key: "pki.encrypt_ca_key", env: "PKI_ENCRYPT_CA_KEY", default: "false", help: "encrypt a new CA key under PKI_CA_PASSPHRASE" },
+    Setting { key: "pki.ca_pkcs11_uri", env: "PKI_CA_PKCS11_URI", default: "", help: "keep the CA key in this PKCS#11 token" },
+    Setting { key: "pki.tsa_url", env: "PKI_TSA_URL", default: "", help: "RFC 3161 timestamp authority" },
+    Setting { key: "pki.tsa_ca_file", env: "PKI_TSA_CA_FILE", default: "", help: "CA certificate of the timestamp authority" },
+    Setting { key: "pki.crl_url", env: "PKI_CRL_URL", default: "", help: "CRL distribution point in issued certificates" },
+    Setting { key: "pki.ocsp_url", env: "PKI_OCSP_URL", default: "", help: "OCSP responder in issued certificates" },
+    Setting { key: "pki.ca_issuers_url", env: "PKI_CA_ISSUERS_URL", default: "", help: "CA certificate URL in issued certificates" },
+    Setting { key: "pki.csr_san_patterns", env: "PKI_CSR_SAN_PATTERNS", default: "", help: "subject alternative names external CSRs may request, comma-separated, such as DNS:*.example.com" },
+    Setting { key: "pki.listen", env: "PKI_LISTEN", default: ":8443", help: "where `pki serve` listens" },
+];
+
+/// Which layer a value came from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    File(PathBuf),
+    Env(&'static str),
+    Flag,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::Env(name) => write!(f, "${}", name),
+            Source::Flag => write!(f, "command line"),
+        }
+    }
+}
+
+/// The value of every setting and where it came from
+#[derive(Clone, Debug)]
+pub struct Config {
+    values: BTreeMap<&'static str, (String, Source)>,
+}
+
+impl Config {
+    /// Built-in defaults only
+    pub fn defaults() -> Self {
+        let values = SETTINGS.iter().map(|setting| (setting.key, (setting.default.to_string(), Source::Default))).collect();
+        Config { values }
+    }
+
+    /// Defaults, then the config file if there is one, then the environment
+    pub fn load() -> io::Result<Self> {
+        let mut config = Config::defaults();
+        if let Some(path) = config_path() {
+            config.merge_file(&path)?;
+        }
+        config.merge_env(|name| env::var(name).ok());
+        Ok(config)
+    }
+
+    /// Like [`Config::load`], with the file of `tool` (`$PKI_CONFIG` or
+    /// `pki.toml` for `pki`) between the shared file and the environment
+    pub fn load_for(tool: &str) -> io::Result<Self> {
+        let mut config = Config::defaults();
+        if let Some(path) = config_path() {
+            config.merge_file(&path)?;
+        }
+        if let Some(path) = tool_config_path(tool) {
+            config.merge_tool_file(&path, tool)?;
+        }
+        config.merge_env(|name| env::var(name).ok());
+        Ok(config)
+    }
+
+    pub fn merge_file(&mut self, path: &Path) -> io::Result<()> {
+        self.merge_toml(&read_file(path)?, Source::File(path.to_path_buf()))
+    }
+
+    /// Merge a tool's own file, where keys outside any `[section]` are
+    /// settings of `section` (`ca_key_bits` is `pki.ca_key_bits`)
+    pub fn merge_tool_file(&mut self, path: &Path, section: &str) -> io::Result<()> {
+        self.merge_assignments(&read_file(path)?, Source::File(path.to_path_buf()), Some(section))
+    }
+
+    /// Take every `[section]` `key = value` in `text`. Unknown settings are
+    /// errors, so a misspelt key is not silently ignored.
+    pub fn merge_toml(&mut self, text: &str, source: Source) -> io::Result<()> {
+        self.merge_assignments(text, source, None)
+    }
+
+    fn merge_assignments(&mut self, text: &str, source: Source, section: Option<&str>) -> io::Result<()> {
+        for (line, key, value) in parse_toml(text).map_err(|e| invalid(format!("{}: {}", source, e)))? {
+            let key = match section {
+                Some(section) if !key.contains('.') => format!("{}.{}", section, key),
+                _ => key,
+            };
+            let Some(setting) = SETTINGS.iter().find(|setting| setting.key == key) else {
+                return Err(invalid(format!("{}:{}: unknown setting {}", source, line, key)));
+            };
+            self.values.insert(setting.key, (value, source.clone()));
+        }
+        Ok(())
+    }
+
+    /// Override with the environment variables that are set, `var` looking
+    /// one up by name
+    pub fn merge_env(&mut self, var: impl Fn(&str) -> Option<String>) {
+        for setting in SETTINGS {
+            if let Some(value) = var(setting.env) {
+                self.values.insert(setting.key, (value, Source::Env(setting.env)));
+            }
+        }
+    }
+
+    /// Override with a command-line flag
+    pub fn set(&mut self, key: &str, value: &str) {
+        let (key, _) = self.entry(key);
+        self.values.insert(key, (value.to_string(), Source::Flag));
+    }
+
+    /// Override with a `--set <key>=<value>` flag; a key without a section
+    /// belongs to `section`
+    pub fn set_assignment(&mut self, assignment: &str, section: &str) -> io::Result<()> {
+        let Some((key, value)) = assignment.split_once('=') else {
+            return Err(invalid(format!("Expected <key>=<value>, not {}", assignment)));
+        };
+        let key = key.trim();
+        let key = if key.contains('.') { key.to_string() } else { format!("{}.{}", section, key) };
+        let Some(setting) = SETTINGS.iter().find(|setting| setting.key == key) else {
+            return Err(invalid(format!("unknown setting {}", key)));
+        };
+        self.values.insert(setting.key, (value.trim().to_string(), Source::Flag));
+        Ok(())
+    }
+
+    /// The value, or `None` when it is empty (unset)
+    pub fn get(&self, key: &str) -> Option<&str> {
+        let (_, (value, _)) = self.entry(key);
+        Some(value.as_str()).filter(|value| !value.is_empty())
+    }
+
+    /// The value parsed, with an error naming the setting and its source
+    pub fn parse<T: FromStr>(&self, key: &str) -> io::Result<T>
+    where
+        T::Err: fmt::Display,
+    {
+        let (key, (value, source)) = self.entry(key);
+        value.parse().map_err(|e| invalid(format!("Invalid {} {:?} (from {}): {}", key, value, source, e)))
+    }
+
+    pub fn source(&self, key: &str) -> &Source {
+        &self.entry(key).1 .1
+    }
+
+    /// Every setting in name order, with its value and source
+    pub fn entries(&self) -> impl Iterator<Item = (&'static str, &str, &Source)> {
+        self.values.iter().map(|(key, (value, source))| (*key, value.as_str(), source))
+    }
+
+    fn entry(&self, key: &str) -> (&'static str, &(String, Source)) {
+        let (key, entry) = self.values.get_key_value(key).unwrap_or_else(|| panic!("no setting named {}", key));
+        (*key, entry)
+    }
+}
+
+/// `$CRYPTO_CONFIG`, or `crypto.toml` in the working directory if it exists
+pub fn config_path() -> Option<PathBuf> {
+    match env::var_os("CRYPTO_CONFIG") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => Some(PathBuf::from("crypto.toml")).filter(|path| path.is_file()),
+    }
+}
+
+/// `$<TOOL>_CONFIG`, or `<tool>.toml` in the working directory if it exists
+pub fn tool_config_path(tool: &str) -> Option<PathBuf> {
+    match env::var_os(format!("{}_CONFIG", tool.to_uppercase())) {
+        Some(path) => Some(PathBuf::from(path)),
+        None => Some(PathBuf::from(format!("{}.toml", tool))).filter(|path| path.is_file()),
+    }
+}
+
+fn read_file(path: &Path) -> io::Result<String> {
+    fs::read_to_string(path).map_err(|e| io::Error::new(e.kind(), format!("Failed to read {}: {}", path.display(), e)))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// The part of TOML settings need: `[section]` headers, `key = value` with
+/// a string, integer or boolean value, and `#` comments. Returns the line,
+/// dotted key and value of each assignment.
+fn parse_toml(text: &str) -> Result<Vec<(usize, String, String)>, String> {
+    let mut section = String::new();
+    let mut assignments = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            if name.is_empty() || !name.split('.').all(is_bare_key) {
+                return Err(format!("line {}: invalid table name [{}]", number, name));
+            }
+            section = name.to_string();
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("line {}: expected key = value", number));
+        };
+        let key = key.trim();
+        if !key.split('.').all(is_bare_key) {
+            return Err(format!("line {}: invalid key {}", number, key));
+        }
+        let value = parse_value(value.trim()).ok_or_else(|| format!("line {}: expected a string, integer or boolean", number))?;
+        let key = if section.is_empty() { key.to_string() } else { format!("{}.{}", section, key) };
+        assignments.push((number, key, value));
+    }
+
+    Ok(assignments)
+}
+
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Drop a `#` comment, leaving any `#` inside a string alone
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (position, c) in line.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => return &line[..position],
+            _ => {}
+        }
+        escaped = false;
+    }
+    line
+}
+
+fn parse_value(value: &str) -> Option<String> {
+    if let Some(literal) = value.strip_prefix('\'').and_then(|rest| rest.strip_suffix('\'')) {
+        return (!literal.contains('\'')).then(|| literal.to_string());
+    }
+    if let Some(basic) = value.strip_prefix('"').and_then(|rest| rest.strip_suffix('"')) {
+        let mut unescaped = String::new();
+        let mut chars = basic.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return None,
+                '\\' => unescaped.push(match chars.next()? {
+                    '"' => '"',
+                    '\\' => '\\',
+                    'n' => '\n',
+                    't' => '\t',
+                    _ => return None,
+                }),
+                c => unescaped.push(c),
+            }
+        }
+        return Some(unescaped);
+    }
+    if value == "true" || value == "false" {
+        return Some(value.to_string());
+    }
+    let digits = value.strip_prefix(['+', '-']).unwrap_or(value).replace('_', "");
+    (!digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())).then(|| value.replace(['_', '+'], ""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn later_layers_override_earlier_ones() {
+        let mut config = Config::defaults();
+        assert_eq!(config.get("pki.ca_dir"), Some("./pki/ca"));
+        assert_eq!(config.get("pki.tsa_url"), None);
+
+        let file = "# course lab\n[pki]\nca_dir = \"/srv/ca\" # shared\nca_key_bits = 3_072\n\n[output]\nformat = 'json'\n";
+        config.merge_toml(file, Source::File(PathBuf::from("crypto.toml"))).unwrap();
+        config.merge_env(|name| (name == "PKI_CA_KEY_BITS").then(|| String::from("2048")));
+        config.set("output.format", "text");
+
+        assert_eq!(config.get("pki.ca_dir"), Some("/srv/ca"));
+        assert_eq!(config.source("pki.ca_dir"), &Source::File(PathBuf::from("crypto.toml")));
+        assert_eq!(config.parse::<u32>("pki.ca_key_bits").unwrap(), 2048);
+        assert_eq!(config.source("pki.ca_key_bits"), &Source::Env("PKI_CA_KEY_BITS"));
+        assert_eq!((config.get("output.format"), config.source("output.format")), (Some("text"), &Source::Flag));
+    }
+
+    #[test]
+    fn bad_files_and_values_name_where_they_came_from() {
+        let mut config = Config::defaults();
+        let source = || Source::File(PathBuf::from("crypto.toml"));
+
+        let error = config.merge_toml("[pki]\nca_dri = \"x\"", source()).unwrap_err();
+        assert_eq!(error.to_string(), "crypto.toml:2: unknown setting pki.ca_dri");
+        assert!(config.merge_toml("[pki\n", source()).is_err());
+        assert!(config.merge_toml("pki.ca_dir = [1, 2]", source()).is_err());
+        assert!(config.merge_toml("pki.ca_dir = \"unterminated", source()).is_err());
+
+        config.merge_toml("pki.ca_key_bits = \"lots\"", source()).unwrap();
+        let error = config.parse::<u32>("pki.ca_key_bits").unwrap_err();
+        assert!(error.to_string().starts_with("Invalid pki.ca_key_bits \"lots\" (from crypto.toml)"));
+    }
+
+    #[test]
+    fn tool_files_and_flags_default_to_the_tool_section() {
+        let mut config = Config::defaults();
+        config.merge_toml("[pki]\nca_key_bits = 3072\nuser_key_bits = 3072", Source::File(PathBuf::from("crypto.toml"))).unwrap();
+        let file = "ca_key_bits = 2048\nusers_dir = \"people\"\n\n[output]\nformat = \"json\"\n";
+        config.merge_assignments(file, Source::File(PathBuf::from("pki.toml")), Some("pki")).unwrap();
+
+        assert_eq!(config.get("pki.ca_key_bits"), Some("2048"));
+        assert_eq!(config.source("pki.ca_key_bits"), &Source::File(PathBuf::from("pki.toml")));
+        assert_eq!(config.get("pki.user_key_bits"), Some("3072"));
+        assert_eq!(config.get("pki.users_dir"), Some("people"));
+        assert_eq!(config.get("output.format"), Some("json"));
+
+        config.set_assignment("user_validity_days=30", "pki").unwrap();
+        config.set_assignment("output.format = text", "pki").unwrap();
+        assert_eq!((config.get("pki.user_validity_days"), config.source("pki.user_validity_days")), (Some("30"), &Source::Flag));
+        assert_eq!(config.get("output.format"), Some("text"));
+        assert!(config.set_assignment("user_validity_days", "pki").is_err());
+        assert!(config.set_assignment("ca_dri=x", "pki").is_err());
+    }
+
+    #[test]
+    fn strings_keep_hashes_and_escapes() {
+        let parsed = parse_toml("url = \"http://ca/#crl\" # comment\npath = 'C:\\keys'\nquote = \"say \\\"hi\\\"\"").unwrap();
+        let values: Vec<&str> = parsed.iter().map(|(_, _, value)| value.as_str()).collect();
+        assert_eq!(values, ["http://ca/#crl", "C:\\keys", "say \"hi\""]);
+    }
+}
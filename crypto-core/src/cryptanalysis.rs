@@ -0,0 +1,277 @@
+//! Frequency analysis for any ciphertext the classical ciphers produce:
+//! monogram, bigram and trigram counts, the index of coincidence and
+//! chi-squared distances from a language model, plus a first guess at the
+//! kind of cipher from how those statistics moved away from the language
+
+use std::sync::OnceLock;
+
+use crate::language::{Alphabet, ENGLISH_FREQUENCIES, ENGLISH_SAMPLE};
+
+/// Expected relative frequencies of the letters, letter pairs and triples
+/// of a language, indexed like base-`size` numbers (`first * size + second`)
+#[derive(Clone, Debug)]
+pub struct LanguageModel {
+    pub name: String,
+    pub alphabet: Alphabet,
+    ngrams: [Vec<f64>; 3],
+}
+
+impl LanguageModel {
+    /// English letter frequencies, with pairs and triples counted from
+    /// [`ENGLISH_SAMPLE`]
+    pub fn english() -> &'static Self {
+        static MODEL: OnceLock<LanguageModel> = OnceLock::new();
+        MODEL.get_or_init(|| {
+            let mut model = Self::from_sample("english", ENGLISH_SAMPLE, Alphabet::Latin);
+            model.ngrams[0] = ENGLISH_FREQUENCIES.to_vec();
+            model
+        })
+    }
+
+    /// Every letter equally likely, as in random text
+    pub fn uniform(alphabet: Alphabet) -> Self {
+        let size = alphabet.size();
+        let ngrams = [1, 2, 3].map(|n| vec![1.0 / size.pow(n) as f64; size.pow(n)]);
+        LanguageModel { name: "uniform".to_string(), alphabet, ngrams }
+    }
+
+    /// Count the n-grams of a sample text. Unseen ones get half a count,
+    /// so that they are rare rather than impossible.
+    pub fn from_sample(name: &str, sample: &str, alphabet: Alphabet) -> Self {
+        let letters = letters(sample, alphabet);
+        let ngrams = [1, 2, 3].map(|n| {
+            let counts = ngram_counts(&letters, n, alphabet.size());
+            let total = counts.iter().sum::<usize>() as f64 + 0.5 * counts.len() as f64;
+            counts.iter().map(|&count| (count as f64 + 0.5) / total).collect()
+        });
+        LanguageModel { name: name.to_string(), alphabet, ngrams }
+    }
+
+    /// Expected frequencies of the `n`-grams, for `n` from 1 to 3
+    pub fn frequencies(&self, n: usize) -> &[f64] {
+        &self.ngrams[n - 1]
+    }
+
+    /// Probability that two letters of the language are the same
+    pub fn index_of_coincidence(&self) -> f64 {
+        self.ngrams[0].iter().map(|p| p * p).sum()
+    }
+
+    /// Average log probability of the letter pairs of `letters`
+    fn bigram_score(&self, letters: &[usize]) -> f64 {
+        let size = self.alphabet.size();
+        let pairs = self.frequencies(2);
+        let total: f64 = letters.windows(2).map(|pair| pairs[pair[0] * size + pair[1]].ln()).sum();
+        total / letters.len().saturating_sub(1).max(1) as f64
+    }
+
+    /// Bigram score halfway between the language and its letters in random
+    /// order, i.e. between a plaintext and a transposition of it
+    fn bigram_threshold(&self) -> f64 {
+        let (size, letters, pairs) = (self.alphabet.size(), self.frequencies(1), self.frequencies(2));
+        let language: f64 = pairs.iter().map(|p| p * p.ln()).sum::<f64>() / pairs.iter().sum::<f64>();
+        let shuffled: f64 = (0..size * size).map(|i| letters[i / size] * letters[i % size] * pairs[i].ln()).sum();
+        (language + shuffled) / 2.0
+    }
+}
+
+/// The positions in `alphabet` of the letters of `text`; other characters
+/// are dropped
+pub fn letters(text: &str, alphabet: Alphabet) -> Vec<usize> {
+    text.chars().filter_map(|c| alphabet.index(c)).collect()
+}
+
+/// How often each `n`-gram of consecutive letters occurs, indexed like
+/// [`LanguageModel::frequencies`]
+pub fn ngram_counts(letters: &[usize], n: usize, size: usize) -> Vec<usize> {
+    let mut counts = vec![0; size.pow(n as u32)];
+    for window in letters.windows(n) {
+        counts[window.iter().fold(0, |index, &letter| index * size + letter)] += 1;
+    }
+    counts
+}
+
+/// Probability that two letters drawn from the text are the same
+pub fn index_of_coincidence(letters: &[usize], size: usize) -> f64 {
+    if letters.len() < 2 {
+        return 0.0;
+    }
+    let pairs: usize = ngram_counts(letters, 1, size).iter().map(|&count| count * count.saturating_sub(1)).sum();
+    pairs as f64 / (letters.len() * (letters.len() - 1)) as f64
+}
+
+/// Chi-squared distance between the `n`-gram counts of `letters` and the
+/// model's expectation; lower is closer to the language
+pub fn chi_squared(letters: &[usize], n: usize, model: &LanguageModel) -> f64 {
+    let counts = ngram_counts(letters, n, model.alphabet.size());
+    let total: usize = counts.iter().sum();
+    counts
+        .iter()
+        .zip(model.frequencies(n))
+        .map(|(&count, frequency)| {
+            let expected = frequency * total as f64;
+            (count as f64 - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+/// One row of a frequency table
+#[derive(Clone, Debug, PartialEq)]
+pub struct NgramCount {
+    pub ngram: String,
+    pub count: usize,
+    /// Share of all n-grams in the text
+    pub frequency: f64,
+    /// Share expected from the language model
+    pub expected: f64,
+}
+
+/// The most frequent `n`-grams, most frequent first
+pub fn top_ngrams(letters: &[usize], n: usize, model: &LanguageModel, limit: usize) -> Vec<NgramCount> {
+    let size = model.alphabet.size();
+    let counts = ngram_counts(letters, n, size);
+    let total = counts.iter().sum::<usize>().max(1) as f64;
+
+    let mut indices: Vec<usize> = (0..counts.len()).filter(|&i| counts[i] > 0).collect();
+    indices.sort_by(|&a, &b| counts[b].cmp(&counts[a]).then(a.cmp(&b)));
+    indices
+        .into_iter()
+        .take(limit)
+        .map(|index| NgramCount {
+            ngram: (0..n).rev().map(|position| model.alphabet.letter(index / size.pow(position as u32) % size)).collect(),
+            count: counts[index],
+            frequency: counts[index] as f64 / total,
+            expected: model.frequencies(n)[index],
+        })
+        .collect()
+}
+
+/// What the statistics suggest about how the text was made
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Assessment {
+    TooShort,
+    /// The language itself: letters and their order both match
+    Plaintext,
+    /// The language's letters in another order (rail fence, columnar)
+    Transposition,
+    /// Every letter shifted by the same amount
+    Caesar { shift: usize },
+    /// One fixed letter-for-letter substitution
+    Substitution,
+    /// Letter frequencies flattened by several alphabets or by enciphering
+    /// pairs (Vigenère, Hill, Playfair)
+    Polyalphabetic,
+}
+
+impl Assessment {
+    pub fn description(self) -> String {
+        match self {
+            Self::TooShort => crate::tr!("too few letters to tell").to_string(),
+            Self::Plaintext => crate::tr!("plaintext: letter frequencies and pairs both fit the language").to_string(),
+            Self::Transposition => crate::tr!("transposition: the language's letter frequencies, but not its letter pairs").to_string(),
+            Self::Caesar { shift } => crate::tr!("Caesar shift {}: shifting back fits the language's letter frequencies", shift),
+            Self::Substitution => crate::tr!("monoalphabetic substitution: a language-like index of coincidence, but other letters").to_string(),
+            Self::Polyalphabetic => crate::tr!("polyalphabetic or polygraphic (Vigenère, Hill, Playfair): flattened letter frequencies").to_string(),
+        }
+    }
+}
+
+/// Everything `crypto analyze` reports about a text
+#[derive(Clone, Debug)]
+pub struct Analysis {
+    pub letters: usize,
+    pub index_of_coincidence: f64,
+    /// Chi-squared distance from the model for monograms, bigrams and trigrams
+    pub chi_squared: [f64; 3],
+    /// Average log probability of the letter pairs under the model
+    pub bigram_score: f64,
+    /// The most frequent monograms, bigrams and trigrams
+    pub top: [Vec<NgramCount>; 3],
+    pub assessment: Assessment,
+}
+
+/// Fewer letters than this leave the statistics too noisy to judge
+const MINIMUM_LETTERS: usize = 20;
+
+/// Monogram chi-squared per letter below which the letters count as the
+/// language's; real text stays well under it, substitutions far above
+const LANGUAGE_CHI_SQUARED_PER_LETTER: f64 = 0.5;
+
+pub fn analyze(text: &str, model: &LanguageModel, limit: usize) -> Analysis {
+    let size = model.alphabet.size();
+    let letters = letters(text, model.alphabet);
+    let index_of_coincidence = index_of_coincidence(&letters, size);
+    let bigram_score = model.bigram_score(&letters);
+
+    let assessment = if letters.len() < MINIMUM_LETTERS {
+        Assessment::TooShort
+    } else if index_of_coincidence < (model.index_of_coincidence() + 1.0 / size as f64) / 2.0 {
+        Assessment::Polyalphabetic
+    } else {
+        let fits = |shift: usize| {
+            let shifted: Vec<usize> = letters.iter().map(|&letter| (letter + size - shift) % size).collect();
+            chi_squared(&shifted, 1, model) / letters.len() as f64
+        };
+        let best_shift = (0..size).min_by(|&a, &b| fits(a).total_cmp(&fits(b))).unwrap_or(0);
+        match (best_shift, fits(best_shift) < LANGUAGE_CHI_SQUARED_PER_LETTER) {
+            (_, false) => Assessment::Substitution,
+            (0, true) if bigram_score > model.bigram_threshold() => Assessment::Plaintext,
+            (0, true) => Assessment::Transposition,
+            (shift, true) => Assessment::Caesar { shift },
+        }
+    };
+
+    Analysis {
+        letters: letters.len(),
+        index_of_coincidence,
+        chi_squared: [1, 2, 3].map(|n| chi_squared(&letters, n, model)),
+        bigram_score,
+        top: [1, 2, 3].map(|n| top_ngrams(&letters, n, model, limit)),
+        assessment,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::columnar::Columnar;
+    use crate::vigenere::Vigenere;
+    use crate::ClassicalCipher;
+
+    const PLAINTEXT: &str = "Cryptanalysis is the study of analysing information systems in order to discover \
+                             their hidden aspects. It is used to breach cryptographic security systems and gain \
+                             access to the contents of encrypted messages, even if the key is unknown.";
+
+    fn assess(text: &str) -> Assessment {
+        analyze(text, LanguageModel::english(), 5).assessment
+    }
+
+    #[test]
+    fn counts_and_coincidence() {
+        let letters = letters("abAB, c!", Alphabet::Latin);
+        assert_eq!(letters, [0, 1, 0, 1, 2]);
+        assert_eq!(ngram_counts(&letters, 2, 26)[1], 2);
+        assert!((index_of_coincidence(&letters, 26) - 0.2).abs() < 1e-12);
+
+        let top = top_ngrams(&letters, 2, LanguageModel::english(), 1);
+        assert_eq!((top[0].ngram.as_str(), top[0].count), ("AB", 2));
+        assert!((LanguageModel::uniform(Alphabet::Latin).index_of_coincidence() - 1.0 / 26.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn tells_the_classical_ciphers_apart() {
+        assert_eq!(assess(PLAINTEXT), Assessment::Plaintext);
+        assert_eq!(assess(&crate::caesar::Caesar::new("3").unwrap().encrypt(PLAINTEXT).unwrap()), Assessment::Caesar { shift: 3 });
+        assert_eq!(assess(&Columnar::new("ZEBRAS").unwrap().encrypt(PLAINTEXT).unwrap()), Assessment::Transposition);
+        assert_eq!(assess(&Vigenere::new("LEMON").unwrap().encrypt(PLAINTEXT).unwrap()), Assessment::Polyalphabetic);
+        assert_eq!(assess("short"), Assessment::TooShort);
+
+        // The keyboard as the cipher alphabet: letters swapped, not shifted
+        let key = "QWERTYUIOPASDFGHJKLZXCVBNM";
+        let substituted: String = PLAINTEXT
+            .chars()
+            .map(|c| crate::language::letter_index(c).map_or(c, |i| key.as_bytes()[i] as char))
+            .collect();
+        assert_eq!(assess(&substituted), Assessment::Substitution);
+    }
+}
@@ -0,0 +1,147 @@
+//! Finite-field Diffie–Hellman: both parties publish gˣ mod p and each
+//! raises the other's value to its own secret exponent
+
+use std::io;
+
+use num_bigint::BigUint;
+use num_traits::One;
+
+use crate::cipher::CipherError;
+use crate::hash::sha256;
+use crate::modular::mod_pow;
+use crate::primes::{random_range, safe_prime};
+
+/// A safe prime p = 2q + 1 and a generator g of the subgroup of prime order q
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DhParameters {
+    pub p: BigUint,
+    pub g: BigUint,
+}
+
+/// A secret exponent and the public value gˣ mod p
+#[derive(Clone, Debug)]
+pub struct DhKeyPair {
+    pub private: BigUint,
+    pub public: BigUint,
+}
+
+impl DhParameters {
+    /// Fresh parameters with a `bits`-bit safe prime. Squaring a random
+    /// element lands in the order-q subgroup, so g = h² (≠ 1) generates it.
+    pub fn generate(bits: u64) -> io::Result<Self> {
+        let p = safe_prime(bits)?;
+        let two = BigUint::from(2u32);
+        loop {
+            let h = random_range(&two, &(&p - 1u32))?;
+            let g = mod_pow(&h, &two, &p);
+            if !g.is_one() {
+                return Ok(DhParameters { p, g });
+            }
+        }
+    }
+
+    /// Order of the subgroup g generates, (p − 1) / 2
+    pub fn q(&self) -> BigUint {
+        (&self.p - 1u32) >> 1u32
+    }
+
+    pub fn generate_key_pair(&self) -> io::Result<DhKeyPair> {
+        let private = random_range(&BigUint::from(2u32), &self.q())?;
+        let public = mod_pow(&self.g, &private, &self.p);
+        Ok(DhKeyPair { private, public })
+    }
+
+    /// Check a public value from the other party: 1 < y < p − 1 and in the
+    /// subgroup (yᑫ ≡ 1), which rules out the small-subgroup values 1 and p − 1
+    pub fn validate_public(&self, public: &BigUint) -> Result<(), CipherError> {
+        let in_range = public > &BigUint::one() && public < &(&self.p - 1u32);
+        if in_range && mod_pow(public, &self.q(), &self.p).is_one() {
+            Ok(())
+        } else {
+            Err(CipherError::InvalidInput(String::from("the public value is not in the DH subgroup")))
+        }
+    }
+
+    /// (gʸ)ˣ = gˣʸ mod p, the same number on both sides
+    pub fn shared_secret(&self, own: &DhKeyPair, other_public: &BigUint) -> Result<BigUint, CipherError> {
+        self.validate_public(other_public)?;
+        Ok(mod_pow(other_public, &own.private, &self.p))
+    }
+}
+
+/// Turn a shared secret into a symmetric key of `len` bytes (8 for DES, 16
+/// for AES) by hashing it: the secret itself is not uniformly random bits
+pub fn derive_key(secret: &BigUint, len: usize) -> Vec<u8> {
+    let mut key = sha256(&[&b"dh key"[..], &secret.to_bytes_be()].concat()).to_vec();
+    key.truncate(len);
+    key
+}
+
+/// A man in the middle swapping both public values for his own
+pub struct ManInTheMiddle {
+    /// What Alice and Bob each believe they share with the other
+    pub alice_secret: BigUint,
+    pub bob_secret: BigUint,
+    /// What Mallory shares with each of them
+    pub mallory_with_alice: BigUint,
+    pub mallory_with_bob: BigUint,
+}
+
+/// Run an exchange where Mallory intercepts both public values: Alice and Bob
+/// end up with different secrets, each known to Mallory, and nothing in
+/// unauthenticated DH tells them so
+pub fn man_in_the_middle(parameters: &DhParameters) -> io::Result<ManInTheMiddle> {
+    let invalid = |e: CipherError| io::Error::new(io::ErrorKind::InvalidData, e.to_string());
+    let alice = parameters.generate_key_pair()?;
+    let bob = parameters.generate_key_pair()?;
+    let mallory_to_alice = parameters.generate_key_pair()?;
+    let mallory_to_bob = parameters.generate_key_pair()?;
+
+    Ok(ManInTheMiddle {
+        alice_secret: parameters.shared_secret(&alice, &mallory_to_alice.public).map_err(invalid)?,
+        bob_secret: parameters.shared_secret(&bob, &mallory_to_bob.public).map_err(invalid)?,
+        mallory_with_alice: parameters.shared_secret(&mallory_to_alice, &alice.public).map_err(invalid)?,
+        mallory_with_bob: parameters.shared_secret(&mallory_to_bob, &bob.public).map_err(invalid)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn both_parties_agree_on_the_secret() {
+        let parameters = DhParameters::generate(128).unwrap();
+        assert!(mod_pow(&parameters.g, &parameters.q(), &parameters.p).is_one());
+
+        let alice = parameters.generate_key_pair().unwrap();
+        let bob = parameters.generate_key_pair().unwrap();
+        let secret = parameters.shared_secret(&alice, &bob.public).unwrap();
+        assert_eq!(secret, parameters.shared_secret(&bob, &alice.public).unwrap());
+        assert_eq!(derive_key(&secret, 16).len(), 16);
+
+        // p − 1 would force the secret into {1, p − 1}
+        assert!(parameters.shared_secret(&alice, &(&parameters.p - 1u32)).is_err());
+    }
+
+    #[test]
+    fn textbook_numbers() {
+        // p = 23 = 2·11 + 1, g = 4 of order 11
+        let parameters = DhParameters { p: 23u32.into(), g: 4u32.into() };
+        let key_pair = |private: u32| DhKeyPair {
+            private: private.into(),
+            public: mod_pow(&parameters.g, &private.into(), &parameters.p),
+        };
+        let (alice, bob) = (key_pair(6), key_pair(9));
+        assert_eq!((alice.public.clone(), bob.public.clone()), (2u32.into(), 13u32.into()));
+        assert_eq!(parameters.shared_secret(&alice, &bob.public).unwrap(), BigUint::from(6u32));
+    }
+
+    #[test]
+    fn a_man_in_the_middle_shares_both_secrets() {
+        let report = man_in_the_middle(&DhParameters::generate(128).unwrap()).unwrap();
+        assert_eq!(report.alice_secret, report.mallory_with_alice);
+        assert_eq!(report.bob_secret, report.mallory_with_bob);
+        assert_ne!(report.alice_secret, report.bob_secret);
+    }
+}
@@ -0,0 +1,227 @@
+//! DSA as in FIPS 186: ElGamal signatures moved into a subgroup of prime
+//! order q, so a signature is two N-bit numbers however large p is
+
+use std::io;
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+use crate::hash::sha256;
+use crate::modular::{mod_inverse, mod_pow, mod_sub};
+use crate::primes::{is_probable_prime, random_bits, random_prime, random_range, DEFAULT_ROUNDS};
+
+/// The (L, N) sizes FIPS 186-4 allows for new keys
+pub const STANDARD_SIZES: [(u64, u64); 3] = [(2048, 224), (2048, 256), (3072, 256)];
+
+/// p of L bits, q of N bits dividing p − 1, and g of order q
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DsaParameters {
+    pub p: BigUint,
+    pub q: BigUint,
+    pub g: BigUint,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DsaPublicKey {
+    pub parameters: DsaParameters,
+    /// y = gˣ mod p
+    pub y: BigUint,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DsaPrivateKey {
+    pub parameters: DsaParameters,
+    pub x: BigUint,
+    pub y: BigUint,
+}
+
+/// r = (gᵏ mod p) mod q and s = k⁻¹(H(m) + x·r) mod q
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DsaSignature {
+    pub r: BigUint,
+    pub s: BigUint,
+}
+
+impl DsaParameters {
+    /// Fresh parameters with an `l_bits`-bit p and an `n_bits`-bit q. After
+    /// q is found, candidates p are random L-bit numbers rounded to
+    /// 1 mod 2q; if 4L of them are composite, a new q is drawn.
+    pub fn generate(l_bits: u64, n_bits: u64) -> io::Result<Self> {
+        if n_bits < 2 || l_bits <= n_bits {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "p must have more bits than q"));
+        }
+        let top = BigUint::one() << (l_bits - 1);
+        loop {
+            let q = random_prime(n_bits)?;
+            let two_q = &q << 1u32;
+            for _ in 0..4 * l_bits {
+                let x = random_bits(l_bits - 1)? | &top;
+                let p = &x - &x % &two_q + 1u32;
+                if p.bits() == l_bits && is_probable_prime(&p, DEFAULT_ROUNDS)? {
+                    let g = Self::generator(&p, &q)?;
+                    return Ok(DsaParameters { p, q, g });
+                }
+            }
+        }
+    }
+
+    /// g = h^((p − 1)/q) mod p for random h, which has order q unless it is 1
+    fn generator(p: &BigUint, q: &BigUint) -> io::Result<BigUint> {
+        let exponent = (p - 1u32) / q;
+        loop {
+            let h = random_range(&BigUint::from(2u32), &(p - 1u32))?;
+            let g = mod_pow(&h, &exponent, p);
+            if !g.is_one() {
+                return Ok(g);
+            }
+        }
+    }
+
+    /// Whether q is prime, divides p − 1 and g has order q
+    pub fn is_valid(&self) -> io::Result<bool> {
+        let DsaParameters { p, q, g } = self;
+        Ok(((p - 1u32) % q).is_zero()
+            && g > &BigUint::one()
+            && g < p
+            && mod_pow(g, q, p).is_one()
+            && is_probable_prime(q, DEFAULT_ROUNDS)?
+            && is_probable_prime(p, DEFAULT_ROUNDS)?)
+    }
+
+    /// The leftmost N bits of SHA-256(m), as FIPS 186 truncates the hash
+    pub fn message_hash(&self, message: &[u8]) -> BigUint {
+        let hash = BigUint::from_bytes_be(&sha256(message));
+        match 256u64.checked_sub(self.q.bits()) {
+            Some(excess) => hash >> excess,
+            None => hash,
+        }
+    }
+}
+
+impl DsaPublicKey {
+    /// With w = s⁻¹: v = (g^(H·w)·y^(r·w) mod p) mod q must equal r
+    pub fn verify(&self, message: &[u8], signature: &DsaSignature) -> bool {
+        let DsaParameters { p, q, g } = &self.parameters;
+        let DsaSignature { r, s } = signature;
+        if r.is_zero() || r >= q || s.is_zero() || s >= q {
+            return false;
+        }
+        let Some(w) = mod_inverse(s, q) else {
+            return false;
+        };
+        let u1 = self.parameters.message_hash(message) * &w % q;
+        let u2 = r * &w % q;
+        let v = mod_pow(g, &u1, p) * mod_pow(&self.y, &u2, p) % p % q;
+        &v == r
+    }
+}
+
+impl DsaPrivateKey {
+    /// A key in fresh parameters of the given sizes
+    pub fn generate(l_bits: u64, n_bits: u64) -> io::Result<Self> {
+        Self::generate_in(DsaParameters::generate(l_bits, n_bits)?)
+    }
+
+    /// A key in existing parameters, which a group of users may share
+    pub fn generate_in(parameters: DsaParameters) -> io::Result<Self> {
+        let x = random_range(&BigUint::one(), &parameters.q)?;
+        let y = mod_pow(&parameters.g, &x, &parameters.p);
+        Ok(DsaPrivateKey { parameters, x, y })
+    }
+
+    pub fn public(&self) -> DsaPublicKey {
+        DsaPublicKey { parameters: self.parameters.clone(), y: self.y.clone() }
+    }
+
+    /// Sign with a fresh random k per message
+    pub fn sign(&self, message: &[u8]) -> io::Result<DsaSignature> {
+        loop {
+            let k = random_range(&BigUint::one(), &self.parameters.q)?;
+            if let Some(signature) = self.sign_with_nonce(message, &k) {
+                return Ok(signature);
+            }
+        }
+    }
+
+    /// Sign with a caller-chosen k in [1, q). Only for showing what a
+    /// repeated k gives away; `None` when r or s comes out 0 and another k
+    /// is needed.
+    pub fn sign_with_nonce(&self, message: &[u8], k: &BigUint) -> Option<DsaSignature> {
+        let DsaParameters { p, q, g } = &self.parameters;
+        let r = mod_pow(g, k, p) % q;
+        if r.is_zero() {
+            return None;
+        }
+        let s = mod_inverse(k, q)? * (self.parameters.message_hash(message) + &self.x * &r) % q;
+        (!s.is_zero()).then_some(DsaSignature { r, s })
+    }
+}
+
+/// Repeated-k attack, the one that exposed the PlayStation 3 signing key:
+/// equal r means equal k, then k = (H₁ − H₂)/(s₁ − s₂) and
+/// x = (s₁·k − H₁)/r mod q. Returns the recovered key.
+pub fn recover_key_from_repeated_nonce(
+    public: &DsaPublicKey,
+    first: (&[u8], &DsaSignature),
+    second: (&[u8], &DsaSignature),
+) -> Option<DsaPrivateKey> {
+    let (message1, signature1) = first;
+    let (message2, signature2) = second;
+    if signature1.r != signature2.r {
+        return None;
+    }
+    let parameters = &public.parameters;
+    let q = &parameters.q;
+    let (h1, h2) = (parameters.message_hash(message1), parameters.message_hash(message2));
+
+    let k = mod_sub(&h1, &h2, q) * mod_inverse(&mod_sub(&signature1.s, &signature2.s, q), q)? % q;
+    let x = mod_sub(&(&signature1.s * &k), &h1, q) * mod_inverse(&signature1.r, q)? % q;
+
+    (mod_pow(&parameters.g, &x, &parameters.p) == public.y)
+        .then(|| DsaPrivateKey { parameters: parameters.clone(), x, y: public.y.clone() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_parameters_sign_and_verify() {
+        let key = DsaPrivateKey::generate(512, 160).unwrap();
+        assert_eq!((key.parameters.p.bits(), key.parameters.q.bits()), (512, 160));
+        assert!(key.parameters.is_valid().unwrap());
+
+        let signature = key.sign(b"I owe you 10 lei").unwrap();
+        assert!(signature.r.bits() <= 160 && signature.s.bits() <= 160);
+        assert!(key.public().verify(b"I owe you 10 lei", &signature));
+        assert!(!key.public().verify(b"I owe you 99 lei", &signature));
+    }
+
+    #[test]
+    fn textbook_numbers() {
+        // p = 23, q = 11, g = 4; x = 7 gives y = 8, and k = 3 gives r = 18 mod 11
+        let key = DsaPrivateKey {
+            parameters: DsaParameters { p: 23u32.into(), q: 11u32.into(), g: 4u32.into() },
+            x: 7u32.into(),
+            y: 8u32.into(),
+        };
+        let signature = key.sign_with_nonce(b"hello", &3u32.into()).unwrap();
+        assert_eq!(signature.r, BigUint::from(7u32));
+        assert!(key.public().verify(b"hello", &signature));
+    }
+
+    #[test]
+    fn a_repeated_nonce_reveals_the_key() {
+        let key = DsaPrivateKey::generate(512, 160).unwrap();
+        let k = random_range(&BigUint::one(), &key.parameters.q).unwrap();
+        let first = key.sign_with_nonce(b"first message", &k).unwrap();
+        let second = key.sign_with_nonce(b"second message", &k).unwrap();
+
+        let recovered =
+            recover_key_from_repeated_nonce(&key.public(), (b"first message", &first), (b"second message", &second)).unwrap();
+        assert_eq!(recovered, key);
+
+        let fresh = key.sign(b"second message").unwrap();
+        assert!(recover_key_from_repeated_nonce(&key.public(), (b"first message", &first), (b"second message", &fresh)).is_none());
+    }
+}
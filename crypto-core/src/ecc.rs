@@ -0,0 +1,353 @@
+//! Elliptic curves y² = x³ + ax + b over a prime field: the chord-and-tangent
+//! group law, double-and-add scalar multiplication, and ECDSA over both a
+//! toy curve small enough to list by hand and NIST P-256
+
+use std::fmt;
+use std::io;
+
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+
+use crate::cipher::CipherError;
+use crate::hash::sha256;
+use crate::modular::{mod_inverse, mod_pow, mod_sub};
+use crate::primes::random_range;
+
+/// A point on a curve, or the point at infinity O that acts as the identity
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Point {
+    Infinity,
+    Affine(BigUint, BigUint),
+}
+
+impl Point {
+    pub fn new(x: impl Into<BigUint>, y: impl Into<BigUint>) -> Self {
+        Point::Affine(x.into(), y.into())
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Point::Infinity => write!(f, "O"),
+            Point::Affine(x, y) => write!(f, "({}, {})", x, y),
+        }
+    }
+}
+
+/// A curve with a base point G of prime order n and cofactor h
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Curve {
+    pub name: String,
+    pub p: BigUint,
+    pub a: BigUint,
+    pub b: BigUint,
+    pub g: Point,
+    pub n: BigUint,
+    pub h: u32,
+}
+
+/// One bit of a double-and-add scalar multiplication, from the top
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScalarStep {
+    pub bit: bool,
+    /// The running result after doubling, and after adding the point when
+    /// the bit is set
+    pub doubled: Point,
+    pub result: Point,
+}
+
+fn hex(text: &str) -> BigUint {
+    BigUint::parse_bytes(text.as_bytes(), 16).expect("invalid curve constant")
+}
+
+impl Curve {
+    /// y² = x³ + 2x + 2 over F₁₇ with G = (5, 1) of order 19: every point
+    /// but O is a multiple of G, so the whole group fits on one slide
+    pub fn toy() -> Self {
+        Curve {
+            name: String::from("toy"),
+            p: 17u32.into(),
+            a: 2u32.into(),
+            b: 2u32.into(),
+            g: Point::new(5u32, 1u32),
+            n: 19u32.into(),
+            h: 1,
+        }
+    }
+
+    /// NIST P-256 (secp256r1) from FIPS 186-4 D.1.2.3
+    pub fn p256() -> Self {
+        let p = hex("ffffffff00000001000000000000000000000000ffffffffffffffffffffffff");
+        Curve {
+            name: String::from("P-256"),
+            a: &p - 3u32,
+            p,
+            b: hex("5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b"),
+            g: Point::Affine(
+                hex("6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296"),
+                hex("4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5"),
+            ),
+            n: hex("ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551"),
+            h: 1,
+        }
+    }
+
+    /// A curve by name: `toy` or `p256`
+    pub fn by_name(name: &str) -> Result<Self, CipherError> {
+        match name.to_ascii_lowercase().replace('-', "").as_str() {
+            "toy" => Ok(Self::toy()),
+            "p256" | "secp256r1" | "prime256v1" => Ok(Self::p256()),
+            _ => Err(CipherError::InvalidKey(format!("Unknown curve {} (expected toy or p256)", name))),
+        }
+    }
+
+    /// Whether the cubic has distinct roots (4a³ + 27b² ≠ 0 mod p); a
+    /// singular "curve" has no group law worth the name
+    pub fn is_nonsingular(&self) -> bool {
+        let p = &self.p;
+        let discriminant = (BigUint::from(4u32) * mod_pow(&self.a, &3u32.into(), p) + BigUint::from(27u32) * &self.b * &self.b) % p;
+        !discriminant.is_zero()
+    }
+
+    pub fn contains(&self, point: &Point) -> bool {
+        match point {
+            Point::Infinity => true,
+            Point::Affine(x, y) => {
+                x < &self.p && y < &self.p && y * y % &self.p == (x * x * x + &self.a * x + &self.b) % &self.p
+            }
+        }
+    }
+
+    pub fn negate(&self, point: &Point) -> Point {
+        match point {
+            Point::Infinity => Point::Infinity,
+            Point::Affine(x, y) => Point::Affine(x.clone(), mod_sub(&BigUint::zero(), y, &self.p)),
+        }
+    }
+
+    /// P + Q: the third intersection of the chord through P and Q (the
+    /// tangent when P = Q), reflected in the x axis
+    pub fn add(&self, first: &Point, second: &Point) -> Point {
+        let p = &self.p;
+        let (x1, y1, x2, y2) = match (first, second) {
+            (Point::Infinity, other) | (other, Point::Infinity) => return other.clone(),
+            (Point::Affine(x1, y1), Point::Affine(x2, y2)) => (x1, y1, x2, y2),
+        };
+        // P + (−P) = O, which also covers doubling a point with y = 0
+        if x1 == x2 && (y1 + y2) % p == BigUint::zero() {
+            return Point::Infinity;
+        }
+
+        let slope = if x1 == x2 {
+            // Tangent: λ = (3x² + a) / 2y
+            (BigUint::from(3u32) * x1 * x1 + &self.a) * mod_inverse(&(y1 << 1u32), p).expect("p is prime") % p
+        } else {
+            // Chord: λ = (y₂ − y₁) / (x₂ − x₁)
+            mod_sub(y2, y1, p) * mod_inverse(&mod_sub(x2, x1, p), p).expect("p is prime") % p
+        };
+        let x3 = mod_sub(&mod_sub(&(&slope * &slope), x1, p), x2, p);
+        let y3 = mod_sub(&(&slope * mod_sub(x1, &x3, p)), y1, p);
+        Point::Affine(x3, y3)
+    }
+
+    pub fn double(&self, point: &Point) -> Point {
+        self.add(point, point)
+    }
+
+    /// k·P by double-and-add over the bits of k, from the top
+    pub fn multiply(&self, k: &BigUint, point: &Point) -> Point {
+        self.multiply_steps(k, point).pop().map_or(Point::Infinity, |step| step.result)
+    }
+
+    /// Each doubling and addition `multiply` makes, for tracing by hand
+    pub fn multiply_steps(&self, k: &BigUint, point: &Point) -> Vec<ScalarStep> {
+        let mut result = Point::Infinity;
+        let mut steps = Vec::new();
+        for bit in (0..k.bits()).rev().map(|index| k.bit(index)) {
+            let doubled = self.double(&result);
+            result = if bit { self.add(&doubled, point) } else { doubled.clone() };
+            steps.push(ScalarStep { bit, doubled, result: result.clone() });
+        }
+        steps
+    }
+
+    /// Every point on the curve, O first, by trying each x. Only sensible
+    /// for toy fields; the square roots are found by search.
+    pub fn points(&self) -> Vec<Point> {
+        let p = self.p.to_u64().filter(|&p| p <= 1 << 16).expect("points() is only for toy curves");
+        let mut points = vec![Point::Infinity];
+        for x in 0..p {
+            let rhs = (BigUint::from(x).pow(3) + &self.a * x + &self.b) % &self.p;
+            for y in 0..p {
+                if BigUint::from(y * y % p) == rhs {
+                    points.push(Point::new(x, y));
+                }
+            }
+        }
+        points
+    }
+
+    /// The smallest k > 0 with k·P = O
+    pub fn order_of(&self, point: &Point) -> u64 {
+        let mut multiple = point.clone();
+        let mut order = 1;
+        while multiple != Point::Infinity {
+            multiple = self.add(&multiple, point);
+            order += 1;
+        }
+        order
+    }
+
+    /// The leftmost bits of SHA-256(m), as many as n has
+    pub fn message_hash(&self, message: &[u8]) -> BigUint {
+        let hash = BigUint::from_bytes_be(&sha256(message));
+        match 256u64.checked_sub(self.n.bits()) {
+            Some(excess) => hash >> excess,
+            None => hash,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EcdsaPublicKey {
+    pub curve: Curve,
+    /// Q = d·G
+    pub q: Point,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EcdsaPrivateKey {
+    pub curve: Curve,
+    pub d: BigUint,
+    pub q: Point,
+}
+
+/// r = x(k·G) mod n and s = k⁻¹(H(m) + d·r) mod n
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EcdsaSignature {
+    pub r: BigUint,
+    pub s: BigUint,
+}
+
+impl EcdsaPublicKey {
+    /// With w = s⁻¹: the x coordinate of H·w·G + r·w·Q must be r mod n
+    pub fn verify(&self, message: &[u8], signature: &EcdsaSignature) -> bool {
+        let curve = &self.curve;
+        let EcdsaSignature { r, s } = signature;
+        if r.is_zero() || r >= &curve.n || s.is_zero() || s >= &curve.n || !curve.contains(&self.q) {
+            return false;
+        }
+        let Some(w) = mod_inverse(s, &curve.n) else {
+            return false;
+        };
+        let u1 = curve.message_hash(message) * &w % &curve.n;
+        let u2 = r * &w % &curve.n;
+        match curve.add(&curve.multiply(&u1, &curve.g), &curve.multiply(&u2, &self.q)) {
+            Point::Infinity => false,
+            Point::Affine(x, _) => &(x % &curve.n) == r,
+        }
+    }
+}
+
+impl EcdsaPrivateKey {
+    pub fn generate(curve: Curve) -> io::Result<Self> {
+        let d = random_range(&BigUint::one(), &curve.n)?;
+        Ok(Self::from_scalar(curve, d))
+    }
+
+    pub fn from_scalar(curve: Curve, d: BigUint) -> Self {
+        let q = curve.multiply(&d, &curve.g);
+        EcdsaPrivateKey { curve, d, q }
+    }
+
+    pub fn public(&self) -> EcdsaPublicKey {
+        EcdsaPublicKey { curve: self.curve.clone(), q: self.q.clone() }
+    }
+
+    pub fn sign(&self, message: &[u8]) -> io::Result<EcdsaSignature> {
+        loop {
+            let k = random_range(&BigUint::one(), &self.curve.n)?;
+            if let Some(signature) = self.sign_with_nonce(message, &k) {
+                return Ok(signature);
+            }
+        }
+    }
+
+    /// Sign with a caller-chosen k in [1, n), e.g. to replay a published
+    /// test vector; `None` when r or s is 0 and another k is needed
+    pub fn sign_with_nonce(&self, message: &[u8], k: &BigUint) -> Option<EcdsaSignature> {
+        let curve = &self.curve;
+        let Point::Affine(x, _) = curve.multiply(k, &curve.g) else {
+            return None;
+        };
+        let r = x % &curve.n;
+        if r.is_zero() {
+            return None;
+        }
+        let s = mod_inverse(k, &curve.n)? * (curve.message_hash(message) + &self.d * &r) % &curve.n;
+        (!s.is_zero()).then_some(EcdsaSignature { r, s })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toy_curve_group_law() {
+        let curve = Curve::toy();
+        assert!(curve.is_nonsingular());
+        let g = curve.g.clone();
+
+        assert_eq!(curve.double(&g), Point::new(6u32, 3u32));
+        assert_eq!(curve.add(&g, &Point::new(6u32, 3u32)), Point::new(10u32, 6u32));
+        assert_eq!(curve.add(&g, &curve.negate(&g)), Point::Infinity);
+        assert_eq!(curve.multiply(&19u32.into(), &g), Point::Infinity);
+        assert_eq!(curve.multiply(&18u32.into(), &g), curve.negate(&g));
+
+        // 19 points counting O, all multiples of G
+        let points = curve.points();
+        assert_eq!(points.len(), 19);
+        assert!(points.iter().all(|point| curve.contains(point)));
+        assert_eq!(curve.order_of(&g), 19);
+    }
+
+    #[test]
+    fn p256_base_point() {
+        let curve = Curve::p256();
+        assert!(curve.contains(&curve.g));
+        assert_eq!(curve.multiply(&curve.n, &curve.g), Point::Infinity);
+        assert_eq!(curve.multiply(&2u32.into(), &curve.g), curve.double(&curve.g));
+    }
+
+    #[test]
+    fn p256_matches_rfc_6979() {
+        // RFC 6979 A.2.5, P-256 with SHA-256, message "sample"
+        let key = EcdsaPrivateKey::from_scalar(Curve::p256(), hex("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721"));
+        assert_eq!(
+            key.q,
+            Point::Affine(
+                hex("60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6"),
+                hex("7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299"),
+            )
+        );
+        let k = hex("a6e3c57dd01abe90086538398355dd4c3b17aa873382b0f24d6129493d8aad60");
+        let signature = key.sign_with_nonce(b"sample", &k).unwrap();
+        assert_eq!(signature.r, hex("efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716"));
+        assert_eq!(signature.s, hex("f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8"));
+        assert!(key.public().verify(b"sample", &signature));
+    }
+
+    #[test]
+    fn ecdsa_round_trip_on_both_curves() {
+        for curve in [Curve::toy(), Curve::p256()] {
+            let key = EcdsaPrivateKey::generate(curve).unwrap();
+            let signature = key.sign(b"I owe you 10 lei").unwrap();
+            assert!(key.public().verify(b"I owe you 10 lei", &signature));
+        }
+        let key = EcdsaPrivateKey::generate(Curve::p256()).unwrap();
+        let signature = key.sign(b"I owe you 10 lei").unwrap();
+        assert!(!key.public().verify(b"I owe you 99 lei", &signature));
+    }
+}
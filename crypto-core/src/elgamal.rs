@@ -0,0 +1,193 @@
+//! ElGamal over the same prime-field group as Diffie–Hellman: encryption is
+//! a one-sided DH with a fresh ephemeral key per message, and the signature
+//! scheme is the one DSA later trimmed down
+
+use std::io;
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+use crate::cipher::CipherError;
+use crate::dh::DhParameters;
+use crate::hash::sha256;
+use crate::modular::{mod_inverse, mod_pow, mod_sub};
+use crate::primes::random_range;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ElGamalPublicKey {
+    pub parameters: DhParameters,
+    /// y = gˣ mod p
+    pub y: BigUint,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ElGamalPrivateKey {
+    pub parameters: DhParameters,
+    pub x: BigUint,
+    pub y: BigUint,
+}
+
+/// (c₁, c₂) = (gᵏ, m·yᵏ) mod p
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ElGamalCiphertext {
+    pub c1: BigUint,
+    pub c2: BigUint,
+}
+
+/// (r, s) with r = gᵏ mod p and s = k⁻¹(H(m) − x·r) mod q
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ElGamalSignature {
+    pub r: BigUint,
+    pub s: BigUint,
+}
+
+impl ElGamalPublicKey {
+    /// Encrypt a number below p under a fresh ephemeral exponent k
+    pub fn encrypt(&self, message: &BigUint) -> io::Result<ElGamalCiphertext> {
+        let DhParameters { p, g } = &self.parameters;
+        if message.is_zero() || message >= p {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("The message must be between 1 and p − 1 ({} bits)", p.bits()),
+            ));
+        }
+        let k = random_range(&BigUint::from(2u32), &self.parameters.q())?;
+        Ok(ElGamalCiphertext { c1: mod_pow(g, &k, p), c2: message * mod_pow(&self.y, &k, p) % p })
+    }
+
+    /// Check gᴴ⁽ᵐ⁾ ≡ yʳ·rˢ (mod p), with 0 < r < p and 0 < s < q
+    pub fn verify(&self, message: &[u8], signature: &ElGamalSignature) -> bool {
+        let DhParameters { p, g } = &self.parameters;
+        let ElGamalSignature { r, s } = signature;
+        if r.is_zero() || r >= p || s.is_zero() || s >= &self.parameters.q() {
+            return false;
+        }
+        let expected = mod_pow(g, &message_hash(&self.parameters, message), p);
+        expected == mod_pow(&self.y, r, p) * mod_pow(r, s, p) % p
+    }
+}
+
+impl ElGamalPrivateKey {
+    /// A key in freshly generated `bits`-bit parameters
+    pub fn generate(bits: u64) -> io::Result<Self> {
+        Self::generate_in(DhParameters::generate(bits)?)
+    }
+
+    /// A key in existing parameters, e.g. ones shared by a class
+    pub fn generate_in(parameters: DhParameters) -> io::Result<Self> {
+        let key_pair = parameters.generate_key_pair()?;
+        Ok(ElGamalPrivateKey { parameters, x: key_pair.private, y: key_pair.public })
+    }
+
+    pub fn public(&self) -> ElGamalPublicKey {
+        ElGamalPublicKey { parameters: self.parameters.clone(), y: self.y.clone() }
+    }
+
+    /// m = c₂·(c₁ˣ)⁻¹ mod p
+    pub fn decrypt(&self, ciphertext: &ElGamalCiphertext) -> Result<BigUint, CipherError> {
+        let p = &self.parameters.p;
+        let shared = mod_pow(&ciphertext.c1, &self.x, p);
+        let inverse = mod_inverse(&shared, p)
+            .ok_or_else(|| CipherError::InvalidInput(String::from("c₁ is not invertible modulo p")))?;
+        Ok(&ciphertext.c2 * inverse % p)
+    }
+
+    pub fn sign(&self, message: &[u8]) -> io::Result<ElGamalSignature> {
+        loop {
+            let k = random_range(&BigUint::from(2u32), &self.parameters.q())?;
+            if let Some(signature) = self.sign_with_nonce(message, &k) {
+                return Ok(signature);
+            }
+        }
+    }
+
+    /// Sign with a caller-chosen nonce k in [1, q). Only for demonstrating
+    /// what a repeated or predictable k gives away; `None` when this k
+    /// yields s = 0 and another must be drawn.
+    pub fn sign_with_nonce(&self, message: &[u8], k: &BigUint) -> Option<ElGamalSignature> {
+        let DhParameters { p, g } = &self.parameters;
+        let q = self.parameters.q();
+        let r = mod_pow(g, k, p);
+        let s = mod_inverse(k, &q)? * mod_sub(&message_hash(&self.parameters, message), &(&self.x * &r), &q) % &q;
+        (!s.is_zero()).then_some(ElGamalSignature { r, s })
+    }
+}
+
+/// H(m) = SHA-256(m) mod q, the exponent a signature vouches for
+pub fn message_hash(parameters: &DhParameters, message: &[u8]) -> BigUint {
+    BigUint::from_bytes_be(&sha256(message)) % parameters.q()
+}
+
+/// Nonce-reuse attack: two signatures sharing r were made with the same k.
+/// From s₁ − s₂ = k⁻¹(H₁ − H₂) the nonce follows, and from either
+/// signature then x = (H − k·s)·r⁻¹ mod q. Returns the recovered key.
+pub fn recover_key_from_reused_nonce(
+    public: &ElGamalPublicKey,
+    first: (&[u8], &ElGamalSignature),
+    second: (&[u8], &ElGamalSignature),
+) -> Option<ElGamalPrivateKey> {
+    let (message1, signature1) = first;
+    let (message2, signature2) = second;
+    if signature1.r != signature2.r {
+        return None;
+    }
+    let parameters = &public.parameters;
+    let q = parameters.q();
+    let (h1, h2) = (message_hash(parameters, message1), message_hash(parameters, message2));
+
+    let k = mod_sub(&h1, &h2, &q) * mod_inverse(&mod_sub(&signature1.s, &signature2.s, &q), &q)? % &q;
+    let x = mod_sub(&h1, &(&k * &signature1.s), &q) * mod_inverse(&(&signature1.r % &q), &q)? % &q;
+
+    // Right answer only if it reproduces the public key
+    (mod_pow(&parameters.g, &x, &parameters.p) == public.y)
+        .then(|| ElGamalPrivateKey { parameters: parameters.clone(), x, y: public.y.clone() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encryption_and_signatures_round_trip() {
+        let key = ElGamalPrivateKey::generate(256).unwrap();
+        let message = BigUint::from_bytes_be(b"attack at dawn");
+
+        let ciphertext = key.public().encrypt(&message).unwrap();
+        assert_ne!(ciphertext, key.public().encrypt(&message).unwrap());
+        assert_eq!(key.decrypt(&ciphertext).unwrap(), message);
+        assert!(key.public().encrypt(&key.parameters.p).is_err());
+
+        let signature = key.sign(b"I owe you 10 lei").unwrap();
+        assert!(key.public().verify(b"I owe you 10 lei", &signature));
+        assert!(!key.public().verify(b"I owe you 99 lei", &signature));
+    }
+
+    #[test]
+    fn textbook_numbers() {
+        // p = 23, g = 4 of order q = 11, x = 6 so y = 2
+        let key = ElGamalPrivateKey {
+            parameters: DhParameters { p: 23u32.into(), g: 4u32.into() },
+            x: 6u32.into(),
+            y: 2u32.into(),
+        };
+        let ciphertext = ElGamalCiphertext { c1: mod_pow(&4u32.into(), &3u32.into(), &23u32.into()), c2: BigUint::from(10u32 * 8 % 23) };
+        assert_eq!(ciphertext.c1, BigUint::from(18u32));
+        assert_eq!(key.decrypt(&ciphertext).unwrap(), BigUint::from(10u32));
+    }
+
+    #[test]
+    fn a_reused_nonce_reveals_the_key() {
+        let key = ElGamalPrivateKey::generate(128).unwrap();
+        let k = random_range(&BigUint::from(2u32), &key.parameters.q()).unwrap();
+        let first = key.sign_with_nonce(b"first message", &k).unwrap();
+        let second = key.sign_with_nonce(b"second message", &k).unwrap();
+        assert!(key.public().verify(b"second message", &second));
+
+        let recovered =
+            recover_key_from_reused_nonce(&key.public(), (b"first message", &first), (b"second message", &second)).unwrap();
+        assert_eq!(recovered, key);
+
+        let fresh = key.sign(b"second message").unwrap();
+        assert!(recover_key_from_reused_nonce(&key.public(), (b"first message", &first), (b"second message", &fresh)).is_none());
+    }
+}
@@ -0,0 +1,265 @@
+//! Hill cipher: blocks of letters multiplied by an invertible key matrix
+//! modulo the alphabet size
+
+use crate::cipher::{CipherError, CipherFamily, CipherInfo, ClassicalCipher};
+use crate::language::Alphabet;
+
+/// Square matrix of residues, stored row by row
+pub type Matrix = Vec<Vec<i64>>;
+
+/// Encrypts column vectors of `n` letters as `c = K·p` (mod the alphabet size)
+pub struct Hill {
+    key: Matrix,
+    inverse: Matrix,
+    alphabet: Alphabet,
+}
+
+impl Hill {
+    /// Build from a key given as `n²` numbers (`"3 3 2 5"`) or `n²` letters
+    /// (`"HILL"`), with n = 2 or 3
+    pub fn with_alphabet(key: &str, alphabet: Alphabet) -> Result<Self, CipherError> {
+        Self::from_matrix(parse_key(key, alphabet)?, alphabet)
+    }
+
+    pub fn from_matrix(key: Matrix, alphabet: Alphabet) -> Result<Self, CipherError> {
+        let modulus = alphabet.size() as i64;
+        let inverse = inverse_matrix(&key, modulus).ok_or_else(|| {
+            CipherError::InvalidKey(format!(
+                "the determinant {} has no inverse modulo {}",
+                determinant(&key, modulus),
+                modulus
+            ))
+        })?;
+        Ok(Hill { key, inverse, alphabet })
+    }
+
+    pub fn key(&self) -> &Matrix {
+        &self.key
+    }
+
+    pub fn inverse(&self) -> &Matrix {
+        &self.inverse
+    }
+
+    /// Letters of the alphabet in `text`, padded with X to whole blocks
+    fn blocks(&self, text: &str) -> Vec<i64> {
+        let mut values: Vec<i64> = text.chars().filter_map(|c| self.alphabet.index(c)).map(|index| index as i64).collect();
+        let padding = self.alphabet.index('X').unwrap_or_default() as i64;
+        while !values.len().is_multiple_of(self.key.len()) {
+            values.push(padding);
+        }
+        values
+    }
+
+    fn apply(&self, matrix: &Matrix, text: &str) -> String {
+        let modulus = self.alphabet.size() as i64;
+        self.blocks(text)
+            .chunks(matrix.len())
+            .flat_map(|block| multiply_vector(matrix, block, modulus))
+            .map(|value| self.alphabet.letter(value as usize))
+            .collect()
+    }
+}
+
+impl ClassicalCipher for Hill {
+    fn validate_key(key: &str) -> Result<(), CipherError> {
+        Self::with_alphabet(key, Alphabet::Latin).map(|_| ())
+    }
+
+    fn new(key: &str) -> Result<Self, CipherError> {
+        Self::with_alphabet(key, Alphabet::Latin)
+    }
+
+    fn info(&self) -> CipherInfo {
+        INFO
+    }
+
+    /// Non-letters are dropped and the last block is padded with X
+    fn encrypt(&self, plaintext: &str) -> Result<String, CipherError> {
+        Ok(self.apply(&self.key, plaintext))
+    }
+
+    fn decrypt(&self, ciphertext: &str) -> Result<String, CipherError> {
+        Ok(self.apply(&self.inverse, ciphertext))
+    }
+}
+
+const INFO: CipherInfo = CipherInfo {
+    name: "hill",
+    family: CipherFamily::Substitution,
+    key_description: "4 or 9 numbers or letters forming an invertible 2×2 or 3×3 matrix",
+    block_size: None,
+};
+
+fn parse_key(key: &str, alphabet: Alphabet) -> Result<Matrix, CipherError> {
+    let modulus = alphabet.size() as i64;
+    let values: Vec<i64> = if key.split_whitespace().all(|token| token.parse::<i64>().is_ok()) {
+        key.split_whitespace()
+            .map(|token| token.parse::<i64>().unwrap_or_default().rem_euclid(modulus))
+            .collect()
+    } else {
+        key.chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| alphabet.index(c).map(|index| index as i64))
+            .collect::<Option<_>>()
+            .ok_or_else(|| CipherError::InvalidKey(format!("{} contains letters outside the alphabet", key)))?
+    };
+
+    let size = match values.len() {
+        4 => 2,
+        9 => 3,
+        _ => return Err(CipherError::InvalidKey(INFO.key_description.to_string())),
+    };
+    Ok(values.chunks(size).map(<[i64]>::to_vec).collect())
+}
+
+fn multiply_vector(matrix: &Matrix, vector: &[i64], modulus: i64) -> Vec<i64> {
+    matrix
+        .iter()
+        .map(|row| row.iter().zip(vector).map(|(a, b)| a * b).sum::<i64>().rem_euclid(modulus))
+        .collect()
+}
+
+fn multiply(a: &Matrix, b: &Matrix, modulus: i64) -> Matrix {
+    (0..a.len())
+        .map(|i| {
+            (0..b[0].len())
+                .map(|j| (0..b.len()).map(|k| a[i][k] * b[k][j]).sum::<i64>().rem_euclid(modulus))
+                .collect()
+        })
+        .collect()
+}
+
+/// Matrix without row `row` and column `column`
+fn minor(matrix: &Matrix, row: usize, column: usize) -> Matrix {
+    matrix
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| i != row)
+        .map(|(_, values)| values.iter().enumerate().filter(|&(j, _)| j != column).map(|(_, &v)| v).collect())
+        .collect()
+}
+
+/// Determinant by cofactor expansion along the first row
+pub fn determinant(matrix: &Matrix, modulus: i64) -> i64 {
+    if matrix.len() == 1 {
+        return matrix[0][0].rem_euclid(modulus);
+    }
+    (0..matrix.len())
+        .map(|column| {
+            let sign = if column % 2 == 0 { 1 } else { -1 };
+            sign * matrix[0][column] * determinant(&minor(matrix, 0, column), modulus)
+        })
+        .sum::<i64>()
+        .rem_euclid(modulus)
+}
+
+/// `a⁻¹ mod modulus` by the extended Euclidean algorithm, if gcd(a, modulus) = 1
+pub fn mod_inverse(a: i64, modulus: i64) -> Option<i64> {
+    let (mut old_r, mut r) = (a.rem_euclid(modulus), modulus);
+    let (mut old_s, mut s) = (1, 0);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_s, s) = (s, old_s - quotient * s);
+    }
+    (old_r == 1).then(|| old_s.rem_euclid(modulus))
+}
+
+/// Inverse modulo `modulus` as det⁻¹ · adj(K), if the determinant is invertible
+pub fn inverse_matrix(matrix: &Matrix, modulus: i64) -> Option<Matrix> {
+    let det_inverse = mod_inverse(determinant(matrix, modulus), modulus)?;
+    let n = matrix.len();
+    if n == 1 {
+        return Some(vec![vec![det_inverse]]);
+    }
+
+    // The adjugate is the transposed cofactor matrix
+    Some(
+        (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| {
+                        let sign = if (i + j) % 2 == 0 { 1 } else { -1 };
+                        (sign * determinant(&minor(matrix, j, i), modulus) * det_inverse).rem_euclid(modulus)
+                    })
+                    .collect()
+            })
+            .collect(),
+    )
+}
+
+/// Known-plaintext attack: with `n` plaintext blocks P (as columns) whose
+/// matrix is invertible, the key is K = C·P⁻¹. Tries every choice of `n`
+/// blocks from the matching plaintext and ciphertext.
+pub fn known_plaintext_attack(plaintext: &str, ciphertext: &str, size: usize, alphabet: Alphabet) -> Option<Matrix> {
+    let modulus = alphabet.size() as i64;
+    let values = |text: &str| -> Vec<i64> { text.chars().filter_map(|c| alphabet.index(c)).map(|index| index as i64).collect() };
+    let plain_blocks: Vec<Vec<i64>> = values(plaintext).chunks_exact(size).map(<[i64]>::to_vec).collect();
+    let cipher_blocks: Vec<Vec<i64>> = values(ciphertext).chunks_exact(size).map(<[i64]>::to_vec).collect();
+    let count = plain_blocks.len().min(cipher_blocks.len());
+
+    let columns = |blocks: &[&Vec<i64>]| -> Matrix { (0..size).map(|row| blocks.iter().map(|block| block[row]).collect()).collect() };
+    for chosen in combinations(count, size) {
+        let p = columns(&chosen.iter().map(|&i| &plain_blocks[i]).collect::<Vec<_>>());
+        if let Some(p_inverse) = inverse_matrix(&p, modulus) {
+            let c = columns(&chosen.iter().map(|&i| &cipher_blocks[i]).collect::<Vec<_>>());
+            return Some(multiply(&c, &p_inverse, modulus));
+        }
+    }
+    None
+}
+
+/// All ways to pick `k` of `0..n`, in lexicographic order
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    fn extend(start: usize, n: usize, k: usize, chosen: &mut Vec<usize>, all: &mut Vec<Vec<usize>>) {
+        if chosen.len() == k {
+            all.push(chosen.clone());
+            return;
+        }
+        for next in start..n {
+            chosen.push(next);
+            extend(next + 1, n, k, chosen, all);
+            chosen.pop();
+        }
+    }
+
+    let mut all = Vec::new();
+    extend(0, n, k, &mut Vec::new(), &mut all);
+    all
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypts_the_textbook_example() {
+        // Stinson, "Cryptography: Theory and Practice", example 1.5 (row vectors
+        // there, so the key is transposed here)
+        let hill = Hill::new("11 3 8 7").unwrap();
+        assert_eq!(hill.encrypt("july").unwrap(), "DELW");
+        assert_eq!(hill.decrypt("DELW").unwrap(), "JULY");
+        assert_eq!(hill.inverse(), &vec![vec![7, 23], vec![18, 11]]);
+
+        // Even determinant: not invertible mod 26
+        assert!(Hill::new("2 4 6 8").is_err());
+    }
+
+    #[test]
+    fn three_by_three_romanian_round_trip() {
+        let hill = Hill::with_alphabet("6 24 1 13 16 10 20 17 15", Alphabet::Romanian).unwrap();
+        let ciphertext = hill.encrypt("Știință și țară").unwrap();
+        assert_eq!(hill.decrypt(&ciphertext).unwrap(), "ȘTIINȚĂȘIȚARĂXX");
+    }
+
+    #[test]
+    fn known_plaintext_recovers_the_key() {
+        let hill = Hill::with_alphabet("GYBNQKURP", Alphabet::Latin).unwrap();
+        let plaintext = "THEQUICKBROWNFOXJUMPSOVERTHELAZYDOG";
+        let ciphertext = hill.encrypt(plaintext).unwrap();
+
+        let key = known_plaintext_attack(plaintext, &ciphertext, 3, Alphabet::Latin).unwrap();
+        assert_eq!(&key, hill.key());
+    }
+}
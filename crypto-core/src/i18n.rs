@@ -0,0 +1,299 @@
+//! English and Romanian messages for every binary in the workspace.
+//!
+//! Messages are written in English where they are used, as
+//! `tr!("Wrote {} bytes to {}", len, path)`, and the English text is the
+//! key looked up in the Romanian catalogs when Romanian is selected. A
+//! message missing from the catalogs is shown in English. Placeholders are
+//! plain `{}`, filled in order, so a translation keeps as many as its
+//! original; values needing a width or precision are formatted first.
+//!
+//! The language comes from `--lang en|ro`, else the `ui.lang` setting
+//! (`CRYPTO_LANG`), else the locale (`LC_ALL`, `LC_MESSAGES`, `LANG`).
+//! `--output json` keeps English field names and results; only its `error`
+//! message is translated.
+
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use crate::config::Config;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Lang {
+    #[default]
+    En,
+    Ro,
+}
+
+impl Lang {
+    pub fn parse(code: &str) -> io::Result<Self> {
+        match code {
+            "en" => Ok(Self::En),
+            "ro" => Ok(Self::Ro),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                crate::tr!("Unknown language {} (expected en or ro)", code),
+            )),
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::Ro => "ro",
+        }
+    }
+
+    /// `ui.lang` when set, else the locale
+    pub fn from_config(config: &Config) -> io::Result<Self> {
+        match config.get("ui.lang") {
+            Some(_) => config.parse("ui.lang"),
+            None => Ok(Self::from_locale(|name| env::var(name).ok())),
+        }
+    }
+
+    /// The first of `LC_ALL`, `LC_MESSAGES` and `LANG` that is set decides,
+    /// `var` looking one up by name: Romanian for `ro` and `ro_RO.UTF-8`,
+    /// English for anything else
+    pub fn from_locale(var: impl Fn(&str) -> Option<String>) -> Self {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"].into_iter().find_map(|name| var(name).filter(|value| !value.is_empty()));
+        match locale {
+            Some(locale) if locale == "ro" || locale.starts_with("ro_") || locale.starts_with("ro.") => Self::Ro,
+            _ => Self::En,
+        }
+    }
+
+    /// Take `--lang <code>` out of `args`, returning the language (`default`
+    /// unless given) and the remaining arguments
+    pub fn take_from(args: &[String], default: Lang) -> io::Result<(Self, Vec<String>)> {
+        let mut lang = default;
+        let mut rest = Vec::new();
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            if arg == "--lang" {
+                let code = iter.next().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, crate::tr!("Missing value for {}", arg)))?;
+                lang = Lang::parse(code)?;
+            } else {
+                rest.push(arg.clone());
+            }
+        }
+
+        Ok((lang, rest))
+    }
+}
+
+impl FromStr for Lang {
+    type Err = io::Error;
+
+    fn from_str(code: &str) -> io::Result<Self> {
+        Self::parse(code)
+    }
+}
+
+/// Romanian translations, keyed by the English message
+pub type Catalog = &'static [(&'static str, &'static str)];
+
+/// Messages crypto-core itself produces, and those every binary shares
+pub const CORE: Catalog = &[
+    ("Invalid key: {}", "Cheie invalidă: {}"),
+    ("Invalid input: {}", "Date de intrare invalide: {}"),
+    ("Usage: {}", "Utilizare: {}"),
+    ("Missing value for {}", "Lipsește valoarea pentru {}"),
+    ("Unknown command: {}", "Comandă necunoscută: {}"),
+    ("Unknown output format {} (expected text or json)", "Format de ieșire necunoscut {} (se așteaptă text sau json)"),
+    ("Unknown language {} (expected en or ro)", "Limbă necunoscută {} (se așteaptă en sau ro)"),
+    ("too few letters to tell", "prea puține litere pentru a decide"),
+    (
+        "plaintext: letter frequencies and pairs both fit the language",
+        "text clar: frecvențele literelor și perechile lor se potrivesc limbii",
+    ),
+    (
+        "transposition: the language's letter frequencies, but not its letter pairs",
+        "transpoziție: frecvențele literelor limbii, dar nu și perechile lor",
+    ),
+    (
+        "Caesar shift {}: shifting back fits the language's letter frequencies",
+        "deplasare Caesar {}: deplasarea înapoi se potrivește frecvențelor literelor limbii",
+    ),
+    (
+        "monoalphabetic substitution: a language-like index of coincidence, but other letters",
+        "substituție monoalfabetică: indice de coincidență ca al limbii, dar alte litere",
+    ),
+    (
+        "polyalphabetic or polygraphic (Vigenère, Hill, Playfair): flattened letter frequencies",
+        "polialfabetic sau poligrafic (Vigenère, Hill, Playfair): frecvențe ale literelor aplatizate",
+    ),
+    (
+        "Invalid vault entry name {} (letters, digits, -, _ and . only)",
+        "Nume invalid de intrare în seif {} (doar litere, cifre, -, _ și .)",
+    ),
+    ("Cannot read the vault {}: {}", "Seiful {} nu poate fi citit: {}"),
+    ("{} is not a vault", "{} nu este un seif"),
+    ("Missing or invalid {}", "{} lipsă sau invalid"),
+    ("Wrong passphrase, or the vault was altered", "Parolă greșită, sau seiful a fost modificat"),
+    ("The vault's entries are damaged", "Intrările seifului sunt deteriorate"),
+    ("The vault has no entry {}", "Seiful nu are intrarea {}"),
+    ("The vault already has an entry {}", "Seiful are deja o intrare {}"),
+    (
+        "The pad {} has {} unused bytes, the message needs {}",
+        "Blocul {} are {} octeți nefolosiți, mesajul are nevoie de {}",
+    ),
+    ("The pad {} has no {} bytes at offset {}", "Blocul {} nu are {} octeți la poziția {}"),
+    ("The vault entry {} is not a pad", "Intrarea {} din seif nu este un bloc de unică folosință"),
+];
+
+static ROMANIAN: AtomicBool = AtomicBool::new(false);
+static MESSAGES: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+
+/// Select the language for the rest of the process, with `catalogs` (the
+/// binary's own) translating on top of [`CORE`]. Only the first call's
+/// catalogs are kept.
+pub fn init(lang: Lang, catalogs: &[Catalog]) {
+    MESSAGES.get_or_init(|| [CORE].iter().chain(catalogs).flat_map(|catalog| catalog.iter().copied()).collect());
+    ROMANIAN.store(lang == Lang::Ro, Ordering::Relaxed);
+}
+
+pub fn lang() -> Lang {
+    if ROMANIAN.load(Ordering::Relaxed) {
+        Lang::Ro
+    } else {
+        Lang::En
+    }
+}
+
+/// `message` in the selected language
+pub fn translate(message: &'static str) -> &'static str {
+    if lang() == Lang::En {
+        return message;
+    }
+    MESSAGES.get().and_then(|messages| messages.get(message)).copied().unwrap_or(message)
+}
+
+/// `template` with each `{}` replaced by the next of `args`
+pub fn fill(template: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut pieces = template.split("{}");
+    filled.push_str(pieces.next().unwrap_or(""));
+    for piece in pieces {
+        if let Some(arg) = args.next() {
+            filled.push_str(&arg.to_string());
+        }
+        filled.push_str(piece);
+    }
+    filled
+}
+
+/// The message in the selected language, `&'static str` without arguments
+/// and a `String` with them
+#[macro_export]
+macro_rules! tr {
+    ($message:expr) => {
+        $crate::i18n::translate($message)
+    };
+    ($message:expr, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::fill($crate::i18n::translate($message), &[$(&$arg as &dyn ::std::fmt::Display),+])
+    };
+}
+
+/// Messages given to `tr!` as string literals in `source` that none of
+/// `catalogs` translates, for each binary's test that its catalog is
+/// complete
+pub fn untranslated(source: &str, catalogs: &[Catalog]) -> Vec<String> {
+    let translated = |message: &str| catalogs.iter().any(|catalog| catalog.iter().any(|(english, _)| *english == message));
+    source
+        .match_indices("tr!(")
+        .filter(|(start, _)| !source[..*start].ends_with(|c: char| c.is_alphanumeric() || c == '_'))
+        .filter_map(|(start, call)| string_literal(source[start + call.len()..].trim_start()))
+        .filter(|message| !translated(message))
+        .collect()
+}
+
+/// The string literal `text` starts with, escapes resolved
+fn string_literal(text: &str) -> Option<String> {
+    let mut chars = text.strip_prefix('"')?.chars();
+    let mut value = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some(value),
+            '\\' => match chars.next()? {
+                'n' => value.push('\n'),
+                't' => value.push('\t'),
+                '\n' => {
+                    // A line continuation also swallows the next line's indentation
+                    let rest = chars.as_str().trim_start();
+                    chars = rest.chars();
+                }
+                escaped => value.push(escaped),
+            },
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+/// Translations whose `{}` placeholders do not match their English
+pub fn mismatched_placeholders(catalog: Catalog) -> Vec<&'static str> {
+    catalog
+        .iter()
+        .filter(|(english, romanian)| english.matches("{}").count() != romanian.matches("{}").count())
+        .map(|(english, _)| *english)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_placeholders_in_order() {
+        assert_eq!(fill("Wrote {} bytes to {}", &[&12, &"pad.bin"]), "Wrote 12 bytes to pad.bin");
+        assert_eq!(fill("{}{}", &[&"a", &'b']), "ab");
+        assert_eq!(fill("no placeholders", &[]), "no placeholders");
+        assert!(mismatched_placeholders(CORE).is_empty());
+    }
+
+    #[test]
+    fn the_locale_picks_romanian_only_for_ro() {
+        let locale = |vars: &'static [(&'static str, &'static str)]| {
+            Lang::from_locale(|name| vars.iter().find(|(var, _)| *var == name).map(|(_, value)| value.to_string()))
+        };
+        assert_eq!(locale(&[("LANG", "ro_RO.UTF-8")]), Lang::Ro);
+        assert_eq!(locale(&[("LC_ALL", "ro"), ("LANG", "en_US.UTF-8")]), Lang::Ro);
+        assert_eq!(locale(&[("LC_ALL", ""), ("LC_MESSAGES", "C"), ("LANG", "ro_RO.UTF-8")]), Lang::En);
+        assert_eq!(locale(&[("LANG", "rom")]), Lang::En);
+        assert_eq!(locale(&[]), Lang::En);
+    }
+
+    #[test]
+    fn takes_the_lang_flag() {
+        let args: Vec<String> = ["caesar", "--lang", "ro", "brute"].iter().map(|arg| arg.to_string()).collect();
+        let (lang, rest) = Lang::take_from(&args, Lang::En).unwrap();
+        assert_eq!(lang, Lang::Ro);
+        assert_eq!(rest, ["caesar", "brute"]);
+        assert!(Lang::take_from(&args[..2], Lang::En).is_err());
+        assert!(Lang::parse("fr").is_err());
+    }
+
+    #[test]
+    fn finds_messages_missing_from_the_catalogs() {
+        let source = "out.line(tr!(\"Invalid key: {}\", key));\n\
+                      tr!( \"Say \\\"hi\\\"\\n\");\n\
+                      tr!(\"Two \\\n    lines\");\n\
+                      tr!(USAGE);\n\
+                      include_str!(\"main.rs\");";
+        assert_eq!(untranslated(source, &[CORE]), ["Say \"hi\"\n", "Two lines"]);
+    }
+
+    #[test]
+    fn every_core_message_has_a_romanian_translation() {
+        for source in [include_str!("cipher.rs"), include_str!("cryptanalysis.rs"), include_str!("vault.rs")] {
+            assert_eq!(untranslated(source, &[CORE]), Vec::<String>::new());
+        }
+    }
+}
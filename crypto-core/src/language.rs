@@ -0,0 +1,308 @@
+//! Letter statistics of natural language, for scoring candidate plaintexts
+
+use std::sync::OnceLock;
+
+/// Relative frequencies of A–Z in English text
+pub const ENGLISH_FREQUENCIES: [f64; 26] = [
+    0.08167, 0.01492, 0.02782, 0.04253, 0.12702, 0.02228, 0.02015,
+    0.06094, 0.06966, 0.00153, 0.00772, 0.04025, 0.02406, 0.06749,
+    0.07507, 0.01929, 0.00095, 0.05987, 0.06327, 0.09056, 0.02758,
+    0.00978, 0.02360, 0.00150, 0.01974, 0.00074,
+];
+
+/// Expected index of coincidence of English text
+pub const ENGLISH_INDEX_OF_COINCIDENCE: f64 = 0.0667;
+
+/// Index of coincidence of uniformly random letters (1/26)
+pub const RANDOM_INDEX_OF_COINCIDENCE: f64 = 1.0 / 26.0;
+
+/// Public-domain English prose (Dickens, Jefferson, Lincoln, Melville,
+/// Austen, Carroll, Doyle and others) that letter statistics are counted from
+pub const ENGLISH_SAMPLE: &str = include_str!("../data/english.txt");
+
+/// Romanian prose (Creangă, Eminescu and plain modern text) that letter
+/// statistics are counted from
+pub const ROMANIAN_SAMPLE: &str = include_str!("../data/romanian.txt");
+
+/// Letters a cipher works over, numbered from 0
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alphabet {
+    /// A–Z
+    Latin,
+    /// The 31 letters of Romanian, diacritics in dictionary order
+    Romanian,
+}
+
+impl Alphabet {
+    pub fn letters(self) -> &'static [char] {
+        match self {
+            Self::Latin => &[
+                'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M',
+                'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z',
+            ],
+            Self::Romanian => &[
+                'A', 'Ă', 'Â', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'Î', 'J', 'K', 'L', 'M',
+                'N', 'O', 'P', 'Q', 'R', 'S', 'Ș', 'T', 'Ț', 'U', 'V', 'W', 'X', 'Y', 'Z',
+            ],
+        }
+    }
+
+    /// Number of letters, the modulus for arithmetic on them
+    pub fn size(self) -> usize {
+        self.letters().len()
+    }
+
+    /// Position of a letter, ignoring case (and cedilla forms of ș and ț)
+    pub fn index(self, c: char) -> Option<usize> {
+        let c = match c.to_uppercase().next().unwrap_or(c) {
+            'Ş' => 'Ș',
+            'Ţ' => 'Ț',
+            upper => upper,
+        };
+        self.letters().iter().position(|&letter| letter == c)
+    }
+
+    pub fn letter(self, index: usize) -> char {
+        self.letters()[index % self.size()]
+    }
+}
+
+/// Position of an ASCII letter in the alphabet, ignoring case
+pub fn letter_index(c: char) -> Option<usize> {
+    c.is_ascii_alphabetic().then(|| (c.to_ascii_uppercase() as u8 - b'A') as usize)
+}
+
+/// How often each of A–Z occurs; other characters are ignored
+pub fn letter_counts(text: &str) -> [usize; 26] {
+    let mut counts = [0; 26];
+    for index in text.chars().filter_map(letter_index) {
+        counts[index] += 1;
+    }
+    counts
+}
+
+/// Probability that two letters drawn from `counts` are the same
+pub fn index_of_coincidence(counts: &[usize; 26]) -> f64 {
+    let total: usize = counts.iter().sum();
+    if total < 2 {
+        return 0.0;
+    }
+    let pairs: usize = counts.iter().map(|&count| count * count.saturating_sub(1)).sum();
+    pairs as f64 / (total * (total - 1)) as f64
+}
+
+/// Natural log of the probability of each letter pair in English, indexed
+/// `first * 26 + second`, counted from [`ENGLISH_SAMPLE`]
+pub fn english_bigram_log_probabilities() -> &'static [f64; 676] {
+    static TABLE: OnceLock<[f64; 676]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let letters: Vec<usize> = ENGLISH_SAMPLE.chars().filter_map(letter_index).collect();
+        let mut counts = [0usize; 676];
+        for pair in letters.windows(2) {
+            counts[pair[0] * 26 + pair[1]] += 1;
+        }
+
+        // Half a count for unseen pairs, so that rare ones are unlikely rather than impossible
+        let total = (letters.len() - 1) as f64 + 0.5 * 676.0;
+        counts.map(|count| ((count as f64 + 0.5) / total).ln())
+    })
+}
+
+/// Average log probability of the adjacent letter pairs in `text`. Higher
+/// (closer to zero) is more English-like; unlike letter frequencies, this
+/// tells apart rearrangements of the same letters.
+pub fn bigram_score(text: &str) -> f64 {
+    let letters: Vec<usize> = text.chars().filter_map(letter_index).collect();
+    if letters.len() < 2 {
+        return f64::NEG_INFINITY;
+    }
+
+    let table = english_bigram_log_probabilities();
+    let total: f64 = letters.windows(2).map(|pair| table[pair[0] * 26 + pair[1]]).sum();
+    total / (letters.len() - 1) as f64
+}
+
+/// Position in A–Z of a letter, with the Romanian ă, â, î, ș and ț folded
+/// onto their base letters, as the ciphers that work on A–Z see them
+pub fn folded_letter_index(c: char) -> Option<usize> {
+    match c {
+        'ă' | 'Ă' | 'â' | 'Â' => Some(0),
+        'î' | 'Î' => Some(8),
+        'ș' | 'Ș' | 'ş' | 'Ş' => Some(18),
+        'ț' | 'Ț' | 'ţ' | 'Ţ' => Some(19),
+        _ => letter_index(c),
+    }
+}
+
+/// A language the crackers rank candidate plaintexts in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    English,
+    Romanian,
+}
+
+impl Language {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::English => "english",
+            Self::Romanian => "romanian",
+        }
+    }
+
+    pub fn sample(self) -> &'static str {
+        match self {
+            Self::English => ENGLISH_SAMPLE,
+            Self::Romanian => ROMANIAN_SAMPLE,
+        }
+    }
+
+    /// Relative frequencies of A–Z, diacritics counted as their base letters
+    pub fn frequencies(self) -> &'static [f64; 26] {
+        static ROMANIAN: OnceLock<[f64; 26]> = OnceLock::new();
+        match self {
+            Self::English => &ENGLISH_FREQUENCIES,
+            Self::Romanian => ROMANIAN.get_or_init(|| {
+                let mut counts = [0usize; 26];
+                for index in ROMANIAN_SAMPLE.chars().filter_map(folded_letter_index) {
+                    counts[index] += 1;
+                }
+                // Half a count for letters the sample lacks (Q, W, Y), so none is impossible
+                let total = counts.iter().sum::<usize>() as f64 + 13.0;
+                counts.map(|count| (count as f64 + 0.5) / total)
+            }),
+        }
+    }
+
+    /// The quadgram model counted from [`Language::sample`], built on first use
+    pub fn quadgrams(self) -> &'static Quadgrams {
+        static ENGLISH: OnceLock<Quadgrams> = OnceLock::new();
+        static ROMANIAN: OnceLock<Quadgrams> = OnceLock::new();
+        match self {
+            Self::English => ENGLISH.get_or_init(|| Quadgrams::from_sample(ENGLISH_SAMPLE)),
+            Self::Romanian => ROMANIAN.get_or_init(|| Quadgrams::from_sample(ROMANIAN_SAMPLE)),
+        }
+    }
+}
+
+/// Chi-squared distance between the letters of `text` and the language,
+/// diacritics folded. Lower is closer; text without letters scores infinity.
+pub fn chi_squared(text: &str, language: Language) -> f64 {
+    let mut counts = [0usize; 26];
+    for index in text.chars().filter_map(folded_letter_index) {
+        counts[index] += 1;
+    }
+    let total: usize = counts.iter().sum();
+    if total == 0 {
+        return f64::INFINITY;
+    }
+
+    counts
+        .iter()
+        .zip(language.frequencies())
+        .map(|(&count, frequency)| {
+            let expected = frequency * total as f64;
+            (count as f64 - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+/// Weight of the quadgram, trigram, bigram and single-letter estimates in
+/// [`Quadgrams`]. A sample of a few thousand words leaves most quadgrams
+/// unseen, so the shorter contexts fill in for them.
+const INTERPOLATION: [f64; 4] = [0.85, 0.1, 0.04, 0.01];
+
+/// Natural log of the probability of each letter given the three before
+/// it, indexed like a base-26 number (`((a * 26 + b) * 26 + c) * 26 + d`).
+/// Scoring a text sums these over its letters, which rewards common words
+/// and syllables far more sharply than letter pairs do.
+pub struct Quadgrams {
+    table: Vec<f32>,
+}
+
+impl Quadgrams {
+    /// Count the letters of a sample text, diacritics folded, and mix the
+    /// estimates from the last three, two, one and no letters of context
+    pub fn from_sample(sample: &str) -> Self {
+        let letters: Vec<usize> = sample.chars().filter_map(folded_letter_index).collect();
+        let counts = [1, 2, 3, 4].map(|n| {
+            let mut counts = vec![0usize; 26usize.pow(n)];
+            for window in letters.windows(n as usize) {
+                counts[window.iter().fold(0, |index, &letter| index * 26 + letter)] += 1;
+            }
+            counts
+        });
+
+        // count(context + letter) / count(context), zero for unseen contexts
+        let conditional = |n: usize, index: usize| {
+            let context = counts[n - 2][index / 26];
+            if context == 0 {
+                0.0
+            } else {
+                counts[n - 1][index] as f64 / context as f64
+            }
+        };
+        let table = (0..26usize.pow(4))
+            .map(|index| {
+                let letter = index % 26;
+                // Half a count for unseen letters, as in the bigram table
+                let single = (counts[0][letter] as f64 + 0.5) / (letters.len() as f64 + 13.0);
+                let probability = INTERPOLATION[0] * conditional(4, index)
+                    + INTERPOLATION[1] * conditional(3, index % 26usize.pow(3))
+                    + INTERPOLATION[2] * conditional(2, index % 26usize.pow(2))
+                    + INTERPOLATION[3] * single;
+                probability.ln() as f32
+            })
+            .collect();
+        Quadgrams { table }
+    }
+
+    /// Sum of the log probabilities of the quadgrams of `letters` (A–Z
+    /// positions), for comparing decryptions of the same ciphertext
+    pub fn total(&self, letters: &[usize]) -> f64 {
+        letters
+            .windows(4)
+            .map(|window| f64::from(self.table[((window[0] * 26 + window[1]) * 26 + window[2]) * 26 + window[3]]))
+            .sum()
+    }
+
+    /// Average log probability per quadgram of the letters of `text`, the
+    /// higher (closer to zero) the more it reads like the language
+    pub fn score(&self, text: &str) -> f64 {
+        let letters: Vec<usize> = text.chars().filter_map(folded_letter_index).collect();
+        if letters.len() < 4 {
+            return f64::NEG_INFINITY;
+        }
+        self.total(&letters) / (letters.len() - 3) as f64
+    }
+}
+
+/// How much `text` reads like `language`; see [`Quadgrams::score`]
+pub fn quadgram_score(text: &str, language: Language) -> f64 {
+    language.quadgrams().score(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quadgrams_prefer_the_language_over_its_letters_shuffled() {
+        let english = "The students attacked the cipher with frequency counts and found the key";
+        let shuffled = "Ehtstudtens tackatedth echiper iwth quencyfre tsconu nad ofnud het eyk";
+        assert!(quadgram_score(english, Language::English) > quadgram_score(shuffled, Language::English) + 0.3);
+
+        let romanian = "Cheia trebuie păstrată ascunsă, iar mesajul rămâne secret";
+        assert!(quadgram_score(romanian, Language::Romanian) > quadgram_score(romanian, Language::English));
+        assert!(quadgram_score(english, Language::English) > quadgram_score(english, Language::Romanian));
+        assert_eq!(quadgram_score("abc", Language::English), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn diacritics_fold_onto_their_base_letters() {
+        assert_eq!(folded_letter_index('ș'), folded_letter_index('S'));
+        assert_eq!(folded_letter_index('Ţ'), Some(19));
+        assert_eq!(folded_letter_index('1'), None);
+        assert!((Language::Romanian.frequencies().iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(chi_squared("Aceasta este o propozitie", Language::Romanian) < chi_squared("Zyxw qvkj", Language::Romanian));
+    }
+}
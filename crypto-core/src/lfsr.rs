@@ -0,0 +1,327 @@
+//! Linear feedback shift registers and keystream generators built from
+//! several of them: the Geffe generator with its correlation attack, and A5/1
+
+use crate::cipher::CipherError;
+
+/// Fibonacci LFSR. Stage 1 is output each step and the XOR of the tapped
+/// stages enters at stage `length`, so taps `[1, 2]` on 4 stages give
+/// `s(t+4) = s(t) ⊕ s(t+1)`, i.e. the polynomial x⁴ + x + 1.
+#[derive(Clone, Debug)]
+pub struct Lfsr {
+    length: u32,
+    /// Bit `k` is stage `k + 1`
+    state: u64,
+    tap_mask: u64,
+}
+
+impl Lfsr {
+    pub fn new(length: u32, taps: &[u32], state: u64) -> Result<Self, CipherError> {
+        if !(1..=64).contains(&length) {
+            return Err(CipherError::InvalidKey(String::from("an LFSR has 1 to 64 stages")));
+        }
+        if taps.is_empty() || taps.iter().any(|tap| !(1..=length).contains(tap)) {
+            return Err(CipherError::InvalidKey(format!("taps must be stages 1 to {}", length)));
+        }
+
+        let mask = Self::mask(length);
+        if state & mask == 0 {
+            return Err(CipherError::InvalidKey(String::from("an all-zero state only ever outputs zeros")));
+        }
+        let tap_mask = taps.iter().fold(0, |mask, tap| mask | 1 << (tap - 1));
+        Ok(Lfsr { length, state: state & mask, tap_mask })
+    }
+
+    fn mask(length: u32) -> u64 {
+        if length == 64 { u64::MAX } else { (1 << length) - 1 }
+    }
+
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    /// Output stage 1 and shift in the feedback bit
+    pub fn step(&mut self) -> u8 {
+        let output = (self.state & 1) as u8;
+        let feedback = (self.state & self.tap_mask).count_ones() as u64 & 1;
+        self.state = (self.state >> 1) | feedback << (self.length - 1);
+        output
+    }
+
+    /// Steps until the starting state comes back, or `None` when it never
+    /// does (without stage 1 among the taps, early states fall away for good).
+    /// Registers of more than 32 stages are not tried.
+    pub fn period(&self) -> Option<u64> {
+        if self.length > 32 {
+            return None;
+        }
+        let mut lfsr = self.clone();
+        for steps in 1..=1u64 << self.length {
+            lfsr.step();
+            if lfsr.state == self.state {
+                return Some(steps);
+            }
+        }
+        None
+    }
+
+    /// Whether the period is 2ⁿ − 1, every non-zero state, which needs a
+    /// primitive feedback polynomial
+    pub fn is_maximal(&self) -> bool {
+        self.period() == Some((1 << self.length) - 1)
+    }
+}
+
+impl Iterator for Lfsr {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        Some(self.step())
+    }
+}
+
+/// Berlekamp–Massey: the shortest LFSR that generates `bits`. Returns its
+/// length (the linear complexity) and its connection polynomial coefficients
+/// `c0 = 1, c1, ...`, so `2 × complexity` keystream bits reveal the whole register.
+pub fn berlekamp_massey(bits: &[u8]) -> (usize, Vec<u8>) {
+    let n = bits.len();
+    let mut connection = vec![0u8; n + 1];
+    let mut previous = vec![0u8; n + 1];
+    connection[0] = 1;
+    previous[0] = 1;
+    let mut complexity = 0;
+    let mut last_change: isize = -1;
+
+    for i in 0..n {
+        let discrepancy = (0..=complexity).fold(0, |d, j| d ^ (connection[j] & bits[i - j]));
+        if discrepancy == 1 {
+            let before = connection.clone();
+            let shift = (i as isize - last_change) as usize;
+            for j in 0..=n - shift {
+                connection[j + shift] ^= previous[j];
+            }
+            if 2 * complexity <= i {
+                complexity = i + 1 - complexity;
+                last_change = i as isize;
+                previous = before;
+            }
+        }
+    }
+
+    connection.truncate(complexity + 1);
+    (complexity, connection)
+}
+
+/// Geffe generator: register 1 selects whether register 2 or 3 is output
+/// (`x1·x2 ⊕ ¬x1·x3`). The output agrees with registers 2 and 3 three
+/// quarters of the time, which the correlation attack exploits.
+#[derive(Clone, Debug)]
+pub struct Geffe {
+    pub registers: [Lfsr; 3],
+}
+
+impl Geffe {
+    /// A generator over [`GEFFE_REGISTERS`] with the given start states
+    pub fn new(states: [u64; 3]) -> Result<Self, CipherError> {
+        let [selector, first, second] = [0, 1, 2].map(|index| {
+            let (length, taps) = GEFFE_REGISTERS[index];
+            Lfsr::new(length, taps, states[index])
+        });
+        Ok(Geffe { registers: [selector?, first?, second?] })
+    }
+}
+
+impl Iterator for Geffe {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let [selector, first, second] = &mut self.registers;
+        let (x1, x2, x3) = (selector.step(), first.step(), second.step());
+        Some(if x1 == 1 { x2 } else { x3 })
+    }
+}
+
+/// Register lengths and taps for a Geffe generator: x⁵ + x² + 1, x⁷ + x + 1
+/// and x¹¹ + x² + 1, all primitive. The key is 23 bits.
+pub const GEFFE_REGISTERS: [(u32, &[u32]); 3] = [(5, &[1, 3]), (7, &[1, 2]), (11, &[1, 3])];
+
+/// What the correlation attack found
+pub struct CorrelationAttack {
+    /// Recovered start states of registers 1, 2 and 3
+    pub states: [u64; 3],
+    /// Share of keystream bits each of registers 2 and 3 agreed with
+    pub agreements: [f64; 2],
+    /// States tried, against 2²³ for brute force over the whole key
+    pub trials: u64,
+}
+
+/// Recover a Geffe generator's start states from its keystream by finding
+/// registers 2 and 3 separately, as the states agreeing with about 75% of
+/// the output, and only then searching register 1
+pub fn correlation_attack(keystream: &[u8], registers: [(u32, &[u32]); 3]) -> Option<CorrelationAttack> {
+    let agreement = |lfsr: Lfsr| {
+        let matching = lfsr.zip(keystream).filter(|(bit, output)| bit == *output).count();
+        matching as f64 / keystream.len() as f64
+    };
+    fn candidates((length, taps): (u32, &[u32])) -> impl Iterator<Item = Lfsr> + '_ {
+        (1..1u64 << length).filter_map(move |state| Lfsr::new(length, taps, state).ok())
+    }
+
+    let mut trials = 0;
+    let mut best = [(0, 0.0); 2];
+    for (index, register) in registers[1..].iter().enumerate() {
+        for lfsr in candidates(*register) {
+            trials += 1;
+            let state = lfsr.state();
+            let score = agreement(lfsr);
+            if score > best[index].1 {
+                best[index] = (state, score);
+            }
+        }
+    }
+
+    let (length, taps) = registers[1];
+    let first = Lfsr::new(length, taps, best[0].0).ok()?;
+    let (length, taps) = registers[2];
+    let second = Lfsr::new(length, taps, best[1].0).ok()?;
+    for selector in candidates(registers[0]) {
+        trials += 1;
+        let state = selector.state();
+        let geffe = Geffe { registers: [selector, first.clone(), second.clone()] };
+        if geffe.zip(keystream).all(|(bit, output)| bit == *output) {
+            return Some(CorrelationAttack { states: [state, best[0].0, best[1].0], agreements: [best[0].1, best[1].1], trials });
+        }
+    }
+    None
+}
+
+/// A5/1, the GSM voice cipher: three registers of 19, 22 and 23 bits, each
+/// clocked only when its clocking bit agrees with the majority of the three
+pub struct A51 {
+    registers: [u32; 3],
+}
+
+/// (mask, clocking bit, taps, output bit) for each register
+const A51_REGISTERS: [(u32, u32, u32, u32); 3] = [
+    (0x07ffff, 0x000100, 0x072000, 0x040000),
+    (0x3fffff, 0x000400, 0x300000, 0x200000),
+    (0x7fffff, 0x000400, 0x700080, 0x400000),
+];
+
+/// Bits of keystream per direction for one GSM frame
+pub const A51_FRAME_BITS: usize = 114;
+
+impl A51 {
+    /// Load the 64-bit session key (least significant bit first) and the
+    /// 22-bit frame number, then mix for 100 steps
+    pub fn new(key: u64, frame: u32) -> Self {
+        let mut a51 = A51 { registers: [0; 3] };
+        let bits = (0..64).map(|i| (key >> i) as u32 & 1).chain((0..22).map(|i| (frame >> i) & 1));
+        for bit in bits {
+            a51.clock_all();
+            for register in &mut a51.registers {
+                *register ^= bit;
+            }
+        }
+        for _ in 0..100 {
+            a51.clock();
+        }
+        a51
+    }
+
+    fn clock_register(&mut self, index: usize) {
+        let (mask, _, taps, _) = A51_REGISTERS[index];
+        let register = &mut self.registers[index];
+        let feedback = (*register & taps).count_ones() & 1;
+        *register = ((*register << 1) & mask) | feedback;
+    }
+
+    fn clock_all(&mut self) {
+        (0..3).for_each(|index| self.clock_register(index));
+    }
+
+    /// Majority clocking: registers whose clocking bit is in the majority step
+    fn clock(&mut self) {
+        let clocking: Vec<bool> = (0..3).map(|index| self.registers[index] & A51_REGISTERS[index].1 != 0).collect();
+        let majority = clocking.iter().filter(|&&bit| bit).count() >= 2;
+        for (index, bit) in clocking.into_iter().enumerate() {
+            if bit == majority {
+                self.clock_register(index);
+            }
+        }
+    }
+
+    /// The two 114-bit keystreams of a frame (network to phone, phone to
+    /// network), packed most significant bit first
+    pub fn frame_keystreams(mut self) -> (Vec<u8>, Vec<u8>) {
+        let downlink = pack_bits(&mut self, A51_FRAME_BITS);
+        let uplink = pack_bits(&mut self, A51_FRAME_BITS);
+        (downlink, uplink)
+    }
+}
+
+impl Iterator for A51 {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        self.clock();
+        let output = (0..3).fold(0, |bit, index| bit ^ (self.registers[index] & A51_REGISTERS[index].3 != 0) as u8);
+        Some(output)
+    }
+}
+
+/// The next `count` bits as bytes, most significant bit first
+pub fn pack_bits(bits: &mut impl Iterator<Item = u8>, count: usize) -> Vec<u8> {
+    let mut bytes = vec![0; count.div_ceil(8)];
+    for (i, bit) in bits.take(count).enumerate() {
+        bytes[i / 8] |= bit << (7 - i % 8);
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primitive_taps_give_maximal_periods() {
+        let lfsr = Lfsr::new(4, &[1, 2], 0b1000).unwrap();
+        assert_eq!(lfsr.period(), Some(15));
+        // x⁴ + x² + 1 is not irreducible
+        assert_eq!(Lfsr::new(4, &[1, 3], 0b0001).unwrap().period(), Some(6));
+        assert!(GEFFE_REGISTERS.iter().all(|&(length, taps)| Lfsr::new(length, taps, 1).unwrap().is_maximal()));
+        assert!(Lfsr::new(4, &[1, 2], 0).is_err());
+    }
+
+    #[test]
+    fn berlekamp_massey_finds_the_register_length() {
+        let bits: Vec<u8> = Lfsr::new(11, &[1, 3], 0x5a5).unwrap().take(40).collect();
+        let (complexity, connection) = berlekamp_massey(&bits);
+        assert_eq!(complexity, 11);
+        // s(t) = s(t-9) ⊕ s(t-11)
+        assert_eq!(connection, [1, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn correlation_attack_recovers_the_geffe_key() {
+        let states = [0b10110, 0b1100101, 0b10011100011];
+        let keystream: Vec<u8> = Geffe::new(states).unwrap().take(200).collect();
+
+        let attack = correlation_attack(&keystream, GEFFE_REGISTERS).unwrap();
+        assert_eq!(attack.states, states);
+        assert!(attack.agreements.iter().all(|&agreement| agreement > 0.65));
+        assert!(attack.trials < 1 << 12);
+    }
+
+    #[test]
+    fn a51_matches_the_reference_implementation() {
+        // Test vector from Briceno, Goldberg and Wagner's reference code
+        let key = u64::from_le_bytes([0x12, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef]);
+        let (downlink, uplink) = A51::new(key, 0x134).frame_keystreams();
+        assert_eq!(downlink, [0x53, 0x4e, 0xaa, 0x58, 0x2f, 0xe8, 0x15, 0x1a, 0xb6, 0xe1, 0x85, 0x5a, 0x72, 0x8c, 0x00]);
+        assert_eq!(uplink, [0x24, 0xfd, 0x35, 0xa3, 0x5d, 0x5f, 0xb6, 0x52, 0x6d, 0x32, 0xf9, 0x06, 0xdf, 0x1a, 0xc0]);
+    }
+}
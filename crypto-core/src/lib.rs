@@ -0,0 +1,43 @@
+//! Building blocks shared by the course modules
+
+pub mod aes;
+pub mod argon2;
+pub mod caesar;
+pub mod chacha20;
+pub mod chain;
+pub mod cipher;
+pub mod codec;
+pub mod columnar;
+pub mod config;
+pub mod cryptanalysis;
+pub mod der;
+pub mod dh;
+pub mod dsa;
+pub mod ecc;
+pub mod elgamal;
+pub mod hash;
+pub mod hill;
+pub mod i18n;
+pub mod language;
+pub mod lfsr;
+#[cfg(feature = "logging")]
+pub mod logging;
+pub mod mac;
+pub mod merkle;
+pub mod modes;
+pub mod modular;
+pub mod otp;
+pub mod password;
+pub mod primes;
+pub mod railfence;
+pub mod randomness;
+pub mod registry;
+pub mod rc4;
+pub mod rng;
+pub mod rsa;
+pub mod shamir;
+pub mod stego;
+pub mod vault;
+pub mod vigenere;
+
+pub use cipher::{BlockCipher, CipherError, CipherFamily, CipherInfo, ClassicalCipher, SymmetricCipher};
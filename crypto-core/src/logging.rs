@@ -0,0 +1,156 @@
+//! Logging for every binary in the workspace, through `tracing`: warnings
+//! only by default, `-v` for progress, `-vv` for debug detail such as each
+//! external command run, `-vvv` for everything. `--log-format json` writes
+//! one JSON object per event. Logs go to stderr, so command output on
+//! stdout stays parseable, and `RUST_LOG` overrides the level when set.
+
+use std::io::{self, IsTerminal};
+use std::str::FromStr;
+
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::EnvFilter;
+
+use crate::config::Config;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(name: &str) -> io::Result<Self> {
+        match name {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unknown log format {} (expected text or json)", name),
+            )),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Json => "json",
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = io::Error;
+
+    fn from_str(name: &str) -> io::Result<Self> {
+        Self::parse(name)
+    }
+}
+
+/// What the global logging flags asked for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LogOptions {
+    /// How many times `-v` was given
+    pub verbosity: u8,
+    pub format: LogFormat,
+}
+
+impl Default for LogOptions {
+    fn default() -> Self {
+        LogOptions { verbosity: 0, format: LogFormat::Text }
+    }
+}
+
+impl LogOptions {
+    /// Quiet, in the format `log.format` configures; the flags go on top
+    pub fn from_config(config: &Config) -> io::Result<Self> {
+        Ok(LogOptions { verbosity: 0, format: config.parse("log.format")? })
+    }
+
+    /// Take `-v`, `-vv`, `-vvv`, `--verbose` and `--log-format <format>`
+    /// out of `args`, returning `defaults` overridden by them and the
+    /// remaining arguments
+    pub fn take_from(args: &[String], defaults: LogOptions) -> io::Result<(Self, Vec<String>)> {
+        let mut options = defaults;
+        let mut rest = Vec::new();
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--verbose" => options.verbosity = options.verbosity.saturating_add(1),
+                "--log-format" => {
+                    let name = iter.next().ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidInput, "Missing value for --log-format")
+                    })?;
+                    options.format = LogFormat::parse(name)?;
+                }
+                flag if flag.len() > 1 && flag.starts_with('-') && flag[1..].bytes().all(|c| c == b'v') => {
+                    options.verbosity = options.verbosity.saturating_add((flag.len() - 1).min(3) as u8);
+                }
+                _ => rest.push(arg.clone()),
+            }
+        }
+
+        Ok((options, rest))
+    }
+
+    /// The same flags again, for a child process that takes them too
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.verbosity > 0 {
+            args.push(format!("-{}", "v".repeat(self.verbosity.into())));
+        }
+        if self.format != LogFormat::Text {
+            args.extend([String::from("--log-format"), self.format.name().to_string()]);
+        }
+        args
+    }
+
+    pub fn level(&self) -> LevelFilter {
+        match self.verbosity {
+            0 => LevelFilter::WARN,
+            1 => LevelFilter::INFO,
+            2 => LevelFilter::DEBUG,
+            _ => LevelFilter::TRACE,
+        }
+    }
+}
+
+/// Install the process-wide subscriber. Only the first call has any
+/// effect, so tests and embedders may call it freely.
+pub fn init(options: &LogOptions) {
+    let filter = EnvFilter::builder()
+        .with_default_directive(options.level().into())
+        .from_env_lossy();
+    let builder = tracing_subscriber::fmt().with_env_filter(filter).with_writer(io::stderr);
+
+    let _ = match options.format {
+        LogFormat::Text => builder.with_target(false).with_ansi(io::stderr().is_terminal()).try_init(),
+        LogFormat::Json => builder.json().flatten_event(true).with_current_span(true).try_init(),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn takes_the_logging_flags_and_leaves_the_rest() {
+        let (options, rest) = LogOptions::take_from(&args(&["-vv", "issue", "--log-format", "json", "alice", "-v"]), LogOptions::default()).unwrap();
+        assert_eq!(options, LogOptions { verbosity: 3, format: LogFormat::Json });
+        assert_eq!(rest, args(&["issue", "alice"]));
+        assert_eq!(options.level(), LevelFilter::TRACE);
+        assert_eq!(LogOptions::take_from(&options.to_args(), LogOptions::default()).unwrap(), (options, Vec::new()));
+
+        let json = LogOptions { verbosity: 0, format: LogFormat::Json };
+        let (options, rest) = LogOptions::take_from(&args(&["--verbose", "-", "-x"]), json).unwrap();
+        assert_eq!(options, LogOptions { verbosity: 1, format: LogFormat::Json });
+        assert_eq!(rest, args(&["-", "-x"]));
+
+        assert!(LogOptions::take_from(&args(&["--log-format"]), json).is_err());
+        assert!(LogOptions::take_from(&args(&["--log-format", "xml"]), json).is_err());
+    }
+}
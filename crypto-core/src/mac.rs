@@ -0,0 +1,120 @@
+//! HMAC (RFC 2104) over any [`HashFunction`]:
+//! H((K ⊕ opad) ‖ H((K ⊕ ipad) ‖ m)), the nesting that stops the
+//! length-extension forgeries a plain H(K ‖ m) allows
+
+use crate::hash::HashFunction;
+
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+
+/// Incremental HMAC: key it, feed data with `update`, then `finalize`.
+/// Cloning a keyed `Hmac` skips rehashing the padded key, which PBKDF2
+/// relies on.
+#[derive(Clone)]
+pub struct Hmac<H: HashFunction> {
+    inner: H,
+    /// The outer hash, already fed K ⊕ opad
+    outer: H,
+}
+
+impl<H: HashFunction> Hmac<H> {
+    /// Keys longer than a block are hashed first; shorter ones are padded
+    /// with zeros to the block size
+    pub fn new(key: &[u8]) -> Self {
+        let mut block_key = if key.len() > H::BLOCK_SIZE { H::digest(key) } else { key.to_vec() };
+        block_key.resize(H::BLOCK_SIZE, 0);
+
+        let mut inner = H::new();
+        inner.update(&block_key.iter().map(|byte| byte ^ IPAD).collect::<Vec<u8>>());
+        let mut outer = H::new();
+        outer.update(&block_key.iter().map(|byte| byte ^ OPAD).collect::<Vec<u8>>());
+        Hmac { inner, outer }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    pub fn finalize(self) -> Vec<u8> {
+        let mut outer = self.outer;
+        outer.update(&self.inner.finalize());
+        outer.finalize()
+    }
+}
+
+/// HMAC of a whole message
+pub fn hmac<H: HashFunction>(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<H>::new(key);
+    mac.update(data);
+    mac.finalize()
+}
+
+/// Check a tag without an early exit, so the time taken does not reveal
+/// how many leading bytes of a forgery were right
+pub fn verify<H: HashFunction>(key: &[u8], data: &[u8], tag: &[u8]) -> bool {
+    constant_time_eq(&hmac::<H>(key, data), tag)
+}
+
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |difference, (x, y)| difference | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Sha256;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn rfc_4231_vectors() {
+        assert_eq!(
+            hex(&hmac::<Sha256>(&[0x0b; 20], b"Hi There")),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"
+        );
+        assert_eq!(
+            hex(&hmac::<Sha256>(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        // Keys longer than the 64-byte block are hashed first
+        assert_eq!(
+            hex(&hmac::<Sha256>(&[0xaa; 131], b"Test Using Larger Than Block-Size Key - Hash Key First")),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+        assert_eq!(
+            hex(&hmac::<Sha256>(
+                &[0xaa; 131],
+                b"This is a test using a larger than block-size key and a larger than block-size data. \
+                  The key needs to be hashed before being used by the HMAC algorithm."
+            )),
+            "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2"
+        );
+    }
+
+    #[test]
+    fn verification_rejects_changed_data_and_tags() {
+        let tag = hmac::<Sha256>(b"key", b"pay 10 lei");
+        assert!(verify::<Sha256>(b"key", b"pay 10 lei", &tag));
+        assert!(!verify::<Sha256>(b"key", b"pay 99 lei", &tag));
+        assert!(!verify::<Sha256>(b"other key", b"pay 10 lei", &tag));
+        assert!(!verify::<Sha256>(b"key", b"pay 10 lei", &tag[..31]));
+    }
+
+    #[test]
+    fn encrypt_then_mac_rejects_tampering_before_decrypting() {
+        use crate::aes::Aes128;
+        use crate::SymmetricCipher;
+        use crate::modes::{self, Mode};
+
+        let cipher = Aes128::new(b"sixteen byte key").unwrap();
+        let sealed = modes::encrypt_then_mac(&cipher, Mode::Cbc, &[7; 16], b"mac key", b"attack at dawn").unwrap();
+        assert_eq!(modes::decrypt_verified(&cipher, Mode::Cbc, b"mac key", &sealed).unwrap(), b"attack at dawn");
+
+        let mut altered = sealed.clone();
+        altered[20] ^= 1;
+        assert!(modes::decrypt_verified(&cipher, Mode::Cbc, b"mac key", &altered).is_err());
+        assert!(modes::decrypt_verified(&cipher, Mode::Cbc, b"wrong key", &sealed).is_err());
+    }
+}
@@ -0,0 +1,236 @@
+//! Merkle trees as Certificate Transparency builds them (RFC 6962 and
+//! RFC 9162): leaves and inner nodes are hashed with different prefixes, a
+//! tree of n leaves splits at the largest power of two below n, and short
+//! proofs show that a leaf is in a tree or that a tree extends an older one
+
+use crate::hash::Sha256;
+
+pub type Hash = [u8; 32];
+
+fn sha256(parts: &[&[u8]]) -> Hash {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize()
+}
+
+/// SHA-256(0x00 ‖ data); the prefix stops an inner node passing as a leaf
+pub fn leaf_hash(data: &[u8]) -> Hash {
+    sha256(&[&[0x00], data])
+}
+
+/// SHA-256(0x01 ‖ left ‖ right)
+pub fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    sha256(&[&[0x01], left, right])
+}
+
+/// Largest power of two smaller than `n` (for `n` > 1)
+fn split_point(n: usize) -> usize {
+    let mut k = 1;
+    while k * 2 < n {
+        k *= 2;
+    }
+    k
+}
+
+/// Merkle tree hash of the leaves (RFC 6962, section 2.1)
+pub fn root(leaves: &[Hash]) -> Hash {
+    match leaves {
+        [] => sha256(&[]),
+        [leaf] => *leaf,
+        _ => {
+            let k = split_point(leaves.len());
+            node_hash(&root(&leaves[..k]), &root(&leaves[k..]))
+        }
+    }
+}
+
+/// Audit path of leaf `index`: the sibling hashes from the leaf up to the
+/// root (RFC 6962, section 2.1.1)
+pub fn inclusion_proof(index: usize, leaves: &[Hash]) -> Vec<Hash> {
+    if leaves.len() <= 1 {
+        return Vec::new();
+    }
+
+    let k = split_point(leaves.len());
+    if index < k {
+        let mut path = inclusion_proof(index, &leaves[..k]);
+        path.push(root(&leaves[k..]));
+        path
+    } else {
+        let mut path = inclusion_proof(index - k, &leaves[k..]);
+        path.push(root(&leaves[..k]));
+        path
+    }
+}
+
+/// Root implied by an inclusion proof, or `None` if the proof is malformed
+/// (RFC 9162, section 2.1.3.2)
+pub fn root_from_inclusion_proof(index: usize, tree_size: usize, leaf: &Hash, path: &[Hash]) -> Option<Hash> {
+    if index >= tree_size {
+        return None;
+    }
+
+    let (mut node, mut last_node) = (index, tree_size - 1);
+    let mut root = *leaf;
+    for sibling in path {
+        if last_node == 0 {
+            return None;
+        }
+        if node & 1 == 1 || node == last_node {
+            root = node_hash(sibling, &root);
+            while node & 1 == 0 && node != 0 {
+                node >>= 1;
+                last_node >>= 1;
+            }
+        } else {
+            root = node_hash(&root, sibling);
+        }
+        node >>= 1;
+        last_node >>= 1;
+    }
+
+    (last_node == 0).then_some(root)
+}
+
+pub fn verify_inclusion(index: usize, tree_size: usize, leaf: &Hash, path: &[Hash], expected_root: &Hash) -> bool {
+    root_from_inclusion_proof(index, tree_size, leaf, path).is_some_and(|root| &root == expected_root)
+}
+
+/// Proof that the tree of the first `old_size` leaves is a prefix of the
+/// tree of all of them, i.e. that nothing logged was changed or dropped
+/// (RFC 9162, section 2.1.4.1)
+pub fn consistency_proof(old_size: usize, leaves: &[Hash]) -> Vec<Hash> {
+    if old_size == 0 || old_size >= leaves.len() {
+        return Vec::new();
+    }
+    subproof(old_size, leaves, true)
+}
+
+fn subproof(m: usize, leaves: &[Hash], complete: bool) -> Vec<Hash> {
+    let n = leaves.len();
+    if m == n {
+        return if complete { Vec::new() } else { vec![root(leaves)] };
+    }
+
+    let k = split_point(n);
+    if m <= k {
+        let mut proof = subproof(m, &leaves[..k], complete);
+        proof.push(root(&leaves[k..]));
+        proof
+    } else {
+        let mut proof = subproof(m - k, &leaves[k..], false);
+        proof.push(root(&leaves[..k]));
+        proof
+    }
+}
+
+/// Check a consistency proof between two tree heads (RFC 9162, section 2.1.4.2)
+pub fn verify_consistency(old_size: usize, new_size: usize, old_root: &Hash, new_root: &Hash, proof: &[Hash]) -> bool {
+    if old_size == new_size {
+        return proof.is_empty() && old_root == new_root;
+    }
+    if old_size == 0 || old_size > new_size {
+        return false;
+    }
+
+    // A power-of-two old tree is a complete subtree, so its root starts the path
+    let mut path = proof.to_vec();
+    if old_size.is_power_of_two() {
+        path.insert(0, *old_root);
+    }
+    let Some((first, rest)) = path.split_first() else {
+        return false;
+    };
+
+    let (mut node, mut last_node) = (old_size - 1, new_size - 1);
+    while node & 1 == 1 {
+        node >>= 1;
+        last_node >>= 1;
+    }
+
+    let (mut old, mut new) = (*first, *first);
+    for sibling in rest {
+        if last_node == 0 {
+            return false;
+        }
+        if node & 1 == 1 || node == last_node {
+            old = node_hash(sibling, &old);
+            new = node_hash(sibling, &new);
+            while node & 1 == 0 && node != 0 {
+                node >>= 1;
+                last_node >>= 1;
+            }
+        } else {
+            new = node_hash(&new, sibling);
+        }
+        node >>= 1;
+        last_node >>= 1;
+    }
+
+    last_node == 0 && &old == old_root && &new == new_root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(n: usize) -> Vec<Hash> {
+        (0..n).map(|i| leaf_hash(&[i as u8])).collect()
+    }
+
+    fn hex(hash: &Hash) -> String {
+        hash.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn every_leaf_proves_inclusion_in_every_tree_size() {
+        for size in 1..=17 {
+            let leaves = leaves(size);
+            let root = root(&leaves);
+            for index in 0..size {
+                let path = inclusion_proof(index, &leaves);
+                assert!(verify_inclusion(index, size, &leaves[index], &path, &root));
+            }
+        }
+    }
+
+    #[test]
+    fn proof_for_another_leaf_or_size_fails() {
+        let leaves = leaves(7);
+        let root = root(&leaves);
+        let path = inclusion_proof(6, &leaves);
+
+        assert!(!verify_inclusion(6, 7, &leaves[5], &path, &root));
+        assert!(!verify_inclusion(6, 8, &leaves[6], &path, &root));
+        assert_eq!(root_from_inclusion_proof(7, 7, &leaves[6], &path), None);
+    }
+
+    #[test]
+    fn empty_tree_hashes_the_empty_string() {
+        assert_eq!(hex(&root(&[])), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+    }
+
+    #[test]
+    fn every_prefix_is_consistent_with_every_extension() {
+        let all = leaves(17);
+        for new_size in 1..=17 {
+            let new_root = root(&all[..new_size]);
+            for old_size in 1..=new_size {
+                let proof = consistency_proof(old_size, &all[..new_size]);
+                assert!(verify_consistency(old_size, new_size, &root(&all[..old_size]), &new_root, &proof), "{} -> {}", old_size, new_size);
+            }
+        }
+    }
+
+    #[test]
+    fn a_rewritten_history_is_inconsistent() {
+        let mut all = leaves(10);
+        let old_root = root(&all[..6]);
+        all[3] = leaf_hash(b"rewritten");
+        let proof = consistency_proof(6, &all);
+        assert!(!verify_consistency(6, 10, &old_root, &root(&all), &proof));
+        assert!(!verify_consistency(5, 10, &root(&all[..5]), &root(&all), &proof));
+    }
+}
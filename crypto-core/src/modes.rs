@@ -0,0 +1,180 @@
+//! Modes of operation for any [`BlockCipher`], with PKCS#7 padding for the
+//! modes that need whole blocks
+
+use crate::cipher::{BlockCipher, CipherError};
+use crate::hash::Sha256;
+use crate::mac::{self, hmac};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Each block on its own: equal plaintext blocks give equal ciphertext blocks
+    Ecb,
+    /// Each plaintext block is XORed with the previous ciphertext block (the IV first)
+    Cbc,
+    /// The encrypted counter block is a keystream, so no padding is needed
+    Ctr,
+}
+
+impl Mode {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ecb => "ecb",
+            Self::Cbc => "cbc",
+            Self::Ctr => "ctr",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "ecb" => Some(Self::Ecb),
+            "cbc" => Some(Self::Cbc),
+            "ctr" => Some(Self::Ctr),
+            _ => None,
+        }
+    }
+
+    /// Whether the mode takes an IV (or initial counter block)
+    pub fn needs_iv(self) -> bool {
+        self != Self::Ecb
+    }
+}
+
+/// Pad to a whole number of blocks with n bytes of value n (1 ≤ n ≤ block size)
+pub fn pad_pkcs7(data: &[u8], block_size: usize) -> Vec<u8> {
+    let padding = block_size - data.len() % block_size;
+    let mut padded = data.to_vec();
+    padded.resize(data.len() + padding, padding as u8);
+    padded
+}
+
+pub fn unpad_pkcs7(data: &[u8], block_size: usize) -> Result<Vec<u8>, CipherError> {
+    let padding = data.last().copied().unwrap_or_default() as usize;
+    if !(1..=block_size).contains(&padding) || padding > data.len() || data[data.len() - padding..].iter().any(|&byte| byte as usize != padding) {
+        return Err(CipherError::InvalidInput(String::from("bad padding (wrong key?)")));
+    }
+    Ok(data[..data.len() - padding].to_vec())
+}
+
+fn check_iv(cipher: &dyn BlockCipher, iv: &[u8]) -> Result<(), CipherError> {
+    if iv.len() == cipher.block_size() {
+        Ok(())
+    } else {
+        Err(CipherError::InvalidInput(format!("the IV must be {} bytes", cipher.block_size())))
+    }
+}
+
+fn check_blocks(cipher: &dyn BlockCipher, ciphertext: &[u8]) -> Result<(), CipherError> {
+    if !ciphertext.is_empty() && ciphertext.len().is_multiple_of(cipher.block_size()) {
+        Ok(())
+    } else {
+        Err(CipherError::InvalidInput(format!("ciphertext must be a non-empty multiple of {} bytes", cipher.block_size())))
+    }
+}
+
+/// Encrypt with padding where the mode needs it. `iv` is ignored for ECB.
+pub fn encrypt(cipher: &dyn BlockCipher, mode: Mode, iv: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, CipherError> {
+    if mode.needs_iv() {
+        check_iv(cipher, iv)?;
+    }
+    let block_size = cipher.block_size();
+
+    Ok(match mode {
+        Mode::Ecb => {
+            let mut data = pad_pkcs7(plaintext, block_size);
+            data.chunks_mut(block_size).for_each(|block| cipher.encrypt_block(block));
+            data
+        }
+        Mode::Cbc => {
+            let mut data = pad_pkcs7(plaintext, block_size);
+            let mut previous = iv.to_vec();
+            for block in data.chunks_mut(block_size) {
+                block.iter_mut().zip(&previous).for_each(|(byte, chain)| *byte ^= chain);
+                cipher.encrypt_block(block);
+                previous.copy_from_slice(block);
+            }
+            data
+        }
+        Mode::Ctr => ctr(cipher, iv, plaintext),
+    })
+}
+
+pub fn decrypt(cipher: &dyn BlockCipher, mode: Mode, iv: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, CipherError> {
+    if mode.needs_iv() {
+        check_iv(cipher, iv)?;
+    }
+    let block_size = cipher.block_size();
+
+    match mode {
+        Mode::Ecb => {
+            check_blocks(cipher, ciphertext)?;
+            let mut data = ciphertext.to_vec();
+            data.chunks_mut(block_size).for_each(|block| cipher.decrypt_block(block));
+            unpad_pkcs7(&data, block_size)
+        }
+        Mode::Cbc => {
+            check_blocks(cipher, ciphertext)?;
+            let mut data = ciphertext.to_vec();
+            let previous_blocks = [iv].into_iter().chain(ciphertext.chunks(block_size));
+            for (block, previous) in data.chunks_mut(block_size).zip(previous_blocks) {
+                cipher.decrypt_block(block);
+                block.iter_mut().zip(previous).for_each(|(byte, chain)| *byte ^= chain);
+            }
+            unpad_pkcs7(&data, block_size)
+        }
+        Mode::Ctr => Ok(ctr(cipher, iv, ciphertext)),
+    }
+}
+
+/// Bytes of the HMAC-SHA256 tag `encrypt_then_mac` appends
+pub const MAC_LENGTH: usize = 32;
+
+/// Encrypt, then authenticate: IV ‖ ciphertext ‖ HMAC-SHA256(mac_key, IV ‖
+/// ciphertext). The MAC key must be independent of the cipher key.
+pub fn encrypt_then_mac(
+    cipher: &dyn BlockCipher,
+    mode: Mode,
+    iv: &[u8],
+    mac_key: &[u8],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, CipherError> {
+    let iv = if mode.needs_iv() { iv } else { &[] };
+    let mut sealed = [iv, &encrypt(cipher, mode, iv, plaintext)?].concat();
+    let tag = hmac::<Sha256>(mac_key, &sealed);
+    sealed.extend(tag);
+    Ok(sealed)
+}
+
+/// Undo `encrypt_then_mac`, checking the tag before anything is decrypted:
+/// a forged or altered message is rejected without ever reaching the
+/// padding check, so there is no padding oracle to query
+pub fn decrypt_verified(cipher: &dyn BlockCipher, mode: Mode, mac_key: &[u8], sealed: &[u8]) -> Result<Vec<u8>, CipherError> {
+    let iv_length = if mode.needs_iv() { cipher.block_size() } else { 0 };
+    if sealed.len() < iv_length + MAC_LENGTH {
+        return Err(CipherError::InvalidInput(String::from("too short to hold an IV and a MAC")));
+    }
+    let (authenticated, tag) = sealed.split_at(sealed.len() - MAC_LENGTH);
+    if !mac::verify::<Sha256>(mac_key, authenticated, tag) {
+        return Err(CipherError::InvalidInput(String::from("MAC check failed: the message was altered or the MAC key is wrong")));
+    }
+    let (iv, ciphertext) = authenticated.split_at(iv_length);
+    decrypt(cipher, mode, iv, ciphertext)
+}
+
+/// XOR with encryptions of the counter block, incremented as a big-endian number
+fn ctr(cipher: &dyn BlockCipher, initial_counter: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut counter = initial_counter.to_vec();
+    let mut output = Vec::with_capacity(data.len());
+    for chunk in data.chunks(cipher.block_size()) {
+        let mut keystream = counter.clone();
+        cipher.encrypt_block(&mut keystream);
+        output.extend(chunk.iter().zip(&keystream).map(|(byte, key)| byte ^ key));
+
+        for byte in counter.iter_mut().rev() {
+            *byte = byte.wrapping_add(1);
+            if *byte != 0 {
+                break;
+            }
+        }
+    }
+    output
+}
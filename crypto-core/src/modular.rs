@@ -0,0 +1,113 @@
+//! Modular arithmetic on big integers: the extended Euclidean algorithm,
+//! inverses, square-and-multiply exponentiation and the Chinese remainder
+//! theorem
+
+use num_bigint::{BigInt, BigUint};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+/// Extended Euclidean algorithm: `(g, x, y)` with g = gcd(a, b) = a·x + b·y
+pub fn extended_gcd(a: &BigUint, b: &BigUint) -> (BigUint, BigInt, BigInt) {
+    let (mut old_r, mut r) = (BigInt::from(a.clone()), BigInt::from(b.clone()));
+    let (mut old_x, mut x) = (BigInt::one(), BigInt::zero());
+    let (mut old_y, mut y) = (BigInt::zero(), BigInt::one());
+
+    while !r.is_zero() {
+        let quotient = &old_r / &r;
+        (old_r, r) = (r.clone(), old_r - &quotient * r);
+        (old_x, x) = (x.clone(), old_x - &quotient * x);
+        (old_y, y) = (y.clone(), old_y - &quotient * y);
+    }
+
+    (old_r.magnitude().clone(), old_x, old_y)
+}
+
+/// a⁻¹ mod m, which exists exactly when gcd(a, m) = 1
+pub fn mod_inverse(a: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+    if modulus.is_zero() {
+        return None;
+    }
+    let (g, x, _) = extended_gcd(&(a % modulus), modulus);
+    g.is_one().then(|| x.mod_floor(&BigInt::from(modulus.clone())).magnitude().clone())
+}
+
+/// (a − b) mod m without leaving the unsigned integers
+pub fn mod_sub(a: &BigUint, b: &BigUint, modulus: &BigUint) -> BigUint {
+    (a % modulus + modulus - b % modulus) % modulus
+}
+
+/// baseᵉˣᵖ mod m by square-and-multiply over the exponent's bits, from the top
+pub fn mod_pow(base: &BigUint, exponent: &BigUint, modulus: &BigUint) -> BigUint {
+    if modulus.is_one() {
+        return BigUint::zero();
+    }
+    let base = base % modulus;
+    let mut result = BigUint::one();
+    for bit in (0..exponent.bits()).rev() {
+        result = &result * &result % modulus;
+        if exponent.bit(bit) {
+            result = result * &base % modulus;
+        }
+    }
+    result
+}
+
+/// Chinese remainder theorem: the x with x ≡ rᵢ (mod mᵢ) for every
+/// `(rᵢ, mᵢ)`, as `(x, lcm of the moduli)`. Moduli need not be coprime, but
+/// then the congruences must agree modulo each gcd, or there is no solution.
+pub fn crt(congruences: &[(BigUint, BigUint)]) -> Option<(BigUint, BigUint)> {
+    let mut solution = (BigUint::zero(), BigUint::one());
+    for (residue, modulus) in congruences {
+        if modulus.is_zero() {
+            return None;
+        }
+        let (x, m) = solution;
+        let residue = residue % modulus;
+
+        // x + m·t ≡ r (mod n): solve m·t ≡ r − x (mod n) after dividing by g
+        let (g, inverse, _) = extended_gcd(&m, modulus);
+        let difference = BigInt::from(residue) - BigInt::from(x.clone());
+        let g_signed = BigInt::from(g.clone());
+        if !(&difference % &g_signed).is_zero() {
+            return None;
+        }
+        let reduced_modulus = BigInt::from(modulus / &g);
+        let t = (difference / &g_signed * inverse).mod_floor(&reduced_modulus);
+        let combined = m.lcm(modulus);
+        let x = (BigInt::from(x) + BigInt::from(m) * t).mod_floor(&BigInt::from(combined.clone()));
+        solution = (x.magnitude().clone(), combined);
+    }
+    Some(solution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn n(value: u64) -> BigUint {
+        BigUint::from(value)
+    }
+
+    #[test]
+    fn euclid_inverse_and_powers() {
+        let (g, x, y) = extended_gcd(&n(240), &n(46));
+        assert_eq!(g, n(2));
+        assert_eq!(BigInt::from(240) * x + BigInt::from(46) * y, BigInt::from(2));
+
+        assert_eq!(mod_inverse(&n(17), &n(3120)), Some(n(2753)));
+        assert_eq!(mod_inverse(&n(6), &n(9)), None);
+
+        assert_eq!(mod_pow(&n(4), &n(13), &n(497)), n(445));
+        let (base, exponent, modulus) = (n(0xdead_beef), n(0x1234_5678_9abc), n(1_000_000_007));
+        assert_eq!(mod_pow(&base, &exponent, &modulus), base.modpow(&exponent, &modulus));
+    }
+
+    #[test]
+    fn chinese_remainders() {
+        // Sunzi's problem: remainders 2, 3, 2 when divided by 3, 5, 7
+        assert_eq!(crt(&[(n(2), n(3)), (n(3), n(5)), (n(2), n(7))]), Some((n(23), n(105))));
+        // Moduli sharing a factor work when the residues agree on it
+        assert_eq!(crt(&[(n(3), n(4)), (n(5), n(6))]), Some((n(11), n(12))));
+        assert_eq!(crt(&[(n(3), n(4)), (n(4), n(6))]), None);
+    }
+}
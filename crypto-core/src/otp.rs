@@ -0,0 +1,235 @@
+//! One-time pad: data XORed with random pad bytes that are never used twice
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::rng;
+
+/// `len` random pad bytes, drawn like any other key
+pub fn generate_pad(len: usize) -> io::Result<Vec<u8>> {
+    rng::key(len)
+}
+
+/// XOR `data` with the start of `pad`, which both encrypts and decrypts
+pub fn xor(data: &[u8], pad: &[u8]) -> io::Result<Vec<u8>> {
+    if pad.len() < data.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("The pad has {} bytes, the message needs {}", pad.len(), data.len())
+        ));
+    }
+    Ok(data.iter().zip(pad).map(|(byte, key)| byte ^ key).collect())
+}
+
+/// A pad file shared by sender and receiver. Bytes are zeroed on disk as
+/// soon as they are used, and the used ranges are recorded next to the pad
+/// in `<pad>.consumed` so no offset is ever handed out twice.
+pub struct PadFile {
+    path: PathBuf,
+    len: u64,
+    /// Used byte ranges as `(start, end)`, sorted and not overlapping
+    consumed: Vec<(u64, u64)>,
+}
+
+impl PadFile {
+    /// Write `len` random bytes to a new owner-only pad file
+    pub fn create(path: impl AsRef<Path>, len: usize) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        options.open(&path)?.write_all(&generate_pad(len)?)?;
+        let pad = PadFile { path, len: len as u64, consumed: Vec::new() };
+        pad.save_consumed()?;
+        Ok(pad)
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let len = fs::metadata(&path)?.len();
+
+        let consumed_path = consumed_path(&path);
+        let consumed = if consumed_path.exists() {
+            fs::read_to_string(&consumed_path)?
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| {
+                    let (start, end) = line.split_once(' ').unwrap_or_default();
+                    match (start.parse::<u64>(), end.parse::<u64>()) {
+                        (Ok(start), Ok(end)) if start < end => Ok((start, end)),
+                        _ => Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("Invalid consumed range \"{}\" in {}", line, consumed_path.display())
+                        )),
+                    }
+                })
+                .collect::<io::Result<_>>()?
+        } else {
+            Vec::new()
+        };
+
+        Ok(PadFile { path, len, consumed })
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn consumed(&self) -> &[(u64, u64)] {
+        &self.consumed
+    }
+
+    /// Bytes after the last used range, which is where the next message goes
+    pub fn remaining(&self) -> u64 {
+        self.len - self.next_offset()
+    }
+
+    fn next_offset(&self) -> u64 {
+        self.consumed.last().map(|&(_, end)| end).unwrap_or_default()
+    }
+
+    /// Use the next `len` unused bytes, for encrypting. Returns their offset,
+    /// which the receiver needs, and the pad bytes.
+    pub fn take(&mut self, len: usize) -> io::Result<(u64, Vec<u8>)> {
+        let offset = self.next_offset();
+        Ok((offset, self.take_at(offset, len)?))
+    }
+
+    /// Use `len` bytes at `offset`, for decrypting a message sent with them.
+    /// Fails if any of them have been used before.
+    pub fn take_at(&mut self, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        let end = offset + len as u64;
+        if end > self.len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("The pad has {} bytes, the message needs bytes {}..{}", self.len, offset, end)
+            ));
+        }
+        if let Some(&(start, used_end)) = self.consumed.iter().find(|&&(start, used_end)| offset < used_end && start < end) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("Pad bytes {}..{} were already used; a pad must never be reused", start, used_end)
+            ));
+        }
+
+        let mut file = OpenOptions::new().read(true).write(true).open(&self.path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut pad = vec![0; len];
+        file.read_exact(&mut pad)?;
+
+        // Destroy the bytes before handing them out
+        file.seek(SeekFrom::Start(offset))?;
+        file.write_all(&vec![0; len])?;
+        file.sync_all()?;
+
+        self.consumed.push((offset, end));
+        self.consumed.sort_unstable();
+        self.save_consumed()?;
+
+        if self.remaining() == 0 {
+            self.destroy()?;
+        }
+        Ok(pad)
+    }
+
+    /// Delete a pad whose bytes have all been handed out
+    fn destroy(&self) -> io::Result<()> {
+        File::create(&self.path)?.sync_all()?;
+        fs::remove_file(&self.path)
+    }
+
+    fn save_consumed(&self) -> io::Result<()> {
+        let lines: String = self.consumed.iter().map(|(start, end)| format!("{} {}\n", start, end)).collect();
+        fs::write(consumed_path(&self.path), lines)
+    }
+}
+
+fn consumed_path(pad_path: &Path) -> PathBuf {
+    let mut path = pad_path.as_os_str().to_owned();
+    path.push(".consumed");
+    PathBuf::from(path)
+}
+
+/// Two ciphertexts under the same pad XOR to the XOR of their plaintexts;
+/// the pad drops out entirely
+pub fn xor_ciphertexts(first: &[u8], second: &[u8]) -> Vec<u8> {
+    first.iter().zip(second).map(|(a, b)| a ^ b).collect()
+}
+
+/// A crib that fits at `position` of one plaintext, revealing `other` there
+/// in the other plaintext
+pub struct CribMatch {
+    pub position: usize,
+    pub other: String,
+}
+
+/// Crib dragging: slide a guessed word along `p1 ⊕ p2` and keep the
+/// positions where the other plaintext comes out as plausible text
+pub fn crib_drag(xored: &[u8], crib: &[u8]) -> Vec<CribMatch> {
+    if crib.is_empty() || crib.len() > xored.len() {
+        return Vec::new();
+    }
+    xored
+        .windows(crib.len())
+        .enumerate()
+        .filter_map(|(position, window)| {
+            let other: Vec<u8> = window.iter().zip(crib).map(|(a, b)| a ^ b).collect();
+            let plausible = other.iter().all(|&byte| byte.is_ascii_alphabetic() || b" .,'!?".contains(&byte));
+            plausible.then(|| CribMatch { position, other: String::from_utf8_lossy(&other).into_owned() })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pad_bytes_are_destroyed_and_never_reused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pad.bin");
+        let mut sender = PadFile::create(&path, 32).unwrap();
+        fs::copy(&path, dir.path().join("receiver.bin")).unwrap();
+        fs::copy(consumed_path(&path), dir.path().join("receiver.bin.consumed")).unwrap();
+
+        let (offset, pad) = sender.take(11).unwrap();
+        let ciphertext = xor(b"hello world", &pad).unwrap();
+        assert_eq!(offset, 0);
+        assert_eq!(&fs::read(&path).unwrap()[..11], &[0; 11]);
+        assert_eq!(sender.remaining(), 21);
+
+        let mut receiver = PadFile::open(dir.path().join("receiver.bin")).unwrap();
+        let pad = receiver.take_at(offset, ciphertext.len()).unwrap();
+        assert_eq!(xor(&ciphertext, &pad).unwrap(), b"hello world");
+        assert_eq!(receiver.take_at(5, 4).unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+
+        // Reopening keeps the record, and using the last bytes removes the pad
+        let mut sender = PadFile::open(&path).unwrap();
+        assert_eq!(sender.consumed(), &[(0, 11)]);
+        assert!(sender.take(22).is_err());
+        assert_eq!(sender.take(21).unwrap().0, 11);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn crib_dragging_a_reused_pad() {
+        let pad = generate_pad(64).unwrap();
+        let first = xor(b"attack the north gate at dawn", &pad).unwrap();
+        let second = xor(b"send more troops to the river", &pad).unwrap();
+
+        let matches = crib_drag(&xor_ciphertexts(&first, &second), b" the ");
+        assert!(matches.iter().any(|found| found.position == 6 && found.other == "ore t"));
+        assert!(matches.iter().any(|found| found.position == 19 && found.other == "te at"));
+    }
+}
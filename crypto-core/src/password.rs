@@ -0,0 +1,175 @@
+//! Password storage. PBKDF2 (RFC 8018) makes every guess cost thousands of
+//! HMAC calls; the memory-hard variant, a cut-down scrypt ROMix, also makes
+//! every guess fill and walk a large table, which GPUs and ASICs are bad at.
+
+use std::fmt;
+use std::io;
+
+use crate::codec::{decode_hex, encode_hex};
+use crate::hash::{sha256, HashFunction, Sha256};
+use crate::mac::{self, Hmac};
+use crate::rng;
+
+/// PBKDF2-HMAC-SHA256 rounds for new hashes (OWASP 2023 guidance)
+pub const DEFAULT_ITERATIONS: u32 = 600_000;
+
+/// log₂ of the memory-hard table size: 2¹⁶ entries of 32 bytes is 2 MiB
+pub const DEFAULT_COST: u32 = 16;
+
+/// 2²⁴ entries is 512 MiB, beyond which a stored hash is refused
+pub const MAX_COST: u32 = 24;
+
+pub const SALT_LENGTH: usize = 16;
+
+const HASH_LENGTH: usize = 32;
+
+/// How a stored password hash was derived
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kdf {
+    Pbkdf2 { iterations: u32 },
+    /// A table of 2^`cost` hashes
+    MemoryHard { cost: u32 },
+}
+
+impl Kdf {
+    pub fn derive(&self, password: &[u8], salt: &[u8]) -> Vec<u8> {
+        match *self {
+            Kdf::Pbkdf2 { iterations } => pbkdf2::<Sha256>(password, salt, iterations, HASH_LENGTH),
+            Kdf::MemoryHard { cost } => memory_hard(password, salt, cost),
+        }
+    }
+}
+
+/// A salted password hash, stored as `$pbkdf2-sha256$i=<iterations>$<salt>$<hash>`
+/// or `$romix-sha256$n=<cost>$<salt>$<hash>` with hex salt and hash
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PasswordHash {
+    pub kdf: Kdf,
+    pub salt: Vec<u8>,
+    pub hash: Vec<u8>,
+}
+
+impl PasswordHash {
+    /// Hash a password under a fresh random salt, so equal passwords get
+    /// different hashes and precomputed tables are useless
+    pub fn new(password: &[u8], kdf: Kdf) -> io::Result<Self> {
+        let mut salt = vec![0; SALT_LENGTH];
+        rng::fill(&mut salt)?;
+        Ok(Self::with_salt(password, kdf, salt))
+    }
+
+    pub fn with_salt(password: &[u8], kdf: Kdf, salt: Vec<u8>) -> Self {
+        let hash = kdf.derive(password, &salt);
+        PasswordHash { kdf, salt, hash }
+    }
+
+    pub fn verify(&self, password: &[u8]) -> bool {
+        mac::constant_time_eq(&self.kdf.derive(password, &self.salt), &self.hash)
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut fields = text.strip_prefix('$')?.split('$');
+        let kdf = match (fields.next()?, fields.next()?.split_once('=')?) {
+            ("pbkdf2-sha256", ("i", iterations)) => Kdf::Pbkdf2 { iterations: iterations.parse().ok().filter(|&i| i > 0)? },
+            ("romix-sha256", ("n", cost)) => Kdf::MemoryHard { cost: cost.parse().ok().filter(|&cost| cost <= MAX_COST)? },
+            _ => return None,
+        };
+        let salt = decode_hex(fields.next()?).ok()?;
+        let hash = decode_hex(fields.next()?).ok()?;
+        fields.next().is_none().then_some(PasswordHash { kdf, salt, hash })
+    }
+}
+
+impl fmt::Display for PasswordHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kdf {
+            Kdf::Pbkdf2 { iterations } => write!(f, "$pbkdf2-sha256$i={}", iterations)?,
+            Kdf::MemoryHard { cost } => write!(f, "$romix-sha256$n={}", cost)?,
+        }
+        write!(f, "${}${}", encode_hex(&self.salt), encode_hex(&self.hash))
+    }
+}
+
+/// PBKDF2: block i of the output is U₁ ⊕ U₂ ⊕ … ⊕ U_c with
+/// U₁ = HMAC(P, S ‖ i) and U_j = HMAC(P, U_{j−1})
+pub fn pbkdf2<H: HashFunction + Clone>(password: &[u8], salt: &[u8], iterations: u32, length: usize) -> Vec<u8> {
+    let keyed = Hmac::<H>::new(password);
+    let mut output = Vec::with_capacity(length);
+    for block in 1u32.. {
+        if output.len() >= length {
+            break;
+        }
+        let mut mac = keyed.clone();
+        mac.update(salt);
+        mac.update(&block.to_be_bytes());
+        let mut u = mac.finalize();
+        let mut t = u.clone();
+        for _ in 1..iterations {
+            let mut mac = keyed.clone();
+            mac.update(&u);
+            u = mac.finalize();
+            t.iter_mut().zip(&u).for_each(|(t, u)| *t ^= u);
+        }
+        output.extend_from_slice(&t);
+    }
+    output.truncate(length);
+    output
+}
+
+/// scrypt's ROMix with SHA-256 in place of Salsa20/8: fill a table with
+/// 2^`cost` chained hashes, then take as many steps to table entries chosen
+/// by the running value. Skipping the table means recomputing its entries
+/// on every step, so the memory cannot be traded away cheaply.
+pub fn memory_hard(password: &[u8], salt: &[u8], cost: u32) -> Vec<u8> {
+    let entries = 1usize << cost;
+    let mut x: [u8; 32] = pbkdf2::<Sha256>(password, salt, 1, 32).try_into().expect("32 bytes");
+    let mut table = Vec::with_capacity(entries);
+    for _ in 0..entries {
+        table.push(x);
+        x = sha256(&x);
+    }
+    for _ in 0..entries {
+        let j = u64::from_le_bytes(x[..8].try_into().expect("8 bytes")) as usize % entries;
+        x.iter_mut().zip(&table[j]).for_each(|(x, entry)| *x ^= entry);
+        x = sha256(&x);
+    }
+    pbkdf2::<Sha256>(password, &x, 1, HASH_LENGTH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc_7914_pbkdf2_vectors() {
+        assert_eq!(
+            encode_hex(&pbkdf2::<Sha256>(b"passwd", b"salt", 1, 64)),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+             49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+        );
+        assert_eq!(
+            encode_hex(&pbkdf2::<Sha256>(b"password", b"salt", 4096, 32)),
+            "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a"
+        );
+    }
+
+    #[test]
+    fn stored_hashes_round_trip_and_verify() {
+        for kdf in [Kdf::Pbkdf2 { iterations: 1000 }, Kdf::MemoryHard { cost: 8 }] {
+            let stored = PasswordHash::new(b"correct horse", kdf).unwrap();
+            let parsed = PasswordHash::parse(&stored.to_string()).unwrap();
+            assert_eq!(parsed, stored);
+            assert!(parsed.verify(b"correct horse"));
+            assert!(!parsed.verify(b"correct horse battery"));
+            assert_ne!(PasswordHash::new(b"correct horse", kdf).unwrap().hash, stored.hash);
+        }
+        assert!(PasswordHash::parse("$md5$i=1$00$00").is_none());
+        assert!(PasswordHash::parse("$pbkdf2-sha256$i=0$00$00").is_none());
+    }
+
+    #[test]
+    fn memory_hard_output_depends_on_cost() {
+        assert_eq!(memory_hard(b"pw", b"salt", 6), memory_hard(b"pw", b"salt", 6));
+        assert_ne!(memory_hard(b"pw", b"salt", 6), memory_hard(b"pw", b"salt", 7));
+    }
+}
@@ -0,0 +1,165 @@
+//! Primality testing and prime generation for the public-key modules
+
+use std::io;
+use std::sync::OnceLock;
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+use crate::rng;
+
+/// Miller–Rabin rounds for generated primes: a composite survives each
+/// round with probability at most 1/4, so 2⁻⁸⁰ overall
+pub const DEFAULT_ROUNDS: usize = 40;
+
+/// Primes below 2000, by the sieve of Eratosthenes
+pub fn small_primes() -> &'static [u32] {
+    static PRIMES: OnceLock<Vec<u32>> = OnceLock::new();
+    PRIMES.get_or_init(|| {
+        const LIMIT: usize = 2000;
+        let mut composite = vec![false; LIMIT];
+        let mut primes = Vec::new();
+        for n in 2..LIMIT {
+            if !composite[n] {
+                primes.push(n as u32);
+                (n * n..LIMIT).step_by(n).for_each(|multiple| composite[multiple] = true);
+            }
+        }
+        primes
+    })
+}
+
+/// A uniformly random number of at most `bits` bits
+pub fn random_bits(bits: u64) -> io::Result<BigUint> {
+    let bytes = bits.div_ceil(8);
+    Ok(BigUint::from_bytes_be(&rng::bytes(bytes as usize)?) >> (bytes * 8 - bits))
+}
+
+/// A uniformly random number in `[low, high)`, by rejection sampling
+pub fn random_range(low: &BigUint, high: &BigUint) -> io::Result<BigUint> {
+    if low >= high {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "The range is empty"));
+    }
+    let width = high - low;
+    loop {
+        let candidate = random_bits(width.bits())?;
+        if candidate < width {
+            return Ok(low + candidate);
+        }
+    }
+}
+
+/// Whether `base` proves `n` (odd, > 3) composite: with n − 1 = 2ˢ·d, a prime
+/// n has either bᵈ ≡ 1 or b^(2ʳ·d) ≡ −1 for some r < s
+pub fn is_witness(n: &BigUint, base: &BigUint) -> bool {
+    let n_minus_one = n - 1u32;
+    let s = n_minus_one.trailing_zeros().unwrap_or_default();
+    let d = &n_minus_one >> s;
+
+    let mut x = base.modpow(&d, n);
+    if x.is_one() || x == n_minus_one {
+        return false;
+    }
+    for _ in 1..s {
+        x = x.modpow(&BigUint::from(2u32), n);
+        if x == n_minus_one {
+            return false;
+        }
+    }
+    true
+}
+
+/// Trial division by the small primes, then Miller–Rabin with `rounds` random bases
+pub fn is_probable_prime(n: &BigUint, rounds: usize) -> io::Result<bool> {
+    if n < &BigUint::from(2u32) {
+        return Ok(false);
+    }
+    for &small in small_primes() {
+        if n == &BigUint::from(small) {
+            return Ok(true);
+        }
+        if (n % small).is_zero() {
+            return Ok(false);
+        }
+    }
+
+    let two = BigUint::from(2u32);
+    let upper = n - 1u32;
+    for _ in 0..rounds {
+        if is_witness(n, &random_range(&two, &upper)?) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// A random prime of exactly `bits` bits. The top two bits are set, so the
+/// product of two such primes has exactly twice as many bits.
+pub fn random_prime(bits: u64) -> io::Result<BigUint> {
+    if bits < 3 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Primes are generated with at least 3 bits"));
+    }
+    loop {
+        let mut candidate = random_bits(bits)?;
+        candidate.set_bit(bits - 1, true);
+        candidate.set_bit(bits - 2, true);
+        candidate.set_bit(0, true);
+        if is_probable_prime(&candidate, DEFAULT_ROUNDS)? {
+            return Ok(candidate);
+        }
+    }
+}
+
+/// A safe prime p = 2q + 1 of `bits` bits with q prime too, so the
+/// multiplicative group mod p has a large prime-order subgroup (for
+/// Diffie–Hellman and ElGamal). Both candidates are sieved before the slow test.
+pub fn safe_prime(bits: u64) -> io::Result<BigUint> {
+    if bits < 4 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Safe primes are generated with at least 4 bits"));
+    }
+    loop {
+        let mut q = random_bits(bits - 1)?;
+        q.set_bit(bits - 2, true);
+        q.set_bit(0, true);
+        let p: BigUint = (&q << 1u32) + 1u32;
+
+        // p ≡ 0 (mod r) exactly when q ≡ (r − 1)/2, so both sieve on q
+        let sieved = small_primes().iter().skip(1).all(|&r| {
+            let residue = (&q % r).iter_u32_digits().next().unwrap_or_default();
+            (residue != 0 || q == BigUint::from(r)) && residue != (r - 1) / 2
+        });
+        if sieved && is_probable_prime(&q, DEFAULT_ROUNDS)? && is_probable_prime(&p, DEFAULT_ROUNDS)? {
+            return Ok(p);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tells_primes_from_composites() {
+        let check = |n: u64| is_probable_prime(&BigUint::from(n), DEFAULT_ROUNDS).unwrap();
+        assert!(check(2) && check(1999) && check(1_000_000_007));
+        assert!(!check(1) && !check(1_000_000_007 * 3));
+        // Carmichael numbers fool the Fermat test but not Miller–Rabin
+        assert!(!check(561) && !check(41_041) && !check(3_215_031_751));
+        assert!(is_witness(&BigUint::from(221u32), &BigUint::from(137u32)));
+        assert!(!is_witness(&BigUint::from(221u32), &BigUint::from(174u32)));
+
+        // 2¹²⁷ − 1, a Mersenne prime
+        let mersenne = (BigUint::one() << 127u32) - 1u32;
+        assert!(is_probable_prime(&mersenne, DEFAULT_ROUNDS).unwrap());
+    }
+
+    #[test]
+    fn generates_primes_of_the_requested_size() {
+        let p = random_prime(256).unwrap();
+        assert_eq!(p.bits(), 256);
+
+        let safe = safe_prime(128).unwrap();
+        assert_eq!(safe.bits(), 128);
+        assert!(is_probable_prime(&(&safe >> 1u32), DEFAULT_ROUNDS).unwrap());
+    }
+}
@@ -0,0 +1,158 @@
+//! Rail fence cipher: the text is written in a zigzag over a number of rails
+//! and read off rail by rail
+
+use crate::cipher::{CipherError, CipherFamily, CipherInfo, ClassicalCipher};
+use crate::language::{quadgram_score, Language};
+
+/// Rearranges every character of the text, spaces and punctuation included
+pub struct RailFence {
+    rails: usize,
+    /// How far into the zigzag the first character is written
+    offset: usize,
+}
+
+impl RailFence {
+    pub fn with_offset(rails: usize, offset: usize) -> Result<Self, CipherError> {
+        // Past usize::MAX / 2 the period no longer fits in a usize
+        if !(2..=usize::MAX / 2).contains(&rails) {
+            return Err(CipherError::InvalidKey(INFO.key_description.to_string()));
+        }
+        Ok(RailFence { rails, offset: offset % period(rails) })
+    }
+
+    pub fn rails(&self) -> usize {
+        self.rails
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Rail of the character at `position` in the plaintext
+    pub fn rail(&self, position: usize) -> usize {
+        let phase = (position + self.offset) % period(self.rails);
+        if phase < self.rails {
+            phase
+        } else {
+            period(self.rails) - phase
+        }
+    }
+
+    /// Plaintext positions in the order they appear in the ciphertext
+    fn order(&self, length: usize) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..length).collect();
+        // Stable, so each rail keeps its characters left to right
+        order.sort_by_key(|&position| self.rail(position));
+        order
+    }
+}
+
+/// Length of one down-and-up stroke of the zigzag
+fn period(rails: usize) -> usize {
+    2 * (rails - 1)
+}
+
+impl ClassicalCipher for RailFence {
+    fn validate_key(key: &str) -> Result<(), CipherError> {
+        parse_key(key).map(|_| ())
+    }
+
+    fn new(key: &str) -> Result<Self, CipherError> {
+        let (rails, offset) = parse_key(key)?;
+        Self::with_offset(rails, offset)
+    }
+
+    fn info(&self) -> CipherInfo {
+        INFO
+    }
+
+    fn encrypt(&self, plaintext: &str) -> Result<String, CipherError> {
+        let chars: Vec<char> = plaintext.chars().collect();
+        Ok(self.order(chars.len()).into_iter().map(|position| chars[position]).collect())
+    }
+
+    fn decrypt(&self, ciphertext: &str) -> Result<String, CipherError> {
+        let chars: Vec<char> = ciphertext.chars().collect();
+        let mut plaintext = vec![' '; chars.len()];
+        for (&c, position) in chars.iter().zip(self.order(chars.len())) {
+            plaintext[position] = c;
+        }
+        Ok(plaintext.into_iter().collect())
+    }
+}
+
+const INFO: CipherInfo = CipherInfo {
+    name: "railfence",
+    family: CipherFamily::Transposition,
+    key_description: "a number of rails of at least 2, optionally followed by an offset (\"3,1\")",
+    block_size: None,
+};
+
+fn parse_key(key: &str) -> Result<(usize, usize), CipherError> {
+    let invalid = || CipherError::InvalidKey(INFO.key_description.to_string());
+    let (rails, offset) = key.split_once(',').unwrap_or((key, "0"));
+    let rails = rails.trim().parse::<usize>().ok().filter(|&rails| rails >= 2).ok_or_else(invalid)?;
+    let offset = offset.trim().parse::<usize>().map_err(|_| invalid())?;
+    Ok((rails, offset))
+}
+
+/// A decryption tried by the brute-force solver
+pub struct Candidate {
+    pub rails: usize,
+    pub offset: usize,
+    pub plaintext: String,
+    /// Quadgram score in the language, higher is better
+    pub score: f64,
+}
+
+/// Decrypt with every rail count up to `max_rails` and every offset, best first
+pub fn brute_force(ciphertext: &str, max_rails: usize, language: Language) -> Vec<Candidate> {
+    let max_rails = max_rails.min(ciphertext.chars().count().max(2));
+    let mut candidates: Vec<Candidate> = (2..=max_rails)
+        .flat_map(|rails| (0..period(rails)).map(move |offset| (rails, offset)))
+        .map(|(rails, offset)| {
+            let cipher = RailFence { rails, offset };
+            let plaintext = cipher.decrypt(ciphertext).unwrap_or_default();
+            let score = quadgram_score(&plaintext, language);
+            Candidate { rails, offset, plaintext, score }
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypts_the_textbook_example() {
+        let cipher = RailFence::new("3").unwrap();
+        let ciphertext = cipher.encrypt("WEAREDISCOVEREDFLEEATONCE").unwrap();
+        assert_eq!(ciphertext, "WECRLTEERDSOEEFEAOCAIVDEN");
+        assert_eq!(cipher.decrypt(&ciphertext).unwrap(), "WEAREDISCOVEREDFLEEATONCE");
+
+        assert!(RailFence::new("1").is_err());
+        assert!(RailFence::new("3,x").is_err());
+        assert!(RailFence::new(&usize::MAX.to_string()).is_err());
+    }
+
+    #[test]
+    fn offsets_round_trip_and_change_the_ciphertext() {
+        let plain = RailFence::new("4").unwrap();
+        let shifted = RailFence::new("4,2").unwrap();
+        let text = "Meet me at the usual place, 10pm.";
+        let ciphertext = shifted.encrypt(text).unwrap();
+        assert_ne!(ciphertext, plain.encrypt(text).unwrap());
+        assert_eq!(shifted.decrypt(&ciphertext).unwrap(), text);
+    }
+
+    #[test]
+    fn brute_force_finds_the_rails_and_offset() {
+        let text = "THE ENEMY WILL ATTACK AT DAWN FROM THE NORTHERN RIDGE SO HOLD THE BRIDGE";
+        let ciphertext = RailFence::new("5,3").unwrap().encrypt(text).unwrap();
+        let best = &brute_force(&ciphertext, 10, Language::English)[0];
+        assert_eq!(best.plaintext, text);
+        assert_eq!((best.rails, best.offset), (5, 3));
+    }
+}
@@ -0,0 +1,290 @@
+//! Statistical tests from NIST SP 800-22 (frequency, runs, serial and
+//! approximate entropy) and entropy estimates, for judging keystreams and
+//! generator output. Each test yields p-values: the probability that truly
+//! random bits look at least this unusual. A p-value below 0.01 fails.
+
+use std::f64::consts::LN_2;
+
+/// Significance level below which a p-value fails (SP 800-22, section 1.1.5)
+pub const SIGNIFICANCE: f64 = 0.01;
+
+/// Length SP 800-22 recommends as the minimum for every test here
+pub const RECOMMENDED_BITS: usize = 100;
+
+/// The bits of `bytes`, most significant first, as 0s and 1s
+pub fn bits_of(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|byte| (0..8).rev().map(move |bit| (byte >> bit) & 1)).collect()
+}
+
+/// Bits written as `0` and `1` characters; whitespace is skipped
+pub fn parse_bits(text: &str) -> Option<Vec<u8>> {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            '0' => Some(0),
+            '1' => Some(1),
+            _ => None,
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestResult {
+    pub name: &'static str,
+    /// The test statistic the p-values come from
+    pub statistic: f64,
+    pub p_values: Vec<f64>,
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        self.p_values.iter().all(|&p| p >= SIGNIFICANCE)
+    }
+}
+
+/// Frequency (monobit) test, section 2.1: about as many ones as zeros
+pub fn monobit(bits: &[u8]) -> TestResult {
+    let sum: i64 = bits.iter().map(|&bit| if bit == 1 { 1 } else { -1 }).sum();
+    let statistic = sum.unsigned_abs() as f64 / (bits.len() as f64).sqrt();
+    TestResult { name: "monobit", statistic, p_values: vec![erfc(statistic / 2f64.sqrt())] }
+}
+
+/// Runs test, section 2.3: the number of unbroken runs of equal bits, so
+/// that the sequence neither sticks nor alternates too much
+pub fn runs(bits: &[u8]) -> TestResult {
+    let n = bits.len() as f64;
+    let pi = bits.iter().filter(|&&bit| bit == 1).count() as f64 / n;
+    let runs = 1 + bits.windows(2).filter(|pair| pair[0] != pair[1]).count();
+
+    // The test is meaningless if the frequency test already fails badly
+    let p_value = if (pi - 0.5).abs() >= 2.0 / n.sqrt() {
+        0.0
+    } else {
+        erfc((runs as f64 - 2.0 * n * pi * (1.0 - pi)).abs() / (2.0 * (2.0 * n).sqrt() * pi * (1.0 - pi)))
+    };
+    TestResult { name: "runs", statistic: runs as f64, p_values: vec![p_value] }
+}
+
+/// Counts of every overlapping `m`-bit pattern, the sequence wrapping
+/// around at the end
+fn pattern_counts(bits: &[u8], m: usize) -> Vec<usize> {
+    let mut counts = vec![0; 1 << m];
+    if m == 0 {
+        counts[0] = bits.len();
+        return counts;
+    }
+    for start in 0..bits.len() {
+        let pattern = (0..m).fold(0, |pattern, offset| (pattern << 1) | usize::from(bits[(start + offset) % bits.len()]));
+        counts[pattern] += 1;
+    }
+    counts
+}
+
+/// ψ²ₘ of section 2.11.4
+fn psi_squared(bits: &[u8], m: usize) -> f64 {
+    if m == 0 {
+        return 0.0;
+    }
+    let n = bits.len() as f64;
+    let sum: f64 = pattern_counts(bits, m).iter().map(|&count| (count * count) as f64).sum();
+    (1u64 << m) as f64 / n * sum - n
+}
+
+/// Serial test, section 2.11: every `m`-bit pattern about equally common.
+/// Gives two p-values, from the first and second differences of ψ².
+pub fn serial(bits: &[u8], m: usize) -> TestResult {
+    let psi = [m, m.saturating_sub(1), m.saturating_sub(2)].map(|m| psi_squared(bits, m));
+    let delta = psi[0] - psi[1];
+    let delta_squared = psi[0] - 2.0 * psi[1] + psi[2];
+    let p_values = vec![
+        igamc(2f64.powi(m as i32 - 2), delta / 2.0),
+        igamc(2f64.powi(m as i32 - 3), delta_squared / 2.0),
+    ];
+    TestResult { name: "serial", statistic: delta, p_values }
+}
+
+/// Φ of section 2.12.4: the average log frequency of the `m`-bit patterns
+fn phi(bits: &[u8], m: usize) -> f64 {
+    let n = bits.len() as f64;
+    pattern_counts(bits, m)
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let frequency = count as f64 / n;
+            frequency * frequency.ln()
+        })
+        .sum()
+}
+
+/// Approximate entropy test, section 2.12: `m`-bit patterns should tell
+/// little about the bit that follows them
+pub fn approximate_entropy(bits: &[u8], m: usize) -> TestResult {
+    let approximate_entropy = phi(bits, m) - phi(bits, m + 1);
+    let chi_squared = 2.0 * bits.len() as f64 * (LN_2 - approximate_entropy);
+    TestResult {
+        name: "approximate-entropy",
+        statistic: chi_squared,
+        p_values: vec![igamc(2f64.powi(m as i32 - 1), chi_squared / 2.0)],
+    }
+}
+
+/// Largest block lengths SP 800-22 allows for the serial and approximate
+/// entropy tests on `n` bits (⌊log₂ n⌋ − 3 and ⌊log₂ n⌋ − 6), at least 2
+pub fn default_block_lengths(n: usize) -> (usize, usize) {
+    let log2 = n.max(1).ilog2() as usize;
+    (log2.saturating_sub(3).clamp(2, 16), log2.saturating_sub(6).clamp(2, 10))
+}
+
+/// Entropy per bit estimated from the non-overlapping `block`-bit symbols:
+/// Shannon entropy (the average surprise) and min-entropy (the surprise
+/// of the most common symbol, what an attacker guessing it faces)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EntropyEstimate {
+    pub block: usize,
+    pub shannon: f64,
+    pub min: f64,
+}
+
+pub fn entropy_estimate(bits: &[u8], block: usize) -> EntropyEstimate {
+    let mut counts = vec![0usize; 1 << block];
+    for chunk in bits.chunks_exact(block) {
+        counts[chunk.iter().fold(0, |symbol, &bit| (symbol << 1) | usize::from(bit))] += 1;
+    }
+    let total = counts.iter().sum::<usize>().max(1) as f64;
+
+    let shannon: f64 = counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / total;
+            p * (1.0 / p).log2()
+        })
+        .sum();
+    let most_common = counts.iter().max().copied().unwrap_or(0).max(1) as f64 / total;
+    EntropyEstimate { block, shannon: shannon / block as f64, min: (1.0 / most_common).log2() / block as f64 }
+}
+
+/// The four tests at the default block lengths
+pub fn run_all(bits: &[u8]) -> Vec<TestResult> {
+    let (serial_block, entropy_block) = default_block_lengths(bits.len());
+    vec![monobit(bits), runs(bits), serial(bits, serial_block), approximate_entropy(bits, entropy_block)]
+}
+
+/// Complementary error function, erfc(x) = Q(½, x²) for x ≥ 0
+pub fn erfc(x: f64) -> f64 {
+    if x < 0.0 {
+        2.0 - erfc(-x)
+    } else {
+        igamc(0.5, x * x)
+    }
+}
+
+/// Upper regularized incomplete gamma function Q(a, x), the chi-square
+/// tail probability with 2a degrees of freedom at 2x: one minus a power
+/// series below a + 1, a continued fraction (Lentz's method) above
+pub fn igamc(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let prefix = (a * x.ln() - x - ln_gamma(a)).exp();
+
+    if x < a + 1.0 {
+        let (mut term, mut sum, mut n) = (1.0 / a, 1.0 / a, a);
+        while term.abs() > sum.abs() * 1e-15 {
+            n += 1.0;
+            term *= x / n;
+            sum += term;
+        }
+        (1.0 - sum * prefix).max(0.0)
+    } else {
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut fraction = d;
+        for i in 1..1000 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            d = if d.abs() < tiny { 1.0 / tiny } else { 1.0 / d };
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            let delta = d * c;
+            fraction *= delta;
+            if (delta - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        (prefix * fraction).min(1.0)
+    }
+}
+
+/// ln Γ(x) for x > 0 by the Lanczos approximation (g = 7, n = 9)
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // Reflection: Γ(x)Γ(1 − x) = π / sin(πx)
+        return (std::f64::consts::PI / (std::f64::consts::PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let series = COEFFICIENTS[1..].iter().enumerate().fold(COEFFICIENTS[0], |sum, (i, c)| sum + c / (x + i as f64 + 1.0));
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(actual: f64, expected: f64) -> bool {
+        (actual - expected).abs() < 1e-6
+    }
+
+    #[test]
+    fn sp_800_22_worked_examples() {
+        let bits = |text: &str| parse_bits(text).unwrap();
+        // Sections 2.1.8, 2.3.8, 2.11.8 and 2.12.8
+        assert!(close(monobit(&bits("1011010101")).p_values[0], 0.527089));
+        assert!(close(runs(&bits("1001101011")).p_values[0], 0.147232));
+        let serial = serial(&bits("0011011101"), 3);
+        assert!(close(serial.p_values[0], 0.808792) && close(serial.p_values[1], 0.670320), "{:?}", serial);
+        assert!(close(approximate_entropy(&bits("0100110101"), 3).p_values[0], 0.261961));
+    }
+
+    #[test]
+    fn special_functions_match_closed_forms() {
+        assert!((ln_gamma(5.0) - 24f64.ln()).abs() < 1e-12);
+        assert!((ln_gamma(0.5) - std::f64::consts::PI.sqrt().ln()).abs() < 1e-12);
+        // Q(1, x) = e^(−x)
+        assert!((igamc(1.0, 1.5) - (-1.5f64).exp()).abs() < 1e-12);
+        assert!((igamc(1.0, 0.2) - (-0.2f64).exp()).abs() < 1e-12);
+        assert!((erfc(1.0) - 0.157_299_207_050_285_1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn stuck_and_periodic_streams_fail() {
+        let random = bits_of(&crate::otp::generate_pad(2048).unwrap());
+        let entropy = entropy_estimate(&random, 8);
+        assert!(entropy.shannon > 0.95 && entropy.min > 0.7, "{:?}", entropy);
+
+        let alternating: Vec<u8> = (0..4096).map(|i| (i % 2) as u8).collect();
+        assert!(monobit(&alternating).passed());
+        assert!(!runs(&alternating).passed());
+        assert!(!serial(&alternating, 4).passed());
+        assert!(!monobit(&vec![1; 4096]).passed());
+        assert!(entropy_estimate(&alternating, 8).min < 0.01);
+    }
+}
@@ -0,0 +1,251 @@
+//! Every cipher the tools know, by name. In Kerckhoffs's spirit a cipher is
+//! fully described in public (its name, family and the key it takes), and a
+//! [`Registration`] adds what a tool needs to run it without knowing its
+//! type: a constructor, a valid example key and a few capabilities.
+//!
+//! crypto-core registers its own ciphers in [`Registry::builtin`]; other
+//! crates, the student plugins among them, implement [`CipherPlugin`] and
+//! are added with [`Registry::install`]. Whatever is registered is
+//! available to `crypto` as a subcommand and a pipeline stage, and to the
+//! benchmarks, the conformance suite and `crypto analyze --cipher`.
+
+use std::io;
+
+use crate::aes::Aes128;
+use crate::caesar::Caesar;
+use crate::chacha20::ChaCha20;
+use crate::cipher::{CipherError, CipherInfo, ClassicalCipher, SymmetricCipher};
+use crate::columnar::Columnar;
+use crate::hill::Hill;
+use crate::railfence::RailFence;
+use crate::rc4::Rc4;
+use crate::vigenere::Vigenere;
+
+type NewClassical = fn(&str) -> Result<Box<dyn ClassicalCipher>, CipherError>;
+type NewSymmetric = fn(&[u8]) -> Result<Box<dyn SymmetricCipher>, CipherError>;
+
+/// Builds a cipher from its key, behind the trait object the tools use
+#[derive(Clone, Copy)]
+pub enum Constructor {
+    /// Letters in, letters out, with a text key
+    Classical(NewClassical),
+    /// Bytes in, bytes out, with a binary key
+    Symmetric(NewSymmetric),
+}
+
+/// What tools may rely on when testing or showing a cipher
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Decryption gives back exactly the plaintext. Otherwise it gives back
+    /// a normalised form (upper-cased, padded, letters only) which itself
+    /// round-trips exactly.
+    pub exact_round_trip: bool,
+    /// The same plaintext and key always give the same ciphertext, with no
+    /// random IV or nonce
+    pub deterministic: bool,
+}
+
+/// One cipher as the tools see it
+#[derive(Clone)]
+pub struct Registration {
+    pub info: CipherInfo,
+    /// A key the cipher accepts, as text (for symmetric ciphers its bytes
+    /// are the key), for benchmarks, the conformance suite and demos
+    pub example_key: &'static str,
+    pub capabilities: Capabilities,
+    pub constructor: Constructor,
+    /// Name of the plugin that registered it, `crypto-core` for the built-in ones
+    pub origin: &'static str,
+}
+
+fn classical<C: ClassicalCipher + 'static>(key: &str) -> Result<Box<dyn ClassicalCipher>, CipherError> {
+    Ok(Box::new(C::new(key)?))
+}
+
+fn symmetric<C: SymmetricCipher + 'static>(key: &[u8]) -> Result<Box<dyn SymmetricCipher>, CipherError> {
+    Ok(Box::new(C::new(key)?))
+}
+
+impl Registration {
+    /// A classical cipher, described by an instance built from `example_key`
+    pub fn classical<C: ClassicalCipher + 'static>(example_key: &'static str, capabilities: Capabilities) -> Result<Self, CipherError> {
+        Ok(Registration {
+            info: C::new(example_key)?.info(),
+            example_key,
+            capabilities,
+            constructor: Constructor::Classical(classical::<C>),
+            origin: "",
+        })
+    }
+
+    /// A symmetric cipher, described by an instance built from `example_key`
+    pub fn symmetric<C: SymmetricCipher + 'static>(example_key: &'static str, capabilities: Capabilities) -> Result<Self, CipherError> {
+        Ok(Registration {
+            info: C::new(example_key.as_bytes())?.info(),
+            example_key,
+            capabilities,
+            constructor: Constructor::Symmetric(symmetric::<C>),
+            origin: "",
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.info.name
+    }
+
+    pub fn is_classical(&self) -> bool {
+        matches!(self.constructor, Constructor::Classical(_))
+    }
+
+    /// The classical cipher under `key`, or an error for a symmetric one
+    pub fn classical_cipher(&self, key: &str) -> Result<Box<dyn ClassicalCipher>, CipherError> {
+        match self.constructor {
+            Constructor::Classical(new) => new(key),
+            Constructor::Symmetric(_) => Err(CipherError::InvalidInput(format!("{} works on bytes, not letters", self.name()))),
+        }
+    }
+
+    /// The symmetric cipher under `key`, or an error for a classical one
+    pub fn symmetric_cipher(&self, key: &[u8]) -> Result<Box<dyn SymmetricCipher>, CipherError> {
+        match self.constructor {
+            Constructor::Symmetric(new) => new(key),
+            Constructor::Classical(_) => Err(CipherError::InvalidInput(format!("{} works on letters, not bytes", self.name()))),
+        }
+    }
+}
+
+/// A crate of ciphers outside crypto-core. Build the crate into a tool
+/// (the `student-ciphers` feature of `crypto` does this for the student
+/// plugins), install it, and its ciphers are registered next to the rest.
+pub trait CipherPlugin {
+    /// Shown as the origin of its ciphers
+    fn name(&self) -> &'static str;
+
+    /// The plugin's ciphers
+    fn registrations(&self) -> Result<Vec<Registration>, CipherError>;
+}
+
+/// Ciphers by name, in the order they were registered
+#[derive(Clone, Default)]
+pub struct Registry {
+    registrations: Vec<Registration>,
+}
+
+/// The example keys are valid, so this only fails if a cipher is broken
+fn builtin_registrations() -> Result<Vec<Registration>, CipherError> {
+    let exact = Capabilities { exact_round_trip: true, deterministic: true };
+    let normalising = Capabilities { exact_round_trip: false, deterministic: true };
+    Ok(vec![
+        Registration::classical::<Caesar>("3", exact)?,
+        Registration::classical::<Vigenere>("LEMON", exact)?,
+        Registration::classical::<Hill>("GYBNQKURP", normalising)?,
+        Registration::classical::<RailFence>("3", exact)?,
+        Registration::classical::<Columnar>("ZEBRAS", normalising)?,
+        Registration::symmetric::<Aes128>("YELLOW SUBMARINE", exact)?,
+        Registration::symmetric::<Rc4>("Key", exact)?,
+        Registration::symmetric::<ChaCha20>("a 32-byte key for ChaCha20 demos", Capabilities { exact_round_trip: true, deterministic: false })?,
+    ])
+}
+
+impl Registry {
+    /// crypto-core's own ciphers
+    pub fn builtin() -> Self {
+        let mut registry = Registry::default();
+        for mut registration in builtin_registrations().expect("the built-in example keys are valid") {
+            registration.origin = "crypto-core";
+            registry.add(registration).expect("the built-in names are distinct");
+        }
+        registry
+    }
+
+    /// Register one cipher. Names are lowercase letters, digits and `-`,
+    /// and unique, since each becomes a subcommand and a stage name.
+    pub fn add(&mut self, registration: Registration) -> io::Result<()> {
+        let name = registration.name();
+        let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !valid {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("invalid cipher name {:?}", name)));
+        }
+        if let Some(existing) = self.get(name) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("a cipher named {} is already registered by {}", name, existing.origin),
+            ));
+        }
+        self.registrations.push(registration);
+        Ok(())
+    }
+
+    /// Register every cipher of `plugin`; none of them if any is rejected
+    pub fn install(&mut self, plugin: &dyn CipherPlugin) -> io::Result<()> {
+        let registrations = plugin
+            .registrations()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("{}: {}", plugin.name(), e)))?;
+        let mut extended = self.clone();
+        for mut registration in registrations {
+            registration.origin = plugin.name();
+            extended.add(registration)?;
+        }
+        *self = extended;
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Registration> {
+        self.registrations.iter().find(|registration| registration.name() == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Registration> {
+        self.registrations.iter()
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.iter().map(Registration::name).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Doubled;
+
+    impl CipherPlugin for Doubled {
+        fn name(&self) -> &'static str {
+            "doubled"
+        }
+
+        fn registrations(&self) -> Result<Vec<Registration>, CipherError> {
+            let exact = Capabilities { exact_round_trip: true, deterministic: true };
+            Ok(vec![Registration::classical::<Caesar>("5", exact)?])
+        }
+    }
+
+    #[test]
+    fn builtin_ciphers_run_by_name() {
+        let registry = Registry::builtin();
+        assert_eq!(registry.names(), ["caesar", "vigenere", "hill", "railfence", "columnar", "aes", "rc4", "chacha20"]);
+
+        let caesar = registry.get("caesar").unwrap();
+        assert_eq!(caesar.origin, "crypto-core");
+        assert_eq!(caesar.classical_cipher("3").unwrap().encrypt("abc").unwrap(), "def");
+        assert!(caesar.symmetric_cipher(b"3").is_err());
+
+        let aes = registry.get("aes").unwrap();
+        assert_eq!(aes.info.block_size, Some(16));
+        let cipher = aes.symmetric_cipher(aes.example_key.as_bytes()).unwrap();
+        assert_eq!(cipher.decrypt(&cipher.encrypt(b"hi")).unwrap(), b"hi");
+        assert!(registry.get("enigma").is_none());
+    }
+
+    #[test]
+    fn plugins_cannot_take_a_registered_name() {
+        let mut registry = Registry::builtin();
+        let error = registry.install(&Doubled).unwrap_err();
+        assert!(error.to_string().contains("already registered by crypto-core"));
+        assert_eq!(registry.names().len(), 8);
+
+        let mut empty = Registry::default();
+        empty.install(&Doubled).unwrap();
+        assert_eq!(empty.get("caesar").unwrap().origin, "doubled");
+    }
+}
@@ -0,0 +1,134 @@
+//! Shamir's (k, n) threshold scheme over GF(2⁸): every byte of the secret is
+//! the constant term of its own random polynomial of degree k − 1, and share
+//! x holds the values of all those polynomials at x. Any k shares fix the
+//! polynomials by Lagrange interpolation; k − 1 say nothing about the secret.
+
+use std::fmt;
+use std::io;
+
+use crate::aes::{gf_inverse, gf_mul};
+use crate::codec::{decode_hex, encode_hex};
+use crate::rng;
+
+/// One share: the x coordinate (never 0, which would be the secret itself)
+/// and the polynomial values there, one per secret byte
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Share {
+    pub x: u8,
+    pub y: Vec<u8>,
+}
+
+impl Share {
+    /// Parse the `<x>-<hex>` form `Display` writes
+    pub fn parse(text: &str) -> Option<Self> {
+        let (x, y) = text.trim().split_once('-')?;
+        let x = x.parse().ok().filter(|&x| x != 0)?;
+        Some(Share { x, y: decode_hex(y).ok()? })
+    }
+}
+
+impl fmt::Display for Share {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.x, encode_hex(&self.y))
+    }
+}
+
+/// Split a secret into `count` shares, any `threshold` of which recover it
+pub fn split(secret: &[u8], threshold: u8, count: u8) -> io::Result<Vec<Share>> {
+    if threshold == 0 || threshold > count {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Need 1 ≤ threshold ≤ shares, got threshold {} of {}", threshold, count)
+        ));
+    }
+    // Coefficients a₁ … a_{k−1} of each byte's polynomial; a₀ is the byte
+    let degree = usize::from(threshold) - 1;
+    let mut coefficients = vec![0; secret.len() * degree];
+    rng::fill(&mut coefficients)?;
+
+    Ok((1..=count)
+        .map(|x| {
+            let y = secret
+                .iter()
+                .enumerate()
+                .map(|(i, &byte)| {
+                    // Horner's rule from the highest coefficient down
+                    let higher = &coefficients[i * degree..(i + 1) * degree];
+                    let upper = higher.iter().rev().fold(0, |value, &a| gf_mul(value, x) ^ a);
+                    gf_mul(upper, x) ^ byte
+                })
+                .collect();
+            Share { x, y }
+        })
+        .collect())
+}
+
+/// Interpolate the shares' polynomials at 0. With fewer shares than the
+/// threshold this still returns bytes, just not the secret.
+pub fn combine(shares: &[Share]) -> io::Result<Vec<u8>> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidInput, message.to_string());
+    let Some(first) = shares.first() else {
+        return Err(invalid("No shares given"));
+    };
+    if shares.iter().any(|share| share.y.len() != first.y.len()) {
+        return Err(invalid("The shares are of different secrets (lengths differ)"));
+    }
+    for (i, share) in shares.iter().enumerate() {
+        if share.x == 0 || shares[..i].iter().any(|other| other.x == share.x) {
+            return Err(invalid(&format!("Share {} is given twice or invalid", share.x)));
+        }
+    }
+
+    // Lagrange basis at 0: ℓᵢ(0) = ∏ xⱼ / (xⱼ − xᵢ), where − is ⊕ in GF(2⁸)
+    let basis: Vec<u8> = shares
+        .iter()
+        .map(|share| {
+            shares
+                .iter()
+                .filter(|other| other.x != share.x)
+                .fold(1, |product, other| gf_mul(product, gf_mul(other.x, gf_inverse(other.x ^ share.x))))
+        })
+        .collect();
+
+    Ok((0..first.y.len())
+        .map(|i| shares.iter().zip(&basis).fold(0, |secret, (share, &weight)| secret ^ gf_mul(share.y[i], weight)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn any_threshold_subset_recovers_the_secret() {
+        let secret = b"CA private key";
+        let shares = split(secret, 3, 5).unwrap();
+        for a in 0..5 {
+            for b in a + 1..5 {
+                for c in b + 1..5 {
+                    let subset = [shares[a].clone(), shares[b].clone(), shares[c].clone()];
+                    assert_eq!(combine(&subset).unwrap(), secret);
+                }
+            }
+        }
+        assert_ne!(combine(&shares[..2]).unwrap(), secret);
+        assert_eq!(combine(&split(secret, 1, 1).unwrap()).unwrap(), secret);
+    }
+
+    #[test]
+    fn hand_computed_line() {
+        // f(x) = 0x42 ⊕ 7x, so f(1) = 0x45 and f(2) = 0x42 ⊕ 0x0e = 0x4c
+        let shares = [Share { x: 1, y: vec![0x45] }, Share { x: 2, y: vec![0x4c] }];
+        assert_eq!(combine(&shares).unwrap(), [0x42]);
+    }
+
+    #[test]
+    fn shares_print_and_parse() {
+        let share = Share { x: 3, y: vec![0x0a, 0xff] };
+        assert_eq!(share.to_string(), "3-0aff");
+        assert_eq!(Share::parse("3-0aff"), Some(share));
+        assert!(Share::parse("0-0aff").is_none());
+        assert!(split(b"secret", 4, 3).is_err());
+        assert!(combine(&[Share { x: 1, y: vec![1] }, Share { x: 1, y: vec![2] }]).is_err());
+    }
+}
@@ -0,0 +1,166 @@
+//! LSB steganography: a payload hidden in the least significant bit of each
+//! colour sample of an image, where a ±1 change is invisible. The payload is
+//! stored behind a 32-bit big-endian length, one bit per sample in raster
+//! order, most significant bit first; alpha samples are left alone.
+
+use std::io;
+
+use crate::randomness::igamc;
+
+/// Bytes taken by the length in front of the payload
+pub const HEADER_LENGTH: usize = 4;
+
+/// Decoded 8-bit pixels, row by row from the top, channels interleaved
+/// (1 grey, 2 grey + alpha, 3 RGB, 4 RGBA)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub channels: usize,
+    pub samples: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, channels: usize, samples: Vec<u8>) -> io::Result<Self> {
+        if !(1..=4).contains(&channels) || samples.len() != width * height * channels {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} samples do not make a {}x{} image with {} channel(s)", samples.len(), width, height, channels)
+            ));
+        }
+        Ok(Image { width, height, channels, samples })
+    }
+
+    fn has_alpha(&self) -> bool {
+        self.channels == 2 || self.channels == 4
+    }
+
+    /// Indices of the samples that carry payload bits
+    fn carrier_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.samples.len()).filter(|i| !self.has_alpha() || i % self.channels != self.channels - 1)
+    }
+
+    /// The samples that carry payload bits, in embedding order
+    pub fn carrier_samples(&self) -> Vec<u8> {
+        self.carrier_indices().map(|i| self.samples[i]).collect()
+    }
+}
+
+/// Largest payload, in bytes, the image can hold
+pub fn capacity(image: &Image) -> usize {
+    (image.carrier_indices().count() / 8).saturating_sub(HEADER_LENGTH)
+}
+
+/// Hide a non-empty `payload` in the image, overwriting the low bit of as
+/// many samples as it needs
+pub fn embed(image: &mut Image, payload: &[u8]) -> io::Result<()> {
+    if payload.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "The payload is empty"));
+    }
+    let available = capacity(image);
+    let length = u32::try_from(payload.len()).ok().filter(|_| payload.len() <= available).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("The payload is {} bytes, the image holds at most {}", payload.len(), available)
+        )
+    })?;
+
+    let data = [&length.to_be_bytes()[..], payload].concat();
+    let bits = data.iter().flat_map(|byte| (0..8).rev().map(move |bit| (byte >> bit) & 1));
+    let indices: Vec<usize> = image.carrier_indices().collect();
+    for (i, bit) in indices.into_iter().zip(bits) {
+        image.samples[i] = (image.samples[i] & !1) | bit;
+    }
+    Ok(())
+}
+
+/// Recover a payload written by [`embed`]
+pub fn extract(image: &Image) -> io::Result<Vec<u8>> {
+    let mut bytes: Vec<u8> = image
+        .carrier_samples()
+        .chunks_exact(8)
+        .map(|chunk| chunk.iter().fold(0, |byte, sample| (byte << 1) | (sample & 1)))
+        .collect();
+    if bytes.len() < HEADER_LENGTH {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "The image is too small to hold a payload"));
+    }
+
+    let length = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+    if length == 0 || length > bytes.len() - HEADER_LENGTH {
+        // A clean image's low bits read as a random, usually huge, length,
+        // or as zero where they are all clear
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "No hidden payload found"));
+    }
+    bytes.truncate(HEADER_LENGTH + length);
+    Ok(bytes.split_off(HEADER_LENGTH))
+}
+
+/// Westfeld and Pfitzmann's chi-square attack. Overwriting low bits with
+/// random payload bits evens out the counts of each value pair 2k and 2k + 1,
+/// which natural images rarely have. Returns the probability that `samples`
+/// carry embedded data: near 1 when the pairs match, near 0 when they do not.
+pub fn chi_square_probability(samples: &[u8]) -> f64 {
+    let mut histogram = [0u64; 256];
+    for &sample in samples {
+        histogram[usize::from(sample)] += 1;
+    }
+
+    let (mut statistic, mut pairs) = (0.0, 0);
+    for pair in histogram.chunks_exact(2) {
+        let expected = (pair[0] + pair[1]) as f64 / 2.0;
+        // Pairs too rare to compare would only add noise
+        if expected >= 5.0 {
+            statistic += (pair[0] as f64 - expected).powi(2) / expected;
+            pairs += 1;
+        }
+    }
+    if pairs < 2 {
+        return 0.0;
+    }
+
+    igamc(f64::from(pairs - 1) / 2.0, statistic / 2.0)
+}
+
+/// The embedding probability for the first tenth of the carrier samples,
+/// then the first two tenths, and so on. A message embedded from the start
+/// shows as probabilities near 1 up to where it ends.
+pub fn chi_square_profile(image: &Image) -> Vec<f64> {
+    let samples = image.carrier_samples();
+    (1..=10).map(|tenth| chi_square_probability(&samples[..samples.len() * tenth / 10])).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An RGBA gradient using only even values, as a cover with uneven pairs
+    fn cover(width: usize, height: usize) -> Image {
+        let samples = (0..width * height * 4).map(|i| if i % 4 == 3 { 255 } else { ((i * 7 / 3) % 128 * 2) as u8 }).collect();
+        Image::new(width, height, 4, samples).unwrap()
+    }
+
+    #[test]
+    fn payload_round_trips_and_leaves_alpha_alone() {
+        let mut image = cover(16, 16);
+        assert_eq!(capacity(&image), 16 * 16 * 3 / 8 - HEADER_LENGTH);
+
+        embed(&mut image, b"meet at dawn").unwrap();
+        assert_eq!(extract(&image).unwrap(), b"meet at dawn");
+        assert!(image.samples.iter().skip(3).step_by(4).all(|&alpha| alpha == 255));
+        assert!(image.samples.iter().zip(&cover(16, 16).samples).all(|(a, b)| a.abs_diff(*b) <= 1));
+
+        assert!(extract(&cover(16, 16)).is_err());
+        let too_long = vec![0; capacity(&image) + 1];
+        assert!(embed(&mut image, &too_long).is_err());
+    }
+
+    #[test]
+    fn chi_square_attack_spots_a_full_embedding() {
+        let mut image = cover(64, 64);
+        assert!(chi_square_probability(&image.carrier_samples()) < 0.01);
+
+        let payload = crate::otp::generate_pad(capacity(&image)).unwrap();
+        embed(&mut image, &payload).unwrap();
+        assert!(chi_square_probability(&image.carrier_samples()) > 0.5);
+    }
+}
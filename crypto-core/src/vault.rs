@@ -0,0 +1,296 @@
+//! A passphrase-encrypted key vault shared by the tools: symmetric keys,
+//! one-time pads and references to key files (PKI keys, RSA/ECC/ElGamal
+//! key files) in one file, so none of them has to be typed on a command
+//! line. The passphrase goes through Argon2id; the entries are sealed with
+//! AES-128-CBC and an HMAC-SHA256 tag (encrypt-then-MAC), so a wrong
+//! passphrase and a tampered file are both refused before decrypting.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::aes::Aes128;
+use crate::argon2::Argon2id;
+use crate::codec::{decode_hex, encode_hex};
+use crate::modes::{self, Mode};
+use crate::rng;
+use crate::{tr, SymmetricCipher};
+
+const FORMAT: &str = "crypto-vault 1";
+
+/// What an entry holds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntryKind {
+    /// Key bytes for DES, AES, RC4, HMAC or a classical cipher
+    Key,
+    /// One-time pad bytes, used up from the front
+    Pad,
+    /// The path of a key file kept elsewhere, such as a PKI private key
+    Reference,
+}
+
+impl EntryKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Key => "key",
+            Self::Pad => "pad",
+            Self::Reference => "ref",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "key" => Some(Self::Key),
+            "pad" => Some(Self::Pad),
+            "ref" => Some(Self::Reference),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    pub kind: EntryKind,
+    pub data: Vec<u8>,
+    /// Pad bytes already used; pads only
+    pub used: usize,
+}
+
+impl Entry {
+    pub fn new(name: &str, kind: EntryKind, data: Vec<u8>) -> io::Result<Self> {
+        let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c));
+        if !valid {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("Invalid vault entry name {} (letters, digits, -, _ and . only)", name)
+            ));
+        }
+        Ok(Entry { name: name.to_string(), kind, data, used: 0 })
+    }
+
+    /// The path a reference points to
+    pub fn path(&self) -> PathBuf {
+        PathBuf::from(String::from_utf8_lossy(&self.data).into_owned())
+    }
+}
+
+/// An open vault: its entries in the clear, and the keys to seal them again
+pub struct Vault {
+    path: PathBuf,
+    kdf: Argon2id,
+    salt: Vec<u8>,
+    cipher: Aes128,
+    mac_key: Vec<u8>,
+    entries: Vec<Entry>,
+}
+
+/// AES-128 and HMAC keys from the passphrase, independent parts of one derivation
+fn derive_keys(passphrase: &str, salt: &[u8], kdf: Argon2id) -> io::Result<(Aes128, Vec<u8>)> {
+    let derived = kdf.derive(passphrase.as_bytes(), salt, 48);
+    let (cipher_key, mac_key) = derived.split_at(16);
+    let cipher = Aes128::new(cipher_key).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+    Ok((cipher, mac_key.to_vec()))
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+impl Vault {
+    /// A new, empty vault at `path`, written by [`Vault::save`]
+    pub fn create(path: impl AsRef<Path>, passphrase: &str, kdf: Argon2id) -> io::Result<Self> {
+        let salt = rng::salt()?;
+        let (cipher, mac_key) = derive_keys(passphrase, &salt, kdf)?;
+        Ok(Vault { path: path.as_ref().to_path_buf(), kdf, salt, cipher, mac_key, entries: Vec::new() })
+    }
+
+    /// Check the tag and decrypt the vault at `path`
+    pub fn open(path: impl AsRef<Path>, passphrase: &str) -> io::Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| io::Error::new(e.kind(), tr!("Cannot read the vault {}: {}", path.display(), e)))?;
+        let mut lines = text.lines();
+        if lines.next() != Some(FORMAT) {
+            return Err(invalid(tr!("{} is not a vault", path.display())));
+        }
+
+        let mut field = |name: &str| {
+            lines
+                .next()
+                .and_then(|line| line.strip_prefix(name))
+                .and_then(|value| value.strip_prefix(' '))
+                .ok_or_else(|| invalid(tr!("Missing or invalid {}", name)))
+        };
+        let kdf = parse_kdf(field("kdf")?).ok_or_else(|| invalid(tr!("Missing or invalid {}", "kdf")))?;
+        let salt = decode_hex(field("salt")?).map_err(|_| invalid(tr!("Missing or invalid {}", "salt")))?;
+        let sealed = decode_hex(field("sealed")?).map_err(|_| invalid(tr!("Missing or invalid {}", "sealed")))?;
+
+        let (cipher, mac_key) = derive_keys(passphrase, &salt, kdf)?;
+        let plaintext = modes::decrypt_verified(&cipher, Mode::Cbc, &mac_key, &sealed)
+            .map_err(|_| io::Error::new(io::ErrorKind::PermissionDenied, tr!("Wrong passphrase, or the vault was altered")))?;
+        let entries = String::from_utf8(plaintext)
+            .ok()
+            .and_then(|text| text.lines().map(parse_entry).collect::<Option<Vec<_>>>())
+            .ok_or_else(|| invalid(tr!("The vault's entries are damaged")))?;
+
+        Ok(Vault { path: path.to_path_buf(), kdf, salt, cipher, mac_key, entries })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn get(&self, name: &str) -> io::Result<&Entry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.name == name)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, tr!("The vault has no entry {}", name)))
+    }
+
+    /// Add an entry; names are unique
+    pub fn add(&mut self, entry: Entry) -> io::Result<()> {
+        if self.get(&entry.name).is_ok() {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, tr!("The vault already has an entry {}", entry.name)));
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// The next `len` unused bytes of a pad and their offset, marked used;
+    /// [`Vault::save`] makes that stick
+    pub fn take_pad(&mut self, name: &str, len: usize) -> io::Result<(usize, Vec<u8>)> {
+        let entry = self.pad_entry(name)?;
+        if entry.data.len() - entry.used < len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("The pad {} has {} unused bytes, the message needs {}", name, entry.data.len() - entry.used, len)
+            ));
+        }
+        let offset = entry.used;
+        entry.used += len;
+        Ok((offset, entry.data[offset..offset + len].to_vec()))
+    }
+
+    /// `len` pad bytes at `offset`, for decrypting what the other side sent
+    pub fn pad_at(&mut self, name: &str, offset: usize, len: usize) -> io::Result<Vec<u8>> {
+        let entry = self.pad_entry(name)?;
+        entry.data.get(offset..offset + len).map(<[u8]>::to_vec).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, tr!("The pad {} has no {} bytes at offset {}", name, len, offset))
+        })
+    }
+
+    fn pad_entry(&mut self, name: &str) -> io::Result<&mut Entry> {
+        match self.entries.iter_mut().find(|entry| entry.name == name) {
+            Some(entry) if entry.kind == EntryKind::Pad => Ok(entry),
+            Some(_) => Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("The vault entry {} is not a pad", name))),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, tr!("The vault has no entry {}", name))),
+        }
+    }
+
+    /// Seal the entries under a fresh IV and write the vault, owner-only
+    pub fn save(&self) -> io::Result<()> {
+        let plaintext: String = self.entries.iter().map(|entry| {
+            format!("{} {} {} {}\n", entry.kind.name(), entry.name, encode_hex(&entry.data), entry.used)
+        }).collect();
+        let iv = rng::iv(16)?;
+        let sealed = modes::encrypt_then_mac(&self.cipher, Mode::Cbc, &iv, &self.mac_key, plaintext.as_bytes())
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        let Argon2id { memory_kib, iterations, lanes } = self.kdf;
+        let document = format!(
+            "{}\nkdf argon2id m={} t={} p={}\nsalt {}\nsealed {}\n",
+            FORMAT,
+            memory_kib,
+            iterations,
+            lanes,
+            encode_hex(&self.salt),
+            encode_hex(&sealed)
+        );
+
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options.open(&self.path)?.write_all(document.as_bytes())
+    }
+}
+
+/// `argon2id m=<KiB> t=<passes> p=<lanes>`
+fn parse_kdf(text: &str) -> Option<Argon2id> {
+    let mut parts = text.split(' ');
+    if parts.next()? != "argon2id" {
+        return None;
+    }
+    let mut value = |prefix: &str| parts.next()?.strip_prefix(prefix)?.parse::<u32>().ok();
+    let kdf = Argon2id { memory_kib: value("m=")?, iterations: value("t=")?, lanes: value("p=")? };
+    // Refuse costs no one would choose, such as a file edited to make
+    // opening it take forever
+    let sane = (8..=4 * 1024 * 1024).contains(&kdf.memory_kib) && (1..=64).contains(&kdf.iterations) && (1..=16).contains(&kdf.lanes);
+    sane.then_some(kdf)
+}
+
+/// `<kind> <name> <hex data> <used>`
+fn parse_entry(line: &str) -> Option<Entry> {
+    let mut parts = line.split(' ');
+    let kind = EntryKind::parse(parts.next()?)?;
+    let name = parts.next()?.to_string();
+    let data = decode_hex(parts.next()?).ok()?;
+    let used = parts.next()?.parse().ok()?;
+    Some(Entry { name, kind, data, used })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHEAP: Argon2id = Argon2id { memory_kib: 64, iterations: 1, lanes: 1 };
+
+    #[test]
+    fn entries_survive_sealing_and_a_wrong_passphrase_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lab.vault");
+        let mut vault = Vault::create(&path, "correct horse", CHEAP).unwrap();
+        vault.add(Entry::new("des-lab", EntryKind::Key, b"MORTYNOR".to_vec()).unwrap()).unwrap();
+        vault.add(Entry::new("pad", EntryKind::Pad, vec![7; 32]).unwrap()).unwrap();
+        vault.add(Entry::new("alice", EntryKind::Reference, b"pki/users/alice_private_key.pem".to_vec()).unwrap()).unwrap();
+        assert!(vault.add(Entry::new("des-lab", EntryKind::Key, vec![1]).unwrap()).is_err());
+        assert_eq!(vault.take_pad("pad", 20).unwrap(), (0, vec![7; 20]));
+        vault.save().unwrap();
+
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(!text.contains(&encode_hex(b"MORTYNOR")));
+        let mut reopened = Vault::open(&path, "correct horse").unwrap();
+        assert_eq!(reopened.entries(), vault.entries());
+        assert_eq!(reopened.get("alice").unwrap().path(), PathBuf::from("pki/users/alice_private_key.pem"));
+        // Pad bytes are never handed out twice
+        assert!(reopened.take_pad("pad", 13).is_err());
+        assert_eq!(reopened.take_pad("pad", 12).unwrap().0, 20);
+        assert!(reopened.take_pad("des-lab", 1).is_err());
+
+        let error = Vault::open(&path, "wrong horse").err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        assert!(Entry::new("two words", EntryKind::Key, vec![]).is_err());
+    }
+
+    #[test]
+    fn a_tampered_vault_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lab.vault");
+        let mut vault = Vault::create(&path, "passphrase", CHEAP).unwrap();
+        vault.add(Entry::new("key", EntryKind::Key, vec![1, 2, 3]).unwrap()).unwrap();
+        vault.save().unwrap();
+
+        let text = fs::read_to_string(&path).unwrap();
+        let sealed_at = text.find("sealed ").unwrap() + 7;
+        let mut bytes = text.into_bytes();
+        bytes[sealed_at + 40] = if bytes[sealed_at + 40] == b'0' { b'1' } else { b'0' };
+        fs::write(&path, bytes).unwrap();
+        assert_eq!(Vault::open(&path, "passphrase").err().unwrap().kind(), io::ErrorKind::PermissionDenied);
+    }
+}
@@ -0,0 +1,247 @@
+//! Vigenère cipher and the classic attacks on it: Kasiski examination,
+//! index-of-coincidence key length estimation and column-by-column
+//! frequency analysis
+
+use std::collections::HashMap;
+
+use crate::cipher::{CipherError, CipherFamily, CipherInfo, ClassicalCipher};
+use crate::language::{
+    chi_squared, index_of_coincidence, letter_counts, letter_index, Language, ENGLISH_INDEX_OF_COINCIDENCE,
+    RANDOM_INDEX_OF_COINCIDENCE,
+};
+
+/// Shifts each letter by the next key letter, keeping case. Other
+/// characters pass through without using up a key letter.
+pub struct Vigenere {
+    shifts: Vec<u8>,
+}
+
+impl Vigenere {
+    pub fn key(&self) -> String {
+        self.shifts.iter().map(|&shift| (b'A' + shift) as char).collect()
+    }
+
+    fn apply(&self, text: &str, decrypt: bool) -> String {
+        let mut shifts = self.shifts.iter().cycle();
+        text.chars()
+            .map(|c| {
+                let base = match c {
+                    'A'..='Z' => b'A',
+                    'a'..='z' => b'a',
+                    _ => return c,
+                };
+                let shift = shifts.next().copied().unwrap_or_default();
+                let shift = if decrypt { 26 - shift } else { shift };
+                ((c as u8 - base + shift) % 26 + base) as char
+            })
+            .collect()
+    }
+}
+
+impl ClassicalCipher for Vigenere {
+    fn validate_key(key: &str) -> Result<(), CipherError> {
+        if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphabetic()) {
+            Ok(())
+        } else {
+            Err(CipherError::InvalidKey(INFO.key_description.to_string()))
+        }
+    }
+
+    fn new(key: &str) -> Result<Self, CipherError> {
+        <Self as ClassicalCipher>::validate_key(key)?;
+        Ok(Vigenere { shifts: key.chars().filter_map(letter_index).map(|index| index as u8).collect() })
+    }
+
+    fn info(&self) -> CipherInfo {
+        INFO
+    }
+
+    fn encrypt(&self, plaintext: &str) -> Result<String, CipherError> {
+        Ok(self.apply(plaintext, false))
+    }
+
+    fn decrypt(&self, ciphertext: &str) -> Result<String, CipherError> {
+        Ok(self.apply(ciphertext, true))
+    }
+}
+
+const INFO: CipherInfo = CipherInfo {
+    name: "vigenere",
+    family: CipherFamily::Substitution,
+    key_description: "one or more letters A-Z",
+    block_size: None,
+};
+
+/// The letters of `text` in upper case, without anything else
+fn letters(text: &str) -> Vec<u8> {
+    text.chars().filter_map(letter_index).map(|index| index as u8).collect()
+}
+
+/// Kasiski examination: distances between repeated trigrams are multiples
+/// of the key length. Returns how many distances each candidate length
+/// (2..=`max_key_length`) divides, most frequent first.
+pub fn kasiski(ciphertext: &str, max_key_length: usize) -> Vec<(usize, usize)> {
+    let letters = letters(ciphertext);
+    let mut last_seen: HashMap<&[u8], usize> = HashMap::new();
+    let mut factor_counts = vec![0; max_key_length + 1];
+
+    for (position, trigram) in letters.windows(3).enumerate() {
+        if let Some(previous) = last_seen.insert(trigram, position) {
+            let distance = position - previous;
+            for (length, count) in factor_counts.iter_mut().enumerate().skip(2) {
+                if distance % length == 0 {
+                    *count += 1;
+                }
+            }
+        }
+    }
+
+    let mut counts: Vec<(usize, usize)> = factor_counts
+        .into_iter()
+        .enumerate()
+        .skip(2)
+        .filter(|&(_, count)| count > 0)
+        .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    counts
+}
+
+/// Friedman's estimate of the key length from the index of coincidence of
+/// the whole ciphertext
+pub fn friedman_estimate(ciphertext: &str) -> f64 {
+    let text_ioc = index_of_coincidence(&letter_counts(ciphertext));
+    if text_ioc <= RANDOM_INDEX_OF_COINCIDENCE {
+        return f64::INFINITY;
+    }
+    (ENGLISH_INDEX_OF_COINCIDENCE - RANDOM_INDEX_OF_COINCIDENCE) / (text_ioc - RANDOM_INDEX_OF_COINCIDENCE)
+}
+
+/// Average index of coincidence of the columns the ciphertext splits into
+/// for each key length 1..=`max_key_length`. The right length (and its
+/// multiples) gives English-like columns.
+pub fn column_coincidences(ciphertext: &str, max_key_length: usize) -> Vec<(usize, f64)> {
+    let letters = letters(ciphertext);
+    (1..=max_key_length.min(letters.len().max(1)))
+        .map(|length| {
+            let average = (0..length)
+                .map(|column| {
+                    let mut counts = [0; 26];
+                    for &letter in letters.iter().skip(column).step_by(length) {
+                        counts[letter as usize] += 1;
+                    }
+                    index_of_coincidence(&counts)
+                })
+                .sum::<f64>()
+                / length as f64;
+            (length, average)
+        })
+        .collect()
+}
+
+/// The shortest key length whose columns look like English
+pub fn estimate_key_length(ciphertext: &str, max_key_length: usize) -> usize {
+    let coincidences = column_coincidences(ciphertext, max_key_length);
+    let best = coincidences.iter().map(|&(_, ioc)| ioc).fold(0.0, f64::max);
+
+    // Multiples of the key length score as well, so take the first near the best
+    coincidences
+        .iter()
+        .find(|&&(_, ioc)| ioc >= best - 0.01)
+        .map(|&(length, _)| length)
+        .unwrap_or(1)
+}
+
+/// Recover each key letter as the shift that brings its column's letter
+/// frequencies closest to the language's
+pub fn recover_key(ciphertext: &str, key_length: usize, language: Language) -> String {
+    let letters = letters(ciphertext);
+    let key_length = key_length.max(1);
+    (0..key_length)
+        .map(|column| {
+            let column: Vec<u8> = letters.iter().skip(column).step_by(key_length).copied().collect();
+            let distance = |shift: u8| {
+                let shifted: String = column.iter().map(|&letter| (b'A' + (letter + 26 - shift) % 26) as char).collect();
+                chi_squared(&shifted, language)
+            };
+            (b'A' + (0..26).min_by(|&a, &b| distance(a).total_cmp(&distance(b))).unwrap_or(0)) as char
+        })
+        .collect()
+}
+
+/// Quadgram log probability of the decryption of `letters` under `shifts`
+fn decryption_score(letters: &[u8], shifts: &[u8], language: Language) -> f64 {
+    let plaintext: Vec<usize> = letters
+        .iter()
+        .zip(shifts.iter().cycle())
+        .map(|(&letter, &shift)| usize::from((letter + 26 - shift) % 26))
+        .collect();
+    language.quadgrams().total(&plaintext)
+}
+
+/// For every key length up to `max_key_length`, recover a key from the
+/// letter frequencies and then change one key letter at a time while the
+/// decryption reads more like the language. The decryption that reads best
+/// wins; the shortest key wins a tie, as its multiples decrypt the same way.
+pub fn crack(ciphertext: &str, max_key_length: usize, language: Language) -> (String, String) {
+    let letters = letters(ciphertext);
+    let mut best: Option<(f64, Vec<u8>)> = None;
+    for key_length in 1..=max_key_length.max(1).min(letters.len().max(1)) {
+        let mut shifts: Vec<u8> = recover_key(ciphertext, key_length, language).bytes().map(|letter| letter - b'A').collect();
+        let mut score = decryption_score(&letters, &shifts, language);
+        for position in 0..key_length {
+            for shift in 0..26 {
+                let previous = std::mem::replace(&mut shifts[position], shift);
+                let candidate = decryption_score(&letters, &shifts, language);
+                if candidate > score {
+                    score = candidate;
+                } else {
+                    shifts[position] = previous;
+                }
+            }
+        }
+        if best.as_ref().is_none_or(|(best_score, _)| score > *best_score) {
+            best = Some((score, shifts));
+        }
+    }
+
+    let key: String = best.map(|(_, shifts)| shifts.iter().map(|&shift| (b'A' + shift) as char).collect()).unwrap_or_default();
+    let plaintext = Vigenere::new(&key)
+        .and_then(|cipher| cipher.decrypt(ciphertext))
+        .unwrap_or_default();
+    (key, plaintext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAINTEXT: &str = "Cryptography is the practice and study of techniques for secure communication \
+        in the presence of adversarial behavior. More generally, cryptography is about constructing and \
+        analyzing protocols that prevent third parties or the public from reading private messages. \
+        Modern cryptography exists at the intersection of the disciplines of mathematics, computer \
+        science, information security, electrical engineering, digital signal processing, physics, and \
+        others. Core concepts related to information security, including data confidentiality, data \
+        integrity, authentication, and non-repudiation, are also central to cryptography.";
+
+    #[test]
+    fn round_trip_skips_punctuation() {
+        let cipher = Vigenere::new("LEMON").unwrap();
+        assert_eq!(cipher.encrypt("Attack at dawn!").unwrap(), "Lxfopv ef rnhr!");
+        assert_eq!(cipher.decrypt("Lxfopv ef rnhr!").unwrap(), "Attack at dawn!");
+        assert!(Vigenere::new("K3Y").is_err());
+    }
+
+    #[test]
+    fn cracks_english_with_a_short_key() {
+        let ciphertext = Vigenere::new("CIPHER").unwrap().encrypt(PLAINTEXT).unwrap();
+
+        assert_eq!(estimate_key_length(&ciphertext, 12), 6);
+        assert!(kasiski(&ciphertext, 12).iter().take(3).any(|&(length, _)| length == 6));
+        assert_eq!(crack(&ciphertext, 12, Language::English), (String::from("CIPHER"), String::from(PLAINTEXT)));
+
+        // Too short for the column statistics alone; the quadgrams fix the key letters
+        let short = "Meet me by the old mill at midnight and bring the map";
+        let ciphertext = Vigenere::new("KEY").unwrap().encrypt(short).unwrap();
+        assert_eq!(crack(&ciphertext, 6, Language::English).1, short);
+    }
+}
@@ -0,0 +1,30 @@
+//! `crypto aes`: AES-128 in ECB, CBC or CTR mode, optionally encrypt-then-MAC
+
+use std::io;
+
+use crypto_core::SymmetricCipher;
+use crypto_core::aes::Aes128;
+use crypto_core::modes::Mode;
+
+use crate::output::CommandOutput;
+use crate::{cipher_error, key_bytes, run_block_mode, run_symmetric, take_flag_values, take_mode_flags, usage_error, ModeOptions};
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto aes (encrypt | decrypt) --key <key> [--hex] [--mode ecb|cbc|ctr] [--iv <hex>] [--mac-key <key>] [<text>]";
+    let (keys, rest) = take_flag_values(args, "--key")?;
+    let (mac_keys, rest) = take_flag_values(&rest, "--mac-key")?;
+    let (mode, iv, rest) = take_mode_flags(&rest)?;
+    let hex = rest.iter().any(|arg| arg == "--hex");
+    let rest: Vec<String> = rest.into_iter().filter(|arg| arg != "--hex").collect();
+    let (Some((operation, rest)), Some(key)) = (rest.split_first(), keys.last()) else {
+        return Err(usage_error(USAGE));
+    };
+
+    let cipher = Aes128::new(&key_bytes(key, hex)?).map_err(cipher_error)?;
+    let mac_key = mac_keys.last().map(|key| key_bytes(key, hex)).transpose()?;
+    match (mode, &mac_key) {
+        (Some(mode), _) => run_block_mode(&cipher, ModeOptions { mode, iv: iv.as_ref(), mac_key: mac_key.as_deref() }, operation, rest, USAGE, out),
+        (None, Some(_)) => run_block_mode(&cipher, ModeOptions { mode: Mode::Cbc, iv: iv.as_ref(), mac_key: mac_key.as_deref() }, operation, rest, USAGE, out),
+        (None, None) => run_symmetric(&cipher, operation, rest, USAGE, out),
+    }
+}
@@ -0,0 +1,121 @@
+//! `crypto analyze`: letter frequencies of a text against a language model,
+//! and a guess at the cipher that produced it
+
+use std::io;
+
+use crypto_core::cryptanalysis::{self, Assessment, LanguageModel};
+use crypto_core::language::Alphabet;
+use crypto_core::tr;
+use serde_json::json;
+
+use crate::ciphers::registry;
+use crate::output::CommandOutput;
+use crate::{cipher_error, take_flag_values, take_romanian, text_argument, usage_error};
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto analyze [--model english|uniform|<sample file>] [--romanian] [--top <n>] [--cipher <name> --key <key>] [<text>]";
+    let (models, rest) = take_flag_values(args, "--model")?;
+    let (tops, rest) = take_flag_values(&rest, "--top")?;
+    let (ciphers, rest) = take_flag_values(&rest, "--cipher")?;
+    let (keys, rest) = take_flag_values(&rest, "--key")?;
+    let (romanian, rest) = take_romanian(&rest);
+    let alphabet = if romanian { Alphabet::Romanian } else { Alphabet::Latin };
+    let limit = match tops.last() {
+        Some(top) => top.parse::<usize>().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid count {}", top)))?,
+        None => 10,
+    };
+
+    let uniform;
+    let sample;
+    let model = match (models.last().map(String::as_str), alphabet) {
+        (None | Some("english"), Alphabet::Latin) => LanguageModel::english(),
+        (Some("uniform"), _) => {
+            uniform = LanguageModel::uniform(alphabet);
+            &uniform
+        }
+        (None | Some("english"), Alphabet::Romanian) => {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("Romanian text needs --model with a Romanian sample file")));
+        }
+        (Some(path), _) => {
+            sample = LanguageModel::from_sample(path, &std::fs::read_to_string(path)?, alphabet);
+            &sample
+        }
+    };
+
+    // With --cipher, the text is encrypted first and its ciphertext analysed,
+    // to see what the cipher leaves of the language's statistics
+    let text = text_argument(&rest, USAGE)?;
+    let text = match (ciphers.last(), keys.last()) {
+        (None, None) => text,
+        (Some(name), Some(key)) => {
+            let registration = registry()
+                .get(name)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, tr!("Unknown cipher {} (see crypto ciphers)", name)))?;
+            if !registration.is_classical() {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("{} works on bytes; analyze --cipher takes a letter cipher", name)));
+            }
+            let ciphertext = registration.classical_cipher(key).and_then(|cipher| cipher.encrypt(&text)).map_err(cipher_error)?;
+            out.line(tr!("Ciphertext under {}: {}", name, ciphertext));
+            out.field("cipher", name.as_str());
+            out.field("ciphertext", ciphertext.as_str());
+            ciphertext
+        }
+        _ => return Err(usage_error(USAGE)),
+    };
+    let analysis = cryptanalysis::analyze(&text, model, limit);
+    let random_ioc = 1.0 / alphabet.size() as f64;
+    out.line(tr!("Letters: {}", analysis.letters));
+    out.line(tr!(
+        "Index of coincidence: {} ({} {}, random {})",
+        format!("{:.4}", analysis.index_of_coincidence),
+        model.name,
+        format!("{:.4}", model.index_of_coincidence()),
+        format!("{:.4}", random_ioc),
+    ));
+    out.line(tr!(
+        "Chi-squared against {}: monograms {}, bigrams {}, trigrams {}",
+        model.name,
+        format!("{:.1}", analysis.chi_squared[0]),
+        format!("{:.1}", analysis.chi_squared[1]),
+        format!("{:.1}", analysis.chi_squared[2]),
+    ));
+    for (n, name) in [(1, tr!("Monograms")), (2, tr!("Bigrams")), (3, tr!("Trigrams"))] {
+        out.line(tr!("{} (count, share, expected)", name));
+        for row in &analysis.top[n - 1] {
+            // One # per percentage point, so the shape reads at a glance
+            let bar = "#".repeat((row.frequency * 100.0).round() as usize);
+            out.line(format!("  {}  {:>4}  {:>5.2}%  {:>5.2}%  {}", row.ngram, row.count, row.frequency * 100.0, row.expected * 100.0, bar));
+        }
+    }
+    out.line(tr!("Looks like {}", analysis.assessment.description()));
+
+    let table = |rows: &[cryptanalysis::NgramCount]| {
+        rows.iter()
+            .map(|row| json!({ "ngram": row.ngram, "count": row.count, "frequency": row.frequency, "expected": row.expected }))
+            .collect::<Vec<_>>()
+    };
+    out.field("model", model.name.as_str());
+    out.field("letters", analysis.letters);
+    out.field("index_of_coincidence", analysis.index_of_coincidence);
+    out.field("chi_squared", json!({
+        "monograms": analysis.chi_squared[0],
+        "bigrams": analysis.chi_squared[1],
+        "trigrams": analysis.chi_squared[2],
+    }));
+    out.field("monograms", table(&analysis.top[0]));
+    out.field("bigrams", table(&analysis.top[1]));
+    out.field("trigrams", table(&analysis.top[2]));
+    out.field("assessment", match analysis.assessment {
+        Assessment::TooShort => "too-short",
+        Assessment::Plaintext => "plaintext",
+        Assessment::Transposition => "transposition",
+        Assessment::Caesar { .. } => "caesar",
+        Assessment::Substitution => "substitution",
+        Assessment::Polyalphabetic => "polyalphabetic",
+    });
+    if let Assessment::Caesar { shift } = analysis.assessment {
+        out.field("shift", shift);
+    }
+
+    Ok(())
+}
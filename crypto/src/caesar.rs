@@ -0,0 +1,49 @@
+//! `crypto caesar`: shift ciphers, and breaking them by trying every shift
+
+use std::io;
+
+use crypto_core::caesar::{self, Caesar};
+use crypto_core::language::Language;
+use crypto_core::{tr, ClassicalCipher};
+use serde_json::json;
+
+use crate::output::CommandOutput;
+use crate::{cipher_error, run_classical, take_flag_values, take_romanian, text_argument, usage_error};
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto caesar (encrypt | decrypt) --key <shift> [<text>] | crypto caesar (brute | crack) [--romanian] [<text>]";
+    let (keys, rest) = take_flag_values(args, "--key")?;
+    let (romanian, rest) = take_romanian(&rest);
+    let language = if romanian { Language::Romanian } else { Language::English };
+    let Some((operation, rest)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+
+    match (operation.as_str(), keys.last()) {
+        ("brute", None) => {
+            let candidates = caesar::brute_force(&text_argument(rest, USAGE)?, language);
+            for candidate in &candidates {
+                out.line(format!("{:>2} {:>7.3}  {}", candidate.shift, candidate.score, candidate.plaintext));
+            }
+            out.field("candidates", candidates.iter().map(|candidate| json!({
+                "shift": candidate.shift,
+                "plaintext": candidate.plaintext,
+                "score": candidate.score,
+            })).collect::<Vec<_>>());
+        }
+        ("crack", None) => {
+            let candidate = caesar::crack(&text_argument(rest, USAGE)?, language);
+            out.line(tr!("Shift {}: {}", candidate.shift, candidate.plaintext));
+            out.field("shift", candidate.shift);
+            out.field("result", candidate.plaintext);
+            out.field("score", candidate.score);
+        }
+        (_, Some(key)) => {
+            let cipher = Caesar::new(key).map_err(cipher_error)?;
+            return run_classical(&cipher, operation, rest, USAGE, out);
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
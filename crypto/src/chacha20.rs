@@ -0,0 +1,67 @@
+//! `crypto chacha20`: the ChaCha20 stream cipher, or ChaCha20-Poly1305 with `--aead`
+
+use std::io;
+
+use crypto_core::chacha20::{self, ChaCha20, ChaCha20Poly1305};
+use crypto_core::codec::encode_hex;
+use crypto_core::{rng, tr, CipherError, SymmetricCipher};
+
+use crate::output::CommandOutput;
+use crate::{cipher_error, hex_input, key_bytes, run_symmetric, take_flag_values, text_argument, usage_error};
+
+/// ChaCha20 on its own, like RC4, or as the ChaCha20-Poly1305 AEAD with
+/// `--aead`; the nonce, random unless given, starts the ciphertext
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto chacha20 encrypt --key <key> [--hex] [--aead [--aad <text>]] [--nonce <hex>] [<text>] | \
+                         crypto chacha20 decrypt --key <key> [--hex] [--aead [--aad <text>]] [<hex>]";
+    let (keys, rest) = take_flag_values(args, "--key")?;
+    let (nonces, rest) = take_flag_values(&rest, "--nonce")?;
+    let (associated, rest) = take_flag_values(&rest, "--aad")?;
+    let hex = rest.iter().any(|arg| arg == "--hex");
+    let aead = rest.iter().any(|arg| arg == "--aead");
+    let rest: Vec<String> = rest.into_iter().filter(|arg| arg != "--hex" && arg != "--aead").collect();
+    let (Some((operation, rest)), Some(key), true) = (rest.split_first(), keys.last(), aead || associated.is_empty()) else {
+        return Err(usage_error(USAGE));
+    };
+    let key = key_bytes(key, hex)?;
+    if !aead && nonces.is_empty() {
+        return run_symmetric(&ChaCha20::new(&key).map_err(cipher_error)?, operation, rest, USAGE, out);
+    }
+    let associated = associated.last().map(String::as_bytes).unwrap_or_default();
+    let text = text_argument(rest, USAGE)?;
+
+    let result = match operation.as_str() {
+        "encrypt" => {
+            let nonce = match nonces.last() {
+                Some(nonce) => hex_input(nonce, tr!("nonce"))?,
+                None => rng::nonce(chacha20::NONCE_LENGTH)?,
+            };
+            let nonce: [u8; chacha20::NONCE_LENGTH] = nonce.try_into().map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidInput, tr!("The nonce must be {} bytes", chacha20::NONCE_LENGTH))
+            })?;
+            let body = if aead {
+                ChaCha20Poly1305::new(&key).map_err(cipher_error)?.seal(&nonce, associated, text.as_bytes())
+            } else {
+                ChaCha20::new(&key).map_err(cipher_error)?.apply(&nonce, 1, text.as_bytes())
+            };
+            encode_hex(&[&nonce[..], &body].concat())
+        }
+        "decrypt" if nonces.is_empty() => {
+            let data = hex_input(&text, tr!("ciphertext"))?;
+            if data.len() < chacha20::NONCE_LENGTH {
+                return Err(cipher_error(CipherError::InvalidInput(String::from("too short to hold a nonce"))));
+            }
+            let (nonce, sealed) = data.split_at(chacha20::NONCE_LENGTH);
+            let aead = ChaCha20Poly1305::new(&key).map_err(cipher_error)?;
+            let plaintext = aead.open(nonce.try_into().expect("12 bytes"), associated, sealed).map_err(cipher_error)?;
+            String::from_utf8_lossy(&plaintext).into_owned()
+        }
+        _ => return Err(usage_error(USAGE)),
+    };
+
+    out.line(&result);
+    out.field("cipher", if aead { "chacha20-poly1305" } else { "chacha20" });
+    out.field("operation", operation.as_str());
+    out.field("result", result);
+    Ok(())
+}
@@ -0,0 +1,469 @@
+use std::io;
+
+use crypto_core::aes::Aes128;
+use crypto_core::caesar::Caesar;
+use crypto_core::codec::{decode_hex, encode_hex};
+use crypto_core::columnar::Columnar;
+use crypto_core::hill::{self, Hill};
+use crypto_core::language::{folded_letter_index, Alphabet, Language};
+use crypto_core::modes::{self, Mode};
+use crypto_core::password::Kdf;
+use crypto_core::railfence::RailFence;
+use crypto_core::rng;
+use crypto_core::vigenere::Vigenere;
+use crypto_core::{tr, ClassicalCipher, SymmetricCipher};
+use playfair::Playfair;
+use serde_json::{json, Value};
+
+use crate::output::CommandOutput;
+use crate::{take_flag_values, take_romanian, text_argument, usage_error};
+
+/// PBKDF2 rounds protecting the answer key; it is opened rarely, so
+/// guessing the passphrase can be made expensive
+const SEAL_ITERATIONS: u32 = 100_000;
+
+const SEAL_FORMAT: &str = "crypto-challenge-answers";
+
+/// The classical ciphers students are asked to break
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ChallengeCipher {
+    Caesar,
+    Vigenere,
+    Hill,
+    RailFence,
+    Columnar,
+    Playfair,
+}
+
+impl ChallengeCipher {
+    pub(crate) fn parse(name: &str) -> io::Result<Self> {
+        match name {
+            "caesar" => Ok(Self::Caesar),
+            "vigenere" => Ok(Self::Vigenere),
+            "hill" => Ok(Self::Hill),
+            "railfence" => Ok(Self::RailFence),
+            "columnar" => Ok(Self::Columnar),
+            "playfair" => Ok(Self::Playfair),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("Unknown challenge cipher {} (expected caesar, vigenere, hill, railfence, columnar or playfair)", name)
+            )),
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Caesar => "caesar",
+            Self::Vigenere => "vigenere",
+            Self::Hill => "hill",
+            Self::RailFence => "railfence",
+            Self::Columnar => "columnar",
+            Self::Playfair => "playfair",
+        }
+    }
+
+    /// Letters of plaintext at each difficulty. Playfair needs a couple of
+    /// hundred letters before its digraph statistics show at all.
+    fn letters(self, difficulty: Difficulty) -> usize {
+        let lengths = match self {
+            Self::Playfair => [500, 350, 240],
+            _ => [400, 250, 120],
+        };
+        lengths[difficulty as usize]
+    }
+
+    /// Whether word breaks, case and punctuation survive encryption, which
+    /// makes the easy substitution and rail fence challenges much easier
+    fn keeps_formatting(self) -> bool {
+        matches!(self, Self::Caesar | Self::Vigenere | Self::RailFence)
+    }
+}
+
+/// How much help a challenge gives: shorter texts, longer keys and fewer
+/// hints as it gets harder
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub(crate) fn parse(name: &str) -> io::Result<Self> {
+        match name {
+            "easy" => Ok(Self::Easy),
+            "medium" => Ok(Self::Medium),
+            "hard" => Ok(Self::Hard),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("Unknown difficulty {} (expected easy, medium or hard)", name)
+            )),
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Easy => "easy",
+            Self::Medium => "medium",
+            Self::Hard => "hard",
+        }
+    }
+}
+
+/// One student's ciphertext and what it takes to check their answer
+pub(crate) struct Challenge {
+    pub(crate) student: String,
+    pub(crate) key: String,
+    pub(crate) plaintext: String,
+    pub(crate) ciphertext: String,
+    pub(crate) hints: Vec<String>,
+}
+
+impl Challenge {
+    /// The sheet handed to the student: no key, no plaintext
+    pub(crate) fn sheet(&self, cipher: ChallengeCipher, difficulty: Difficulty) -> String {
+        let mut sheet = format!("Cryptanalysis challenge for {}\nCipher: {} ({})\n", self.student, cipher.name(), difficulty.name());
+        for hint in &self.hints {
+            sheet.push_str(&format!("Hint: {}\n", hint));
+        }
+        sheet.push_str(&format!("\n{}\n", self.ciphertext));
+        sheet
+    }
+}
+
+fn random_between(low: usize, high: usize) -> io::Result<usize> {
+    Ok(low + rng::below(high - low + 1)?)
+}
+
+/// `length` distinct random letters from `letters`
+fn random_word(letters: &str, length: usize) -> io::Result<String> {
+    let mut letters: Vec<char> = letters.chars().collect();
+    for i in (1..letters.len()).rev() {
+        letters.swap(i, rng::below(i + 1)?);
+    }
+    Ok(letters.into_iter().take(length).collect())
+}
+
+/// Romanian letters written as their base letters, as the A–Z ciphers see them
+fn fold(c: char) -> char {
+    match folded_letter_index(c) {
+        Some(index) if !c.is_ascii() => {
+            let base = (b'A' + index as u8) as char;
+            if c.is_lowercase() { base.to_ascii_lowercase() } else { base }
+        }
+        _ => c,
+    }
+}
+
+/// Whole words of the language sample, starting at a random sentence and
+/// wrapping around, until there are at least `letters` letters
+fn excerpt(language: Language, letters: usize) -> io::Result<String> {
+    let sentences: Vec<&str> = language
+        .sample()
+        .split_inclusive(['.', '!', '?'])
+        .map(str::trim)
+        .filter(|sentence| !sentence.is_empty())
+        .collect();
+    let start = rng::below(sentences.len())?;
+
+    let mut words = Vec::new();
+    let mut count = 0;
+    for word in sentences.iter().cycle().skip(start).flat_map(|sentence| sentence.split_whitespace()) {
+        if count >= letters {
+            break;
+        }
+        count += word.chars().filter(|c| c.is_alphabetic()).count();
+        words.push(word);
+    }
+    Ok(words.join(" ").chars().map(fold).collect())
+}
+
+fn only_letters(text: &str) -> String {
+    text.chars().filter(char::is_ascii_alphabetic).map(|c| c.to_ascii_uppercase()).collect()
+}
+
+/// A random invertible Hill key modulo 26, as the numbers `crypto hill` reads
+fn random_hill_key(size: usize) -> io::Result<Hill> {
+    loop {
+        let mut matrix = vec![vec![0; size]; size];
+        for value in matrix.iter_mut().flatten() {
+            *value = rng::below(26)? as i64;
+        }
+        if hill::inverse_matrix(&matrix, 26).is_some() {
+            return Hill::from_matrix(matrix, Alphabet::Latin).map_err(cipher_error);
+        }
+    }
+}
+
+fn cipher_error(error: crypto_core::CipherError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, error.to_string())
+}
+
+/// A fresh plaintext, key and ciphertext for one student
+pub(crate) fn generate(student: &str, cipher: ChallengeCipher, difficulty: Difficulty, language: Language) -> io::Result<Challenge> {
+    let text = excerpt(language, cipher.letters(difficulty))?;
+    let plaintext = match cipher {
+        _ if cipher.keeps_formatting() && difficulty == Difficulty::Easy => text,
+        // Playfair has no J; the answer is checked against what decryption gives
+        ChallengeCipher::Playfair => only_letters(&text).replace('J', "I"),
+        _ => only_letters(&text),
+    };
+    let mut hints = Vec::new();
+
+    let (key, ciphertext) = match cipher {
+        ChallengeCipher::Caesar => {
+            let shift = random_between(1, 25)? as u8;
+            (shift.to_string(), Caesar::with_shift(shift).encrypt(&plaintext))
+        }
+        ChallengeCipher::Vigenere => {
+            let length = match difficulty {
+                Difficulty::Easy => random_between(3, 4)?,
+                Difficulty::Medium => random_between(5, 7)?,
+                Difficulty::Hard => random_between(8, 12)?,
+            };
+            match difficulty {
+                Difficulty::Easy => hints.push(format!("The key has {} letters.", length)),
+                Difficulty::Medium => hints.push(String::from("The key has at most 8 letters.")),
+                Difficulty::Hard => {}
+            }
+            let key = random_word("ABCDEFGHIJKLMNOPQRSTUVWXYZ", length)?;
+            let ciphertext = Vigenere::new(&key).and_then(|cipher| cipher.encrypt(&plaintext));
+            (key, ciphertext)
+        }
+        ChallengeCipher::Hill => {
+            let size = if difficulty == Difficulty::Hard { 3 } else { 2 };
+            let cipher = random_hill_key(size)?;
+            // Hill falls to known plaintext, not to statistics; the crib is the way in
+            let crib: String = plaintext.chars().take(2 * size * size).collect();
+            hints.push(format!("The key is a {0}x{0} matrix modulo 26.", size));
+            hints.push(format!("The plaintext begins with {}.", crib));
+            let key = cipher.key().iter().flatten().map(i64::to_string).collect::<Vec<_>>().join(" ");
+            (key, cipher.encrypt(&plaintext))
+        }
+        ChallengeCipher::RailFence => {
+            let rails = match difficulty {
+                Difficulty::Easy => random_between(2, 3)?,
+                Difficulty::Medium => random_between(3, 5)?,
+                Difficulty::Hard => random_between(5, 8)?,
+            };
+            let offset = if difficulty == Difficulty::Easy { 0 } else { rng::below(2 * (rails - 1))? };
+            match difficulty {
+                Difficulty::Easy => hints.push(format!("{} rails, starting on the top rail.", rails)),
+                Difficulty::Medium => hints.push(String::from("At most 5 rails.")),
+                Difficulty::Hard => {}
+            }
+            let ciphertext = RailFence::with_offset(rails, offset).and_then(|cipher| cipher.encrypt(&plaintext));
+            (format!("{},{}", rails, offset), ciphertext)
+        }
+        ChallengeCipher::Columnar => {
+            let columns = match difficulty {
+                Difficulty::Easy => random_between(4, 5)?,
+                Difficulty::Medium => random_between(6, 7)?,
+                Difficulty::Hard => 8,
+            };
+            match difficulty {
+                Difficulty::Easy => hints.push(format!("{} columns.", columns)),
+                Difficulty::Medium => hints.push(String::from("6 or 7 columns.")),
+                Difficulty::Hard => {}
+            }
+            let key = random_word("ABCDEFGHIJKLMNOPQRSTUVWXYZ", columns)?;
+            let ciphertext = Columnar::new(&key).and_then(|cipher| cipher.encrypt(&plaintext));
+            (key, ciphertext)
+        }
+        ChallengeCipher::Playfair => {
+            let crib_length = match difficulty {
+                Difficulty::Easy => 20,
+                Difficulty::Medium => 10,
+                Difficulty::Hard => 0,
+            };
+            if crib_length > 0 {
+                hints.push(format!("The plaintext begins with {}.", &plaintext[..crib_length]));
+            }
+            let key = random_word("ABCDEFGHIKLMNOPQRSTUVWXYZ", random_between(7, 10)?)?;
+            let ciphertext = Playfair::new(&key).and_then(|cipher| cipher.encrypt(&plaintext));
+            (key, ciphertext)
+        }
+    };
+
+    Ok(Challenge {
+        student: student.to_string(),
+        key,
+        plaintext,
+        ciphertext: ciphertext.map_err(cipher_error)?,
+        hints,
+    })
+}
+
+/// The answer key as JSON, before sealing
+pub(crate) fn answer_key(challenges: &[Challenge], cipher: ChallengeCipher, difficulty: Difficulty, language: Language) -> Value {
+    json!({
+        "cipher": cipher.name(),
+        "difficulty": difficulty.name(),
+        "language": language.name(),
+        "students": challenges.iter().map(|challenge| json!({
+            "student": challenge.student,
+            "key": challenge.key,
+            "plaintext": challenge.plaintext,
+            "ciphertext": challenge.ciphertext,
+        })).collect::<Vec<_>>(),
+    })
+}
+
+/// AES-128 and HMAC keys from the passphrase, independent halves of one derivation
+fn seal_keys(passphrase: &str, salt: &[u8], iterations: u32) -> io::Result<(Aes128, Vec<u8>)> {
+    let derived = Kdf::Pbkdf2 { iterations }.derive(passphrase.as_bytes(), salt);
+    let (cipher_key, mac_key) = derived.split_at(16);
+    Ok((Aes128::new(cipher_key).map_err(cipher_error)?, mac_key.to_vec()))
+}
+
+/// Encrypt the answer key under a passphrase (PBKDF2, then AES-128-CBC
+/// with an HMAC-SHA256 tag), so it can sit next to the challenges
+pub(crate) fn seal(answers: &Value, passphrase: &str) -> io::Result<String> {
+    let salt = rng::salt()?;
+    let iv = rng::iv(16)?;
+    let (cipher, mac_key) = seal_keys(passphrase, &salt, SEAL_ITERATIONS)?;
+    let sealed = modes::encrypt_then_mac(&cipher, Mode::Cbc, &iv, &mac_key, answers.to_string().as_bytes()).map_err(cipher_error)?;
+
+    let document = json!({
+        "format": SEAL_FORMAT,
+        "kdf": "pbkdf2-sha256",
+        "iterations": SEAL_ITERATIONS,
+        "salt": encode_hex(&salt),
+        "sealed": encode_hex(&sealed),
+    });
+    Ok(format!("{:#}\n", document))
+}
+
+/// Check the tag and decrypt an answer key written by [`seal`]
+pub(crate) fn unseal(document: &str, passphrase: &str) -> io::Result<Value> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let document: Value = serde_json::from_str(document).map_err(|_| invalid(tr!("Not a sealed answer key")))?;
+    if document["format"] != SEAL_FORMAT {
+        return Err(invalid(tr!("Not a sealed answer key")));
+    }
+    let hex = |field: &str| document[field].as_str().and_then(|hex| decode_hex(hex).ok()).ok_or_else(|| invalid(&tr!("Missing or invalid {}", field)));
+    let iterations = document["iterations"].as_u64().and_then(|n| u32::try_from(n).ok()).ok_or_else(|| invalid(tr!("Missing or invalid iterations")))?;
+
+    let (cipher, mac_key) = seal_keys(passphrase, &hex("salt")?, iterations)?;
+    let plaintext = modes::decrypt_verified(&cipher, Mode::Cbc, &mac_key, &hex("sealed")?)
+        .map_err(|_| io::Error::new(io::ErrorKind::PermissionDenied, tr!("Wrong passphrase, or the answer key was altered")))?;
+    serde_json::from_slice(&plaintext).map_err(|_| invalid(tr!("The sealed answer key is not JSON")))
+}
+
+/// Whether a submitted plaintext matches, ignoring case, spacing and punctuation
+pub(crate) fn is_correct(expected: &str, submitted: &str) -> bool {
+    let expected = only_letters(&expected.chars().map(fold).collect::<String>());
+    let submitted = only_letters(&submitted.chars().map(fold).collect::<String>());
+    // Playfair and Hill pad the last block with X
+    !submitted.is_empty() && submitted.trim_end_matches('X') == expected.trim_end_matches('X')
+}
+
+/// File name for a student's sheet: their name with anything that is not a
+/// letter, digit, `-` or `.` replaced by `_`
+pub(crate) fn sheet_file_name(student: &str) -> String {
+    let name: String = student
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect();
+    format!("{}.txt", name.trim_start_matches('.'))
+}
+
+/// Generate a sheet per student with a sealed answer key, open the key, or
+/// check a student's answer
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto challenge gen --cipher <name> [--difficulty easy|medium|hard] [--romanian] --students <file> --passphrase <p> [--out <dir>] | \
+                         crypto challenge open --passphrase <p> <answers> | \
+                         crypto challenge check --passphrase <p> --student <name> <answers> [<plaintext>]";
+    let (ciphers, rest) = take_flag_values(args, "--cipher")?;
+    let (difficulties, rest) = take_flag_values(&rest, "--difficulty")?;
+    let (student_files, rest) = take_flag_values(&rest, "--students")?;
+    let (students, rest) = take_flag_values(&rest, "--student")?;
+    let (passphrases, rest) = take_flag_values(&rest, "--passphrase")?;
+    let (outputs, rest) = take_flag_values(&rest, "--out")?;
+    let (romanian, rest) = take_romanian(&rest);
+    let language = if romanian { Language::Romanian } else { Language::English };
+    let (Some((operation, rest)), Some(passphrase)) = (rest.split_first(), passphrases.last()) else {
+        return Err(usage_error(USAGE));
+    };
+
+    match (operation.as_str(), rest) {
+        ("gen", []) => {
+            let (Some(cipher), Some(student_file)) = (ciphers.last(), student_files.last()) else {
+                return Err(usage_error(USAGE));
+            };
+            let cipher = ChallengeCipher::parse(cipher)?;
+            let difficulty = match difficulties.last() {
+                Some(name) => Difficulty::parse(name)?,
+                None => Difficulty::Medium,
+            };
+            let names = std::fs::read_to_string(student_file)
+                .map_err(|e| io::Error::new(e.kind(), tr!("Cannot read {}: {}", student_file, e)))?;
+            let names: Vec<&str> = names.lines().map(str::trim).filter(|name| !name.is_empty() && !name.starts_with('#')).collect();
+            if names.is_empty() {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("{} lists no students", student_file)));
+            }
+
+            let dir = std::path::PathBuf::from(outputs.last().map(String::as_str).unwrap_or("challenges"));
+            std::fs::create_dir_all(&dir)?;
+            let mut challenges = Vec::new();
+            let mut sheets = Vec::new();
+            for name in names {
+                let challenge = generate(name, cipher, difficulty, language)?;
+                let path = dir.join(sheet_file_name(name));
+                std::fs::write(&path, challenge.sheet(cipher, difficulty))?;
+                sheets.push(json!({"student": name, "sheet": path.display().to_string()}));
+                challenges.push(challenge);
+            }
+            let answers_path = dir.join("answers.sealed");
+            let answers = answer_key(&challenges, cipher, difficulty, language);
+            std::fs::write(&answers_path, seal(&answers, passphrase)?)?;
+
+            out.line(tr!(
+                "Wrote {} {} {} challenges to {}; answers sealed in {}",
+                challenges.len(),
+                difficulty.name(),
+                cipher.name(),
+                dir.display(),
+                answers_path.display()
+            ));
+            out.field("cipher", cipher.name());
+            out.field("difficulty", difficulty.name());
+            out.field("language", language.name());
+            out.field("challenges", sheets);
+            out.field("answers", answers_path.display().to_string());
+        }
+        ("open", [path]) => {
+            let answers = unseal(&std::fs::read_to_string(path)?, passphrase)?;
+            out.line(format!("{} {} ({})", answers["difficulty"].as_str().unwrap_or(""), answers["cipher"].as_str().unwrap_or(""), answers["language"].as_str().unwrap_or("")));
+            for entry in answers["students"].as_array().into_iter().flatten() {
+                out.line(tr!("{}  key {}", entry["student"].as_str().unwrap_or(""), entry["key"].as_str().unwrap_or("")));
+                out.line(format!("  {}", entry["plaintext"].as_str().unwrap_or("")));
+            }
+            if let Value::Object(fields) = answers {
+                for (key, value) in fields {
+                    out.field(&key, value);
+                }
+            }
+        }
+        ("check", [path, submitted @ ..]) => {
+            let Some(student) = students.last() else {
+                return Err(usage_error(USAGE));
+            };
+            let answers = unseal(&std::fs::read_to_string(path)?, passphrase)?;
+            let entry = answers["students"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find(|entry| entry["student"] == student.as_str())
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, tr!("No challenge for {}", student)))?;
+            let correct = is_correct(entry["plaintext"].as_str().unwrap_or(""), &text_argument(submitted, USAGE)?);
+
+            out.line(format!("{}: {}", student, if correct { tr!("correct") } else { tr!("incorrect") }));
+            out.field("student", student.as_str());
+            out.field("correct", correct);
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+//! The cipher registry: built-in ciphers, DES, Playfair and plugins, and the
+//! generic `encrypt`/`decrypt` command for those without one of their own
+
+use std::io;
+use std::sync::OnceLock;
+
+use crypto_core::registry::{CipherPlugin, Registration, Registry};
+use crypto_core::tr;
+use des::DesPlugin;
+use playfair::PlayfairPlugin;
+use serde_json::json;
+
+use crate::output::CommandOutput;
+use crate::{cipher_error, key_bytes, run_classical, run_symmetric, take_flag_values, usage_error};
+
+/// Every registered cipher: crypto-core's, DES and Playfair, and the
+/// student plugins when built with the `student-ciphers` feature
+pub(crate) fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut registry = Registry::builtin();
+        let plugins: &[&dyn CipherPlugin] = &[
+            &DesPlugin,
+            &PlayfairPlugin,
+            #[cfg(feature = "student-ciphers")]
+            &student_ciphers::StudentCiphers,
+        ];
+        for plugin in plugins {
+            // A broken plugin leaves the other ciphers usable
+            if let Err(e) = registry.install(*plugin) {
+                tracing::warn!(plugin = plugin.name(), error = %e, "cipher plugin not installed");
+            }
+        }
+        registry
+    })
+}
+
+/// `crypto <name> (encrypt | decrypt) --key <key> [--hex] [<text>]` for a
+/// registered cipher without a subcommand of its own, such as a plugin's
+pub(crate) fn run_registered(registration: &Registration, args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    let usage = if registration.is_classical() {
+        format!("crypto {} (encrypt | decrypt) --key <key> [<text>]", registration.name())
+    } else {
+        format!("crypto {} (encrypt | decrypt) --key <key> [--hex] [<text>]", registration.name())
+    };
+    let (keys, rest) = take_flag_values(args, "--key")?;
+    let hex = rest.iter().any(|arg| arg == "--hex");
+    let rest: Vec<String> = rest.into_iter().filter(|arg| arg != "--hex").collect();
+    let (Some(key), Some((operation, rest))) = (keys.last(), rest.split_first()) else {
+        return Err(usage_error(&usage));
+    };
+
+    if registration.is_classical() {
+        if hex {
+            return Err(usage_error(&usage));
+        }
+        run_classical(registration.classical_cipher(key).map_err(cipher_error)?.as_ref(), operation, rest, &usage, out)
+    } else {
+        let cipher = registration.symmetric_cipher(&key_bytes(key, hex)?).map_err(cipher_error)?;
+        run_symmetric(cipher.as_ref(), operation, rest, &usage, out)
+    }
+}
+
+/// `crypto ciphers`: what is registered, where it comes from and the key it takes
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    if !args.is_empty() {
+        return Err(usage_error("crypto ciphers"));
+    }
+    out.line(tr!("Registered ciphers (name, family, origin, key):"));
+    for registration in registry().iter() {
+        let info = &registration.info;
+        out.line(format!("  {:<10} {:<13} {:<16} {}", info.name, info.family.name(), registration.origin, info.key_description));
+    }
+    out.field("ciphers", registry().iter().map(|registration| json!({
+        "name": registration.name(),
+        "family": registration.info.family.name(),
+        "origin": registration.origin,
+        "key": registration.info.key_description,
+        "example_key": registration.example_key,
+        "block_size": registration.info.block_size,
+        "exact_round_trip": registration.capabilities.exact_round_trip,
+        "deterministic": registration.capabilities.deterministic,
+    })).collect::<Vec<_>>());
+    Ok(())
+}
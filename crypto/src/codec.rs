@@ -0,0 +1,60 @@
+//! `crypto encode` and `crypto decode`: hex, Base32 and Base64
+
+use std::io;
+
+use crypto_core::codec::{encode_hex, Encoding, Strictness};
+use crypto_core::tr;
+
+use crate::output::CommandOutput;
+use crate::{key_bytes, take_flag_values, text_argument, usage_error};
+
+/// `crypto encode` and `crypto decode`: bytes to and from hex, Base32 or Base64
+pub(crate) fn run(command: &str, args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto encode (hex | base32 | base64) [--hex | --file <path>] [<text>] | \
+                         crypto decode (hex | base32 | base64) [--lenient] [--hex | --out <file>] [<text>]";
+    let (files, rest) = take_flag_values(args, "--file")?;
+    let (outputs, rest) = take_flag_values(&rest, "--out")?;
+    let hex = rest.iter().any(|arg| arg == "--hex");
+    let lenient = rest.iter().any(|arg| arg == "--lenient");
+    let rest: Vec<String> = rest.into_iter().filter(|arg| arg != "--hex" && arg != "--lenient").collect();
+    let Some((name, rest)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+    let encoding = Encoding::parse(name)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, tr!("Unknown encoding {} (hex, base32 or base64)", name)))?;
+
+    match command {
+        "encode" if outputs.is_empty() && !lenient && (!hex || files.is_empty()) => {
+            let bytes = match files.last() {
+                Some(path) if rest.is_empty() => std::fs::read(path)?,
+                Some(_) => return Err(usage_error(USAGE)),
+                None => key_bytes(&text_argument(rest, USAGE)?, hex)?,
+            };
+            let result = encoding.encode(&bytes);
+            out.line(&result);
+            out.field("encoding", encoding.name());
+            out.field("result", result);
+        }
+        "decode" if files.is_empty() && (!hex || outputs.is_empty()) => {
+            let strictness = if lenient { Strictness::Lenient } else { Strictness::Strict };
+            let bytes = encoding.decode(&text_argument(rest, USAGE)?, strictness)?;
+            out.field("encoding", encoding.name());
+            out.field("bytes", bytes.len());
+            match outputs.last() {
+                Some(path) => {
+                    std::fs::write(path, &bytes)?;
+                    out.line(tr!("Wrote {} bytes to {}", bytes.len(), path));
+                    out.field("output", path.as_str());
+                }
+                None => {
+                    let result = if hex { encode_hex(&bytes) } else { String::from_utf8_lossy(&bytes).into_owned() };
+                    out.line(&result);
+                    out.field("result", result);
+                }
+            }
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
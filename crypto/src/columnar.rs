@@ -0,0 +1,74 @@
+//! `crypto columnar`: single and double columnar transposition, optionally
+//! after Playfair, and recovering the column order by anagramming
+
+use std::io;
+
+use crypto_core::columnar::{self, Columnar};
+use crypto_core::language::{quadgram_score, Language};
+use crypto_core::{tr, ClassicalCipher};
+use playfair::Playfair;
+
+use crate::output::CommandOutput;
+use crate::{cipher_error, run_classical, take_flag_values, take_romanian, text_argument, usage_error};
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto columnar (encrypt | decrypt) --key <keyword>[ <keyword>] [--playfair <key>] [<text>] | \
+                         crypto columnar crack [--columns <n>] [--romanian] [<text>]";
+    let (keys, rest) = take_flag_values(args, "--key")?;
+    let (playfair_keys, rest) = take_flag_values(&rest, "--playfair")?;
+    let (columns, rest) = take_flag_values(&rest, "--columns")?;
+    let (romanian, rest) = take_romanian(&rest);
+    let language = if romanian { Language::Romanian } else { Language::English };
+    let Some((operation, rest)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+
+    match (operation.as_str(), keys.last()) {
+        ("crack", None) => {
+            let ciphertext = text_argument(rest, USAGE)?;
+            let widths = match columns.last() {
+                Some(columns) => {
+                    let columns = columns.parse::<usize>().ok().filter(|&columns| columns >= 2).ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid column count {}", columns))
+                    })?;
+                    columns..=columns
+                }
+                None => 2..=8,
+            };
+            let (order, plaintext) = widths
+                .map(|columns| columnar::crack(&ciphertext, columns, language))
+                .max_by(|a, b| quadgram_score(&a.1, language).total_cmp(&quadgram_score(&b.1, language)))
+                .unwrap_or_default();
+            let key = columnar::order_keyword(&order);
+
+            out.line(tr!("{} columns, key {}: {}", order.len(), key, plaintext));
+            out.field("columns", order.len());
+            out.field("key", key);
+            out.field("result", plaintext);
+        }
+        (_, Some(key)) => {
+            let cipher = Columnar::new(key).map_err(cipher_error)?;
+            let Some(playfair_key) = playfair_keys.last() else {
+                return run_classical(&cipher, operation, rest, USAGE, out);
+            };
+
+            // Playfair then transposition, the product cipher the course builds up to
+            let playfair = Playfair::new(playfair_key).map_err(cipher_error)?;
+            let text = text_argument(rest, USAGE)?;
+            let result = match operation.as_str() {
+                "encrypt" => playfair.encrypt(&text).and_then(|substituted| cipher.encrypt(&substituted)),
+                "decrypt" => cipher.decrypt(&text).and_then(|transposed| playfair.decrypt(&transposed)),
+                _ => return Err(usage_error(USAGE)),
+            }
+            .map_err(cipher_error)?;
+
+            out.line(&result);
+            out.field("cipher", "playfair+columnar");
+            out.field("operation", operation.as_str());
+            out.field("result", result);
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use std::env;
+use std::io;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crypto_core::config::Config;
+use crypto_core::i18n::Lang;
+use crypto_core::logging::LogOptions;
+use crypto_core::tr;
+
+use crate::output::OutputFormat;
+
+/// Settings shared by every subcommand, from the layered configuration
+/// (defaults, `crypto.toml`, environment) with the global flags on top
+pub(crate) struct Settings {
+    /// `output.format`, or `--output`
+    pub(crate) format: OutputFormat,
+    /// `pki` executable (`crypto.pki_bin`); by default the one installed
+    /// next to `crypto`, then the one on the PATH
+    pub(crate) pki_bin: PathBuf,
+    /// `CRYPTO_API_KEY`, the key `crypto serve` requires, or `--api-key`.
+    /// A secret, so never read from the config file.
+    pub(crate) api_key: Option<String>,
+    /// `-v`/`-vv` and `--log-format` (`log.format`), passed on to `pki`
+    pub(crate) log: LogOptions,
+    /// `ui.lang` or `--lang`, else the locale; also passed on to `pki`
+    pub(crate) lang: Lang,
+    /// `ciphers.alphabet = "romanian"`: commands act as if given `--romanian`
+    pub(crate) romanian: bool,
+    /// `keys.dir`, where relative key file paths are resolved
+    pub(crate) key_dir: PathBuf,
+    /// `serve.address`, where `crypto serve` listens without an address argument
+    pub(crate) serve_address: String,
+    /// `quiz.results`, the CSV file `crypto quiz` appends scores to
+    pub(crate) quiz_results: PathBuf,
+    /// `vault.path`, the key vault `crypto vault` keeps, or `--vault`
+    pub(crate) vault: PathBuf,
+    /// `pki.users_dir`, where `crypto hybrid` finds certificates and keys
+    pub(crate) pki_users_dir: PathBuf,
+}
+
+static INSTALLED: OnceLock<Settings> = OnceLock::new();
+
+impl Settings {
+    pub(crate) fn from_config(config: &Config) -> io::Result<Self> {
+        let format = config.parse("output.format")?;
+
+        let pki_bin = match config.get("crypto.pki_bin") {
+            Some(path) => PathBuf::from(path),
+            None => env::current_exe()
+                .ok()
+                .and_then(|exe| Some(exe.parent()?.join(format!("pki{}", env::consts::EXE_SUFFIX))))
+                .filter(|path| path.is_file())
+                .unwrap_or_else(|| PathBuf::from("pki")),
+        };
+
+        let romanian = match config.get("ciphers.alphabet") {
+            Some("latin") | None => false,
+            Some("romanian") => true,
+            Some(other) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    tr!("Invalid ciphers.alphabet {} (from {}): expected latin or romanian", format!("{:?}", other), config.source("ciphers.alphabet")),
+                ));
+            }
+        };
+
+        Ok(Settings {
+            format,
+            pki_bin,
+            api_key: env::var("CRYPTO_API_KEY").ok().filter(|key| !key.is_empty()),
+            log: LogOptions::from_config(config)?,
+            lang: Lang::from_config(config)?,
+            romanian,
+            key_dir: PathBuf::from(config.get("keys.dir").unwrap_or(".")),
+            serve_address: config.get("serve.address").unwrap_or("127.0.0.1:8080").to_string(),
+            quiz_results: PathBuf::from(config.get("quiz.results").unwrap_or("quiz-results.csv")),
+            vault: PathBuf::from(config.get("vault.path").unwrap_or("crypto.vault")),
+            pki_users_dir: PathBuf::from(config.get("pki.users_dir").unwrap_or("./pki/users")),
+        })
+    }
+
+    /// Make the settings, flags applied, what [`settings`] returns
+    pub(crate) fn install(self) -> &'static Settings {
+        INSTALLED.get_or_init(|| self)
+    }
+}
+
+/// The installed settings, or the built-in defaults before (or without)
+/// [`Settings::install`]
+pub(crate) fn settings() -> &'static Settings {
+    INSTALLED.get_or_init(|| Settings::from_config(&Config::defaults()).expect("the built-in defaults are valid"))
+}
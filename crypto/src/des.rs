@@ -0,0 +1,56 @@
+//! `crypto des`: DES keys and encryption in ECB, CBC or CTR mode
+
+use std::io;
+
+use crypto_core::codec::encode_hex;
+use crypto_core::modes::Mode;
+use crypto_core::{tr, SymmetricCipher};
+use des::{Des, DesKeyGenerator};
+
+use crate::output::CommandOutput;
+use crate::{cipher_error, key_bytes, run_block_mode, run_symmetric, take_flag_values, take_mode_flags, usage_error, ModeOptions};
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto des key ([--hex] <key> | --random) | \
+                         crypto des (encrypt | decrypt) --key <key> [--hex] [--mode ecb|cbc|ctr] [--iv <hex>] [--mac-key <key>] [<text>]";
+    let (keys, rest) = take_flag_values(args, "--key")?;
+    let (mac_keys, rest) = take_flag_values(&rest, "--mac-key")?;
+    let (mode, iv, rest) = take_mode_flags(&rest)?;
+    let hex = rest.iter().any(|arg| arg == "--hex");
+    let random = rest.iter().any(|arg| arg == "--random");
+    let rest: Vec<String> = rest.into_iter().filter(|arg| arg != "--hex" && arg != "--random").collect();
+    let Some((operation, rest)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+
+    if operation != "key" && !random {
+        let Some(key) = keys.last() else {
+            return Err(usage_error(USAGE));
+        };
+        let cipher = Des::new(&key_bytes(key, hex)?).map_err(cipher_error)?;
+        let mac_key = mac_keys.last().map(|key| key_bytes(key, hex)).transpose()?;
+        // Encrypt-then-MAC defaults to CBC, the mode it protects from padding oracles
+        return match (mode, &mac_key) {
+            (Some(mode), _) => run_block_mode(&cipher, ModeOptions { mode, iv: iv.as_ref(), mac_key: mac_key.as_deref() }, operation, rest, USAGE, out),
+            (None, Some(_)) => run_block_mode(&cipher, ModeOptions { mode: Mode::Cbc, iv: iv.as_ref(), mac_key: mac_key.as_deref() }, operation, rest, USAGE, out),
+            (None, None) => run_symmetric(&cipher, operation, rest, USAGE, out),
+        };
+    }
+
+    let key_gen = match (rest, keys.is_empty(), random) {
+        ([key], true, false) => DesKeyGenerator::new(&key_bytes(key, hex)?),
+        ([], true, true) if operation == "key" && !hex => DesKeyGenerator::random(),
+        _ => return Err(usage_error(USAGE)),
+    }
+    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+
+    if random {
+        out.line(tr!("Random key (hex, odd parity): {}", encode_hex(key_gen.raw_key())));
+    }
+    // Keys are zero-padded or truncated to 8 bytes before PC-1
+    out.line(tr!("K+ (56 bits after PC-1): 0x{}", format!("{:014X}", key_gen.k_plus())));
+    out.field("key", encode_hex(key_gen.raw_key()));
+    out.field("k_plus", format!("{:014x}", key_gen.k_plus()));
+
+    Ok(())
+}
@@ -0,0 +1,193 @@
+//! `crypto ecc`: point arithmetic on the toy curve and P-256, and ECDSA
+
+use std::io;
+
+use crypto_core::ecc::{Curve, EcdsaPrivateKey, EcdsaSignature, Point};
+use crypto_core::tr;
+use num_bigint::BigUint;
+use serde_json::{json, Value};
+
+use crate::output::CommandOutput;
+use crate::{big_number, cipher_error, hex_pair, key_path, read_key_json, take_flag_values, text_argument, usage_error};
+
+/// A point as `x,y` (decimal or 0x hex), `G` for the base point or `O`
+fn curve_point(curve: &Curve, text: &str) -> io::Result<Point> {
+    let point = match text {
+        "O" => Point::Infinity,
+        "G" => curve.g.clone(),
+        _ => {
+            let (x, y) = text
+                .trim_matches(|c| c == '(' || c == ')')
+                .split_once(',')
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, tr!("Expected a point x,y, G or O, got {}", text)))?;
+            Point::Affine(big_number(x.trim())?, big_number(y.trim())?)
+        }
+    };
+    if !curve.contains(&point) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("{} is not on the {} curve", point, curve.name)));
+    }
+    Ok(point)
+}
+
+fn point_json(point: &Point) -> Value {
+    match point {
+        Point::Infinity => Value::Null,
+        Point::Affine(x, y) => json!({ "x": x.to_string(), "y": y.to_string() }),
+    }
+}
+
+/// The points of a toy curve on a grid, x to the right and y upwards
+fn plot_points(curve: &Curve, points: &[Point]) -> Vec<String> {
+    let size = u32::try_from(&curve.p).unwrap_or(0);
+    let marked = |x: u32, y: u32| points.contains(&Point::new(x, y));
+    let mut lines: Vec<String> = (0..size)
+        .rev()
+        .map(|y| {
+            let row: String = (0..size).map(|x| if marked(x, y) { "  ●" } else { "  ·" }).collect();
+            format!("{:>3} |{}", y, row)
+        })
+        .collect();
+    lines.push(format!("    +{}", "───".repeat(size as usize)));
+    lines.push(format!("     {}", (0..size).map(|x| format!("{:>3}", x)).collect::<String>()));
+    lines
+}
+
+fn write_ecdsa_key(path: &str, key: &EcdsaPrivateKey) -> io::Result<()> {
+    let Point::Affine(x, y) = &key.q else {
+        return Err(io::Error::other(tr!("The public key is the point at infinity")));
+    };
+    let json = json!({
+        "curve": key.curve.name,
+        "d": key.d.to_str_radix(16),
+        "x": x.to_str_radix(16),
+        "y": y.to_str_radix(16),
+    });
+    std::fs::write(key_path(path), format!("{:#}\n", json))
+}
+
+fn read_ecdsa_key(path: &str) -> io::Result<EcdsaPrivateKey> {
+    let (json, name) = read_key_json(path)?;
+    let invalid = |field: &str| io::Error::new(io::ErrorKind::InvalidData, tr!("{} has no valid {}", name, field));
+    let curve = Curve::by_name(json["curve"].as_str().ok_or_else(|| invalid("curve"))?).map_err(cipher_error)?;
+    let d = json["d"].as_str().and_then(|hex| BigUint::parse_bytes(hex.as_bytes(), 16)).ok_or_else(|| invalid("d"))?;
+    let key = EcdsaPrivateKey::from_scalar(curve, d);
+    // The stored public point must be the one d gives
+    let stored = json["x"].as_str().zip(json["y"].as_str()).and_then(|(x, y)| {
+        Some(Point::Affine(BigUint::parse_bytes(x.as_bytes(), 16)?, BigUint::parse_bytes(y.as_bytes(), 16)?))
+    });
+    if stored.as_ref() != Some(&key.q) {
+        return Err(invalid("public point"));
+    }
+    Ok(key)
+}
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto ecc (points | multiples) [--curve <name>] | crypto ecc (add | double) [--curve <name>] <x,y> [<x,y>] | \
+                         crypto ecc multiply [--curve <name>] <k> [<x,y>] | crypto ecc keygen [--curve <name>] <key.json> | \
+                         crypto ecc sign --key <key.json> [<text>] | crypto ecc verify --key <key.json> --signature <r:s> [<text>]";
+    let (curves, rest) = take_flag_values(args, "--curve")?;
+    let (keys, rest) = take_flag_values(&rest, "--key")?;
+    let (signatures, rest) = take_flag_values(&rest, "--signature")?;
+    let Some((operation, rest)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+    // The toy curve for the lecture operations, P-256 for real keys
+    let default_curve = if operation == "keygen" { "p256" } else { "toy" };
+    let curve = Curve::by_name(curves.last().map(String::as_str).unwrap_or(default_curve)).map_err(cipher_error)?;
+    let toy_only = || {
+        if curve.p.bits() > 8 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("{} has far too many points to list", curve.name)));
+        }
+        Ok(())
+    };
+
+    match (operation.as_str(), keys.last(), rest) {
+        ("points", None, []) => {
+            toy_only()?;
+            let points = curve.points();
+            out.line(tr!("y² = x³ + {}x + {} over F{}: {} points counting O", curve.a, curve.b, curve.p, points.len()));
+            for line in plot_points(&curve, &points) {
+                out.line(line);
+            }
+            out.line("");
+            out.line(format!("{:<12} {:>5}", tr!("Point"), tr!("Order")));
+            let mut listed = Vec::new();
+            for point in &points {
+                let order = curve.order_of(point);
+                out.line(format!("{:<12} {:>5}", point.to_string(), order));
+                listed.push(json!({ "point": point_json(point), "order": order }));
+            }
+            out.field("curve", curve.name.as_str());
+            out.field("points", listed);
+        }
+        ("multiples", None, []) => {
+            toy_only()?;
+            out.line(tr!("Multiples of G = {} on the {} curve", curve.g, curve.name));
+            let mut multiples = Vec::new();
+            let mut multiple = Point::Infinity;
+            for k in 1..=curve.order_of(&curve.g) {
+                multiple = curve.add(&multiple, &curve.g);
+                out.line(format!("{:>4}G = {}", k, multiple));
+                multiples.push(point_json(&multiple));
+            }
+            out.field("curve", curve.name.as_str());
+            out.field("multiples", multiples);
+        }
+        ("add", None, [first, second]) => {
+            let (first, second) = (curve_point(&curve, first)?, curve_point(&curve, second)?);
+            let sum = curve.add(&first, &second);
+            out.line(format!("{} + {} = {}", first, second, sum));
+            out.field("result", point_json(&sum));
+        }
+        ("double", None, [point]) => {
+            let point = curve_point(&curve, point)?;
+            let doubled = curve.double(&point);
+            out.line(format!("2·{} = {}", point, doubled));
+            out.field("result", point_json(&doubled));
+        }
+        ("multiply", None, [k, point @ ..]) if point.len() <= 1 => {
+            let k = big_number(k)?;
+            let point = match point {
+                [point] => curve_point(&curve, point)?,
+                _ => curve.g.clone(),
+            };
+            out.line(tr!("{}·{} by double-and-add over k = {}", k, point, format!("{:b}", k)));
+            out.line(format!("{:<4} {:<24} {}", tr!("Bit"), tr!("Doubled"), tr!("Result")));
+            let steps = curve.multiply_steps(&k, &point);
+            for step in &steps {
+                let bit = if step.bit { "1 +P" } else { "0" };
+                out.line(format!("{:<4} {:<24} {}", bit, step.doubled.to_string(), step.result));
+            }
+            let result = steps.last().map_or(Point::Infinity, |step| step.result.clone());
+            out.field("steps", steps.len());
+            out.field("result", point_json(&result));
+        }
+        ("keygen", None, [path]) => {
+            let key = EcdsaPrivateKey::generate(curve)?;
+            write_ecdsa_key(path, &key)?;
+            out.line(tr!("Wrote a {} key to {}", key.curve.name, path));
+            out.line(format!("Q = {}", key.q));
+            out.field("key", path.as_str());
+            out.field("curve", key.curve.name.as_str());
+            out.field("public", point_json(&key.q));
+        }
+        ("sign", Some(path), rest) => {
+            let signature = read_ecdsa_key(path)?.sign(text_argument(rest, USAGE)?.as_bytes())?;
+            let signature = format!("{}:{}", signature.r.to_str_radix(16), signature.s.to_str_radix(16));
+            out.line(&signature);
+            out.field("signature", signature);
+        }
+        ("verify", Some(path), rest) => {
+            let (r, s) = hex_pair(signatures.last().ok_or_else(|| usage_error(USAGE))?)?;
+            let valid = read_ecdsa_key(path)?.public().verify(text_argument(rest, USAGE)?.as_bytes(), &EcdsaSignature { r, s });
+            out.line(if valid { tr!("Signature valid") } else { tr!("Signature INVALID") });
+            out.field("valid", valid);
+            if !valid {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, tr!("Signature verification failed")));
+            }
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+//! `crypto elgamal`: ElGamal keys, encryption and signatures
+
+use std::io;
+
+use crypto_core::dh::DhParameters;
+use crypto_core::elgamal::{self, ElGamalCiphertext, ElGamalPrivateKey, ElGamalSignature};
+use crypto_core::tr;
+use num_bigint::BigUint;
+use serde_json::json;
+
+use crate::output::CommandOutput;
+use crate::{cipher_error, hex_pair, key_path, read_key_json, take_flag_values, text_argument, usage_error};
+
+fn write_elgamal_key(path: &str, key: &ElGamalPrivateKey) -> io::Result<()> {
+    let json = json!({
+        "p": key.parameters.p.to_str_radix(16),
+        "g": key.parameters.g.to_str_radix(16),
+        "x": key.x.to_str_radix(16),
+        "y": key.y.to_str_radix(16),
+    });
+    std::fs::write(key_path(path), format!("{:#}\n", json))
+}
+
+fn read_elgamal_key(path: &str) -> io::Result<ElGamalPrivateKey> {
+    let (json, name) = read_key_json(path)?;
+    let invalid = |field: &str| io::Error::new(io::ErrorKind::InvalidData, tr!("{} has no valid {}", name, field));
+    let number = |field: &str| {
+        json[field].as_str().and_then(|hex| BigUint::parse_bytes(hex.as_bytes(), 16)).ok_or_else(|| invalid(field))
+    };
+    Ok(ElGamalPrivateKey { parameters: DhParameters { p: number("p")?, g: number("g")? }, x: number("x")?, y: number("y")? })
+}
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto elgamal keygen [--bits <n>] <key.json> | \
+                         crypto elgamal (encrypt | decrypt) --key <key.json> [<text>] | \
+                         crypto elgamal sign --key <key.json> [<text>] | \
+                         crypto elgamal verify --key <key.json> --signature <r:s> [<text>] | \
+                         crypto elgamal attack nonce-reuse [--bits <n>]";
+    let (keys, rest) = take_flag_values(args, "--key")?;
+    let (bits, rest) = take_flag_values(&rest, "--bits")?;
+    let (signatures, rest) = take_flag_values(&rest, "--signature")?;
+    let Some((operation, rest)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+    // Safe primes take a while to find, as for `crypto dh`
+    let bits = match bits.last() {
+        Some(value) => value.parse::<u64>().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid bit count {}", value)))?,
+        None => 512,
+    };
+
+    match (operation.as_str(), keys.last()) {
+        ("keygen", None) => {
+            let [path] = rest else {
+                return Err(usage_error(USAGE));
+            };
+            let key = ElGamalPrivateKey::generate(bits)?;
+            write_elgamal_key(path, &key)?;
+
+            out.line(tr!("Wrote a {}-bit key to {}", key.parameters.p.bits(), path));
+            out.field("key", path.as_str());
+            out.field("bits", key.parameters.p.bits());
+            out.field("y", key.y.to_str_radix(16));
+        }
+        ("encrypt", Some(path)) => {
+            let plaintext = text_argument(rest, USAGE)?;
+            let ciphertext = read_elgamal_key(path)?.public().encrypt(&BigUint::from_bytes_be(plaintext.as_bytes()))?;
+            let result = format!("{}:{}", ciphertext.c1.to_str_radix(16), ciphertext.c2.to_str_radix(16));
+            out.line(&result);
+            out.field("result", result);
+        }
+        ("decrypt", Some(path)) => {
+            let (c1, c2) = hex_pair(&text_argument(rest, USAGE)?)?;
+            let plaintext = read_elgamal_key(path)?.decrypt(&ElGamalCiphertext { c1, c2 }).map_err(cipher_error)?;
+            let plaintext = String::from_utf8_lossy(&plaintext.to_bytes_be()).into_owned();
+            out.line(&plaintext);
+            out.field("result", plaintext);
+        }
+        ("sign", Some(path)) => {
+            let signature = read_elgamal_key(path)?.sign(text_argument(rest, USAGE)?.as_bytes())?;
+            let signature = format!("{}:{}", signature.r.to_str_radix(16), signature.s.to_str_radix(16));
+            out.line(&signature);
+            out.field("signature", signature);
+        }
+        ("verify", Some(path)) => {
+            let (r, s) = hex_pair(signatures.last().ok_or_else(|| usage_error(USAGE))?)?;
+            let valid = read_elgamal_key(path)?.public().verify(text_argument(rest, USAGE)?.as_bytes(), &ElGamalSignature { r, s });
+            out.line(if valid { tr!("Signature valid") } else { tr!("Signature INVALID") });
+            out.field("valid", valid);
+            if !valid {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, tr!("Signature verification failed")));
+            }
+        }
+        ("attack", None) => match rest {
+            [kind] if kind == "nonce-reuse" => {
+                let key = ElGamalPrivateKey::generate(bits)?;
+                // The same k for two messages, as a broken random number generator would give
+                let k = crypto_core::primes::random_range(&BigUint::from(2u32), &key.parameters.q())?;
+                let messages: [&[u8]; 2] = [b"Pay Alice 10 lei", b"Pay Bob 20 lei"];
+                let [first, second] = messages.map(|message| key.sign_with_nonce(message, &k));
+                let (Some(first), Some(second)) = (first, second) else {
+                    return Err(io::Error::other(tr!("The chosen nonce gave s = 0; run the demo again")));
+                };
+                let recovered = elgamal::recover_key_from_reused_nonce(&key.public(), (messages[0], &first), (messages[1], &second));
+
+                out.line(tr!("Two signatures share r = {}", first.r.to_str_radix(16)));
+                out.line(format!("  s₁ = {}", first.s.to_str_radix(16)));
+                out.line(format!("  s₂ = {}", second.s.to_str_radix(16)));
+                out.line(tr!("k = (H₁ − H₂)/(s₁ − s₂) mod q, then x = (H₁ − k·s₁)/r mod q"));
+                match &recovered {
+                    Some(recovered) => out.line(tr!("Recovered x = {} (matches the real key: {})", recovered.x.to_str_radix(16), recovered.x == key.x)),
+                    None => out.line(tr!("The recovered exponent does not reproduce y")),
+                }
+                out.field("r", first.r.to_str_radix(16));
+                out.field("recovered", recovered.is_some_and(|recovered| recovered.x == key.x));
+            }
+            _ => return Err(usage_error(USAGE)),
+        },
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
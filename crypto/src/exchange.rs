@@ -0,0 +1,184 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+
+use crypto_core::aes::Aes128;
+use crypto_core::codec::{decode_hex, encode_hex};
+use crypto_core::dh::{self, derive_key, DhKeyPair, DhParameters};
+use crypto_core::modes::{self, Mode};
+use crypto_core::rng;
+use crypto_core::{tr, SymmetricCipher};
+use num_bigint::BigUint;
+use serde_json::{json, Value};
+
+use crate::output::CommandOutput;
+use crate::{take_flag_values, usage_error};
+
+/// What one side of `crypto dh listen` / `crypto dh connect` saw
+pub(crate) struct ExchangeReport {
+    pub(crate) parameters: DhParameters,
+    pub(crate) own_public: BigUint,
+    pub(crate) peer_public: BigUint,
+    /// AES-128 key derived from the shared secret
+    pub(crate) key: Vec<u8>,
+    /// The message the connecting side sent under that key
+    pub(crate) message: String,
+}
+
+fn send(stream: &mut TcpStream, message: Value) -> io::Result<()> {
+    writeln!(stream, "{}", message)?;
+    stream.flush()
+}
+
+fn receive(reader: &mut impl BufRead) -> io::Result<Value> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, tr!("The other side hung up")));
+    }
+    serde_json::from_str(&line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+fn hex_field(message: &Value, field: &str) -> io::Result<Vec<u8>> {
+    message[field]
+        .as_str()
+        .and_then(|hex| decode_hex(hex).ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, tr!("Missing or invalid {}", field)))
+}
+
+fn number_field(message: &Value, field: &str) -> io::Result<BigUint> {
+    Ok(BigUint::from_bytes_be(&hex_field(message, field)?))
+}
+
+fn agree(parameters: &DhParameters, own: &DhKeyPair, peer_public: &BigUint) -> io::Result<Vec<u8>> {
+    let secret = parameters
+        .shared_secret(own, peer_public)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    Ok(derive_key(&secret, 16))
+}
+
+fn cipher(key: &[u8]) -> io::Result<Aes128> {
+    Aes128::new(key).map_err(|e| io::Error::other(e.to_string()))
+}
+
+/// Accept one connection on `listener`: send the parameters and our public
+/// value, take the peer's, and decrypt the message it sends under the
+/// derived key
+pub(crate) fn listen(listener: TcpListener, parameters: DhParameters) -> io::Result<ExchangeReport> {
+    let (mut stream, _) = listener.accept()?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let own = parameters.generate_key_pair()?;
+    send(&mut stream, json!({
+        "p": encode_hex(&parameters.p.to_bytes_be()),
+        "g": encode_hex(&parameters.g.to_bytes_be()),
+        "public": encode_hex(&own.public.to_bytes_be()),
+    }))?;
+
+    let reply = receive(&mut reader)?;
+    let peer_public = number_field(&reply, "public")?;
+    let key = agree(&parameters, &own, &peer_public)?;
+    let plaintext = modes::decrypt(&cipher(&key)?, Mode::Cbc, &hex_field(&reply, "iv")?, &hex_field(&reply, "message")?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+
+    Ok(ExchangeReport {
+        parameters,
+        own_public: own.public,
+        peer_public,
+        key,
+        message: String::from_utf8_lossy(&plaintext).into_owned(),
+    })
+}
+
+/// Connect to `crypto dh listen`, agree on a key and send `message` with it
+pub(crate) fn connect(address: &str, message: &str) -> io::Result<ExchangeReport> {
+    let mut stream = TcpStream::connect(address)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let offer = receive(&mut reader)?;
+    let parameters = DhParameters { p: number_field(&offer, "p")?, g: number_field(&offer, "g")? };
+    let peer_public = number_field(&offer, "public")?;
+    let own = parameters.generate_key_pair()?;
+    let key = agree(&parameters, &own, &peer_public)?;
+
+    let iv = rng::iv(16)?;
+    let ciphertext = modes::encrypt(&cipher(&key)?, Mode::Cbc, &iv, message.as_bytes())
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    send(&mut stream, json!({
+        "public": encode_hex(&own.public.to_bytes_be()),
+        "iv": encode_hex(&iv),
+        "message": encode_hex(&ciphertext),
+    }))?;
+
+    Ok(ExchangeReport { parameters, own_public: own.public, peer_public, key, message: message.to_string() })
+}
+
+/// Parameters, the two public values and the derived key of a DH exchange
+fn report_exchange(report: &ExchangeReport, out: &mut CommandOutput) {
+    out.line(tr!("p = {} ({} bits), g = {}", report.parameters.p.to_str_radix(16), report.parameters.p.bits(), report.parameters.g.to_str_radix(16)));
+    out.line(tr!("Our public value:   {}", report.own_public.to_str_radix(16)));
+    out.line(tr!("Their public value: {}", report.peer_public.to_str_radix(16)));
+    out.line(tr!("AES-128 key from the shared secret: {}", encode_hex(&report.key)));
+    out.field("p", report.parameters.p.to_str_radix(16));
+    out.field("g", report.parameters.g.to_str_radix(16));
+    out.field("own_public", report.own_public.to_str_radix(16));
+    out.field("peer_public", report.peer_public.to_str_radix(16));
+    out.field("key", encode_hex(&report.key));
+    out.field("message", report.message.as_str());
+}
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto dh params [--bits <n>] | crypto dh listen [--bits <n>] <address> | \
+                         crypto dh connect [--message <text>] <address> | crypto dh mitm [--bits <n>]";
+    let (bits, rest) = take_flag_values(args, "--bits")?;
+    let (messages, rest) = take_flag_values(&rest, "--message")?;
+    let Some((operation, rest)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+    // Safe primes take a while to find; 512 bits keeps the demo quick
+    let parameters = || match bits.last() {
+        Some(value) => {
+            let bits = value.parse::<u64>().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid bit count {}", value)))?;
+            DhParameters::generate(bits)
+        }
+        None => DhParameters::generate(512),
+    };
+
+    match (operation.as_str(), rest) {
+        ("params", []) => {
+            let parameters = parameters()?;
+            out.line(format!("p = {}", parameters.p));
+            out.line(format!("g = {}", parameters.g));
+            out.field("p", parameters.p.to_str_radix(16));
+            out.field("g", parameters.g.to_str_radix(16));
+        }
+        ("listen", [address]) => {
+            let listener = TcpListener::bind(address)?;
+            let parameters = parameters()?;
+            out.line(tr!("Listening on {}; run `crypto dh connect {}` in another terminal", listener.local_addr()?, listener.local_addr()?));
+            let report = listen(listener, parameters)?;
+            report_exchange(&report, out);
+            out.line(tr!("Decrypted message: {}", report.message));
+        }
+        ("connect", [address]) => {
+            let message = messages.last().map(String::as_str).unwrap_or("Hello over a key nobody sent");
+            let report = connect(address, message)?;
+            report_exchange(&report, out);
+            out.line(tr!("Sent AES-128-CBC encrypted: {}", report.message));
+        }
+        ("mitm", []) => {
+            let report = dh::man_in_the_middle(&parameters()?)?;
+            let hex = |secret: &BigUint| encode_hex(&dh::derive_key(secret, 16));
+            out.line(tr!("Mallory replaces both public values with her own:"));
+            out.line(tr!("  Alice's key:              {}", hex(&report.alice_secret)));
+            out.line(tr!("  Mallory's key with Alice: {}", hex(&report.mallory_with_alice)));
+            out.line(tr!("  Bob's key:                {}", hex(&report.bob_secret)));
+            out.line(tr!("  Mallory's key with Bob:   {}", hex(&report.mallory_with_bob)));
+            out.line(tr!("Alice and Bob hold different keys and Mallory holds both, so she can decrypt, read and re-encrypt"));
+            out.line(tr!("every message. Signing the public values (authenticated DH) is what stops this."));
+            out.field("alice_key", hex(&report.alice_secret));
+            out.field("bob_key", hex(&report.bob_secret));
+            out.field("mallory_keys", vec![hex(&report.mallory_with_alice), hex(&report.mallory_with_bob)]);
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
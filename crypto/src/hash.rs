@@ -0,0 +1,49 @@
+//! `crypto hash`: SHA-256 of files or standard input
+
+use std::io::{self, Read};
+
+use crypto_core::codec::encode_hex;
+use crypto_core::hash::Sha256;
+use serde_json::json;
+
+use crate::output::CommandOutput;
+use crate::usage_error;
+
+/// SHA-256 of a reader, fed through in chunks
+fn sha256_reader(mut reader: impl Read) -> io::Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    let mut buffer = [0; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(hasher.finalize());
+        }
+        hasher.update(&buffer[..read]);
+    }
+}
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto hash sha256 [<file>...]";
+    let Some((algorithm, files)) = args.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+    if algorithm != "sha256" {
+        return Err(usage_error(USAGE));
+    }
+
+    // Standard input when no file (or `-`) is given, like sha256sum
+    let files = if files.is_empty() { vec![String::from("-")] } else { files.to_vec() };
+    let mut hashes = Vec::new();
+    for file in &files {
+        let digest = match file.as_str() {
+            "-" => sha256_reader(io::stdin().lock())?,
+            path => sha256_reader(std::fs::File::open(path)?)?,
+        };
+        out.line(format!("{}  {}", encode_hex(&digest), file));
+        hashes.push(json!({ "file": file, "sha256": encode_hex(&digest) }));
+    }
+    out.field("algorithm", algorithm.as_str());
+    out.field("hashes", hashes);
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//! `crypto hill`: the Hill cipher and its known-plaintext attack
+
+use std::io;
+
+use crypto_core::hill::{self, Hill};
+use crypto_core::language::Alphabet;
+use crypto_core::{tr, ClassicalCipher};
+
+use crate::output::CommandOutput;
+use crate::{cipher_error, run_classical, take_flag_values, take_romanian, text_argument, usage_error};
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto hill (encrypt | decrypt) --key <matrix> [--romanian] [<text>] | \
+                         crypto hill attack --plaintext <text> [--size 2|3] [--romanian] [<ciphertext>]";
+    let (keys, rest) = take_flag_values(args, "--key")?;
+    let (plaintexts, rest) = take_flag_values(&rest, "--plaintext")?;
+    let (sizes, rest) = take_flag_values(&rest, "--size")?;
+    let (romanian, rest) = take_romanian(&rest);
+    let alphabet = if romanian { Alphabet::Romanian } else { Alphabet::Latin };
+    let Some((operation, rest)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+
+    match (operation.as_str(), keys.last(), plaintexts.last()) {
+        ("attack", None, Some(plaintext)) => {
+            let size = match sizes.last().map(String::as_str) {
+                None | Some("2") => 2,
+                Some("3") => 3,
+                Some(size) => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid matrix size {}", size)));
+                }
+            };
+            let ciphertext = text_argument(rest, USAGE)?;
+            let key = hill::known_plaintext_attack(plaintext, &ciphertext, size, alphabet).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    tr!("No {} plaintext blocks form an invertible matrix", size)
+                )
+            })?;
+            let cipher = Hill::from_matrix(key.clone(), alphabet).map_err(cipher_error)?;
+
+            out.line(tr!("Key matrix"));
+            for row in &key {
+                out.line(row.iter().map(|value| format!("{:>2}", value)).collect::<Vec<_>>().join(" "));
+            }
+            let plaintext = cipher.decrypt(&ciphertext).map_err(cipher_error)?;
+            out.line(tr!("Decryption: {}", plaintext));
+            out.field("key", key);
+            out.field("result", plaintext);
+        }
+        (_, Some(key), None) => {
+            let cipher = Hill::with_alphabet(key, alphabet).map_err(cipher_error)?;
+            return run_classical(&cipher, operation, rest, USAGE, out);
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,224 @@
+//! Hybrid encryption: a file is encrypted under a fresh symmetric key, and
+//! only that key is encrypted to the recipient's RSA certificate from the
+//! PKI, so the slow public-key step is done once on a few bytes
+
+use std::io;
+use std::path::PathBuf;
+
+use crypto_core::aes::Aes128;
+use crypto_core::codec::{decode_base64, encode_base64, Strictness};
+use crypto_core::modes::{self, Mode};
+use crypto_core::rng;
+use crypto_core::rsa::{RsaPrivateKey, RsaPublicKey};
+use crypto_core::{tr, BlockCipher, CipherError, SymmetricCipher};
+use des::{Des, TripleDes};
+use serde_json::{json, Value};
+
+use crate::output::CommandOutput;
+use crate::{config, take_flag_values, usage_error};
+
+/// The `format` of every container
+const FORMAT: &str = "crypto-hybrid 1";
+
+/// Bytes of the HMAC-SHA256 key wrapped along with the cipher key
+const MAC_KEY_LENGTH: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum HybridCipher {
+    Des,
+    TripleDes,
+    Aes,
+}
+
+impl HybridCipher {
+    pub(crate) fn parse(name: &str) -> io::Result<Self> {
+        match name {
+            "des" => Ok(Self::Des),
+            "3des" => Ok(Self::TripleDes),
+            "aes" => Ok(Self::Aes),
+            other => Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("Unknown cipher {} (des, 3des or aes)", other))),
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Des => "des",
+            Self::TripleDes => "3des",
+            Self::Aes => "aes",
+        }
+    }
+
+    fn key_length(self) -> usize {
+        match self {
+            Self::Des => 8,
+            Self::TripleDes => 24,
+            Self::Aes => 16,
+        }
+    }
+
+    fn cipher(self, key: &[u8]) -> Result<Box<dyn BlockCipher>, CipherError> {
+        Ok(match self {
+            Self::Des => Box::new(Des::new(key)?),
+            Self::TripleDes => Box::new(TripleDes::new(key)?),
+            Self::Aes => Box::new(Aes128::new(key)?),
+        })
+    }
+}
+
+fn cipher_error(error: CipherError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
+
+/// Encrypt `plaintext` for `recipient`: a random cipher key and MAC key,
+/// the data sealed with CBC and HMAC-SHA256 (encrypt-then-MAC), and both
+/// keys wrapped together with RSA-OAEP under the certificate's key
+pub(crate) fn seal(recipient: &str, certificate: &RsaPublicKey, cipher: HybridCipher, plaintext: &[u8]) -> io::Result<Value> {
+    let key = rng::key(cipher.key_length())?;
+    let mac_key = rng::key(MAC_KEY_LENGTH)?;
+    let block_cipher = cipher.cipher(&key).map_err(cipher_error)?;
+    let iv = rng::iv(block_cipher.block_size())?;
+
+    let sealed = modes::encrypt_then_mac(block_cipher.as_ref(), Mode::Cbc, &iv, &mac_key, plaintext).map_err(cipher_error)?;
+    let wrapped_key = certificate.encrypt_oaep(&[key, mac_key].concat())?;
+
+    Ok(json!({
+        "format": FORMAT,
+        "recipient": recipient,
+        "key_wrap": "rsa-oaep-sha256",
+        "cipher": cipher.name(),
+        "mode": "cbc-hmac-sha256",
+        "wrapped_key": encode_base64(&wrapped_key),
+        "sealed": encode_base64(&sealed),
+    }))
+}
+
+/// A container as `seal` writes it
+pub(crate) struct Container {
+    pub(crate) recipient: String,
+    pub(crate) cipher: HybridCipher,
+    wrapped_key: Vec<u8>,
+    sealed: Vec<u8>,
+}
+
+impl Container {
+    pub(crate) fn parse(text: &str) -> io::Result<Self> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, tr!("Not a hybrid container"));
+        let container: Value = serde_json::from_str(text).map_err(|_| invalid())?;
+        if container["format"] != FORMAT {
+            return Err(invalid());
+        }
+        let base64 = |field: &str| {
+            let text = container[field].as_str().ok_or_else(invalid)?;
+            decode_base64(text, Strictness::Strict).map_err(|_| invalid())
+        };
+        Ok(Container {
+            recipient: container["recipient"].as_str().ok_or_else(invalid)?.to_string(),
+            cipher: HybridCipher::parse(container["cipher"].as_str().ok_or_else(invalid)?)?,
+            wrapped_key: base64("wrapped_key")?,
+            sealed: base64("sealed")?,
+        })
+    }
+
+    /// Unwrap the keys with the recipient's private key, check the MAC and decrypt
+    pub(crate) fn open(&self, private_key: &RsaPrivateKey) -> io::Result<Vec<u8>> {
+        let keys = private_key.decrypt_oaep(&self.wrapped_key).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidData, tr!("The key could not be unwrapped: this is not {}'s private key", self.recipient))
+        })?;
+        if keys.len() != self.cipher.key_length() + MAC_KEY_LENGTH {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, tr!("Not a hybrid container")));
+        }
+        let (key, mac_key) = keys.split_at(self.cipher.key_length());
+        let block_cipher = self.cipher.cipher(key).map_err(cipher_error)?;
+        modes::decrypt_verified(block_cipher.as_ref(), Mode::Cbc, mac_key, &self.sealed).map_err(cipher_error)
+    }
+}
+
+/// `crypto hybrid`: encrypt a file to a PKI user's certificate, or open
+/// such a container with the user's private key (by default the one the
+/// PKI keeps next to the certificate)
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto hybrid encrypt --for <user> [--cipher des|3des|aes] [--out <file>] <file> | \
+                         crypto hybrid decrypt [--key <private key>] [--out <file>] <container>";
+    let (recipients, rest) = take_flag_values(args, "--for")?;
+    let (ciphers, rest) = take_flag_values(&rest, "--cipher")?;
+    let (keys, rest) = take_flag_values(&rest, "--key")?;
+    let (outputs, rest) = take_flag_values(&rest, "--out")?;
+    let users_dir = &config::settings().pki_users_dir;
+    let read = |path: &std::path::Path| {
+        std::fs::read_to_string(path).map_err(|e| io::Error::new(e.kind(), tr!("Cannot read {}: {}", path.display(), e)))
+    };
+
+    match (rest.as_slice(), recipients.last()) {
+        ([operation, input], Some(recipient)) if operation == "encrypt" && keys.is_empty() => {
+            let cipher = HybridCipher::parse(ciphers.last().map_or("aes", String::as_str))?;
+            let certificate_path = users_dir.join(format!("{}_certificate.pem", recipient));
+            let certificate = RsaPublicKey::from_certificate_pem(&read(&certificate_path)?).map_err(cipher_error)?;
+            let plaintext = std::fs::read(input).map_err(|e| io::Error::new(e.kind(), tr!("Cannot read {}: {}", input, e)))?;
+            let container = seal(recipient, &certificate, cipher, &plaintext)?;
+
+            let output = outputs.last().cloned().unwrap_or_else(|| format!("{}.hybrid", input));
+            std::fs::write(&output, format!("{:#}\n", container))?;
+            out.line(tr!("Encrypted {} for {} under a fresh {} key -> {}", input, recipient, cipher.name(), output));
+            out.field("input", input.as_str());
+            out.field("output", output);
+            out.field("recipient", recipient.as_str());
+            out.field("cipher", cipher.name());
+        }
+        ([operation, input], None) if operation == "decrypt" && ciphers.is_empty() => {
+            let container = Container::parse(&read(std::path::Path::new(input))?)?;
+            let key_path = match keys.last() {
+                Some(path) => PathBuf::from(path),
+                None => users_dir.join(format!("{}_private_key.pem", container.recipient)),
+            };
+            let pem = read(&key_path)?;
+            if pem.contains("ENCRYPTED") {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    tr!("{} is encrypted; pass a decrypted copy (openssl pkey -in {} -out <file>) as --key", key_path.display(), key_path.display()),
+                ));
+            }
+            let private_key = RsaPrivateKey::from_pem(&pem).map_err(cipher_error)?;
+            let plaintext = container.open(&private_key)?;
+
+            let output = match (outputs.last(), input.strip_suffix(".hybrid")) {
+                (Some(path), _) => path.clone(),
+                (None, Some(stripped)) => stripped.to_string(),
+                (None, None) => format!("{}.dec", input),
+            };
+            std::fs::write(&output, &plaintext)?;
+            out.line(tr!("Decrypted {} -> {}", input, output));
+            out.field("input", input.as_str());
+            out.field("output", output);
+            out.field("recipient", container.recipient.as_str());
+            out.field("cipher", container.cipher.name());
+            out.field("bytes", plaintext.len());
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn containers_open_only_with_the_recipients_key() {
+        let alice = RsaPrivateKey::generate(1024).unwrap();
+        let bob = RsaPrivateKey::generate(1024).unwrap();
+        for cipher in [HybridCipher::Des, HybridCipher::TripleDes, HybridCipher::Aes] {
+            let sealed = seal("alice", &alice.public(), cipher, b"lab 7 results").unwrap();
+            let container = Container::parse(&sealed.to_string()).unwrap();
+            assert_eq!((container.recipient.as_str(), container.cipher), ("alice", cipher));
+            assert_eq!(container.open(&alice).unwrap(), b"lab 7 results");
+            assert!(container.open(&bob).is_err());
+        }
+
+        let mut altered = seal("alice", &alice.public(), HybridCipher::Aes, b"lab 7 results").unwrap();
+        let mut sealed = decode_base64(altered["sealed"].as_str().unwrap(), Strictness::Strict).unwrap();
+        sealed[20] ^= 1;
+        altered["sealed"] = Value::from(encode_base64(&sealed));
+        assert!(Container::parse(&altered.to_string()).unwrap().open(&alice).is_err());
+        assert!(Container::parse("{\"format\": \"other\"}").is_err());
+    }
+}
@@ -0,0 +1,173 @@
+use std::fs;
+use std::io::{self, BufWriter};
+use std::path::Path;
+
+use crypto_core::stego::Image;
+use crypto_core::tr;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Read a PNG or an uncompressed BMP, whichever the file turns out to be
+pub(crate) fn load(path: &str) -> io::Result<Image> {
+    let bytes = fs::read(path).map_err(|e| io::Error::new(e.kind(), tr!("Cannot read {}: {}", path, e)))?;
+    if bytes.starts_with(PNG_SIGNATURE) {
+        decode_png(&bytes)
+    } else if bytes.starts_with(b"BM") {
+        decode_bmp(&bytes)
+    } else {
+        Err(invalid(tr!("{} is neither a PNG nor a BMP image", path)))
+    }
+}
+
+/// Write a PNG or a BMP, chosen by the extension. Lossy formats such as
+/// JPEG would destroy the low bits, so they are not offered.
+pub(crate) fn save(path: &str, image: &Image) -> io::Result<()> {
+    let extension = Path::new(path).extension().and_then(|extension| extension.to_str()).map(str::to_ascii_lowercase);
+    let bytes = match extension.as_deref() {
+        Some("png") => encode_png(image)?,
+        Some("bmp") => encode_bmp(image)?,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("{} must end in .png or .bmp; lossy formats lose the hidden bits", path)
+            ));
+        }
+    };
+    fs::write(path, bytes)
+}
+
+fn decode_png(bytes: &[u8]) -> io::Result<Image> {
+    let mut decoder = png::Decoder::new(bytes);
+    // Palettes become RGB and 16-bit samples 8-bit, so every sample is a byte
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| invalid(tr!("Invalid PNG: {}", e)))?;
+    let mut samples = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut samples).map_err(|e| invalid(tr!("Invalid PNG: {}", e)))?;
+    samples.truncate(info.buffer_size());
+
+    let channels = match info.color_type {
+        png::ColorType::Grayscale => 1,
+        png::ColorType::GrayscaleAlpha => 2,
+        png::ColorType::Rgb => 3,
+        png::ColorType::Rgba => 4,
+        png::ColorType::Indexed => return Err(invalid(tr!("Palette PNG was not expanded"))),
+    };
+    Image::new(info.width as usize, info.height as usize, channels, samples)
+}
+
+fn encode_png(image: &Image) -> io::Result<Vec<u8>> {
+    let color = match image.channels {
+        1 => png::ColorType::Grayscale,
+        2 => png::ColorType::GrayscaleAlpha,
+        3 => png::ColorType::Rgb,
+        _ => png::ColorType::Rgba,
+    };
+    let dimension = |value: usize| u32::try_from(value).map_err(|_| invalid(tr!("The image is too large for PNG")));
+
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(BufWriter::new(&mut bytes), dimension(image.width)?, dimension(image.height)?);
+    encoder.set_color(color);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(&image.samples).map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)?;
+    Ok(bytes)
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> io::Result<u16> {
+    bytes
+        .get(offset..offset + 2)
+        .map(|field| u16::from_le_bytes([field[0], field[1]]))
+        .ok_or_else(|| invalid(tr!("Truncated BMP header")))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> io::Result<u32> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|field| u32::from_le_bytes([field[0], field[1], field[2], field[3]]))
+        .ok_or_else(|| invalid(tr!("Truncated BMP header")))
+}
+
+/// Uncompressed 24-bit BGR or 32-bit BGRA rows, bottom-up unless the height
+/// is negative, each padded to a multiple of 4 bytes
+fn decode_bmp(bytes: &[u8]) -> io::Result<Image> {
+    let pixel_offset = read_u32(bytes, 10)? as usize;
+    let width = read_u32(bytes, 18)? as i32;
+    let height = read_u32(bytes, 22)? as i32;
+    let bits = read_u16(bytes, 28)?;
+    let compression = read_u32(bytes, 30)?;
+
+    // BI_BITFIELDS is only accepted with the masks of plain BGRA
+    let standard_masks = compression == 3 && bits == 32 && read_u32(bytes, 54)? == 0x00ff_0000 && read_u32(bytes, 58)? == 0x0000_ff00;
+    if !(bits == 24 || bits == 32) || !(compression == 0 || standard_masks) {
+        return Err(invalid(tr!("Only uncompressed 24- and 32-bit BMPs are supported, not {}-bit compression {}", bits, compression)));
+    }
+    if width <= 0 || height == 0 {
+        return Err(invalid(tr!("Invalid BMP dimensions")));
+    }
+
+    let (width, height) = (width as usize, height.unsigned_abs() as usize);
+    let top_down = (read_u32(bytes, 22)? as i32) < 0;
+    let channels = usize::from(bits / 8);
+    let stride = (width * channels).div_ceil(4) * 4;
+    let pixels = bytes
+        .get(pixel_offset..pixel_offset + stride * height)
+        .ok_or_else(|| invalid(tr!("The BMP pixel data is truncated")))?;
+
+    let mut samples = Vec::with_capacity(width * height * channels);
+    for row in 0..height {
+        let stored_row = if top_down { row } else { height - 1 - row };
+        let line = &pixels[stored_row * stride..stored_row * stride + width * channels];
+        for pixel in line.chunks_exact(channels) {
+            samples.extend_from_slice(&[pixel[2], pixel[1], pixel[0]]);
+            if channels == 4 {
+                samples.push(pixel[3]);
+            }
+        }
+    }
+    Image::new(width, height, channels, samples)
+}
+
+fn encode_bmp(image: &Image) -> io::Result<Vec<u8>> {
+    if image.channels < 3 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("BMP output needs an RGB image; save greyscale images as PNG")));
+    }
+    let channels = image.channels;
+    let stride = (image.width * channels).div_ceil(4) * 4;
+    let pixel_bytes = stride * image.height;
+    let file_size = u32::try_from(54 + pixel_bytes).map_err(|_| invalid(tr!("The image is too large for BMP")))?;
+
+    let mut bytes = Vec::with_capacity(54 + pixel_bytes);
+    bytes.extend_from_slice(b"BM");
+    bytes.extend_from_slice(&file_size.to_le_bytes());
+    bytes.extend_from_slice(&[0; 4]);
+    bytes.extend_from_slice(&54u32.to_le_bytes());
+    // BITMAPINFOHEADER
+    bytes.extend_from_slice(&40u32.to_le_bytes());
+    bytes.extend_from_slice(&(image.width as u32).to_le_bytes());
+    bytes.extend_from_slice(&(image.height as u32).to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&(channels as u16 * 8).to_le_bytes());
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(&(pixel_bytes as u32).to_le_bytes());
+    // 2835 pixels per metre is 72 DPI
+    bytes.extend_from_slice(&2835u32.to_le_bytes());
+    bytes.extend_from_slice(&2835u32.to_le_bytes());
+    bytes.extend_from_slice(&[0; 8]);
+
+    for row in (0..image.height).rev() {
+        let line = &image.samples[row * image.width * channels..(row + 1) * image.width * channels];
+        for pixel in line.chunks_exact(channels) {
+            bytes.extend_from_slice(&[pixel[2], pixel[1], pixel[0]]);
+            if channels == 4 {
+                bytes.push(pixel[3]);
+            }
+        }
+        bytes.resize(bytes.len() + stride - image.width * channels, 0);
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,392 @@
+//! `crypto learn`: a screen per cipher that encrypts as you type and shows
+//! the working (Caesar alphabet, Vigenère key stream, Playfair matrix, rail
+//! fence zigzag, DES rounds), and a screen of exercises checked with the
+//! same implementations
+
+use std::io;
+
+use crypto_core::caesar::Caesar;
+use crypto_core::modes::pad_pkcs7;
+use crypto_core::railfence::RailFence;
+use crypto_core::vigenere::Vigenere;
+use crypto_core::{tr, ClassicalCipher, SymmetricCipher};
+use des::Des;
+use playfair::Playfair;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table, Tabs, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::quiz::{Question, Topic};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Screen {
+    Caesar,
+    Vigenere,
+    Playfair,
+    RailFence,
+    Des,
+    Exercises,
+}
+
+const SCREENS: [Screen; 6] = [Screen::Caesar, Screen::Vigenere, Screen::Playfair, Screen::RailFence, Screen::Des, Screen::Exercises];
+
+impl Screen {
+    fn title(self) -> &'static str {
+        match self {
+            Self::Caesar => "Caesar",
+            Self::Vigenere => "Vigenère",
+            Self::Playfair => "Playfair",
+            Self::RailFence => tr!("Rail fence"),
+            Self::Des => "DES",
+            Self::Exercises => tr!("Exercises"),
+        }
+    }
+
+    /// Key and text each screen starts with
+    fn example(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Caesar => ("3", "Veni, vidi, vici"),
+            Self::Vigenere => ("LEMON", "Attack at dawn"),
+            Self::Playfair => ("MONARCHY", "INSTRUMENTS"),
+            Self::RailFence => ("3", "WE ARE DISCOVERED FLEE AT ONCE"),
+            Self::Des => ("MORTYNOR", "hello DES"),
+            Self::Exercises => ("", ""),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    Key,
+    Text,
+}
+
+struct App {
+    screen: usize,
+    field: Field,
+    /// Key and text typed on each screen; the exercise answer is the text
+    /// of the last one
+    inputs: Vec<[String; 2]>,
+    exercise: Question,
+    /// Whether the last answer was right
+    feedback: Option<bool>,
+    solved: u32,
+    attempted: u32,
+    quit: bool,
+}
+
+impl App {
+    fn new() -> io::Result<Self> {
+        Ok(App {
+            screen: 0,
+            field: Field::Text,
+            inputs: SCREENS.iter().map(|screen| screen.example()).map(|(key, text)| [key.to_string(), text.to_string()]).collect(),
+            exercise: Question::random(&Topic::ALL)?,
+            feedback: None,
+            solved: 0,
+            attempted: 0,
+            quit: false,
+        })
+    }
+
+    fn screen(&self) -> Screen {
+        SCREENS[self.screen]
+    }
+
+    fn key(&self) -> &str {
+        &self.inputs[self.screen][0]
+    }
+
+    fn text(&self) -> &str {
+        &self.inputs[self.screen][1]
+    }
+
+    fn input_mut(&mut self) -> &mut String {
+        let index = if self.field == Field::Key && self.screen() != Screen::Exercises { 0 } else { 1 };
+        &mut self.inputs[self.screen][index]
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => self.quit = true,
+            KeyCode::Char('c') if control => self.quit = true,
+            KeyCode::Tab => self.screen = (self.screen + 1) % SCREENS.len(),
+            KeyCode::BackTab => self.screen = (self.screen + SCREENS.len() - 1) % SCREENS.len(),
+            KeyCode::Up | KeyCode::Down => {
+                self.field = if self.field == Field::Key { Field::Text } else { Field::Key };
+            }
+            KeyCode::Char('n') if control && self.screen() == Screen::Exercises => {
+                self.exercise = Question::random(&Topic::ALL)?;
+                self.input_mut().clear();
+                self.feedback = None;
+            }
+            KeyCode::Enter if self.screen() == Screen::Exercises => {
+                let correct = self.exercise.is_correct(self.text());
+                self.attempted += 1;
+                if correct {
+                    self.solved += 1;
+                    self.exercise = Question::random(&Topic::ALL)?;
+                    self.input_mut().clear();
+                }
+                self.feedback = Some(correct);
+            }
+            KeyCode::Backspace => {
+                self.input_mut().pop();
+            }
+            KeyCode::Char(c) if !control => self.input_mut().push(c),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Run the learning app until Esc or Ctrl-C
+pub(crate) fn run() -> io::Result<()> {
+    let mut app = App::new()?;
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut app);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, app: &mut App) -> io::Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| draw(frame, app))?;
+        if let Event::Key(key) = event::read()? {
+            // Windows also reports releases
+            if key.kind == KeyEventKind::Press {
+                app.handle_key(key)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+const HIGHLIGHT: Style = Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+const RESULT: Style = Style::new().fg(Color::Green).add_modifier(Modifier::BOLD);
+const ERROR: Style = Style::new().fg(Color::Red);
+
+fn draw(frame: &mut Frame, app: &App) {
+    let [tabs, inputs, body, help] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(if app.screen() == Screen::Exercises { 0 } else { 3 }),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let titles: Vec<&str> = SCREENS.iter().map(|screen| screen.title()).collect();
+    frame.render_widget(
+        Tabs::new(titles).select(app.screen).highlight_style(HIGHLIGHT).block(Block::default().borders(Borders::ALL).title(" crypto learn ")),
+        tabs,
+    );
+
+    if app.screen() != Screen::Exercises {
+        let [key, text] = Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)]).areas(inputs);
+        draw_input(frame, key, tr!("Key"), app.key(), app.field == Field::Key);
+        draw_input(frame, text, tr!("Text"), app.text(), app.field == Field::Text);
+    }
+
+    match app.screen() {
+        Screen::Caesar => draw_caesar(frame, body, app),
+        Screen::Vigenere => draw_vigenere(frame, body, app),
+        Screen::Playfair => draw_playfair(frame, body, app),
+        Screen::RailFence => draw_rail_fence(frame, body, app),
+        Screen::Des => draw_des(frame, body, app),
+        Screen::Exercises => draw_exercises(frame, body, app),
+    }
+
+    let keys = match app.screen() {
+        Screen::Exercises => tr!("Tab: next screen  Enter: check  Ctrl-N: skip  Esc: quit"),
+        _ => tr!("Tab: next screen  ↑↓: key or text  Esc: quit"),
+    };
+    frame.render_widget(Paragraph::new(keys).style(Style::new().fg(Color::DarkGray)), help);
+}
+
+fn draw_input(frame: &mut Frame, area: Rect, title: &str, value: &str, focused: bool) {
+    let border = if focused { HIGHLIGHT } else { Style::new() };
+    let block = Block::default().borders(Borders::ALL).border_style(border).title(format!(" {} ", title));
+    let cursor = if focused { "▏" } else { "" };
+    frame.render_widget(Paragraph::new(format!("{}{}", value, cursor)).block(block), area);
+}
+
+fn boxed(title: &str) -> Block<'_> {
+    Block::default().borders(Borders::ALL).title(format!(" {} ", title))
+}
+
+/// The output, or why the key or text was rejected
+fn result_paragraph(result: &Result<String, String>) -> Paragraph<'static> {
+    match result {
+        Ok(text) => Paragraph::new(text.clone()).style(RESULT).wrap(Wrap { trim: false }).block(boxed(tr!("Ciphertext"))),
+        Err(reason) => Paragraph::new(reason.clone()).style(ERROR).wrap(Wrap { trim: false }).block(boxed(tr!("Error"))),
+    }
+}
+
+fn split_body(area: Rect) -> [Rect; 2] {
+    Layout::vertical([Constraint::Min(0), Constraint::Length(5)]).areas(area)
+}
+
+fn draw_caesar(frame: &mut Frame, area: Rect, app: &App) {
+    let [working, output] = split_body(area);
+    let cipher = Caesar::new(app.key());
+    let result = cipher.as_ref().map_err(ToString::to_string).and_then(|cipher| cipher.encrypt(app.text()).map_err(|e| e.to_string()));
+
+    let mut lines = Vec::new();
+    if let Ok(cipher) = &cipher {
+        let plain: String = ('A'..='Z').flat_map(|c| [c, ' ']).collect();
+        let shifted: String = ('A'..='Z').map(|c| (b'A' + (c as u8 - b'A' + cipher.shift()) % 26) as char).flat_map(|c| [c, ' ']).collect();
+        lines.push(Line::from(tr!("plain   {}", plain)));
+        lines.push(Line::from(Span::styled(tr!("cipher  {}", shifted), RESULT)));
+        lines.push(Line::from(""));
+        lines.push(Line::from(tr!("Each letter moves {} places along the alphabet, wrapping from Z to A.", cipher.shift())));
+    }
+    frame.render_widget(Paragraph::new(lines).block(boxed(tr!("Shifted alphabet"))), working);
+    frame.render_widget(result_paragraph(&result), output);
+}
+
+fn draw_vigenere(frame: &mut Frame, area: Rect, app: &App) {
+    let [working, output] = split_body(area);
+    let result = Vigenere::new(app.key()).and_then(|cipher| cipher.encrypt(app.text())).map_err(|e| e.to_string());
+
+    let mut lines = Vec::new();
+    if let Ok(ciphertext) = &result {
+        let key: Vec<char> = app.key().chars().filter(char::is_ascii_alphabetic).map(|c| c.to_ascii_uppercase()).collect();
+        let mut used = 0;
+        let stream: String = app
+            .text()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphabetic() && !key.is_empty() {
+                    used += 1;
+                    key[(used - 1) % key.len()]
+                } else {
+                    ' '
+                }
+            })
+            .collect();
+        lines.push(Line::from(tr!("text    {}", app.text())));
+        lines.push(Line::from(Span::styled(tr!("key     {}", stream), HIGHLIGHT)));
+        lines.push(Line::from(Span::styled(tr!("cipher  {}", ciphertext), RESULT)));
+        lines.push(Line::from(""));
+        lines.push(Line::from(tr!("Each letter is shifted by the key letter above it (A = 0, B = 1, ...).")));
+    }
+    frame.render_widget(Paragraph::new(lines).block(boxed(tr!("Key stream"))), working);
+    frame.render_widget(result_paragraph(&result), output);
+}
+
+fn draw_playfair(frame: &mut Frame, area: Rect, app: &App) {
+    let [working, output] = split_body(area);
+    let [matrix_area, digraph_area] = Layout::horizontal([Constraint::Length(24), Constraint::Min(0)]).areas(working);
+    let cipher = Playfair::new(app.key()).map_err(|e| e.to_string());
+    // Playfair takes letters only; spaces typed between words are dropped
+    let letters: String = app.text().chars().filter(|c| c.is_alphabetic()).flat_map(char::to_uppercase).collect();
+    let result = cipher.as_ref().map_err(Clone::clone).and_then(|cipher| cipher.encrypt(&letters).map_err(|e| e.to_string()));
+
+    let (Ok(cipher), Ok(ciphertext)) = (&cipher, &result) else {
+        frame.render_widget(result_paragraph(&result), output);
+        return;
+    };
+    let plain: Vec<char> = letters.replace('J', "I").chars().collect();
+    let encrypted: Vec<char> = ciphertext.chars().collect();
+    let pairs: Vec<(String, String)> = plain
+        .chunks(2)
+        .zip(encrypted.chunks(2))
+        .map(|(plain, cipher)| {
+            let plain: String = if plain.len() == 2 { plain.iter().collect() } else { format!("{}X", plain[0]) };
+            (plain, cipher.iter().collect())
+        })
+        .collect();
+
+    // The last digraph typed is marked in the matrix: its letters in yellow, their encryption in green
+    let (last_plain, last_cipher) = pairs.last().cloned().unwrap_or_default();
+    let rows: Vec<Row> = cipher
+        .matrix()
+        .iter()
+        .map(|row| {
+            Row::new(row.iter().map(|&c| {
+                let style = if last_plain.contains(c) {
+                    HIGHLIGHT
+                } else if last_cipher.contains(c) {
+                    RESULT
+                } else {
+                    Style::new()
+                };
+                Span::styled(c.to_string(), style)
+            }))
+        })
+        .collect();
+    frame.render_widget(Table::new(rows, [Constraint::Length(3); 5]).block(boxed(tr!("Matrix"))), matrix_area);
+
+    let digraphs: Vec<Line> = pairs.iter().map(|(plain, cipher)| Line::from(format!("{} → {}", plain, cipher))).collect();
+    frame.render_widget(Paragraph::new(digraphs).wrap(Wrap { trim: false }).block(boxed(tr!("Digraphs"))), digraph_area);
+    frame.render_widget(result_paragraph(&result), output);
+}
+
+fn draw_rail_fence(frame: &mut Frame, area: Rect, app: &App) {
+    let [working, output] = split_body(area);
+    let cipher = RailFence::new(app.key()).map_err(|e| e.to_string());
+    let result = cipher.as_ref().map_err(Clone::clone).and_then(|cipher| cipher.encrypt(app.text()).map_err(|e| e.to_string()));
+
+    let mut lines = Vec::new();
+    if let Ok(cipher) = &cipher {
+        let chars: Vec<char> = app.text().chars().collect();
+        for rail in 0..cipher.rails() {
+            let row: String = chars
+                .iter()
+                .enumerate()
+                .map(|(position, &c)| if cipher.rail(position) == rail { if c == ' ' { '_' } else { c } } else { '·' })
+                .collect();
+            lines.push(Line::from(tr!("rail {}  {}", rail + 1, row)));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(tr!("The ciphertext reads the rails from top to bottom (spaces shown as _).")));
+    }
+    frame.render_widget(Paragraph::new(lines).block(boxed(tr!("Zigzag"))), working);
+    frame.render_widget(result_paragraph(&result), output);
+}
+
+fn draw_des(frame: &mut Frame, area: Rect, app: &App) {
+    let [working, output] = split_body(area);
+    let cipher = Des::new(app.key().as_bytes()).map_err(|e| e.to_string());
+    let result = cipher.as_ref().map_err(Clone::clone).map(|cipher| crypto_core::codec::encode_hex(&cipher.encrypt(app.text().as_bytes())));
+
+    if let Ok(cipher) = &cipher {
+        // The first block, padded as ECB pads the whole message
+        let block = u64::from_be_bytes(pad_pkcs7(app.text().as_bytes(), 8)[..8].try_into().expect("8 bytes"));
+        let trace = cipher.trace_encryption(block);
+        let mut rows = vec![Row::new(vec![
+            String::from("IP"),
+            String::new(),
+            format!("{:08X}", trace.permuted >> 32),
+            format!("{:08X}", trace.permuted & 0xFFFF_FFFF),
+        ])];
+        rows.extend(trace.rounds.iter().zip(cipher.subkeys()).enumerate().map(|(round, (&(left, right), subkey))| {
+            Row::new(vec![(round + 1).to_string(), format!("{:012X}", subkey), format!("{:08X}", left), format!("{:08X}", right)])
+        }));
+        rows.push(Row::new(vec![String::from("FP"), String::new(), format!("{:016X}", trace.output), String::new()]).style(RESULT));
+
+        let title = tr!("First block {}: L(i) = R(i-1), R(i) = L(i-1) ⊕ f(R(i-1), K(i))", format!("{:016X}", block));
+        let widths = [Constraint::Length(6), Constraint::Length(14), Constraint::Length(18), Constraint::Length(10)];
+        let table = Table::new(rows, widths).header(Row::new(vec![tr!("Round"), "K", "L", "R"]).style(HIGHLIGHT)).block(boxed(&title));
+        frame.render_widget(table, working);
+    }
+    frame.render_widget(result_paragraph(&result), output);
+}
+
+fn draw_exercises(frame: &mut Frame, area: Rect, app: &App) {
+    let [question, answer, feedback] = Layout::vertical([Constraint::Length(5), Constraint::Length(3), Constraint::Min(0)]).areas(area);
+    let score = tr!("Question (solved {} of {} attempts)", app.solved, app.attempted);
+    frame.render_widget(Paragraph::new(app.exercise.prompt.clone()).wrap(Wrap { trim: false }).block(boxed(&score)), question);
+    draw_input(frame, answer, tr!("Answer"), app.text(), true);
+
+    let line = match &app.feedback {
+        Some(true) => Line::from(Span::styled(tr!("Correct! Here is the next one."), RESULT)),
+        Some(false) => Line::from(Span::styled(tr!("Not quite; try again, or Ctrl-N to skip."), ERROR)),
+        None => Line::from(tr!("Type your answer and press Enter.")),
+    };
+    frame.render_widget(Paragraph::new(line), feedback);
+}
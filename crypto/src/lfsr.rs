@@ -0,0 +1,119 @@
+//! `crypto lfsr`: linear feedback shift registers, A5/1 and the Geffe generator
+
+use std::io;
+
+use crypto_core::codec::{decode_hex, encode_hex};
+use crypto_core::lfsr::{self, Geffe, Lfsr, A51, GEFFE_REGISTERS};
+use crypto_core::{rng, tr};
+
+use crate::output::CommandOutput;
+use crate::{cipher_error, take_flag_values, text_argument, usage_error};
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto lfsr (keystream | period) --length <n> --taps <a,b,...> [--state <hex>] [--bits <n>] | \
+                         crypto lfsr complexity [<bits>] | crypto lfsr a51 --key <hex> [--frame <n>] | \
+                         crypto lfsr correlation [--bits <n>]";
+    let (lengths, rest) = take_flag_values(args, "--length")?;
+    let (taps, rest) = take_flag_values(&rest, "--taps")?;
+    let (states, rest) = take_flag_values(&rest, "--state")?;
+    let (bit_counts, rest) = take_flag_values(&rest, "--bits")?;
+    let (keys, rest) = take_flag_values(&rest, "--key")?;
+    let (frames, rest) = take_flag_values(&rest, "--frame")?;
+    let Some((operation, rest)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+    let invalid = |what: &str, value: &str| io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid {} {}", what, value));
+    let number = |values: &[String], what: &str, default: u64| match values.last() {
+        Some(value) => value.parse::<u64>().map_err(|_| invalid(what, value)),
+        None => Ok(default),
+    };
+    let bits_text = |bits: &[u8]| bits.iter().map(|bit| char::from(b'0' + bit)).collect::<String>();
+
+    match operation.as_str() {
+        "keystream" | "period" if rest.is_empty() => {
+            let (Some(length), Some(tap_list)) = (lengths.last(), taps.last()) else {
+                return Err(usage_error(USAGE));
+            };
+            let length = length.parse::<u32>().map_err(|_| invalid(tr!("length"), length))?;
+            let tap_list = tap_list
+                .split(',')
+                .map(|tap| tap.trim().parse::<u32>().map_err(|_| invalid(tr!("tap"), tap)))
+                .collect::<io::Result<Vec<_>>>()?;
+            let state = match states.last() {
+                Some(state) => u64::from_str_radix(state, 16).map_err(|_| invalid(tr!("state"), state))?,
+                None => 1,
+            };
+            let lfsr = Lfsr::new(length, &tap_list, state).map_err(cipher_error)?;
+
+            if operation == "keystream" {
+                let bits: Vec<u8> = lfsr.take(number(&bit_counts, tr!("bit count"), 64)? as usize).collect();
+                out.line(bits_text(&bits));
+                out.field("keystream", bits_text(&bits));
+            } else {
+                let period = lfsr.period();
+                let maximal = period == Some((1 << length) - 1);
+                match period {
+                    Some(period) if maximal => out.line(tr!("Period {} (maximal, 2^{} - 1 = {})", period, length, (1u64 << length) - 1)),
+                    Some(period) => out.line(tr!("Period {} (not maximal, 2^{} - 1 = {})", period, length, (1u64 << length) - 1)),
+                    None => out.line(tr!("The starting state never comes back (or the register is too long to check)")),
+                }
+                out.field("period", period);
+                out.field("maximal", maximal);
+            }
+        }
+        "complexity" => {
+            let text = text_argument(rest, USAGE)?;
+            let bits = text
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .map(|c| c.to_digit(2).map(|bit| bit as u8).ok_or_else(|| invalid(tr!("bit"), &c.to_string())))
+                .collect::<io::Result<Vec<_>>>()?;
+            let (complexity, connection) = lfsr::berlekamp_massey(&bits);
+            out.line(tr!("Linear complexity {}: the shortest LFSR producing these {} bits has {} stages", complexity, bits.len(), complexity));
+            out.line(tr!("Connection polynomial coefficients c0..c{}: {}", complexity, bits_text(&connection)));
+            out.field("complexity", complexity);
+            out.field("connection", bits_text(&connection));
+        }
+        "a51" if rest.is_empty() => {
+            let key = keys.last().ok_or_else(|| usage_error(USAGE))?;
+            let key_bytes: [u8; 8] = decode_hex(key).ok().and_then(|bytes| bytes.try_into().ok()).ok_or_else(|| invalid(tr!("64-bit hex key"), key))?;
+            let frame = number(&frames, tr!("frame number"), 0)?;
+            if frame >= 1 << 22 {
+                return Err(invalid(tr!("22-bit frame number"), &frame.to_string()));
+            }
+            let (downlink, uplink) = A51::new(u64::from_le_bytes(key_bytes), frame as u32).frame_keystreams();
+
+            out.line(tr!("Frame {} downlink: {}", frame, encode_hex(&downlink)));
+            out.line(tr!("Frame {} uplink:   {}", frame, encode_hex(&uplink)));
+            out.field("frame", frame);
+            out.field("downlink", encode_hex(&downlink));
+            out.field("uplink", encode_hex(&uplink));
+        }
+        "correlation" if rest.is_empty() => {
+            // A random 23-bit Geffe key, attacked one register at a time
+            let random = rng::key(24)?;
+            let states = [0, 1, 2].map(|index| {
+                let (length, _) = GEFFE_REGISTERS[index];
+                let value = random[index * 8..index * 8 + 8].iter().fold(0u64, |value, &byte| value << 8 | byte as u64);
+                value % ((1 << length) - 1) + 1
+            });
+            let geffe = Geffe::new(states).map_err(cipher_error)?;
+            let keystream: Vec<u8> = geffe.take(number(&bit_counts, tr!("bit count"), 200)? as usize).collect();
+
+            let attack = lfsr::correlation_attack(&keystream, GEFFE_REGISTERS)
+                .ok_or_else(|| io::Error::other(tr!("No key reproduces the keystream; try more --bits")))?;
+            out.line(tr!("Geffe generator with registers of {}, {} and {} stages, {} keystream bits", GEFFE_REGISTERS[0].0, GEFFE_REGISTERS[1].0, GEFFE_REGISTERS[2].0, keystream.len()));
+            out.line(tr!("Register 2 state {}: agrees with {}% of the output", format!("{:x}", attack.states[1]), format!("{:.0}", attack.agreements[0] * 100.0)));
+            out.line(tr!("Register 3 state {}: agrees with {}% of the output", format!("{:x}", attack.states[2]), format!("{:.0}", attack.agreements[1] * 100.0)));
+            out.line(tr!("Register 1 state {}: reproduces the keystream exactly", format!("{:x}", attack.states[0])));
+            out.line(tr!("{} states tried instead of {} for the whole key", attack.trials, 1u64 << 23));
+            out.field("states", attack.states.iter().map(|state| format!("{:x}", state)).collect::<Vec<_>>());
+            out.field("recovered", attack.states == states);
+            out.field("agreements", attack.agreements.to_vec());
+            out.field("trials", attack.trials);
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,570 @@
+use std::env;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::process::{self, Command};
+
+mod aes;
+mod analyze;
+mod caesar;
+mod chacha20;
+mod challenge;
+mod ciphers;
+mod codec;
+mod columnar;
+mod config;
+mod des;
+mod ecc;
+mod elgamal;
+mod exchange;
+mod hash;
+mod hill;
+mod hybrid;
+mod image;
+mod learn;
+mod lfsr;
+mod mac;
+mod math;
+mod merkle;
+mod messages;
+mod otp;
+mod output;
+mod passwd;
+mod pipeline;
+mod playfair;
+mod primes;
+mod quiz;
+mod railfence;
+mod randtest;
+mod rc4;
+mod report;
+mod rsa;
+mod serve;
+mod shamir;
+mod stego;
+mod vault;
+mod vigenere;
+
+use crypto_core::codec::{encode_hex, Encoding, Strictness};
+use crypto_core::config::{config_path, Config};
+use crypto_core::i18n::{self, Lang};
+use crypto_core::logging::{self, LogOptions};
+use crypto_core::modes::{self, Mode};
+use crypto_core::{rng, tr, BlockCipher, CipherError, ClassicalCipher, SymmetricCipher};
+use num_bigint::BigUint;
+use serde_json::{json, Value};
+
+use config::Settings;
+use output::{CommandOutput, OutputFormat};
+
+const SYNOPSIS: &str = "crypto [--output text|json] [--lang en|ro] [-v | -vv] [--log-format text|json] <command> ...";
+
+const COMMANDS: &str = "\
+  caesar (encrypt | decrypt) --key <shift> [<text>]
+  caesar (brute | crack) [--romanian] [<text>]
+  vigenere (encrypt | decrypt) --key <word> [<text>]
+  vigenere analyze [--max-key-length <n>] [<text>]
+  vigenere crack [--max-key-length <n>] [--romanian] [<text>]
+  hill (encrypt | decrypt) --key <matrix> [--romanian] [<text>]
+  hill attack --plaintext <text> [--size 2|3] [--romanian] [<ciphertext>]
+  railfence (encrypt | decrypt) --key <rails>[,<offset>] [<text>]
+  railfence brute [--max-rails <n>] [--romanian] [<text>]
+  columnar (encrypt | decrypt) --key <keyword>[ <keyword>] [--playfair <key>] [<text>]
+  columnar crack [--columns <n>] [--romanian] [<text>]
+  playfair (encrypt | decrypt) --key <key> [<text>]
+  playfair matrix --key <key>
+  playfair crack [--romanian] [--restarts <n>] [<text>]
+  des (encrypt | decrypt) --key <key> [--hex] [--mode ecb|cbc|ctr] [--iv <hex>] [--mac-key <key>] [<text>]
+  aes (encrypt | decrypt) --key <key> [--hex] [--mode ecb|cbc|ctr] [--iv <hex>] [--mac-key <key>] [<text>]
+  rc4 (encrypt | decrypt) --key <key> [--hex] [<text>]
+  rc4 keystream --key <key> [--hex] [--bytes <n>]
+  rc4 bias [--trials <n>]
+  chacha20 (encrypt | decrypt) --key <key> [--hex] [--aead [--aad <text>]] [--nonce <hex>] [<text>]
+  lfsr (keystream | period) --length <n> --taps <a,b,...> [--state <hex>] [--bits <n>]
+  lfsr complexity [<bits>]
+  lfsr a51 --key <hex> [--frame <n>]
+  lfsr correlation [--bits <n>]
+  pipeline (encrypt | decrypt) [--romanian] \"<stage> | <stage> ...\" [<text>]
+  challenge gen --cipher <name> [--difficulty easy|medium|hard] [--romanian] --students <file> --passphrase <p> [--out <dir>]
+  challenge open --passphrase <p> <answers>
+  challenge check --passphrase <p> --student <name> <answers> [<plaintext>]
+  report --cipher <name> --key <key> [--hex] [--romanian] [--out <file>] [--text <text> | <text>]
+  quiz --student <name> [--questions <n>] [--topic <name>]... [--results <file>]
+  learn
+  serve [--api-key <key>] [<address>]
+  analyze [--model english|uniform|<sample file>] [--romanian] [--top <n>] [--cipher <name> --key <key>] [<text>]
+  ciphers
+  <registered cipher> (encrypt | decrypt) --key <key> [--hex] [<text>]
+  encode (hex | base32 | base64) [--hex | --file <path>] [<text>]
+  decode (hex | base32 | base64) [--lenient] [--hex | --out <file>] [<text>]
+  randtest [--file <path> | --os <bytes> | [--hex] <bits or hex>] [--block <m>]
+  mac (tag | verify) --key <key> [--hex] [--hash sha256] [--tag <hex>] [<text>]
+  merkle (root | prove --index <i> | consistency --old-size <m>) [--messages] <item>...
+  merkle verify --index <i> --size <n> --root <hex> [--path <hex,...>] [--messages] <item>
+  passwd hash [--iterations <n> | --memory-hard [--cost <log2 n>]] [<password>]
+  passwd verify --hash <stored> [<password>]
+  passwd bench [--iterations <n>,...]
+  shamir split --threshold <k> --shares <n> [--hex] [<secret>]
+  shamir combine [--hex] <share>...
+  stego hide [--cipher des|aes --key <key> [--hex]] [--file <payload>] <cover> <output> [<text>]
+  stego extract [--cipher des|aes --key <key> [--hex]] [--out <file>] <image>
+  stego (capacity | detect) <image>
+  math gcd <a> <b>
+  math inverse <a> <modulus>
+  math pow <base> <exponent> <modulus>
+  math crt <residue>:<modulus>...
+  primes gen [--safe] <bits>
+  primes test <number>
+  rsa keygen [--bits <n>] [--exponent <e>] <key.json>
+  rsa (encrypt | decrypt) --key <key.json> [--raw] [<text>]
+  rsa sign --key <key.json> [<text>]
+  rsa verify --key <key.json> --signature <hex> [<text>]
+  rsa attack (small-factor | low-exponent) [<text>]
+  dh params [--bits <n>]
+  dh listen [--bits <n>] <address>
+  dh connect [--message <text>] <address>
+  dh mitm [--bits <n>]
+  ecc (points | multiples) [--curve <name>]
+  ecc (add | double) [--curve <name>] <x,y> [<x,y>]
+  ecc multiply [--curve <name>] <k> [<x,y>]
+  ecc keygen [--curve <name>] <key.json>
+  ecc sign --key <key.json> [<text>]
+  ecc verify --key <key.json> --signature <r:s> [<text>]
+  elgamal keygen [--bits <n>] <key.json>
+  elgamal (encrypt | decrypt) --key <key.json> [<text>]
+  elgamal sign --key <key.json> [<text>]
+  elgamal verify --key <key.json> --signature <r:s> [<text>]
+  elgamal attack nonce-reuse [--bits <n>]
+  otp generate --bytes <n> <pad>
+  otp encrypt --pad <pad> [<text>]
+  otp decrypt --pad <pad> --offset <n> [<hex>]
+  otp status <pad>
+  otp reuse [--crib <word>] <message> <message>
+  des key ([--hex] <key> | --random)
+  vault add <name> (--key <key> [--hex] | --generate <bytes> | --pad <file> | --ref <key file>) [--memory <KiB>]
+  vault list
+  vault use <name> <command> [<arguments>...]
+  hybrid encrypt --for <user> [--cipher des|3des|aes] [--out <file>] <file>
+  hybrid decrypt [--key <private key>] [--out <file>] <container>
+  pki <pki arguments>...
+  config";
+
+const NOTES: &str = "\
+Text is read from standard input when it is not given as an argument.
+Hill keys are 4 or 9 numbers (\"3 3 2 5\") or letters, read row by row.
+Two columnar keywords give double transposition; --playfair applies Playfair first.
+Cracking ranks candidates by quadgram statistics of English, or of Romanian
+with --romanian; ciphers.alphabet = \"romanian\" makes that the default and
+--english overrides it.
+Numbers are decimal, or hex with a 0x prefix.
+OTP pad bytes are zeroed once used and never handed out twice.
+The vault (vault.path, --vault) holds keys, pads and key file references
+under the passphrase from --passphrase or CRYPTO_VAULT_PASSPHRASE; vault use
+gives the command the entry as --key (or as the pad for otp).
+DES, AES, RC4 and ChaCha20 ciphertext is hex; --hex takes the key as hex too.
+Hex arguments may contain whitespace, : separators or a 0x prefix; decode
+accepts only canonical text unless --lenient is given.
+With --mode cbc or ctr the IV (random unless --iv is given) starts the ciphertext.
+Stego images are PNG or uncompressed BMP; the output format follows its extension.
+Challenge students are read one per line; each gets a sheet in the output
+directory and the answers are sealed under the passphrase in answers.sealed.
+randtest reads 0s and 1s as bits and anything else as hex; tests fail below p = 0.01.
+serve answers JSON-RPC 2.0 on POST /rpc (methods such as caesar.encrypt or
+rsa.sign, listed at GET /methods) with the API key from --api-key or
+CRYPTO_API_KEY, sent as Authorization: Bearer <key>. Key file arguments may
+also be given as the key file's JSON.
+Settings layer defaults, crypto.toml (or $CRYPTO_CONFIG), environment
+variables and flags; config lists each with the layer it came from.
+Logs go to standard error: warnings by default, progress with -v, detail
+with -vv; RUST_LOG overrides the level. The logging flags pass on to pki.
+Messages are in English or Romanian: --lang, else ui.lang (CRYPTO_LANG),
+else the locale. JSON keeps English fields; only errors are translated.";
+
+/// The help text, in the selected language
+fn help() -> String {
+    format!("{}\n\n{}\n{}\n\n{}", tr!("Usage: {}", SYNOPSIS), tr!("Commands:"), COMMANDS, tr!(NOTES))
+}
+
+/// Remove every `flag <value>` pair from `args`, returning the values and the remaining arguments
+fn take_flag_values(args: &[String], flag: &str) -> io::Result<(Vec<String>, Vec<String>)> {
+    let mut values = Vec::new();
+    let mut rest = Vec::new();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        if arg == flag {
+            match iter.next() {
+                Some(value) => values.push(value.clone()),
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        tr!("Missing value for {}", flag)
+                    ));
+                }
+            }
+        } else {
+            rest.push(arg.clone());
+        }
+    }
+
+    Ok((values, rest))
+}
+
+/// Remove `--romanian` and `--english`, returning whether Romanian applies:
+/// the flag given last, else the `ciphers.alphabet` setting
+fn take_romanian(args: &[String]) -> (bool, Vec<String>) {
+    let mut romanian = config::settings().romanian;
+    let mut rest = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--romanian" => romanian = true,
+            "--english" => romanian = false,
+            _ => rest.push(arg.clone()),
+        }
+    }
+    (romanian, rest)
+}
+
+/// A key file path, relative ones taken from `keys.dir`
+fn key_path(path: &str) -> PathBuf {
+    config::settings().key_dir.join(path)
+}
+
+fn usage_error(usage: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, tr!("Usage: {}", usage))
+}
+
+/// The text argument, or standard input without its trailing newline
+fn text_argument(rest: &[String], usage: &str) -> io::Result<String> {
+    match rest {
+        [text] => Ok(text.clone()),
+        [] => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)?;
+            Ok(text.trim_end_matches(['\r', '\n']).to_string())
+        }
+        _ => Err(usage_error(usage)),
+    }
+}
+
+fn cipher_error(error: CipherError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, error.to_string())
+}
+
+/// Encrypt or decrypt text with any classical cipher
+fn run_classical(cipher: &dyn ClassicalCipher, operation: &str, rest: &[String], usage: &str, out: &mut CommandOutput) -> io::Result<()> {
+    let text = text_argument(rest, usage)?;
+    let result = match operation {
+        "encrypt" => cipher.encrypt(&text),
+        "decrypt" => cipher.decrypt(&text),
+        _ => return Err(usage_error(usage)),
+    }
+    .map_err(cipher_error)?;
+
+    out.line(&result);
+    out.field("cipher", cipher.info().name);
+    out.field("operation", operation);
+    out.field("result", result);
+    Ok(())
+}
+
+/// Hex from the command line, where pasted whitespace, `:` separators and
+/// a `0x` prefix are fine; `what` names the argument in the error
+fn hex_input(text: &str, what: &str) -> io::Result<Vec<u8>> {
+    Encoding::Hex
+        .decode(text, Strictness::Lenient)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, tr!("The {} must be hex: {}", what, e.reason)))
+}
+
+/// A key given as text, or as hex with `--hex`
+fn key_bytes(key: &str, hex: bool) -> io::Result<Vec<u8>> {
+    if hex {
+        hex_input(key, tr!("key"))
+    } else {
+        Ok(key.as_bytes().to_vec())
+    }
+}
+
+/// Encrypt text to hex, or decrypt hex to text, with any symmetric cipher
+fn run_symmetric(cipher: &dyn SymmetricCipher, operation: &str, rest: &[String], usage: &str, out: &mut CommandOutput) -> io::Result<()> {
+    let text = text_argument(rest, usage)?;
+    let result = match operation {
+        "encrypt" => encode_hex(&cipher.encrypt(text.as_bytes())),
+        "decrypt" => {
+            let ciphertext = hex_input(&text, tr!("ciphertext"))?;
+            String::from_utf8_lossy(&cipher.decrypt(&ciphertext).map_err(cipher_error)?).into_owned()
+        }
+        _ => return Err(usage_error(usage)),
+    };
+
+    out.line(&result);
+    out.field("cipher", cipher.info().name);
+    out.field("operation", operation);
+    out.field("result", result);
+    Ok(())
+}
+
+/// The mode, IV and MAC key a block cipher command runs with
+struct ModeOptions<'a> {
+    mode: Mode,
+    iv: Option<&'a String>,
+    mac_key: Option<&'a [u8]>,
+}
+
+/// Encrypt text to hex, or decrypt hex to text, with a block cipher in the
+/// given mode. The IV is sent as the first block of the ciphertext; with a
+/// MAC key an HMAC-SHA256 tag follows it (encrypt-then-MAC).
+fn run_block_mode(cipher: &dyn BlockCipher, options: ModeOptions, operation: &str, rest: &[String], usage: &str, out: &mut CommandOutput) -> io::Result<()> {
+    let ModeOptions { mode, iv, mac_key } = options;
+    let text = text_argument(rest, usage)?;
+    let result = match operation {
+        "encrypt" => {
+            let iv = match (mode.needs_iv(), iv) {
+                (false, _) => Vec::new(),
+                (true, Some(iv)) => hex_input(iv, tr!("IV"))?,
+                (true, None) => rng::iv(cipher.block_size())?,
+            };
+            if let Some(mac_key) = mac_key {
+                let sealed = modes::encrypt_then_mac(cipher, mode, &iv, mac_key, text.as_bytes()).map_err(cipher_error)?;
+                encode_hex(&sealed)
+            } else {
+                let ciphertext = modes::encrypt(cipher, mode, &iv, text.as_bytes()).map_err(cipher_error)?;
+                encode_hex(&[iv, ciphertext].concat())
+            }
+        }
+        "decrypt" => {
+            let data = hex_input(&text, tr!("ciphertext"))?;
+            let plaintext = match mac_key {
+                Some(mac_key) => modes::decrypt_verified(cipher, mode, mac_key, &data),
+                None => {
+                    let iv_length = if mode.needs_iv() { cipher.block_size().min(data.len()) } else { 0 };
+                    let (iv, ciphertext) = data.split_at(iv_length);
+                    modes::decrypt(cipher, mode, iv, ciphertext)
+                }
+            };
+            String::from_utf8_lossy(&plaintext.map_err(cipher_error)?).into_owned()
+        }
+        _ => return Err(usage_error(usage)),
+    };
+
+    out.line(&result);
+    out.field("mode", mode.name());
+    out.field("operation", operation);
+    out.field("authenticated", mac_key.is_some());
+    out.field("result", result);
+    Ok(())
+}
+
+/// The `--mode` and `--iv` flags of the block cipher commands
+fn take_mode_flags(args: &[String]) -> io::Result<(Option<Mode>, Option<String>, Vec<String>)> {
+    let (modes, rest) = take_flag_values(args, "--mode")?;
+    let (ivs, rest) = take_flag_values(&rest, "--iv")?;
+    let mode = match modes.last() {
+        Some(name) => Some(Mode::parse(name).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, tr!("Unknown mode {}", name)))?),
+        None => None,
+    };
+    Ok((mode, ivs.last().cloned(), rest))
+}
+
+/// Run the `pki` tool with the same output format, passing its exit status through
+fn run_pki(args: &[String], settings: &Settings) -> io::Result<()> {
+    let status = Command::new(&settings.pki_bin)
+        .args(["--output", settings.format.name()])
+        .args(settings.log.to_args())
+        .args(["--lang", settings.lang.code()])
+        .args(args)
+        .status()
+        .map_err(|e| io::Error::new(e.kind(), tr!("Failed to run {}: {}", settings.pki_bin.display(), e)))?;
+
+    if !status.success() {
+        process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+/// A decimal number, or hex with a 0x prefix
+fn big_number(text: &str) -> io::Result<BigUint> {
+    let parsed = match text.strip_prefix("0x") {
+        Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+        None => BigUint::parse_bytes(text.as_bytes(), 10),
+    };
+    parsed.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid number {}", text)))
+}
+
+/// The JSON of a key file, and what errors call it. `--key` may also be
+/// the JSON itself, which is how `crypto serve` is sent keys.
+fn read_key_json(source: &str) -> io::Result<(Value, String)> {
+    let (text, name) = if source.trim_start().starts_with('{') {
+        (source.to_string(), String::from("The key"))
+    } else {
+        (std::fs::read_to_string(key_path(source))?, source.to_string())
+    };
+    let json = serde_json::from_str(&text)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, tr!("{} has no valid JSON", name)))?;
+    Ok((json, name))
+}
+
+/// ElGamal ciphertexts and signatures are pairs of numbers, written `a:b` in hex
+fn hex_pair(text: &str) -> io::Result<(BigUint, BigUint)> {
+    let number = |hex: &str| BigUint::parse_bytes(hex.trim().as_bytes(), 16);
+    text.split_once(':')
+        .and_then(|(a, b)| Some((number(a)?, number(b)?)))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, tr!("Expected two hex numbers as <a>:<b>")))
+}
+
+fn main() {
+    // The message alone, not the `Custom { kind, error }` of an `io::Error`
+    if let Err(e) = run() {
+        eprintln!("{}", tr!("Error: {}", e));
+        process::exit(1);
+    }
+}
+
+/// Parse the global flags and run the command they leave
+fn run() -> io::Result<()> {
+    let mut config = Config::load()?;
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // `--output`, `--lang` and the logging flags may be given anywhere,
+    // except that arguments after `pki` are passed on untouched. They are
+    // the last configuration layer.
+    let index = command_index(&args);
+    let (global, passed_on) = match args.get(index) {
+        Some(command) if command == "pki" => args.split_at(index),
+        _ => (args.as_slice(), &[][..]),
+    };
+    let (formats, global) = take_flag_values(global, "--output")?;
+    let (langs, global) = take_flag_values(&global, "--lang")?;
+    let (log_options, mut args) = LogOptions::take_from(&global, LogOptions::from_config(&config)?)?;
+    args.extend_from_slice(passed_on);
+    if let Some(name) = formats.last() {
+        config.set("output.format", name);
+    }
+    if let Some(code) = langs.last() {
+        config.set("ui.lang", code);
+    }
+    logging::init(&log_options);
+    i18n::init(Lang::from_config(&config)?, &[messages::MESSAGES]);
+    let mut settings = Settings::from_config(&config)?;
+    settings.log = log_options;
+    let settings = settings.install();
+
+    let Some((command, rest)) = args.split_first() else {
+        println!("{}", help());
+        return Ok(());
+    };
+    let _span = tracing::info_span!("crypto", command = command.as_str()).entered();
+    match command.as_str() {
+        "pki" => return run_pki(rest, settings),
+        "learn" if rest.is_empty() => return learn::run(),
+        "serve" => return serve::run(rest, settings),
+        "help" | "--help" => {
+            println!("{}", help());
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let mut out = CommandOutput::new(settings.format, command);
+    let result = match command.as_str() {
+        "config" => run_config(rest, &config, &mut out),
+        _ => run_command(command, rest, &mut out),
+    };
+    match result {
+        Ok(()) => out.finish(),
+        Err(e) if settings.format == OutputFormat::Json => {
+            output::print_json_error(command, &e);
+            process::exit(1);
+        }
+        Err(e) => return Err(e),
+    }
+
+    Ok(())
+}
+
+/// `crypto config`: every setting, its value and the layer it came from
+fn run_config(args: &[String], config: &Config, out: &mut CommandOutput) -> io::Result<()> {
+    if !args.is_empty() {
+        return Err(usage_error("crypto config"));
+    }
+
+    match config_path() {
+        Some(path) => {
+            out.line(tr!("Config file: {}", path.display()));
+            out.field("file", path.to_string_lossy());
+        }
+        None => {
+            out.line(tr!("No config file (create crypto.toml or set CRYPTO_CONFIG)"));
+            out.field("file", Value::Null);
+        }
+    }
+
+    let mut settings = serde_json::Map::new();
+    for (key, value, source) in config.entries() {
+        out.line(format!("  {:<24} {:<18} {}", key, format!("{:?}", value), source));
+        settings.insert(key.to_string(), json!({ "value": value, "source": source.to_string() }));
+    }
+    out.field("settings", Value::Object(settings));
+    Ok(())
+}
+
+/// Position of the command: the first argument that is neither a global
+/// flag nor a global flag's value
+fn command_index(args: &[String]) -> usize {
+    let mut index = 0;
+    while let Some(arg) = args.get(index) {
+        match arg.as_str() {
+            "--output" | "--lang" | "--log-format" => index += 2,
+            "--verbose" => index += 1,
+            flag if flag.len() > 1 && flag.starts_with('-') && flag[1..].bytes().all(|c| c == b'v') => index += 1,
+            _ => break,
+        }
+    }
+    index.min(args.len())
+}
+
+/// Run one of the commands that report through [`CommandOutput`]
+fn run_command(command: &str, rest: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    match command {
+        "caesar" => caesar::run(rest, out),
+        "vigenere" => vigenere::run(rest, out),
+        "hill" => hill::run(rest, out),
+        "railfence" => railfence::run(rest, out),
+        "columnar" => columnar::run(rest, out),
+        "playfair" => playfair::run(rest, out),
+        "des" => des::run(rest, out),
+        "aes" => aes::run(rest, out),
+        "rc4" => rc4::run(rest, out),
+        "chacha20" => chacha20::run(rest, out),
+        "lfsr" => lfsr::run(rest, out),
+        "math" => math::run(rest, out),
+        "primes" => primes::run(rest, out),
+        "rsa" => rsa::run(rest, out),
+        "dh" => exchange::run(rest, out),
+        "ecc" => ecc::run(rest, out),
+        "elgamal" => elgamal::run(rest, out),
+        "hash" => hash::run(rest, out),
+        "analyze" => analyze::run(rest, out),
+        "challenge" => challenge::run(rest, out),
+        "pipeline" => pipeline::run(rest, out),
+        "report" => report::run(rest, out),
+        "quiz" => quiz::run(rest, out),
+        "encode" | "decode" => codec::run(command, rest, out),
+        "randtest" => randtest::run(rest, out),
+        "mac" => mac::run(rest, out),
+        "merkle" => merkle::run(rest, out),
+        "otp" => otp::run(rest, out),
+        "vault" => vault::run(rest, out),
+        "hybrid" => hybrid::run(rest, out),
+        "passwd" => passwd::run(rest, out),
+        "shamir" => shamir::run(rest, out),
+        "stego" => stego::run(rest, out),
+        "ciphers" => ciphers::run(rest, out),
+        other => match ciphers::registry().get(other) {
+            Some(registration) => ciphers::run_registered(registration, rest, out),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("Unknown command: {}", other)
+            )),
+        },
+    }
+}
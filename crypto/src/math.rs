@@ -0,0 +1,62 @@
+//! `crypto math`: the modular arithmetic behind the public-key commands
+
+use std::io;
+
+use crypto_core::{modular, tr};
+use num_bigint::BigUint;
+
+use crate::output::CommandOutput;
+use crate::{big_number, usage_error};
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto math gcd <a> <b> | crypto math inverse <a> <modulus> | \
+                         crypto math pow <base> <exponent> <modulus> | crypto math crt <residue>:<modulus>...";
+    let Some((operation, rest)) = args.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+
+    match (operation.as_str(), rest) {
+        ("gcd", [a, b]) => {
+            let (a, b) = (big_number(a)?, big_number(b)?);
+            let (g, x, y) = modular::extended_gcd(&a, &b);
+            out.line(format!("gcd({}, {}) = {} = {}·({}) + {}·({})", a, b, g, a, x, b, y));
+            out.field("gcd", g.to_string());
+            out.field("x", x.to_string());
+            out.field("y", y.to_string());
+        }
+        ("inverse", [a, modulus]) => {
+            let (a, modulus) = (big_number(a)?, big_number(modulus)?);
+            let inverse = modular::mod_inverse(&a, &modulus).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, tr!("{} has no inverse modulo {}", a, modulus))
+            })?;
+            out.line(format!("{}⁻¹ ≡ {} (mod {})", a, inverse, modulus));
+            out.field("inverse", inverse.to_string());
+        }
+        ("pow", [base, exponent, modulus]) => {
+            let (base, exponent, modulus) = (big_number(base)?, big_number(exponent)?, big_number(modulus)?);
+            if modulus == BigUint::from(0u32) {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("The modulus must be positive")));
+            }
+            let result = modular::mod_pow(&base, &exponent, &modulus);
+            out.line(format!("{}^{} ≡ {} (mod {})", base, exponent, result, modulus));
+            out.field("result", result.to_string());
+        }
+        ("crt", congruences) if !congruences.is_empty() => {
+            let congruences = congruences
+                .iter()
+                .map(|congruence| {
+                    let (residue, modulus) = congruence.split_once(':').ok_or_else(|| usage_error(USAGE))?;
+                    Ok((big_number(residue)?, big_number(modulus)?))
+                })
+                .collect::<io::Result<Vec<_>>>()?;
+            let (x, modulus) = modular::crt(&congruences)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, tr!("The congruences have no common solution")))?;
+            out.line(format!("x ≡ {} (mod {})", x, modulus));
+            out.field("result", x.to_string());
+            out.field("modulus", modulus.to_string());
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+//! `crypto merkle`: Merkle roots, inclusion proofs and consistency proofs
+
+use std::io;
+
+use crypto_core::codec::{decode_hex, encode_hex};
+use crypto_core::{merkle, tr};
+
+use crate::output::CommandOutput;
+use crate::{take_flag_values, usage_error};
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto merkle root [--messages] <item>... | \
+                         crypto merkle prove --index <i> [--messages] <item>... | \
+                         crypto merkle verify --index <i> --size <n> --root <hex> [--path <hex,...>] [--messages] <item> | \
+                         crypto merkle consistency --old-size <m> [--messages] <item>...";
+    let (indices, rest) = take_flag_values(args, "--index")?;
+    let (sizes, rest) = take_flag_values(&rest, "--size")?;
+    let (roots, rest) = take_flag_values(&rest, "--root")?;
+    let (paths, rest) = take_flag_values(&rest, "--path")?;
+    let (old_sizes, rest) = take_flag_values(&rest, "--old-size")?;
+    let messages = rest.iter().any(|arg| arg == "--messages");
+    let rest: Vec<String> = rest.into_iter().filter(|arg| arg != "--messages").collect();
+    let Some((operation, items)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+    let number = |value: &String| {
+        value.parse::<usize>().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid number {}", value)))
+    };
+    let hash = |text: &str| {
+        decode_hex(text)
+            .ok()
+            .and_then(|bytes| merkle::Hash::try_from(bytes).ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid SHA-256 hash {}", text)))
+    };
+    let hex_list = |hashes: &[merkle::Hash]| hashes.iter().map(|hash| encode_hex(hash)).collect::<Vec<_>>();
+    // Leaves are the files' contents, or the arguments themselves with --messages
+    let leaves = items
+        .iter()
+        .map(|item| Ok(merkle::leaf_hash(&if messages { item.as_bytes().to_vec() } else { std::fs::read(item)? })))
+        .collect::<io::Result<Vec<_>>>()?;
+    if leaves.is_empty() {
+        return Err(usage_error(USAGE));
+    }
+
+    match (operation.as_str(), indices.last(), old_sizes.last()) {
+        ("root", None, None) => {
+            let root = encode_hex(&merkle::root(&leaves));
+            out.line(tr!("Tree size: {}", leaves.len()));
+            out.line(tr!("Root hash: {}", root));
+            out.field("tree_size", leaves.len());
+            out.field("root_hash", root);
+        }
+        ("prove", Some(index), None) => {
+            let index = number(index)?;
+            if index >= leaves.len() {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("Index {} is past the last leaf", index)));
+            }
+            let path = merkle::inclusion_proof(index, &leaves);
+            let root = encode_hex(&merkle::root(&leaves));
+            out.line(tr!("Leaf {} of {} (root {})", index, leaves.len(), root));
+            for (level, sibling) in path.iter().enumerate() {
+                out.line(tr!("  path[{}] {}", level, encode_hex(sibling)));
+            }
+            out.field("leaf_index", index);
+            out.field("tree_size", leaves.len());
+            out.field("leaf_hash", encode_hex(&leaves[index]));
+            out.field("root_hash", root);
+            out.field("path", hex_list(&path));
+        }
+        ("verify", Some(index), None) if leaves.len() == 1 => {
+            let (Some(size), Some(root)) = (sizes.last(), roots.last()) else {
+                return Err(usage_error(USAGE));
+            };
+            let path = match paths.last() {
+                Some(list) => list.split(',').map(|text| hash(text.trim())).collect::<io::Result<Vec<_>>>()?,
+                None => Vec::new(),
+            };
+            let valid = merkle::verify_inclusion(number(index)?, number(size)?, &leaves[0], &path, &hash(root)?);
+            out.line(if valid { tr!("Inclusion proof valid") } else { tr!("Inclusion proof INVALID") });
+            out.field("valid", valid);
+            if !valid {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, tr!("Inclusion proof verification failed")));
+            }
+        }
+        ("consistency", None, Some(old_size)) => {
+            let old_size = number(old_size)?;
+            if old_size == 0 || old_size > leaves.len() {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("The old size must be 1 to {}", leaves.len())));
+            }
+            let (old_root, new_root) = (merkle::root(&leaves[..old_size]), merkle::root(&leaves));
+            let path = merkle::consistency_proof(old_size, &leaves);
+            out.line(tr!("First {} leaves: root {}", old_size, encode_hex(&old_root)));
+            out.line(tr!("All {} leaves: root {}", leaves.len(), encode_hex(&new_root)));
+            for (level, sibling) in path.iter().enumerate() {
+                out.line(tr!("  path[{}] {}", level, encode_hex(sibling)));
+            }
+            out.field("old_size", old_size);
+            out.field("old_root", encode_hex(&old_root));
+            out.field("new_size", leaves.len());
+            out.field("new_root", encode_hex(&new_root));
+            out.field("path", hex_list(&path));
+            out.field("valid", merkle::verify_consistency(old_size, leaves.len(), &old_root, &new_root, &path));
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
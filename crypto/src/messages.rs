@@ -0,0 +1,571 @@
+//! Romanian for everything `crypto` prints; see [`crypto_core::i18n`]
+
+use crypto_core::i18n::Catalog;
+
+pub(crate) const MESSAGES: Catalog = &[
+    // Help and shared errors
+    ("Error: {}", "Eroare: {}"),
+    ("Commands:", "Comenzi:"),
+    (
+        crate::NOTES,
+        "\
+Textul este citit de la intrarea standard când nu este dat ca argument.
+Cheile Hill sunt 4 sau 9 numere (\"3 3 2 5\") sau litere, citite rând cu rând.
+Două cuvinte-cheie columnar dau transpoziție dublă; --playfair aplică întâi Playfair.
+Spargerea ordonează candidații după statistica cvadrigramelor din engleză, sau
+din română cu --romanian; ciphers.alphabet = \"romanian\" face din aceasta
+implicitul, iar --english o anulează.
+Numerele sunt zecimale, sau hex cu prefixul 0x.
+Octeții unui pad OTP sunt puși pe zero după folosire și nu sunt dați de două ori.
+Seiful (vault.path, --vault) păstrează chei, pad-uri și referințe la fișiere de
+cheie sub fraza de acces din --passphrase sau CRYPTO_VAULT_PASSPHRASE; vault use
+dă comenzii intrarea ca --key (sau ca pad pentru otp).
+Textul cifrat DES, AES, RC4 și ChaCha20 este hex; --hex ia și cheia ca hex.
+Argumentele hex pot conține spații, separatori : sau prefixul 0x; decode
+acceptă doar text canonic dacă nu este dat --lenient.
+Cu --mode cbc sau ctr, IV-ul (aleator dacă nu este dat --iv) începe textul cifrat.
+Imaginile stego sunt PNG sau BMP necomprimat; formatul ieșirii urmează extensia.
+Studenții unei provocări sunt citiți câte unul pe rând; fiecare primește o foaie
+în directorul de ieșire, iar răspunsurile sunt sigilate cu fraza de acces în answers.sealed.
+randtest citește 0 și 1 ca biți și orice altceva ca hex; testele eșuează sub p = 0.01.
+serve răspunde JSON-RPC 2.0 la POST /rpc (metode precum caesar.encrypt sau
+rsa.sign, listate la GET /methods) cu cheia API din --api-key sau
+CRYPTO_API_KEY, trimisă ca Authorization: Bearer <cheie>. Argumentele fișier
+de cheie pot fi date și ca JSON-ul fișierului.
+Setările se suprapun: implicite, crypto.toml (sau $CRYPTO_CONFIG), variabile
+de mediu și opțiuni; config le listează pe fiecare cu stratul din care provine.
+Jurnalele merg la ieșirea de eroare standard: avertismente implicit, progres cu -v,
+detalii cu -vv; RUST_LOG suprascrie nivelul. Opțiunile de jurnalizare trec la pki.
+Mesajele sunt în engleză sau română: --lang, altfel ui.lang (CRYPTO_LANG),
+altfel localizarea sistemului. JSON-ul păstrează câmpurile în engleză; doar
+erorile sunt traduse.",
+    ),
+    ("The {} must be hex: {}", "Valoarea pentru {} trebuie să fie hex: {}"),
+    ("key", "cheie"),
+    ("ciphertext", "text cifrat"),
+    ("IV", "IV"),
+    ("argument", "argument"),
+    ("data", "date"),
+    ("tag", "etichetă"),
+    ("Unknown mode {}", "Mod necunoscut {}"),
+    ("Failed to run {}: {}", "Nu s-a putut rula {}: {}"),
+    ("Invalid count {}", "Număr invalid {}"),
+    ("Invalid {} {}", "Valoare invalidă pentru {}: {}"),
+    ("Invalid number {}", "Număr invalid {}"),
+    ("Invalid bit count {}", "Număr de biți invalid {}"),
+    ("Cannot read {}: {}", "Nu se poate citi {}: {}"),
+    ("Wrote {} bytes to {}", "S-au scris {} octeți în {}"),
+    ("Config file: {}", "Fișier de configurare: {}"),
+    ("No config file (create crypto.toml or set CRYPTO_CONFIG)", "Niciun fișier de configurare (creați crypto.toml sau setați CRYPTO_CONFIG)"),
+    (
+        "Invalid ciphers.alphabet {} (from {}): expected latin or romanian",
+        "ciphers.alphabet invalid {} (din {}): se așteaptă latin sau romanian",
+    ),
+    // Classical ciphers
+    ("Shift {}: {}", "Deplasare {}: {}"),
+    ("Invalid key length {}", "Lungime de cheie invalidă {}"),
+    (
+        "Kasiski examination (key length: repeated trigram distances it divides)",
+        "Examinarea Kasiski (lungimea cheii: distanțe între trigrame repetate pe care le divide)",
+    ),
+    ("Friedman estimate: {}", "Estimarea Friedman: {}"),
+    (
+        "Average column index of coincidence (English is about 0.067)",
+        "Indicele de coincidență mediu pe coloane (engleza are circa 0.067)",
+    ),
+    ("Most likely key length: {}", "Cea mai probabilă lungime a cheii: {}"),
+    ("Key {}: {}", "Cheia {}: {}"),
+    ("Invalid matrix size {}", "Dimensiune de matrice invalidă {}"),
+    ("No {} plaintext blocks form an invertible matrix", "Niciun set de {} blocuri de text clar nu formează o matrice inversabilă"),
+    ("Key matrix", "Matricea cheii"),
+    ("Decryption: {}", "Decriptare: {}"),
+    ("Invalid rail count {}", "Număr de șine invalid {}"),
+    ("Invalid column count {}", "Număr de coloane invalid {}"),
+    ("{} columns, key {}: {}", "{} coloane, cheia {}: {}"),
+    ("Invalid restart count {}", "Număr de reporniri invalid {}"),
+    ("Score {}: {}", "Scor {}: {}"),
+    // DES, RC4 and ChaCha20
+    ("K+ (56 bits after PC-1): 0x{}", "K+ (56 de biți după PC-1): 0x{}"),
+    ("Random key (hex, odd parity): {}", "Cheie aleatoare (hex, paritate impară): {}"),
+    (
+        "Zero bytes per keystream position over {} random keys (unbiased: {})",
+        "Octeți zero pe poziție în fluxul de cheie, pentru {} chei aleatoare (fără bias: {})",
+    ),
+    ("  byte {}: {}", "  octetul {}: {}"),
+    (
+        "The second byte is zero about twice as often as it should be.",
+        "Al doilea octet este zero de aproape două ori mai des decât ar trebui.",
+    ),
+    ("nonce", "nonce"),
+    ("The nonce must be {} bytes", "Nonce-ul trebuie să aibă {} octeți"),
+    // LFSR
+    ("length", "lungime"),
+    ("tap", "priză"),
+    ("state", "stare"),
+    ("bit", "bit"),
+    ("bit count", "număr de biți"),
+    ("64-bit hex key", "cheie hex de 64 de biți"),
+    ("frame number", "număr de cadru"),
+    ("22-bit frame number", "număr de cadru pe 22 de biți"),
+    ("Period {} (maximal, 2^{} - 1 = {})", "Perioada {} (maximală, 2^{} - 1 = {})"),
+    ("Period {} (not maximal, 2^{} - 1 = {})", "Perioada {} (nu este maximală, 2^{} - 1 = {})"),
+    (
+        "The starting state never comes back (or the register is too long to check)",
+        "Starea inițială nu mai revine (sau registrul este prea lung pentru verificare)",
+    ),
+    (
+        "Linear complexity {}: the shortest LFSR producing these {} bits has {} stages",
+        "Complexitate liniară {}: cel mai scurt LFSR care produce acești {} biți are {} etaje",
+    ),
+    ("Connection polynomial coefficients c0..c{}: {}", "Coeficienții polinomului de conexiune c0..c{}: {}"),
+    ("Frame {} downlink: {}", "Cadrul {} downlink: {}"),
+    ("Frame {} uplink:   {}", "Cadrul {} uplink:   {}"),
+    ("No key reproduces the keystream; try more --bits", "Nicio cheie nu reproduce fluxul de cheie; încercați mai mulți --bits"),
+    (
+        "Geffe generator with registers of {}, {} and {} stages, {} keystream bits",
+        "Generator Geffe cu registre de {}, {} și {} etaje, {} biți de flux de cheie",
+    ),
+    ("Register 2 state {}: agrees with {}% of the output", "Registrul 2, starea {}: coincide cu {}% din ieșire"),
+    ("Register 3 state {}: agrees with {}% of the output", "Registrul 3, starea {}: coincide cu {}% din ieșire"),
+    ("Register 1 state {}: reproduces the keystream exactly", "Registrul 1, starea {}: reproduce exact fluxul de cheie"),
+    ("{} states tried instead of {} for the whole key", "{} stări încercate în loc de {} pentru întreaga cheie"),
+    // Number theory
+    ("{} has no inverse modulo {}", "{} nu are invers modulo {}"),
+    ("The modulus must be positive", "Modulul trebuie să fie pozitiv"),
+    ("The congruences have no common solution", "Congruențele nu au o soluție comună"),
+    ("{} is prime (Miller–Rabin, {} rounds)", "{} este prim (Miller–Rabin, {} runde)"),
+    ("{} is composite", "{} este compus"),
+    // Public-key commands
+    ("{} has no valid JSON", "{} nu conține JSON valid"),
+    ("{} has no valid {}", "{} nu are un {} valid"),
+    ("exponent", "exponent"),
+    ("The exponent must fit in 32 bits", "Exponentul trebuie să încapă în 32 de biți"),
+    ("Wrote a {}-bit key with e = {} to {}", "S-a scris o cheie de {} biți cu e = {} în {}"),
+    ("Signature valid", "Semnătură validă"),
+    ("Signature INVALID", "Semnătură INVALIDĂ"),
+    ("Signature verification failed", "Verificarea semnăturii a eșuat"),
+    ("No small factor found", "Nu s-a găsit niciun factor mic"),
+    ("n = {} ({} bits)", "n = {} ({} biți)"),
+    ("Trial division finds p = {}", "Împărțirea prin încercări găsește p = {}"),
+    ("Recovered d matches the real one: {}", "d recuperat coincide cu cel real: {}"),
+    ("e = 3, unpadded ciphertext: {}", "e = 3, text cifrat fără umplutură: {}"),
+    ("m³ < n, so the integer cube root is the message: {}", "m³ < n, deci rădăcina cubică întreagă este mesajul: {}"),
+    (
+        "m³ wrapped around n; the cube root does not give the message",
+        "m³ a depășit n; rădăcina cubică nu dă mesajul",
+    ),
+    ("Expected a point x,y, G or O, got {}", "Se aștepta un punct x,y, G sau O, s-a primit {}"),
+    ("{} is not on the {} curve", "{} nu este pe curba {}"),
+    ("The public key is the point at infinity", "Cheia publică este punctul de la infinit"),
+    ("{} has far too many points to list", "{} are mult prea multe puncte pentru a fi listate"),
+    ("y² = x³ + {}x + {} over F{}: {} points counting O", "y² = x³ + {}x + {} peste F{}: {} puncte, cu tot cu O"),
+    ("Point", "Punct"),
+    ("Order", "Ordin"),
+    ("Multiples of G = {} on the {} curve", "Multiplii lui G = {} pe curba {}"),
+    ("{}·{} by double-and-add over k = {}", "{}·{} prin dublare și adunare după k = {}"),
+    ("Bit", "Bit"),
+    ("Doubled", "Dublat"),
+    ("Result", "Rezultat"),
+    ("Wrote a {} key to {}", "S-a scris o cheie {} în {}"),
+    ("Expected two hex numbers as <a>:<b>", "Se așteptau două numere hex de forma <a>:<b>"),
+    ("Wrote a {}-bit key to {}", "S-a scris o cheie de {} biți în {}"),
+    ("The chosen nonce gave s = 0; run the demo again", "Nonce-ul ales a dat s = 0; rulați din nou demonstrația"),
+    ("Two signatures share r = {}", "Două semnături au același r = {}"),
+    (
+        "k = (H₁ − H₂)/(s₁ − s₂) mod q, then x = (H₁ − k·s₁)/r mod q",
+        "k = (H₁ − H₂)/(s₁ − s₂) mod q, apoi x = (H₁ − k·s₁)/r mod q",
+    ),
+    ("Recovered x = {} (matches the real key: {})", "x recuperat = {} (coincide cu cheia reală: {})"),
+    ("The recovered exponent does not reproduce y", "Exponentul recuperat nu îl reproduce pe y"),
+    // Diffie-Hellman
+    ("p = {} ({} bits), g = {}", "p = {} ({} biți), g = {}"),
+    ("Our public value:   {}", "Valoarea noastră publică: {}"),
+    ("Their public value: {}", "Valoarea lor publică:     {}"),
+    ("AES-128 key from the shared secret: {}", "Cheia AES-128 din secretul comun: {}"),
+    (
+        "Listening on {}; run `crypto dh connect {}` in another terminal",
+        "Se ascultă pe {}; rulați `crypto dh connect {}` în alt terminal",
+    ),
+    ("Decrypted message: {}", "Mesaj decriptat: {}"),
+    ("Sent AES-128-CBC encrypted: {}", "Trimis criptat AES-128-CBC: {}"),
+    ("Mallory replaces both public values with her own:", "Mallory înlocuiește ambele valori publice cu ale ei:"),
+    ("  Alice's key:              {}", "  Cheia lui Alice:              {}"),
+    ("  Mallory's key with Alice: {}", "  Cheia lui Mallory cu Alice:   {}"),
+    ("  Bob's key:                {}", "  Cheia lui Bob:                {}"),
+    ("  Mallory's key with Bob:   {}", "  Cheia lui Mallory cu Bob:     {}"),
+    (
+        "Alice and Bob hold different keys and Mallory holds both, so she can decrypt, read and re-encrypt",
+        "Alice și Bob au chei diferite, iar Mallory le are pe amândouă, deci poate decripta, citi și recripta",
+    ),
+    (
+        "every message. Signing the public values (authenticated DH) is what stops this.",
+        "orice mesaj. Semnarea valorilor publice (DH autentificat) este ceea ce previne asta.",
+    ),
+    // Frequency analysis and randomness tests
+    ("Romanian text needs --model with a Romanian sample file", "Textul în română necesită --model cu un fișier-eșantion în română"),
+    ("Letters: {}", "Litere: {}"),
+    ("Index of coincidence: {} ({} {}, random {})", "Indice de coincidență: {} ({} {}, aleator {})"),
+    (
+        "Chi-squared against {}: monograms {}, bigrams {}, trigrams {}",
+        "Chi-pătrat față de {}: monograme {}, bigrame {}, trigrame {}",
+    ),
+    ("Monograms", "Monograme"),
+    ("Bigrams", "Bigrame"),
+    ("Trigrams", "Trigrame"),
+    ("{} (count, share, expected)", "{} (număr, pondere, așteptat)"),
+    ("Looks like {}", "Pare {}"),
+    ("Need at least two bits to test", "Sunt necesari cel puțin doi biți pentru test"),
+    ("The block length is at most 16", "Lungimea blocului este cel mult 16"),
+    ("Bits: {}", "Biți: {}"),
+    (
+        "Warning: SP 800-22 needs at least {} bits for meaningful results",
+        "Atenție: SP 800-22 necesită cel puțin {} biți pentru rezultate relevante",
+    ),
+    ("{} statistic {}  p {} {}", "{} statistica {}  p {} {}"),
+    ("PASS", "TRECUT"),
+    ("FAIL", "EȘUAT"),
+    (
+        "Entropy per bit ({}-bit blocks): Shannon {}, min-entropy {}",
+        "Entropie pe bit (blocuri de {} biți): Shannon {}, min-entropie {}",
+    ),
+    ("All tests passed at α = 0.01", "Toate testele au trecut la α = 0.01"),
+    ("Not random at α = 0.01", "Nu este aleator la α = 0.01"),
+    // Challenges
+    (
+        "Unknown challenge cipher {} (expected caesar, vigenere, hill, railfence, columnar or playfair)",
+        "Cifru de provocare necunoscut {} (se așteaptă caesar, vigenere, hill, railfence, columnar sau playfair)",
+    ),
+    ("Unknown difficulty {} (expected easy, medium or hard)", "Dificultate necunoscută {} (se așteaptă easy, medium sau hard)"),
+    ("Not a sealed answer key", "Nu este o cheie de răspunsuri sigilată"),
+    ("Missing or invalid iterations", "Număr de iterații lipsă sau invalid"),
+    ("Wrong passphrase, or the answer key was altered", "Frază de acces greșită, sau cheia de răspunsuri a fost modificată"),
+    ("The sealed answer key is not JSON", "Cheia de răspunsuri sigilată nu este JSON"),
+    ("{} lists no students", "{} nu conține niciun student"),
+    ("Wrote {} {} {} challenges to {}; answers sealed in {}", "S-au scris {} provocări {} {} în {}; răspunsurile sunt sigilate în {}"),
+    ("{}  key {}", "{}  cheia {}"),
+    ("No challenge for {}", "Nicio provocare pentru {}"),
+    ("correct", "corect"),
+    ("incorrect", "greșit"),
+    // Encodings, MACs, Merkle trees and passwords
+    ("Unknown encoding {} (hex, base32 or base64)", "Codificare necunoscută {} (hex, base32 sau base64)"),
+    ("Unknown hash {} (expected sha256)", "Funcție hash necunoscută {} (se așteaptă sha256)"),
+    ("MAC valid", "MAC valid"),
+    ("MAC INVALID", "MAC INVALID"),
+    ("MAC verification failed", "Verificarea MAC a eșuat"),
+    ("Invalid SHA-256 hash {}", "Hash SHA-256 invalid {}"),
+    ("Tree size: {}", "Dimensiunea arborelui: {}"),
+    ("Root hash: {}", "Hash-ul rădăcinii: {}"),
+    ("Index {} is past the last leaf", "Indexul {} este după ultima frunză"),
+    ("Leaf {} of {} (root {})", "Frunza {} din {} (rădăcina {})"),
+    ("  path[{}] {}", "  drum[{}] {}"),
+    ("Inclusion proof valid", "Dovadă de includere validă"),
+    ("Inclusion proof INVALID", "Dovadă de includere INVALIDĂ"),
+    ("Inclusion proof verification failed", "Verificarea dovezii de includere a eșuat"),
+    ("The old size must be 1 to {}", "Dimensiunea veche trebuie să fie între 1 și {}"),
+    ("First {} leaves: root {}", "Primele {} frunze: rădăcina {}"),
+    ("All {} leaves: root {}", "Toate cele {} frunze: rădăcina {}"),
+    ("The cost is at most {}", "Costul este cel mult {}"),
+    ("Unrecognised password hash {}", "Hash de parolă nerecunoscut {}"),
+    ("Password correct", "Parolă corectă"),
+    ("Password WRONG", "Parolă GREȘITĂ"),
+    ("Password verification failed", "Verificarea parolei a eșuat"),
+    ("iterations", "iterații"),
+    ("per hash", "per hash"),
+    ("guesses/s", "încercări/s"),
+    ("10k common passwords", "10k parole uzuale"),
+    ("8 lowercase letters", "8 litere mici"),
+    ("8 printable characters", "8 caractere tipăribile"),
+    (
+        "Times are for one core of this machine trying PBKDF2-HMAC-SHA256; the",
+        "Timpii sunt pentru un nucleu al acestei mașini care încearcă PBKDF2-HMAC-SHA256;",
+    ),
+    (
+        "defender pays one hash per login, the attacker one per guess.",
+        "apărătorul plătește un hash per autentificare, atacatorul unul per încercare.",
+    ),
+    // Secret sharing and steganography
+    ("Invalid count {} (1 to 255)", "Număr invalid {} (între 1 și 255)"),
+    ("Invalid share {}", "Parte invalidă {}"),
+    ("--cipher {} needs --key", "--cipher {} necesită --key"),
+    ("--key needs --cipher des or aes", "--key necesită --cipher des sau aes"),
+    ("Unknown cipher {} (des or aes)", "Cifru necunoscut {} (des sau aes)"),
+    (
+        "Hid {} bytes of ciphertext in {} ({}% of its {}-byte capacity)",
+        "S-au ascuns {} octeți de text cifrat în {} ({}% din capacitatea sa de {} octeți)",
+    ),
+    ("Hid {} bytes in {} ({}% of its {}-byte capacity)", "S-au ascuns {} octeți în {} ({}% din capacitatea sa de {} octeți)"),
+    ("{}x{}, {} channel(s): {} bytes, {} with DES or {} with AES", "{}x{}, {} canal(e): {} octeți, {} cu DES sau {} cu AES"),
+    (
+        "Chi-square probability of embedding, by share of the image scanned",
+        "Probabilitatea chi-pătrat de inserare, după porțiunea de imagine scanată",
+    ),
+    ("No sign of LSB embedding", "Niciun semn de inserare LSB"),
+    ("Likely LSB payload in the first {}% of the image", "Probabil date LSB în primele {}% din imagine"),
+    ("{} is neither a PNG nor a BMP image", "{} nu este nici imagine PNG, nici BMP"),
+    (
+        "{} must end in .png or .bmp; lossy formats lose the hidden bits",
+        "{} trebuie să se termine în .png sau .bmp; formatele cu pierderi pierd biții ascunși",
+    ),
+    ("Invalid PNG: {}", "PNG invalid: {}"),
+    ("Palette PNG was not expanded", "PNG-ul cu paletă nu a fost expandat"),
+    ("The image is too large for PNG", "Imaginea este prea mare pentru PNG"),
+    ("Truncated BMP header", "Antet BMP trunchiat"),
+    (
+        "Only uncompressed 24- and 32-bit BMPs are supported, not {}-bit compression {}",
+        "Sunt acceptate doar BMP-uri necomprimate de 24 și 32 de biți, nu {} biți cu compresia {}",
+    ),
+    ("Invalid BMP dimensions", "Dimensiuni BMP invalide"),
+    ("The BMP pixel data is truncated", "Datele de pixeli BMP sunt trunchiate"),
+    ("BMP output needs an RGB image; save greyscale images as PNG", "Ieșirea BMP necesită o imagine RGB; salvați imaginile în tonuri de gri ca PNG"),
+    ("The image is too large for BMP", "Imaginea este prea mare pentru BMP"),
+    // One-time pads
+    ("size", "dimensiune"),
+    ("offset", "deplasament"),
+    ("Wrote {} random bytes to {}", "S-au scris {} octeți aleatori în {}"),
+    ("Offset {}: {}", "Deplasament {}: {}"),
+    ("{}: {} bytes, {} unused", "{}: {} octeți, {} nefolosiți"),
+    ("  used {}..{}", "  folosiți {}..{}"),
+    ("Ciphertext 1: {}", "Text cifrat 1: {}"),
+    ("Ciphertext 2: {}", "Text cifrat 2: {}"),
+    ("XOR of both, with the pad gone: {}", "XOR-ul lor, fără pad: {}"),
+    ("Dragging the crib \"{}\" reveals the other message at", "Glisarea fragmentului cunoscut \"{}\" dezvăluie celălalt mesaj la"),
+    // Vault
+    ("A passphrase is required (--passphrase or CRYPTO_VAULT_PASSPHRASE)", "Este necesară o frază de acces (--passphrase sau CRYPTO_VAULT_PASSPHRASE)"),
+    ("memory", "memorie"),
+    ("Added the {} {} ({} bytes) to {}", "S-a adăugat intrarea {} {} ({} octeți) în {}"),
+    ("  {} key, {} bytes", "  {} cheie, {} octeți"),
+    ("  {} pad, {} bytes, {} unused", "  {} pad, {} octeți, {} nefolosiți"),
+    ("  {} ref, {}", "  {} referință, {}"),
+    ("The pad {} can only be used with otp encrypt or decrypt", "Pad-ul {} poate fi folosit doar cu otp encrypt sau decrypt"),
+    // Cipher registry
+    ("Registered ciphers (name, family, origin, key):", "Cifruri înregistrate (nume, familie, origine, cheie):"),
+    ("Unknown cipher {} (see crypto ciphers)", "Cifru necunoscut {} (vedeți crypto ciphers)"),
+    ("{} works on bytes; analyze --cipher takes a letter cipher", "{} lucrează pe octeți; analyze --cipher ia un cifru pe litere"),
+    ("Ciphertext under {}: {}", "Textul cifrat cu {}: {}"),
+    // Hybrid encryption
+    ("Unknown cipher {} (des, 3des or aes)", "Cifru necunoscut {} (des, 3des sau aes)"),
+    ("Encrypted {} for {} under a fresh {} key -> {}", "S-a criptat {} pentru {} cu o cheie {} nouă -> {}"),
+    ("Decrypted {} -> {}", "S-a decriptat {} -> {}"),
+    (
+        "{} is encrypted; pass a decrypted copy (openssl pkey -in {} -out <file>) as --key",
+        "{} este criptată; dați o copie decriptată (openssl pkey -in {} -out <fișier>) ca --key",
+    ),
+    ("Not a hybrid container", "Nu este un container hibrid"),
+    ("The key could not be unwrapped: this is not {}'s private key", "Cheia nu a putut fi despachetată: aceasta nu este cheia privată a lui {}"),
+    // Key exchange over the network
+    ("The other side hung up", "Cealaltă parte a închis conexiunea"),
+    ("Missing or invalid {}", "{} lipsă sau invalid"),
+    // serve
+    (
+        "Serving JSON-RPC on http://{}/rpc (methods at http://{}/methods)",
+        "Se servește JSON-RPC la http://{}/rpc (metodele la http://{}/methods)",
+    ),
+    ("No --api-key or CRYPTO_API_KEY given; send this one: {}", "Nu s-a dat --api-key sau CRYPTO_API_KEY; trimiteți aceasta: {}"),
+    // learn
+    ("Rail fence", "Gard feroviar"),
+    ("Exercises", "Exerciții"),
+    ("Encrypt {} with a Caesar shift of {}.", "Criptați {} cu o deplasare Caesar de {}."),
+    ("Decrypt the Vigenère ciphertext {} with the key {}.", "Decriptați textul cifrat Vigenère {} cu cheia {}."),
+    ("Encrypt the digraph {} with the Playfair key {}.", "Criptați digrama {} cu cheia Playfair {}."),
+    ("Encrypt {} on a rail fence of {} rails.", "Criptați {} pe un gard feroviar cu {} șine."),
+    ("Find the inverse of {} modulo 26.", "Găsiți inversul lui {} modulo 26."),
+    ("Key", "Cheie"),
+    ("Text", "Text"),
+    ("Tab: next screen  Enter: check  Ctrl-N: skip  Esc: quit", "Tab: ecranul următor  Enter: verificare  Ctrl-N: sari peste  Esc: ieșire"),
+    ("Tab: next screen  ↑↓: key or text  Esc: quit", "Tab: ecranul următor  ↑↓: cheie sau text  Esc: ieșire"),
+    ("Ciphertext", "Text cifrat"),
+    ("Error", "Eroare"),
+    ("plain   {}", "clar    {}"),
+    ("cipher  {}", "cifrat  {}"),
+    (
+        "Each letter moves {} places along the alphabet, wrapping from Z to A.",
+        "Fiecare literă se mută cu {} poziții în alfabet, trecând de la Z înapoi la A.",
+    ),
+    ("Shifted alphabet", "Alfabet deplasat"),
+    ("text    {}", "text    {}"),
+    ("key     {}", "cheie   {}"),
+    (
+        "Each letter is shifted by the key letter above it (A = 0, B = 1, ...).",
+        "Fiecare literă este deplasată cu litera cheii de deasupra ei (A = 0, B = 1, ...).",
+    ),
+    ("Key stream", "Flux de cheie"),
+    ("Matrix", "Matrice"),
+    ("Digraphs", "Digrame"),
+    ("rail {}  {}", "șina {}  {}"),
+    (
+        "The ciphertext reads the rails from top to bottom (spaces shown as _).",
+        "Textul cifrat citește șinele de sus în jos (spațiile apar ca _).",
+    ),
+    ("Zigzag", "Zigzag"),
+    ("First block {}: L(i) = R(i-1), R(i) = L(i-1) ⊕ f(R(i-1), K(i))", "Primul bloc {}: L(i) = R(i-1), R(i) = L(i-1) ⊕ f(R(i-1), K(i))"),
+    ("Round", "Runda"),
+    ("Question (solved {} of {} attempts)", "Întrebare (rezolvate {} din {} încercări)"),
+    ("Answer", "Răspuns"),
+    ("Correct! Here is the next one.", "Corect! Iată următoarea."),
+    ("Not quite; try again, or Ctrl-N to skip.", "Nu chiar; încercați din nou, sau Ctrl-N pentru a sări peste."),
+    ("Type your answer and press Enter.", "Scrieți răspunsul și apăsați Enter."),
+    (
+        "Unknown report cipher {} (expected caesar, vigenere, hill, railfence, columnar, playfair or des)",
+        "Cifru necunoscut pentru raport {} (se aștepta caesar, vigenere, hill, railfence, columnar, playfair sau des)",
+    ),
+    ("Wrote the {} worked example to {}", "S-a scris exemplul rezolvat {} în {}"),
+    ("{} worked example", "{}: exemplu rezolvat"),
+    ("Caesar cipher", "Cifrul Caesar"),
+    ("Vigenère cipher", "Cifrul Vigenère"),
+    ("Hill cipher", "Cifrul Hill"),
+    ("Rail fence cipher", "Cifrul gardului (rail fence)"),
+    ("Columnar transposition", "Transpoziția pe coloane"),
+    ("Playfair cipher", "Cifrul Playfair"),
+    ("Plaintext", "Text clar"),
+    ("Letter by letter", "Literă cu literă"),
+    (
+        "Each letter p becomes (p + {}) mod 26; other characters are copied unchanged.",
+        "Fiecare literă p devine (p + {}) mod 26; celelalte caractere sunt copiate neschimbate.",
+    ),
+    ("Plain", "Clar"),
+    ("Cipher", "Cifrat"),
+    (
+        "The key {} is repeated under the letters; each letter p with key letter k becomes (p + k) mod 26. Other characters use up no key letter.",
+        "Cheia {} se repetă sub litere; fiecare literă p cu litera de cheie k devine (p + k) mod 26. Celelalte caractere nu consumă litere din cheie.",
+    ),
+    (
+        "det K ≡ {} (mod {}) has an inverse modulo {}, so K is invertible; decryption multiplies by K⁻¹:",
+        "det K ≡ {} (mod {}) are invers modulo {}, deci K este inversabilă; decriptarea înmulțește cu K⁻¹:",
+    ),
+    ("Blocks", "Blocuri"),
+    (
+        "The letters become numbers ({} = 0, {} = 1, ...) taken {} at a time as column vectors p; the last block is padded with X, and each block becomes c = K·p mod {}.",
+        "Literele devin numere ({} = 0, {} = 1, ...) luate câte {} ca vectori coloană p; ultimul bloc se completează cu X, iar fiecare bloc devine c = K·p mod {}.",
+    ),
+    ("Block", "Bloc"),
+    (
+        "The text is written diagonally down and up over {} rails, starting {} places into the zigzag (spaces shown as _).",
+        "Textul se scrie în diagonală, în jos și în sus, pe {} șine, începând la {} poziții în zigzag (spațiile apar ca _).",
+    ),
+    ("Rail {}", "Șina {}"),
+    ("Reading the rails", "Citirea șinelor"),
+    (
+        "The ciphertext reads the rails from top to bottom, each left to right.",
+        "Textul cifrat citește șinele de sus în jos, fiecare de la stânga la dreapta.",
+    ),
+    ("Rail", "Șina"),
+    ("Characters", "Caractere"),
+    (
+        "Two keywords: the output of the first transposition is transposed again under the second.",
+        "Două cuvinte-cheie: rezultatul primei transpoziții este transpus din nou sub al doilea.",
+    ),
+    ("Keyword {}", "Cuvântul-cheie {}"),
+    (
+        "Only letters are kept. They are written in rows of {} under the keyword, and the columns are read off in the alphabetical order of its letters (numbered below it).",
+        "Se păstrează doar literele. Ele se scriu pe rânduri de câte {} sub cuvântul-cheie, iar coloanele se citesc în ordinea alfabetică a literelor acestuia (numerotată dedesubt).",
+    ),
+    ("Column", "Coloana"),
+    ("Letters", "Litere"),
+    ("After this transposition: {}", "După această transpoziție: {}"),
+    (
+        "The key's letters come first without repeats, then the rest of the alphabet; J is merged into I.",
+        "Întâi vin literele cheii fără repetări, apoi restul alfabetului; J se contopește cu I.",
+    ),
+    ("Only letters are kept, split into pairs and padded with X: {}", "Se păstrează doar literele, împărțite în perechi și completate cu X: {}"),
+    ("same row: take the letters to the right", "același rând: se iau literele din dreapta"),
+    ("same column: take the letters below", "aceeași coloană: se iau literele de dedesubt"),
+    ("rectangle: take the other two corners", "dreptunghi: se iau celelalte două colțuri"),
+    ("not in the matrix: copied", "nu este în matrice: copiat"),
+    ("Pair", "Pereche"),
+    ("Row, column", "Rând, coloană"),
+    ("Rule", "Regula"),
+    ("Key schedule", "Generarea subcheilor"),
+    (
+        "PC-1 drops the parity bits: K+ = {} (56 bits). Round key Ki is PC-2 of K+'s halves, rotated left once or twice per round.",
+        "PC-1 elimină biții de paritate: K+ = {} (56 de biți). Subcheia Ki este PC-2 aplicat jumătăților lui K+, rotite la stânga cu una sau două poziții pe rundă.",
+    ),
+    ("Round key", "Subcheie"),
+    (
+        "The text is padded with PKCS#7 to {} whole 8-byte blocks, each encrypted on its own (ECB): {}",
+        "Textul se completează cu PKCS#7 până la {} blocuri întregi de 8 octeți, fiecare criptat separat (ECB): {}",
+    ),
+    ("Block {}: {}", "Blocul {}: {}"),
+    (
+        "After IP the block is split into L0 and R0; each round sets L(i) = R(i-1) and R(i) = L(i-1) ⊕ f(R(i-1), K(i)).",
+        "După IP blocul se împarte în L0 și R0; fiecare rundă calculează L(i) = R(i-1) și R(i) = L(i-1) ⊕ f(R(i-1), K(i)).",
+    ),
+    ("The final permutation of R16 ‖ L16 gives {}", "Permutarea finală a lui R16 ‖ L16 dă {}"),
+    (
+        "Unknown quiz topic {} (expected caesar, vigenere, playfair, railfence, des or inverse)",
+        "Temă de test necunoscută {} (se aștepta caesar, vigenere, playfair, railfence, des sau inverse)",
+    ),
+    ("Invalid question count {}", "Număr de întrebări invalid {}"),
+    ("Question {} of {}: {}", "Întrebarea {} din {}: {}"),
+    ("Correct.", "Corect."),
+    ("Wrong; the answer is {}.", "Greșit; răspunsul este {}."),
+    ("Score: {} of {}, recorded in {}", "Scor: {} din {}, înregistrat în {}"),
+    ("Compute the DES round key K{} of the key {} (hex), as 12 hex digits.", "Calculați subcheia DES K{} a cheii {} (hex), ca 12 cifre hex."),
+    ("Cannot write {}: {}", "Nu se poate scrie {}: {}"),
+    ("Stage {}: {}", "Etapa {}: {}"),
+    ("Stage {} needs a key, as {}:<key>", "Etapa {} are nevoie de o cheie, ca {}:<cheie>"),
+    ("Unknown stage {} (expected {}, hex, base32 or base64)", "Etapă necunoscută {} (se aștepta {}, hex, base32 sau base64)"),
+    (
+        "Stage {} needs text but gets binary data; put an encoding such as base64 between them",
+        "Etapa {} are nevoie de text, dar primește date binare; puneți între ele o codificare precum base64",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_core::i18n::{mismatched_placeholders, untranslated, CORE};
+
+    #[test]
+    fn every_message_has_a_romanian_translation() {
+        let sources = [
+            include_str!("main.rs"),
+            include_str!("aes.rs"),
+            include_str!("analyze.rs"),
+            include_str!("caesar.rs"),
+            include_str!("chacha20.rs"),
+            include_str!("challenge.rs"),
+            include_str!("ciphers.rs"),
+            include_str!("codec.rs"),
+            include_str!("columnar.rs"),
+            include_str!("config.rs"),
+            include_str!("des.rs"),
+            include_str!("ecc.rs"),
+            include_str!("elgamal.rs"),
+            include_str!("exchange.rs"),
+            include_str!("hash.rs"),
+            include_str!("hill.rs"),
+            include_str!("hybrid.rs"),
+            include_str!("image.rs"),
+            include_str!("learn.rs"),
+            include_str!("lfsr.rs"),
+            include_str!("mac.rs"),
+            include_str!("math.rs"),
+            include_str!("merkle.rs"),
+            include_str!("otp.rs"),
+            include_str!("output.rs"),
+            include_str!("passwd.rs"),
+            include_str!("pipeline.rs"),
+            include_str!("playfair.rs"),
+            include_str!("primes.rs"),
+            include_str!("quiz.rs"),
+            include_str!("railfence.rs"),
+            include_str!("randtest.rs"),
+            include_str!("rc4.rs"),
+            include_str!("report.rs"),
+            include_str!("rsa.rs"),
+            include_str!("serve.rs"),
+            include_str!("shamir.rs"),
+            include_str!("stego.rs"),
+            include_str!("vault.rs"),
+            include_str!("vigenere.rs"),
+        ];
+        for source in sources {
+            assert_eq!(untranslated(source, &[CORE, MESSAGES]), Vec::<String>::new());
+        }
+        // Shown through `tr!` but not written as literals there
+        for message in [crate::NOTES, "10k common passwords", "8 lowercase letters", "8 printable characters"] {
+            assert!(MESSAGES.iter().any(|(english, _)| *english == message), "{}", message);
+        }
+        assert!(mismatched_placeholders(MESSAGES).is_empty());
+    }
+}
@@ -0,0 +1,92 @@
+//! `crypto otp`: one-time pads that refuse to reuse key material, and what
+//! happens when a pad is reused anyway
+
+use std::io;
+
+use crypto_core::codec::encode_hex;
+use crypto_core::otp::{self, PadFile};
+use crypto_core::tr;
+use serde_json::json;
+
+use crate::output::CommandOutput;
+use crate::{hex_input, take_flag_values, text_argument, usage_error};
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto otp generate --bytes <n> <pad> | crypto otp encrypt --pad <pad> [<text>] | \
+                         crypto otp decrypt --pad <pad> --offset <n> [<hex>] | crypto otp status <pad> | \
+                         crypto otp reuse [--crib <word>] <message> <message>";
+    let (pads, rest) = take_flag_values(args, "--pad")?;
+    let (sizes, rest) = take_flag_values(&rest, "--bytes")?;
+    let (offsets, rest) = take_flag_values(&rest, "--offset")?;
+    let (cribs, rest) = take_flag_values(&rest, "--crib")?;
+    let Some((operation, rest)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+    let number = |value: &String, what: &str| {
+        value.parse::<u64>().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid {} {}", what, value)))
+    };
+
+    match (operation.as_str(), pads.last(), rest) {
+        ("generate", None, [path]) => {
+            let size = sizes.last().ok_or_else(|| usage_error(USAGE))?;
+            let pad = PadFile::create(path, number(size, tr!("size"))? as usize)?;
+            out.line(tr!("Wrote {} random bytes to {}", pad.len(), path));
+            out.field("pad", path.as_str());
+            out.field("bytes", pad.len());
+        }
+        ("encrypt", Some(path), rest) => {
+            let plaintext = text_argument(rest, USAGE)?;
+            let mut pad = PadFile::open(path)?;
+            let (offset, key) = pad.take(plaintext.len())?;
+            let ciphertext = encode_hex(&otp::xor(plaintext.as_bytes(), &key)?);
+
+            out.line(tr!("Offset {}: {}", offset, ciphertext));
+            out.field("offset", offset);
+            out.field("result", ciphertext);
+            out.field("remaining", pad.remaining());
+        }
+        ("decrypt", Some(path), rest) => {
+            let offset = number(offsets.last().ok_or_else(|| usage_error(USAGE))?, tr!("offset"))?;
+            let ciphertext = hex_input(&text_argument(rest, USAGE)?, tr!("ciphertext"))?;
+            let key = PadFile::open(path)?.take_at(offset, ciphertext.len())?;
+            let plaintext = String::from_utf8_lossy(&otp::xor(&ciphertext, &key)?).into_owned();
+
+            out.line(&plaintext);
+            out.field("result", plaintext);
+        }
+        ("status", None, [path]) => {
+            let pad = PadFile::open(path)?;
+            out.line(tr!("{}: {} bytes, {} unused", path, pad.len(), pad.remaining()));
+            for (start, end) in pad.consumed() {
+                out.line(tr!("  used {}..{}", start, end));
+            }
+            out.field("bytes", pad.len());
+            out.field("remaining", pad.remaining());
+            out.field("consumed", pad.consumed().iter().map(|(start, end)| json!([start, end])).collect::<Vec<_>>());
+        }
+        ("reuse", None, [first, second]) => {
+            // Encrypt both messages with one pad, as a careless operator would
+            let pad = otp::generate_pad(first.len().max(second.len()))?;
+            let first_ciphertext = otp::xor(first.as_bytes(), &pad)?;
+            let second_ciphertext = otp::xor(second.as_bytes(), &pad)?;
+            let xored = otp::xor_ciphertexts(&first_ciphertext, &second_ciphertext);
+            let crib = cribs.last().map(String::as_str).unwrap_or(" the ");
+            let matches = otp::crib_drag(&xored, crib.as_bytes());
+
+            out.line(tr!("Ciphertext 1: {}", encode_hex(&first_ciphertext)));
+            out.line(tr!("Ciphertext 2: {}", encode_hex(&second_ciphertext)));
+            out.line(tr!("XOR of both, with the pad gone: {}", encode_hex(&xored)));
+            out.line(tr!("Dragging the crib \"{}\" reveals the other message at", crib));
+            for found in &matches {
+                out.line(format!("  {:>3}: \"{}\"", found.position, found.other));
+            }
+            out.field("ciphertexts", vec![encode_hex(&first_ciphertext), encode_hex(&second_ciphertext)]);
+            out.field("xor", encode_hex(&xored));
+            out.field("crib", crib);
+            out.field("matches", matches.iter().map(|found| json!({"position": found.position, "other": found.other})).collect::<Vec<_>>());
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use std::io;
+use std::str::FromStr;
+
+use crypto_core::tr;
+use serde_json::{Map, Value};
+
+/// How command results are printed (`--output text|json`), as in `pki`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl OutputFormat {
+    pub(crate) fn parse(name: &str) -> io::Result<Self> {
+        match name {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("Unknown output format {} (expected text or json)", name)
+            )),
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Json => "json",
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = io::Error;
+
+    fn from_str(name: &str) -> io::Result<Self> {
+        Self::parse(name)
+    }
+}
+
+/// Result of one command. Text lines are printed as they are produced;
+/// fields are collected and printed as a single JSON object at the end.
+pub(crate) struct CommandOutput {
+    format: OutputFormat,
+    fields: Map<String, Value>,
+}
+
+impl CommandOutput {
+    pub(crate) fn new(format: OutputFormat, command: &str) -> Self {
+        let mut fields = Map::new();
+        fields.insert("command".to_string(), Value::from(command));
+        fields.insert("ok".to_string(), Value::from(true));
+        CommandOutput { format, fields }
+    }
+
+    pub(crate) fn is_json(&self) -> bool {
+        self.format == OutputFormat::Json
+    }
+
+    /// Human-readable line, shown in text mode only
+    pub(crate) fn line(&self, text: impl AsRef<str>) {
+        if !self.is_json() {
+            println!("{}", text.as_ref());
+        }
+    }
+
+    /// Structured result, shown in JSON mode only
+    pub(crate) fn field(&mut self, key: &str, value: impl Into<Value>) {
+        self.fields.insert(key.to_string(), value.into());
+    }
+
+    /// The fields collected so far, for `crypto serve` to send back
+    pub(crate) fn into_fields(self) -> Map<String, Value> {
+        self.fields
+    }
+
+    /// Print the JSON document in JSON mode
+    pub(crate) fn finish(self) {
+        if self.is_json() {
+            println!("{}", Value::Object(self.fields));
+        }
+    }
+}
+
+/// Report a failed command as `{"command": ..., "ok": false, "error": ...}`
+pub(crate) fn print_json_error(command: &str, error: &io::Error) {
+    let mut fields = Map::new();
+    fields.insert("command".to_string(), Value::from(command));
+    fields.insert("ok".to_string(), Value::from(false));
+    fields.insert("error".to_string(), Value::from(error.to_string()));
+    println!("{}", Value::Object(fields));
+}
@@ -0,0 +1,131 @@
+//! `crypto passwd`: salted password hashes with PBKDF2 or Argon2id, and how
+//! long guessing them takes
+
+use std::io;
+use std::time::{Duration, Instant};
+
+use crypto_core::hash::Sha256;
+use crypto_core::password::{self, Kdf, PasswordHash};
+use crypto_core::tr;
+use serde_json::json;
+
+use crate::output::CommandOutput;
+use crate::{take_flag_values, text_argument, usage_error};
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto passwd hash [--iterations <n> | --memory-hard [--cost <log2 n>]] [<password>] | \
+                         crypto passwd verify --hash <stored> [<password>] | \
+                         crypto passwd bench [--iterations <n>,...]";
+    let (iterations, rest) = take_flag_values(args, "--iterations")?;
+    let (costs, rest) = take_flag_values(&rest, "--cost")?;
+    let (hashes, rest) = take_flag_values(&rest, "--hash")?;
+    let memory_hard = rest.iter().any(|arg| arg == "--memory-hard");
+    let rest: Vec<String> = rest.into_iter().filter(|arg| arg != "--memory-hard").collect();
+    let Some((operation, rest)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+    let number = |value: &str| {
+        value.parse::<u32>().ok().filter(|&value| value > 0).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid count {}", value))
+        })
+    };
+
+    match (operation.as_str(), hashes.last()) {
+        ("hash", None) => {
+            let kdf = match (memory_hard, iterations.last(), costs.last()) {
+                (false, iterations, None) => Kdf::Pbkdf2 {
+                    iterations: iterations.map(|value| number(value)).transpose()?.unwrap_or(password::DEFAULT_ITERATIONS),
+                },
+                (true, None, cost) => {
+                    let cost = cost.map(|value| number(value)).transpose()?.unwrap_or(password::DEFAULT_COST);
+                    if cost > password::MAX_COST {
+                        return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("The cost is at most {}", password::MAX_COST)));
+                    }
+                    Kdf::MemoryHard { cost }
+                }
+                _ => return Err(usage_error(USAGE)),
+            };
+            let stored = PasswordHash::new(text_argument(rest, USAGE)?.as_bytes(), kdf)?;
+            out.line(stored.to_string());
+            out.field("hash", stored.to_string());
+        }
+        ("verify", Some(stored)) => {
+            let stored = PasswordHash::parse(stored)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, tr!("Unrecognised password hash {}", stored)))?;
+            let valid = stored.verify(text_argument(rest, USAGE)?.as_bytes());
+            out.line(if valid { tr!("Password correct") } else { tr!("Password WRONG") });
+            out.field("valid", valid);
+            if !valid {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, tr!("Password verification failed")));
+            }
+        }
+        ("bench", None) if rest.is_empty() => {
+            let counts = match iterations.last() {
+                Some(list) => list.split(',').map(|value| number(value.trim())).collect::<io::Result<Vec<u32>>>()?,
+                None => vec![1, 1_000, 10_000, 100_000, password::DEFAULT_ITERATIONS],
+            };
+            // What an attacker holding the hash has to search through
+            let spaces = [
+                ("10k common passwords", 1e4),
+                ("8 lowercase letters", 26f64.powi(8)),
+                ("8 printable characters", 95f64.powi(8)),
+            ];
+
+            out.line(format!(
+                "{:>10}  {:>10}  {:>12}  {}",
+                tr!("iterations"),
+                tr!("per hash"),
+                tr!("guesses/s"),
+                spaces.iter().map(|(name, _)| format!("{:>24}", tr!(name))).collect::<String>()
+            ));
+            let mut rows = Vec::new();
+            for &count in &counts {
+                let seconds = seconds_per_hash(count);
+                let times: Vec<f64> = spaces.iter().map(|(_, size)| size * seconds).collect();
+                out.line(format!(
+                    "{:>10}  {:>10}  {:>12.0}  {}",
+                    count,
+                    format_duration(seconds),
+                    1.0 / seconds,
+                    times.iter().map(|&time| format!("{:>24}", format_duration(time))).collect::<String>()
+                ));
+                rows.push(json!({
+                    "iterations": count,
+                    "seconds_per_hash": seconds,
+                    "exhaustive_search": spaces.iter().zip(&times).map(|((name, _), time)| json!({"space": name, "seconds": time})).collect::<Vec<_>>(),
+                }));
+            }
+            out.line(tr!("Times are for one core of this machine trying PBKDF2-HMAC-SHA256; the"));
+            out.line(tr!("defender pays one hash per login, the attacker one per guess."));
+            out.field("benchmarks", rows);
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
+
+/// Average time of one PBKDF2 hash, repeated until at least 50 ms have passed
+fn seconds_per_hash(iterations: u32) -> f64 {
+    let start = Instant::now();
+    let mut runs = 0;
+    while runs == 0 || start.elapsed() < Duration::from_millis(50) {
+        password::pbkdf2::<Sha256>(b"password", b"saltsaltsaltsalt", iterations, 32);
+        runs += 1;
+    }
+    start.elapsed().as_secs_f64() / runs as f64
+}
+
+fn format_duration(seconds: f64) -> String {
+    const YEAR: f64 = 365.25 * 86_400.0;
+    match seconds {
+        s if s < 1e-3 => format!("{:.1} µs", s * 1e6),
+        s if s < 1.0 => format!("{:.1} ms", s * 1e3),
+        s if s < 120.0 => format!("{:.1} s", s),
+        s if s < 7_200.0 => format!("{:.0} min", s / 60.0),
+        s if s < 172_800.0 => format!("{:.0} h", s / 3_600.0),
+        s if s < 2.0 * YEAR => format!("{:.0} days", s / 86_400.0),
+        s if s < 1e6 * YEAR => format!("{:.0} years", s / YEAR),
+        s => format!("{:.1e} years", s / YEAR),
+    }
+}
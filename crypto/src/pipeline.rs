@@ -0,0 +1,221 @@
+//! `crypto pipeline`: any sequence of ciphers and encodings, written
+//! `"playfair:KEY | columnar:KEY2 | base64"`, applied left to right to
+//! encrypt and undone right to left to decrypt, for product-cipher
+//! experiments across modules
+
+use std::io;
+
+use crypto_core::codec::{encode_hex, Encoding, Strictness};
+use crypto_core::hill::Hill;
+use crypto_core::language::Alphabet;
+use crypto_core::{tr, CipherError, ClassicalCipher, SymmetricCipher};
+use serde_json::json;
+
+use crate::output::CommandOutput;
+use crate::{take_romanian, text_argument, usage_error};
+
+/// What a stage does to the data passing through it
+enum Transform {
+    /// Text to text
+    Classical(Box<dyn ClassicalCipher>),
+    /// Bytes to bytes
+    Symmetric(Box<dyn SymmetricCipher>),
+    /// Bytes to text when encrypting, text to bytes when decrypting
+    Encoding(Encoding),
+}
+
+/// One `name[:key]` of a pipeline
+struct Stage {
+    spec: String,
+    transform: Transform,
+}
+
+/// The data after one stage: its spec and the result, as text when it is
+/// text and as hex otherwise
+pub(crate) struct Step {
+    pub(crate) stage: String,
+    pub(crate) result: String,
+}
+
+impl Stage {
+    /// Parse `name[:key]`, where the name is any registered cipher or an
+    /// encoding; Hill works over `alphabet`, and keys of the byte ciphers
+    /// starting with `0x` are hex
+    fn parse(spec: &str, alphabet: Alphabet) -> io::Result<Self> {
+        let (name, key) = match spec.split_once(':') {
+            Some((name, key)) => (name.trim(), Some(key.trim())),
+            None => (spec.trim(), None),
+        };
+        let invalid = |error: CipherError| io::Error::new(io::ErrorKind::InvalidInput, tr!("Stage {}: {}", spec.trim(), error));
+        let symmetric_key = |key: &str| match key.strip_prefix("0x") {
+            Some(hex) => Encoding::Hex.decode(hex, Strictness::Lenient).map_err(io::Error::from),
+            None => Ok(key.as_bytes().to_vec()),
+        };
+
+        if let (Some(encoding), None) = (Encoding::parse(name), key) {
+            return Ok(Stage { spec: spec.trim().to_string(), transform: Transform::Encoding(encoding) });
+        }
+        let Some(registration) = crate::ciphers::registry().get(name) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("Unknown stage {} (expected {}, hex, base32 or base64)", spec.trim(), crate::ciphers::registry().names().join(", "))
+            ));
+        };
+        let Some(key) = key else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("Stage {} needs a key, as {}:<key>", name, name)));
+        };
+        let transform = match name {
+            // Hill is the one registered cipher whose alphabet can change
+            "hill" => Transform::Classical(Box::new(Hill::with_alphabet(key, alphabet).map_err(invalid)?)),
+            _ if registration.is_classical() => Transform::Classical(registration.classical_cipher(key).map_err(invalid)?),
+            _ => Transform::Symmetric(registration.symmetric_cipher(&symmetric_key(key)?).map_err(invalid)?),
+        };
+        Ok(Stage { spec: spec.trim().to_string(), transform })
+    }
+
+    fn encrypt(&self, data: Vec<u8>) -> io::Result<Vec<u8>> {
+        match &self.transform {
+            Transform::Classical(cipher) => Ok(cipher.encrypt(&self.text(data)?).map_err(|e| self.error(e))?.into_bytes()),
+            Transform::Symmetric(cipher) => Ok(cipher.encrypt(&data)),
+            Transform::Encoding(encoding) => Ok(encoding.encode(&data).into_bytes()),
+        }
+    }
+
+    fn decrypt(&self, data: Vec<u8>) -> io::Result<Vec<u8>> {
+        match &self.transform {
+            Transform::Classical(cipher) => Ok(cipher.decrypt(&self.text(data)?).map_err(|e| self.error(e))?.into_bytes()),
+            Transform::Symmetric(cipher) => cipher.decrypt(&data).map_err(|e| self.error(e)),
+            Transform::Encoding(encoding) => Ok(encoding.decode(&self.text(data)?, Strictness::Lenient)?),
+        }
+    }
+
+    /// The data as the text a classical cipher or a decoder needs
+    fn text(&self, data: Vec<u8>) -> io::Result<String> {
+        String::from_utf8(data).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("Stage {} needs text but gets binary data; put an encoding such as base64 between them", self.spec)
+            )
+        })
+    }
+
+    fn error(&self, error: CipherError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidInput, tr!("Stage {}: {}", self.spec, error))
+    }
+
+    fn is_binary(&self) -> bool {
+        matches!(self.transform, Transform::Symmetric(_))
+    }
+}
+
+/// Stages separated by `|`
+pub(crate) struct Pipeline {
+    stages: Vec<Stage>,
+}
+
+impl Pipeline {
+    pub(crate) fn parse(spec: &str, alphabet: Alphabet) -> io::Result<Self> {
+        let stages = spec.split('|').map(|stage| Stage::parse(stage, alphabet)).collect::<io::Result<Vec<_>>>()?;
+        Ok(Pipeline { stages })
+    }
+
+    /// Whether the ciphertext is bytes (the last stage is a byte cipher
+    /// such as DES or RC4), written as hex
+    pub(crate) fn binary_ciphertext(&self) -> bool {
+        self.stages.last().is_some_and(Stage::is_binary)
+    }
+
+    /// Run `text` through every stage in order, keeping each result; a
+    /// binary ciphertext comes out as hex
+    pub(crate) fn encrypt(&self, text: &str) -> io::Result<(String, Vec<Step>)> {
+        let mut data = text.as_bytes().to_vec();
+        let mut steps = Vec::new();
+        for stage in &self.stages {
+            data = stage.encrypt(data)?;
+            steps.push(Step { stage: stage.spec.clone(), result: display(&data) });
+        }
+        let ciphertext = if self.binary_ciphertext() { encode_hex(&data) } else { display(&data) };
+        Ok((ciphertext, steps))
+    }
+
+    /// Undo every stage from the last to the first; a binary ciphertext is
+    /// read as hex
+    pub(crate) fn decrypt(&self, ciphertext: &str) -> io::Result<(String, Vec<Step>)> {
+        let mut data = if self.binary_ciphertext() {
+            Encoding::Hex.decode(ciphertext, Strictness::Lenient)?
+        } else {
+            ciphertext.as_bytes().to_vec()
+        };
+        let mut steps = Vec::new();
+        for stage in self.stages.iter().rev() {
+            data = stage.decrypt(data)?;
+            steps.push(Step { stage: stage.spec.clone(), result: display(&data) });
+        }
+        Ok((String::from_utf8_lossy(&data).into_owned(), steps))
+    }
+}
+
+/// Text as itself, binary data as hex
+fn display(data: &[u8]) -> String {
+    match std::str::from_utf8(data) {
+        Ok(text) => text.to_string(),
+        Err(_) => encode_hex(data),
+    }
+}
+
+/// Encrypt through a sequence of ciphers and encodings, or undo it
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto pipeline (encrypt | decrypt) [--romanian] \"<stage> | <stage> ...\" [<text>]";
+    let (romanian, rest) = take_romanian(args);
+    let Some((operation, [spec, rest @ ..])) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+    let alphabet = if romanian { Alphabet::Romanian } else { Alphabet::Latin };
+    let pipeline = Pipeline::parse(spec, alphabet)?;
+    let text = text_argument(rest, USAGE)?;
+
+    let (result, steps) = match operation.as_str() {
+        "encrypt" => pipeline.encrypt(&text)?,
+        "decrypt" => pipeline.decrypt(&text)?,
+        _ => return Err(usage_error(USAGE)),
+    };
+
+    out.line(&result);
+    out.field("operation", operation.as_str());
+    out.field("stages", steps.iter().map(|step| json!({"stage": step.stage, "result": step.result})).collect::<Vec<_>>());
+    out.field("result", result);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decrypting_undoes_every_stage() {
+        let text = "Meet me at the library";
+        for spec in [
+            "playfair:MONARCHY | columnar:ZEBRAS | base64",
+            "vigenere:LEMON | hill:3 3 2 5 | railfence:3,1",
+            "caesar:3 | des:MORTYNOR | base32 | aes:0x000102030405060708090a0b0c0d0e0f",
+            "rc4:Key | hex",
+            "chacha20:0x000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f | base64",
+        ] {
+            let pipeline = Pipeline::parse(spec, Alphabet::Latin).unwrap();
+            let plaintext = if spec.starts_with("playfair") { "MEETMEATTHELIBRARY" } else { text };
+            let (ciphertext, steps) = pipeline.encrypt(plaintext).unwrap();
+            assert_eq!(steps.len(), spec.split('|').count());
+            let (decrypted, _) = pipeline.decrypt(&ciphertext).unwrap();
+            let letters = |text: &str| text.replace(' ', "").to_uppercase();
+            assert_eq!(letters(&decrypted), letters(plaintext), "{}", spec);
+        }
+    }
+
+    #[test]
+    fn a_classical_stage_after_binary_data_is_refused() {
+        let pipeline = Pipeline::parse("des:MORTYNOR | caesar:3", Alphabet::Latin).unwrap();
+        assert!(pipeline.encrypt("hello").is_err());
+        assert!(Pipeline::parse("enigma:ABC", Alphabet::Latin).is_err());
+        assert!(Pipeline::parse("caesar", Alphabet::Latin).is_err());
+    }
+}
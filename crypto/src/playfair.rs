@@ -0,0 +1,72 @@
+//! `crypto playfair`: the Playfair cipher, its key matrix and a
+//! simulated-annealing attack
+
+use std::io;
+
+use crypto_core::language::Language;
+use crypto_core::{tr, ClassicalCipher};
+use playfair::Playfair;
+
+use crate::output::CommandOutput;
+use crate::{cipher_error, run_classical, take_flag_values, take_romanian, text_argument, usage_error};
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto playfair (encrypt | decrypt | matrix) --key <key> [<text>] | \
+                         crypto playfair crack [--romanian] [--restarts <n>] [<text>]";
+    let (keys, rest) = take_flag_values(args, "--key")?;
+    let (restarts, rest) = take_flag_values(&rest, "--restarts")?;
+    let (romanian, rest) = take_romanian(&rest);
+    let language = if romanian { Language::Romanian } else { Language::English };
+    let Some((operation, rest)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+
+    if operation == "crack" {
+        if !keys.is_empty() {
+            return Err(usage_error(USAGE));
+        }
+        let restarts = match restarts.last() {
+            Some(restarts) => restarts.parse::<u64>().ok().filter(|&restarts| restarts > 0).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid restart count {}", restarts))
+            })?,
+            None => 4,
+        };
+        let ciphertext = text_argument(rest, USAGE)?;
+        // Each restart anneals from its own random matrix; the best reading wins
+        let mut best: Option<playfair::Crack> = None;
+        for seed in 1..=restarts {
+            let crack = playfair::crack(&ciphertext, language, seed).map_err(cipher_error)?;
+            if best.as_ref().is_none_or(|best| crack.score > best.score) {
+                best = Some(crack);
+            }
+        }
+        let crack = best.expect("at least one restart");
+
+        for row in &crack.matrix {
+            out.line(row.iter().map(char::to_string).collect::<Vec<_>>().join(" "));
+        }
+        out.line(tr!("Score {}: {}", format!("{:.3}", crack.score), crack.plaintext));
+        out.field("matrix", crack.matrix.iter().map(|row| row.iter().collect::<String>()).collect::<Vec<_>>());
+        out.field("score", crack.score);
+        out.field("result", crack.plaintext);
+        return Ok(());
+    }
+
+    let (Some(key), true) = (keys.last(), restarts.is_empty()) else {
+        return Err(usage_error(USAGE));
+    };
+    let cipher = Playfair::new(key).map_err(cipher_error)?;
+
+    if operation != "matrix" {
+        return run_classical(&cipher, operation, rest, USAGE, out);
+    }
+    if !rest.is_empty() {
+        return Err(usage_error(USAGE));
+    }
+    for row in cipher.matrix() {
+        out.line(row.iter().map(char::to_string).collect::<Vec<_>>().join(" "));
+    }
+    out.field("matrix", cipher.matrix().iter().map(|row| row.iter().collect::<String>()).collect::<Vec<_>>());
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+//! `crypto primes`: probable primes, safe primes and primality tests
+
+use std::io;
+
+use crypto_core::{primes, tr};
+
+use crate::output::CommandOutput;
+use crate::{big_number, usage_error};
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto primes gen [--safe] <bits> | crypto primes test <number>";
+    let safe = args.iter().any(|arg| arg == "--safe");
+    let rest: Vec<String> = args.iter().filter(|arg| *arg != "--safe").cloned().collect();
+
+    match rest.as_slice() {
+        [operation, bits] if operation == "gen" => {
+            let bits = bits.parse::<u64>().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid bit count {}", bits)))?;
+            let prime = if safe { primes::safe_prime(bits)? } else { primes::random_prime(bits)? };
+            out.line(prime.to_string());
+            out.field("bits", prime.bits());
+            out.field("safe", safe);
+            out.field("prime", prime.to_string());
+        }
+        [operation, number] if operation == "test" && !safe => {
+            let number = big_number(number)?;
+            let prime = primes::is_probable_prime(&number, primes::DEFAULT_ROUNDS)?;
+            out.line(if prime {
+                tr!("{} is prime (Miller–Rabin, {} rounds)", number, primes::DEFAULT_ROUNDS)
+            } else {
+                tr!("{} is composite", number)
+            });
+            out.field("number", number.to_string());
+            out.field("prime", prime);
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
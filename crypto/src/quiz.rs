@@ -0,0 +1,269 @@
+//! `crypto quiz`: randomized questions (encrypt a word or a digraph, compute
+//! a DES round key, find a modular inverse) checked with the crate's own
+//! implementations, each score appended to a results file the teacher
+//! collects. `crypto learn` asks the same questions on its exercise screen.
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crypto_core::caesar::Caesar;
+use crypto_core::codec::encode_hex;
+use crypto_core::hill::mod_inverse;
+use crypto_core::railfence::RailFence;
+use crypto_core::rng;
+use crypto_core::vigenere::Vigenere;
+use crypto_core::{tr, ClassicalCipher, SymmetricCipher};
+use des::{Des, DesKeyGenerator};
+use playfair::Playfair;
+use serde_json::json;
+
+use crate::output::CommandOutput;
+use crate::{config, take_flag_values, usage_error};
+
+/// What a question asks for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Topic {
+    Caesar,
+    Vigenere,
+    Playfair,
+    RailFence,
+    RoundKey,
+    Inverse,
+}
+
+impl Topic {
+    pub(crate) const ALL: [Topic; 6] = [Topic::Caesar, Topic::Vigenere, Topic::Playfair, Topic::RailFence, Topic::RoundKey, Topic::Inverse];
+
+    pub(crate) fn parse(name: &str) -> io::Result<Self> {
+        match name {
+            "caesar" => Ok(Self::Caesar),
+            "vigenere" => Ok(Self::Vigenere),
+            "playfair" => Ok(Self::Playfair),
+            "railfence" => Ok(Self::RailFence),
+            "des" => Ok(Self::RoundKey),
+            "inverse" => Ok(Self::Inverse),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("Unknown quiz topic {} (expected caesar, vigenere, playfair, railfence, des or inverse)", name)
+            )),
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Caesar => "caesar",
+            Self::Vigenere => "vigenere",
+            Self::Playfair => "playfair",
+            Self::RailFence => "railfence",
+            Self::RoundKey => "des",
+            Self::Inverse => "inverse",
+        }
+    }
+}
+
+/// A question and the answer the implementations give
+pub(crate) struct Question {
+    pub(crate) topic: Topic,
+    pub(crate) prompt: String,
+    pub(crate) answer: String,
+}
+
+const WORDS: [&str; 10] = [
+    "CIPHER", "SECRET", "ATTACK", "LECTURE", "STUDENT", "MATRIX", "PLAINTEXT", "KEYWORD", "BUCHAREST", "CHISINAU",
+];
+
+fn random_word() -> io::Result<&'static str> {
+    Ok(WORDS[rng::below(WORDS.len())?])
+}
+
+impl Question {
+    /// A question on one of `topics`, picked at random
+    pub(crate) fn random(topics: &[Topic]) -> io::Result<Self> {
+        let topic = topics[rng::below(topics.len())?];
+        let word = random_word()?;
+        let (prompt, answer) = match topic {
+            Topic::Caesar => {
+                let shift = 1 + rng::below(25)? as u8;
+                let answer = Caesar::with_shift(shift).encrypt(word);
+                (tr!("Encrypt {} with a Caesar shift of {}.", word, shift), answer.unwrap_or_default())
+            }
+            Topic::Vigenere => {
+                let key = random_word()?;
+                let ciphertext = Vigenere::new(key).and_then(|cipher| cipher.encrypt(word)).unwrap_or_default();
+                (tr!("Decrypt the Vigenère ciphertext {} with the key {}.", ciphertext, key), word.to_string())
+            }
+            Topic::Playfair => {
+                let key = ["MONARCHY", "PLAYFAIR", "KEYWORDS", "ROMANIA"][rng::below(4)?];
+                let start = rng::below(word.len() - 1)?;
+                let digraph = &word[start..start + 2];
+                let answer = Playfair::new(key).and_then(|cipher| cipher.encrypt(digraph)).unwrap_or_default();
+                (tr!("Encrypt the digraph {} with the Playfair key {}.", digraph, key), answer)
+            }
+            Topic::RailFence => {
+                let rails = 2 + rng::below(2)?;
+                let answer = RailFence::with_offset(rails, 0).and_then(|cipher| cipher.encrypt(word)).unwrap_or_default();
+                (tr!("Encrypt {} on a rail fence of {} rails.", word, rails), answer)
+            }
+            Topic::RoundKey => {
+                let key = DesKeyGenerator::random().map_err(|e| io::Error::other(e.to_string()))?.raw_key().to_vec();
+                let round = 1 + rng::below(16)?;
+                let subkey = Des::new(&key).map(|cipher| cipher.subkeys()[round - 1]).unwrap_or_default();
+                (
+                    tr!("Compute the DES round key K{} of the key {} (hex), as 12 hex digits.", round, encode_hex(&key).to_uppercase()),
+                    format!("{:012X}", subkey),
+                )
+            }
+            Topic::Inverse => {
+                // Units modulo 26 are the odd numbers other than 13
+                let value = loop {
+                    let value = 1 + rng::below(25)? as i64;
+                    if mod_inverse(value, 26).is_some() {
+                        break value;
+                    }
+                };
+                let answer = mod_inverse(value, 26).unwrap_or_default().to_string();
+                (tr!("Find the inverse of {} modulo 26.", value), answer)
+            }
+        };
+        Ok(Question { topic, prompt, answer })
+    }
+
+    /// Case, spaces and punctuation do not count
+    pub(crate) fn is_correct(&self, answer: &str) -> bool {
+        let normalize = |text: &str| text.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_uppercase).collect::<String>();
+        normalize(answer) == normalize(&self.answer)
+    }
+}
+
+/// Append one line, `time,student,topics,score,questions`, to the CSV file
+/// at `path`, writing the header first when the file is new
+pub(crate) fn record(path: &Path, student: &str, topics: &[Topic], score: usize, questions: usize) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| io::Error::new(e.kind(), tr!("Cannot write {}: {}", path.display(), e)))?;
+    let mut lines = String::new();
+    if file.metadata()?.len() == 0 {
+        lines.push_str("time,student,topics,score,questions\n");
+    }
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or_default();
+    let topics: Vec<&str> = topics.iter().map(|topic| topic.name()).collect();
+    lines.push_str(&format!("{},{},{},{},{}\n", utc_time(seconds), csv_field(student), topics.join(" "), score, questions));
+    file.write_all(lines.as_bytes())
+}
+
+/// A CSV field, quoted when it holds a comma, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Seconds since the Unix epoch as `YYYY-MM-DDTHH:MM:SSZ`
+fn utc_time(seconds: u64) -> String {
+    // Civil-from-days (Howard Hinnant's algorithm)
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    let time = seconds % 86_400;
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, time / 3600, time / 60 % 60, time % 60)
+}
+
+/// Ask randomized questions on standard input, check each answer and append
+/// the score to the results file
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto quiz --student <name> [--questions <n>] [--topic caesar|vigenere|playfair|railfence|des|inverse]... [--results <file>]";
+    let (students, rest) = take_flag_values(args, "--student")?;
+    let (counts, rest) = take_flag_values(&rest, "--questions")?;
+    let (topic_names, rest) = take_flag_values(&rest, "--topic")?;
+    let (results, rest) = take_flag_values(&rest, "--results")?;
+    let (Some(student), true) = (students.last(), rest.is_empty()) else {
+        return Err(usage_error(USAGE));
+    };
+    let count = match counts.last() {
+        Some(count) => count.parse::<usize>().ok().filter(|&count| count > 0).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid question count {}", count))
+        })?,
+        None => 5,
+    };
+    let mut topics = topic_names.iter().map(|name| Topic::parse(name)).collect::<io::Result<Vec<_>>>()?;
+    if topics.is_empty() {
+        topics = Topic::ALL.to_vec();
+    }
+    let results = results.last().map(PathBuf::from).unwrap_or_else(|| config::settings().quiz_results.clone());
+
+    let mut lines = io::stdin().lines();
+    let mut answers = Vec::new();
+    let mut score = 0;
+    for number in 1..=count {
+        let question = Question::random(&topics)?;
+        let prompt = tr!("Question {} of {}: {}", number, count, question.prompt);
+        // JSON mode keeps standard output for the final document
+        if out.is_json() {
+            eprintln!("{}", prompt);
+        } else {
+            out.line(prompt);
+        }
+
+        let answer = lines.next().transpose()?.unwrap_or_default();
+        let correct = question.is_correct(&answer);
+        if correct {
+            score += 1;
+            out.line(tr!("Correct."));
+        } else {
+            out.line(tr!("Wrong; the answer is {}.", question.answer));
+        }
+        answers.push(json!({
+            "topic": question.topic.name(),
+            "question": question.prompt,
+            "answer": answer.trim(),
+            "expected": question.answer,
+            "correct": correct,
+        }));
+    }
+
+    record(&results, student, &topics, score, count)?;
+    out.line(tr!("Score: {} of {}, recorded in {}", score, count, results.display()));
+    out.field("student", student.as_str());
+    out.field("topics", topics.iter().map(|topic| topic.name()).collect::<Vec<_>>());
+    out.field("score", score);
+    out.field("questions", count);
+    out.field("answers", answers);
+    out.field("results", results.display().to_string());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results_lines_are_csv_with_utc_times() {
+        assert_eq!(utc_time(0), "1970-01-01T00:00:00Z");
+        assert_eq!(utc_time(951_827_696), "2000-02-29T12:34:56Z");
+        assert_eq!(csv_field("Popescu Ion"), "Popescu Ion");
+        assert_eq!(csv_field("Popescu, \"Ionel\""), "\"Popescu, \"\"Ionel\"\"\"");
+    }
+
+    #[test]
+    fn answers_are_checked_against_the_implementations() {
+        for topic in Topic::ALL {
+            let question = Question::random(&[topic]).unwrap();
+            assert_eq!(question.topic, topic);
+            assert!(question.is_correct(&question.answer.to_lowercase()), "{}", question.prompt);
+            assert!(!question.is_correct("?"));
+        }
+    }
+}
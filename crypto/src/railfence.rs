@@ -0,0 +1,51 @@
+//! `crypto railfence`: the rail fence cipher and a brute force over rail counts
+
+use std::io;
+
+use crypto_core::language::Language;
+use crypto_core::railfence::{self, RailFence};
+use crypto_core::{tr, ClassicalCipher};
+use serde_json::json;
+
+use crate::output::CommandOutput;
+use crate::{cipher_error, run_classical, take_flag_values, take_romanian, text_argument, usage_error};
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto railfence (encrypt | decrypt) --key <rails>[,<offset>] [<text>] | \
+                         crypto railfence brute [--max-rails <n>] [--romanian] [<text>]";
+    let (keys, rest) = take_flag_values(args, "--key")?;
+    let (max_rails, rest) = take_flag_values(&rest, "--max-rails")?;
+    let (romanian, rest) = take_romanian(&rest);
+    let language = if romanian { Language::Romanian } else { Language::English };
+    let Some((operation, rest)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+
+    match (operation.as_str(), keys.last()) {
+        ("brute", None) => {
+            let max_rails = match max_rails.last() {
+                Some(rails) => rails.parse::<usize>().ok().filter(|&rails| rails >= 2).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid rail count {}", rails))
+                })?,
+                None => 10,
+            };
+            let candidates = railfence::brute_force(&text_argument(rest, USAGE)?, max_rails, language);
+            for candidate in candidates.iter().take(5) {
+                out.line(format!("{:>2},{:<2} {:>7.3}  {}", candidate.rails, candidate.offset, candidate.score, candidate.plaintext));
+            }
+            out.field("candidates", candidates.iter().take(10).map(|candidate| json!({
+                "rails": candidate.rails,
+                "offset": candidate.offset,
+                "plaintext": candidate.plaintext,
+                "score": candidate.score,
+            })).collect::<Vec<_>>());
+        }
+        (_, Some(key)) => {
+            let cipher = RailFence::new(key).map_err(cipher_error)?;
+            return run_classical(&cipher, operation, rest, USAGE, out);
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+//! `crypto randtest`: NIST SP 800-22 style statistical tests of a bit sequence
+
+use std::io;
+
+use crypto_core::{randomness, rng, tr};
+use serde_json::json;
+
+use crate::output::CommandOutput;
+use crate::{hex_input, take_flag_values, text_argument, usage_error};
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto randtest [--file <path> | --os <bytes> | [--hex] <bits or hex>] [--block <m>]";
+    let (files, rest) = take_flag_values(args, "--file")?;
+    let (os_bytes, rest) = take_flag_values(&rest, "--os")?;
+    let (blocks, rest) = take_flag_values(&rest, "--block")?;
+    let hex = rest.iter().any(|arg| arg == "--hex");
+    let rest: Vec<String> = rest.into_iter().filter(|arg| arg != "--hex").collect();
+    let count = |value: &String| {
+        value.parse::<usize>().ok().filter(|&n| n > 0).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid count {}", value)))
+    };
+
+    let (bits, source) = match (files.last(), os_bytes.last()) {
+        (Some(path), None) if rest.is_empty() && !hex => (randomness::bits_of(&std::fs::read(path)?), path.clone()),
+        (None, Some(bytes)) if rest.is_empty() && !hex => (randomness::bits_of(&rng::bytes(count(bytes)?)?), "os".to_string()),
+        (None, None) => {
+            let text = text_argument(&rest, USAGE)?;
+            // Keystreams print as hex (rc4) or as 0s and 1s (lfsr)
+            match randomness::parse_bits(&text).filter(|_| !hex) {
+                Some(bits) => (bits, "bits".to_string()),
+                None => (randomness::bits_of(&hex_input(&text, tr!("data"))?), "hex".to_string()),
+            }
+        }
+        _ => return Err(usage_error(USAGE)),
+    };
+    if bits.len() < 2 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("Need at least two bits to test")));
+    }
+
+    let (serial_block, entropy_block) = match blocks.last() {
+        Some(block) => {
+            let block = count(block)?;
+            if block > 16 {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("The block length is at most 16")));
+            }
+            (block.max(2), block)
+        }
+        None => randomness::default_block_lengths(bits.len()),
+    };
+    let results = [
+        randomness::monobit(&bits),
+        randomness::runs(&bits),
+        randomness::serial(&bits, serial_block),
+        randomness::approximate_entropy(&bits, entropy_block),
+    ];
+    let entropy = randomness::entropy_estimate(&bits, 8.min(bits.len()));
+
+    out.line(tr!("Bits: {}", bits.len()));
+    if bits.len() < randomness::RECOMMENDED_BITS {
+        out.line(tr!("Warning: SP 800-22 needs at least {} bits for meaningful results", randomness::RECOMMENDED_BITS));
+    }
+    for result in &results {
+        let p_values: Vec<String> = result.p_values.iter().map(|p| format!("{:.6}", p)).collect();
+        out.line(tr!(
+            "{} statistic {}  p {} {}",
+            format!("{:<20}", result.name),
+            format!("{:>12.4}", result.statistic),
+            format!("{:<18}", p_values.join(", ")),
+            if result.passed() { tr!("PASS") } else { tr!("FAIL") },
+        ));
+    }
+    out.line(tr!(
+        "Entropy per bit ({}-bit blocks): Shannon {}, min-entropy {}",
+        entropy.block,
+        format!("{:.4}", entropy.shannon),
+        format!("{:.4}", entropy.min),
+    ));
+
+    let passed = results.iter().all(|result| result.passed());
+    out.line(if passed { tr!("All tests passed at α = 0.01") } else { tr!("Not random at α = 0.01") });
+    out.field("source", source);
+    out.field("bits", bits.len());
+    out.field("tests", results.iter().map(|result| json!({
+        "name": result.name,
+        "statistic": result.statistic,
+        "p_values": result.p_values,
+        "passed": result.passed(),
+    })).collect::<Vec<_>>());
+    out.field("entropy", json!({ "block": entropy.block, "shannon": entropy.shannon, "min": entropy.min }));
+    out.field("passed", passed);
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+//! `crypto rc4`: the RC4 stream cipher and the zero bias in its first output bytes
+
+use std::io;
+
+use crypto_core::codec::encode_hex;
+use crypto_core::rc4::{self, Rc4};
+use crypto_core::{tr, SymmetricCipher};
+
+use crate::output::CommandOutput;
+use crate::{cipher_error, key_bytes, run_symmetric, take_flag_values, usage_error};
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto rc4 (encrypt | decrypt) --key <key> [--hex] [<text>] | \
+                         crypto rc4 keystream --key <key> [--hex] [--bytes <n>] | crypto rc4 bias [--trials <n>]";
+    let (keys, rest) = take_flag_values(args, "--key")?;
+    let (sizes, rest) = take_flag_values(&rest, "--bytes")?;
+    let (trials, rest) = take_flag_values(&rest, "--trials")?;
+    let hex = rest.iter().any(|arg| arg == "--hex");
+    let rest: Vec<String> = rest.into_iter().filter(|arg| arg != "--hex").collect();
+    let Some((operation, rest)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+    let number = |values: &[String], default: usize| match values.last() {
+        Some(value) => value.parse::<usize>().ok().filter(|&value| value > 0).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid count {}", value))
+        }),
+        None => Ok(default),
+    };
+
+    match (operation.as_str(), keys.last()) {
+        ("keystream", Some(key)) if rest.is_empty() => {
+            let cipher = Rc4::new(&key_bytes(key, hex)?).map_err(cipher_error)?;
+            let keystream = encode_hex(&cipher.keystream(number(&sizes, 32)?));
+            out.line(&keystream);
+            out.field("keystream", keystream);
+        }
+        ("bias", None) if rest.is_empty() => {
+            let bias = rc4::zero_byte_bias(number(&trials, 100_000)?, 8)?;
+            out.line(tr!("Zero bytes per keystream position over {} random keys (unbiased: {})", bias.trials, format!("{:.5}", 1.0 / 256.0)));
+            for position in 1..=bias.zeros.len() {
+                out.line(tr!("  byte {}: {}", position, format!("{:.5}", bias.frequency(position))));
+            }
+            out.line(tr!("The second byte is zero about twice as often as it should be."));
+            out.field("trials", bias.trials);
+            out.field("zero_frequencies", (1..=bias.zeros.len()).map(|position| bias.frequency(position)).collect::<Vec<_>>());
+        }
+        (_, Some(key)) => {
+            let cipher = Rc4::new(&key_bytes(key, hex)?).map_err(cipher_error)?;
+            return run_symmetric(&cipher, operation, rest, USAGE, out);
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,562 @@
+//! `crypto report`: a self-contained HTML page that works through one
+//! encryption step by step (shifted alphabet, key stream, key matrix,
+//! digraphs, zigzag, columns, DES rounds), for students to attach to their
+//! homework
+
+use std::io;
+
+use crypto_core::caesar::Caesar;
+use crypto_core::codec::encode_hex;
+use crypto_core::columnar::{column_order, Columnar};
+use crypto_core::hill::{self, Hill};
+use crypto_core::i18n;
+use crypto_core::language::Alphabet;
+use crypto_core::modes::pad_pkcs7;
+use crypto_core::railfence::RailFence;
+use crypto_core::vigenere::Vigenere;
+use crypto_core::{tr, CipherError, ClassicalCipher, SymmetricCipher};
+use des::{Des, DesKeyGenerator};
+use playfair::Playfair;
+
+use crate::output::CommandOutput;
+use crate::{key_bytes, take_flag_values, take_romanian, text_argument, usage_error};
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em auto;max-width:60em;color:#222}\
+table{border-collapse:collapse;margin-bottom:1.5em}\
+th,td{border:1px solid #ccc;padding:.25em .6em;text-align:center;font-family:monospace}\
+th{background:#f0f0f0}\
+.summary td,.summary th{text-align:left}\
+.result{color:#1a7f37;font-weight:bold;font-family:monospace}";
+
+/// The ciphers a report can work through
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ReportCipher {
+    Caesar,
+    Vigenere,
+    Hill,
+    RailFence,
+    Columnar,
+    Playfair,
+    Des,
+}
+
+impl ReportCipher {
+    pub(crate) fn parse(name: &str) -> io::Result<Self> {
+        match name {
+            "caesar" => Ok(Self::Caesar),
+            "vigenere" => Ok(Self::Vigenere),
+            "hill" => Ok(Self::Hill),
+            "railfence" => Ok(Self::RailFence),
+            "columnar" => Ok(Self::Columnar),
+            "playfair" => Ok(Self::Playfair),
+            "des" => Ok(Self::Des),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                tr!("Unknown report cipher {} (expected caesar, vigenere, hill, railfence, columnar, playfair or des)", name)
+            )),
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Caesar => "caesar",
+            Self::Vigenere => "vigenere",
+            Self::Hill => "hill",
+            Self::RailFence => "railfence",
+            Self::Columnar => "columnar",
+            Self::Playfair => "playfair",
+            Self::Des => "des",
+        }
+    }
+}
+
+/// A finished report: the HTML page and the ciphertext it arrives at
+pub(crate) struct Report {
+    pub(crate) html: String,
+    pub(crate) ciphertext: String,
+}
+
+/// Work through encrypting `text` under `key` with one of the classical
+/// ciphers; `alphabet` is the one Hill works over
+pub(crate) fn classical(cipher: ReportCipher, key: &str, text: &str, alphabet: Alphabet) -> io::Result<Report> {
+    let mut page = Page::default();
+    let ciphertext = match cipher {
+        ReportCipher::Caesar => {
+            let cipher = Caesar::new(key).map_err(invalid)?;
+            caesar_steps(&mut page, &cipher, text);
+            cipher.encrypt(text)
+        }
+        ReportCipher::Vigenere => {
+            let cipher = Vigenere::new(key).map_err(invalid)?;
+            vigenere_steps(&mut page, &cipher, text);
+            cipher.encrypt(text)
+        }
+        ReportCipher::Hill => {
+            let cipher = Hill::with_alphabet(key, alphabet).map_err(invalid)?;
+            hill_steps(&mut page, &cipher, alphabet, text);
+            cipher.encrypt(text)
+        }
+        ReportCipher::RailFence => {
+            let cipher = RailFence::new(key).map_err(invalid)?;
+            rail_fence_steps(&mut page, &cipher, text);
+            cipher.encrypt(text)
+        }
+        ReportCipher::Columnar => {
+            let cipher = Columnar::new(key).map_err(invalid)?;
+            columnar_steps(&mut page, key, text);
+            cipher.encrypt(text)
+        }
+        ReportCipher::Playfair => {
+            let cipher = Playfair::new(key).map_err(invalid)?;
+            // Playfair takes letters only; spaces between words are dropped
+            let letters: String = text.chars().filter(|c| c.is_alphabetic()).flat_map(char::to_uppercase).collect();
+            playfair_steps(&mut page, &cipher, &letters);
+            cipher.encrypt(&letters)
+        }
+        ReportCipher::Des => unreachable!("DES reports take a binary key; see des()"),
+    }
+    .map_err(invalid)?;
+
+    let title = tr!("{} worked example", cipher_title(cipher));
+    Ok(Report { html: page.finish(&title, key, text, &ciphertext), ciphertext })
+}
+
+/// Work through encrypting `text` with DES in ECB mode under `key`
+pub(crate) fn des(key: &[u8], text: &str) -> io::Result<Report> {
+    let cipher = Des::new(key).map_err(invalid)?;
+    let mut page = Page::default();
+    des_steps(&mut page, &cipher, key, text.as_bytes())?;
+    let ciphertext = encode_hex(&cipher.encrypt(text.as_bytes()));
+
+    let title = tr!("{} worked example", cipher_title(ReportCipher::Des));
+    Ok(Report { html: page.finish(&title, &encode_hex(key), text, &ciphertext), ciphertext })
+}
+
+fn cipher_title(cipher: ReportCipher) -> &'static str {
+    match cipher {
+        ReportCipher::Caesar => tr!("Caesar cipher"),
+        ReportCipher::Vigenere => tr!("Vigenère cipher"),
+        ReportCipher::Hill => tr!("Hill cipher"),
+        ReportCipher::RailFence => tr!("Rail fence cipher"),
+        ReportCipher::Columnar => tr!("Columnar transposition"),
+        ReportCipher::Playfair => tr!("Playfair cipher"),
+        ReportCipher::Des => "DES",
+    }
+}
+
+fn invalid(error: CipherError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, error.to_string())
+}
+
+/// An HTML document built up section by section
+#[derive(Default)]
+struct Page {
+    body: String,
+}
+
+impl Page {
+    fn section(&mut self, title: &str) {
+        self.body.push_str(&format!("<h2>{}</h2>\n", escape_html(title)));
+    }
+
+    fn paragraph(&mut self, text: &str) {
+        self.body.push_str(&format!("<p>{}</p>\n", escape_html(text)));
+    }
+
+    /// A table with `header` as its first row, unless empty
+    fn table(&mut self, header: &[String], rows: &[Vec<String>]) {
+        self.body.push_str("<table>");
+        if !header.is_empty() {
+            self.body.push_str("<tr>");
+            for cell in header {
+                self.body.push_str(&format!("<th>{}</th>", escape_html(cell)));
+            }
+            self.body.push_str("</tr>");
+        }
+        for row in rows {
+            self.body.push_str("<tr>");
+            for cell in row {
+                self.body.push_str(&format!("<td>{}</td>", escape_html(cell)));
+            }
+            self.body.push_str("</tr>\n");
+        }
+        self.body.push_str("</table>\n");
+    }
+
+    /// The whole document: a summary, the steps so far and the result
+    fn finish(self, title: &str, key: &str, text: &str, ciphertext: &str) -> String {
+        let mut html = String::new();
+        html.push_str(&format!("<!DOCTYPE html>\n<html lang=\"{}\"><head><meta charset=\"utf-8\">", i18n::lang().code()));
+        html.push_str(&format!("<title>{}</title><style>{}</style></head><body>\n", escape_html(title), STYLE));
+        html.push_str(&format!("<h1>{}</h1>\n", escape_html(title)));
+        html.push_str("<table class=\"summary\">");
+        for (label, value) in [(tr!("Key"), key), (tr!("Plaintext"), text), (tr!("Ciphertext"), ciphertext)] {
+            html.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>", escape_html(label), escape_html(value)));
+        }
+        html.push_str("</table>\n");
+        html.push_str(&self.body);
+        html.push_str(&format!("<h2>{}</h2>\n<p class=\"result\">{}</p>\n", escape_html(tr!("Result")), escape_html(ciphertext)));
+        html.push_str("</body></html>\n");
+        html
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Position of an ASCII letter in the alphabet, and the base of its case
+fn latin_letter(c: char) -> Option<(u8, u8)> {
+    match c {
+        'A'..='Z' => Some((c as u8 - b'A', b'A')),
+        'a'..='z' => Some((c as u8 - b'a', b'a')),
+        _ => None,
+    }
+}
+
+/// Returns the ciphertext the steps arrive at
+fn caesar_steps(page: &mut Page, cipher: &Caesar, text: &str) -> String {
+    let shift = cipher.shift();
+    let plain: Vec<String> = ('A'..='Z').map(String::from).collect();
+    let shifted: Vec<String> = (0..26).map(|index| ((b'A' + (index + shift) % 26) as char).to_string()).collect();
+    page.section(tr!("Shifted alphabet"));
+    page.table(&plain, &[shifted]);
+
+    page.section(tr!("Letter by letter"));
+    page.paragraph(&tr!("Each letter p becomes (p + {}) mod 26; other characters are copied unchanged.", shift));
+    let mut rows = Vec::new();
+    let ciphertext: String = text
+        .chars()
+        .map(|c| {
+            let Some((index, base)) = latin_letter(c) else {
+                return c;
+            };
+            let encrypted = (base + (index + shift) % 26) as char;
+            rows.push(vec![c.to_string(), index.to_string(), ((index + shift) % 26).to_string(), encrypted.to_string()]);
+            encrypted
+        })
+        .collect();
+    page.table(&[tr!("Plain").to_string(), String::from("p"), format!("(p + {}) mod 26", shift), tr!("Cipher").to_string()], &rows);
+    ciphertext
+}
+
+fn vigenere_steps(page: &mut Page, cipher: &Vigenere, text: &str) -> String {
+    let key: Vec<u8> = cipher.key().bytes().map(|letter| letter - b'A').collect();
+    page.section(tr!("Key stream"));
+    page.paragraph(&tr!(
+        "The key {} is repeated under the letters; each letter p with key letter k becomes (p + k) mod 26. Other characters use up no key letter.",
+        cipher.key()
+    ));
+
+    let mut rows = Vec::new();
+    let mut stream = key.iter().cycle();
+    let ciphertext: String = text
+        .chars()
+        .map(|c| {
+            let Some((index, base)) = latin_letter(c) else {
+                return c;
+            };
+            let shift = stream.next().copied().unwrap_or_default();
+            let encrypted = (base + (index + shift) % 26) as char;
+            rows.push(vec![
+                c.to_string(),
+                index.to_string(),
+                ((b'A' + shift) as char).to_string(),
+                shift.to_string(),
+                ((index + shift) % 26).to_string(),
+                encrypted.to_string(),
+            ]);
+            encrypted
+        })
+        .collect();
+    page.table(
+        &[tr!("Plain"), "p", tr!("Key"), "k", "(p + k) mod 26", tr!("Cipher")].map(String::from),
+        &rows,
+    );
+    ciphertext
+}
+
+fn matrix_rows(matrix: &[Vec<i64>]) -> Vec<Vec<String>> {
+    matrix.iter().map(|row| row.iter().map(i64::to_string).collect()).collect()
+}
+
+fn hill_steps(page: &mut Page, cipher: &Hill, alphabet: Alphabet, text: &str) -> String {
+    let modulus = alphabet.size() as i64;
+    let key = cipher.key();
+    let size = key.len();
+
+    page.section(tr!("Key matrix"));
+    page.table(&[], &matrix_rows(key));
+    page.paragraph(&tr!(
+        "det K ≡ {} (mod {}) has an inverse modulo {}, so K is invertible; decryption multiplies by K⁻¹:",
+        hill::determinant(key, modulus),
+        modulus,
+        modulus
+    ));
+    page.table(&[], &matrix_rows(cipher.inverse()));
+
+    // Letters of the alphabet as numbers, padded with X to whole blocks
+    let mut values: Vec<i64> = text.chars().filter_map(|c| alphabet.index(c)).map(|index| index as i64).collect();
+    while !values.len().is_multiple_of(size) {
+        values.push(alphabet.index('X').unwrap_or_default() as i64);
+    }
+
+    page.section(tr!("Blocks"));
+    page.paragraph(&tr!(
+        "The letters become numbers ({} = 0, {} = 1, ...) taken {} at a time as column vectors p; the last block is padded with X, and each block becomes c = K·p mod {}.",
+        alphabet.letter(0),
+        alphabet.letter(1),
+        size,
+        modulus
+    ));
+    let letters = |block: &[i64]| block.iter().map(|&value| alphabet.letter(value as usize)).collect::<String>();
+    let vector = |block: &[i64]| format!("({})", block.iter().map(i64::to_string).collect::<Vec<_>>().join(", "));
+    let mut ciphertext = String::new();
+    let mut rows = Vec::new();
+    for block in values.chunks(size) {
+        let products: Vec<i64> = key.iter().map(|row| row.iter().zip(block).map(|(k, p)| k * p).sum()).collect();
+        let working: Vec<String> = key
+            .iter()
+            .zip(&products)
+            .map(|(row, product)| {
+                let terms: Vec<String> = row.iter().zip(block).map(|(k, p)| format!("{}·{}", k, p)).collect();
+                format!("{} = {}", terms.join(" + "), product)
+            })
+            .collect();
+        let encrypted: Vec<i64> = products.iter().map(|product| product.rem_euclid(modulus)).collect();
+        ciphertext.push_str(&letters(&encrypted));
+        rows.push(vec![letters(block), vector(block), working.join("; "), vector(&encrypted), letters(&encrypted)]);
+    }
+    page.table(&[tr!("Block"), "p", "K·p", "c", tr!("Cipher")].map(String::from), &rows);
+    ciphertext
+}
+
+fn rail_fence_steps(page: &mut Page, cipher: &RailFence, text: &str) -> String {
+    let chars: Vec<char> = text.chars().map(|c| if c == ' ' { '_' } else { c }).collect();
+    page.section(tr!("Zigzag"));
+    page.paragraph(&tr!(
+        "The text is written diagonally down and up over {} rails, starting {} places into the zigzag (spaces shown as _).",
+        cipher.rails(),
+        cipher.offset()
+    ));
+    let grid: Vec<Vec<String>> = (0..cipher.rails())
+        .map(|rail| {
+            std::iter::once(tr!("Rail {}", rail + 1))
+                .chain(chars.iter().enumerate().map(|(position, c)| if cipher.rail(position) == rail { c.to_string() } else { String::new() }))
+                .collect()
+        })
+        .collect();
+    page.table(&[], &grid);
+
+    page.section(tr!("Reading the rails"));
+    page.paragraph(tr!("The ciphertext reads the rails from top to bottom, each left to right."));
+    let mut ciphertext = String::new();
+    let mut rows = Vec::new();
+    for rail in 0..cipher.rails() {
+        let read: String = text.chars().enumerate().filter(|&(position, _)| cipher.rail(position) == rail).map(|(_, c)| c).collect();
+        ciphertext.push_str(&read);
+        rows.push(vec![(rail + 1).to_string(), read.replace(' ', "_")]);
+    }
+    page.table(&[tr!("Rail"), tr!("Characters")].map(String::from), &rows);
+    ciphertext
+}
+
+fn columnar_steps(page: &mut Page, key: &str, text: &str) -> String {
+    let keywords: Vec<&str> = key.split_whitespace().collect();
+    let mut letters: Vec<char> = text.chars().filter(|c| c.is_alphabetic()).flat_map(char::to_uppercase).collect();
+    if keywords.len() > 1 {
+        page.paragraph(tr!("Two keywords: the output of the first transposition is transposed again under the second."));
+    }
+
+    for keyword in keywords {
+        let order = column_order(keyword);
+        let columns = order.len();
+        let mut ranks = vec![0; columns];
+        for (rank, &column) in order.iter().enumerate() {
+            ranks[column] = rank + 1;
+        }
+
+        page.section(&tr!("Keyword {}", keyword.to_uppercase()));
+        page.paragraph(&tr!(
+            "Only letters are kept. They are written in rows of {} under the keyword, and the columns are read off in the alphabetical order of its letters (numbered below it).",
+            columns
+        ));
+        let header: Vec<String> = keyword.chars().map(|c| c.to_ascii_uppercase().to_string()).collect();
+        let mut rows = vec![ranks.iter().map(usize::to_string).collect::<Vec<_>>()];
+        rows.extend(letters.chunks(columns).map(|row| row.iter().map(char::to_string).collect()));
+        page.table(&header, &rows);
+
+        let mut read = Vec::new();
+        let mut reading = Vec::new();
+        for (rank, &column) in order.iter().enumerate() {
+            let taken: String = letters.iter().skip(column).step_by(columns).collect();
+            reading.push(vec![(rank + 1).to_string(), header[column].clone(), taken.clone()]);
+            read.extend(taken.chars());
+        }
+        page.table(&[tr!("Order"), tr!("Column"), tr!("Letters")].map(String::from), &reading);
+        page.paragraph(&tr!("After this transposition: {}", read.iter().collect::<String>()));
+        letters = read;
+    }
+    letters.into_iter().collect()
+}
+
+fn playfair_steps(page: &mut Page, cipher: &Playfair, letters: &str) -> String {
+    let matrix = cipher.matrix();
+    page.section(tr!("Matrix"));
+    page.paragraph(tr!("The key's letters come first without repeats, then the rest of the alphabet; J is merged into I."));
+    page.table(&[], &matrix.iter().map(|row| row.iter().map(char::to_string).collect()).collect::<Vec<_>>());
+
+    let mut prepared: Vec<char> = letters.replace('J', "I").chars().collect();
+    if !prepared.len().is_multiple_of(2) {
+        prepared.push('X');
+    }
+    page.section(tr!("Digraphs"));
+    page.paragraph(&tr!("Only letters are kept, split into pairs and padded with X: {}", prepared.iter().collect::<String>()));
+
+    let position = |c: char| {
+        matrix.iter().enumerate().find_map(|(row, letters)| letters.iter().position(|&letter| letter == c).map(|column| (row, column)))
+    };
+    let rows = matrix.len();
+    let mut ciphertext = String::new();
+    let mut table = Vec::new();
+    for pair in prepared.chunks(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let (rule, encrypted, positions) = match (position(a), position(b)) {
+            (Some((r1, c1)), Some((r2, c2))) => {
+                let positions = format!("({}, {}) ({}, {})", r1 + 1, c1 + 1, r2 + 1, c2 + 1);
+                if r1 == r2 {
+                    (tr!("same row: take the letters to the right"), [matrix[r1][(c1 + 1) % 5], matrix[r2][(c2 + 1) % 5]], positions)
+                } else if c1 == c2 {
+                    (tr!("same column: take the letters below"), [matrix[(r1 + 1) % rows][c1], matrix[(r2 + 1) % rows][c2]], positions)
+                } else {
+                    (tr!("rectangle: take the other two corners"), [matrix[r1][c2], matrix[r2][c1]], positions)
+                }
+            }
+            _ => (tr!("not in the matrix: copied"), [a, b], String::new()),
+        };
+        ciphertext.extend(encrypted);
+        table.push(vec![format!("{}{}", a, b), positions, rule.to_string(), encrypted.iter().collect()]);
+    }
+    page.table(&[tr!("Pair"), tr!("Row, column"), tr!("Rule"), tr!("Cipher")].map(String::from), &table);
+    ciphertext
+}
+
+fn des_steps(page: &mut Page, cipher: &Des, key: &[u8], plaintext: &[u8]) -> io::Result<String> {
+    let key_gen = DesKeyGenerator::new(key).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+    page.section(tr!("Key schedule"));
+    page.paragraph(&tr!(
+        "PC-1 drops the parity bits: K+ = {} (56 bits). Round key Ki is PC-2 of K+'s halves, rotated left once or twice per round.",
+        format!("{:014X}", key_gen.k_plus())
+    ));
+    let subkeys: Vec<Vec<String>> = cipher.subkeys().iter().enumerate().map(|(round, subkey)| vec![format!("K{}", round + 1), format!("{:012X}", subkey)]).collect();
+    page.table(&[tr!("Round"), tr!("Round key")].map(String::from), &subkeys);
+
+    let padded = pad_pkcs7(plaintext, 8);
+    page.section(tr!("Blocks"));
+    page.paragraph(&tr!(
+        "The text is padded with PKCS#7 to {} whole 8-byte blocks, each encrypted on its own (ECB): {}",
+        padded.len() / 8,
+        encode_hex(&padded)
+    ));
+
+    let mut ciphertext = Vec::new();
+    for (index, block) in padded.chunks(8).enumerate() {
+        let block = u64::from_be_bytes(block.try_into().expect("8 bytes"));
+        let trace = cipher.trace_encryption(block);
+        page.section(&tr!("Block {}: {}", index + 1, format!("{:016X}", block)));
+        page.paragraph(tr!("After IP the block is split into L0 and R0; each round sets L(i) = R(i-1) and R(i) = L(i-1) ⊕ f(R(i-1), K(i))."));
+        let mut rows = vec![vec![String::from("IP"), format!("{:08X}", trace.permuted >> 32), format!("{:08X}", trace.permuted & 0xFFFF_FFFF)]];
+        rows.extend(trace.rounds.iter().enumerate().map(|(round, (left, right))| vec![(round + 1).to_string(), format!("{:08X}", left), format!("{:08X}", right)]));
+        page.table(&[tr!("Round"), "L", "R"].map(String::from), &rows);
+        page.paragraph(&tr!("The final permutation of R16 ‖ L16 gives {}", format!("{:016X}", trace.output)));
+        ciphertext.extend(trace.output.to_be_bytes());
+    }
+    Ok(encode_hex(&ciphertext))
+}
+
+/// A worked example of one encryption as an HTML page, printed or written
+/// to `--out`
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto report --cipher <name> --key <key> [--hex] [--romanian] [--out <file>] [--text <text> | <text>]";
+    let (ciphers, rest) = take_flag_values(args, "--cipher")?;
+    let (keys, rest) = take_flag_values(&rest, "--key")?;
+    let (texts, rest) = take_flag_values(&rest, "--text")?;
+    let (outputs, rest) = take_flag_values(&rest, "--out")?;
+    let (romanian, rest) = take_romanian(&rest);
+    let hex = rest.iter().any(|arg| arg == "--hex");
+    let rest: Vec<String> = rest.into_iter().filter(|arg| arg != "--hex").collect();
+    let (Some(cipher), Some(key)) = (ciphers.last(), keys.last()) else {
+        return Err(usage_error(USAGE));
+    };
+    let text = match texts.last() {
+        Some(text) if rest.is_empty() => text.clone(),
+        Some(_) => return Err(usage_error(USAGE)),
+        None => text_argument(&rest, USAGE)?,
+    };
+
+    let cipher = ReportCipher::parse(cipher)?;
+    let report = match cipher {
+        ReportCipher::Des => des(&key_bytes(key, hex)?, &text)?,
+        _ => {
+            let alphabet = if romanian { Alphabet::Romanian } else { Alphabet::Latin };
+            classical(cipher, key, &text, alphabet)?
+        }
+    };
+
+    out.field("cipher", cipher.name());
+    out.field("result", report.ciphertext.as_str());
+    match outputs.last() {
+        Some(path) => {
+            std::fs::write(path, &report.html)?;
+            out.line(tr!("Wrote the {} worked example to {}", cipher.name(), path));
+            out.field("output", path.as_str());
+        }
+        None => {
+            out.line(report.html.trim_end());
+            out.field("html", report.html);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_steps_arrive_at_the_ciphers_own_result() {
+        let text = "Meet me at the library, at ten";
+        let mut page = Page::default();
+        let caesar = Caesar::new("3").unwrap();
+        assert_eq!(caesar_steps(&mut page, &caesar, text), caesar.encrypt(text).unwrap());
+        let vigenere = Vigenere::new("LEMON").unwrap();
+        assert_eq!(vigenere_steps(&mut page, &vigenere, text), vigenere.encrypt(text).unwrap());
+        for (key, alphabet) in [("3 3 2 5", Alphabet::Latin), ("6 24 1 13 16 10 20 17 15", Alphabet::Latin), ("3 3 2 5", Alphabet::Romanian)] {
+            let hill = Hill::with_alphabet(key, alphabet).unwrap();
+            assert_eq!(hill_steps(&mut page, &hill, alphabet, text), hill.encrypt(text).unwrap());
+        }
+        for key in ["3", "4,2"] {
+            let rail_fence = RailFence::new(key).unwrap();
+            assert_eq!(rail_fence_steps(&mut page, &rail_fence, text), rail_fence.encrypt(text).unwrap());
+        }
+        for key in ["ZEBRAS", "ZEBRAS STRIPE"] {
+            assert_eq!(columnar_steps(&mut page, key, text), Columnar::new(key).unwrap().encrypt(text).unwrap());
+        }
+        let playfair = Playfair::new("MONARCHY").unwrap();
+        for letters in ["INSTRUMENTSJ", "BALLOON"] {
+            assert_eq!(playfair_steps(&mut page, &playfair, letters), playfair.encrypt(letters).unwrap());
+        }
+        let des = Des::new(b"13345779").unwrap();
+        assert_eq!(des_steps(&mut page, &des, b"13345779", text.as_bytes()).unwrap(), encode_hex(&des.encrypt(text.as_bytes())));
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        let report = classical(ReportCipher::Caesar, "1", "<b>&", Alphabet::Latin).unwrap();
+        assert_eq!(report.ciphertext, "<c>&");
+        assert!(report.html.contains("&lt;c&gt;&amp;") && !report.html.contains("<c>"));
+    }
+}
@@ -0,0 +1,145 @@
+//! `crypto rsa`: textbook and padded RSA with keys stored as JSON
+
+use std::io;
+
+use crypto_core::codec::encode_hex;
+use crypto_core::rsa::{self, RsaPrivateKey};
+use crypto_core::tr;
+use num_bigint::BigUint;
+use serde_json::json;
+
+use crate::output::CommandOutput;
+use crate::{cipher_error, hex_input, key_path, read_key_json, take_flag_values, text_argument, usage_error};
+
+/// RSA key files are JSON with the numbers in hex
+fn write_rsa_key(path: &str, key: &RsaPrivateKey) -> io::Result<()> {
+    let json = json!({
+        "n": key.n.to_str_radix(16),
+        "e": key.e.to_str_radix(16),
+        "d": key.d.to_str_radix(16),
+        "p": key.p.to_str_radix(16),
+        "q": key.q.to_str_radix(16),
+    });
+    std::fs::write(key_path(path), format!("{:#}\n", json))
+}
+
+fn read_rsa_key(path: &str) -> io::Result<RsaPrivateKey> {
+    let (json, name) = read_key_json(path)?;
+    let invalid = |field: &str| io::Error::new(io::ErrorKind::InvalidData, tr!("{} has no valid {}", name, field));
+    let number = |field: &str| {
+        json[field].as_str().and_then(|hex| BigUint::parse_bytes(hex.as_bytes(), 16)).ok_or_else(|| invalid(field))
+    };
+    Ok(RsaPrivateKey { n: number("n")?, e: number("e")?, d: number("d")?, p: number("p")?, q: number("q")? })
+}
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto rsa keygen [--bits <n>] [--exponent <e>] <key.json> | \
+                         crypto rsa (encrypt | decrypt) --key <key.json> [--raw] [<text>] | \
+                         crypto rsa sign --key <key.json> [<text>] | \
+                         crypto rsa verify --key <key.json> --signature <hex> [<text>] | \
+                         crypto rsa attack (small-factor | low-exponent) [<text>]";
+    let (keys, rest) = take_flag_values(args, "--key")?;
+    let (bits, rest) = take_flag_values(&rest, "--bits")?;
+    let (exponents, rest) = take_flag_values(&rest, "--exponent")?;
+    let (signatures, rest) = take_flag_values(&rest, "--signature")?;
+    let raw = rest.iter().any(|arg| arg == "--raw");
+    let rest: Vec<String> = rest.into_iter().filter(|arg| arg != "--raw").collect();
+    let Some((operation, rest)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+    let number = |values: &[String], what: &str, default: u64| match values.last() {
+        Some(value) => value.parse::<u64>().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid {} {}", what, value))),
+        None => Ok(default),
+    };
+    let hex_argument = |text: &str| hex_input(text, tr!("argument"));
+
+    match (operation.as_str(), keys.last()) {
+        ("keygen", None) => {
+            let [path] = rest else {
+                return Err(usage_error(USAGE));
+            };
+            let exponent = u32::try_from(number(&exponents, tr!("exponent"), rsa::DEFAULT_EXPONENT as u64)?)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, tr!("The exponent must fit in 32 bits")))?;
+            let key = RsaPrivateKey::generate_with_exponent(number(&bits, tr!("bit count"), 2048)?, exponent)?;
+            write_rsa_key(path, &key)?;
+
+            out.line(tr!("Wrote a {}-bit key with e = {} to {}", key.n.bits(), key.e, path));
+            out.field("key", path.as_str());
+            out.field("bits", key.n.bits());
+            out.field("n", key.n.to_str_radix(16));
+            out.field("e", key.e.to_str_radix(16));
+        }
+        ("encrypt", Some(path)) => {
+            let public = read_rsa_key(path)?.public();
+            let plaintext = text_argument(rest, USAGE)?;
+            let ciphertext = if raw {
+                let ciphertext = public.encrypt_raw(&BigUint::from_bytes_be(plaintext.as_bytes())).map_err(cipher_error)?;
+                ciphertext.to_bytes_be()
+            } else {
+                public.encrypt_oaep(plaintext.as_bytes())?
+            };
+            out.line(encode_hex(&ciphertext));
+            out.field("padding", if raw { "none" } else { "oaep" });
+            out.field("result", encode_hex(&ciphertext));
+        }
+        ("decrypt", Some(path)) => {
+            let key = read_rsa_key(path)?;
+            let ciphertext = hex_argument(&text_argument(rest, USAGE)?)?;
+            let plaintext = if raw {
+                key.decrypt_raw(&BigUint::from_bytes_be(&ciphertext)).map_err(cipher_error)?.to_bytes_be()
+            } else {
+                key.decrypt_oaep(&ciphertext).map_err(cipher_error)?
+            };
+            let plaintext = String::from_utf8_lossy(&plaintext).into_owned();
+            out.line(&plaintext);
+            out.field("result", plaintext);
+        }
+        ("sign", Some(path)) => {
+            let signature = encode_hex(&read_rsa_key(path)?.sign(text_argument(rest, USAGE)?.as_bytes()));
+            out.line(&signature);
+            out.field("signature", signature);
+        }
+        ("verify", Some(path)) => {
+            let signature = hex_argument(signatures.last().ok_or_else(|| usage_error(USAGE))?)?;
+            let valid = read_rsa_key(path)?.public().verify(text_argument(rest, USAGE)?.as_bytes(), &signature);
+            out.line(if valid { tr!("Signature valid") } else { tr!("Signature INVALID") });
+            out.field("valid", valid);
+            if !valid {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, tr!("Signature verification failed")));
+            }
+        }
+        ("attack", None) => match rest {
+            [kind] if kind == "small-factor" => {
+                // One prime far too small: trial division finds it at once
+                let weak = RsaPrivateKey::from_primes(BigUint::from(65_537u32), RsaPrivateKey::generate(1024)?.p, rsa::DEFAULT_EXPONENT.into())
+                    .map_err(cipher_error)?;
+                let recovered = rsa::factor_small(&weak.public(), 1 << 20)
+                    .ok_or_else(|| io::Error::other(tr!("No small factor found")))?;
+                out.line(tr!("n = {} ({} bits)", weak.n.to_str_radix(16), weak.n.bits()));
+                out.line(tr!("Trial division finds p = {}", recovered.p));
+                out.line(tr!("Recovered d matches the real one: {}", recovered.d == weak.d));
+                out.field("p", recovered.p.to_str_radix(16));
+                out.field("recovered", recovered.d == weak.d);
+            }
+            [kind, rest @ ..] if kind == "low-exponent" => {
+                let message = text_argument(rest, USAGE)?;
+                let key = RsaPrivateKey::generate_with_exponent(2048, 3)?;
+                let ciphertext = key.public().encrypt_raw(&BigUint::from_bytes_be(message.as_bytes())).map_err(cipher_error)?;
+                let recovered = rsa::low_exponent_root(&key.public(), &ciphertext)
+                    .map(|root| String::from_utf8_lossy(&root.to_bytes_be()).into_owned());
+
+                out.line(tr!("e = 3, unpadded ciphertext: {}", ciphertext.to_str_radix(16)));
+                match &recovered {
+                    Some(text) => out.line(tr!("m³ < n, so the integer cube root is the message: {}", text)),
+                    None => out.line(tr!("m³ wrapped around n; the cube root does not give the message")),
+                }
+                out.field("ciphertext", ciphertext.to_str_radix(16));
+                out.field("recovered", recovered);
+            }
+            _ => return Err(usage_error(USAGE)),
+        },
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use crypto_core::codec::encode_hex;
+use crypto_core::mac::constant_time_eq;
+use crypto_core::rng;
+use crypto_core::tr;
+use serde_json::{json, Map, Value};
+use tracing::{debug, info};
+
+use crate::config::Settings;
+use crate::output::CommandOutput;
+
+/// The methods served, as `<command>.<operation>`. Each runs the command
+/// the way `crypto --output json` does and returns the object it prints.
+const METHODS: &[(&str, &[&str])] = &[
+    ("caesar", &["encrypt", "decrypt", "brute", "crack"]),
+    ("vigenere", &["encrypt", "decrypt", "analyze", "crack"]),
+    ("hill", &["encrypt", "decrypt"]),
+    ("railfence", &["encrypt", "decrypt", "brute"]),
+    ("columnar", &["encrypt", "decrypt", "crack"]),
+    ("playfair", &["encrypt", "decrypt", "crack"]),
+    ("des", &["encrypt", "decrypt"]),
+    ("aes", &["encrypt", "decrypt"]),
+    ("rc4", &["encrypt", "decrypt"]),
+    ("chacha20", &["encrypt", "decrypt"]),
+    ("mac", &["tag", "verify"]),
+    ("encode", &["hex", "base32", "base64"]),
+    ("decode", &["hex", "base32", "base64"]),
+    ("rsa", &["encrypt", "decrypt", "sign", "verify"]),
+    ("ecc", &["sign", "verify"]),
+    ("elgamal", &["encrypt", "decrypt", "sign", "verify"]),
+];
+
+/// Commands whose `--key` names a key file; over the network the key is
+/// sent as the file's JSON instead, so no path on the server is ever read
+const KEY_FILE_COMMANDS: &[&str] = &["rsa", "ecc", "elgamal"];
+
+/// Flags that read or write files on the server, which calls may not use
+const FILE_FLAGS: &[&str] = &["file", "out"];
+
+/// Largest request body accepted
+const MAX_BODY: usize = 1 << 20;
+
+// JSON-RPC 2.0 error codes, plus two of our own in the server range
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const COMMAND_FAILED: i64 = -32000;
+const UNAUTHORIZED: i64 = -32001;
+
+/// `crypto serve [--api-key <key>] [<address>]`: answer JSON-RPC 2.0 on
+/// `POST /rpc` and list the methods on `GET /methods`. Every request needs
+/// the API key as `Authorization: Bearer <key>` or `X-API-Key: <key>`.
+pub(crate) fn run(args: &[String], settings: &Settings) -> io::Result<()> {
+    const USAGE: &str = "crypto serve [--api-key <key>] [<address>]";
+    let (keys, rest) = crate::take_flag_values(args, "--api-key")?;
+    let address = match rest.as_slice() {
+        [] => settings.serve_address.as_str(),
+        [address] => address.as_str(),
+        _ => return Err(crate::usage_error(USAGE)),
+    };
+    let (api_key, generated) = match keys.last().or(settings.api_key.as_ref()) {
+        Some(key) => (key.clone(), false),
+        None => (encode_hex(&rng::key(16)?), true),
+    };
+
+    let listener = TcpListener::bind(address)?;
+    let address = listener.local_addr()?;
+    let mut out = CommandOutput::new(settings.format, "serve");
+    out.line(tr!("Serving JSON-RPC on http://{}/rpc (methods at http://{}/methods)", address, address));
+    if generated {
+        out.line(tr!("No --api-key or CRYPTO_API_KEY given; send this one: {}", api_key));
+        out.field("api_key", api_key.as_str());
+    }
+    out.field("address", address.to_string());
+    out.finish();
+
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        let api_key = api_key.clone();
+        // A client that drops mid-request only ends its own connection
+        thread::spawn(move || {
+            if let Err(e) = handle_connection(stream, &api_key) {
+                debug!(error = %e, "connection dropped");
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Read one HTTP/1.1 request, answer it and close the connection
+fn handle_connection(mut stream: TcpStream, api_key: &str) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+    let header = |name: &str| headers.get(name).map(String::as_str);
+
+    let presented = header("x-api-key").or_else(|| header("authorization").and_then(|value| value.strip_prefix("Bearer ")));
+    let authorized = presented.is_some_and(|key| constant_time_eq(key.as_bytes(), api_key.as_bytes()));
+    let length = header("content-length").and_then(|length| length.parse::<usize>().ok()).unwrap_or(0);
+
+    let (status, body) = if !authorized {
+        ("401 Unauthorized", Some(error_response(Value::Null, UNAUTHORIZED, "Missing or wrong API key")))
+    } else if length > MAX_BODY {
+        ("413 Payload Too Large", Some(error_response(Value::Null, INVALID_REQUEST, "Request body too large")))
+    } else {
+        match (method, path) {
+            ("GET", "/methods") => ("200 OK", Some(methods())),
+            ("POST", "/rpc") => {
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body)?;
+                match call_body(&body) {
+                    Some(response) => ("200 OK", Some(response)),
+                    None => ("204 No Content", None),
+                }
+            }
+            _ => ("404 Not Found", Some(json!({ "error": "POST /rpc or GET /methods" }))),
+        }
+    };
+
+    // Never the body: calls carry keys and plaintexts
+    info!(peer = ?stream.peer_addr().ok(), method, path, status, "request");
+    let body = body.map(|body| body.to_string()).unwrap_or_default();
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// The method list with the parameter conventions, for `GET /methods`
+fn methods() -> Value {
+    let names: Vec<String> = METHODS
+        .iter()
+        .flat_map(|(command, operations)| operations.iter().map(move |operation| format!("{}.{}", command, operation)))
+        .collect();
+    json!({
+        "methods": names,
+        "params": "the command's flags without the leading --, e.g. {\"key\": \"3\", \"mode\": \"cbc\", \"mac_key\": \"k\"}; \
+                   true for flags without a value; \"text\" is the text argument",
+        "keys": "rsa, ecc and elgamal take \"key\" as the JSON of a key file from `crypto <command> keygen`",
+        "result": "the object `crypto --output json <command> <operation>` prints",
+    })
+}
+
+/// Answer a request body: one call or a batch. Notifications (calls
+/// without an id) get no answer, so a body of only those gets none.
+fn call_body(body: &[u8]) -> Option<Value> {
+    let request: Value = match serde_json::from_slice(body) {
+        Ok(request) => request,
+        Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, &format!("Invalid JSON: {}", e))),
+    };
+
+    match request {
+        Value::Array(calls) if calls.is_empty() => Some(error_response(Value::Null, INVALID_REQUEST, "Empty batch")),
+        Value::Array(calls) => {
+            let responses: Vec<Value> = calls.iter().filter_map(call).collect();
+            (!responses.is_empty()).then_some(Value::Array(responses))
+        }
+        request => call(&request),
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "error": { "code": code, "message": message }, "id": id })
+}
+
+/// Run one JSON-RPC call
+fn call(request: &Value) -> Option<Value> {
+    let Some(id) = request.get("id").cloned() else {
+        let _ = run_method(request);
+        return None;
+    };
+
+    Some(match run_method(request) {
+        Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+        Err((code, message, data)) => {
+            let mut response = error_response(id, code, &message);
+            if let Some(data) = data {
+                response["error"]["data"] = data;
+            }
+            response
+        }
+    })
+}
+
+/// What went wrong: the JSON-RPC code, the message, and for a failed
+/// command the fields it had reported (such as `"valid": false`)
+type CallError = (i64, String, Option<Value>);
+
+fn run_method(request: &Value) -> Result<Value, CallError> {
+    let invalid = |code: i64, message: String| (code, message, None);
+    let (Some("2.0"), Some(method)) = (request["jsonrpc"].as_str(), request["method"].as_str()) else {
+        return Err(invalid(INVALID_REQUEST, String::from("Expected {\"jsonrpc\": \"2.0\", \"method\": ...}")));
+    };
+    let (command, operation) = method.split_once('.').unwrap_or((method, ""));
+    if !METHODS.iter().any(|(name, operations)| *name == command && operations.contains(&operation)) {
+        return Err(invalid(METHOD_NOT_FOUND, format!("Unknown method {}", method)));
+    }
+
+    debug!(method, "call");
+    let args = command_arguments(command, operation, &request["params"]).map_err(|message| invalid(INVALID_PARAMS, message))?;
+    let mut out = CommandOutput::new(crate::output::OutputFormat::Json, command);
+    match crate::run_command(command, &args, &mut out) {
+        Ok(()) => Ok(Value::Object(out.into_fields())),
+        Err(e) => Err((COMMAND_FAILED, e.to_string(), Some(Value::Object(out.into_fields())))),
+    }
+}
+
+/// The command line for a call: the operation, a flag per parameter, and
+/// the text last. The text is always given, so standard input is never read.
+fn command_arguments(command: &str, operation: &str, params: &Value) -> Result<Vec<String>, String> {
+    let params = match params {
+        Value::Object(params) => params.clone(),
+        Value::Null => Map::new(),
+        _ => return Err(String::from("params must be an object")),
+    };
+
+    let mut args = vec![operation.to_string()];
+    for (name, value) in &params {
+        if name == "text" {
+            continue;
+        }
+        if FILE_FLAGS.contains(&name.as_str()) {
+            return Err(format!("{} is not available over the network", name));
+        }
+        let flag = format!("--{}", name.replace('_', "-"));
+        let key_file = name == "key" && KEY_FILE_COMMANDS.contains(&command);
+        match value {
+            Value::Object(_) if key_file => args.extend([flag, value.to_string()]),
+            _ if key_file => return Err(format!("{} keys are sent as the JSON of the key file", command)),
+            Value::Bool(true) => args.push(flag),
+            Value::Bool(false) => {}
+            Value::String(text) => args.extend([flag, text.clone()]),
+            Value::Number(number) => args.extend([flag, number.to_string()]),
+            _ => return Err(format!("{} must be a string, number or boolean", name)),
+        }
+    }
+
+    match params.get("text") {
+        Some(Value::String(text)) => args.push(text.clone()),
+        None => args.push(String::new()),
+        Some(_) => return Err(String::from("text must be a string")),
+    }
+    Ok(args)
+}
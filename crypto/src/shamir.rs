@@ -0,0 +1,54 @@
+//! `crypto shamir`: splitting a secret into shares, any k of which restore it
+
+use std::io;
+
+use crypto_core::codec::encode_hex;
+use crypto_core::shamir::{self, Share};
+use crypto_core::tr;
+
+use crate::output::CommandOutput;
+use crate::{key_bytes, take_flag_values, text_argument, usage_error};
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto shamir split --threshold <k> --shares <n> [--hex] [<secret>] | \
+                         crypto shamir combine [--hex] <share>...";
+    let (thresholds, rest) = take_flag_values(args, "--threshold")?;
+    let (counts, rest) = take_flag_values(&rest, "--shares")?;
+    let hex = rest.iter().any(|arg| arg == "--hex");
+    let rest: Vec<String> = rest.into_iter().filter(|arg| arg != "--hex").collect();
+    let Some((operation, rest)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+    let number = |value: &String| {
+        value.parse::<u8>().ok().filter(|&value| value > 0).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid count {} (1 to 255)", value))
+        })
+    };
+
+    match (operation.as_str(), thresholds.last(), counts.last()) {
+        ("split", Some(threshold), Some(count)) => {
+            let secret = key_bytes(&text_argument(rest, USAGE)?, hex)?;
+            let shares = shamir::split(&secret, number(threshold)?, number(count)?)?;
+            for share in &shares {
+                out.line(share.to_string());
+            }
+            out.field("threshold", number(threshold)?);
+            out.field("shares", shares.iter().map(ToString::to_string).collect::<Vec<_>>());
+        }
+        ("combine", None, None) if !rest.is_empty() => {
+            let shares = rest
+                .iter()
+                .map(|text| {
+                    Share::parse(text).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid share {}", text)))
+                })
+                .collect::<io::Result<Vec<_>>>()?;
+            let secret = shamir::combine(&shares)?;
+            let result = if hex { encode_hex(&secret) } else { String::from_utf8_lossy(&secret).into_owned() };
+            out.line(&result);
+            out.field("result", result);
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
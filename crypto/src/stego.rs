@@ -0,0 +1,147 @@
+//! `crypto stego`: hiding text in the low bits of a PNG, optionally encrypted
+
+use std::io;
+
+use crypto_core::aes::Aes128;
+use crypto_core::modes::{self, Mode};
+use crypto_core::{rng, stego, tr, BlockCipher, SymmetricCipher};
+use des::Des;
+
+use crate::output::CommandOutput;
+use crate::{cipher_error, image, key_bytes, take_flag_values, text_argument, usage_error};
+
+/// The `--cipher des|aes` a stego payload is encrypted with, in CBC mode
+fn stego_cipher(ciphers: &[String], keys: &[String], hex: bool) -> io::Result<Option<Box<dyn BlockCipher>>> {
+    let cipher: Box<dyn BlockCipher> = match (ciphers.last().map(String::as_str), keys.last()) {
+        (None, None) => return Ok(None),
+        (Some("des"), Some(key)) => Box::new(Des::new(&key_bytes(key, hex)?).map_err(cipher_error)?),
+        (Some("aes"), Some(key)) => Box::new(Aes128::new(&key_bytes(key, hex)?).map_err(cipher_error)?),
+        (Some(name @ ("des" | "aes")), None) => {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("--cipher {} needs --key", name)));
+        }
+        (None, Some(_)) => return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("--key needs --cipher des or aes"))),
+        (Some(name), _) => return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("Unknown cipher {} (des or aes)", name))),
+    };
+    Ok(Some(cipher))
+}
+
+/// Largest plaintext that still fits `capacity` bytes once CBC adds an IV
+/// and at least one byte of padding
+fn cbc_plaintext_capacity(capacity: usize, block_size: usize) -> usize {
+    (capacity.saturating_sub(block_size) / block_size * block_size).saturating_sub(1)
+}
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto stego hide [--cipher des|aes --key <key> [--hex]] [--file <payload>] <cover> <output> [<text>] | \
+                         crypto stego extract [--cipher des|aes --key <key> [--hex]] [--out <file>] <image> | \
+                         crypto stego (capacity | detect) <image>";
+    let (ciphers, rest) = take_flag_values(args, "--cipher")?;
+    let (keys, rest) = take_flag_values(&rest, "--key")?;
+    let (files, rest) = take_flag_values(&rest, "--file")?;
+    let (outputs, rest) = take_flag_values(&rest, "--out")?;
+    let hex = rest.iter().any(|arg| arg == "--hex");
+    let rest: Vec<String> = rest.into_iter().filter(|arg| arg != "--hex").collect();
+    let Some((operation, rest)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+    let cipher = stego_cipher(&ciphers, &keys, hex)?;
+
+    match (operation.as_str(), rest) {
+        ("hide", [cover_path, output_path, text @ ..]) if outputs.is_empty() => {
+            let payload = match files.last() {
+                Some(path) if text.is_empty() => std::fs::read(path)?,
+                None => text_argument(text, USAGE)?.into_bytes(),
+                Some(_) => return Err(usage_error(USAGE)),
+            };
+            let data = match &cipher {
+                Some(cipher) => {
+                    let iv = rng::iv(cipher.block_size())?;
+                    let ciphertext = modes::encrypt(cipher.as_ref(), Mode::Cbc, &iv, &payload).map_err(cipher_error)?;
+                    [iv, ciphertext].concat()
+                }
+                None => payload.clone(),
+            };
+
+            let mut image = image::load(cover_path)?;
+            stego::embed(&mut image, &data)?;
+            image::save(output_path, &image)?;
+
+            let capacity = stego::capacity(&image);
+            let share = data.len() * 100 / capacity.max(1);
+            out.line(match cipher {
+                Some(_) => tr!("Hid {} bytes of ciphertext in {} ({}% of its {}-byte capacity)", data.len(), output_path, share, capacity),
+                None => tr!("Hid {} bytes in {} ({}% of its {}-byte capacity)", data.len(), output_path, share, capacity),
+            });
+            out.field("output", output_path.as_str());
+            out.field("payload_bytes", payload.len());
+            out.field("embedded_bytes", data.len());
+            out.field("capacity", capacity);
+            out.field("encrypted", cipher.is_some());
+        }
+        ("extract", [image_path]) if files.is_empty() => {
+            let data = stego::extract(&image::load(image_path)?)?;
+            let payload = match &cipher {
+                Some(cipher) => {
+                    let iv_length = cipher.block_size().min(data.len());
+                    let (iv, ciphertext) = data.split_at(iv_length);
+                    modes::decrypt(cipher.as_ref(), Mode::Cbc, iv, ciphertext).map_err(cipher_error)?
+                }
+                None => data,
+            };
+
+            match outputs.last() {
+                Some(path) => {
+                    std::fs::write(path, &payload)?;
+                    out.line(tr!("Wrote {} bytes to {}", payload.len(), path));
+                    out.field("output", path.as_str());
+                }
+                None => {
+                    let text = String::from_utf8_lossy(&payload).into_owned();
+                    out.line(&text);
+                    out.field("result", text);
+                }
+            }
+            out.field("payload_bytes", payload.len());
+        }
+        ("capacity", [image_path]) if cipher.is_none() && files.is_empty() && outputs.is_empty() => {
+            let image = image::load(image_path)?;
+            let capacity = stego::capacity(&image);
+            out.line(tr!(
+                "{}x{}, {} channel(s): {} bytes, {} with DES or {} with AES",
+                image.width,
+                image.height,
+                image.channels,
+                capacity,
+                cbc_plaintext_capacity(capacity, 8),
+                cbc_plaintext_capacity(capacity, 16)
+            ));
+            out.field("width", image.width);
+            out.field("height", image.height);
+            out.field("channels", image.channels);
+            out.field("capacity", capacity);
+            out.field("des_capacity", cbc_plaintext_capacity(capacity, 8));
+            out.field("aes_capacity", cbc_plaintext_capacity(capacity, 16));
+        }
+        ("detect", [image_path]) if cipher.is_none() && files.is_empty() && outputs.is_empty() => {
+            let profile = stego::chi_square_profile(&image::load(image_path)?);
+            out.line(tr!("Chi-square probability of embedding, by share of the image scanned"));
+            for (tenth, probability) in profile.iter().enumerate() {
+                out.line(format!("  {:>3}%: {:.3}", (tenth + 1) * 10, probability));
+            }
+            // Sequential embedding fills the image from the start, so the
+            // pairs stay even as far as the payload reaches (the first
+            // tenth of a small image may hold too few samples to tell)
+            let suspicious = profile.iter().rposition(|&probability| probability > 0.5).map_or(0, |tenth| tenth + 1);
+            if suspicious == 0 {
+                out.line(tr!("No sign of LSB embedding"));
+            } else {
+                out.line(tr!("Likely LSB payload in the first {}% of the image", suspicious * 10));
+            }
+            out.field("profile", profile);
+            out.field("suspicious_percent", suspicious * 10);
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,170 @@
+//! `crypto vault`: a passphrase-protected store of keys and pads
+
+use std::path::PathBuf;
+use std::{env, io};
+
+use crypto_core::argon2::Argon2id;
+use crypto_core::codec::encode_hex;
+use crypto_core::vault::{Entry, EntryKind, Vault};
+use crypto_core::{otp, rng, tr};
+use serde_json::json;
+
+use crate::output::CommandOutput;
+use crate::{config, hex_input, key_bytes, run_command, take_flag_values, text_argument, usage_error};
+
+/// Keep keys, pads and key file references in the passphrase-encrypted
+/// vault, and run commands with them without the key on the command line
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto vault [--vault <file>] [--passphrase <p>] add <name> \
+                         (--key <key> [--hex] | --generate <bytes> | --pad <file> | --ref <key file>) [--memory <KiB>] | \
+                         crypto vault [--vault <file>] [--passphrase <p>] list | \
+                         crypto vault [--vault <file>] [--passphrase <p>] use <name> <command> [<arguments>...]";
+    // The arguments after `use <name> <command>` belong to that command
+    let mut split = args.len();
+    let mut positional = Vec::new();
+    let mut index = 0;
+    while index < args.len() {
+        match args[index].as_str() {
+            "--vault" | "--passphrase" => index += 2,
+            arg => {
+                positional.push(arg);
+                index += 1;
+                if positional.len() == 3 && positional[0] == "use" {
+                    split = index;
+                    break;
+                }
+            }
+        }
+    }
+    let (head, passed_on) = args.split_at(split.min(args.len()));
+    let (paths, rest) = take_flag_values(head, "--vault")?;
+    let (passphrases, rest) = take_flag_values(&rest, "--passphrase")?;
+    let path = paths.last().map(PathBuf::from).unwrap_or_else(|| config::settings().vault.clone());
+    let passphrase = passphrases
+        .last()
+        .cloned()
+        .or_else(|| env::var("CRYPTO_VAULT_PASSPHRASE").ok().filter(|passphrase| !passphrase.is_empty()))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, tr!("A passphrase is required (--passphrase or CRYPTO_VAULT_PASSPHRASE)")))?;
+    let Some((operation, rest)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+
+    match (operation.as_str(), rest) {
+        ("add", [name, rest @ ..]) => {
+            let (keys, rest) = take_flag_values(rest, "--key")?;
+            let (sizes, rest) = take_flag_values(&rest, "--generate")?;
+            let (pads, rest) = take_flag_values(&rest, "--pad")?;
+            let (references, rest) = take_flag_values(&rest, "--ref")?;
+            let (memories, rest) = take_flag_values(&rest, "--memory")?;
+            let hex = rest.iter().any(|arg| arg == "--hex");
+            let rest: Vec<String> = rest.into_iter().filter(|arg| arg != "--hex").collect();
+            let (kind, data) = match (keys.last(), sizes.last(), pads.last(), references.last()) {
+                (Some(key), None, None, None) if rest.is_empty() => (EntryKind::Key, key_bytes(key, hex)?),
+                (None, Some(size), None, None) if rest.is_empty() && !hex => {
+                    let size = size.parse::<usize>().ok().filter(|&size| size > 0).ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid {} {}", tr!("size"), size))
+                    })?;
+                    (EntryKind::Key, rng::key(size)?)
+                }
+                (None, None, Some(pad), None) if rest.is_empty() && !hex => (EntryKind::Pad, std::fs::read(pad)?),
+                (None, None, None, Some(reference)) if rest.is_empty() && !hex => (EntryKind::Reference, reference.as_bytes().to_vec()),
+                (None, None, None, None) => (EntryKind::Key, key_bytes(&text_argument(&rest, USAGE)?, hex)?),
+                _ => return Err(usage_error(USAGE)),
+            };
+            let entry = Entry::new(name, kind, data)?;
+
+            let mut vault = if path.exists() {
+                Vault::open(&path, &passphrase)?
+            } else {
+                let mut kdf = Argon2id::DEFAULT;
+                if let Some(memory) = memories.last() {
+                    kdf.memory_kib = memory.parse::<u32>().ok().filter(|&memory| memory >= 8).ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid {} {}", tr!("memory"), memory))
+                    })?;
+                }
+                Vault::create(&path, &passphrase, kdf)?
+            };
+            let size = entry.data.len();
+            vault.add(entry)?;
+            vault.save()?;
+            out.line(tr!("Added the {} {} ({} bytes) to {}", kind.name(), name, size, path.display()));
+            out.field("name", name.as_str());
+            out.field("kind", kind.name());
+            out.field("bytes", size);
+            out.field("vault", path.display().to_string());
+        }
+        ("list", []) => {
+            let vault = Vault::open(&path, &passphrase)?;
+            let mut entries = Vec::new();
+            for entry in vault.entries() {
+                let name = format!("{:<16}", entry.name);
+                let line = match entry.kind {
+                    EntryKind::Key => tr!("  {} key, {} bytes", name, entry.data.len()),
+                    EntryKind::Pad => tr!("  {} pad, {} bytes, {} unused", name, entry.data.len(), entry.data.len() - entry.used),
+                    EntryKind::Reference => tr!("  {} ref, {}", name, entry.path().display()),
+                };
+                out.line(line);
+                entries.push(json!({
+                    "name": entry.name,
+                    "kind": entry.kind.name(),
+                    "bytes": entry.data.len(),
+                    "remaining": (entry.kind == EntryKind::Pad).then(|| entry.data.len() - entry.used),
+                    "path": (entry.kind == EntryKind::Reference).then(|| entry.path().display().to_string()),
+                }));
+            }
+            out.field("entries", entries);
+        }
+        ("use", [name, command]) => {
+            let mut vault = Vault::open(&path, &passphrase)?;
+            let entry = vault.get(name)?.clone();
+            let mut args = passed_on.to_vec();
+            match entry.kind {
+                EntryKind::Key => match std::str::from_utf8(&entry.data) {
+                    Ok(text) if !text.chars().any(char::is_control) => args.extend([String::from("--key"), text.to_string()]),
+                    _ => args.extend([String::from("--key"), encode_hex(&entry.data), String::from("--hex")]),
+                },
+                EntryKind::Reference => args.extend([String::from("--key"), entry.path().to_string_lossy().into_owned()]),
+                EntryKind::Pad if command == "otp" => return run_vault_pad(&mut vault, name, &args, out),
+                EntryKind::Pad => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidInput, tr!("The pad {} can only be used with otp encrypt or decrypt", name)));
+                }
+            }
+            run_command(command, &args, out)?;
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
+
+/// `crypto vault use <pad> otp (encrypt | decrypt --offset <n>)`: the pad
+/// bytes come from the vault, which records the bytes used
+fn run_vault_pad(vault: &mut Vault, name: &str, args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto vault use <pad> otp encrypt [<text>] | crypto vault use <pad> otp decrypt --offset <n> [<hex>]";
+    let (offsets, rest) = take_flag_values(args, "--offset")?;
+    match (rest.split_first(), offsets.last()) {
+        (Some((operation, rest)), None) if operation == "encrypt" => {
+            let plaintext = text_argument(rest, USAGE)?;
+            let (offset, key) = vault.take_pad(name, plaintext.len())?;
+            vault.save()?;
+            let ciphertext = encode_hex(&otp::xor(plaintext.as_bytes(), &key)?);
+
+            out.line(tr!("Offset {}: {}", offset, ciphertext));
+            out.field("offset", offset);
+            out.field("result", ciphertext);
+        }
+        (Some((operation, rest)), Some(offset)) if operation == "decrypt" => {
+            let offset = offset
+                .parse::<usize>()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid {} {}", tr!("offset"), offset)))?;
+            let ciphertext = hex_input(&text_argument(rest, USAGE)?, tr!("ciphertext"))?;
+            let key = vault.pad_at(name, offset, ciphertext.len())?;
+            let plaintext = String::from_utf8_lossy(&otp::xor(&ciphertext, &key)?).into_owned();
+
+            out.line(&plaintext);
+            out.field("result", plaintext);
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+//! `crypto vigenere`: the Vigenère cipher, Kasiski and index-of-coincidence
+//! analysis, and cracking it
+
+use std::io;
+
+use crypto_core::language::Language;
+use crypto_core::vigenere::{self, Vigenere};
+use crypto_core::{tr, ClassicalCipher};
+use serde_json::json;
+
+use crate::output::CommandOutput;
+use crate::{cipher_error, run_classical, take_flag_values, take_romanian, text_argument, usage_error};
+
+pub(crate) fn run(args: &[String], out: &mut CommandOutput) -> io::Result<()> {
+    const USAGE: &str = "crypto vigenere (encrypt | decrypt) --key <word> [<text>] | \
+                         crypto vigenere analyze [--max-key-length <n>] [<text>] | \
+                         crypto vigenere crack [--max-key-length <n>] [--romanian] [<text>]";
+    let (keys, rest) = take_flag_values(args, "--key")?;
+    let (max_lengths, rest) = take_flag_values(&rest, "--max-key-length")?;
+    let (romanian, rest) = take_romanian(&rest);
+    let language = if romanian { Language::Romanian } else { Language::English };
+    let Some((operation, rest)) = rest.split_first() else {
+        return Err(usage_error(USAGE));
+    };
+    let max_key_length = match max_lengths.last() {
+        Some(length) => length.parse::<usize>().ok().filter(|&length| length > 0).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, tr!("Invalid key length {}", length))
+        })?,
+        None => 16,
+    };
+
+    match (operation.as_str(), keys.last()) {
+        ("analyze", None) => {
+            let ciphertext = text_argument(rest, USAGE)?;
+            let kasiski = vigenere::kasiski(&ciphertext, max_key_length);
+            let coincidences = vigenere::column_coincidences(&ciphertext, max_key_length);
+            let friedman = vigenere::friedman_estimate(&ciphertext);
+            let key_length = vigenere::estimate_key_length(&ciphertext, max_key_length);
+
+            out.line(tr!("Kasiski examination (key length: repeated trigram distances it divides)"));
+            for (length, count) in kasiski.iter().take(5) {
+                out.line(format!("  {:>2}: {}", length, count));
+            }
+            out.line(tr!("Friedman estimate: {}", format!("{:.1}", friedman)));
+            out.line(tr!("Average column index of coincidence (English is about 0.067)"));
+            for (length, ioc) in &coincidences {
+                out.line(format!("  {:>2}: {:.4}", length, ioc));
+            }
+            out.line(tr!("Most likely key length: {}", key_length));
+
+            out.field("kasiski", kasiski.iter().map(|(length, count)| json!({"key_length": length, "count": count})).collect::<Vec<_>>());
+            out.field("friedman_estimate", if friedman.is_finite() { json!(friedman) } else { json!(null) });
+            out.field("column_coincidences", coincidences.iter().map(|(length, ioc)| json!({"key_length": length, "ioc": ioc})).collect::<Vec<_>>());
+            out.field("key_length", key_length);
+        }
+        ("crack", None) => {
+            let (key, plaintext) = vigenere::crack(&text_argument(rest, USAGE)?, max_key_length, language);
+            out.line(tr!("Key {}: {}", key, plaintext));
+            out.field("key", key);
+            out.field("result", plaintext);
+        }
+        (_, Some(key)) => {
+            let cipher = Vigenere::new(key).map_err(cipher_error)?;
+            return run_classical(&cipher, operation, rest, USAGE, out);
+        }
+        _ => return Err(usage_error(USAGE)),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,671 @@
+//! Runs the `crypto` binary the way students use it
+
+use std::process::{Command, Output};
+
+use serde_json::{json, Value};
+
+fn crypto(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_crypto"))
+        .args(args)
+        .output()
+        .expect("failed to run crypto")
+}
+
+/// Run `crypto --output json` and parse the result
+fn crypto_json(args: &[&str]) -> Value {
+    let args: Vec<&str> = ["--output", "json"].iter().chain(args).copied().collect();
+    let output = crypto(&args);
+    serde_json::from_slice(&output.stdout).expect("crypto printed invalid JSON")
+}
+
+#[test]
+fn playfair_round_trip() {
+    let encrypted = crypto_json(&["playfair", "encrypt", "--key", "MONARCHY", "INSTRUMENTS"]);
+    assert_eq!(encrypted["ok"], true);
+    let ciphertext = encrypted["result"].as_str().unwrap();
+    assert_ne!(ciphertext, "INSTRUMENTS");
+
+    let decrypted = crypto_json(&["playfair", "decrypt", "--key", "MONARCHY", ciphertext]);
+    assert!(decrypted["result"].as_str().unwrap().starts_with("INSTRUMENTS"));
+
+    let rejected = crypto_json(&["playfair", "encrypt", "--key", "short", "TEXT"]);
+    assert_eq!(rejected["ok"], false);
+}
+
+#[test]
+fn des_key_schedule_matches_the_textbook_example() {
+    let report = crypto_json(&["des", "key", "--hex", "133457799BBCDFF1"]);
+    assert_eq!(report["k_plus"], "f0ccaaf556678f");
+}
+
+#[test]
+fn des_round_trip_through_the_symmetric_cipher_interface() {
+    let encrypted = crypto_json(&["des", "encrypt", "--key", "MORTYNOR", "hello DES"]);
+    assert_eq!(encrypted["cipher"], "des");
+    // Interoperable with `openssl enc -des-ecb -K 4d4f5254594e4f52`
+    assert_eq!(encrypted["result"], "1634d740da0ec08eae40c6291fa7a9c1");
+
+    let decrypted = crypto_json(&["des", "decrypt", "--key", "MORTYNOR", "1634d740da0ec08eae40c6291fa7a9c1"]);
+    assert_eq!(decrypted["result"], "hello DES");
+    assert_eq!(crypto_json(&["des", "decrypt", "--key", "WRONGKEY", "1634d740da0ec08eae40c6291fa7a9c1"])["ok"], false);
+}
+
+#[test]
+fn caesar_crack_recovers_the_shift() {
+    let encrypted = crypto_json(&["caesar", "encrypt", "--key", "7", "Meet me after the lecture in the library"]);
+    let cracked = crypto_json(&["caesar", "crack", encrypted["result"].as_str().unwrap()]);
+    assert_eq!(cracked["shift"], 7);
+    assert_eq!(cracked["result"], "Meet me after the lecture in the library");
+}
+
+#[test]
+fn hill_known_plaintext_attack_recovers_the_key() {
+    let encrypted = crypto_json(&["hill", "encrypt", "--key", "HILL", "SHORTEXAMPLE"]);
+    let ciphertext = encrypted["result"].as_str().unwrap();
+
+    let attack = crypto_json(&["hill", "attack", "--plaintext", "SHORTEXAMPLE", ciphertext]);
+    assert_eq!(attack["key"], serde_json::json!([[7, 8], [11, 11]]));
+    assert_eq!(attack["result"], "SHORTEXAMPLE");
+
+    let singular = crypto_json(&["hill", "encrypt", "--key", "2 4 6 8", "TEXT"]);
+    assert_eq!(singular["ok"], false);
+}
+
+#[test]
+fn columnar_after_playfair_round_trip() {
+    let args = ["--key", "ZEBRAS", "--playfair", "MONARCHY"];
+    let encrypted = crypto_json(&[&["columnar", "encrypt"][..], &args, &["INSTRUMENTS"]].concat());
+    let ciphertext = encrypted["result"].as_str().unwrap();
+    let playfair = crypto_json(&["playfair", "encrypt", "--key", "MONARCHY", "INSTRUMENTS"]);
+    assert_ne!(ciphertext, playfair["result"]);
+
+    let decrypted = crypto_json(&[&["columnar", "decrypt"][..], &args, &[ciphertext]].concat());
+    assert!(decrypted["result"].as_str().unwrap().starts_with("INSTRUMENTS"));
+}
+
+#[test]
+fn pipeline_decryption_undoes_every_stage() {
+    let stages = "playfair:MONARCHY | columnar:ZEBRAS | base64";
+    let encrypted = crypto_json(&["pipeline", "encrypt", stages, "INSTRUMENTS"]);
+    let playfair = crypto_json(&["playfair", "encrypt", "--key", "MONARCHY", "INSTRUMENTS"]);
+    assert_eq!(encrypted["stages"][0]["result"], playfair["result"]);
+    assert_eq!(encrypted["stages"].as_array().unwrap().len(), 3);
+
+    let decrypted = crypto_json(&["pipeline", "decrypt", stages, encrypted["result"].as_str().unwrap()]);
+    assert!(decrypted["result"].as_str().unwrap().starts_with("INSTRUMENTS"));
+
+    // A binary ciphertext is written as hex, as `crypto des` does
+    let des = crypto_json(&["pipeline", "encrypt", "des:MORTYNOR", "hello DES"]);
+    assert_eq!(des["result"], "1634d740da0ec08eae40c6291fa7a9c1");
+    assert_eq!(crypto_json(&["pipeline", "encrypt", "des:MORTYNOR | caesar:3", "hello"])["ok"], false);
+}
+
+#[test]
+fn plugin_ciphers_get_a_subcommand_a_stage_and_analysis() {
+    let ciphers = crypto_json(&["ciphers"]);
+    let affine = ciphers["ciphers"].as_array().unwrap().iter().find(|cipher| cipher["name"] == "affine").unwrap();
+    assert_eq!(affine["origin"], "student-ciphers");
+
+    assert_eq!(crypto_json(&["affine", "encrypt", "--key", "5,8", "AFFINE CIPHER"])["result"], "IHHWVC SWFRCP");
+    assert_eq!(crypto_json(&["affine", "decrypt", "--key", "5,8", "IHHWVC SWFRCP"])["result"], "AFFINE CIPHER");
+    assert_eq!(crypto_json(&["affine", "encrypt", "--key", "13,8", "AFFINE"])["ok"], false);
+
+    let encrypted = crypto_json(&["pipeline", "encrypt", "affine:5,8 | 3des:0x0123456789abcdef23456789abcdef01456789abcdef0123", "AFFINE"]);
+    let decrypted = crypto_json(&["pipeline", "decrypt", "affine:5,8 | 3des:0x0123456789abcdef23456789abcdef01456789abcdef0123", encrypted["result"].as_str().unwrap()]);
+    assert_eq!(decrypted["result"], "AFFINE");
+
+    let analysis = crypto_json(&["analyze", "--cipher", "affine", "--key", "5,8", "AFFINE CIPHER"]);
+    assert_eq!(analysis["ciphertext"], "IHHWVC SWFRCP");
+    assert_eq!(crypto_json(&["analyze", "--cipher", "aes", "--key", "YELLOW SUBMARINE", "text"])["ok"], false);
+}
+
+#[test]
+fn vault_entries_stand_in_for_keys_and_pads() {
+    let vault = std::env::temp_dir().join(format!("crypto-{}.vault", std::process::id()));
+    let _ = std::fs::remove_file(&vault);
+    let vault_path = vault.to_str().unwrap();
+    let run = |args: &[&str]| {
+        let args: Vec<&str> = ["--output", "json", "vault", "--vault", vault_path, "--passphrase", "lab 7"].iter().chain(args).copied().collect();
+        crypto_json(&args)
+    };
+
+    assert_eq!(run(&["add", "des-lab", "--key", "MORTYNOR", "--memory", "64"])["ok"], true);
+    assert_eq!(run(&["add", "pad", "--key", "00112233445566778899", "--hex"])["ok"], true);
+    assert_eq!(run(&["add", "des-lab", "--generate", "8"])["ok"], false);
+    assert_eq!(run(&["list"])["entries"][0], json!({"name": "des-lab", "kind": "key", "bytes": 8, "remaining": null, "path": null}));
+    assert!(!std::fs::read_to_string(&vault).unwrap().contains("MORTYNOR"));
+
+    // The same ciphertext as with the key on the command line
+    let encrypted = run(&["use", "des-lab", "des", "encrypt", "hello DES"]);
+    assert_eq!(encrypted["result"], "1634d740da0ec08eae40c6291fa7a9c1");
+    let decrypted = run(&["use", "des-lab", "des", "decrypt", "1634d740da0ec08eae40c6291fa7a9c1"]);
+    assert_eq!(decrypted["result"], "hello DES");
+
+    let wrong = crypto_json(&["vault", "--vault", vault_path, "--passphrase", "lab 8", "list"]);
+    assert_eq!(wrong["ok"], false);
+    std::fs::remove_file(&vault).unwrap();
+}
+
+#[test]
+fn rc4_matches_the_published_test_vector() {
+    let encrypted = crypto_json(&["rc4", "encrypt", "--key", "Key", "Plaintext"]);
+    assert_eq!(encrypted["result"], "bbf316e8d940af0ad3");
+
+    let keystream = crypto_json(&["rc4", "keystream", "--key", "576971", "--hex", "--bytes", "4"]);
+    assert_eq!(keystream["ok"], true);
+}
+
+#[test]
+fn chacha20_matches_rfc_8439_and_the_aead_refuses_altered_messages() {
+    let key = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+    let encrypted = crypto_json(&["chacha20", "encrypt", "--key", key, "--hex", "--nonce", "000000000000004a00000000", "Ladies and Gentlemen"]);
+    assert_eq!(encrypted["result"], "000000000000004a000000006e2e359a2568f98041ba0728dd0d6981e97e7aec");
+    let decrypted = crypto_json(&["chacha20", "decrypt", "--key", key, "--hex", encrypted["result"].as_str().unwrap()]);
+    assert_eq!(decrypted["result"], "Ladies and Gentlemen");
+
+    let sealed = crypto_json(&["chacha20", "encrypt", "--key", key, "--hex", "--aead", "--aad", "header", "attack at dawn"]);
+    assert_eq!(sealed["cipher"], "chacha20-poly1305");
+    let sealed = sealed["result"].as_str().unwrap();
+    let opened = crypto_json(&["chacha20", "decrypt", "--key", key, "--hex", "--aead", "--aad", "header", sealed]);
+    assert_eq!(opened["result"], "attack at dawn");
+    let other_header = crypto_json(&["chacha20", "decrypt", "--key", key, "--hex", "--aead", "--aad", "footer", sealed]);
+    assert_eq!(other_header["ok"], false);
+}
+
+#[test]
+fn hybrid_containers_open_with_the_recipients_pki_key() {
+    let dir = std::env::temp_dir().join(format!("crypto-hybrid-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    // A user as the PKI leaves it: an OpenSSL key and certificate side by side
+    let openssl = |args: &[&str]| assert!(Command::new("openssl").args(args).current_dir(&dir).output().unwrap().status.success());
+    openssl(&["genrsa", "-out", "alice_private_key.pem", "2048"]);
+    openssl(&["req", "-new", "-x509", "-key", "alice_private_key.pem", "-subj", "/CN=alice", "-out", "alice_certificate.pem"]);
+    std::fs::write(dir.join("grades.csv"), "alice,10\nbob,9\n").unwrap();
+
+    let hybrid = |args: &[&str]| -> Value {
+        let output = Command::new(env!("CARGO_BIN_EXE_crypto"))
+            .args(["--output", "json", "hybrid"])
+            .args(args)
+            .current_dir(&dir)
+            .env("PKI_USERS_DIR", ".")
+            .output()
+            .expect("failed to run crypto");
+        serde_json::from_slice(&output.stdout).expect("crypto printed invalid JSON")
+    };
+
+    let encrypted = hybrid(&["encrypt", "--for", "alice", "--cipher", "3des", "grades.csv"]);
+    assert_eq!(encrypted["output"], "grades.csv.hybrid");
+    let container = std::fs::read_to_string(dir.join("grades.csv.hybrid")).unwrap();
+    assert!(!container.contains("alice,10"));
+
+    let decrypted = hybrid(&["decrypt", "--out", "opened.csv", "grades.csv.hybrid"]);
+    assert_eq!(decrypted["cipher"], "3des");
+    assert_eq!(std::fs::read_to_string(dir.join("opened.csv")).unwrap(), "alice,10\nbob,9\n");
+
+    assert_eq!(hybrid(&["encrypt", "--for", "bob", "grades.csv"])["ok"], false);
+    openssl(&["genrsa", "-out", "mallory.pem", "2048"]);
+    assert_eq!(hybrid(&["decrypt", "--key", "mallory.pem", "--out", "stolen.csv", "grades.csv.hybrid"])["ok"], false);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn aes_modes_match_sp_800_38a() {
+    let key = "2b7e151628aed2a6abf7158809cf4f3c";
+    let iv = "000102030405060708090a0b0c0d0e0f";
+    let encrypted = crypto_json(&["aes", "encrypt", "--key", key, "--hex", "--mode", "cbc", "--iv", iv, "Attack at dawn!!"]);
+    let ciphertext = encrypted["result"].as_str().unwrap();
+    assert!(ciphertext.starts_with(iv));
+
+    let decrypted = crypto_json(&["aes", "decrypt", "--key", key, "--hex", "--mode", "cbc", ciphertext]);
+    assert_eq!(decrypted["result"], "Attack at dawn!!");
+
+    let des = crypto_json(&["des", "encrypt", "--key", "133457799BBCDFF1", "--hex", "--mode", "ctr", "ten bytes!"]);
+    assert_eq!(des["result"].as_str().unwrap().len(), 2 * (8 + 10));
+}
+
+#[test]
+fn primes_gen_and_test_agree() {
+    let generated = crypto_json(&["primes", "gen", "128"]);
+    assert_eq!(generated["bits"], 128);
+
+    let tested = crypto_json(&["primes", "test", generated["prime"].as_str().unwrap()]);
+    assert_eq!(tested["prime"], true);
+    assert_eq!(crypto_json(&["primes", "test", "561"])["prime"], false);
+}
+
+#[test]
+fn math_solves_homework_problems() {
+    assert_eq!(crypto_json(&["math", "inverse", "17", "3120"])["inverse"], "2753");
+    assert_eq!(crypto_json(&["math", "pow", "4", "13", "497"])["result"], "445");
+    assert_eq!(crypto_json(&["math", "crt", "2:3", "3:5", "2:7"])["result"], "23");
+    assert_eq!(crypto_json(&["math", "inverse", "6", "9"])["ok"], false);
+}
+
+#[test]
+fn dh_listen_and_connect_agree_on_a_key() {
+    // Find a free port; the listener binds it again straight away
+    let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+    let listener = Command::new(env!("CARGO_BIN_EXE_crypto"))
+        .args(["--output", "json", "dh", "listen", "--bits", "128", &address])
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .expect("failed to run crypto");
+
+    let mut sender = Value::Null;
+    for _ in 0..100 {
+        sender = crypto_json(&["dh", "connect", "--message", "meet at noon", &address]);
+        if sender["ok"] == true {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    let receiver: Value = serde_json::from_slice(&listener.wait_with_output().unwrap().stdout).unwrap();
+
+    assert_eq!(sender["ok"], true);
+    assert_eq!(receiver["key"], sender["key"]);
+    assert_eq!(receiver["own_public"], sender["peer_public"]);
+    assert_eq!(receiver["message"], "meet at noon");
+}
+
+#[test]
+fn dh_mitm_leaves_alice_and_bob_with_different_keys() {
+    let report = crypto_json(&["dh", "mitm", "--bits", "128"]);
+    assert_ne!(report["alice_key"], report["bob_key"]);
+    assert_eq!(report["mallory_keys"][0], report["alice_key"]);
+    assert_eq!(report["mallory_keys"][1], report["bob_key"]);
+}
+
+#[test]
+fn elgamal_nonce_reuse_recovers_the_key() {
+    let report = crypto_json(&["elgamal", "attack", "nonce-reuse", "--bits", "128"]);
+    assert_eq!(report["recovered"], true);
+}
+
+#[test]
+fn ecc_toy_curve_tables() {
+    let points = crypto_json(&["ecc", "points"]);
+    assert_eq!(points["points"].as_array().unwrap().len(), 19);
+
+    let multiple = crypto_json(&["ecc", "multiply", "13"]);
+    assert_eq!(multiple["result"]["x"], "16");
+    assert_eq!(multiple["result"]["y"], "4");
+    assert_eq!(crypto_json(&["ecc", "add", "5,1", "5,16"])["result"], Value::Null);
+    assert_eq!(crypto_json(&["ecc", "add", "5,1", "5,2"])["ok"], false);
+}
+
+#[test]
+fn hash_sha256_matches_the_nist_example() {
+    let dir = std::env::temp_dir().join(format!("crypto-hash-{}", std::process::id()));
+    std::fs::write(&dir, "abc").unwrap();
+    let report = crypto_json(&["hash", "sha256", dir.to_str().unwrap()]);
+    std::fs::remove_file(&dir).unwrap();
+    assert_eq!(report["hashes"][0]["sha256"], "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+}
+
+#[test]
+fn mac_matches_rfc_4231_and_guards_des() {
+    let report = crypto_json(&["mac", "tag", "--key", "Jefe", "what do ya want for nothing?"]);
+    assert_eq!(report["tag"], "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+
+    let sealed = crypto_json(&["des", "encrypt", "--key", "MORTYNOR", "--mac-key", "secret", "hello DES"]);
+    let ciphertext = sealed["result"].as_str().unwrap();
+    let opened = crypto_json(&["des", "decrypt", "--key", "MORTYNOR", "--mac-key", "secret", ciphertext]);
+    assert_eq!(opened["result"], "hello DES");
+    assert_eq!(opened["authenticated"], true);
+    assert_eq!(crypto_json(&["des", "decrypt", "--key", "MORTYNOR", "--mac-key", "wrong", ciphertext])["ok"], false);
+}
+
+#[test]
+fn passwd_hash_and_verify() {
+    let stored = crypto_json(&["passwd", "hash", "--iterations", "1000", "hunter2"]);
+    let hash = stored["hash"].as_str().unwrap();
+    assert!(hash.starts_with("$pbkdf2-sha256$i=1000$"));
+    assert_eq!(crypto_json(&["passwd", "verify", "--hash", hash, "hunter2"])["valid"], true);
+    assert_eq!(crypto_json(&["passwd", "verify", "--hash", hash, "hunter3"])["ok"], false);
+
+    let memory_hard = crypto_json(&["passwd", "hash", "--memory-hard", "--cost", "8", "hunter2"]);
+    assert_eq!(crypto_json(&["passwd", "verify", "--hash", memory_hard["hash"].as_str().unwrap(), "hunter2"])["valid"], true);
+}
+
+#[test]
+fn shamir_split_and_combine() {
+    let split = crypto_json(&["shamir", "split", "--threshold", "2", "--shares", "3", "exam answers"]);
+    let shares: Vec<&str> = split["shares"].as_array().unwrap().iter().map(|share| share.as_str().unwrap()).collect();
+    assert_eq!(crypto_json(&["shamir", "combine", shares[0], shares[2]])["result"], "exam answers");
+    assert_ne!(crypto_json(&["shamir", "combine", shares[1]])["result"], "exam answers");
+}
+
+#[test]
+fn merkle_proofs_check_out() {
+    let items = ["alice paid bob", "bob paid carol", "carol paid dave", "dave paid erin", "erin paid frank"];
+    let args = |operation: &'static [&'static str]| operation.iter().copied().chain(items).collect::<Vec<&str>>();
+    let proof = crypto_json(&args(&["merkle", "prove", "--index", "3", "--messages"]));
+    let path: Vec<&str> = proof["path"].as_array().unwrap().iter().map(|hash| hash.as_str().unwrap()).collect();
+    let path = path.join(",");
+    let root = proof["root_hash"].as_str().unwrap();
+
+    let verify = |item: &str| crypto_json(&["merkle", "verify", "--index", "3", "--size", "5", "--root", root, "--path", &path, "--messages", item]);
+    assert_eq!(verify("dave paid erin")["valid"], true);
+    assert_eq!(verify("dave paid mallory")["ok"], false);
+
+    let consistency = crypto_json(&args(&["merkle", "consistency", "--old-size", "3", "--messages"]));
+    assert_eq!(consistency["new_root"], root);
+    assert_eq!(consistency["valid"], true);
+}
+
+/// A 40x30 24-bit BMP gradient using only even values
+fn write_cover_bmp(path: &std::path::Path) {
+    let (width, height) = (40u32, 30u32);
+    let stride = (width * 3).div_ceil(4) * 4;
+    let mut bmp = b"BM".to_vec();
+    bmp.extend((54 + stride * height).to_le_bytes());
+    bmp.extend([0; 4]);
+    bmp.extend(54u32.to_le_bytes());
+    bmp.extend(40u32.to_le_bytes());
+    bmp.extend(width.to_le_bytes());
+    bmp.extend(height.to_le_bytes());
+    bmp.extend(1u16.to_le_bytes());
+    bmp.extend(24u16.to_le_bytes());
+    bmp.extend([0; 24]);
+    for y in 0..height {
+        for x in 0..width {
+            bmp.extend([(x * 6) as u8, (y * 8) as u8, ((x + y) * 2) as u8]);
+        }
+        bmp.resize(bmp.len() + (stride - width * 3) as usize, 0);
+    }
+    std::fs::write(path, bmp).unwrap();
+}
+
+#[test]
+fn stego_hides_an_encrypted_message_in_an_image() {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("stego");
+    std::fs::create_dir_all(&dir).unwrap();
+    let (cover, stego) = (dir.join("cover.bmp"), dir.join("stego.png"));
+    write_cover_bmp(&cover);
+    let (cover, stego) = (cover.to_str().unwrap(), stego.to_str().unwrap());
+
+    let capacity = crypto_json(&["stego", "capacity", cover]);
+    assert_eq!(capacity["capacity"], 40 * 30 * 3 / 8 - 4);
+    assert_eq!(crypto_json(&["stego", "detect", cover])["suspicious_percent"], 0);
+
+    let hidden = crypto_json(&["stego", "hide", "--cipher", "aes", "--key", "YELLOW SUBMARINE", cover, stego, "the key is under the mat"]);
+    assert_eq!(hidden["encrypted"], true);
+    let extracted = crypto_json(&["stego", "extract", "--cipher", "aes", "--key", "YELLOW SUBMARINE", stego]);
+    assert_eq!(extracted["result"], "the key is under the mat");
+    assert_eq!(crypto_json(&["stego", "extract", cover])["ok"], false);
+}
+
+#[test]
+fn encode_and_decode_round_trip() {
+    assert_eq!(crypto_json(&["encode", "base64", "foobar"])["result"], "Zm9vYmFy");
+    assert_eq!(crypto_json(&["encode", "base32", "--hex", "666f6f"])["result"], "MZXW6===");
+    assert_eq!(crypto_json(&["decode", "base32", "MZXW6==="])["result"], "foo");
+    assert_eq!(crypto_json(&["decode", "base64", "--hex", "Zm8="])["result"], "666f");
+
+    assert_eq!(crypto_json(&["decode", "base64", "Zm9v\nYmE"])["ok"], false);
+    assert_eq!(crypto_json(&["decode", "base64", "--lenient", "Zm9v\nYmE"])["result"], "fooba");
+    // Pasted hex keys and ciphertexts may carry separators
+    assert_eq!(crypto_json(&["des", "decrypt", "--key", "MORTYNOR", "0x1634d740:da0ec08e ae40c629:1fa7a9c1"])["result"], "hello DES");
+}
+
+#[test]
+fn analyze_recognises_what_the_classical_ciphers_did() {
+    let plaintext = "It is a truth universally acknowledged, that a single man in possession of a good fortune, must be in want of a wife";
+    let encrypt = |args: &[&str]| crypto_json(&[args, &[plaintext]].concat())["result"].as_str().unwrap().to_string();
+
+    let caesar = crypto_json(&["analyze", &encrypt(&["caesar", "encrypt", "--key", "7"])]);
+    assert_eq!((caesar["assessment"].as_str(), caesar["shift"].as_u64()), (Some("caesar"), Some(7)));
+    assert_eq!(crypto_json(&["analyze", &encrypt(&["railfence", "encrypt", "--key", "3"])])["assessment"], "transposition");
+    assert_eq!(crypto_json(&["analyze", &encrypt(&["vigenere", "encrypt", "--key", "LEMON"])])["assessment"], "polyalphabetic");
+
+    let report = crypto_json(&["analyze", "--top", "1", plaintext]);
+    assert_eq!(report["assessment"], "plaintext");
+    assert_eq!(report["letters"], 92);
+    assert_eq!(report["trigrams"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn randtest_passes_keystreams_and_fails_stuck_bits() {
+    let keystream = crypto_json(&["rc4", "keystream", "--key", "Key", "--bytes", "4096"])["keystream"].as_str().unwrap().to_string();
+    let report = crypto_json(&["randtest", &keystream]);
+    assert_eq!((report["source"].as_str(), report["bits"].as_u64()), (Some("hex"), Some(32768)));
+    assert_eq!(report["passed"], true);
+    assert_eq!(crypto_json(&["randtest", "--os", "512"])["tests"].as_array().unwrap().len(), 4);
+
+    let stuck = crypto_json(&["randtest", &"1101".repeat(64)]);
+    assert_eq!(stuck["source"], "bits");
+    assert_eq!(stuck["passed"], false);
+    // The worked example of SP 800-22 section 2.1.8
+    let monobit = &crypto_json(&["randtest", "1011010101"])["tests"][0];
+    assert!((monobit["p_values"][0].as_f64().unwrap() - 0.527089).abs() < 1e-6);
+}
+
+#[test]
+fn challenges_are_unique_per_student_and_checked_against_the_sealed_key() {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("challenge");
+    std::fs::create_dir_all(&dir).unwrap();
+    let students = dir.join("students.txt");
+    std::fs::write(&students, "Ana Popescu\n# absent\nIon\n").unwrap();
+    let (students, dir) = (students.to_str().unwrap(), dir.to_str().unwrap());
+
+    let generated = crypto_json(&["challenge", "gen", "--cipher", "vigenere", "--difficulty", "hard", "--students", students, "--passphrase", "grader", "--out", dir]);
+    assert_eq!(generated["challenges"].as_array().unwrap().len(), 2);
+    let answers = generated["answers"].as_str().unwrap();
+    let sheet = std::fs::read_to_string(generated["challenges"][0]["sheet"].as_str().unwrap()).unwrap();
+    assert!(sheet.starts_with("Cryptanalysis challenge for Ana Popescu\nCipher: vigenere (hard)"));
+
+    let opened = crypto_json(&["challenge", "open", "--passphrase", "grader", answers]);
+    let (ana, ion) = (&opened["students"][0], &opened["students"][1]);
+    assert_ne!(ana["ciphertext"], ion["ciphertext"]);
+    assert!(sheet.contains(ana["ciphertext"].as_str().unwrap()));
+    assert!(!std::fs::read_to_string(answers).unwrap().contains(ana["plaintext"].as_str().unwrap()));
+
+    let check = |student: &str, plaintext: &str| crypto_json(&["challenge", "check", "--passphrase", "grader", "--student", student, answers, plaintext]);
+    let answer = ana["plaintext"].as_str().unwrap().to_lowercase();
+    assert_eq!(check("Ana Popescu", &answer)["correct"], true);
+    assert_eq!(check("Ion", &answer)["correct"], false);
+    assert_eq!(crypto_json(&["challenge", "open", "--passphrase", "guess", answers])["ok"], false);
+}
+
+#[test]
+fn reports_work_through_every_step_to_the_ciphers_own_result() {
+    let playfair = crypto_json(&["report", "--cipher", "playfair", "--key", "MONARCHY", "--text", "instruments"]);
+    assert_eq!(playfair["result"], crypto_json(&["playfair", "encrypt", "--key", "MONARCHY", "INSTRUMENTS"])["result"]);
+    let html = playfair["html"].as_str().unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<td>M</td><td>O</td><td>N</td><td>A</td><td>R</td>"));
+    assert!(html.contains("<td>IN</td><td>(3, 4) (1, 3)</td><td>rectangle: take the other two corners</td><td>GA</td>"));
+
+    let des = crypto_json(&["report", "--cipher", "des", "--key", "133457799BBCDFF1", "--hex", "hello DES"]);
+    assert_eq!(des["result"], crypto_json(&["des", "encrypt", "--key", "133457799BBCDFF1", "--hex", "hello DES"])["result"]);
+    let html = des["html"].as_str().unwrap();
+    // K+ and K1 of the textbook key schedule
+    assert!(html.contains("F0CCAAF556678F") && html.contains("<td>K1</td><td>1B02EFFC7072</td>"));
+    assert_eq!(html.matches("<td>16</td>").count(), 2);
+
+    let file = std::env::temp_dir().join(format!("crypto-report-{}.html", std::process::id()));
+    let written = crypto_json(&["--lang", "ro", "report", "--cipher", "hill", "--key", "3 3 2 5", "--out", file.to_str().unwrap(), "help"]);
+    assert_eq!(written["result"], "HIAT");
+    let html = std::fs::read_to_string(&file).unwrap();
+    std::fs::remove_file(&file).unwrap();
+    assert!(html.contains("<html lang=\"ro\">") && html.contains("Cifrul Hill"));
+
+    assert_eq!(crypto_json(&["report", "--cipher", "enigma", "--key", "A", "text"])["ok"], false);
+}
+
+#[test]
+fn quiz_checks_answers_and_records_the_score() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::process::Stdio;
+
+    let results = std::env::temp_dir().join(format!("crypto-quiz-{}.csv", std::process::id()));
+    let mut quiz = Command::new(env!("CARGO_BIN_EXE_crypto"))
+        .args(["--output", "json", "quiz", "--student", "Popescu, Ion", "--questions", "3", "--topic", "inverse"])
+        .args(["--results", results.to_str().unwrap()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run crypto");
+
+    // Answer the first two questions right and the last one wrong
+    let mut stdin = quiz.stdin.take().unwrap();
+    let mut prompts = BufReader::new(quiz.stderr.take().unwrap()).lines();
+    for number in 1..=3 {
+        let prompt = prompts.next().unwrap().unwrap();
+        let value: i64 = prompt.split("inverse of ").nth(1).unwrap().split(' ').next().unwrap().parse().unwrap();
+        let inverse = (1..26).find(|candidate| value * candidate % 26 == 1).unwrap();
+        let answer = if number < 3 { inverse } else { inverse + 1 };
+        writeln!(stdin, "{}", answer).unwrap();
+    }
+    drop(stdin);
+
+    let output = quiz.wait_with_output().unwrap();
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["score"], 2);
+    assert_eq!(report["answers"].as_array().unwrap().len(), 3);
+    assert_eq!(report["answers"][2]["correct"], false);
+
+    let csv = std::fs::read_to_string(&results).unwrap();
+    std::fs::remove_file(&results).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "time,student,topics,score,questions");
+    assert!(lines[1].ends_with(",\"Popescu, Ion\",inverse,2,3"));
+
+    assert_eq!(crypto_json(&["quiz", "--student", "Ana", "--topic", "enigma"])["ok"], false);
+}
+
+/// POST a JSON-RPC body to `crypto serve`, returning the status line and the JSON answer
+fn rpc(address: &str, api_key: Option<&str>, body: &Value) -> (String, Value) {
+    use std::io::{Read, Write};
+
+    let mut stream = std::net::TcpStream::connect(address).unwrap();
+    let body = body.to_string();
+    let auth = api_key.map(|key| format!("Authorization: Bearer {}\r\n", key)).unwrap_or_default();
+    write!(stream, "POST /rpc HTTP/1.1\r\nHost: {}\r\n{}Content-Length: {}\r\n\r\n{}", address, auth, body.len(), body).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    (head.lines().next().unwrap().to_string(), serde_json::from_str(body).unwrap())
+}
+
+#[test]
+fn serve_answers_json_rpc_calls_with_an_api_key() {
+    use std::io::BufRead;
+
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("serve");
+    std::fs::create_dir_all(&dir).unwrap();
+    let key_path = dir.join("rsa.json");
+    assert_eq!(crypto_json(&["rsa", "keygen", "--bits", "512", key_path.to_str().unwrap()])["ok"], true);
+    let key: Value = serde_json::from_str(&std::fs::read_to_string(&key_path).unwrap()).unwrap();
+
+    let mut server = Command::new(env!("CARGO_BIN_EXE_crypto"))
+        .args(["--output", "json", "serve", "--api-key", "lab-key", "127.0.0.1:0"])
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .expect("failed to run crypto");
+    let mut started = String::new();
+    std::io::BufReader::new(server.stdout.take().unwrap()).read_line(&mut started).unwrap();
+    let started: Value = serde_json::from_str(&started).unwrap();
+    let address = started["address"].as_str().unwrap();
+
+    let call = |method: &str, params: Value| json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": 1 });
+    let (status, _) = rpc(address, None, &call("caesar.encrypt", json!({ "key": "3", "text": "attack" })));
+    assert!(status.contains("401"));
+
+    let (status, encrypted) = rpc(address, Some("lab-key"), &call("caesar.encrypt", json!({ "key": "3", "text": "attack" })));
+    assert!(status.contains("200"));
+    assert_eq!(encrypted["result"]["result"], "dwwdfn");
+
+    let (_, sealed) = rpc(address, Some("lab-key"), &call("aes.encrypt", json!({ "key": "YELLOW SUBMARINE", "mode": "cbc", "text": "lab" })));
+    let ciphertext = sealed["result"]["result"].clone();
+    let (_, opened) = rpc(address, Some("lab-key"), &call("aes.decrypt", json!({ "key": "YELLOW SUBMARINE", "mode": "cbc", "text": ciphertext })));
+    assert_eq!(opened["result"]["result"], "lab");
+
+    let (_, signed) = rpc(address, Some("lab-key"), &call("rsa.sign", json!({ "key": key, "text": "grade: 10" })));
+    let signature = signed["result"]["signature"].clone();
+    let (_, verified) = rpc(address, Some("lab-key"), &call("rsa.verify", json!({ "key": key, "signature": signature, "text": "grade: 10" })));
+    assert_eq!(verified["result"]["valid"], true);
+    let (_, forged) = rpc(address, Some("lab-key"), &call("rsa.verify", json!({ "key": key, "signature": signature, "text": "grade: 4" })));
+    assert_eq!(forged["error"]["data"]["valid"], false);
+
+    // Key files and other paths on the server are never read
+    let (_, refused) = rpc(address, Some("lab-key"), &call("rsa.sign", json!({ "key": key_path, "text": "x" })));
+    assert_eq!(refused["error"]["code"], -32602);
+    let (_, unknown) = rpc(address, Some("lab-key"), &call("otp.generate", json!({})));
+    assert_eq!(unknown["error"]["code"], -32601);
+
+    server.kill().unwrap();
+    server.wait().unwrap();
+}
+
+#[test]
+fn settings_layer_the_file_the_environment_and_flags() {
+    let file = std::env::temp_dir().join(format!("crypto-config-{}.toml", std::process::id()));
+    std::fs::write(&file, "[output]\nformat = \"json\"\n\n[ciphers]\nalphabet = \"romanian\"  # lab 3\n").unwrap();
+    let run = |args: &[&str], env: &[(&str, &str)]| {
+        Command::new(env!("CARGO_BIN_EXE_crypto"))
+            .args(args)
+            .env("CRYPTO_CONFIG", &file)
+            .env_remove("CRYPTO_OUTPUT")
+            .env_remove("CRYPTO_ALPHABET")
+            .envs(env.iter().copied())
+            .output()
+            .expect("failed to run crypto")
+    };
+    let json = |output: Output| -> Value { serde_json::from_slice(&output.stdout).expect("crypto printed invalid JSON") };
+
+    let report = json(run(&["config"], &[]));
+    assert_eq!(report["file"], file.to_str().unwrap());
+    assert_eq!(report["settings"]["ciphers.alphabet"], json!({ "value": "romanian", "source": file.to_str().unwrap() }));
+    assert_eq!(report["settings"]["pki.ca_dir"], json!({ "value": "./pki/ca", "source": "default" }));
+
+    let report = json(run(&["config"], &[("CRYPTO_ALPHABET", "latin")]));
+    assert_eq!(report["settings"]["ciphers.alphabet"], json!({ "value": "latin", "source": "$CRYPTO_ALPHABET" }));
+    let text = String::from_utf8(run(&["--output", "text", "config"], &[]).stdout).unwrap();
+    assert!(text.contains("output.format") && text.contains("command line"), "{}", text);
+
+    // Hill works modulo 31 over the Romanian alphabet, modulo 26 with --english
+    let romanian = json(run(&["hill", "encrypt", "--key", "3 3 2 5", "HELP"], &[]));
+    let english = json(run(&["hill", "encrypt", "--key", "3 3 2 5", "--english", "HELP"], &[]));
+    assert_eq!(english["result"], crypto_json(&["hill", "encrypt", "--key", "3 3 2 5", "HELP"])["result"]);
+    assert_ne!(romanian["result"], english["result"]);
+
+    std::fs::write(&file, "[ciphers]\nalphabett = \"romanian\"\n").unwrap();
+    let rejected = run(&["config"], &[]);
+    std::fs::remove_file(&file).unwrap();
+    assert!(!rejected.status.success());
+    let stderr = String::from_utf8_lossy(&rejected.stderr);
+    assert!(stderr.starts_with("Error: ") && stderr.contains("unknown setting ciphers.alphabett") && !stderr.contains("Custom {"), "{}", stderr);
+}
+
+#[test]
+fn messages_follow_the_lang_flag_then_the_setting_then_the_locale() {
+    let run = |args: &[&str], env: &[(&str, &str)]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_crypto"))
+            .args(args)
+            .env_remove("CRYPTO_LANG")
+            .env_remove("LC_ALL")
+            .env_remove("LC_MESSAGES")
+            .env_remove("LANG")
+            .envs(env.iter().copied())
+            .output()
+            .expect("failed to run crypto");
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let crack = ["caesar", "crack", "Tlla tl hmaly aol sljabyl"];
+    let with_lang: Vec<&str> = ["--lang", "ro"].iter().chain(&crack).copied().collect();
+
+    assert!(run(&crack, &[]).starts_with("Shift 7: "));
+    assert!(run(&with_lang, &[]).starts_with("Deplasare 7: "));
+    assert!(run(&crack, &[("LANG", "ro_RO.UTF-8")]).starts_with("Deplasare 7: "));
+    assert!(run(&crack, &[("LANG", "ro_RO.UTF-8"), ("CRYPTO_LANG", "en")]).starts_with("Shift 7: "));
+    assert!(run(&["--help"], &[("CRYPTO_LANG", "ro")]).starts_with("Utilizare: crypto"));
+
+    // JSON keeps its English fields, and only the error is translated
+    let json: Value = serde_json::from_str(&run(&["--output", "json", "--lang", "ro", "caesar", "crack", crack[2]], &[])).unwrap();
+    assert_eq!(json["shift"], 7);
+    let json: Value = serde_json::from_str(&run(&["--output", "json", "--lang", "ro", "pki", "nosuch"], &[])).unwrap();
+    assert_eq!(json["error"], "Comandă necunoscută: nosuch");
+}
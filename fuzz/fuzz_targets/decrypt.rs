@@ -0,0 +1,39 @@
+//! Every decrypt path on arbitrary containers: it may reject them, but it
+//! must not panic, and an authenticated one must not open unless its tag
+//! checks out
+
+#![no_main]
+
+use crypto_core::mac;
+use crypto_core::hash::Sha256;
+use crypto_core::modes::{self, MAC_LENGTH};
+use crypto_core::SymmetricCipher;
+use encryption_courses_fuzz::{decrypt_selector, rc4, rsa_key, Container, MAC_KEY};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, container)) = data.split_first() else {
+        return;
+    };
+    let (cipher, mode, kind) = decrypt_selector(selector);
+
+    match kind {
+        Container::Plain => {
+            let iv_length = if mode.needs_iv() { cipher.block_size().min(container.len()) } else { 0 };
+            let (iv, ciphertext) = container.split_at(iv_length);
+            let _ = modes::decrypt(cipher.as_ref(), mode, iv, ciphertext);
+        }
+        Container::Authenticated => {
+            if modes::decrypt_verified(cipher.as_ref(), mode, MAC_KEY, container).is_ok() {
+                let (authenticated, tag) = container.split_at(container.len() - MAC_LENGTH);
+                assert!(mac::verify::<Sha256>(MAC_KEY, authenticated, tag));
+            }
+        }
+        Container::Rc4 => {
+            let _ = rc4().decrypt(container);
+        }
+        Container::RsaOaep => {
+            let _ = rsa_key().decrypt_oaep(container);
+        }
+    }
+});
@@ -0,0 +1,56 @@
+//! Key and parameter parsing across the modules: arbitrary text is either
+//! rejected or gives a cipher that then works on the same text
+
+#![no_main]
+
+use crypto_core::caesar::Caesar;
+use crypto_core::codec::{Encoding, Strictness};
+use crypto_core::columnar::Columnar;
+use crypto_core::ecc::Curve;
+use crypto_core::hill::Hill;
+use crypto_core::language::Alphabet;
+use crypto_core::modes::Mode;
+use crypto_core::railfence::RailFence;
+use crypto_core::shamir::Share;
+use crypto_core::vigenere::Vigenere;
+use crypto_core::ClassicalCipher;
+use libfuzzer_sys::fuzz_target;
+use playfair::Playfair;
+
+fn exercise(cipher: Option<impl ClassicalCipher>, text: &str) {
+    if let Some(cipher) = cipher {
+        if let Ok(ciphertext) = cipher.encrypt(text) {
+            let _ = cipher.decrypt(&ciphertext);
+        }
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    // The key is the first line, the text to try it on the rest
+    let (key, message) = text.split_once('\n').unwrap_or((text, "ATTACK AT DAWN"));
+
+    exercise(Caesar::new(key).ok(), message);
+    exercise(Vigenere::new(key).ok(), message);
+    exercise(Hill::with_alphabet(key, Alphabet::Latin).ok(), message);
+    exercise(Hill::with_alphabet(key, Alphabet::Romanian).ok(), message);
+    exercise(RailFence::new(key).ok(), message);
+    exercise(Columnar::new(key).ok(), message);
+    exercise(Playfair::new(key).ok(), message);
+
+    for encoding in [Encoding::Hex, Encoding::Base32, Encoding::Base64] {
+        // Strict hex takes either case; everything else only what encode writes
+        if let Ok(bytes) = encoding.decode(key, Strictness::Strict) {
+            let encoded = encoding.encode(&bytes);
+            assert!(encoded == key || encoding == Encoding::Hex && encoded.eq_ignore_ascii_case(key));
+        }
+        let _ = encoding.decode(key, Strictness::Lenient);
+    }
+    if let Some(share) = Share::parse(key) {
+        assert_eq!(Share::parse(&share.to_string()), Some(share));
+    }
+    let _ = Curve::by_name(key);
+    let _ = Mode::parse(key);
+});
@@ -0,0 +1,21 @@
+//! PKCS#7 padding removal: never a panic, and whatever it accepts from
+//! whole blocks, padding restores exactly
+
+#![no_main]
+
+use crypto_core::modes::{pad_pkcs7, unpad_pkcs7};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&block_size, data)) = data.split_first() else {
+        return;
+    };
+    let block_size = usize::from(block_size % 32) + 1;
+
+    if let Ok(unpadded) = unpad_pkcs7(data, block_size) {
+        assert!(unpadded.len() < data.len());
+        if data.len() % block_size == 0 {
+            assert_eq!(pad_pkcs7(&unpadded, block_size), data);
+        }
+    }
+});
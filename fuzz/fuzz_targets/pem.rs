@@ -0,0 +1,24 @@
+//! The PKI's PEM bundle splitting, subject parsing and EST request
+//! wrapping on arbitrary text
+
+#![no_main]
+
+#[path = "../../DSA/src/pem.rs"]
+mod pem;
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let text = String::from_utf8_lossy(data);
+
+    for certificate in pem::split_certificates(&text) {
+        assert!(certificate.starts_with("-----BEGIN CERTIFICATE-----"));
+        assert!(certificate.ends_with("-----END CERTIFICATE-----\n"));
+    }
+    if let Some(name) = pem::common_name_from_subject(&text) {
+        assert!(!name.is_empty());
+    }
+    if let Ok(csr) = pem::csr_pem_from_base64(&text) {
+        assert!(csr.lines().all(|line| line.len() <= 64 || line.starts_with("-----")));
+    }
+});
@@ -0,0 +1,67 @@
+//! Fixed keys and input layouts shared by the fuzz targets and by
+//! `seed-corpus`, which writes each target's starting corpus from what the
+//! crates themselves produce under those keys.
+
+use std::sync::OnceLock;
+
+use crypto_core::aes::Aes128;
+use crypto_core::modes::Mode;
+use crypto_core::rc4::Rc4;
+use crypto_core::rsa::RsaPrivateKey;
+use crypto_core::{BlockCipher, SymmetricCipher};
+use des::Des;
+use num_bigint::BigUint;
+
+pub const DES_KEY: &[u8] = b"MORTYNOR";
+pub const AES_KEY: &[u8] = b"YELLOW SUBMARINE";
+pub const MAC_KEY: &[u8] = b"independent mac key";
+pub const RC4_KEY: &[u8] = b"Key";
+
+/// How the `decrypt` target reads the bytes after its selector byte
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Container {
+    /// IV ‖ ciphertext, as `crypto des|aes encrypt` prints it
+    Plain,
+    /// IV ‖ ciphertext ‖ HMAC-SHA256 tag, from `modes::encrypt_then_mac`
+    Authenticated,
+    Rc4,
+    RsaOaep,
+}
+
+/// What the first byte of a `decrypt` input selects: DES or AES, the
+/// mode, and the container
+pub fn decrypt_selector(selector: u8) -> (Box<dyn BlockCipher>, Mode, Container) {
+    let cipher: Box<dyn BlockCipher> = if selector & 1 == 0 {
+        Box::new(Des::new(DES_KEY).unwrap())
+    } else {
+        Box::new(Aes128::new(AES_KEY).unwrap())
+    };
+    let mode = [Mode::Ecb, Mode::Cbc, Mode::Ctr][(selector as usize >> 1) % 3];
+    let container = [Container::Plain, Container::Authenticated, Container::Rc4, Container::RsaOaep][(selector as usize >> 3) % 4];
+    (cipher, mode, container)
+}
+
+/// The selector byte for a cipher (0 DES, 1 AES), mode index and container
+pub fn selector(aes: bool, mode: usize, container: Container) -> u8 {
+    let container = match container {
+        Container::Plain => 0,
+        Container::Authenticated => 1,
+        Container::Rc4 => 2,
+        Container::RsaOaep => 3,
+    };
+    u8::from(aes) | (mode as u8) << 1 | container << 3
+}
+
+pub fn rc4() -> Rc4 {
+    Rc4::new(RC4_KEY).unwrap()
+}
+
+/// An RSA key from two Mersenne primes, 2⁵²¹ − 1 and 2⁶⁰⁷ − 1, so every
+/// run decrypts under the same 1128-bit modulus
+pub fn rsa_key() -> &'static RsaPrivateKey {
+    static KEY: OnceLock<RsaPrivateKey> = OnceLock::new();
+    KEY.get_or_init(|| {
+        let mersenne = |exponent: u32| (BigUint::from(1u8) << exponent) - 1u8;
+        RsaPrivateKey::from_primes(mersenne(521), mersenne(607), BigUint::from(65_537u32)).unwrap()
+    })
+}
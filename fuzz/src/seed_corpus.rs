@@ -0,0 +1,112 @@
+//! Write a starting corpus for every target into `fuzz/corpus/<target>/`,
+//! from what the crates themselves produce, so the fuzzer begins from
+//! inputs that get past the first length and format checks
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crypto_core::aes::Aes128;
+use crypto_core::codec::encode_base64;
+use crypto_core::modes::{self, Mode};
+use crypto_core::shamir;
+use crypto_core::{BlockCipher, SymmetricCipher};
+use des::Des;
+use encryption_courses_fuzz::{rc4, rsa_key, selector, Container, AES_KEY, DES_KEY, MAC_KEY};
+
+const PLAINTEXTS: [&[u8]; 4] = [b"", b"ATTACK AT DAWN", b"exactly 16 bytes", b"Two blocks and a bit of a third one!"];
+
+fn corpus_dir(target: &str) -> io::Result<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("corpus").join(target);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Write each seed under its index, returning how many there were
+fn write_seeds(target: &str, seeds: &[Vec<u8>]) -> io::Result<usize> {
+    let dir = corpus_dir(target)?;
+    for (index, seed) in seeds.iter().enumerate() {
+        fs::write(dir.join(format!("seed-{:03}", index)), seed)?;
+    }
+    println!("{:>8}: {} seeds in {}", target, seeds.len(), dir.display());
+    Ok(seeds.len())
+}
+
+fn decrypt_seeds() -> Vec<Vec<u8>> {
+    let ciphers: [(bool, Box<dyn BlockCipher>); 2] =
+        [(false, Box::new(Des::new(DES_KEY).unwrap())), (true, Box::new(Aes128::new(AES_KEY).unwrap()))];
+    let mut seeds = Vec::new();
+
+    for (aes, cipher) in &ciphers {
+        let iv = vec![0x24; cipher.block_size()];
+        for (index, mode) in [Mode::Ecb, Mode::Cbc, Mode::Ctr].into_iter().enumerate() {
+            for plaintext in PLAINTEXTS {
+                let iv_prefix = if mode.needs_iv() { iv.as_slice() } else { &[] };
+                let ciphertext = modes::encrypt(cipher.as_ref(), mode, &iv, plaintext).unwrap();
+                seeds.push([&[selector(*aes, index, Container::Plain)], iv_prefix, &ciphertext].concat());
+
+                let sealed = modes::encrypt_then_mac(cipher.as_ref(), mode, &iv, MAC_KEY, plaintext).unwrap();
+                seeds.push([&[selector(*aes, index, Container::Authenticated)][..], &sealed].concat());
+            }
+        }
+    }
+    for plaintext in PLAINTEXTS {
+        seeds.push([&[selector(false, 0, Container::Rc4)][..], &rc4().encrypt(plaintext)].concat());
+        let ciphertext = rsa_key().public().encrypt_oaep(plaintext).unwrap();
+        seeds.push([&[selector(false, 0, Container::RsaOaep)][..], &ciphertext].concat());
+    }
+    seeds
+}
+
+fn padding_seeds() -> Vec<Vec<u8>> {
+    [8u8, 16]
+        .into_iter()
+        .flat_map(|block_size| {
+            PLAINTEXTS.into_iter().map(move |plaintext| {
+                // The target reads the block size as the first byte plus one
+                [&[block_size - 1][..], &modes::pad_pkcs7(plaintext, usize::from(block_size))].concat()
+            })
+        })
+        .collect()
+}
+
+fn key_seeds() -> Vec<Vec<u8>> {
+    let shares = shamir::split(b"secret", 2, 3).unwrap();
+    let keys = [
+        "3",
+        "LEMON",
+        "3 3 2 5",
+        "6 24 1 13 16 10 20 17 15",
+        "3,2",
+        "ZEBRAS",
+        "ZEBRAS STRIPE",
+        "PLAYFAIREXAMPLE",
+        "48656c6c6f",
+        "JBSWY3DP",
+        "SGVsbG8=",
+        "p256",
+        "cbc",
+        &shares[0].to_string(),
+    ];
+    keys.iter().map(|key| format!("{}\nWE ARE DISCOVERED, FLEE AT ONCE", key).into_bytes()).collect()
+}
+
+fn pem_seeds() -> Vec<Vec<u8>> {
+    let der = b"\x30\x82\x01\x0a\x02\x82\x01\x01\x00 not a real request";
+    let certificate = |body: &str| format!("-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n", body);
+    vec![
+        encode_base64(der).into_bytes(),
+        [certificate("TUlJQkl3"), certificate("TUlJQ0lq")].concat().into_bytes(),
+        format!("subject=CN=leaf.example,O=Encryption Courses\n{}", certificate("TUlJQkl3")).into_bytes(),
+        b"subject=C=MD, O=Encryption Courses, CN=Issuing CA".to_vec(),
+    ]
+}
+
+fn main() -> io::Result<()> {
+    let total = write_seeds("decrypt", &decrypt_seeds())?
+        + write_seeds("padding", &padding_seeds())?
+        + write_seeds("keys", &key_seeds())?
+        + write_seeds("pem", &pem_seeds())?;
+    println!("{} seeds written", total);
+    Ok(())
+}
@@ -0,0 +1,369 @@
+//! Playfair cipher with Romanian character support
+
+use std::collections::HashSet;
+
+use crypto_core::language::{folded_letter_index, Language};
+use crypto_core::registry::{Capabilities, CipherPlugin, Registration};
+use crypto_core::{CipherError, CipherFamily, CipherInfo, ClassicalCipher};
+
+/// Every letter of the matrix, J merged into I: the 25 Latin letters, then
+/// the Romanian ones, 6 rows of 5
+const ALPHABET: &str = "ABCDEFGHIKLMNOPQRSTUVWXYZĂÂÎȘȚ";
+
+/// Whether `text` holds only letters (including Romanian ones)
+pub fn validate_text(text: &str) -> bool {
+    text.chars().all(|c| c.is_alphabetic() || "ăâîșț".contains(c))
+}
+
+/// Keys must be at least 7 letters long
+pub fn validate_key(key: &str) -> bool {
+    key.len() >= 7 && validate_text(key)
+}
+
+fn remove_duplicates(key: &str) -> String {
+    let mut result = String::new();
+    let mut seen = HashSet::new();
+
+    for c in key.chars() {
+        if !seen.contains(&c) {
+            result.push(c);
+            seen.insert(c);
+        }
+    }
+    result
+}
+
+/// Build the Playfair matrix for `key`, 5 columns wide
+pub fn create_matrix(key: &str) -> Vec<Vec<char>> {
+    // Create a flexible-sized matrix to accommodate all characters
+    let mut matrix = Vec::new();
+    let mut current_row = Vec::new();
+    
+    // Process the key first
+    let key_processed = remove_duplicates(&key.to_uppercase().replace('J', "I"));
+    let mut all_chars: Vec<char> = key_processed.chars().collect();
+    
+    // Add remaining alphabet and Romanian characters
+    for c in ALPHABET.chars() {
+        if !all_chars.contains(&c) {
+            all_chars.push(c);
+        }
+    }
+
+    // Create the matrix with 5 columns
+    for &c in all_chars.iter() {
+        current_row.push(c);
+        if current_row.len() == 5 {
+            matrix.push(current_row);
+            current_row = Vec::new();
+        }
+    }
+    
+    // Push the last row if it exists
+    if !current_row.is_empty() {
+        while current_row.len() < 5 {
+            current_row.push('X');  // Fill with X if needed
+        }
+        matrix.push(current_row);
+    }
+
+    matrix
+}
+
+fn find_position(matrix: &[Vec<char>], c: char) -> Option<(usize, usize)> {
+    for (i, row) in matrix.iter().enumerate() {
+        for (j, &matrix_char) in row.iter().enumerate() {
+            if matrix_char == c {
+                return Some((i, j));
+            }
+        }
+    }
+    None
+}
+
+pub fn encrypt_playfair(matrix: &[Vec<char>], text: &str) -> String {
+    let text = text.to_uppercase().replace('J', "I");
+    let mut text_chars: Vec<char> = text.chars().collect();
+    
+    // Add padding if necessary
+    if !text_chars.len().is_multiple_of(2) {
+        text_chars.push('X');
+    }
+
+    let mut result = String::new();
+    let rows = matrix.len();
+
+    for chunk in text_chars.chunks(2) {
+        let (c1, c2) = (chunk[0], chunk[chunk.len() - 1]);
+        
+        if let (Some((r1, c1_pos)), Some((r2, c2_pos))) = (find_position(matrix, c1), find_position(matrix, c2)) {
+            if r1 == r2 {
+                // Same row
+                result.push(matrix[r1][(c1_pos + 1) % 5]);
+                result.push(matrix[r2][(c2_pos + 1) % 5]);
+            } else if c1_pos == c2_pos {
+                // Same column
+                result.push(matrix[(r1 + 1) % rows][c1_pos]);
+                result.push(matrix[(r2 + 1) % rows][c2_pos]);
+            } else {
+                // Rectangle
+                result.push(matrix[r1][c2_pos]);
+                result.push(matrix[r2][c1_pos]);
+            }
+        } else {
+            // If character not found, append it unchanged
+            result.push(c1);
+            if chunk.len() > 1 {
+                result.push(c2);
+            }
+        }
+    }
+    result
+}
+
+pub fn decrypt_playfair(matrix: &[Vec<char>], text: &str) -> String {
+    let text = text.to_uppercase();
+    let mut result = String::new();
+    let rows = matrix.len();
+
+    for chunk in text.chars().collect::<Vec<char>>().chunks(2) {
+        let (c1, c2) = (chunk[0], chunk[chunk.len() - 1]);
+        
+        if let (Some((r1, c1_pos)), Some((r2, c2_pos))) = (find_position(matrix, c1), find_position(matrix, c2)) {
+            if r1 == r2 {
+                // Same row
+                result.push(matrix[r1][(c1_pos + 4) % 5]);
+                result.push(matrix[r2][(c2_pos + 4) % 5]);
+            } else if c1_pos == c2_pos {
+                // Same column
+                result.push(matrix[(r1 + rows - 1) % rows][c1_pos]);
+                result.push(matrix[(r2 + rows - 1) % rows][c2_pos]);
+            } else {
+                // Rectangle
+                result.push(matrix[r1][c2_pos]);
+                result.push(matrix[r2][c1_pos]);
+            }
+        } else {
+            // If character not found, append it unchanged
+            result.push(c1);
+            if chunk.len() > 1 {
+                result.push(c2);
+            }
+        }
+    }
+    result
+}
+
+/// Playfair as a [`ClassicalCipher`], holding the matrix built from its key
+pub struct Playfair {
+    matrix: Vec<Vec<char>>,
+}
+
+impl Playfair {
+    pub fn matrix(&self) -> &[Vec<char>] {
+        &self.matrix
+    }
+}
+
+impl ClassicalCipher for Playfair {
+    fn validate_key(key: &str) -> Result<(), CipherError> {
+        if validate_key(key) {
+            Ok(())
+        } else {
+            Err(CipherError::InvalidKey(INFO.key_description.to_string()))
+        }
+    }
+
+    fn new(key: &str) -> Result<Self, CipherError> {
+        <Self as ClassicalCipher>::validate_key(key)?;
+        Ok(Playfair { matrix: create_matrix(key) })
+    }
+
+    fn info(&self) -> CipherInfo {
+        INFO
+    }
+
+    fn encrypt(&self, plaintext: &str) -> Result<String, CipherError> {
+        check_text(plaintext)?;
+        Ok(encrypt_playfair(&self.matrix, plaintext))
+    }
+
+    fn decrypt(&self, ciphertext: &str) -> Result<String, CipherError> {
+        check_text(ciphertext)?;
+        Ok(decrypt_playfair(&self.matrix, ciphertext))
+    }
+}
+
+const INFO: CipherInfo = CipherInfo {
+    name: "playfair",
+    family: CipherFamily::Substitution,
+    key_description: "at least 7 letters (including Romanian ones)",
+    block_size: None,
+};
+
+/// Playfair for the cipher registry
+pub struct PlayfairPlugin;
+
+impl CipherPlugin for PlayfairPlugin {
+    fn name(&self) -> &'static str {
+        "playfair"
+    }
+
+    fn registrations(&self) -> Result<Vec<Registration>, CipherError> {
+        // Upper-cased, J merged into I and padded with X
+        let normalising = Capabilities { exact_round_trip: false, deterministic: true };
+        Ok(vec![Registration::classical::<Playfair>("MONARCHY", normalising)?])
+    }
+}
+
+fn check_text(text: &str) -> Result<(), CipherError> {
+    if validate_text(text) {
+        Ok(())
+    } else {
+        Err(CipherError::InvalidInput(String::from("only letters (including Romanian ones) are allowed")))
+    }
+}
+
+/// A matrix found by [`crack`] and the decryption it gives
+pub struct Crack {
+    pub matrix: Vec<Vec<char>>,
+    pub plaintext: String,
+    /// Average quadgram log probability of the plaintext, higher is better
+    pub score: f64,
+}
+
+/// Annealing schedule: the starting temperature per quadgram, the number of
+/// steps it cools down in and the matrix changes tried at each step
+const START_TEMPERATURE: f64 = 0.05;
+const COOLING_STEPS: usize = 100;
+const TRIALS_PER_STEP: usize = 2000;
+
+/// Log probability taken off for each Romanian letter in an English
+/// decryption, which the quadgrams alone would read as its base letter
+const FOREIGN_PENALTY: f64 = 10.0;
+
+/// xorshift64*, enough to drive the search; nothing secret depends on it
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self, below: usize) -> usize {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 33) as usize % below
+    }
+
+    fn unit(&mut self) -> f64 {
+        self.next(1 << 30) as f64 / f64::from(1u32 << 30)
+    }
+}
+
+/// Recover a matrix from ciphertext alone by simulated annealing: change
+/// the matrix at random (mostly swapping two letters, sometimes rows or
+/// columns), always keep changes that make the decryption read more like
+/// the language, and keep worse ones with a probability that shrinks as
+/// the search cools. Needs a couple of hundred letters to be reliable;
+/// `seed` makes a run repeatable.
+pub fn crack(ciphertext: &str, language: Language, seed: u64) -> Result<Crack, CipherError> {
+    let alphabet: Vec<char> = ALPHABET.chars().collect();
+    let symbols: Option<Vec<usize>> = ciphertext
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_uppercase)
+        .map(|c| alphabet.iter().position(|&letter| letter == if c == 'J' { 'I' } else { c }))
+        .collect();
+    let symbols = symbols
+        .filter(|symbols| symbols.len() >= 8 && symbols.len().is_multiple_of(2))
+        .ok_or_else(|| CipherError::InvalidInput(String::from("the ciphertext must be an even number of letters, at least 8")))?;
+    let folded: Vec<usize> = alphabet.iter().map(|&c| folded_letter_index(c).unwrap_or(0)).collect();
+    let quadgrams = language.quadgrams();
+    let native: Vec<bool> = alphabet.iter().map(|&c| language == Language::Romanian || c.is_ascii()).collect();
+
+    let (rows, size) = (alphabet.len() / 5, alphabet.len());
+    let mut plaintext = vec![0; symbols.len()];
+    let decrypt = |key: &[usize], plaintext: &mut [usize]| {
+        let mut cell = vec![0; size];
+        for (position, &symbol) in key.iter().enumerate() {
+            cell[symbol] = position;
+        }
+        let mut foreign = 0;
+        for (pair, output) in symbols.chunks_exact(2).zip(plaintext.chunks_exact_mut(2)) {
+            let ((r1, c1), (r2, c2)) = ((cell[pair[0]] / 5, cell[pair[0]] % 5), (cell[pair[1]] / 5, cell[pair[1]] % 5));
+            let (first, second) = if r1 == r2 {
+                (r1 * 5 + (c1 + 4) % 5, r2 * 5 + (c2 + 4) % 5)
+            } else if c1 == c2 {
+                ((r1 + rows - 1) % rows * 5 + c1, (r2 + rows - 1) % rows * 5 + c2)
+            } else {
+                (r1 * 5 + c2, r2 * 5 + c1)
+            };
+            output[0] = folded[key[first]];
+            output[1] = folded[key[second]];
+            foreign += usize::from(!native[key[first]]) + usize::from(!native[key[second]]);
+        }
+        quadgrams.total(plaintext) - FOREIGN_PENALTY * foreign as f64
+    };
+
+    let mut rng = Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1);
+    let mut key: Vec<usize> = (0..size).collect();
+    for i in (1..size).rev() {
+        key.swap(i, rng.next(i + 1));
+    }
+    let mut score = decrypt(&key, &mut plaintext);
+    let (mut best_key, mut best_score) = (key.clone(), score);
+    let quadgram_count = (symbols.len() - 3) as f64;
+
+    for step in 0..COOLING_STEPS {
+        let temperature = START_TEMPERATURE * quadgram_count * (1.0 - step as f64 / COOLING_STEPS as f64);
+        for _ in 0..TRIALS_PER_STEP {
+            let mut candidate = key.clone();
+            match rng.next(50) {
+                0 => {
+                    let (a, b) = (rng.next(rows), rng.next(rows));
+                    for column in 0..5 {
+                        candidate.swap(a * 5 + column, b * 5 + column);
+                    }
+                }
+                1 => {
+                    let (a, b) = (rng.next(5), rng.next(5));
+                    for row in 0..rows {
+                        candidate.swap(row * 5 + a, row * 5 + b);
+                    }
+                }
+                2 => candidate.reverse(),
+                _ => candidate.swap(rng.next(size), rng.next(size)),
+            }
+
+            let candidate_score = decrypt(&candidate, &mut plaintext);
+            let delta = candidate_score - score;
+            if delta >= 0.0 || rng.unit() < (delta / temperature).exp() {
+                key = candidate;
+                score = candidate_score;
+                if score > best_score {
+                    best_key.clone_from(&key);
+                    best_score = score;
+                }
+            }
+        }
+    }
+
+    let matrix: Vec<Vec<char>> = best_key.chunks(5).map(|row| row.iter().map(|&symbol| alphabet[symbol]).collect()).collect();
+    let plaintext = decrypt_playfair(&matrix, &ciphertext.chars().filter(|c| !c.is_whitespace()).collect::<String>().replace(['J', 'j'], "I"));
+    Ok(Crack { matrix, plaintext, score: best_score / quadgram_count })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn annealing_recovers_a_long_message() {
+        let plaintext = "THEREWASNOPOSSIBILITYOFTAKINGAWALKTHATDAYWEHADBEENWANDERINGINDEEDINTHELEAFLESSSHRUBBERYANHOURINTHEMORNINGBU\
+                         TSINCEDINNERTHECOLDWINTERWINDHADBROUGHTWITHITCLOUDSSOSOMBREANDARAINSOPENETRATINGTHATFURTHEROUTDOOREXERCISEWASNOWOUTOFTHEQUE";
+        let ciphertext = encrypt_playfair(&create_matrix("MONARCHY"), plaintext);
+
+        let crack = crack(&ciphertext, Language::English, 4).unwrap();
+        assert_eq!(crack.plaintext, plaintext);
+        assert_eq!(decrypt_playfair(&crack.matrix, &ciphertext), plaintext);
+        assert!(super::crack("ABC", Language::English, 1).is_err());
+    }
+}
@@ -1,151 +1,31 @@
-use std::collections::HashSet;
+use std::env;
 use std::io::{self, Write};
 
-fn validate_text(text: &str) -> bool {
-    text.chars().all(|c| c.is_alphabetic() || "ăâîșț".contains(c))
-}
-
-fn validate_key(key: &str) -> bool {
-    key.len() >= 7 && validate_text(key)
-}
-
-fn remove_duplicates(key: &str) -> String {
-    let mut result = String::new();
-    let mut seen = HashSet::new();
-
-    for c in key.chars() {
-        if !seen.contains(&c) {
-            result.push(c);
-            seen.insert(c);
-        }
-    }
-    result
-}
-
-fn create_matrix(key: &str) -> Vec<Vec<char>> {
-    // Create a flexible-sized matrix to accommodate all characters
-    let mut matrix = Vec::new();
-    let mut current_row = Vec::new();
-    
-    // Process the key first
-    let key_processed = remove_duplicates(&key.to_uppercase().replace('J', "I"));
-    let mut all_chars: Vec<char> = key_processed.chars().collect();
-    
-    // Add remaining alphabet and Romanian characters
-    let alphabet = "ABCDEFGHIKLMNOPQRSTUVWXYZĂÂÎȘȚ";
-    for c in alphabet.chars() {
-        if !all_chars.contains(&c) {
-            all_chars.push(c);
-        }
-    }
-
-    // Create the matrix with 5 columns
-    for (_idx, &c) in all_chars.iter().enumerate() {
-        current_row.push(c);
-        if current_row.len() == 5 {
-            matrix.push(current_row);
-            current_row = Vec::new();
-        }
-    }
-    
-    // Push the last row if it exists
-    if !current_row.is_empty() {
-        while current_row.len() < 5 {
-            current_row.push('X');  // Fill with X if needed
-        }
-        matrix.push(current_row);
-    }
-
-    matrix
-}
-
-fn find_position(matrix: &[Vec<char>], c: char) -> Option<(usize, usize)> {
-    for (i, row) in matrix.iter().enumerate() {
-        for (j, &matrix_char) in row.iter().enumerate() {
-            if matrix_char == c {
-                return Some((i, j));
-            }
-        }
-    }
-    None
-}
-
-fn encrypt_playfair(matrix: &[Vec<char>], text: &str) -> String {
-    let text = text.to_uppercase().replace('J', "I");
-    let mut text_chars: Vec<char> = text.chars().collect();
-    
-    // Add padding if necessary
-    if text_chars.len() % 2 != 0 {
-        text_chars.push('X');
-    }
-
-    let mut result = String::new();
-    let rows = matrix.len();
-
-    for chunk in text_chars.chunks(2) {
-        let (c1, c2) = (chunk[0], chunk[chunk.len() - 1]);
-        
-        if let (Some((r1, c1_pos)), Some((r2, c2_pos))) = (find_position(matrix, c1), find_position(matrix, c2)) {
-            if r1 == r2 {
-                // Same row
-                result.push(matrix[r1][(c1_pos + 1) % 5]);
-                result.push(matrix[r2][(c2_pos + 1) % 5]);
-            } else if c1_pos == c2_pos {
-                // Same column
-                result.push(matrix[(r1 + 1) % rows][c1_pos]);
-                result.push(matrix[(r2 + 1) % rows][c2_pos]);
-            } else {
-                // Rectangle
-                result.push(matrix[r1][c2_pos]);
-                result.push(matrix[r2][c1_pos]);
-            }
-        } else {
-            // If character not found, append it unchanged
-            result.push(c1);
-            if chunk.len() > 1 {
-                result.push(c2);
-            }
-        }
-    }
-    result
-}
-
-fn decrypt_playfair(matrix: &[Vec<char>], text: &str) -> String {
-    let text = text.to_uppercase();
-    let mut result = String::new();
-    let rows = matrix.len();
-
-    for chunk in text.chars().collect::<Vec<char>>().chunks(2) {
-        let (c1, c2) = (chunk[0], chunk[chunk.len() - 1]);
-        
-        if let (Some((r1, c1_pos)), Some((r2, c2_pos))) = (find_position(matrix, c1), find_position(matrix, c2)) {
-            if r1 == r2 {
-                // Same row
-                result.push(matrix[r1][(c1_pos + 4) % 5]);
-                result.push(matrix[r2][(c2_pos + 4) % 5]);
-            } else if c1_pos == c2_pos {
-                // Same column
-                result.push(matrix[(r1 + rows - 1) % rows][c1_pos]);
-                result.push(matrix[(r2 + rows - 1) % rows][c2_pos]);
-            } else {
-                // Rectangle
-                result.push(matrix[r1][c2_pos]);
-                result.push(matrix[r2][c1_pos]);
-            }
-        } else {
-            // If character not found, append it unchanged
-            result.push(c1);
-            if chunk.len() > 1 {
-                result.push(c2);
-            }
-        }
-    }
-    result
-}
+use crypto_core::config::Config;
+use crypto_core::i18n::{self, Catalog, Lang};
+use crypto_core::tr;
+use playfair::{create_matrix, decrypt_playfair, encrypt_playfair, validate_key, validate_text};
+
+/// Romanian for the prompts and results
+const MESSAGES: Catalog = &[
+    ("=== Playfair Cipher (with Romanian character support) ===", "=== Cifrul Playfair (cu suport pentru diacritice românești) ==="),
+    ("Enter the key (at least 7 characters): ", "Introduceți cheia (cel puțin 7 caractere): "),
+    (
+        "The key must be at least 7 characters long and contain only letters (including Romanian ones)!",
+        "Cheia trebuie să aibă cel puțin 7 caractere și să conțină doar litere (inclusiv diacritice românești)!",
+    ),
+    ("Playfair Matrix:", "Matricea Playfair:"),
+    ("Choose an operation (1: Encrypt, 2: Decrypt): ", "Alegeți o operație (1: Criptare, 2: Decriptare): "),
+    ("Please enter either 1 for encryption or 2 for decryption.", "Introduceți 1 pentru criptare sau 2 pentru decriptare."),
+    ("Enter the message for encryption/decryption: ", "Introduceți mesajul pentru criptare/decriptare: "),
+    ("The message must contain only letters (including Romanian ones)!", "Mesajul trebuie să conțină doar litere (inclusiv diacritice românești)!"),
+    ("Encrypted text: {}", "Text criptat: {}"),
+    ("Decrypted message: {}", "Mesaj decriptat: {}"),
+];
 
 fn get_valid_operation() -> io::Result<u32> {
     loop {
-        print!("Choose an operation (1: Encrypt, 2: Decrypt): ");
+        print!("{}", tr!("Choose an operation (1: Encrypt, 2: Decrypt): "));
         io::stdout().flush()?;
         
         let mut input = String::new();
@@ -154,7 +34,7 @@ fn get_valid_operation() -> io::Result<u32> {
         match input.trim().parse::<u32>() {
             Ok(num) if num == 1 || num == 2 => return Ok(num),
             _ => {
-                println!("Please enter either 1 for encryption or 2 for decryption.");
+                println!("{}", tr!("Please enter either 1 for encryption or 2 for decryption."));
                 continue;
             }
         }
@@ -163,7 +43,7 @@ fn get_valid_operation() -> io::Result<u32> {
 
 fn get_valid_key() -> io::Result<String> {
     loop {
-        print!("Enter the key (at least 7 characters): ");
+        print!("{}", tr!("Enter the key (at least 7 characters): "));
         io::stdout().flush()?;
         
         let mut input = String::new();
@@ -173,14 +53,14 @@ fn get_valid_key() -> io::Result<String> {
         if validate_key(&key) {
             return Ok(key);
         } else {
-            println!("The key must be at least 7 characters long and contain only letters (including Romanian ones)!");
+            println!("{}", tr!("The key must be at least 7 characters long and contain only letters (including Romanian ones)!"));
         }
     }
 }
 
 fn get_valid_message() -> io::Result<String> {
     loop {
-        print!("Enter the message for encryption/decryption: ");
+        print!("{}", tr!("Enter the message for encryption/decryption: "));
         io::stdout().flush()?;
         
         let mut input = String::new();
@@ -190,19 +70,23 @@ fn get_valid_message() -> io::Result<String> {
         if validate_text(&message) {
             return Ok(message);
         } else {
-            println!("The message must contain only letters (including Romanian ones)!");
+            println!("{}", tr!("The message must contain only letters (including Romanian ones)!"));
         }
     }
 }
 
 fn main() -> io::Result<()> {
-    println!("=== Playfair Cipher (with Romanian character support) ===\n");
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (lang, _) = Lang::take_from(&args, Lang::from_config(&Config::load()?)?)?;
+    i18n::init(lang, &[MESSAGES]);
+
+    println!("{}\n", tr!("=== Playfair Cipher (with Romanian character support) ==="));
     
     let key = get_valid_key()?;
     let matrix = create_matrix(&key);
     
     // Debug: Print the matrix
-    println!("\nPlayfair Matrix:");
+    println!("\n{}", tr!("Playfair Matrix:"));
     for row in &matrix {
         println!("{:?}", row);
     }
@@ -214,14 +98,25 @@ fn main() -> io::Result<()> {
     match operation {
         1 => {
             let ciphertext = encrypt_playfair(&matrix, &message);
-            println!("\nEncrypted text: {}", ciphertext);
+            println!("\n{}", tr!("Encrypted text: {}", ciphertext));
         },
         2 => {
             let decrypted_message = decrypt_playfair(&matrix, &message);
-            println!("\nDecrypted message: {}", decrypted_message);
+            println!("\n{}", tr!("Decrypted message: {}", decrypted_message));
         },
         _ => unreachable!()
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_prompt_has_a_romanian_translation() {
+        assert_eq!(i18n::untranslated(include_str!("main.rs"), &[MESSAGES]), Vec::<String>::new());
+        assert!(i18n::mismatched_placeholders(MESSAGES).is_empty());
+    }
+}
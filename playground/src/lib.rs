@@ -0,0 +1,400 @@
+//! The cipher collection for the browser. Every operation is one JSON
+//! request in, one JSON response out (`{"ok": true, ...}` or `{"ok": false,
+//! "error": ...}`, as `crypto --output json` prints), so the WebAssembly
+//! interface is three functions that pass strings through linear memory.
+
+use crypto_core::aes::Aes128;
+use crypto_core::caesar::{self, Caesar};
+use crypto_core::codec::{encode_hex, Encoding, Strictness};
+use crypto_core::columnar::{self, Columnar};
+use crypto_core::cryptanalysis::{self, LanguageModel};
+use crypto_core::hash::{sha256, Sha256};
+use crypto_core::hill::Hill;
+use crypto_core::language::{quadgram_score, Alphabet, Language};
+use crypto_core::mac;
+use crypto_core::modes::{self, Mode};
+use crypto_core::railfence::{self, RailFence};
+use crypto_core::rc4::Rc4;
+use crypto_core::rng;
+use crypto_core::rsa::{RsaPrivateKey, RsaPublicKey};
+use crypto_core::vigenere::{self, Vigenere};
+use crypto_core::{BlockCipher, ClassicalCipher, SymmetricCipher};
+use des::Des;
+use num_bigint::BigUint;
+use playfair::Playfair;
+use serde_json::{json, Map, Value};
+
+/// Largest RSA modulus generated in the page, which has no worker thread
+const MAX_RSA_BITS: u64 = 1024;
+
+/// The ciphers and operations offered, for building the page's menus
+pub fn catalog() -> Value {
+    json!([
+        {"command": "caesar", "operations": ["encrypt", "decrypt", "crack"], "key": "shift"},
+        {"command": "vigenere", "operations": ["encrypt", "decrypt", "crack"], "key": "word"},
+        {"command": "hill", "operations": ["encrypt", "decrypt"], "key": "4 or 9 numbers or letters"},
+        {"command": "railfence", "operations": ["encrypt", "decrypt", "crack"], "key": "rails[,offset]"},
+        {"command": "columnar", "operations": ["encrypt", "decrypt", "crack"], "key": "keyword[ keyword]"},
+        {"command": "playfair", "operations": ["encrypt", "decrypt", "matrix", "crack"], "key": "at least 7 letters"},
+        {"command": "des", "operations": ["encrypt", "decrypt"], "key": "8 characters"},
+        {"command": "aes", "operations": ["encrypt", "decrypt"], "key": "16 characters"},
+        {"command": "rc4", "operations": ["encrypt", "decrypt"], "key": "text"},
+        {"command": "rsa", "operations": ["keygen", "encrypt", "decrypt"], "key": "n,e or n,e,d (decimal)"},
+        {"command": "hash", "operations": ["sha256"], "key": null},
+        {"command": "mac", "operations": ["tag"], "key": "text"},
+        {"command": "encode", "operations": ["hex", "base32", "base64"], "key": null},
+        {"command": "decode", "operations": ["hex", "base32", "base64"], "key": null},
+        {"command": "analyze", "operations": ["english"], "key": null},
+    ])
+}
+
+/// One playground request, e.g. `{"command": "caesar", "operation":
+/// "encrypt", "key": "3", "text": "attack"}`
+struct Request {
+    fields: Value,
+}
+
+impl Request {
+    fn string(&self, name: &str) -> Option<&str> {
+        self.fields[name].as_str()
+    }
+
+    fn required(&self, name: &str) -> Result<&str, String> {
+        self.string(name).ok_or_else(|| format!("missing {}", name))
+    }
+
+    fn text(&self) -> &str {
+        self.string("text").unwrap_or("")
+    }
+
+    fn language(&self) -> Language {
+        if self.fields["romanian"] == true { Language::Romanian } else { Language::English }
+    }
+}
+
+/// Run one JSON request and return the JSON response
+pub fn handle(request: &str) -> String {
+    let response = match serde_json::from_str::<Value>(request) {
+        Ok(fields) => dispatch(&Request { fields }),
+        Err(e) => Err(format!("invalid request: {}", e)),
+    };
+
+    let mut output = Map::new();
+    match response {
+        Ok(Value::Object(fields)) => {
+            output.insert(String::from("ok"), Value::from(true));
+            output.extend(fields);
+        }
+        Ok(value) => {
+            output.insert(String::from("ok"), Value::from(true));
+            output.insert(String::from("result"), value);
+        }
+        Err(error) => {
+            output.insert(String::from("ok"), Value::from(false));
+            output.insert(String::from("error"), Value::from(error));
+        }
+    }
+    Value::Object(output).to_string()
+}
+
+fn dispatch(request: &Request) -> Result<Value, String> {
+    let operation = request.string("operation").unwrap_or("");
+    match request.required("command")? {
+        "catalog" => Ok(catalog()),
+        "caesar" | "vigenere" | "hill" | "railfence" | "columnar" | "playfair" => classical(request, operation),
+        "des" | "aes" | "rc4" => symmetric(request, operation),
+        "rsa" => rsa(request, operation),
+        "hash" if operation == "sha256" => Ok(json!({"result": encode_hex(&sha256(request.text().as_bytes()))})),
+        "mac" if operation == "tag" => {
+            let tag = mac::hmac::<Sha256>(request.required("key")?.as_bytes(), request.text().as_bytes());
+            Ok(json!({"result": encode_hex(&tag)}))
+        }
+        "encode" | "decode" => codec(request, operation),
+        "analyze" => analyze(request),
+        command => Err(format!("unknown command {} {}", command, operation)),
+    }
+}
+
+fn error(error: impl ToString) -> String {
+    error.to_string()
+}
+
+fn classical(request: &Request, operation: &str) -> Result<Value, String> {
+    let command = request.required("command")?;
+    let text = request.text();
+    let language = request.language();
+
+    if operation == "crack" {
+        let (key, plaintext) = match command {
+            "caesar" => {
+                let candidate = caesar::crack(text, language);
+                (candidate.shift.to_string(), candidate.plaintext)
+            }
+            "vigenere" => vigenere::crack(text, 12, language),
+            "railfence" => {
+                let best = railfence::brute_force(text, 10, language).into_iter().next().ok_or("nothing to crack")?;
+                (format!("{},{}", best.rails, best.offset), best.plaintext)
+            }
+            "columnar" => {
+                let (order, plaintext) = (2..=8)
+                    .map(|columns| columnar::crack(text, columns, language))
+                    .max_by(|a, b| quadgram_score(&a.1, language).total_cmp(&quadgram_score(&b.1, language)))
+                    .unwrap_or_default();
+                (columnar::order_keyword(&order), plaintext)
+            }
+            "playfair" => {
+                let crack = playfair::crack(text, language, 1).map_err(error)?;
+                let key = crack.matrix.iter().map(|row| row.iter().collect::<String>()).collect::<Vec<_>>().join(" ");
+                (key, crack.plaintext)
+            }
+            _ => return Err(format!("{} has no crack operation", command)),
+        };
+        return Ok(json!({"key": key, "result": plaintext}));
+    }
+
+    let key = request.required("key")?;
+    let cipher: Box<dyn ClassicalCipher> = match command {
+        "caesar" => Box::new(Caesar::new(key).map_err(error)?),
+        "vigenere" => Box::new(Vigenere::new(key).map_err(error)?),
+        "hill" => {
+            let alphabet = if request.language() == Language::Romanian { Alphabet::Romanian } else { Alphabet::Latin };
+            Box::new(Hill::with_alphabet(key, alphabet).map_err(error)?)
+        }
+        "railfence" => Box::new(RailFence::new(key).map_err(error)?),
+        "columnar" => Box::new(Columnar::new(key).map_err(error)?),
+        _ => {
+            let cipher = Playfair::new(key).map_err(error)?;
+            if operation == "matrix" {
+                let matrix: Vec<String> = cipher.matrix().iter().map(|row| row.iter().collect()).collect();
+                return Ok(json!({"matrix": matrix}));
+            }
+            Box::new(cipher)
+        }
+    };
+
+    let result = match operation {
+        "encrypt" => cipher.encrypt(text),
+        "decrypt" => cipher.decrypt(text),
+        _ => return Err(format!("unknown {} operation {}", command, operation)),
+    }
+    .map_err(error)?;
+    Ok(json!({"cipher": cipher.info().name, "result": result}))
+}
+
+/// DES and AES in a mode (ECB by default) with the IV leading the
+/// ciphertext, or RC4; ciphertext is hex as on the command line
+fn symmetric(request: &Request, operation: &str) -> Result<Value, String> {
+    let command = request.required("command")?;
+    let key = request.required("key")?.as_bytes();
+    let text = request.text();
+
+    if command == "rc4" {
+        let cipher = Rc4::new(key).map_err(error)?;
+        let result = match operation {
+            "encrypt" => encode_hex(&cipher.encrypt(text.as_bytes())),
+            "decrypt" => String::from_utf8_lossy(&cipher.decrypt(&hex(text)?).map_err(error)?).into_owned(),
+            _ => return Err(format!("unknown rc4 operation {}", operation)),
+        };
+        return Ok(json!({"cipher": "rc4", "result": result}));
+    }
+
+    let cipher: Box<dyn BlockCipher> = match command {
+        "des" => Box::new(Des::new(key).map_err(error)?),
+        _ => Box::new(Aes128::new(key).map_err(error)?),
+    };
+    let mode = match request.string("mode") {
+        Some(name) => Mode::parse(name).ok_or_else(|| format!("unknown mode {}", name))?,
+        None => Mode::Ecb,
+    };
+    let iv_length = if mode.needs_iv() { cipher.block_size() } else { 0 };
+
+    let result = match operation {
+        "encrypt" => {
+            let iv = match request.string("iv") {
+                Some(iv) if mode.needs_iv() => hex(iv)?,
+                _ => rng::iv(iv_length).map_err(error)?,
+            };
+            let ciphertext = modes::encrypt(cipher.as_ref(), mode, &iv, text.as_bytes()).map_err(error)?;
+            encode_hex(&[iv, ciphertext].concat())
+        }
+        "decrypt" => {
+            let data = hex(text)?;
+            let (iv, ciphertext) = data.split_at(iv_length.min(data.len()));
+            String::from_utf8_lossy(&modes::decrypt(cipher.as_ref(), mode, iv, ciphertext).map_err(error)?).into_owned()
+        }
+        _ => return Err(format!("unknown {} operation {}", command, operation)),
+    };
+    Ok(json!({"cipher": command, "mode": mode.name(), "result": result}))
+}
+
+fn hex(text: &str) -> Result<Vec<u8>, String> {
+    Encoding::Hex.decode(text, Strictness::Lenient).map_err(|e| format!("expected hex: {}", e.reason))
+}
+
+fn number(text: &str) -> Result<BigUint, String> {
+    text.trim().parse().map_err(|_| format!("expected a decimal number, got {}", text))
+}
+
+/// Textbook RSA on decimal numbers; the key is `n,e` to encrypt and `n,e,d` to decrypt
+fn rsa(request: &Request, operation: &str) -> Result<Value, String> {
+    if operation == "keygen" {
+        let bits = request.fields["bits"].as_u64().unwrap_or(512);
+        if !(64..=MAX_RSA_BITS).contains(&bits) {
+            return Err(format!("key sizes run from 64 to {} bits here", MAX_RSA_BITS));
+        }
+        let key = RsaPrivateKey::generate(bits).map_err(error)?;
+        return Ok(json!({"n": key.n.to_string(), "e": key.e.to_string(), "d": key.d.to_string(), "key": format!("{},{},{}", key.n, key.e, key.d)}));
+    }
+
+    let parts: Vec<&str> = request.required("key")?.split(',').collect();
+    let message = number(request.text())?;
+    let result = match (operation, parts.as_slice()) {
+        ("encrypt", [n, e, ..]) => RsaPublicKey { n: number(n)?, e: number(e)? }.encrypt_raw(&message),
+        ("decrypt", [n, e, d]) => {
+            let (n, e, d) = (number(n)?, number(e)?, number(d)?);
+            // Only n and d are needed for c^d mod n
+            RsaPrivateKey { n, e, d, p: BigUint::default(), q: BigUint::default() }.decrypt_raw(&message)
+        }
+        _ => return Err(String::from("the key is n,e to encrypt and n,e,d to decrypt")),
+    }
+    .map_err(error)?;
+    Ok(json!({"result": result.to_string()}))
+}
+
+fn codec(request: &Request, operation: &str) -> Result<Value, String> {
+    let encoding = Encoding::parse(operation).ok_or_else(|| format!("unknown encoding {}", operation))?;
+    let result = match request.required("command")? {
+        "encode" => encoding.encode(request.text().as_bytes()),
+        _ => {
+            let bytes = encoding.decode(request.text(), Strictness::Lenient).map_err(|e| e.reason)?;
+            String::from_utf8_lossy(&bytes).into_owned()
+        }
+    };
+    Ok(json!({"result": result}))
+}
+
+fn analyze(request: &Request) -> Result<Value, String> {
+    let analysis = cryptanalysis::analyze(request.text(), LanguageModel::english(), 5);
+    let top = |n: usize| -> Vec<Value> {
+        analysis.top[n].iter().map(|ngram| json!({"ngram": ngram.ngram, "count": ngram.count, "frequency": ngram.frequency})).collect()
+    };
+    Ok(json!({
+        "letters": analysis.letters,
+        "index_of_coincidence": analysis.index_of_coincidence,
+        "chi_squared": analysis.chi_squared,
+        "assessment": analysis.assessment.description(),
+        "monograms": top(0),
+        "bigrams": top(1),
+        "trigrams": top(2),
+    }))
+}
+
+/// Reserve `len` bytes for the page to write a request into
+#[no_mangle]
+pub extern "C" fn playground_alloc(len: usize) -> *mut u8 {
+    into_raw(vec![0; len])
+}
+
+/// Hand a buffer to the page as a boxed slice, whose allocation is exactly
+/// its length, so [`playground_free`] can rebuild it from that length alone
+fn into_raw(buffer: Vec<u8>) -> *mut u8 {
+    Box::into_raw(buffer.into_boxed_slice()).cast()
+}
+
+/// Give back a buffer from [`playground_alloc`] or [`playground_call`]
+///
+/// # Safety
+///
+/// `pointer` and `len` must be exactly what the allocation was made with,
+/// and the buffer must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn playground_free(pointer: *mut u8, len: usize) {
+    drop(unsafe { Box::from_raw(std::ptr::slice_from_raw_parts_mut(pointer, len)) });
+}
+
+/// Run the UTF-8 request at `pointer` and return a buffer holding the
+/// response length as 4 little-endian bytes, then the response. The page
+/// frees it with `playground_free(buffer, 4 + length)`.
+///
+/// # Safety
+///
+/// `pointer` must point to `len` initialised bytes.
+#[no_mangle]
+pub unsafe extern "C" fn playground_call(pointer: *const u8, len: usize) -> *mut u8 {
+    let request = unsafe { std::slice::from_raw_parts(pointer, len) };
+    let response = handle(&String::from_utf8_lossy(request));
+
+    let mut buffer = (response.len() as u32).to_le_bytes().to_vec();
+    buffer.extend_from_slice(response.as_bytes());
+    into_raw(buffer)
+}
+
+/// getrandom's source in the browser: the page's `fill_random` import,
+/// which calls `crypto.getRandomValues`
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+mod random {
+    #[link(wasm_import_module = "env")]
+    extern "C" {
+        fn fill_random(destination: *mut u8, len: usize);
+    }
+
+    #[no_mangle]
+    unsafe extern "Rust" fn __getrandom_v03_custom(destination: *mut u8, len: usize) -> Result<(), getrandom::Error> {
+        unsafe { fill_random(destination, len) };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(request: Value) -> Value {
+        serde_json::from_str(&handle(&request.to_string())).unwrap()
+    }
+
+    #[test]
+    fn requests_run_the_same_implementations_as_the_cli() {
+        let encrypted = call(json!({"command": "playfair", "operation": "encrypt", "key": "MONARCHY", "text": "INSTRUMENTS"}));
+        assert_eq!(encrypted["ok"], true);
+        let decrypted = call(json!({"command": "playfair", "operation": "decrypt", "key": "MONARCHY", "text": encrypted["result"]}));
+        assert!(decrypted["result"].as_str().unwrap().starts_with("INSTRUMENTS"));
+
+        let des = call(json!({"command": "des", "operation": "encrypt", "key": "MORTYNOR", "text": "hello DES"}));
+        assert_eq!(des["result"], "1634d740da0ec08eae40c6291fa7a9c1");
+        let cbc = call(json!({"command": "aes", "operation": "encrypt", "key": "YELLOW SUBMARINE", "mode": "cbc", "text": "lecture"}));
+        let back = call(json!({"command": "aes", "operation": "decrypt", "key": "YELLOW SUBMARINE", "mode": "cbc", "text": cbc["result"]}));
+        assert_eq!(back["result"], "lecture");
+
+        let cracked = call(json!({"command": "caesar", "operation": "crack", "text": "Phhw ph diwhu wkh ohfwxuh"}));
+        assert_eq!((cracked["key"].as_str(), cracked["result"].as_str()), (Some("3"), Some("Meet me after the lecture")));
+        let rsa = call(json!({"command": "rsa", "operation": "encrypt", "key": "3233,17", "text": "65"}));
+        assert_eq!(rsa["result"], "2790");
+        assert_eq!(call(json!({"command": "rsa", "operation": "decrypt", "key": "3233,17,2753", "text": "2790"}))["result"], "65");
+    }
+
+    #[test]
+    fn errors_come_back_as_json() {
+        let rejected = call(json!({"command": "playfair", "operation": "encrypt", "key": "short", "text": "TEXT"}));
+        assert_eq!(rejected["ok"], false);
+        assert!(rejected["error"].as_str().unwrap().contains("7 letters"));
+        assert_eq!(serde_json::from_str::<Value>(&handle("not json")).unwrap()["ok"], false);
+        assert!(call(json!({"command": "catalog"}))["result"].as_array().unwrap().len() > 10);
+    }
+
+    #[test]
+    fn responses_cross_linear_memory_with_a_length_prefix() {
+        let request = br#"{"command": "hash", "operation": "sha256", "text": "abc"}"#;
+        let input = playground_alloc(request.len());
+        unsafe {
+            std::ptr::copy_nonoverlapping(request.as_ptr(), input, request.len());
+            let output = playground_call(input, request.len());
+            playground_free(input, request.len());
+
+            let len = u32::from_le_bytes(std::slice::from_raw_parts(output, 4).try_into().unwrap()) as usize;
+            let response: Value = serde_json::from_slice(std::slice::from_raw_parts(output.add(4), len)).unwrap();
+            playground_free(output, 4 + len);
+            assert_eq!(response["result"], "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        }
+    }
+}
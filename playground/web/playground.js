@@ -0,0 +1,71 @@
+// Loads playground.wasm and passes JSON requests through its memory:
+// playground_alloc a buffer for the request, playground_call it, and read
+// the response behind its 4-byte little-endian length.
+
+let wasm;
+
+function call(request) {
+  const input = new TextEncoder().encode(JSON.stringify(request));
+  const pointer = wasm.playground_alloc(input.length);
+  new Uint8Array(wasm.memory.buffer, pointer, input.length).set(input);
+
+  const output = wasm.playground_call(pointer, input.length);
+  wasm.playground_free(pointer, input.length);
+  const length = new DataView(wasm.memory.buffer).getUint32(output, true);
+  const response = new TextDecoder().decode(new Uint8Array(wasm.memory.buffer, output + 4, length));
+  wasm.playground_free(output, 4 + length);
+  return JSON.parse(response);
+}
+
+const imports = {
+  env: {
+    fill_random(pointer, length) {
+      crypto.getRandomValues(new Uint8Array(wasm.memory.buffer, pointer, length));
+    },
+  },
+};
+
+const element = (id) => document.getElementById(id);
+
+function showOperations(catalog) {
+  const entry = catalog.find((cipher) => cipher.command === element("command").value);
+  element("operation").replaceChildren(...entry.operations.map((name) => new Option(name)));
+  element("key-hint").textContent = entry.key ? `(${entry.key})` : "";
+}
+
+function run() {
+  const request = {
+    command: element("command").value,
+    operation: element("operation").value,
+    key: element("key").value,
+    text: element("text").value,
+    mode: element("mode").value,
+    romanian: element("romanian").checked,
+  };
+  const response = call(request);
+  const output = element("output");
+  output.className = response.ok ? "" : "error";
+  if (!response.ok) {
+    output.textContent = response.error;
+  } else if (response.matrix) {
+    output.textContent = response.matrix.join("\n");
+  } else {
+    const { ok, ...fields } = response;
+    output.textContent = Object.keys(fields).length === 1 && "result" in fields
+      ? fields.result
+      : JSON.stringify(fields, null, 2);
+  }
+}
+
+WebAssembly.instantiateStreaming(fetch("playground.wasm"), imports).then(({ instance }) => {
+  wasm = instance.exports;
+  const catalog = call({ command: "catalog" }).result;
+  element("command").replaceChildren(...catalog.map((cipher) => new Option(cipher.command)));
+  element("command").addEventListener("change", () => showOperations(catalog));
+  element("run").addEventListener("click", run);
+  showOperations(catalog);
+  element("output").textContent = "Ready.";
+}).catch((error) => {
+  element("output").className = "error";
+  element("output").textContent = `Could not load playground.wasm: ${error}`;
+});
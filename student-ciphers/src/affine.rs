@@ -0,0 +1,86 @@
+//! Affine cipher: letter x becomes (a·x + b) mod 26, and decryption
+//! multiplies by the inverse of a, which exists only when a is coprime to 26
+
+use crypto_core::{CipherError, CipherFamily, CipherInfo, ClassicalCipher};
+
+/// Keeps case; other characters pass through
+pub struct Affine {
+    a: u8,
+    b: u8,
+    /// a⁻¹ mod 26
+    a_inverse: u8,
+}
+
+impl Affine {
+    /// `(a, b)` from `a,b`
+    fn parse_key(key: &str) -> Option<(u8, u8)> {
+        let (a, b) = key.split_once(',')?;
+        let (a, b) = (a.trim().parse::<u8>().ok()?, b.trim().parse::<u8>().ok()?);
+        (a < 26 && b < 26).then_some((a, b))
+    }
+
+    fn inverse(a: u8) -> Option<u8> {
+        (1..26).find(|&candidate| (a as u32 * candidate as u32) % 26 == 1)
+    }
+
+    fn apply(text: &str, map: impl Fn(u32) -> u32) -> String {
+        text.chars()
+            .map(|c| {
+                let base = match c {
+                    'A'..='Z' => b'A',
+                    'a'..='z' => b'a',
+                    _ => return c,
+                };
+                ((map((c as u8 - base) as u32) % 26) as u8 + base) as char
+            })
+            .collect()
+    }
+}
+
+impl ClassicalCipher for Affine {
+    fn validate_key(key: &str) -> Result<(), CipherError> {
+        match Self::parse_key(key) {
+            Some((a, _)) if Self::inverse(a).is_some() => Ok(()),
+            _ => Err(CipherError::InvalidKey(INFO.key_description.to_string())),
+        }
+    }
+
+    fn new(key: &str) -> Result<Self, CipherError> {
+        <Self as ClassicalCipher>::validate_key(key)?;
+        let (a, b) = Self::parse_key(key).unwrap_or_default();
+        Ok(Affine { a, b, a_inverse: Self::inverse(a).unwrap_or_default() })
+    }
+
+    fn info(&self) -> CipherInfo {
+        INFO
+    }
+
+    fn encrypt(&self, plaintext: &str) -> Result<String, CipherError> {
+        Ok(Self::apply(plaintext, |x| self.a as u32 * x + self.b as u32))
+    }
+
+    fn decrypt(&self, ciphertext: &str) -> Result<String, CipherError> {
+        Ok(Self::apply(ciphertext, |y| self.a_inverse as u32 * (y + 26 - self.b as u32)))
+    }
+}
+
+const INFO: CipherInfo = CipherInfo {
+    name: "affine",
+    family: CipherFamily::Substitution,
+    key_description: "a,b with a coprime to 26 and both below 26",
+    block_size: None,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypts_the_textbook_example() {
+        let affine = Affine::new("5,8").unwrap();
+        assert_eq!(affine.encrypt("Affine cipher!").unwrap(), "Ihhwvc swfrcp!");
+        assert_eq!(affine.decrypt("Ihhwvc swfrcp!").unwrap(), "Affine cipher!");
+        assert!(Affine::new("13,8").is_err());
+        assert!(Affine::new("5").is_err());
+    }
+}
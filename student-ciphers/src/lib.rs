@@ -0,0 +1,30 @@
+//! Ciphers written by students, as a plugin for the cipher registry.
+//!
+//! To add one: write a module implementing `ClassicalCipher` (letters and
+//! a text key) or `SymmetricCipher` (bytes and a binary key), declare it
+//! below and list it in [`StudentCiphers::registrations`] with a valid
+//! example key. Without touching any other crate it then becomes
+//! `crypto <name> encrypt|decrypt`, a `crypto pipeline` stage and a
+//! `crypto analyze --cipher` option, and is benchmarked and put through the
+//! conformance suite. `crypto ciphers` lists everything registered.
+
+pub mod affine;
+
+use crypto_core::registry::{Capabilities, CipherPlugin, Registration};
+use crypto_core::CipherError;
+
+use affine::Affine;
+
+/// The ciphers in this crate
+pub struct StudentCiphers;
+
+impl CipherPlugin for StudentCiphers {
+    fn name(&self) -> &'static str {
+        "student-ciphers"
+    }
+
+    fn registrations(&self) -> Result<Vec<Registration>, CipherError> {
+        let exact = Capabilities { exact_round_trip: true, deterministic: true };
+        Ok(vec![Registration::classical::<Affine>("5,8", exact)?])
+    }
+}